@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use bevy_game::physics::{
+    collision::{calculate_collision_dt, find_floor_contacts, FloorContacts},
+    friction::apply_friction_impulse,
+    heightfield::Heightfield,
+    integrator::{integrate_after, integrate_before},
+    sleep::StaticHash,
+    Collider, PhysObj,
+};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const DT: f32 = 1.0 / 60.0;
+
+// Bodies with varied positions, velocities and accelerations, so nothing folds into constants
+fn bodies(count: usize) -> Vec<(Transform, PhysObj, Collider)> {
+    (0..count)
+        .map(|i| {
+            let t = i as f32;
+            (
+                Transform::from_xyz(
+                    (t * 37.0) % 2000.0 - 1000.0,
+                    (t * 53.0) % 800.0 - 400.0,
+                    0.0,
+                ),
+                PhysObj {
+                    mass: 1.0 + t % 5.0,
+                    vel: Vec2::new((t * 7.0) % 300.0 - 150.0, (t * 11.0) % 500.0 - 250.0),
+                    acc: Vec2::new(0.0, -2000.0),
+                    acc_prev: Vec2::new(0.0, -2000.0),
+                    moment_of_inertia: 50.0 + t % 20.0,
+                    angular_vel: (t * 3.0) % 20.0 - 10.0,
+                    angular_acc: 0.0,
+                    angular_acc_prev: 0.0,
+                    com_offset: Vec2::ZERO,
+                },
+                Collider::Ball {
+                    radius: 5.0 + t % 15.0,
+                    coef_of_restitution: 0.5,
+                    touching_ground: i % 4 == 0,
+                    kinetic_friction: 0.5,
+                    friction_acc: 0.0,
+                    friction_acc_prev: 0.0,
+                },
+            )
+        })
+        .collect()
+}
+
+fn integrator(c: &mut Criterion) {
+    let mut group = c.benchmark_group("integrator");
+    let bodies = bodies(10_000);
+    group.bench_function("integrate_before_10k", |b| {
+        b.iter_batched_ref(
+            || bodies.clone(),
+            |bodies| {
+                for (transform, phys_obj, _) in bodies {
+                    integrate_before(DT, transform, phys_obj);
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("integrate_after_10k", |b| {
+        b.iter_batched_ref(
+            || bodies.clone(),
+            |bodies| {
+                for (_, phys_obj, _) in bodies {
+                    integrate_after(DT, phys_obj);
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+// Falling, rising and resting bodies, with and without acceleration
+fn collision_dt(c: &mut Criterion) {
+    let inputs: Vec<(f32, f32, f32)> = (0..1000)
+        .map(|i| {
+            let t = i as f32;
+            (
+                -(t % 50.0) * 0.1,
+                (t % 40.0) * 25.0 - 500.0,
+                if i % 5 == 0 { 0.0 } else { -2000.0 },
+            )
+        })
+        .collect();
+    c.bench_function("calculate_collision_dt_1k", |b| {
+        b.iter(|| {
+            for &(s, v, a) in &inputs {
+                black_box(calculate_collision_dt(
+                    black_box(s),
+                    black_box(v),
+                    black_box(a),
+                ));
+            }
+        })
+    });
+}
+
+fn friction(c: &mut Criterion) {
+    let bodies = bodies(1000);
+    c.bench_function("apply_friction_impulse_1k", |b| {
+        b.iter_batched_ref(
+            || bodies.clone(),
+            |bodies| {
+                for (_, phys_obj, collider) in bodies {
+                    let Collider::Ball { radius, .. } = *collider;
+                    apply_friction_impulse(phys_obj, radius, 30.0, 0.5, 0.0);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn narrow_phase(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_floor_contacts");
+    for count in [1000, 10_000] {
+        let bodies: Vec<_> = bodies(count)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (transform, _, collider))| (Entity::from_raw(i as u32), transform, collider))
+            .collect();
+        let mut contacts = FloorContacts::default();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &bodies, |b, bodies| {
+            b.iter(|| {
+                find_floor_contacts(
+                    black_box(-300.0),
+                    bodies
+                        .iter()
+                        .map(|(entity, transform, collider)| (*entity, transform, collider)),
+                    &mut contacts,
+                );
+                black_box(&contacts);
+            })
+        });
+    }
+    group.finish();
+}
+
+// Balls against the segments of bumpy ground, some in it, some just over it and some well clear
+fn circle_vs_segment(c: &mut Criterion) {
+    let ground = Heightfield {
+        spacing: 25.0,
+        heights: (0..=1024)
+            .map(|i| 100.0 + 40.0 * (i as f32 * 0.3).sin())
+            .collect(),
+    };
+    let mut group = c.benchmark_group("heightfield_contact");
+    for count in [1000, 10_000] {
+        let balls: Vec<(Vec2, f32)> = bodies(count)
+            .into_iter()
+            .map(|(transform, _, collider)| {
+                let Collider::Ball { radius, .. } = collider;
+                let x = (transform.translation.x + 1000.0) / 2000.0 * ground.width();
+                let y = ground.height_at(x).unwrap() + transform.translation.y / 20.0;
+                (Vec2::new(x, y), radius)
+            })
+            .collect();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &balls, |b, balls| {
+            b.iter(|| {
+                for &(center, radius) in balls {
+                    black_box(ground.contact(black_box(center), radius));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+// Rebuilding the static hash from scratch, as when a platform moves, then looking up what's under
+// as many points
+fn static_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("static_hash_build_and_query");
+    for count in [1000, 10_000] {
+        let floors: Vec<(Entity, f32, f32)> = (0..count)
+            .map(|i| {
+                let t = i as f32;
+                (
+                    Entity::from_raw(i as u32),
+                    t * 40.0,
+                    50.0 + (t * 13.0) % 400.0,
+                )
+            })
+            .collect();
+        let points: Vec<f32> = (0..count)
+            .map(|i| (i as f32 * 97.0) % (count as f32 * 40.0))
+            .collect();
+        let mut hash = StaticHash::default();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &floors, |b, floors| {
+            b.iter(|| {
+                hash.rebuild(floors.iter().copied());
+                for &x in &points {
+                    black_box(hash.near(black_box(x)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    integrator,
+    collision_dt,
+    friction,
+    narrow_phase,
+    circle_vs_segment,
+    static_hash
+);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+// The physics stress scene without the rest of the game (menus, sound, tooling):
+//   cargo run --release --example stress -- --stress 1000
+use bevy::prelude::*;
+use bevy_game::{launch::LaunchOptions, prelude::*, stress::DEFAULT_STRESS_BALLS};
+
+fn main() {
+    let count = LaunchOptions::from_env()
+        .stress
+        .unwrap_or(DEFAULT_STRESS_BALLS);
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AppStatePlugin)
+        .add_plugin(PhysicsPlugin::default())
+        .add_plugin(LevelPlugin)
+        .add_plugin(WindowBoundsPlugin)
+        .add_plugin(PlayerPlugin)
+        .add_plugin(ShapesPlugin)
+        .add_plugin(VisualsPlugin)
+        .add_plugin(StressPlugin { count })
+        .run();
+}
@@ -0,0 +1,170 @@
+// Sound effects and music, behind the audio feature. Everything is silent while the window isn't
+// focused (e.g. while the game's tab is hidden), and the mute key (M by default) mutes the game.
+use std::sync::Arc;
+
+use bevy::{asset::LoadState, prelude::*, utils::HashMap, window::WindowFocused};
+
+use crate::settings::{InputAction, Settings};
+
+pub mod music;
+pub mod rolling;
+pub mod sfx;
+
+// Loads the sounds and adds the plugins that play them. Needs an AssetServer and Bevy's audio.
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<Sounds>()
+            .init_resource::<AudioFocus>()
+            .add_systems((
+                sound_fallback_system,
+                audio_focus_system,
+                mute_toggle_system,
+            ))
+            .add_plugin(sfx::SfxPlugin)
+            .add_plugin(rolling::RollingSoundPlugin)
+            .add_plugin(music::MusicPlugin);
+    }
+}
+
+// Every sound the game plays. They're loaded from `assets/sounds`, and a copy is built into the
+// binary for when the file is missing (e.g. a web build deployed without the assets).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Thud,
+    Boing,
+    Jump,
+    Overheat,
+    // Loops
+    Rolling,
+    Skid,
+    Music,
+}
+
+impl Sound {
+    pub const ALL: [Sound; 7] = [
+        Sound::Thud,
+        Sound::Boing,
+        Sound::Jump,
+        Sound::Overheat,
+        Sound::Rolling,
+        Sound::Skid,
+        Sound::Music,
+    ];
+
+    pub fn path(self) -> &'static str {
+        match self {
+            Sound::Thud => "sounds/thud.wav",
+            Sound::Boing => "sounds/boing.wav",
+            Sound::Jump => "sounds/jump.wav",
+            Sound::Overheat => "sounds/overheat.wav",
+            Sound::Rolling => "sounds/rolling.wav",
+            Sound::Skid => "sounds/skid.wav",
+            Sound::Music => "sounds/music.wav",
+        }
+    }
+
+    // The sound a level calls `name`, which is its file's name without the extension
+    pub fn named(name: &str) -> Option<Sound> {
+        Sound::ALL
+            .into_iter()
+            .find(|sound| sound.path() == format!("sounds/{name}.wav"))
+    }
+
+    fn embedded(self) -> &'static [u8] {
+        match self {
+            Sound::Thud => include_bytes!("../../assets/sounds/thud.wav"),
+            Sound::Boing => include_bytes!("../../assets/sounds/boing.wav"),
+            Sound::Jump => include_bytes!("../../assets/sounds/jump.wav"),
+            Sound::Overheat => include_bytes!("../../assets/sounds/overheat.wav"),
+            Sound::Rolling => include_bytes!("../../assets/sounds/rolling.wav"),
+            Sound::Skid => include_bytes!("../../assets/sounds/skid.wav"),
+            Sound::Music => include_bytes!("../../assets/sounds/music.wav"),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Sounds(HashMap<Sound, Handle<AudioSource>>);
+
+impl Sounds {
+    pub fn get(&self, sound: Sound) -> Handle<AudioSource> {
+        self.0[&sound].clone()
+    }
+}
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self(
+            Sound::ALL
+                .into_iter()
+                .map(|sound| (sound, asset_server.load(sound.path())))
+                .collect(),
+        )
+    }
+}
+
+// Sounds whose file failed to load are replaced by the built-in copy
+fn sound_fallback_system(
+    asset_server: Res<AssetServer>,
+    mut sources: ResMut<Assets<AudioSource>>,
+    mut sounds: ResMut<Sounds>,
+) {
+    for (sound, handle) in &mut sounds.0 {
+        if asset_server.get_load_state(&*handle) == LoadState::Failed {
+            warn!(
+                "Using the built-in {sound:?} sound, {} failed to load",
+                sound.path()
+            );
+            *handle = sources.add(AudioSource {
+                bytes: Arc::from(sound.embedded()),
+            });
+        }
+    }
+}
+
+// Whether the game can be heard at all
+#[derive(Resource)]
+pub struct AudioFocus {
+    pub focused: bool,
+}
+
+impl Default for AudioFocus {
+    fn default() -> Self {
+        Self { focused: true }
+    }
+}
+
+fn audio_focus_system(mut focus_events: EventReader<WindowFocused>, mut focus: ResMut<AudioFocus>) {
+    if let Some(event) = focus_events.iter().last() {
+        focus.focused = event.focused;
+    }
+}
+
+fn mute_toggle_system(input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if settings.input.just_pressed(&input, InputAction::Mute) {
+        settings.muted = !settings.muted;
+    }
+}
+
+// Which of the Settings' volumes applies to a sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VolumeCategory {
+    Sfx,
+    Music,
+}
+
+// How loud sounds in `category` play at their own full volume
+pub fn category_volume(settings: &Settings, focus: &AudioFocus, category: VolumeCategory) -> f32 {
+    if settings.muted || !focus.focused {
+        return 0.0;
+    }
+    let volume = match category {
+        VolumeCategory::Sfx => settings.sfx_volume,
+        VolumeCategory::Music => settings.music_volume,
+    };
+    settings.master_volume * volume
+}
@@ -0,0 +1,77 @@
+use bevy::{audio::AudioSinkPlayback, prelude::*};
+
+use super::{category_volume, AudioFocus, Sound, Sounds, VolumeCategory};
+use crate::{settings::Settings, state::AppState};
+
+// Share of the volume the music keeps while the game is paused or the level is over
+const DUCKED_VOLUME: f32 = 0.3;
+// Seconds it takes the music to fade between silent and full volume
+const MUSIC_FADE_TIME: f32 = 1.0;
+
+// Background music, looping from startup. It's quieter on the pause and level complete screens,
+// and silent until the game has loaded.
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Music>()
+            .add_systems((music_start_system, music_volume_system).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Music {
+    // None until the track has loaded
+    sink: Option<Handle<AudioSink>>,
+    // Share of the music volume it's playing at, moving towards duck_target over time
+    pub level: f32,
+}
+
+// Share of the music volume to play at in `state`
+pub fn duck_target(state: AppState) -> f32 {
+    match state {
+        AppState::Loading | AppState::AssetError => 0.0,
+        AppState::MainMenu | AppState::SettingsMenu | AppState::Playing => 1.0,
+        AppState::Paused | AppState::LevelComplete => DUCKED_VOLUME,
+    }
+}
+
+// Moves `level` linearly towards `target`, taking MUSIC_FADE_TIME for the full range. It always
+// continues from where it is, so quickly switching back and forth between states doesn't jump.
+pub fn fade_towards(level: f32, target: f32, dt: f32) -> f32 {
+    let max_change = dt / MUSIC_FADE_TIME;
+    level + (target - level).clamp(-max_change, max_change)
+}
+
+fn music_start_system(
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    sources: Res<Assets<AudioSource>>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<Music>,
+) {
+    if music.sink.is_some() {
+        return;
+    }
+    let track = sounds.get(Sound::Music);
+    if sources.get(&track).is_none() {
+        return;
+    }
+    let sink = audio.play_with_settings(track, PlaybackSettings::LOOP.with_volume(0.0));
+    music.sink = Some(sinks.get_handle(sink));
+}
+
+fn music_volume_system(
+    time: Res<Time>,
+    state: Res<State<AppState>>,
+    settings: Res<Settings>,
+    focus: Res<AudioFocus>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<Music>,
+) {
+    music.level = fade_towards(music.level, duck_target(state.0), time.delta_seconds());
+    let Some(sink) = music.sink.as_ref().and_then(|sink| sinks.get(sink)) else {
+        return;
+    };
+    sink.set_volume(category_volume(&settings, &focus, VolumeCategory::Music) * music.level);
+}
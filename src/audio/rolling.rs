@@ -0,0 +1,175 @@
+use bevy::{audio::AudioSinkPlayback, prelude::*, utils::HashMap};
+
+use super::{category_volume, AudioFocus, Sound, Sounds, VolumeCategory};
+use crate::{
+    physics::{Collider, PhysObj, PhysicsStep},
+    player::Player,
+    settings::Settings,
+};
+
+// Slower than this, the ball is silent
+const ROLL_MIN_SPEED: f32 = 30.0;
+// At this speed and above, the rolling sound is at its loudest and highest
+const ROLL_FULL_SPEED: f32 = 1200.0;
+const ROLL_MIN_VOLUME: f32 = 0.2;
+const ROLL_MIN_PITCH: f32 = 0.7;
+const ROLL_MAX_PITCH: f32 = 1.4;
+// The contact point sliding faster than this over the ground skids instead of rolling
+const SKID_SLIP_SPEED: f32 = 200.0;
+// Seconds it takes a loop to fade all the way in or out
+const FADE_TIME: f32 = 0.2;
+// The sinks are only adjusted every this many frames
+const SINK_UPDATE_FRAMES: u32 = 2;
+
+// The player's ball rumbles while it rolls along the ground, and hisses while it skids
+pub struct RollingSoundPlugin;
+
+impl Plugin for RollingSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((rolling_sound_setup_system, rolling_sound_system).after(PhysicsStep));
+    }
+}
+
+// The player's rolling loops. They keep playing (silently, or paused) for as long as the player
+// exists, and only their settings change.
+#[derive(Component, Default)]
+pub struct RollingSound {
+    pub rolling: LoopFade,
+    pub skid: LoopFade,
+    // Kept from the last frame the ball was rolling, for fading out
+    pub volume: f32,
+    pub pitch: f32,
+    rolling_sink: Handle<AudioSink>,
+    skid_sink: Handle<AudioSink>,
+}
+
+// How far a loop has faded in. Starting and stopping a sound at full volume clicks, so loops are
+// faded over FADE_TIME instead.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct LoopFade {
+    pub level: f32,
+}
+
+impl LoopFade {
+    // Moves the fade towards fully in while `on`, and towards silent otherwise
+    pub fn update(&mut self, on: bool, dt: f32) -> f32 {
+        let step = dt / FADE_TIME;
+        self.level = if on {
+            (self.level + step).min(1.0)
+        } else {
+            (self.level - step).max(0.0)
+        };
+        self.level
+    }
+}
+
+// Volume and playback speed of the rolling loop at a ground speed of `speed`, or None if it's too
+// slow to be heard
+pub fn rolling_sound(speed: f32) -> Option<(f32, f32)> {
+    if speed.is_nan() || speed < ROLL_MIN_SPEED {
+        return None;
+    }
+    let t = ((speed - ROLL_MIN_SPEED) / (ROLL_FULL_SPEED - ROLL_MIN_SPEED)).min(1.0);
+    Some((
+        ROLL_MIN_VOLUME + (1.0 - ROLL_MIN_VOLUME) * t,
+        ROLL_MIN_PITCH + (ROLL_MAX_PITCH - ROLL_MIN_PITCH) * t,
+    ))
+}
+
+// Starts the loops once their sounds are ready
+fn rolling_sound_setup_system(
+    mut commands: Commands,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    sources: Res<Assets<AudioSource>>,
+    sinks: Res<Assets<AudioSink>>,
+    query: Query<Entity, (With<Player>, Without<RollingSound>)>,
+) {
+    let (rolling, skid) = (sounds.get(Sound::Rolling), sounds.get(Sound::Skid));
+    if sources.get(&rolling).is_none() || sources.get(&skid).is_none() {
+        return;
+    }
+    let play = |source| {
+        sinks.get_handle(audio.play_with_settings(source, PlaybackSettings::LOOP.with_volume(0.0)))
+    };
+
+    for entity in &query {
+        commands.entity(entity).insert(RollingSound {
+            rolling_sink: play(rolling.clone()),
+            skid_sink: play(skid.clone()),
+            ..default()
+        });
+    }
+}
+
+fn rolling_sound_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    focus: Res<AudioFocus>,
+    sinks: Res<Assets<AudioSink>>,
+    mut query: Query<(Entity, &mut RollingSound, &PhysObj, &Collider)>,
+    mut frame: Local<u32>,
+    // Every sink that was started, so that those of despawned players can be stopped
+    mut started: Local<HashMap<Entity, [Handle<AudioSink>; 2]>>,
+) {
+    let dt = time.delta_seconds();
+    *frame = frame.wrapping_add(1);
+    let update_sinks = frame.is_multiple_of(SINK_UPDATE_FRAMES);
+    let volume = category_volume(&settings, &focus, VolumeCategory::Sfx);
+
+    for (
+        entity,
+        mut sound,
+        phys_obj,
+        &Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        },
+    ) in &mut query
+    {
+        let speed = phys_obj.vel.x.abs();
+        // The same relative speed friction works against
+        let slip = (phys_obj.vel.x + phys_obj.angular_vel * radius).abs();
+        let skidding = slip > SKID_SLIP_SPEED;
+        let rolling = touching_ground.then(|| rolling_sound(speed)).flatten();
+        if let Some((volume, pitch)) = rolling {
+            (sound.volume, sound.pitch) = (volume, pitch);
+        }
+        sound.rolling.update(rolling.is_some() && !skidding, dt);
+        sound.skid.update(rolling.is_some() && skidding, dt);
+
+        started
+            .entry(entity)
+            .or_insert_with(|| [sound.rolling_sink.clone(), sound.skid_sink.clone()]);
+        if !update_sinks {
+            continue;
+        }
+        for (fade, sink) in [
+            (sound.rolling, &sound.rolling_sink),
+            (sound.skid, &sound.skid_sink),
+        ] {
+            let Some(sink) = sinks.get(sink) else {
+                continue;
+            };
+            if fade.level == 0.0 {
+                sink.pause();
+                continue;
+            }
+            sink.set_volume(volume * sound.volume * fade.level);
+            sink.set_speed(sound.pitch);
+            sink.play();
+        }
+    }
+
+    // Their sinks would otherwise keep looping, as sinks play on after their handles are dropped
+    started.retain(|&entity, handles| {
+        if query.contains(entity) {
+            return true;
+        }
+        for sink in handles.iter().filter_map(|handle| sinks.get(handle)) {
+            sink.stop();
+        }
+        false
+    });
+}
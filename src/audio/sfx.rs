@@ -0,0 +1,216 @@
+use std::marker::PhantomData;
+
+use bevy::{ecs::event::Event, prelude::*, utils::HashMap};
+
+use super::{category_volume, AudioFocus, Sound, Sounds, VolumeCategory};
+use crate::{
+    level::{surface_below, Floor, SurfaceMaterial},
+    physics::{BounceEvent, PhysicsStep},
+    player::{JumpEvent, OverheatEvent, SlamEvent},
+    rng::GameRng,
+    settings::Settings,
+    trigger::PlaySoundEvent,
+};
+
+// Impacts slower than this are silent, so a ball resting on the floor doesn't keep thudding
+const IMPACT_MIN_SPEED: f32 = 150.0;
+// Impacts this fast or faster play at full volume
+const IMPACT_FULL_SPEED: f32 = 1500.0;
+const IMPACT_MIN_VOLUME: f32 = 0.15;
+// How far the playback speed of an impact strays from normal, either way, drawn from the GameRng
+const IMPACT_SPEED_VARIATION: f32 = 0.08;
+// Each extra event merged into one sound makes it this much louder, up to MERGED_MAX_BOOST
+const MERGED_BOOST: f32 = 0.15;
+const MERGED_MAX_BOOST: f32 = 1.5;
+
+// One-shot sounds for what happens in the game
+pub struct SfxPlugin;
+
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .add_event::<PlaySoundEvent>()
+            .add_system(impact_sound_system.after(PhysicsStep))
+            .add_system(level_sound_system.after(PhysicsStep))
+            // Sound, volume category and cooldown of each event that makes a sound
+            .add_event_sound::<JumpEvent>(Sound::Jump, VolumeCategory::Sfx, 0.1)
+            .add_event_sound::<OverheatEvent>(Sound::Overheat, VolumeCategory::Sfx, 0.5)
+            // On top of the landing's own impact
+            .add_event_sound::<SlamEvent>(Sound::Thud, VolumeCategory::Sfx, 0.1);
+    }
+}
+
+pub trait SfxAppExt {
+    // Plays `sound` for events of type E. Events coming in faster than `cooldown` seconds apart
+    // are merged into a single, slightly louder sound instead of stacking up.
+    fn add_event_sound<E: Event>(
+        &mut self,
+        sound: Sound,
+        category: VolumeCategory,
+        cooldown: f32,
+    ) -> &mut Self;
+}
+
+impl SfxAppExt for App {
+    fn add_event_sound<E: Event>(
+        &mut self,
+        sound: Sound,
+        category: VolumeCategory,
+        cooldown: f32,
+    ) -> &mut Self {
+        self.add_event::<E>()
+            .insert_resource(EventSound::<E> {
+                sound,
+                category,
+                cooldown: SoundCooldown::new(cooldown),
+                _event: PhantomData,
+            })
+            .add_system(event_sound_system::<E>.after(PhysicsStep))
+    }
+}
+
+#[derive(Resource)]
+struct EventSound<E> {
+    sound: Sound,
+    category: VolumeCategory,
+    cooldown: SoundCooldown,
+    _event: PhantomData<fn(E)>,
+}
+
+// When an event sound plays, and how many events it stands for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundCooldown {
+    pub cooldown: f32,
+    // Seconds until the sound can play again
+    pub remaining: f32,
+    // Events that came in during the cooldown, played with the next sound
+    pub merged: usize,
+}
+
+impl SoundCooldown {
+    pub fn new(cooldown: f32) -> Self {
+        Self {
+            cooldown,
+            remaining: 0.0,
+            merged: 0,
+        }
+    }
+
+    // Adds the `events` of a frame `dt` long. Returns how many events the sound stands for if it
+    // plays now.
+    pub fn update(&mut self, dt: f32, events: usize) -> Option<usize> {
+        self.remaining = (self.remaining - dt).max(0.0);
+        self.merged += events;
+        if self.merged == 0 || self.remaining > 0.0 {
+            return None;
+        }
+        self.remaining = self.cooldown;
+        Some(std::mem::take(&mut self.merged))
+    }
+}
+
+// Volume of a sound standing in for `count` events
+pub fn merged_volume(count: usize) -> f32 {
+    (1.0 + MERGED_BOOST * count.saturating_sub(1) as f32).min(MERGED_MAX_BOOST)
+}
+
+fn event_sound_system<E: Event>(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    focus: Res<AudioFocus>,
+    mut events: EventReader<E>,
+    mut entry: ResMut<EventSound<E>>,
+) {
+    let Some(count) = entry
+        .cooldown
+        .update(time.delta_seconds(), events.iter().count())
+    else {
+        return;
+    };
+
+    let volume = category_volume(&settings, &focus, entry.category) * merged_volume(count);
+    if volume > 0.0 {
+        audio.play_with_settings(
+            sounds.get(entry.sound),
+            PlaybackSettings::ONCE.with_volume(volume),
+        );
+    }
+}
+
+// Sounds the level's triggers play
+fn level_sound_system(
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    focus: Res<AudioFocus>,
+    mut events: EventReader<PlaySoundEvent>,
+) {
+    for PlaySoundEvent(name) in events.iter() {
+        let Some(sound) = Sound::named(name) else {
+            warn!("The level played a sound that doesn't exist: {name:?}");
+            continue;
+        };
+        let volume = category_volume(&settings, &focus, VolumeCategory::Sfx);
+        if volume > 0.0 {
+            audio.play_with_settings(
+                sounds.get(sound),
+                PlaybackSettings::ONCE.with_volume(volume),
+            );
+        }
+    }
+}
+
+// Volume of an impact at `impact_speed`, or None if it's too soft to be heard
+pub fn impact_volume(impact_speed: f32) -> Option<f32> {
+    if impact_speed.is_nan() || impact_speed < IMPACT_MIN_SPEED {
+        return None;
+    }
+    let t = ((impact_speed - IMPACT_MIN_SPEED) / (IMPACT_FULL_SPEED - IMPACT_MIN_SPEED)).min(1.0);
+    Some(IMPACT_MIN_VOLUME + (1.0 - IMPACT_MIN_VOLUME) * t)
+}
+
+// Bodies thud when they bounce, or boing on a trampoline
+#[allow(clippy::too_many_arguments)]
+fn impact_sound_system(
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    focus: Res<AudioFocus>,
+    mut bounces: EventReader<BounceEvent>,
+    bodies: Query<&Transform>,
+    floors: Query<(&Transform, &Floor, Option<&SurfaceMaterial>)>,
+    mut rng: ResMut<GameRng>,
+) {
+    // A body can bounce more than once a frame; only its hardest impact is played
+    let mut impacts = HashMap::<Entity, f32>::default();
+    for bounce in bounces.iter() {
+        let speed = impacts.entry(bounce.entity).or_default();
+        *speed = speed.max(bounce.impact_speed);
+    }
+
+    let volume = category_volume(&settings, &focus, VolumeCategory::Sfx);
+    if volume == 0.0 {
+        return;
+    }
+    for (entity, impact_speed) in impacts {
+        let Some(impact_volume) = impact_volume(impact_speed) else {
+            continue;
+        };
+        let surface = bodies
+            .get(entity)
+            .ok()
+            .and_then(|transform| surface_below(transform.translation.truncate(), &floors));
+        let sound = match surface {
+            Some(SurfaceMaterial::Trampoline { .. }) => Sound::Boing,
+            _ => Sound::Thud,
+        };
+        audio.play_with_settings(
+            sounds.get(sound),
+            PlaybackSettings::ONCE
+                .with_volume(volume * impact_volume)
+                .with_speed(1.0 + IMPACT_SPEED_VARIATION * rng.stream("impact_pitch").signed()),
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::{
+    level::{CurrentLevel, RestartLevelEvent},
+    physics::{simulation_running, PhysicsConfig, PhysicsStep, PhysicsTime},
+    player::PlayerInput,
+    progress::{level_complete_system, level_timer_system, LevelStats, LevelTimer},
+    replay::{GhostPlayback, Replay, ReplayFrame},
+    save::SaveGame,
+    state::AppState,
+    storage::{load_ron, save_ron, Storage, StorageBackend, StorageError},
+};
+
+// Most replay frames the ghost plays in one frame to catch up with the level timer, e.g. after a
+// hitch
+const MAX_GHOST_FRAMES: usize = 4;
+
+// Records every attempt at a level and keeps the one with the best time, then races the player
+// against a ghost of it on later attempts. The ghost is the best run simulated again in its own
+// App, like the F7 ghost, so nothing in the level can touch it or be touched by it. Needs the
+// ProgressPlugin, which decides what's a best time.
+pub struct BestRunPlugin;
+
+impl Plugin for BestRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Storage>()
+            .init_resource::<CurrentLevel>()
+            .init_resource::<BestRunRecorder>()
+            .add_event::<RestartLevelEvent>()
+            .add_system(
+                best_run_start_system
+                    .before(PhysicsStep)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_system(
+                best_run_reset_system
+                    .after(best_run_start_system)
+                    .run_if(on_event::<RestartLevelEvent>()),
+            )
+            .add_system(best_run_reset_system.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_system(
+                best_run_record_system
+                    .after(PhysicsStep)
+                    .after(level_timer_system)
+                    .run_if(simulation_running),
+            )
+            .add_system(
+                best_run_ghost_system
+                    .after(PhysicsStep)
+                    .after(level_timer_system)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_system(
+                best_run_save_system
+                    .after(level_complete_system)
+                    .in_schedule(OnEnter(AppState::LevelComplete)),
+            );
+    }
+}
+
+// The attempt at the current level being played
+#[derive(Resource)]
+pub struct BestRunRecorder {
+    pub attempt: Option<Replay>,
+    // A new attempt starts on the next frame played, once the level's been (re)spawned
+    starting: bool,
+}
+
+impl Default for BestRunRecorder {
+    fn default() -> Self {
+        Self {
+            attempt: None,
+            starting: true,
+        }
+    }
+}
+
+// The ghost of the best run, racing the player
+struct BestRunGhost(GhostPlayback);
+
+fn best_run_name(level: usize) -> String {
+    format!("best_run_{level}")
+}
+
+pub fn save_best_run(
+    storage: &mut dyn StorageBackend,
+    level: usize,
+    replay: &Replay,
+) -> Result<(), StorageError> {
+    save_ron(storage, &best_run_name(level), replay)
+}
+
+pub fn load_best_run(storage: &dyn StorageBackend, level: usize) -> Option<Replay> {
+    load_ron(storage, &best_run_name(level))?
+        .map_err(|error| warn!("Discarding the best run of level {level}: {error}"))
+        .ok()
+}
+
+// Starts recording the attempt from where everything is now, and the best run's ghost from the
+// start
+fn best_run_start_system(world: &mut World) {
+    if !world.resource::<BestRunRecorder>().starting {
+        return;
+    }
+    let attempt = Replay {
+        config: world.resource::<PhysicsConfig>().clone(),
+        initial: SaveGame::capture(world),
+        frames: Vec::new(),
+    };
+    let mut recorder = world.resource_mut::<BestRunRecorder>();
+    recorder.starting = false;
+    recorder.attempt = Some(attempt);
+
+    // Still there if the last attempt finished the level
+    if let Some(BestRunGhost(ghost)) = world.remove_non_send_resource::<BestRunGhost>() {
+        ghost.despawn(world);
+    }
+    let level = world.resource::<CurrentLevel>().0;
+    if let Some(best) = load_best_run(world.resource::<Storage>().0.as_ref(), level) {
+        let ghost = GhostPlayback::spawn(world, &best);
+        world.insert_non_send_resource(BestRunGhost(ghost));
+    }
+}
+
+// Restarting the level (or going back to the menu) abandons the attempt and its ghost
+fn best_run_reset_system(world: &mut World) {
+    let mut recorder = world.resource_mut::<BestRunRecorder>();
+    recorder.attempt = None;
+    recorder.starting = true;
+    if let Some(BestRunGhost(ghost)) = world.remove_non_send_resource::<BestRunGhost>() {
+        ghost.despawn(world);
+    }
+}
+
+fn best_run_record_system(
+    input: Res<PlayerInput>,
+    time: Res<PhysicsTime>,
+    timer: Res<LevelTimer>,
+    mut recorder: ResMut<BestRunRecorder>,
+) {
+    if let Some(attempt) = &mut recorder.attempt {
+        attempt.frames.push(ReplayFrame {
+            input: *input,
+            delta: time.delta,
+            elapsed: timer.elapsed,
+        });
+    }
+}
+
+// Plays the best run up to the level timer, so the ghost is where the player was at the same
+// time in that run. Only while Playing, so the ghost waits while the game's paused.
+fn best_run_ghost_system(world: &mut World) {
+    let Some(BestRunGhost(mut ghost)) = world.remove_non_send_resource::<BestRunGhost>() else {
+        return;
+    };
+    if ghost.next_frame().is_none() {
+        // Shown over the line for a frame, and gone
+        ghost.despawn(world);
+        return;
+    }
+    let elapsed = world.resource::<LevelTimer>().elapsed;
+    for _ in 0..MAX_GHOST_FRAMES {
+        match ghost.next_frame() {
+            Some(frame) if frame.elapsed <= elapsed => ghost.update(world),
+            _ => break,
+        }
+    }
+    world.insert_non_send_resource(BestRunGhost(ghost));
+}
+
+pub(crate) fn best_run_save_system(
+    level: Res<CurrentLevel>,
+    stats: Res<LevelStats>,
+    mut recorder: ResMut<BestRunRecorder>,
+    mut storage: ResMut<Storage>,
+) {
+    let Some(attempt) = recorder.attempt.take() else {
+        return;
+    };
+    recorder.starting = true;
+    if !stats.new_best {
+        return;
+    }
+    info!(
+        "Saving the best run of level {} ({} frames)",
+        level.0,
+        attempt.frames.len()
+    );
+    if let Err(error) = save_best_run(storage.0.as_mut(), level.0, &attempt) {
+        error!("Failed to save the best run: {error}");
+    }
+}
@@ -0,0 +1,490 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use crate::{
+    mesh_cache::MeshCache,
+    physics::{
+        joints::DistanceJoint, sleep::KeepAwake, Collider, Gravity, PhysObj, PhysicsSchedule,
+        PhysicsSet, PhysicsStep, PhysicsTime,
+    },
+    player::{JumpEvent, Player, PlayerInput},
+    shapes::{set_rim_positions, AnimatedSpinner, FidgetSpinner},
+    state::AppState,
+};
+
+pub const BLOB_KEY: KeyCode = KeyCode::G;
+pub const BLOB_PARTICLES: usize = 16;
+const PARTICLE_RADIUS: f32 = 3.0;
+// A blob lands with a splat rather than bouncing like the ball
+const PARTICLE_RESTITUTION: f32 = 0.2;
+// The center particle weighs as much as this many ring particles
+const CENTER_MASS: f32 = 4.0;
+// How much of a spoke's stretch, and of the area the ring is missing, is taken back each step; the
+// rest is what lets the blob squish. Done on the positions like the joints, as springs and a
+// pressure force this stiff blow up at 60 steps a second.
+const SPOKE_STIFFNESS: f32 = 0.3;
+const PRESSURE: f32 = 0.5;
+
+// Experimental: turns the player into a soft blob and back with G. The blob is a ring of small
+// balls around a center one (the player's entity), which all collide on their own. The ring's
+// edges are rigid joints, while the spokes to the center and a pressure keeping the ring's area
+// give a little each step.
+pub struct BlobPlugin;
+
+impl Plugin for BlobPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .add_event::<JumpEvent>()
+            .add_system(
+                blob_toggle_system
+                    .before(PhysicsStep)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_system(blob_mesh_system.after(PhysicsStep))
+            .add_systems(
+                (
+                    blob_impulse_system.in_set(PhysicsSet::ApplyImpulses),
+                    blob_spin_system.in_set(PhysicsSet::ApplyForces),
+                    blob_shape_system
+                        .after(PhysicsSet::ResolveCollisions)
+                        .before(PhysicsSet::SolveConstraints),
+                )
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+// On the center particle of a blob
+#[derive(Component)]
+pub struct Blob {
+    // Counter-clockwise
+    pub ring: Vec<Entity>,
+    // Joints between neighbours on the ring
+    pub edges: Vec<Entity>,
+    pub ring_mass: f32,
+    pub spoke_length: f32,
+    pub rest_area: f32,
+    // What the blob was as a ball, to turn back into
+    pub ball_mass: f32,
+    pub ball_moment_of_inertia: f32,
+    pub ball_collider: Collider,
+}
+
+#[derive(Component)]
+pub struct BlobParticle {
+    pub blob: Entity,
+}
+
+impl Blob {
+    pub fn mass(&self) -> f32 {
+        self.ring_mass * (self.ring.len() as f32 + CENTER_MASS)
+    }
+
+    // Area inside the ring, with `position` looking up where a particle is
+    pub fn area(&self, position: impl Fn(Entity) -> Option<Vec2>) -> f32 {
+        let next = self.ring.iter().cycle().skip(1);
+        0.5 * self
+            .ring
+            .iter()
+            .zip(next)
+            .filter_map(|(&a, &b)| Some(position(a)?.perp_dot(position(b)?)))
+            .sum::<f32>()
+    }
+}
+
+fn particle(
+    mass: f32,
+    vel: Vec2,
+    coef_of_restitution: f32,
+    kinetic_friction: f32,
+) -> (PhysObj, Collider) {
+    (
+        PhysObj {
+            mass,
+            vel,
+            acc: Vec2::ZERO,
+            acc_prev: Vec2::ZERO,
+            moment_of_inertia: mass * 0.5 * PARTICLE_RADIUS.powi(2),
+            angular_vel: 0.0,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: Vec2::ZERO,
+        },
+        Collider::Ball {
+            radius: PARTICLE_RADIUS,
+            coef_of_restitution,
+            touching_ground: false,
+            kinetic_friction,
+            friction_acc: 0.0,
+            friction_acc_prev: 0.0,
+        },
+    )
+}
+
+// Turns a ball into a blob of the same size and mass. Every particle starts with the velocity its
+// point on the ball had, so the blob keeps the ball's momentum and spin.
+pub fn ball_to_blob(world: &mut World, entity: Entity) {
+    let Some(center) = world.get_entity(entity) else {
+        return;
+    };
+    let (Some(&transform), Some(ball), Some(&ball_collider)) = (
+        center.get::<Transform>(),
+        center.get::<PhysObj>().cloned(),
+        center.get::<Collider>(),
+    ) else {
+        return;
+    };
+    if center.contains::<Blob>() {
+        return;
+    }
+    let gravity = center.get::<Gravity>().cloned();
+    let Collider::Ball {
+        radius,
+        kinetic_friction,
+        ..
+    } = ball_collider;
+
+    let ring_mass = ball.mass / (BLOB_PARTICLES as f32 + CENTER_MASS);
+    let spoke_length = radius - PARTICLE_RADIUS;
+    let ring: Vec<Entity> = (0..BLOB_PARTICLES)
+        .map(|i| {
+            let offset = spoke_length
+                * Vec2::from_angle(std::f32::consts::TAU * i as f32 / BLOB_PARTICLES as f32);
+            let vel = ball.vel + ball.angular_vel * offset.perp();
+            let mut spawned = world.spawn((
+                SpatialBundle::from_transform(Transform::from_translation(
+                    transform.translation + offset.extend(0.0),
+                )),
+                particle(ring_mass, vel, PARTICLE_RESTITUTION, kinetic_friction),
+                BlobParticle { blob: entity },
+                KeepAwake,
+            ));
+            if let Some(gravity) = &gravity {
+                spawned.insert(gravity.clone());
+            }
+            spawned.id()
+        })
+        .collect();
+
+    let edge_length = 2.0 * spoke_length * f32::sin(std::f32::consts::PI / BLOB_PARTICLES as f32);
+    let edges = (0..BLOB_PARTICLES)
+        .map(|i| {
+            world
+                .spawn(DistanceJoint {
+                    a: ring[i],
+                    b: ring[(i + 1) % BLOB_PARTICLES],
+                    length: edge_length,
+                    rigid: true,
+                })
+                .id()
+        })
+        .collect();
+
+    let mut blob = Blob {
+        ring,
+        edges,
+        ring_mass,
+        spoke_length,
+        rest_area: 0.0,
+        ball_mass: ball.mass,
+        ball_moment_of_inertia: ball.moment_of_inertia,
+        ball_collider,
+    };
+    blob.rest_area = blob.area(|entity| {
+        world
+            .get::<Transform>(entity)
+            .map(|transform| transform.translation.truncate())
+    });
+
+    let (center_obj, center_collider) = particle(
+        CENTER_MASS * ring_mass,
+        ball.vel,
+        PARTICLE_RESTITUTION,
+        kinetic_friction,
+    );
+    world
+        .entity_mut(entity)
+        .insert((center_obj, center_collider, KeepAwake, blob))
+        .remove::<AnimatedSpinner>();
+
+    // Its own mesh, as blob_mesh_system rewrites it every frame
+    if world.get::<Mesh2dHandle>(entity).is_none() {
+        return;
+    }
+    let mesh = world.get_resource_mut::<Assets<Mesh>>().map(|mut meshes| {
+        meshes.add(
+            FidgetSpinner {
+                radius,
+                bump_size: 0.0,
+                bumps: 0,
+                vertices: BLOB_PARTICLES,
+                ..default()
+            }
+            .into(),
+        )
+    });
+    if let Some(mesh) = mesh {
+        world.entity_mut(entity).insert(Mesh2dHandle(mesh));
+    }
+}
+
+// Turns a blob back into the ball it was made from, at the blob's center of mass and with its
+// momentum and angular momentum
+pub fn blob_to_ball(world: &mut World, entity: Entity) {
+    let Some(blob) = world
+        .get_entity_mut(entity)
+        .and_then(|mut center| center.take::<Blob>())
+    else {
+        return;
+    };
+
+    let particles: Vec<(Vec2, PhysObj)> = std::iter::once(entity)
+        .chain(blob.ring.iter().copied())
+        .filter_map(|entity| {
+            let transform = world.get::<Transform>(entity)?;
+            let phys_obj = world.get::<PhysObj>(entity)?;
+            Some((transform.translation.truncate(), phys_obj.clone()))
+        })
+        .collect();
+    let mass: f32 = particles.iter().map(|(_, phys_obj)| phys_obj.mass).sum();
+    let center: Vec2 = particles
+        .iter()
+        .map(|(position, phys_obj)| phys_obj.mass * *position)
+        .sum::<Vec2>()
+        / mass;
+    let vel: Vec2 = particles
+        .iter()
+        .map(|(_, phys_obj)| phys_obj.mass * phys_obj.vel)
+        .sum::<Vec2>()
+        / mass;
+    let angular_momentum: f32 = particles
+        .iter()
+        .map(|(position, phys_obj)| {
+            phys_obj.mass * (*position - center).perp_dot(phys_obj.vel - vel)
+                + phys_obj.moment_of_inertia * phys_obj.angular_vel
+        })
+        .sum();
+
+    for &particle in blob.ring.iter().chain(&blob.edges) {
+        world.despawn(particle);
+    }
+
+    let mut ball = world.entity_mut(entity);
+    if let Some(mut transform) = ball.get_mut::<Transform>() {
+        transform.translation = center.extend(transform.translation.z);
+    }
+    ball.insert((
+        PhysObj {
+            mass: blob.ball_mass,
+            vel,
+            acc: Vec2::ZERO,
+            acc_prev: Vec2::ZERO,
+            moment_of_inertia: blob.ball_moment_of_inertia,
+            angular_vel: angular_momentum / blob.ball_moment_of_inertia,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: Vec2::ZERO,
+        },
+        blob.ball_collider,
+    ))
+    .remove::<KeepAwake>();
+
+    let spinner = ball.get::<FidgetSpinner>().copied();
+    if let (Some(spinner), true) = (spinner, ball.contains::<Mesh2dHandle>()) {
+        if world.contains_resource::<MeshCache>() && world.contains_resource::<Assets<Mesh>>() {
+            let mesh = world.resource_scope(|world, mut cache: Mut<MeshCache>| {
+                cache.spinner(&mut world.resource_mut::<Assets<Mesh>>(), spinner)
+            });
+            world.entity_mut(entity).insert(Mesh2dHandle(mesh));
+        }
+    }
+}
+
+fn blob_toggle_system(world: &mut World) {
+    if !world.resource::<Input<KeyCode>>().just_pressed(BLOB_KEY) {
+        return;
+    }
+    let players: Vec<(Entity, bool)> = world
+        .query_filtered::<(Entity, Option<&Blob>), With<Player>>()
+        .iter(world)
+        .map(|(entity, blob)| (entity, blob.is_some()))
+        .collect();
+    for (entity, is_blob) in players {
+        if is_blob {
+            blob_to_ball(world, entity);
+        } else {
+            ball_to_blob(world, entity);
+        }
+    }
+}
+
+// Jumping works while any of the ring touches the ground, and moves every particle alike so that
+// it doesn't deform the blob
+fn blob_impulse_system(
+    input: Res<PlayerInput>,
+    mut jumps: EventWriter<JumpEvent>,
+    blobs: Query<(Entity, &Blob, &Player)>,
+    mut particles: Query<(&mut PhysObj, &Collider)>,
+) {
+    if !input.jump {
+        return;
+    }
+    for (entity, blob, player) in &blobs {
+        let grounded = blob.ring.iter().any(|&particle| {
+            matches!(
+                particles.get(particle),
+                Ok((
+                    _,
+                    Collider::Ball {
+                        touching_ground: true,
+                        ..
+                    }
+                ))
+            )
+        });
+        if !grounded {
+            continue;
+        }
+
+        let dv = Vec2::Y * player.jump_impulse / blob.mass();
+        for &particle in std::iter::once(&entity).chain(&blob.ring) {
+            if let Ok((mut phys_obj, _)) = particles.get_mut(particle) {
+                phys_obj.vel += dv;
+            }
+        }
+        jumps.send(JumpEvent {
+            entity,
+            impulse: player.jump_impulse,
+        });
+    }
+}
+
+// Spinning the player's blob pushes the ring around the center
+fn blob_spin_system(
+    input: Res<PlayerInput>,
+    blobs: Query<(Entity, &Blob, &Player)>,
+    mut particles: Query<(&Transform, &mut PhysObj)>,
+) {
+    for (center, blob, player) in &blobs {
+        let torque = (input.spin_left as i32 - input.spin_right as i32) as f32 * player.torque;
+        if torque == 0.0 {
+            continue;
+        }
+        let spin_force = torque / (blob.ring.len() as f32 * blob.spoke_length);
+        let Ok(center_position) = particles
+            .get(center)
+            .map(|(transform, _)| transform.translation.truncate())
+        else {
+            continue;
+        };
+        for &particle in &blob.ring {
+            let Ok((transform, mut phys_obj)) = particles.get_mut(particle) else {
+                continue;
+            };
+            let Some(direction) =
+                (transform.translation.truncate() - center_position).try_normalize()
+            else {
+                continue;
+            };
+            let mass = phys_obj.mass;
+            phys_obj.acc += spin_force * direction.perp() / mass;
+        }
+    }
+}
+
+// The spokes and the pressure: pulls each ring particle part of the way back to its spoke's
+// length, then pushes the ring part of the way back out to its area. Moves are made as if they
+// happened over the step, so particles get their velocity too, and keep the blob's momentum. The
+// spokes are all worked out from where the particles were, as going round them one at a time
+// would drag the center round with them.
+fn blob_shape_system(
+    time: Res<PhysicsTime>,
+    blobs: Query<(Entity, &Blob)>,
+    mut particles: Query<(&mut Transform, &mut PhysObj)>,
+) {
+    let dt = time.delta;
+    for (center, blob) in &blobs {
+        let Ok((center_transform, center_obj)) = particles.get(center) else {
+            continue;
+        };
+        let (center_position, center_mass) =
+            (center_transform.translation.truncate(), center_obj.mass);
+        let mut center_move = Vec2::ZERO;
+        for &particle in &blob.ring {
+            let Ok((mut transform, mut phys_obj)) = particles.get_mut(particle) else {
+                continue;
+            };
+            let offset = transform.translation.truncate() - center_position;
+            let Some(direction) = offset.try_normalize() else {
+                continue;
+            };
+            let stretch = offset.length() - blob.spoke_length;
+            // Split between the two ends by their inverse masses
+            let correction =
+                SPOKE_STIFFNESS * stretch * direction / (1.0 / center_mass + 1.0 / phys_obj.mass);
+            let particle_move = -correction / phys_obj.mass;
+            transform.translation += particle_move.extend(0.0);
+            phys_obj.vel += particle_move / dt;
+            center_move += correction / center_mass;
+        }
+        if let Ok((mut transform, mut phys_obj)) = particles.get_mut(center) {
+            transform.translation += center_move.extend(0.0);
+            phys_obj.vel += center_move / dt;
+        }
+
+        let positions: Vec<Vec2> = blob
+            .ring
+            .iter()
+            .filter_map(|&particle| Some(particles.get(particle).ok()?.0.translation.truncate()))
+            .collect();
+        if positions.len() != blob.ring.len() {
+            continue;
+        }
+        // How the area changes as each particle moves, which is outwards, and how much to move
+        // them along that for the area to be right if it changed linearly
+        let n = positions.len();
+        let gradients: Vec<Vec2> = (0..n)
+            .map(|i| -0.5 * (positions[(i + 1) % n] - positions[(i + n - 1) % n]).perp())
+            .collect();
+        let area = 0.5
+            * (0..n)
+                .map(|i| positions[i].perp_dot(positions[(i + 1) % n]))
+                .sum::<f32>();
+        let scale = gradients.iter().map(|g| g.length_squared()).sum::<f32>();
+        if scale == 0.0 {
+            continue;
+        }
+        let push = PRESSURE * (blob.rest_area - area) / scale;
+        for (&particle, gradient) in blob.ring.iter().zip(gradients) {
+            if let Ok((mut transform, mut phys_obj)) = particles.get_mut(particle) {
+                transform.translation += (push * gradient).extend(0.0);
+                phys_obj.vel += push * gradient / dt;
+            }
+        }
+    }
+}
+
+// Stretches the blob's mesh over its ring. The rim goes through the outer edges of the particles,
+// where they touch things.
+fn blob_mesh_system(
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    blobs: Query<(&Transform, &Blob, &Mesh2dHandle)>,
+    particles: Query<&Transform, With<BlobParticle>>,
+) {
+    let Some(mut meshes) = meshes else {
+        return;
+    };
+    for (transform, blob, mesh) in &blobs {
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+        let to_local = transform.rotation.inverse();
+        let rim = blob.ring.iter().filter_map(|&particle| {
+            let offset =
+                to_local * (particles.get(particle).ok()?.translation - transform.translation);
+            let offset = offset.truncate();
+            let rim = offset + offset.normalize_or_zero() * PARTICLE_RADIUS;
+            Some([rim.x, rim.y, 0.0])
+        });
+        set_rim_positions(mesh, rim);
+    }
+}
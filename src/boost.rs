@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::physics::{
+    sleep::Sleeping, Collider, PhysObj, PhysicsSchedule, PhysicsSet, PhysicsTime,
+};
+
+// The area of the floor a pad covers, from its center along the floor and up from the floor
+pub const BOOST_PAD_SIZE: Vec2 = Vec2::new(80.0, 10.0);
+// How long a pad flashes for after boosting something
+pub const BOOST_FLASH_TIME: f32 = 0.25;
+
+// Pads lying on the floor that fling whatever rolls onto them. A body that overlaps a pad gets one
+// impulse from it, with the jump's in PhysicsSet::ApplyImpulses, and not another until it's left
+// the pad. Only the simulated parts; VisualsPlugin draws the pads as arrows and flashes them.
+pub struct BoostPadPlugin;
+
+impl Plugin for BoostPadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            boost_pad_system
+                .in_set(PhysicsSet::ApplyImpulses)
+                .in_schedule(PhysicsSchedule),
+        );
+    }
+}
+
+// An impulse of `strength` along `direction`, which doesn't need to be a unit vector
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct BoostPad {
+    pub direction: Vec2,
+    pub strength: f32,
+}
+
+impl BoostPad {
+    pub fn impulse(&self) -> Vec2 {
+        self.direction.normalize_or_zero() * self.strength
+    }
+
+    // Whether a ball at `position` overlaps a pad lying on the floor at `base`
+    pub fn overlaps(base: Vec2, position: Vec2, radius: f32) -> bool {
+        let half_width = Vec2::new(0.5 * BOOST_PAD_SIZE.x, 0.0);
+        let (min, max) = (
+            base - half_width,
+            base + half_width + Vec2::Y * BOOST_PAD_SIZE.y,
+        );
+        position.clamp(min, max).distance(position) < radius
+    }
+}
+
+// Inserted on a pad the first time it's checked
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct BoostPadState {
+    // The bodies on the pad, which it's already boosted
+    pub touching: Vec<Entity>,
+    // Seconds left of the flash from the last boost
+    pub flash: f32,
+}
+
+fn boost_pad_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut pads: Query<(Entity, &Transform, &BoostPad, Option<&mut BoostPadState>), Without<PhysObj>>,
+    mut bodies: Query<(Entity, &Transform, &mut PhysObj, &Collider), Without<Sleeping>>,
+) {
+    for (pad_entity, pad_transform, pad, state) in &mut pads {
+        let base = pad_transform.translation.truncate();
+        let mut next = state.as_deref().cloned().unwrap_or_default();
+        next.flash = (next.flash - time.delta).max(0.0);
+        let mut touching = Vec::new();
+        for (entity, transform, mut phys_obj, &Collider::Ball { radius, .. }) in &mut bodies {
+            if !BoostPad::overlaps(base, transform.translation.truncate(), radius) {
+                continue;
+            }
+            touching.push(entity);
+            if next.touching.contains(&entity) {
+                continue;
+            }
+            let dv = pad.impulse() / phys_obj.mass;
+            phys_obj.vel += dv;
+            next.flash = BOOST_FLASH_TIME;
+        }
+        next.touching = touching;
+
+        match state {
+            // Only on a change, so the visuals can tell when it's flashing
+            Some(mut state) => {
+                if *state != next {
+                    *state = next;
+                }
+            }
+            None => {
+                commands.entity(pad_entity).insert(next);
+            }
+        }
+    }
+}
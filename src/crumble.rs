@@ -0,0 +1,148 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::Floor,
+    physics::{Collider, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsTime},
+};
+
+// Width of the tiles a crumbling stretch of floor is made of
+pub const CRUMBLING_TILE_WIDTH: f32 = 40.0;
+
+// Floor tiles that give way under balls. A ball landing or resting on a tile starts it crumbling,
+// and `delay` seconds later it's gone: its stretch of the floor becomes one of the
+// PhysicsConfig::floor_gaps, and whatever's on it falls through. Tiles with a `respawn` time come
+// back that long after, once nothing's in the hole. Only the simulated parts; VisualsPlugin shakes
+// and fades the tiles.
+pub struct CrumblingPlugin;
+
+impl Plugin for CrumblingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrumbledGaps>()
+            .add_system(
+                crumbling_system
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(crumbled_despawn_system);
+    }
+}
+
+// On a Floor tile
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Crumbling {
+    pub delay: f32,
+    pub respawn: Option<f32>,
+}
+
+// How far a tile is through crumbling. Solid until a ball first touches it, which inserts it.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CrumbleState {
+    #[default]
+    Solid,
+    // Counting down to falling away
+    Shaking {
+        elapsed: f32,
+    },
+    // Fallen away, and a gap in the floor
+    Gone {
+        elapsed: f32,
+    },
+}
+
+// The gaps crumbled tiles have made, so that they can be filled in again if the tiles are
+// despawned (e.g. by the level restarting)
+#[derive(Resource, Default)]
+struct CrumbledGaps(HashMap<Entity, (f32, f32)>);
+
+#[allow(clippy::type_complexity)]
+fn crumbling_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut config: ResMut<PhysicsConfig>,
+    mut gaps: ResMut<CrumbledGaps>,
+    mut tiles: Query<
+        (
+            Entity,
+            &Transform,
+            &Floor,
+            &Crumbling,
+            Option<&mut CrumbleState>,
+        ),
+        Without<PhysObj>,
+    >,
+    balls: Query<(&Transform, &Collider), With<PhysObj>>,
+) {
+    let dt = time.delta;
+    let floor_y = config.floor_y;
+    for (entity, transform, floor, crumbling, state) in &mut tiles {
+        let x = transform.translation.x;
+        let (left, right) = (x - 0.5 * floor.width, x + 0.5 * floor.width);
+        let next = match state.as_deref().copied().unwrap_or_default() {
+            CrumbleState::Solid => {
+                // Landing on it or resting on it, with its center over the tile
+                let touched = balls.iter().any(
+                    |(
+                        ball,
+                        &Collider::Ball {
+                            touching_ground, ..
+                        },
+                    )| {
+                        touching_ground && (left..=right).contains(&ball.translation.x)
+                    },
+                );
+                if !touched {
+                    continue;
+                }
+                CrumbleState::Shaking { elapsed: 0.0 }
+            }
+            CrumbleState::Shaking { elapsed } if elapsed + dt >= crumbling.delay => {
+                config.floor_gaps.push((left, right));
+                gaps.0.insert(entity, (left, right));
+                CrumbleState::Gone { elapsed: 0.0 }
+            }
+            CrumbleState::Shaking { elapsed } => CrumbleState::Shaking {
+                elapsed: elapsed + dt,
+            },
+            CrumbleState::Gone { elapsed } => {
+                let elapsed = elapsed + dt;
+                // Filling the hole in under something in it would pop it back up. Anything that's
+                // fallen all the way through is gone.
+                let in_hole = || {
+                    balls.iter().any(|(ball, &Collider::Ball { radius, .. })| {
+                        (ball.translation.y - floor_y).abs() < radius
+                            && ball.translation.x + radius > left
+                            && ball.translation.x - radius < right
+                    })
+                };
+                match crumbling.respawn {
+                    Some(respawn) if elapsed >= respawn && !in_hole() => {
+                        config.floor_gaps.retain(|&gap| gap != (left, right));
+                        gaps.0.remove(&entity);
+                        CrumbleState::Solid
+                    }
+                    _ => CrumbleState::Gone { elapsed },
+                }
+            }
+        };
+
+        match state {
+            Some(mut state) => *state = next,
+            None => {
+                commands.entity(entity).insert(next);
+            }
+        }
+    }
+}
+
+fn crumbled_despawn_system(
+    mut removed: RemovedComponents<Crumbling>,
+    mut gaps: ResMut<CrumbledGaps>,
+    mut config: ResMut<PhysicsConfig>,
+) {
+    for entity in removed.iter() {
+        if let Some(gap) = gaps.0.remove(&entity) {
+            config.floor_gaps.retain(|&other| other != gap);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::overlay::HistoryBuffer;
+use crate::{save::SavedBody, state::AppState};
+
+// F12 dumps the world (see WorldDump)
+pub struct WorldDumpPlugin;
+
+impl Plugin for WorldDumpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(world_dump_system);
+    }
+}
+
+// Physics-relevant state of the whole world, for reproducing bugs. F12 writes one to
+// `debug_dump_<timestamp>.ron` (or the console on WASM) and `load_dump` restores it.
+#[derive(Serialize, Deserialize)]
+pub struct WorldDump {
+    pub bodies: Vec<SavedBody>,
+    pub state: AppState,
+}
+
+pub fn dump_world(world: &mut World) -> WorldDump {
+    WorldDump {
+        bodies: SavedBody::capture_all(world),
+        state: world
+            .get_resource::<State<AppState>>()
+            .map_or_else(default, |state| state.0),
+    }
+}
+
+// Spawns the bodies of a dump into `world` and restores its resources.
+// Meant to be used on a fresh App when reproducing a bug.
+pub fn load_dump(world: &mut World, dump: &WorldDump) {
+    for body in &dump.bodies {
+        body.spawn(world);
+    }
+    world.insert_resource(NextState(Some(dump.state)));
+}
+
+fn world_dump_system(world: &mut World) {
+    if !world
+        .resource::<Input<KeyCode>>()
+        .just_pressed(KeyCode::F12)
+    {
+        return;
+    }
+
+    let dump = dump_world(world);
+    match ron::ser::to_string_pretty(&dump, ron::ser::PrettyConfig::default()) {
+        Ok(dump) => write_debug_file("debug_dump", "ron", &dump),
+        Err(error) => error!("Failed to serialize world dump: {error}"),
+    }
+
+    if let Some(history) = world.get_resource::<HistoryBuffer>() {
+        if history.frozen {
+            write_debug_file("debug_history", "csv", &history.to_csv());
+        }
+    }
+}
+
+// Writes `<name>_<timestamp>.<extension>` natively
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_debug_file(name: &str, extension: &str, contents: &str) {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let path = format!("{name}_{timestamp}.{extension}");
+    match std::fs::write(&path, contents) {
+        Ok(()) => info!("Wrote {path}"),
+        Err(error) => error!("Failed to write {path}: {error}"),
+    }
+}
+
+// There's no file system on WASM, so the browser downloads the file instead
+#[cfg(target_arch = "wasm32")]
+pub fn write_debug_file(name: &str, extension: &str, contents: &str) {
+    use wasm_bindgen::JsCast;
+
+    let file_name = format!("{name}_{}.{extension}", js_sys::Date::now() as u64);
+    let url = format!(
+        "data:text/plain;charset=utf-8,{}",
+        js_sys::encode_uri_component(contents)
+    );
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok());
+    match anchor {
+        Some(anchor) => {
+            anchor.set_href(&url);
+            anchor.set_download(&file_name);
+            anchor.click();
+        }
+        None => info!("{name}:\n{contents}"),
+    }
+}
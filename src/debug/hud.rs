@@ -0,0 +1,211 @@
+use bevy::{prelude::*, utils::Duration};
+
+use crate::{
+    level::{floor_below, Floor},
+    physics::{timings::PhysicsTimings, Collider, Gravity, PhysObj, PhysicsStep},
+    player::LocalPlayer,
+    state::AssetLoadState,
+};
+
+// Text readout of the player's physics state in the top-left corner, toggled with F1
+pub struct StatsHudPlugin;
+
+impl Plugin for StatsHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatsHud>()
+            .add_startup_system(stats_hud_setup)
+            .add_systems((
+                stats_hud_toggle_system,
+                stats_hud_update_system.after(PhysicsStep),
+            ));
+    }
+}
+
+#[derive(Resource)]
+pub struct StatsHud {
+    pub visible: bool,
+    // Rebuilding the text every frame is wasteful, so it's only updated when this finishes
+    update_timer: Timer,
+}
+
+impl Default for StatsHud {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            update_timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+struct StatsHudText;
+
+const STATS_HUD_LABELS: [&str; 7] = [
+    "speed",
+    "vertical velocity",
+    "angular velocity",
+    "height",
+    "touching ground",
+    "energy",
+    "physics time",
+];
+const STATS_HUD_COLOR: Color = Color::WHITE;
+const STATS_HUD_UNAVAILABLE_COLOR: Color = Color::GRAY;
+
+pub fn format_speed(speed: f32) -> String {
+    format!("{speed:.0} px/s")
+}
+
+pub fn format_rpm(angular_vel: f32) -> String {
+    format!("{:.0} rpm", angular_vel * 60.0 / std::f32::consts::TAU)
+}
+
+pub fn format_distance(distance: f32) -> String {
+    format!("{distance:.1} px")
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn format_energy(energy: f32) -> String {
+    format!("{:.1} kJ", energy / 1000.0)
+}
+
+// Kinetic (linear and rotational) plus gravitational potential energy at `height`
+pub fn mechanical_energy(phys_obj: &PhysObj, height: f32, gravity: Option<&Gravity>) -> f32 {
+    let kinetic = 0.5 * phys_obj.mass * phys_obj.vel.length_squared()
+        + 0.5 * phys_obj.moment_of_inertia * phys_obj.angular_vel.powi(2);
+    let potential = gravity.map_or(0.0, |gravity| phys_obj.mass * gravity.0 * height);
+    kinetic + potential
+}
+
+fn stats_hud_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut load_state: ResMut<AssetLoadState>,
+) {
+    let font = asset_server.load("fonts/DejaVuSansMono.ttf");
+    load_state.track(font.clone_untyped());
+    let style = TextStyle {
+        font,
+        font_size: 16.0,
+        color: STATS_HUD_COLOR,
+    };
+    // A label and a value section for every line
+    let sections = STATS_HUD_LABELS.iter().flat_map(|label| {
+        [
+            TextSection::new(format!("{label}: "), style.clone()),
+            TextSection::new("", style.clone()),
+        ]
+    });
+
+    commands.spawn((
+        TextBundle::from_sections(sections).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(5.0),
+                left: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        }),
+        StatsHudText,
+    ));
+}
+
+fn stats_hud_toggle_system(
+    input: Res<Input<KeyCode>>,
+    mut hud: ResMut<StatsHud>,
+    mut query: Query<&mut Visibility, With<StatsHudText>>,
+) {
+    if !input.just_pressed(KeyCode::F1) {
+        return;
+    }
+    hud.visible = !hud.visible;
+    for mut visibility in &mut query {
+        *visibility = if hud.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn stats_hud_update_system(
+    time: Res<Time>,
+    mut hud: ResMut<StatsHud>,
+    timings: Res<PhysicsTimings>,
+    floors: Query<(&Transform, &Floor)>,
+    bodies: Query<(&Transform, &PhysObj, Option<&Gravity>)>,
+    player: Query<(&Transform, &PhysObj, &Collider), LocalPlayer>,
+    mut text: Query<&mut Text, With<StatsHudText>>,
+) {
+    if !hud.update_timer.tick(time.delta()).just_finished() || !hud.visible {
+        return;
+    }
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+
+    let energy: f32 = bodies
+        .iter()
+        .map(|(transform, phys_obj, gravity)| {
+            let height = floor_below(transform.translation.truncate(), &floors)
+                .map_or(0.0, |floor_y| transform.translation.y - floor_y);
+            mechanical_energy(phys_obj, height, gravity)
+        })
+        .sum();
+
+    let physics_time = timings.enabled.then(|| format_duration(timings.total()));
+    let values: [Option<String>; 7] = match player.get_single() {
+        Ok((
+            transform,
+            phys_obj,
+            &Collider::Ball {
+                radius,
+                touching_ground,
+                ..
+            },
+        )) => {
+            let position = transform.translation.truncate();
+            [
+                Some(format_speed(phys_obj.vel.length())),
+                Some(format_speed(phys_obj.vel.y)),
+                Some(format_rpm(phys_obj.angular_vel)),
+                floor_below(position, &floors)
+                    .map(|floor_y| format_distance(position.y - radius - floor_y)),
+                Some(touching_ground.to_string()),
+                Some(format_energy(energy)),
+                physics_time,
+            ]
+        }
+        Err(_) => [
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(format_energy(energy)),
+            physics_time,
+        ],
+    };
+
+    for (i, value) in values.into_iter().enumerate() {
+        let color = if value.is_some() {
+            STATS_HUD_COLOR
+        } else {
+            STATS_HUD_UNAVAILABLE_COLOR
+        };
+        let newline = if i + 1 < STATS_HUD_LABELS.len() {
+            "\n"
+        } else {
+            ""
+        };
+        for section in &mut text.sections[2 * i..2 * i + 2] {
+            section.style.color = color;
+        }
+        let value = value.as_deref().unwrap_or("n/a");
+        text.sections[2 * i + 1].value = format!("{value}{newline}");
+    }
+}
@@ -0,0 +1,118 @@
+use bevy::{prelude::*, render::mesh::PrimitiveTopology, sprite::Mesh2dHandle};
+
+// Immediate mode line drawing (this version of bevy has no gizmos). Systems add lines to the
+// DebugLines resource during the frame and they are turned into a single line list mesh in
+// PostUpdate, after which the resource is cleared.
+pub struct DebugLinesPlugin;
+
+impl Plugin for DebugLinesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugLines>()
+            .add_startup_system(debug_lines_setup)
+            .add_system(debug_lines_render_system.in_base_set(CoreSet::PostUpdate));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DebugLines {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+}
+
+impl DebugLines {
+    // Lines added since the mesh was last rewritten
+    pub fn len(&self) -> usize {
+        self.positions.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn line(&mut self, start: Vec2, end: Vec2, color: Color) {
+        self.positions.push(start.extend(0.0).into());
+        self.positions.push(end.extend(0.0).into());
+        self.colors
+            .extend_from_slice(&[color.as_linear_rgba_f32(), color.as_linear_rgba_f32()]);
+    }
+
+    // Polyline through `points`
+    pub fn line_strip(&mut self, points: &[Vec2], color: Color) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    // Every other segment of the polyline through `points`
+    pub fn dotted_line_strip(&mut self, points: &[Vec2], color: Color) {
+        for pair in points.windows(2).step_by(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    // Arc from `start_angle` counter-clockwise by `sweep` radians
+    pub fn arc(&mut self, center: Vec2, radius: f32, start_angle: f32, sweep: f32, color: Color) {
+        let segments = ((sweep.abs() / std::f32::consts::TAU * 32.0).ceil() as usize).max(1);
+        let point = |i: usize| {
+            let angle = start_angle + sweep * i as f32 / segments as f32;
+            center + radius * Vec2::from_angle(angle)
+        };
+        for i in 0..segments {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Color) {
+        self.arc(center, radius, 0.0, std::f32::consts::TAU, color);
+    }
+}
+
+#[derive(Component)]
+struct DebugLinesMesh;
+
+fn debug_lines_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // Starts out with a single degenerate line so the mesh is never empty
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0]; 2]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0.0, 0.0, 0.0, 0.0]; 2]);
+
+    commands.spawn((
+        ColorMesh2dBundle {
+            mesh: meshes.add(mesh).into(),
+            material: materials.add(Color::WHITE.into()),
+            // In front of everything else
+            transform: Transform::from_xyz(0.0, 0.0, 10.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DebugLinesMesh,
+    ));
+}
+
+fn debug_lines_render_system(
+    mut lines: ResMut<DebugLines>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut query: Query<(&Mesh2dHandle, &mut Visibility), With<DebugLinesMesh>>,
+) {
+    let Ok((mesh, mut visibility)) = query.get_single_mut() else {
+        return;
+    };
+    if lines.positions.is_empty() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    let Some(mesh) = meshes.get_mut(&mesh.0) else {
+        return;
+    };
+
+    *visibility = Visibility::Inherited;
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        std::mem::take(&mut lines.positions),
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, std::mem::take(&mut lines.colors));
+}
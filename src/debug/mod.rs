@@ -0,0 +1,34 @@
+// Developer tooling: line drawing, the physics overlay, the stats HUD, the performance panel, world
+// dumps, traces and adding keys to actions. Only the line drawing is always there, the rest is
+// behind the debug-tools feature.
+#[cfg(feature = "debug-tools")]
+use bevy::prelude::*;
+
+#[cfg(feature = "debug-tools")]
+pub mod dump;
+#[cfg(feature = "debug-tools")]
+pub mod hud;
+pub mod lines;
+#[cfg(feature = "debug-tools")]
+pub mod overlay;
+#[cfg(feature = "debug-tools")]
+pub mod perf;
+#[cfg(feature = "debug-tools")]
+pub mod rebind;
+#[cfg(feature = "debug-tools")]
+pub mod trace;
+
+#[cfg(feature = "debug-tools")]
+pub struct DebugToolsPlugin;
+
+#[cfg(feature = "debug-tools")]
+impl Plugin for DebugToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(overlay::PhysicsDebugPlugin)
+            .add_plugin(hud::StatsHudPlugin)
+            .add_plugin(perf::PerfPanelPlugin)
+            .add_plugin(dump::WorldDumpPlugin)
+            .add_plugin(trace::TracePlugin)
+            .add_plugin(rebind::KeyListenerPlugin);
+    }
+}
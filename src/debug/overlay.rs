@@ -0,0 +1,293 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::lines::DebugLines;
+use crate::{
+    level::Floor,
+    physics::{
+        joints::{DistanceJoint, RevoluteJoint},
+        Collider, PhysObj, PhysicsSchedule, PhysicsSet, PhysicsStep,
+    },
+    player::LocalPlayer,
+    settings::{InputAction, Settings},
+};
+
+const HISTORY_LEN: usize = 300;
+const HISTORY_PLOT_HEIGHT: f32 = 80.0;
+
+// Physics debug overlay, toggled with F3
+pub struct PhysicsDebugPlugin;
+
+impl Plugin for PhysicsDebugPlugin {
+    fn build(&self, app: &mut App) {
+        let enabled = app
+            .world
+            .get_resource::<Settings>()
+            .is_some_and(|settings| settings.debug_overlay);
+        app.insert_resource(PhysicsDebug { enabled })
+            .init_resource::<HistoryBuffer>()
+            .init_resource::<Settings>()
+            .add_systems((
+                physics_debug_toggle_system,
+                physics_debug_draw_system
+                    .after(physics_debug_toggle_system)
+                    .after(PhysicsStep)
+                    .run_if(physics_debug_enabled),
+                history_freeze_system,
+                history_plot_system
+                    .after(PhysicsStep)
+                    .run_if(physics_debug_enabled),
+            ))
+            .add_system(
+                history_record_system
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PhysicsDebug {
+    pub enabled: bool,
+}
+
+const DEBUG_VELOCITY_COLOR: Color = Color::CYAN;
+const DEBUG_ACCELERATION_COLOR: Color = Color::ORANGE_RED;
+const DEBUG_ANGULAR_VELOCITY_COLOR: Color = Color::FUCHSIA;
+const DEBUG_COLLIDER_COLOR: Color = Color::WHITE;
+const DEBUG_GROUNDED_COLOR: Color = Color::LIME_GREEN;
+const DEBUG_FLOOR_COLOR: Color = Color::GRAY;
+const DEBUG_JOINT_COLOR: Color = Color::YELLOW;
+
+// Seconds of motion drawn for velocity, and the equivalent for acceleration
+const DEBUG_VELOCITY_SCALE: f32 = 0.1;
+const DEBUG_ACCELERATION_SCALE: f32 = 0.02;
+
+// Fixed-capacity ring buffer, never allocates
+#[derive(Clone)]
+pub struct RingBuffer<const N: usize> {
+    values: [f32; N],
+    // Index of the oldest value
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self {
+            values: [0.0; N],
+            start: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize> RingBuffer<N> {
+    pub fn push(&mut self, value: f32) {
+        if self.len < N {
+            self.values[(self.start + self.len) % N] = value;
+            self.len += 1;
+        } else {
+            self.values[self.start] = value;
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    // Oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        (0..self.len).map(|i| self.values[(self.start + i) % N])
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// The player's recent vertical motion, one entry per physics step. F freezes recording so a bad
+// bounce can be inspected; the plots are part of the debug overlay and F12 exports a frozen buffer
+// to CSV along with the world dump.
+#[derive(Resource, Default)]
+pub struct HistoryBuffer {
+    pub frozen: bool,
+    pub y: RingBuffer<HISTORY_LEN>,
+    pub vel_y: RingBuffer<HISTORY_LEN>,
+}
+
+impl HistoryBuffer {
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("step,y,vel_y\n");
+        for (i, (y, vel_y)) in self.y.iter().zip(self.vel_y.iter()).enumerate() {
+            csv.push_str(&format!("{i},{y},{vel_y}\n"));
+        }
+        csv
+    }
+}
+
+fn history_freeze_system(
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut history: ResMut<HistoryBuffer>,
+) {
+    if settings
+        .input
+        .just_pressed(&input, InputAction::FreezeHistory)
+    {
+        history.frozen = !history.frozen;
+    }
+}
+
+fn history_record_system(
+    mut history: ResMut<HistoryBuffer>,
+    query: Query<(&Transform, &PhysObj), LocalPlayer>,
+) {
+    if history.frozen {
+        return;
+    }
+    let Ok((transform, phys_obj)) = query.get_single() else {
+        return;
+    };
+    history.y.push(transform.translation.y);
+    history.vel_y.push(phys_obj.vel.y);
+}
+
+// Plots the history in the bottom-right corner of the screen
+fn history_plot_system(
+    history: Res<HistoryBuffer>,
+    mut lines: ResMut<DebugLines>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<&Transform, With<Camera2d>>,
+) {
+    let (Ok(window), Ok(camera)) = (windows.get_single(), cameras.get_single()) else {
+        return;
+    };
+    let bottom_right =
+        camera.translation.truncate() + Vec2::new(0.5 * window.width(), -0.5 * window.height());
+    let width = HISTORY_LEN as f32;
+    let color = if history.frozen {
+        Color::YELLOW
+    } else {
+        Color::WHITE
+    };
+
+    for (row, buffer) in [&history.y, &history.vel_y].into_iter().enumerate() {
+        let origin = bottom_right
+            + Vec2::new(
+                -width - 10.0,
+                10.0 + row as f32 * (HISTORY_PLOT_HEIGHT + 10.0),
+            );
+        lines.line(origin, origin + Vec2::X * width, Color::DARK_GRAY);
+
+        // Scaled to fit the current contents
+        let (min, max) = buffer
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        let range = (max - min).max(f32::EPSILON);
+        let points: Vec<Vec2> = buffer
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                origin
+                    + Vec2::new(
+                        width * i as f32 / HISTORY_LEN as f32,
+                        HISTORY_PLOT_HEIGHT * (value - min) / range,
+                    )
+            })
+            .collect();
+        if buffer.len() > 1 {
+            lines.line_strip(&points, color);
+        }
+    }
+}
+
+fn physics_debug_enabled(debug: Res<PhysicsDebug>) -> bool {
+    debug.enabled
+}
+
+fn physics_debug_toggle_system(input: Res<Input<KeyCode>>, mut debug: ResMut<PhysicsDebug>) {
+    if input.just_pressed(KeyCode::F3) {
+        debug.enabled = !debug.enabled;
+    }
+}
+
+fn physics_debug_draw_system(
+    mut lines: ResMut<DebugLines>,
+    bodies: Query<(&Transform, &PhysObj, Option<&Collider>)>,
+    floors: Query<(&Transform, &Floor)>,
+    joints: Query<&DistanceJoint>,
+    pins: Query<&RevoluteJoint>,
+    ends: Query<&Transform>,
+) {
+    for (transform, floor) in &floors {
+        let surface = transform.translation.truncate();
+        let half_width = Vec2::X * 0.5 * floor.width;
+        lines.line(
+            surface - half_width,
+            surface + half_width,
+            DEBUG_FLOOR_COLOR,
+        );
+    }
+
+    for joint in &joints {
+        if let Ok([a, b]) = ends.get_many([joint.a, joint.b]) {
+            lines.line(
+                a.translation.truncate(),
+                b.translation.truncate(),
+                DEBUG_JOINT_COLOR,
+            );
+        }
+    }
+
+    for pin in &pins {
+        if let Ok([a, b]) = ends.get_many([pin.a, pin.b]) {
+            for (transform, anchor) in [(a, pin.anchor_on_a), (b, pin.anchor_on_b)] {
+                let center = transform.translation.truncate();
+                let point = center + (transform.rotation * anchor.extend(0.0)).truncate();
+                lines.line(center, point, DEBUG_JOINT_COLOR);
+                lines.circle(point, 3.0, DEBUG_JOINT_COLOR);
+            }
+        }
+    }
+
+    for (transform, phys_obj, collider) in &bodies {
+        let center = transform.translation.truncate();
+        lines.line(
+            center,
+            center + phys_obj.vel * DEBUG_VELOCITY_SCALE,
+            DEBUG_VELOCITY_COLOR,
+        );
+        lines.line(
+            center,
+            center + phys_obj.acc * DEBUG_ACCELERATION_SCALE,
+            DEBUG_ACCELERATION_COLOR,
+        );
+
+        let Some(Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        }) = collider.copied()
+        else {
+            continue;
+        };
+        lines.circle(center, radius, DEBUG_COLLIDER_COLOR);
+        // Angular velocity as an arc starting at the body's current rotation
+        let angle = transform.rotation.to_euler(EulerRot::XYZ).2;
+        let sweep = (phys_obj.angular_vel * DEBUG_VELOCITY_SCALE)
+            .clamp(-std::f32::consts::TAU, std::f32::consts::TAU);
+        lines.arc(
+            center,
+            radius + 6.0,
+            angle,
+            sweep,
+            DEBUG_ANGULAR_VELOCITY_COLOR,
+        );
+        if touching_ground {
+            lines.circle(center - Vec2::Y * radius, 4.0, DEBUG_GROUNDED_COLOR);
+        }
+    }
+}
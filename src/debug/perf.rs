@@ -0,0 +1,262 @@
+use bevy::{prelude::*, utils::Duration, window::PrimaryWindow};
+
+use super::{lines::DebugLines, overlay::RingBuffer};
+use crate::physics::{timings::PhysicsTimings, PhysicsStep, STEP_DT};
+
+pub const PERF_HISTORY_LEN: usize = 120;
+// The panel's redrawn this often, rather than every frame
+pub const PERF_PANEL_INTERVAL: f32 = 0.25;
+// Each frame is a column this wide, and the chart goes up to twice the budget
+const PERF_COLUMN_WIDTH: f32 = 2.0;
+const PERF_CHART_HEIGHT: f32 = 80.0;
+const PERF_MARGIN: f32 = 10.0;
+
+const PERF_FRAME_COLOR: Color = Color::WHITE;
+const PERF_BUDGET_COLOR: Color = Color::RED;
+const PERF_AXIS_COLOR: Color = Color::DARK_GRAY;
+// Integrator, forces, broad phase, narrow phase and friction, stacked from the bottom up
+const PERF_PHASE_COLORS: [Color; 5] = [
+    Color::CYAN,
+    Color::ORANGE_RED,
+    Color::YELLOW,
+    Color::LIME_GREEN,
+    Color::FUCHSIA,
+];
+
+// Frame time and physics phase timings in the top-right corner: a line of the last
+// PERF_HISTORY_LEN frames' times over bars of their physics phases, with a red line at the budget
+// of a fixed tick. Shown while the physics timings are measured (F4, and always in the stress
+// scene).
+pub struct PerfPanelPlugin;
+
+impl Plugin for PerfPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerfHistory>()
+            .init_resource::<PerfPanel>()
+            .add_startup_system(perf_panel_setup)
+            .add_systems((
+                perf_record_system.after(PhysicsStep),
+                perf_panel_update_system.after(perf_record_system),
+                perf_panel_draw_system.after(perf_panel_update_system),
+            ));
+    }
+}
+
+// The last PERF_HISTORY_LEN frames, in milliseconds
+#[derive(Resource, Default)]
+pub struct PerfHistory {
+    pub frame: RingBuffer<PERF_HISTORY_LEN>,
+    pub physics: RingBuffer<PERF_HISTORY_LEN>,
+    pub integrator: RingBuffer<PERF_HISTORY_LEN>,
+    pub forces: RingBuffer<PERF_HISTORY_LEN>,
+    pub broad_phase: RingBuffer<PERF_HISTORY_LEN>,
+    pub narrow_phase: RingBuffer<PERF_HISTORY_LEN>,
+    pub friction: RingBuffer<PERF_HISTORY_LEN>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameStats {
+    pub min: f32,
+    pub avg: f32,
+    // Nearest rank: no more than 1% of the frames took longer
+    pub p99: f32,
+    pub frames: usize,
+}
+
+// None while the buffer is empty
+pub fn frame_stats<const N: usize>(buffer: &RingBuffer<N>) -> Option<FrameStats> {
+    let mut sorted: Vec<f32> = buffer.iter().collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f32::total_cmp);
+    let frames = sorted.len();
+    // 99% of the frames, rounded up
+    let rank = (frames * 99).div_ceil(100);
+    Some(FrameStats {
+        min: sorted[0],
+        avg: sorted.iter().sum::<f32>() / frames as f32,
+        p99: sorted[rank - 1],
+        frames,
+    })
+}
+
+#[derive(Resource)]
+pub struct PerfPanel {
+    update_timer: Timer,
+    // What the last redraw drew, from the chart's bottom-left corner. Drawn again every frame until
+    // the next one.
+    lines: Vec<(Vec2, Vec2, Color)>,
+    // Of the frame times, as of the last redraw
+    pub frame_stats: Option<FrameStats>,
+}
+
+impl Default for PerfPanel {
+    fn default() -> Self {
+        Self {
+            update_timer: Timer::from_seconds(PERF_PANEL_INTERVAL, TimerMode::Repeating),
+            lines: Vec::new(),
+            frame_stats: None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct PerfPanelText;
+
+// Headless apps have no asset server, and no text to show
+fn perf_panel_setup(mut commands: Commands, asset_server: Option<Res<AssetServer>>) {
+    let Some(asset_server) = asset_server else {
+        return;
+    };
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+        font_size: 14.0,
+        color: PERF_FRAME_COLOR,
+    };
+    commands.spawn((
+        TextBundle::from_section("", style).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(2.0 * PERF_MARGIN + PERF_CHART_HEIGHT),
+                right: Val::Px(PERF_MARGIN),
+                ..default()
+            },
+            ..default()
+        }),
+        Visibility::Hidden,
+        PerfPanelText,
+    ));
+}
+
+fn perf_record_system(
+    time: Res<Time>,
+    timings: Res<PhysicsTimings>,
+    mut history: ResMut<PerfHistory>,
+) {
+    if !timings.enabled {
+        return;
+    }
+    let ms = |duration: Duration| duration.as_secs_f32() * 1000.0;
+    history.frame.push(ms(time.delta()));
+    history.physics.push(ms(timings.total()));
+    history.integrator.push(ms(timings.integrator));
+    history.forces.push(ms(timings.forces));
+    history.broad_phase.push(ms(timings.broad_phase));
+    history.narrow_phase.push(ms(timings.narrow_phase));
+    history.friction.push(ms(timings.friction));
+}
+
+// Redraws the panel every PERF_PANEL_INTERVAL
+fn perf_panel_update_system(
+    time: Res<Time>,
+    timings: Res<PhysicsTimings>,
+    history: Res<PerfHistory>,
+    mut panel: ResMut<PerfPanel>,
+    mut text: Query<(&mut Text, &mut Visibility), With<PerfPanelText>>,
+) {
+    for (_, mut visibility) in &mut text {
+        *visibility = if timings.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !timings.enabled || !panel.update_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    panel.lines = perf_chart(&history);
+    panel.frame_stats = frame_stats(&history.frame);
+    let physics_stats = frame_stats(&history.physics);
+    for (mut text, _) in &mut text {
+        text.sections[0].value = format!(
+            "frame   {}\nphysics {}",
+            format_stats(panel.frame_stats),
+            format_stats(physics_stats),
+        );
+    }
+}
+
+// Puts the last redraw on screen, which the line drawing needs every frame
+fn perf_panel_draw_system(
+    timings: Res<PhysicsTimings>,
+    panel: Res<PerfPanel>,
+    mut lines: ResMut<DebugLines>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<&Transform, With<Camera2d>>,
+) {
+    let (Ok(window), Ok(camera)) = (windows.get_single(), cameras.get_single()) else {
+        return;
+    };
+    if !timings.enabled {
+        return;
+    }
+    let width = PERF_HISTORY_LEN as f32 * PERF_COLUMN_WIDTH;
+    let origin = camera.translation.truncate()
+        + Vec2::new(
+            0.5 * window.width() - PERF_MARGIN - width,
+            0.5 * window.height() - PERF_MARGIN - PERF_CHART_HEIGHT,
+        );
+    for &(start, end, color) in &panel.lines {
+        lines.line(origin + start, origin + end, color);
+    }
+}
+
+fn format_stats(stats: Option<FrameStats>) -> String {
+    match stats {
+        Some(FrameStats { min, avg, p99, .. }) => {
+            format!("min {min:.2} avg {avg:.2} p99 {p99:.2} ms")
+        }
+        None => "n/a".to_string(),
+    }
+}
+
+// The chart's lines, from its bottom-left corner
+fn perf_chart(history: &PerfHistory) -> Vec<(Vec2, Vec2, Color)> {
+    let budget = STEP_DT * 1000.0;
+    let height = |ms: f32| (ms / (2.0 * budget)).min(1.0) * PERF_CHART_HEIGHT;
+    let width = PERF_HISTORY_LEN as f32 * PERF_COLUMN_WIDTH;
+    let mut chart = vec![
+        (Vec2::ZERO, Vec2::X * width, PERF_AXIS_COLOR),
+        (
+            Vec2::Y * height(budget),
+            Vec2::new(width, height(budget)),
+            PERF_BUDGET_COLOR,
+        ),
+    ];
+
+    // They're all recorded together, so they're the same length as the frame times
+    let phases: [Vec<f32>; 5] = [
+        &history.integrator,
+        &history.forces,
+        &history.broad_phase,
+        &history.narrow_phase,
+        &history.friction,
+    ]
+    .map(|phase| phase.iter().collect());
+    for i in 0..history.frame.len() {
+        let x = i as f32 * PERF_COLUMN_WIDTH;
+        let mut top = 0.0;
+        for (phase, color) in phases.iter().zip(PERF_PHASE_COLORS) {
+            let ms = phase[i];
+            let bottom = top;
+            top += ms;
+            chart.push((
+                Vec2::new(x, height(bottom)),
+                Vec2::new(x, height(top)),
+                color,
+            ));
+        }
+    }
+
+    let frames: Vec<Vec2> = history
+        .frame
+        .iter()
+        .enumerate()
+        .map(|(i, ms)| Vec2::new(i as f32 * PERF_COLUMN_WIDTH, height(ms)))
+        .collect();
+    for pair in frames.windows(2) {
+        chart.push((pair[0], pair[1], PERF_FRAME_COLOR));
+    }
+    chart
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::{
+    settings::{InputAction, InputMap, Rebind, Settings},
+    toast::ToastEvent,
+};
+
+const LISTEN_KEY: KeyCode = KeyCode::Insert;
+
+// Adds keys to actions without going through the settings menu. Insert listens for a key for the
+// first action, and again for the next one. The next other key is added to the action as one of
+// its extra keys (see InputMap::extra), and Escape stops listening.
+pub struct KeyListenerPlugin;
+
+impl Plugin for KeyListenerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<KeyListener>()
+            .add_event::<ToastEvent>()
+            .add_system(key_listener_system);
+    }
+}
+
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyListener {
+    #[default]
+    Idle,
+    Listening(InputAction),
+}
+
+impl KeyListener {
+    // Handles `key` being pressed, returning what to tell the player
+    pub fn press(&mut self, map: &mut InputMap, key: KeyCode) -> Option<String> {
+        let next = match (*self, key) {
+            (KeyListener::Idle, LISTEN_KEY) => InputAction::ALL[0],
+            (KeyListener::Idle, _) => return None,
+            (KeyListener::Listening(action), LISTEN_KEY) => {
+                let i = InputAction::ALL.iter().position(|&other| other == action);
+                InputAction::ALL[i.map_or(0, |i| (i + 1) % InputAction::ALL.len())]
+            }
+            (KeyListener::Listening(action), _) => {
+                *self = KeyListener::Idle;
+                return Some(match map.add_key(action, key) {
+                    Rebind::Bind => format!("{key:?} added to {}", action.label()),
+                    Rebind::Conflict(other) => format!("{key:?} is already {}", other.label()),
+                    Rebind::Cancel => "Stopped listening for keys".to_string(),
+                });
+            }
+        };
+        *self = KeyListener::Listening(next);
+        Some(format!(
+            "Press a key to add to {} ({LISTEN_KEY:?}: next action, Escape: stop)",
+            next.label()
+        ))
+    }
+}
+
+fn key_listener_system(
+    keys: Res<Input<KeyCode>>,
+    mut listener: ResMut<KeyListener>,
+    mut settings: ResMut<Settings>,
+    mut toasts: EventWriter<ToastEvent>,
+) {
+    // Only touching the settings while listening, as that saves them
+    if *listener == KeyListener::Idle && !keys.just_pressed(LISTEN_KEY) {
+        return;
+    }
+    for &key in keys.get_just_pressed() {
+        if let Some(message) = listener.press(&mut settings.input, key) {
+            toasts.send(ToastEvent(message));
+        }
+    }
+}
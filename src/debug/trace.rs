@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+
+use super::dump::write_debug_file;
+use crate::{
+    physics::{Collider, CollisionEvent, PhysObj, PhysicsSchedule, PhysicsSet, PhysicsTime},
+    player::Player,
+};
+
+// Rows kept per trace. Recording stops adding rows once it's full.
+const MAX_TRACE_ROWS: usize = 100_000;
+
+// F8 starts and stops tracing (see TraceRecorder)
+pub struct TracePlugin;
+
+impl Plugin for TracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TraceRecorder>()
+            .add_system(trace_toggle_system)
+            .add_system(
+                trace_record_system
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule)
+                    .run_if(trace_enabled),
+            );
+    }
+}
+
+// Bodies whose motion is traced. When tracing starts without any, the player is traced.
+#[derive(Component)]
+pub struct Traced;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+// Per-step motion of the Traced bodies, for plotting outside the game. Written to
+// `trace_<timestamp>.csv` (or `.json`) when tracing stops.
+#[derive(Resource, Default)]
+pub struct TraceRecorder {
+    pub enabled: bool,
+    pub format: TraceFormat,
+    // Simulated time since tracing started
+    pub t: f32,
+    pub rows: Vec<TraceRow>,
+}
+
+#[derive(Clone, Copy)]
+pub struct TraceRow {
+    pub t: f32,
+    pub entity: u32,
+    pub x: f32,
+    pub y: f32,
+    pub vel_x: f32,
+    pub vel_y: f32,
+    pub angular_vel: f32,
+    pub touching_ground: bool,
+    pub friction_impulse: f32,
+    pub normal_impulse: f32,
+}
+
+impl TraceRecorder {
+    pub fn start(&mut self) {
+        self.enabled = true;
+        self.t = 0.0;
+        self.rows.clear();
+        self.rows.reserve(MAX_TRACE_ROWS);
+    }
+
+    pub fn stop(&mut self) {
+        self.enabled = false;
+    }
+
+    // Adds a row unless the trace is full
+    pub fn push(&mut self, row: TraceRow) {
+        if self.rows.len() < MAX_TRACE_ROWS {
+            self.rows.push(row);
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.rows.len() >= MAX_TRACE_ROWS
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "t,entity,x,y,vel_x,vel_y,angular_vel,touching_ground,friction_impulse,normal_impulse\n",
+        );
+        for row in &self.rows {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                row.t,
+                row.entity,
+                row.x,
+                row.y,
+                row.vel_x,
+                row.vel_y,
+                row.angular_vel,
+                row.touching_ground as u8,
+                row.friction_impulse,
+                row.normal_impulse,
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        // JSON has no NaN or infinity
+        let number = |value: f32| {
+            if value.is_finite() {
+                value.to_string()
+            } else {
+                "null".to_string()
+            }
+        };
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                format!(
+                    "{{\"t\":{},\"entity\":{},\"x\":{},\"y\":{},\"vel_x\":{},\"vel_y\":{},\"angular_vel\":{},\"touching_ground\":{},\"friction_impulse\":{},\"normal_impulse\":{}}}",
+                    number(row.t),
+                    row.entity,
+                    number(row.x),
+                    number(row.y),
+                    number(row.vel_x),
+                    number(row.vel_y),
+                    number(row.angular_vel),
+                    row.touching_ground,
+                    number(row.friction_impulse),
+                    number(row.normal_impulse),
+                )
+            })
+            .collect();
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+}
+
+fn trace_enabled(recorder: Res<TraceRecorder>) -> bool {
+    recorder.enabled
+}
+
+fn trace_toggle_system(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    mut recorder: ResMut<TraceRecorder>,
+    traced: Query<(), With<Traced>>,
+    players: Query<Entity, With<Player>>,
+) {
+    if !input.just_pressed(KeyCode::F8) {
+        return;
+    }
+
+    if !recorder.enabled {
+        if traced.is_empty() {
+            for player in &players {
+                commands.entity(player).insert(Traced);
+            }
+        }
+        recorder.start();
+        info!("Tracing");
+        return;
+    }
+
+    recorder.stop();
+    match recorder.format {
+        TraceFormat::Csv => write_debug_file("trace", "csv", &recorder.to_csv()),
+        TraceFormat::Json => write_debug_file("trace", "json", &recorder.to_json()),
+    }
+    if recorder.is_full() {
+        warn!("The trace was cut off at {MAX_TRACE_ROWS} rows");
+    }
+}
+
+fn trace_record_system(
+    time: Res<PhysicsTime>,
+    mut recorder: ResMut<TraceRecorder>,
+    mut collisions: EventReader<CollisionEvent>,
+    // Impacts of this step, kept between runs to reuse the allocation
+    mut impacts: Local<Vec<(Entity, f32)>>,
+    query: Query<(Entity, &Transform, &PhysObj, Option<&Collider>), With<Traced>>,
+) {
+    recorder.t += time.delta;
+    impacts.clear();
+    impacts.extend(
+        collisions
+            .iter()
+            .map(|collision| (collision.entity, collision.impulse)),
+    );
+
+    for (entity, transform, phys_obj, collider) in &query {
+        let (touching_ground, friction_acc) = match collider {
+            Some(&Collider::Ball {
+                touching_ground,
+                friction_acc,
+                ..
+            }) => (touching_ground, friction_acc),
+            None => (false, 0.0),
+        };
+        let normal_impulse = impacts
+            .iter()
+            .filter(|(impacted, _)| *impacted == entity)
+            .map(|(_, impulse)| impulse)
+            .sum();
+        let row = TraceRow {
+            t: recorder.t,
+            entity: entity.index(),
+            x: transform.translation.x,
+            y: transform.translation.y,
+            vel_x: phys_obj.vel.x,
+            vel_y: phys_obj.vel.y,
+            angular_vel: phys_obj.angular_vel,
+            touching_ground,
+            friction_impulse: phys_obj.mass * friction_acc * time.delta,
+            normal_impulse,
+        };
+        recorder.push(row);
+    }
+}
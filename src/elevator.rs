@@ -0,0 +1,359 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::physics::{
+    collision::{resolve_contact, ContactPoint},
+    sleep::Sleeping,
+    Collider, LandedEvent, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsTime,
+};
+
+// Elevators are only solid from above, but something sunk into one with its center no deeper than
+// this is pushed back out on top
+pub const ELEVATOR_THICKNESS: f32 = 16.0;
+// Bodies this close above an elevator are resting on it
+pub const RESTING_SLOP: f32 = 0.5;
+
+// Platforms that sink under weight. They're kinematic like saw blades: moved by their load and
+// never pushed back, and what rests on them is carried along. Only the simulated parts;
+// VisualsPlugin makes the elevators visible.
+pub struct ElevatorPlugin;
+
+impl Plugin for ElevatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LandedEvent>()
+            .add_system(
+                elevator_approach_system
+                    .before(PhysicsSet::IntegrateStart)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_systems(
+                (elevator_motion_system, elevator_contact_system)
+                    .chain()
+                    .after(PhysicsSet::ResolveCollisions)
+                    .before(PhysicsSet::SolveConstraints)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+// A platform `width` wide, with its top at its Transform's height. It sinks at `speed` towards
+// `bottom` while at least `threshold` of mass rests on it, and rises back towards `top` when there
+// isn't.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Elevator {
+    pub top: f32,
+    pub bottom: f32,
+    pub speed: f32,
+    pub width: f32,
+    pub threshold: f32,
+}
+
+impl Elevator {
+    // Which way an elevator at `height` goes with `load` resting on it
+    pub fn motion(&self, height: f32, load: f32) -> ElevatorMotion {
+        if load >= self.threshold {
+            self.towards_bottom(height)
+        } else {
+            self.towards_top(height)
+        }
+    }
+
+    // Which way one of a Counterweight pair at `height` goes with `load_difference` more resting
+    // on it than on the other. The pair stays put unless one side is `threshold` heavier.
+    pub fn linked_motion(&self, height: f32, load_difference: f32) -> ElevatorMotion {
+        if load_difference >= self.threshold {
+            self.towards_bottom(height)
+        } else if load_difference <= -self.threshold {
+            self.towards_top(height)
+        } else {
+            ElevatorMotion::Still
+        }
+    }
+
+    // Where an elevator at `height` is `dt` later. One that's out of its travel, e.g. after a
+    // trigger moved it (see TriggerAction::MovePlatformTo), goes back into it at its speed.
+    pub fn step(&self, height: f32, motion: ElevatorMotion, dt: f32) -> f32 {
+        let next = height + motion.direction() * self.speed * dt;
+        match motion {
+            ElevatorMotion::Descending if height > self.top => next.max(self.top),
+            ElevatorMotion::Rising if height < self.bottom => next.min(self.bottom),
+            _ => next.clamp(self.bottom, self.top),
+        }
+    }
+
+    fn towards_bottom(&self, height: f32) -> ElevatorMotion {
+        if height > self.bottom {
+            ElevatorMotion::Descending
+        } else if height < self.bottom {
+            ElevatorMotion::Rising
+        } else {
+            ElevatorMotion::Still
+        }
+    }
+
+    fn towards_top(&self, height: f32) -> ElevatorMotion {
+        if height < self.top {
+            ElevatorMotion::Rising
+        } else if height > self.top {
+            ElevatorMotion::Descending
+        } else {
+            ElevatorMotion::Still
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ElevatorMotion {
+    #[default]
+    Still,
+    Descending,
+    Rising,
+}
+
+impl ElevatorMotion {
+    fn direction(self) -> f32 {
+        match self {
+            ElevatorMotion::Still => 0.0,
+            ElevatorMotion::Descending => -1.0,
+            ElevatorMotion::Rising => 1.0,
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            ElevatorMotion::Still => ElevatorMotion::Still,
+            ElevatorMotion::Descending => ElevatorMotion::Rising,
+            ElevatorMotion::Rising => ElevatorMotion::Descending,
+        }
+    }
+}
+
+// Links one of a pair of elevators to the other: as one sinks, the other rises. Both are the same
+// Elevator, and their heights always add up to its `top + bottom`.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Counterweight(pub Entity);
+
+// Which way an elevator moved in the last step, and what was resting on it then. Inserted on its
+// first step.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ElevatorState {
+    pub motion: ElevatorMotion,
+    pub vel: f32,
+    // The bodies resting on it
+    pub riders: Vec<Entity>,
+    // Their total mass
+    pub load: f32,
+    // The bodies above it at the start of the step. However fast they fall, they can't pass
+    // through it during the step.
+    pub approaching: Vec<Entity>,
+}
+
+// Spawns an unloaded elevator, at the top
+pub fn spawn_elevator(commands: &mut Commands, x: f32, elevator: Elevator) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(x, elevator.top, -0.5)),
+            elevator,
+        ))
+        .id()
+}
+
+// Spawns a Counterweight pair of elevators, the one at `a` at the top and the one at `b` at the
+// bottom
+pub fn spawn_elevator_pair(
+    commands: &mut Commands,
+    elevator: Elevator,
+    a: f32,
+    b: f32,
+) -> [Entity; 2] {
+    let ends = [commands.spawn_empty().id(), commands.spawn_empty().id()];
+    for (end, x, height, other) in [
+        (ends[0], a, elevator.top, ends[1]),
+        (ends[1], b, elevator.bottom, ends[0]),
+    ] {
+        commands.entity(end).insert((
+            SpatialBundle::from_transform(Transform::from_xyz(x, height, -0.5)),
+            elevator,
+            Counterweight(other),
+        ));
+    }
+    ends
+}
+
+// Moves elevators by the load found on them in the last step. One of a pair moves both, so that
+// their heights keep adding up the same.
+#[allow(clippy::type_complexity)]
+fn elevator_motion_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut moves: Local<Vec<(Entity, f32, ElevatorMotion)>>,
+    mut elevators: Query<(
+        Entity,
+        &Elevator,
+        &mut Transform,
+        Option<&mut ElevatorState>,
+        Option<&Counterweight>,
+    )>,
+) {
+    let dt = time.delta;
+    moves.clear();
+    for (entity, elevator, transform, state, counterweight) in &elevators {
+        let height = transform.translation.y;
+        let load = load_of(state);
+        match counterweight {
+            None => {
+                let motion = elevator.motion(height, load);
+                moves.push((entity, elevator.step(height, motion, dt), motion));
+            }
+            Some(&Counterweight(other)) if entity.to_bits() < other.to_bits() => {
+                let Ok((_, _, _, other_state, _)) = elevators.get(other) else {
+                    continue;
+                };
+                let motion = elevator.linked_motion(height, load - load_of(other_state));
+                let next = elevator.step(height, motion, dt);
+                moves.push((entity, next, motion));
+                moves.push((
+                    other,
+                    elevator.top + elevator.bottom - next,
+                    motion.opposite(),
+                ));
+            }
+            // Moved along with the other one
+            Some(_) => {}
+        }
+    }
+
+    for &(entity, height, motion) in moves.iter() {
+        let Ok((_, _, mut transform, state, _)) = elevators.get_mut(entity) else {
+            continue;
+        };
+        let vel = (height - transform.translation.y) / dt;
+        transform.translation.y = height;
+        match state {
+            Some(mut state) => {
+                state.motion = motion;
+                state.vel = vel;
+            }
+            None => {
+                commands.entity(entity).insert(ElevatorState {
+                    motion,
+                    vel,
+                    ..default()
+                });
+            }
+        }
+    }
+}
+
+fn load_of(state: Option<&ElevatorState>) -> f32 {
+    state.map_or(0.0, |state| state.load)
+}
+
+// Notes what's above each elevator before the step moves it
+fn elevator_approach_system(
+    mut elevators: Query<(&Transform, &Elevator, &mut ElevatorState), Without<PhysObj>>,
+    balls: Query<(Entity, &Transform), With<Collider>>,
+) {
+    for (elevator_transform, elevator, mut state) in &mut elevators {
+        let surface = elevator_transform.translation;
+        state.approaching.clear();
+        for (entity, transform) in &balls {
+            let position = transform.translation;
+            if (position.x - surface.x).abs() <= 0.5 * elevator.width
+                && position.y >= surface.y - ELEVATOR_THICKNESS
+            {
+                state.approaching.push(entity);
+            }
+        }
+    }
+}
+
+// Finds what's resting on each elevator, and carries it along. What lands on one sends a
+// LandedEvent, like landing on the floor does.
+pub(crate) fn elevator_contact_system(
+    config: Res<PhysicsConfig>,
+    time: Res<PhysicsTime>,
+    mut landed: EventWriter<LandedEvent>,
+    mut elevators: Query<(&Transform, &Elevator, &mut ElevatorState), Without<PhysObj>>,
+    mut balls: Query<(
+        Entity,
+        &mut Transform,
+        &mut PhysObj,
+        &mut Collider,
+        Option<&Sleeping>,
+    )>,
+) {
+    for (elevator_transform, elevator, mut state) in &mut elevators {
+        let state = &mut *state;
+        let surface = elevator_transform.translation;
+        let previous = std::mem::take(&mut state.riders);
+        state.load = 0.0;
+        for (entity, mut transform, mut phys_obj, mut collider, sleeping) in &mut balls {
+            let Collider::Ball {
+                radius,
+                kinetic_friction,
+                ..
+            } = *collider;
+            let position = transform.translation;
+            // An elevator that starts sinking drops away faster than what rests on it falls, so
+            // what was riding it and isn't going up is still riding it that far above it
+            let carried = previous.contains(&entity) && phys_obj.vel.y <= 0.0;
+            let reach = if carried {
+                RESTING_SLOP - state.vel.min(0.0) * time.delta
+            } else {
+                RESTING_SLOP
+            };
+            if (position.x - surface.x).abs() > 0.5 * elevator.width
+                || position.y - radius > surface.y + reach
+                || (position.y < surface.y - ELEVATOR_THICKNESS
+                    && !state.approaching.contains(&entity))
+            {
+                continue;
+            }
+            state.riders.push(entity);
+            state.load += phys_obj.mass;
+            if sleeping.is_some() {
+                // Carried along as it wakes up (see wake_system), so that it isn't left behind
+                if state.vel != 0.0 {
+                    transform.translation.y = surface.y + radius;
+                    phys_obj.vel.y = state.vel;
+                }
+                continue;
+            }
+
+            let Collider::Ball {
+                touching_ground, ..
+            } = &mut *collider;
+            *touching_ground = true;
+            if position.y - radius < surface.y || carried {
+                transform.translation.y = surface.y + radius;
+            }
+            let point =
+                ContactPoint::below(radius).with_com_offset(phys_obj.com_arm(transform.rotation));
+            // In the elevator's frame, and only if they're coming together
+            let contact_vel = phys_obj.vel + phys_obj.angular_vel * point.offset.perp();
+            if contact_vel.y - state.vel >= 0.0 {
+                if carried {
+                    phys_obj.vel.y = state.vel;
+                }
+                continue;
+            }
+            if !previous.contains(&entity) {
+                landed.send(LandedEvent {
+                    entity,
+                    impact_speed: state.vel - contact_vel.y,
+                    surface_y: surface.y,
+                });
+            }
+            // Nothing bounces off an elevator, so what lands on one rides it
+            phys_obj.vel.y -= state.vel;
+            resolve_contact(
+                &mut phys_obj,
+                &point,
+                0.0,
+                config.friction(kinetic_friction),
+            );
+            phys_obj.vel.y += state.vel;
+        }
+    }
+}
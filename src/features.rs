@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+// The plugins of the cargo features the game was built with, by name, e.g. "DebugToolsPlugin".
+// Made by FeaturePlugins, so that tests (and CI, which also builds with `--no-default-features`)
+// can check that a feature left out really leaves its plugins out.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ActivePlugins(pub Vec<&'static str>);
+
+impl ActivePlugins {
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains(&name)
+    }
+}
+
+// Adds the plugins behind the debug-tools, inspector, audio and leaderboard features, for those
+// the game was built with, listing each in ActivePlugins. The features' only cfg boundaries in
+// the App are here.
+pub struct FeaturePlugins;
+
+impl Plugin for FeaturePlugins {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePlugins>();
+        #[cfg(feature = "audio")]
+        add_feature_plugin(app, crate::audio::GameAudioPlugin);
+        #[cfg(feature = "debug-tools")]
+        add_feature_plugin(app, crate::debug::DebugToolsPlugin);
+        #[cfg(feature = "inspector")]
+        add_feature_plugin(app, crate::inspector::InspectorPlugin);
+        #[cfg(feature = "leaderboard")]
+        add_feature_plugin(app, crate::leaderboard::LeaderboardPlugin);
+    }
+}
+
+#[cfg(any(
+    feature = "audio",
+    feature = "debug-tools",
+    feature = "inspector",
+    feature = "leaderboard"
+))]
+fn add_feature_plugin<P: Plugin>(app: &mut App, plugin: P) {
+    let name = std::any::type_name::<P>().rsplit("::").next().unwrap();
+    app.world.resource_mut::<ActivePlugins>().0.push(name);
+    app.add_plugin(plugin);
+}
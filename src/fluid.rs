@@ -0,0 +1,190 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blob::{Blob, BlobParticle},
+    hazard::DeathEvent,
+    physics::{
+        sleep::Sleeping, Collider, Gravity, PhysObj, PhysicsSchedule, PhysicsSet, PhysicsTime,
+    },
+    player::{Player, PlayerBall},
+};
+
+// Seconds of touching a hostile fluid that kill the player. Long enough that skimming across a
+// pool fast is survivable.
+pub const LAVA_KILL_TIME: f32 = 1.0;
+// Out of a hostile fluid, the player cools off this many seconds of heat per second
+pub const LAVA_COOLING: f32 = 1.0;
+
+// Pools of liquid. Bodies in one float by how much of them is under the surface, and are slowed
+// down. Hostile ones (lava) burn up anything other than the player on contact, and kill the player
+// after LAVA_KILL_TIME of contact. Only the simulated parts; VisualsPlugin makes the pools visible
+// and glowing.
+pub struct FluidPlugin;
+
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DeathEvent>()
+            .add_event::<BurnedEvent>()
+            .add_systems(
+                (
+                    fluid_force_system.in_set(PhysicsSet::ApplyForces),
+                    lava_system.in_set(PhysicsSet::PostSolve),
+                )
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+// A rectangle of liquid `size` big, centered on its Transform. A ball in it is pushed up by the
+// weight of the liquid it displaces, at `density` (mass per square unit, like a ball's mass over
+// its area), and slowed by `drag` (per second) times how much of it is under.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct FluidVolume {
+    pub size: Vec2,
+    pub density: f32,
+    pub drag: f32,
+    pub hostile: bool,
+}
+
+impl FluidVolume {
+    // Denser than the player, which floats about half under
+    pub const fn water(size: Vec2) -> Self {
+        Self {
+            size,
+            density: 0.01,
+            drag: 6.0,
+            hostile: false,
+        }
+    }
+
+    // Thick and hostile. Not as dense as the player, which sinks through it, but slowly enough that
+    // it can get back out.
+    pub const fn lava(size: Vec2) -> Self {
+        Self {
+            size,
+            density: 0.003,
+            drag: 8.0,
+            hostile: true,
+        }
+    }
+
+    // How much of a ball of `radius` at `position` is in a volume centered on `center`, from 0 to 1
+    pub fn submerged(&self, center: Vec2, position: Vec2, radius: f32) -> f32 {
+        let half = 0.5 * self.size;
+        if (position.x - center.x).abs() > half.x || position.y + radius < center.y - half.y {
+            return 0.0;
+        }
+        submerged_fraction(radius, center.y + half.y - (position.y - radius))
+    }
+}
+
+// The fraction of a circle of `radius` that's below a line `depth` above its bottom
+pub fn submerged_fraction(radius: f32, depth: f32) -> f32 {
+    let h = depth.clamp(0.0, 2.0 * radius);
+    // The circular segment below the line
+    let from_center = radius - h;
+    let area = radius * radius * (from_center / radius).acos()
+        - from_center * (2.0 * radius * h - h * h).max(0.0).sqrt();
+    (area / (PI * radius * radius)).clamp(0.0, 1.0)
+}
+
+// On the player while it's hot from a hostile fluid. Removed once it's cooled off.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Scorching {
+    // Seconds of contact, less what's cooled off since
+    pub heat: f32,
+    // Whether it's touching the fluid now
+    pub touching: bool,
+}
+
+// A body other than the player touched a hostile fluid and was destroyed
+pub struct BurnedEvent {
+    pub position: Vec2,
+}
+
+fn fluid_force_system(
+    volumes: Query<(&Transform, &FluidVolume), Without<PhysObj>>,
+    mut bodies: Query<(&Transform, &mut PhysObj, &Collider, Option<&Gravity>), Without<Sleeping>>,
+) {
+    for (center, volume) in &volumes {
+        let center = center.translation.truncate();
+        for (transform, mut phys_obj, &Collider::Ball { radius, .. }, gravity) in &mut bodies {
+            let submerged = volume.submerged(center, transform.translation.truncate(), radius);
+            if submerged == 0.0 {
+                continue;
+            }
+            if let Some(gravity) = gravity {
+                let displaced = volume.density * submerged * PI * radius * radius;
+                phys_obj.acc.y += displaced * gravity.0 / phys_obj.mass;
+            }
+            let drag = volume.drag * submerged;
+            let (vel, angular_vel) = (phys_obj.vel, phys_obj.angular_vel);
+            phys_obj.acc -= drag * vel;
+            phys_obj.angular_acc -= drag * angular_vel;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn lava_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut burns: EventWriter<BurnedEvent>,
+    volumes: Query<(&Transform, &FluidVolume), Without<PhysObj>>,
+    bodies: Query<
+        (Entity, &Transform, &Collider),
+        (
+            With<PhysObj>,
+            Without<Player>,
+            Without<Blob>,
+            Without<BlobParticle>,
+        ),
+    >,
+    mut players: Query<(Entity, &Transform, &Collider, Option<&mut Scorching>), PlayerBall>,
+) {
+    let touching = |position: Vec2, radius: f32| {
+        volumes.iter().any(|(center, volume)| {
+            volume.hostile
+                && volume.submerged(center.translation.truncate(), position, radius) > 0.0
+        })
+    };
+
+    for (entity, transform, &Collider::Ball { radius, .. }) in &bodies {
+        let position = transform.translation.truncate();
+        if touching(position, radius) {
+            commands.entity(entity).despawn_recursive();
+            burns.send(BurnedEvent { position });
+        }
+    }
+
+    let dt = time.delta;
+    for (player, transform, &Collider::Ball { radius, .. }, scorching) in &mut players {
+        let in_lava = touching(transform.translation.truncate(), radius);
+        let mut state = scorching.as_deref().copied().unwrap_or_default();
+        state.touching = in_lava;
+        if in_lava {
+            let was_alive = state.heat < LAVA_KILL_TIME;
+            state.heat += dt;
+            if was_alive && state.heat >= LAVA_KILL_TIME {
+                deaths.send(DeathEvent { player });
+            }
+        } else {
+            state.heat = (state.heat - LAVA_COOLING * dt).max(0.0);
+        }
+
+        match scorching {
+            Some(_) if state.heat == 0.0 => {
+                commands.entity(player).remove::<Scorching>();
+            }
+            Some(mut scorching) => *scorching = state,
+            None if in_lava => {
+                commands.entity(player).insert(state);
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+
+// F11 toggles fullscreen
+pub struct FullscreenPlugin;
+
+impl Plugin for FullscreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(fullscreen_toggle_system);
+        #[cfg(target_arch = "wasm32")]
+        app.add_system(fullscreen_exit_system.before(fullscreen_toggle_system));
+    }
+}
+
+// The mode F11 switches to from `mode`
+pub fn toggled_mode(mode: WindowMode) -> WindowMode {
+    match mode {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+        _ => WindowMode::Windowed,
+    }
+}
+
+fn fullscreen_toggle_system(
+    input: Res<Input<KeyCode>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !input.just_pressed(KeyCode::F11) {
+        return;
+    }
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.mode = toggled_mode(window.mode);
+    }
+}
+
+// The browser leaves fullscreen by itself (e.g. on Escape) without telling the window, so the mode
+// is brought back in line with the document
+#[cfg(target_arch = "wasm32")]
+fn fullscreen_exit_system(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    if window.mode == WindowMode::Windowed {
+        return;
+    }
+    let fullscreen = web_sys::window()
+        .and_then(|window| window.document())
+        .map_or(false, |document| document.fullscreen_element().is_some());
+    if !fullscreen {
+        window.mode = WindowMode::Windowed;
+    }
+}
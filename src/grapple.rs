@@ -0,0 +1,216 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    debug::lines::DebugLines,
+    level::Floor,
+    physics::{
+        raycast::{raycast, RayHit},
+        PhysObj, PhysicsSchedule, PhysicsSet, PhysicsStep, PhysicsTime,
+    },
+    player::{LocalPlayer, PlayerInput},
+    slingshot::cursor_world_position,
+    state::AppState,
+    trigger::{Door, DOOR_THICKNESS},
+};
+
+const GRAPPLE_COLOR: Color = Color::rgb(0.6, 0.45, 0.25);
+
+// Holding the right mouse button shoots a grapple from the player towards the mouse. If it hits
+// something static (the floor or a closed door), the player hangs from where it hit: it can't get
+// further away than when it hit, but swings freely. Climb up and down reel the line in and out.
+// Letting go of the button lets go of the line, and the player keeps its swing.
+pub struct GrapplePlugin;
+
+impl Plugin for GrapplePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GrappleConfig>()
+            .init_resource::<GrappleAim>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<DebugLines>()
+            .add_systems(
+                (grapple_input_system, grapple_attach_system)
+                    .chain()
+                    .before(PhysicsStep)
+                    .distributive_run_if(in_state(AppState::Playing)),
+            )
+            .add_system(grapple_line_system.after(PhysicsStep))
+            // After the integrator has moved the player, before collisions so they still win
+            .add_system(
+                grapple_constraint_system
+                    .after(PhysicsSet::IntegrateEnd)
+                    .before(PhysicsSet::ResolveCollisions)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct GrappleConfig {
+    // How far the grapple reaches
+    pub max_length: f32,
+    // The line can't be reeled in shorter than this
+    pub min_length: f32,
+    // Reeling in or out, per second
+    pub reel_speed: f32,
+}
+
+impl Default for GrappleConfig {
+    fn default() -> Self {
+        Self {
+            max_length: 600.0,
+            min_length: 40.0,
+            reel_speed: 300.0,
+        }
+    }
+}
+
+// Whether the grapple button is held, and where in the world it's aimed. Filled in from the mouse
+// by grapple_input_system; without a window (e.g. in tests) it's left to whoever sets it.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GrappleAim {
+    pub held: bool,
+    pub target: Option<Vec2>,
+}
+
+// On a player hanging from `anchor`, no further than `length` from it
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Grappled {
+    pub anchor: Vec2,
+    pub length: f32,
+}
+
+// The level's static surfaces as segments for raycasts: the tops of floors, and the sides and tops
+// of closed doors
+pub fn static_segments<'a>(
+    floors: impl IntoIterator<Item = (Entity, &'a Transform, &'a Floor)>,
+    doors: impl IntoIterator<Item = (Entity, &'a Transform, &'a Door)>,
+) -> Vec<(Entity, Vec2, Vec2)> {
+    let mut segments = Vec::new();
+    for (entity, transform, floor) in floors {
+        let center = transform.translation.truncate();
+        let half_width = Vec2::new(0.5 * floor.width, 0.0);
+        segments.push((entity, center - half_width, center + half_width));
+    }
+    for (entity, transform, door) in doors {
+        if door.open {
+            continue;
+        }
+        let base = transform.translation.truncate();
+        let (left, right) = (base.x - 0.5 * DOOR_THICKNESS, base.x + 0.5 * DOOR_THICKNESS);
+        let top = base.y + door.height;
+        segments.extend([
+            (entity, Vec2::new(left, base.y), Vec2::new(left, top)),
+            (entity, Vec2::new(right, base.y), Vec2::new(right, top)),
+            (entity, Vec2::new(left, top), Vec2::new(right, top)),
+        ]);
+    }
+    segments
+}
+
+// Ctrl + right click is left to the SandboxPlugin
+fn grapple_input_system(
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut aim: ResMut<GrappleAim>,
+) {
+    if windows.is_empty() {
+        return;
+    }
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    *aim = GrappleAim {
+        held: buttons.pressed(MouseButton::Right) && !ctrl,
+        target: cursor_world_position(&windows, &cameras),
+    };
+}
+
+// Shoots the grapple when the button's pressed, and lets go when it's released
+fn grapple_attach_system(
+    mut commands: Commands,
+    config: Res<GrappleConfig>,
+    aim: Res<GrappleAim>,
+    mut was_held: Local<bool>,
+    floors: Query<(Entity, &Transform, &Floor)>,
+    doors: Query<(Entity, &Transform, &Door)>,
+    players: Query<(Entity, &Transform, Option<&Grappled>), LocalPlayer>,
+) {
+    let pressed = aim.held && !*was_held;
+    *was_held = aim.held;
+    for (entity, transform, grappled) in &players {
+        if !aim.held {
+            if grappled.is_some() {
+                commands.entity(entity).remove::<Grappled>();
+            }
+            continue;
+        }
+        let Some(target) = aim.target.filter(|_| pressed && grappled.is_none()) else {
+            continue;
+        };
+        let origin = transform.translation.truncate();
+        let Some(direction) = (target - origin).try_normalize() else {
+            continue;
+        };
+        let segments = static_segments(&floors, &doors);
+        if let Some(RayHit {
+            point, distance, ..
+        }) = raycast(origin, direction, config.max_length, segments)
+        {
+            commands.entity(entity).insert(Grappled {
+                anchor: point,
+                length: distance.max(config.min_length),
+            });
+        }
+    }
+}
+
+// Reels the line in or out, then pulls a player that's got further than its length back onto the
+// circle around the anchor. Only the velocity away from the anchor is taken off, so the swing is
+// kept.
+fn grapple_constraint_system(
+    time: Res<PhysicsTime>,
+    config: Res<GrappleConfig>,
+    input: Res<PlayerInput>,
+    mut query: Query<(
+        &mut Transform,
+        &mut PhysObj,
+        &mut Grappled,
+        Option<&PlayerInput>,
+    )>,
+) {
+    for (mut transform, mut phys_obj, mut grappled, own_input) in &mut query {
+        let input = own_input.unwrap_or(&input);
+        let reel = match (input.climb_up, input.climb_down) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        if reel != 0.0 {
+            grappled.length = (grappled.length + reel * config.reel_speed * time.delta)
+                .clamp(config.min_length, config.max_length);
+        }
+
+        let offset = transform.translation.truncate() - grappled.anchor;
+        let distance = offset.length();
+        if distance <= grappled.length {
+            continue;
+        }
+        let outward = offset / distance;
+        let position = grappled.anchor + outward * grappled.length;
+        transform.translation = position.extend(transform.translation.z);
+        let outward_speed = phys_obj.vel.dot(outward);
+        if outward_speed > 0.0 {
+            phys_obj.vel -= outward_speed * outward;
+        }
+    }
+}
+
+fn grapple_line_system(mut lines: ResMut<DebugLines>, query: Query<(&Transform, &Grappled)>) {
+    for (transform, grappled) in &query {
+        lines.line(
+            transform.translation.truncate(),
+            grappled.anchor,
+            GRAPPLE_COLOR,
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::{
+    level::{surface_below, Floor, RestartLevelEvent, SurfaceMaterial},
+    physics::{Collider, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsStep},
+    player::{Player, PlayerBall},
+    progress::Progress,
+};
+
+// Falling this far below the floor, through a gap in it, kills the player
+pub const FALL_DEATH_DEPTH: f32 = 600.0;
+
+// Hazards kill the player: a SurfaceMaterial::Hazard floor does when it's touched, so does falling
+// FALL_DEATH_DEPTH through a gap in the floor, and anything else deadly sends a DeathEvent. A death
+// is counted in the Progress, and the level starts over.
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DeathEvent>()
+            .add_event::<RestartLevelEvent>()
+            .add_systems(
+                (hazard_floor_system, fall_death_system)
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(death_system.after(PhysicsStep));
+    }
+}
+
+// The player was killed by a hazard
+pub struct DeathEvent {
+    pub player: Entity,
+}
+
+fn hazard_floor_system(
+    mut deaths: EventWriter<DeathEvent>,
+    floors: Query<(&Transform, &Floor, Option<&SurfaceMaterial>), Without<PhysObj>>,
+    players: Query<(Entity, &Transform, &Collider), PlayerBall>,
+) {
+    for (
+        player,
+        transform,
+        &Collider::Ball {
+            touching_ground, ..
+        },
+    ) in &players
+    {
+        let position = transform.translation.truncate();
+        let hazard = matches!(
+            surface_below(position, &floors),
+            Some(SurfaceMaterial::Hazard)
+        );
+        if touching_ground && hazard {
+            deaths.send(DeathEvent { player });
+        }
+    }
+}
+
+fn fall_death_system(
+    mut deaths: EventWriter<DeathEvent>,
+    config: Res<PhysicsConfig>,
+    players: Query<(Entity, &Transform), With<Player>>,
+) {
+    for (player, transform) in &players {
+        if transform.translation.y < config.floor_y - FALL_DEATH_DEPTH {
+            deaths.send(DeathEvent { player });
+        }
+    }
+}
+
+// However many steps of the frame the player died in, it's the one death
+fn death_system(
+    mut deaths: EventReader<DeathEvent>,
+    progress: Option<ResMut<Progress>>,
+    mut restarts: EventWriter<RestartLevelEvent>,
+) {
+    if deaths.iter().last().is_none() {
+        return;
+    }
+    if let Some(mut progress) = progress {
+        progress.deaths += 1;
+    }
+    info!("The player died");
+    restarts.send(RestartLevelEvent);
+}
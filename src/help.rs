@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    physics::{Collider, PhysObj},
+    player::LocalPlayer,
+    settings::{InputAction, InputMap, Settings},
+    state::AppState,
+    toast::ToastEvent,
+};
+
+// Standing still this long shows the spin hint
+const STILL_HINT_TIME: f32 = 3.0;
+// Slower than this counts as standing still
+const STILL_SPEED: f32 = 5.0;
+
+// Keys that can't be rebound, shown after the InputMap's
+const FIXED_CONTROLS: [(&str, &str); 3] = [
+    ("Escape", "pause"),
+    ("Backspace", "rewind"),
+    ("G", "blob mode (experimental)"),
+];
+
+// Lists the controls over the game the first time it's played, until the player does something.
+// The controls help key (H by default) brings the list back. Also shows one-time hints when they'd help.
+pub struct HelpPlugin;
+
+impl Plugin for HelpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_event::<ToastEvent>()
+            .add_startup_system(help_setup)
+            .add_systems(
+                (help_toggle_system, help_refresh_system, hint_system)
+                    .distributive_run_if(in_state(AppState::Playing)),
+            )
+            .add_system(help_hide_system.in_schedule(OnExit(AppState::Playing)));
+    }
+}
+
+// Hints shown by themselves when the player seems stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hint {
+    // Standing still on the ground
+    Spin,
+}
+
+impl Hint {
+    pub fn text(self, map: &InputMap) -> String {
+        match self {
+            Hint::Spin => format!(
+                "Hold {} or {} to spin and roll",
+                key_name(map.key(InputAction::SpinLeft)),
+                key_name(map.key(InputAction::SpinRight)),
+            ),
+        }
+    }
+}
+
+// Marks `hint` as shown. Returns false if it already was, so that it's only ever shown once.
+pub fn latch_hint(shown: &mut Vec<Hint>, hint: Hint) -> bool {
+    if shown.contains(&hint) {
+        return false;
+    }
+    shown.push(hint);
+    true
+}
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+// All of an action's keys, main key first
+pub fn key_names(map: &InputMap, action: InputAction) -> String {
+    map.keys(action)
+        .map(key_name)
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+// The controls list, with the keys as they're currently bound
+pub fn controls_text(map: &InputMap) -> String {
+    let mut lines: Vec<String> = InputAction::ALL
+        .into_iter()
+        .map(|action| format!("{}: {}", key_names(map, action), action.label()))
+        .collect();
+    lines.extend(
+        FIXED_CONTROLS
+            .into_iter()
+            .map(|(key, action)| format!("{key}: {action}")),
+    );
+    lines.join("\n")
+}
+
+#[derive(Component)]
+struct ControlsHelp;
+
+#[derive(Component)]
+struct ControlsHelpText;
+
+fn help_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::all(Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ControlsHelp,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        gap: Size::all(Val::Px(12.0)),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel.spawn(TextBundle::from_section("Controls", style.clone()));
+                    panel.spawn((TextBundle::from_section("", style), ControlsHelpText));
+                });
+        });
+}
+
+// The controls help key toggles the help, and playing hides it. It's shown by itself until it's been seen.
+fn help_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut overlays: Query<&mut Visibility, With<ControlsHelp>>,
+) {
+    let Ok(mut visibility) = overlays.get_single_mut() else {
+        return;
+    };
+    let map = &settings.input;
+    // Pausing, muting and the like isn't playing
+    let playing = InputAction::ALL
+        .into_iter()
+        .any(|action| action.moves_player() && map.just_pressed(&keys, action));
+    let shown = *visibility != Visibility::Hidden;
+
+    let show = if map.just_pressed(&keys, InputAction::ControlsHelp) {
+        !shown
+    } else if playing {
+        false
+    } else {
+        shown || !settings.controls_help_seen
+    };
+    if show != shown {
+        *visibility = if show {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !show && shown && !settings.controls_help_seen {
+        settings.controls_help_seen = true;
+    }
+}
+
+// Keeps the list in line with the key bindings
+fn help_refresh_system(
+    settings: Res<Settings>,
+    mut texts: Query<&mut Text, With<ControlsHelpText>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut text in &mut texts {
+        text.sections[0].value = controls_text(&settings.input);
+    }
+}
+
+// Menus cover the game, so the help doesn't stay up behind them
+fn help_hide_system(mut overlays: Query<&mut Visibility, With<ControlsHelp>>) {
+    for mut visibility in &mut overlays {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn hint_system(
+    time: Res<Time>,
+    mut settings: ResMut<Settings>,
+    mut toasts: EventWriter<ToastEvent>,
+    players: Query<(&PhysObj, &Collider), LocalPlayer>,
+    mut still_time: Local<f32>,
+) {
+    let Ok((
+        phys_obj,
+        &Collider::Ball {
+            touching_ground, ..
+        },
+    )) = players.get_single()
+    else {
+        return;
+    };
+    if touching_ground && phys_obj.vel.length() < STILL_SPEED {
+        *still_time += time.delta_seconds();
+    } else {
+        *still_time = 0.0;
+    }
+
+    if *still_time < STILL_HINT_TIME {
+        return;
+    }
+    // Settings are only marked as changed (and saved) when a hint is actually shown
+    if latch_hint(
+        &mut settings.bypass_change_detection().hints_shown,
+        Hint::Spin,
+    ) {
+        settings.set_changed();
+        toasts.send(ToastEvent(Hint::Spin.text(&settings.input)));
+    }
+}
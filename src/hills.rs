@@ -0,0 +1,366 @@
+use std::{f32::consts::TAU, ops::RangeInclusive};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    blob::Blob,
+    launch::LaunchOptions,
+    level::{BallEntry, Level, RestartLevelEvent},
+    physics::{heightfield::Heightfield, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsStep},
+    player::{Player, PlayerBall, PLAYER_RADIUS},
+    progress::Score,
+    replay::fnv1a,
+    rng::{GameRng, Rng},
+    runner::PIXELS_PER_METER,
+    saw::SawBlade,
+    state::AppState,
+    trigger::Coin,
+};
+
+// The ground is made in chunks of this many segments, each this wide
+pub const HILLS_CHUNK_SEGMENTS: usize = 32;
+pub const HILLS_SPACING: f32 = 25.0;
+pub const HILLS_CHUNK_WIDTH: f32 = HILLS_CHUNK_SEGMENTS as f32 * HILLS_SPACING;
+// There's always ground from this far behind the camera to this far ahead of it. Chunks entirely
+// outside that are despawned.
+pub const HILLS_BEHIND: f32 = 1600.0;
+pub const HILLS_AHEAD: f32 = 1600.0;
+pub const HILLS_SAW_RADIUS: f32 = 30.0;
+// The hills are sine waves of these wavelengths and amplitudes added up, with bumps of noise on
+// top, around HILL_BASE above the bottom of the ground and never lower than HILL_MIN
+const HILL_LAYERS: [(f32, f32); 3] = [(2400.0, 70.0), (900.0, 35.0), (350.0, 12.0)];
+const HILL_BASE: f32 = 130.0;
+const HILL_MIN: f32 = 20.0;
+const NOISE_WAVELENGTH: f32 = 200.0;
+const NOISE_AMPLITUDE: f32 = 15.0;
+// Chances of a chunk having a row of coins over it, and a saw half sunk into it. The first chunks
+// ahead of the start never have saws, so that the player can get going.
+const COIN_CHANCE: f32 = 0.7;
+const COIN_ROW: usize = 5;
+const COIN_SPACING: f32 = 40.0;
+const COIN_HEIGHT: f32 = 60.0;
+const SAW_CHANCE: f32 = 0.3;
+const SAW_FREE_CHUNKS: i64 = 1;
+const SAW_ANGULAR_SPEED: f32 = -8.0;
+
+// Endless hills: ground made up ahead of the player as they go, from a seed. The distance got to
+// the right of the start is the score, along with the coins scattered over the hills; saws sunk
+// into them kill the player and start the run over, on the same hills. The camera follows the
+// player.
+//
+// The ground is Heightfield chunks, spawned ahead of the camera and despawned once they're far
+// behind it. Everything on them is worked out from the seed and the chunk's index, so a chunk
+// comes out the same whenever it's made. Replaces the Level, so needs the LevelPlugin to spawn it,
+// and the TriggerPlugin and SawBladePlugin for the coins and saws. Enabled with `--hills`, or
+// `?hills` on WASM.
+pub struct HillsPlugin;
+
+impl Plugin for HillsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>();
+        let terrain = HillsTerrain::new(app.world.resource::<GameRng>().fork("hills").next_u64());
+        app.insert_resource(hills_level())
+            .insert_resource(terrain)
+            .init_resource::<HillsRun>()
+            .init_resource::<Score>()
+            .add_event::<RestartLevelEvent>()
+            .add_system(
+                hills_stream_system
+                    .in_set(PhysicsSet::ApplyImpulses)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(
+                hills_distance_system
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(hills_camera_system.after(PhysicsStep))
+            .add_system(hills_reset_system.run_if(on_event::<RestartLevelEvent>()))
+            .add_system(hills_reset_system.in_schedule(OnEnter(AppState::MainMenu)));
+    }
+}
+
+// The ground, worked out from a seed
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct HillsTerrain {
+    seed: u64,
+    // Of each of the HILL_LAYERS
+    phases: [f32; HILL_LAYERS.len()],
+}
+
+// What's in a chunk, measured from the bottom of its left end
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkLayout {
+    // HILLS_CHUNK_SEGMENTS + 1 of them, the last one the same as the first of the next chunk
+    pub heights: Vec<f32>,
+    pub coins: Vec<Vec2>,
+    pub saws: Vec<Vec2>,
+}
+
+impl HillsTerrain {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng::from_seed(seed);
+        Self {
+            seed,
+            phases: [(); HILL_LAYERS.len()].map(|_| rng.range(0.0..TAU)),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Height of the ground at its `sample`th point, HILLS_SPACING apart going right from the
+    // start. Samples are heights from the sines and random numbers of their own, so chunks meet
+    // wherever they're cut.
+    pub fn height(&self, sample: i64) -> f32 {
+        let x = sample as f32 * HILLS_SPACING;
+        let hills: f32 = HILL_LAYERS
+            .iter()
+            .zip(self.phases)
+            .map(|(&(wavelength, amplitude), phase)| {
+                amplitude * (TAU * x / wavelength + phase).sin()
+            })
+            .sum();
+        (HILL_BASE + hills + NOISE_AMPLITUDE * self.noise(x / NOISE_WAVELENGTH)).max(HILL_MIN)
+    }
+
+    // The chunk `index` chunks right of the start
+    pub fn chunk(&self, index: i64) -> ChunkLayout {
+        let first = index * HILLS_CHUNK_SEGMENTS as i64;
+        let heights = (0..=HILLS_CHUNK_SEGMENTS as i64)
+            .map(|i| self.height(first + i))
+            .collect::<Vec<_>>();
+        let ground = Heightfield {
+            spacing: HILLS_SPACING,
+            heights,
+        };
+        let on_ground = |x: f32, height: f32| Vec2::new(x, ground.height_at(x).unwrap() + height);
+
+        let mut rng = self.rng("scatter", index);
+        let mut coins = Vec::new();
+        if rng.next_f32() < COIN_CHANCE {
+            let start = rng.range(0.0..HILLS_CHUNK_WIDTH - COIN_ROW as f32 * COIN_SPACING);
+            coins.extend(
+                (0..COIN_ROW).map(|i| on_ground(start + i as f32 * COIN_SPACING, COIN_HEIGHT)),
+            );
+        }
+        let mut saws = Vec::new();
+        if rng.next_f32() < SAW_CHANCE && index > SAW_FREE_CHUNKS {
+            let x = rng.range(0.2 * HILLS_CHUNK_WIDTH..0.8 * HILLS_CHUNK_WIDTH);
+            saws.push(on_ground(x, 0.5 * HILLS_SAW_RADIUS));
+        }
+
+        ChunkLayout {
+            heights: ground.heights,
+            coins,
+            saws,
+        }
+    }
+
+    // Smooth noise in [-1, 1], between random values at whole numbers
+    fn noise(&self, x: f32) -> f32 {
+        let cell = x.floor();
+        let t = x - cell;
+        let smooth = t * t * (3.0 - 2.0 * t);
+        let value = |cell: i64| self.rng("noise", cell).signed();
+        let left = value(cell as i64);
+        left + smooth * (value(cell as i64 + 1) - left)
+    }
+
+    // A generator for the `index`th of something, the same whenever it's asked for
+    fn rng(&self, label: &str, index: i64) -> Rng {
+        Rng::from_seed(self.seed ^ fnv1a(label.bytes().chain(index.to_le_bytes())))
+    }
+}
+
+// The run in progress
+#[derive(Resource, Clone, Default, Debug)]
+pub struct HillsRun {
+    // Furthest right of the start the player has got
+    pub distance: f32,
+    // The distance in whole meters, as added to the Score
+    pub meters: u32,
+    // Height of the bottom of the ground. Fixed when the run's first chunk is made, so that the
+    // chunks keep meeting if the floor moves (e.g. the window is resized).
+    pub base_y: Option<f32>,
+    // The coins taken from the chunks that have gone out of reach, by chunk and place in its
+    // ChunkLayout::coins. Those chunks come back without them, so going back and forth past
+    // HILLS_BEHIND doesn't make more coins to take.
+    pub collected: HashMap<i64, Vec<usize>>,
+}
+
+// Which chunk something is part of, counted right from the one at the start: on the chunk's
+// Heightfield, and on the coins and saws that came with it
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HillsChunk(pub i64);
+
+// Which of its chunk's ChunkLayout::coins a coin is
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HillsCoin(pub usize);
+
+// Whether the world's LaunchOptions ask for the hills, with `--hills` on the command line or
+// `hills` in the page's URL query on WASM
+pub fn hills_requested(world: &World) -> bool {
+    world
+        .get_resource::<LaunchOptions>()
+        .is_some_and(|options| options.hills)
+}
+
+// Just the player, dropped onto the hills at the start. The ground comes from the chunks.
+fn hills_level() -> Level {
+    Level {
+        balls: vec![BallEntry {
+            position: Vec2::ZERO,
+            radius: PLAYER_RADIUS,
+            mass: 10.0,
+            coef_of_restitution: 0.3,
+            kinetic_friction: 0.5,
+            com_offset: Vec2::ZERO,
+            player: true,
+        }],
+        ..Level::empty()
+    }
+}
+
+// Indices of the chunks that should be there with the camera at `x`
+pub fn chunks_around(x: f32) -> RangeInclusive<i64> {
+    let index = |x: f32| (x / HILLS_CHUNK_WIDTH).floor() as i64;
+    index(x - HILLS_BEHIND)..=index(x + HILLS_AHEAD)
+}
+
+// Spawns the chunks that have come within reach of the camera (or the player, without one), and
+// despawns the ones that have gone out of it, noting which of their coins were taken
+#[allow(clippy::too_many_arguments)]
+fn hills_stream_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    terrain: Res<HillsTerrain>,
+    mut run: ResMut<HillsRun>,
+    mut present: Local<Vec<i64>>,
+    // The coins still there on each chunk going out of reach
+    mut leaving: Local<HashMap<i64, Vec<usize>>>,
+    cameras: Query<&Transform, With<Camera2d>>,
+    players: Query<&Transform, PlayerBall>,
+    chunks: Query<(
+        Entity,
+        &HillsChunk,
+        Option<&Heightfield>,
+        Option<&HillsCoin>,
+    )>,
+) {
+    let Some(focus) = cameras.iter().chain(&players).next() else {
+        return;
+    };
+    let wanted = chunks_around(focus.translation.x);
+    present.clear();
+    for (entity, &HillsChunk(index), heightfield, coin) in &chunks {
+        if !wanted.contains(&index) {
+            commands.entity(entity).despawn_recursive();
+            let left = leaving.entry(index).or_default();
+            left.extend(coin.map(|coin| coin.0));
+        } else if heightfield.is_some() {
+            present.push(index);
+        }
+    }
+    for (index, left) in leaving.drain() {
+        let count = terrain.chunk(index).coins.len();
+        let collected: Vec<usize> = (0..count).filter(|coin| !left.contains(coin)).collect();
+        if collected.is_empty() {
+            run.collected.remove(&index);
+        } else {
+            run.collected.insert(index, collected);
+        }
+    }
+
+    let base_y = *run.base_y.get_or_insert(config.floor_y);
+    for index in wanted {
+        if !present.contains(&index) {
+            let collected = run.collected.get(&index).map_or(&[][..], Vec::as_slice);
+            spawn_chunk(&mut commands, &terrain, base_y, index, collected);
+        }
+    }
+}
+
+// Leaves out the `collected` coins
+fn spawn_chunk(
+    commands: &mut Commands,
+    terrain: &HillsTerrain,
+    base_y: f32,
+    index: i64,
+    collected: &[usize],
+) {
+    let layout = terrain.chunk(index);
+    let origin = Vec2::new(index as f32 * HILLS_CHUNK_WIDTH, base_y);
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(origin.extend(-1.0))),
+        Heightfield {
+            spacing: HILLS_SPACING,
+            heights: layout.heights,
+        },
+        HillsChunk(index),
+    ));
+    for (i, coin) in layout.coins.into_iter().enumerate() {
+        if collected.contains(&i) {
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                (origin + coin).extend(-0.5),
+            )),
+            Coin,
+            HillsCoin(i),
+            HillsChunk(index),
+        ));
+    }
+    for saw in layout.saws {
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation((origin + saw).extend(-0.5))),
+            SawBlade {
+                radius: HILLS_SAW_RADIUS,
+                angular_speed: SAW_ANGULAR_SPEED,
+                path: None,
+            },
+            HillsChunk(index),
+        ));
+    }
+}
+
+// Each meter further than the player has been before is a point of the Score
+fn hills_distance_system(
+    mut run: ResMut<HillsRun>,
+    mut score: ResMut<Score>,
+    players: Query<&Transform, PlayerBall>,
+) {
+    let Ok(transform) = players.get_single() else {
+        return;
+    };
+    if transform.translation.x <= run.distance {
+        return;
+    }
+    run.distance = transform.translation.x;
+    let meters = (run.distance / PIXELS_PER_METER) as u32;
+    if meters > run.meters {
+        score.0 += meters - run.meters;
+        run.meters = meters;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn hills_camera_system(
+    players: Query<&Transform, (With<Player>, Without<Blob>, Without<Camera2d>)>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+    for mut camera in &mut cameras {
+        camera.translation.x = player.translation.x;
+    }
+}
+
+// The level's restart takes the chunks with the rest of the level, and the next step makes them
+// again, with all their coins
+fn hills_reset_system(mut run: ResMut<HillsRun>) {
+    *run = default();
+}
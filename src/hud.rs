@@ -0,0 +1,235 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    physics::{PhysObj, PhysicsStep},
+    player::{LocalPlayer, SpinHeat, SpinHeatConfig},
+    progress::{LevelTimer, Progress, Score},
+    state::AppState,
+    status::StatusEffects,
+};
+
+const SPIN_METER_SIZE: f32 = 80.0;
+const SPIN_METER_SEGMENTS: usize = 12;
+const SPIN_METER_SEGMENT_SIZE: f32 = 8.0;
+// The full meter is this many times the safe spin. Past the safe spin are the last four segments.
+const SPIN_METER_RANGE: f32 = 1.5;
+const SPIN_METER_OFF: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const SPIN_METER_SAFE: Color = Color::WHITE;
+const SPIN_METER_UNSAFE: Color = Color::RED;
+const SPIN_METER_OVERHEATED: Color = Color::rgb(1.0, 0.3, 0.1);
+
+// The score, time, deaths and the player's status effects along the top of the screen while
+// playing. The stats HUD (F1) has the
+// top-left corner, so these sit on the right. The spin meter is in the bottom-left corner.
+pub struct GameHudPlugin;
+
+impl Plugin for GameHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpinHeatConfig>()
+            .add_startup_system(game_hud_setup)
+            .add_startup_system(spin_meter_setup)
+            .add_systems((game_hud_visibility_system, game_hud_update_system))
+            .add_system(spin_meter_system.after(PhysicsStep));
+    }
+}
+
+#[derive(Component)]
+struct GameHud;
+
+#[derive(Component, Clone, Copy)]
+enum HudField {
+    Score,
+    Time,
+    Deaths,
+    Effects,
+}
+
+// One of the meter's segments, counting round from the lowest spin
+#[derive(Component)]
+struct SpinMeterSegment(usize);
+
+pub fn format_level_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.0).floor();
+    format!("{minutes:.0}:{:04.1}", seconds - minutes * 60.0)
+}
+
+fn game_hud_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::width(Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::FlexEnd,
+                    gap: Size::all(Val::Px(24.0)),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GameHud,
+        ))
+        .with_children(|parent| {
+            for field in [
+                HudField::Score,
+                HudField::Time,
+                HudField::Deaths,
+                HudField::Effects,
+            ] {
+                parent.spawn((TextBundle::from_section("", style.clone()), field));
+            }
+        });
+}
+
+// A ring of segments, lit clockwise from the bottom-left as the player spins faster
+fn spin_meter_setup(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(10.0),
+                        left: Val::Px(10.0),
+                        ..default()
+                    },
+                    size: Size::all(Val::Px(SPIN_METER_SIZE)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GameHud,
+        ))
+        .with_children(|parent| {
+            for index in 0..SPIN_METER_SEGMENTS {
+                let corner = spin_meter_segment_position(index);
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Px(corner.x),
+                                top: Val::Px(corner.y),
+                                ..default()
+                            },
+                            size: Size::all(Val::Px(SPIN_METER_SEGMENT_SIZE)),
+                            ..default()
+                        },
+                        background_color: SPIN_METER_OFF.into(),
+                        ..default()
+                    },
+                    SpinMeterSegment(index),
+                ));
+            }
+        });
+}
+
+// Top-left corner of a segment in the meter. The segments go three quarters of the way round,
+// leaving a gap at the bottom.
+fn spin_meter_segment_position(index: usize) -> Vec2 {
+    let angle = 1.25 * PI - 1.5 * PI * index as f32 / (SPIN_METER_SEGMENTS - 1) as f32;
+    let radius = (SPIN_METER_SIZE - SPIN_METER_SEGMENT_SIZE) / 2.0;
+    // UI y goes down
+    Vec2::splat(radius) + radius * Vec2::new(angle.cos(), -angle.sin())
+}
+
+// Lights the segments up to the player's spin: white while it's safe, red past the safe spin, and
+// all of them while the spinner's overheated
+fn spin_meter_system(
+    config: Res<SpinHeatConfig>,
+    players: Query<(&PhysObj, Option<&SpinHeat>), LocalPlayer>,
+    mut segments: Query<(&SpinMeterSegment, &mut BackgroundColor)>,
+) {
+    let Ok((phys_obj, heat)) = players.get_single() else {
+        return;
+    };
+    let overheated = heat.is_some_and(SpinHeat::overheated);
+    let spin = phys_obj.angular_vel.abs() / config.max_safe_spin;
+    let lit = (spin / SPIN_METER_RANGE * SPIN_METER_SEGMENTS as f32).round() as usize;
+    for (segment, mut background) in &mut segments {
+        // Top of the segment's share of the meter, as a fraction of the safe spin
+        let top = SPIN_METER_RANGE * (segment.0 + 1) as f32 / SPIN_METER_SEGMENTS as f32;
+        let color = if overheated {
+            SPIN_METER_OVERHEATED
+        } else if segment.0 >= lit {
+            SPIN_METER_OFF
+        } else if top > 1.0 {
+            SPIN_METER_UNSAFE
+        } else {
+            SPIN_METER_SAFE
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+// Only shown while playing, not on menus and other screens
+fn game_hud_visibility_system(
+    state: Res<State<AppState>>,
+    mut huds: Query<&mut Visibility, With<GameHud>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for mut visibility in &mut huds {
+        *visibility = if state.0 == AppState::Playing {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// Texts are only touched when what they show changes, as that re-lays out the text
+fn game_hud_update_system(
+    score: Res<Score>,
+    timer: Res<LevelTimer>,
+    progress: Res<Progress>,
+    effects: Query<Ref<StatusEffects>, LocalPlayer>,
+    mut fields: Query<(&mut Text, &HudField)>,
+) {
+    let effects = effects.get_single().ok();
+    for (mut text, field) in &mut fields {
+        let changed = match field {
+            HudField::Score => score.is_changed(),
+            HudField::Time => timer.is_changed(),
+            HudField::Deaths => progress.is_changed(),
+            HudField::Effects => effects.as_ref().is_none_or(|effects| effects.is_changed()),
+        };
+        if !changed && !text.sections[0].value.is_empty() {
+            continue;
+        }
+        let value = match field {
+            HudField::Score => format!("Score {}", score.0),
+            HudField::Time => format!("Time {}", format_level_time(timer.elapsed)),
+            HudField::Deaths => format!("Deaths {}", progress.deaths),
+            // Each with the seconds it has left
+            HudField::Effects => effects
+                .as_ref()
+                .map(|effects| {
+                    effects
+                        .iter()
+                        .map(|(effect, remaining)| {
+                            format!("{} {:.0}", effect.label(), remaining.ceil())
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .unwrap_or_default(),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
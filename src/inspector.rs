@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use crate::{physics::disk_moment_of_inertia, prelude::*};
+
+// Panel for tuning the player's physics live, toggled with F2
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EguiPlugin)
+            .init_resource::<InspectorOpen>()
+            .add_systems(
+                (
+                    inspector_toggle_system,
+                    inspector_system.run_if(inspector_open),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct InspectorOpen(bool);
+
+fn inspector_open(open: Res<InspectorOpen>) -> bool {
+    open.0
+}
+
+fn inspector_toggle_system(input: Res<Input<KeyCode>>, mut open: ResMut<InspectorOpen>) {
+    if input.just_pressed(KeyCode::F2) {
+        open.0 = !open.0;
+    }
+}
+
+// Gives a ball of `radius` a new `mass`, with the moment of inertia of a solid disk to match
+pub fn set_mass(phys_obj: &mut PhysObj, radius: f32, mass: f32) {
+    phys_obj.mass = mass;
+    phys_obj.moment_of_inertia = disk_moment_of_inertia(mass, radius);
+}
+
+// Edits the player's components directly, so changes take effect on the next physics step
+fn inspector_system(
+    mut contexts: EguiContexts,
+    mut query: Query<
+        (
+            &mut Player,
+            &mut PhysObj,
+            &mut Collider,
+            Option<&mut Gravity>,
+        ),
+        Without<RemotePlayer>,
+    >,
+) {
+    let Ok((mut player, mut phys_obj, mut collider, gravity)) = query.get_single_mut() else {
+        return;
+    };
+    let Collider::Ball {
+        radius,
+        coef_of_restitution,
+        touching_ground,
+        kinetic_friction,
+        ..
+    } = &mut *collider;
+
+    egui::Window::new("Physics").show(contexts.ctx_mut(), |ui| {
+        match gravity {
+            Some(mut gravity) => {
+                ui.add(egui::Slider::new(&mut gravity.0, 0.0..=5000.0).text("gravity"));
+            }
+            None => {
+                ui.label("gravity: off");
+            }
+        }
+        ui.add(egui::Slider::new(&mut player.jump_impulse, 0.0..=30_000.0).text("jump impulse"));
+        ui.add(egui::Slider::new(&mut player.torque, 0.0..=1_000_000.0).text("torque"));
+        ui.add(egui::Slider::new(coef_of_restitution, 0.0..=1.0).text("restitution"));
+        ui.add(egui::Slider::new(kinetic_friction, 0.0..=2.0).text("kinetic friction"));
+
+        let mut mass = phys_obj.mass;
+        if ui
+            .add(egui::Slider::new(&mut mass, 0.5..=100.0).text("mass"))
+            .changed()
+        {
+            set_mass(&mut phys_obj, *radius, mass);
+        }
+
+        // Weights one side of the ball
+        let mut com_offset = phys_obj.com_offset.x;
+        if ui
+            .add(
+                egui::Slider::new(&mut com_offset, -0.8 * *radius..=0.8 * *radius)
+                    .text("weight offset"),
+            )
+            .changed()
+        {
+            phys_obj.com_offset.x = com_offset;
+        }
+
+        ui.separator();
+        ui.label(format!(
+            "vel: ({:.1}, {:.1})",
+            phys_obj.vel.x, phys_obj.vel.y
+        ));
+        ui.label(format!("angular vel: {:.2}", phys_obj.angular_vel));
+        ui.label(format!("touching ground: {}", touching_ground));
+    });
+}
@@ -0,0 +1,251 @@
+use std::str::FromStr;
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    boost::BoostPadPlugin,
+    crumble::CrumblingPlugin,
+    elevator::ElevatorPlugin,
+    fluid::FluidPlugin,
+    level::{CurrentLevel, LevelPlugin},
+    physics::{PhysObj, PhysicsStep, SpawnOrder},
+    portal::PortalPlugin,
+    powerup::PowerUpPlugin,
+    replay::ordered_state_hash,
+    rng::GameRng,
+    rope::RopePlugin,
+    saw::SawBladePlugin,
+    state::AppState,
+    status::StatusEffectsPlugin,
+    sticky::StickyPlugin,
+    stress::{StressPlugin, DEFAULT_STRESS_BALLS},
+    testing::test_app,
+    trigger::TriggerPlugin,
+};
+
+// How the game was launched: `--level 2 --stress 500 --headless-seconds 10 --seed 42
+// --debug-overlay --hills` (or `--scenario <file>`) on the command line, or `?level=2&seed=42` in
+// the page's URL on WASM. Flags that other plugins read themselves (`--runner`, `--ldtk`...) are let
+// through; anything else is logged as a warning by LaunchPlugin, rather than stopping the game.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct LaunchOptions {
+    // The CurrentLevel to start at
+    pub level: Option<usize>,
+    // For the GameRng
+    pub seed: Option<u64>,
+    // Balls for the StressPlugin, DEFAULT_STRESS_BALLS if no count's given
+    pub stress: Option<usize>,
+    // Simulated seconds to run for without a window, see headless_app
+    pub headless_seconds: Option<f32>,
+    // A physics scenario to run instead of the game, see scenario.rs
+    pub scenario: Option<String>,
+    // Whether the debug tools start shown
+    pub debug_overlay: bool,
+    // Whether to play the endless hills instead of the levels, see HillsPlugin
+    pub hills: bool,
+    // What couldn't be understood
+    pub warnings: Vec<String>,
+}
+
+impl LaunchOptions {
+    // The command line natively, the page's URL query on WASM
+    pub fn from_env() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Self::parse_args(std::env::args().skip(1));
+        #[cfg(target_arch = "wasm32")]
+        return web_sys::window()
+            .and_then(|window| window.location().search().ok())
+            .map_or_else(Self::default, |query| Self::parse_query(&query));
+    }
+
+    // Command line arguments, without the program's name
+    pub fn parse_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut options = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                options
+                    .warnings
+                    .push(format!("Ignoring the argument {arg:?}"));
+                continue;
+            };
+            match name {
+                // Read by other plugins
+                "runner" | "reset-progress" => {}
+                "host" | "join" => {
+                    args.next();
+                }
+                "ldtk" => {
+                    args.nth(1);
+                }
+                // Only followed by a count if there is one
+                "stress" => {
+                    let count = args.next_if(|value| value.parse::<usize>().is_ok());
+                    options.set(name, count.as_deref());
+                }
+                "level" | "seed" | "headless-seconds" | "scenario" => {
+                    let value = args.next();
+                    options.set(name, value.as_deref());
+                }
+                _ => options.set(name, None),
+            }
+        }
+        options
+    }
+
+    // A URL query string like "?level=2&seed=42", with the same names as the flags
+    pub fn parse_query(query: &str) -> Self {
+        let mut options = Self::default();
+        for pair in query.trim_start_matches('?').split('&') {
+            let (name, value) = match pair.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (pair, None),
+            };
+            match name {
+                "" | "runner" => {}
+                _ => options.set(name, value),
+            }
+        }
+        options
+    }
+
+    fn set(&mut self, name: &str, value: Option<&str>) {
+        match name {
+            "level" => self.level = self.number(name, value),
+            "seed" => self.seed = self.number(name, value),
+            "stress" => {
+                let count = value.and_then(|count| count.parse().ok());
+                self.stress = Some(count.unwrap_or(DEFAULT_STRESS_BALLS));
+            }
+            "headless-seconds" => match self.number::<f32>(name, value) {
+                Some(seconds) if !(seconds.is_finite() && seconds > 0.0) => {
+                    self.warnings
+                        .push(format!("Ignoring {name} {seconds}, it isn't positive"));
+                }
+                seconds => self.headless_seconds = seconds,
+            },
+            "scenario" => match value {
+                Some(path) => self.scenario = Some(path.to_string()),
+                None => self
+                    .warnings
+                    .push(format!("Ignoring {name}, it needs a file")),
+            },
+            "debug-overlay" => self.debug_overlay = true,
+            "hills" => self.hills = true,
+            _ => self
+                .warnings
+                .push(format!("Ignoring the unknown option {name:?}")),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, name: &str, value: Option<&str>) -> Option<T> {
+        let number = value.and_then(|value| value.parse().ok());
+        if number.is_none() {
+            self.warnings
+                .push(format!("Ignoring {name}, {value:?} isn't a number"));
+        }
+        number
+    }
+}
+
+// Applies the LaunchOptions resource to the game as it starts
+pub struct LaunchPlugin;
+
+impl Plugin for LaunchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LaunchOptions>()
+            .init_resource::<GameRng>()
+            .init_resource::<CurrentLevel>()
+            .add_startup_system(launch_setup);
+        #[cfg(feature = "debug-tools")]
+        app.add_startup_system(launch_debug_setup);
+    }
+}
+
+fn launch_setup(options: Res<LaunchOptions>, mut level: ResMut<CurrentLevel>) {
+    for warning in &options.warnings {
+        warn!("{warning}");
+    }
+    if let Some(number) = options.level {
+        level.0 = number;
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+fn launch_debug_setup(
+    options: Res<LaunchOptions>,
+    overlay: Option<ResMut<crate::debug::overlay::PhysicsDebug>>,
+    hud: Option<ResMut<crate::debug::hud::StatsHud>>,
+) {
+    if !options.debug_overlay {
+        return;
+    }
+    if let Some(mut overlay) = overlay {
+        overlay.enabled = true;
+    }
+    if let Some(mut hud) = hud {
+        hud.visible = true;
+    }
+}
+
+// How far a headless run has got, and the state hash it finished with
+#[derive(Resource)]
+pub struct HeadlessRun {
+    pub seconds: f32,
+    pub elapsed: f32,
+    pub hash: Option<u64>,
+}
+
+// `--headless-seconds N`: the level simulated for N seconds, without a window and as fast as it
+// goes, like `test_app`. Then the ordered_state_hash of every body is printed and the app exits,
+// so scripts can compare runs, including between machines.
+pub fn headless_app(options: &LaunchOptions, seconds: f32) -> App {
+    let mut app = test_app();
+    app.insert_resource(options.clone())
+        .insert_resource(HeadlessRun {
+            seconds,
+            elapsed: 0.0,
+            hash: None,
+        })
+        .add_plugin(LaunchPlugin)
+        .add_plugin(LevelPlugin)
+        .add_plugin(PowerUpPlugin)
+        .add_plugin(StickyPlugin)
+        .add_plugin(StatusEffectsPlugin)
+        .add_plugin(PortalPlugin)
+        .add_plugin(SawBladePlugin)
+        .add_plugin(CrumblingPlugin)
+        .add_plugin(BoostPadPlugin)
+        .add_plugin(ElevatorPlugin)
+        .add_plugin(RopePlugin)
+        .add_plugin(FluidPlugin)
+        .add_plugin(TriggerPlugin)
+        .add_system(
+            headless_run_system
+                .after(PhysicsStep)
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+    if let Some(count) = options.stress {
+        app.add_plugin(StressPlugin { count });
+    }
+    app
+}
+
+fn headless_run_system(
+    time: Res<Time>,
+    mut run: ResMut<HeadlessRun>,
+    mut exit: EventWriter<AppExit>,
+    bodies: Query<(&SpawnOrder, &Transform, &PhysObj)>,
+) {
+    if run.hash.is_some() {
+        return;
+    }
+    run.elapsed += time.delta_seconds();
+    if run.elapsed < run.seconds {
+        return;
+    }
+    let hash = ordered_state_hash(&bodies);
+    println!("State hash after {:.2}s: {hash:016x}", run.elapsed);
+    run.hash = Some(hash);
+    exit.send(AppExit);
+}
@@ -0,0 +1,326 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    crumble::Crumbling,
+    fluid::FluidVolume,
+    level::{
+        BallEntry, CrumblingEntry, FloorEntry, FluidEntry, Level, PickupEntry, RopeEntry, SawEntry,
+        SurfaceMaterial,
+    },
+    player::PLAYER_RADIUS,
+    powerup::SizeChange,
+    rope::RopeAnchor,
+    saw::SawBlade,
+};
+
+// What the values of an IntGrid layer are made of. 0 is empty.
+pub const TILE_NORMAL: i64 = 1;
+pub const TILE_HAZARD: i64 = 2;
+pub const TILE_STICKY: i64 = 3;
+pub const TILE_CRUMBLING: i64 = 4;
+// Ropes from Rope entities have segments this long, as many as fit in the entity's height
+const ROPE_SEGMENT_LENGTH: f32 = 25.0;
+const SAW_ANGULAR_SPEED: f32 = -12.0;
+
+// Levels drawn in the LDtk editor (https://ldtk.io), read from the project's JSON. The bottom row
+// of the IntGrid layers is the floor, with each stretch of the same value a floor of its material
+// (see TILE_NORMAL and the others), and the entities become the level's other entries by their
+// identifier:
+// - Player: the player's ball, as in the default level
+// - Goal: the goal, at its x
+// - Saw: a saw blade as wide as the entity, spinning at its `angular_speed` field if it has one
+// - Pickup: a size pickup, as in the default level
+// - Rope: a rope hanging from the top of the entity, as long as it's tall
+// - Water and Lava: a pool filling the entity
+// Tiles above the floor and entities the game doesn't have are skipped with a warning, as the
+// floor is the only solid surface there is.
+//
+// LDtk's y goes down from the top of the level. The top of the first IntGrid layer's bottom row
+// ends up at PhysicsConfig::floor_y (`floor_y`), and x stays as it is.
+pub fn import_ldtk(json: &str, level: &str, floor_y: f32) -> Result<Level, LdtkError> {
+    let project: LdtkProject = serde_json::from_str(json).map_err(LdtkError::Json)?;
+    let source = project
+        .levels
+        .iter()
+        .find(|source| source.identifier == level)
+        .ok_or_else(|| LdtkError::MissingLevel(level.to_string()))?;
+    let mut level = Level::empty();
+    // The top of the bottom row of tiles is the floor, in LDtk's pixels
+    let surface = source
+        .layers
+        .iter()
+        .find(|layer| layer.kind == "IntGrid")
+        .map_or(source.height, |layer| {
+            layer.offset_y + (layer.rows as i64 - 1) * layer.grid_size as i64
+        });
+    let to_world = |x: f32, y: f32| Vec2::new(x, floor_y + surface as f32 - y);
+
+    for layer in &source.layers {
+        match layer.kind.as_str() {
+            "IntGrid" => {
+                let offset = Vec2::new(layer.offset_x as f32, layer.offset_y as f32);
+                let grid_size = layer.grid_size as f32;
+                for rect in merge_tiles(&layer.int_grid, layer.columns as usize) {
+                    let left = offset.x + rect.x as f32 * grid_size;
+                    let width = rect.width as f32 * grid_size;
+                    let top = offset.y + rect.y as f32 * grid_size;
+                    if rect.y + rect.height != layer.rows {
+                        warn!(
+                            "Skipping tiles at ({left}, {top}) of {}: only the floor is solid",
+                            layer.identifier,
+                        );
+                        continue;
+                    }
+                    let x = left + 0.5 * width;
+                    let material = match rect.value {
+                        TILE_NORMAL => SurfaceMaterial::Normal,
+                        TILE_HAZARD => SurfaceMaterial::Hazard,
+                        TILE_STICKY => SurfaceMaterial::Sticky { strength: 60_000.0 },
+                        TILE_CRUMBLING => {
+                            level.crumbling.push(CrumblingEntry {
+                                x,
+                                width,
+                                crumbling: Crumbling {
+                                    delay: 0.4,
+                                    respawn: Some(3.0),
+                                },
+                            });
+                            continue;
+                        }
+                        value => {
+                            warn!("Skipping tiles of unknown value {value} at ({left}, {top})");
+                            continue;
+                        }
+                    };
+                    level.floors.push(FloorEntry { x, width, material });
+                }
+            }
+            "Entities" => {
+                for entity in &layer.entities {
+                    let size = Vec2::new(entity.width as f32, entity.height as f32);
+                    // `px` is where the entity's pivot is, from its top left
+                    let pivot = Vec2::from(entity.pivot);
+                    let center = Vec2::new(
+                        layer.offset_x as f32 + entity.px[0] as f32 + (0.5 - pivot.x) * size.x,
+                        layer.offset_y as f32 + entity.px[1] as f32 + (0.5 - pivot.y) * size.y,
+                    );
+                    let position = to_world(center.x, center.y);
+                    add_entity(&mut level, entity, position, size);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(level)
+}
+
+fn add_entity(level: &mut Level, entity: &LdtkEntity, position: Vec2, size: Vec2) {
+    match entity.identifier.as_str() {
+        "Player" => level.balls.push(BallEntry {
+            position,
+            radius: PLAYER_RADIUS,
+            mass: 10.0,
+            coef_of_restitution: 0.3,
+            kinetic_friction: 0.5,
+            com_offset: Vec2::ZERO,
+            player: true,
+        }),
+        "Goal" => level.goal = Some(position.x),
+        "Saw" => level.saws.push(SawEntry {
+            position,
+            blade: SawBlade {
+                radius: 0.5 * size.x,
+                angular_speed: entity
+                    .float_field("angular_speed")
+                    .unwrap_or(SAW_ANGULAR_SPEED),
+                path: None,
+            },
+        }),
+        "Pickup" => level.pickups.push(PickupEntry {
+            position,
+            change: SizeChange {
+                target_radius: 2.0 * PLAYER_RADIUS,
+                duration: 8.0,
+                scale_jump_impulse: true,
+            },
+        }),
+        "Rope" => level.ropes.push(RopeEntry {
+            position: position + Vec2::Y * 0.5 * size.y,
+            anchor: RopeAnchor {
+                segments: (size.y / ROPE_SEGMENT_LENGTH).round().max(1.0) as u32,
+                segment_length: ROPE_SEGMENT_LENGTH,
+            },
+        }),
+        "Water" => level.fluids.push(FluidEntry {
+            position,
+            fluid: FluidVolume::water(size),
+        }),
+        "Lava" => level.fluids.push(FluidEntry {
+            position,
+            fluid: FluidVolume::lava(size),
+        }),
+        identifier => warn!("Skipping {identifier} at {position}: the game doesn't have those"),
+    }
+}
+
+// A rectangle of tiles of the same value, in cells from the top left
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub value: i64,
+}
+
+// The non-empty `cells` of a grid `columns` wide, row by row, merged into rectangles: each row's
+// runs of the same value, extended down by runs just like them on the rows below
+pub fn merge_tiles(cells: &[i64], columns: usize) -> Vec<TileRect> {
+    let mut rects: Vec<TileRect> = Vec::new();
+    // The rectangles that reach down to the last row
+    let mut open = Vec::new();
+    for (row, cells) in cells.chunks(columns.max(1)).enumerate() {
+        let mut still_open = Vec::new();
+        let mut column = 0;
+        while column < cells.len() {
+            let (start, value) = (column, cells[column]);
+            while column < cells.len() && cells[column] == value {
+                column += 1;
+            }
+            if value == 0 {
+                continue;
+            }
+            let (x, width) = (start as u32, (column - start) as u32);
+            let above = open.iter().copied().find(|&i: &usize| {
+                let rect = &rects[i];
+                rect.x == x && rect.width == width && rect.value == value
+            });
+            match above {
+                Some(i) => {
+                    rects[i].height += 1;
+                    still_open.push(i);
+                }
+                None => {
+                    still_open.push(rects.len());
+                    rects.push(TileRect {
+                        x,
+                        y: row as u32,
+                        width,
+                        height: 1,
+                        value,
+                    });
+                }
+            }
+        }
+        open = still_open;
+    }
+    rects
+}
+
+#[derive(Debug)]
+pub enum LdtkError {
+    Json(serde_json::Error),
+    MissingLevel(String),
+}
+
+impl fmt::Display for LdtkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LdtkError::Json(error) => write!(f, "not an LDtk project: {error}"),
+            LdtkError::MissingLevel(level) => write!(f, "there's no level {level}"),
+        }
+    }
+}
+
+impl std::error::Error for LdtkError {}
+
+// `--ldtk <project> <level>` on the command line, imported. Errors are logged, and leave the
+// default level.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn ldtk_level_from_args(floor_y: f32) -> Option<Level> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--ldtk").skip(1);
+    let (path, level) = (args.next()?, args.next()?);
+    let imported = std::fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| import_ldtk(&json, &level, floor_y).map_err(|error| error.to_string()));
+    match imported {
+        Ok(level) => Some(level),
+        Err(error) => {
+            error!("Couldn't import level {level} of {path}: {error}");
+            None
+        }
+    }
+}
+
+// The parts of LDtk's JSON the import uses
+#[derive(Deserialize)]
+struct LdtkProject {
+    levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize)]
+struct LdtkLevel {
+    identifier: String,
+    #[serde(rename = "pxHei")]
+    height: i64,
+    #[serde(rename = "layerInstances", default)]
+    layers: Vec<LdtkLayer>,
+}
+
+#[derive(Deserialize)]
+struct LdtkLayer {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__type")]
+    kind: String,
+    #[serde(rename = "__cWid")]
+    columns: u32,
+    #[serde(rename = "__cHei")]
+    rows: u32,
+    #[serde(rename = "__gridSize")]
+    grid_size: u32,
+    #[serde(rename = "__pxTotalOffsetX", default)]
+    offset_x: i64,
+    #[serde(rename = "__pxTotalOffsetY", default)]
+    offset_y: i64,
+    #[serde(rename = "intGridCsv", default)]
+    int_grid: Vec<i64>,
+    #[serde(rename = "entityInstances", default)]
+    entities: Vec<LdtkEntity>,
+}
+
+#[derive(Deserialize)]
+struct LdtkEntity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    px: [i64; 2],
+    width: i64,
+    height: i64,
+    #[serde(rename = "fieldInstances", default)]
+    fields: Vec<LdtkField>,
+}
+
+impl LdtkEntity {
+    fn float_field(&self, name: &str) -> Option<f32> {
+        self.fields
+            .iter()
+            .find(|field| field.identifier == name)?
+            .value
+            .as_f64()
+            .map(|value| value as f32)
+    }
+}
+
+#[derive(Deserialize)]
+struct LdtkField {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: Value,
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// Most times the top times show
+pub const TOP_COUNT: usize = 10;
+#[cfg(not(target_arch = "wasm32"))]
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// A completed level, as POSTed to `<endpoint>/times`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Submission {
+    pub level: usize,
+    // Seconds
+    pub time: f32,
+    // Replay::hash of the run in hex, if it was recorded
+    pub replay_hash: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub time: f32,
+}
+
+// What `<endpoint>/times/<level>?limit=<count>` answers, fastest first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopTimes {
+    pub level: usize,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug)]
+pub enum LeaderboardError {
+    // Couldn't reach the endpoint, e.g. while offline
+    Request(String),
+    // The endpoint answered with something other than success
+    Status(u16),
+    // The answer wasn't the JSON it should be
+    Response(String),
+}
+
+impl fmt::Display for LeaderboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeaderboardError::Request(error) => {
+                write!(f, "couldn't reach the leaderboard: {error}")
+            }
+            LeaderboardError::Status(status) => write!(f, "the leaderboard answered {status}"),
+            LeaderboardError::Response(error) => write!(f, "unexpected answer: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LeaderboardError {}
+
+fn times_url(endpoint: &str) -> String {
+    format!("{}/times", endpoint.trim_end_matches('/'))
+}
+
+fn top_url(endpoint: &str, level: usize) -> String {
+    format!("{}/{level}?limit={TOP_COUNT}", times_url(endpoint))
+}
+
+// Natively, these block the task pool thread they're run on (never the frame), so the futures
+// are ready as soon as they're polled
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn submit(endpoint: &str, submission: &Submission) -> Result<(), LeaderboardError> {
+    let response = http_client()?
+        .post(times_url(endpoint))
+        .json(submission)
+        .send()
+        .map_err(|error| LeaderboardError::Request(error.to_string()))?;
+    check_status(response.status().as_u16())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn fetch_top(endpoint: &str, level: usize) -> Result<TopTimes, LeaderboardError> {
+    let response = http_client()?
+        .get(top_url(endpoint, level))
+        .send()
+        .map_err(|error| LeaderboardError::Request(error.to_string()))?;
+    check_status(response.status().as_u16())?;
+    response
+        .json()
+        .map_err(|error| LeaderboardError::Response(error.to_string()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn http_client() -> Result<reqwest::blocking::Client, LeaderboardError> {
+    reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|error| LeaderboardError::Request(error.to_string()))
+}
+
+// On WASM, the browser's fetch
+#[cfg(target_arch = "wasm32")]
+pub async fn submit(endpoint: &str, submission: &Submission) -> Result<(), LeaderboardError> {
+    let response = gloo_net::http::Request::post(&times_url(endpoint))
+        .json(submission)
+        .map_err(|error| LeaderboardError::Request(error.to_string()))?
+        .send()
+        .await
+        .map_err(|error| LeaderboardError::Request(error.to_string()))?;
+    check_status(response.status())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn fetch_top(endpoint: &str, level: usize) -> Result<TopTimes, LeaderboardError> {
+    let response = gloo_net::http::Request::get(&top_url(endpoint, level))
+        .send()
+        .await
+        .map_err(|error| LeaderboardError::Request(error.to_string()))?;
+    check_status(response.status())?;
+    response
+        .json()
+        .await
+        .map_err(|error| LeaderboardError::Response(error.to_string()))
+}
+
+fn check_status(status: u16) -> Result<(), LeaderboardError> {
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        Err(LeaderboardError::Status(status))
+    }
+}
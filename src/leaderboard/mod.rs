@@ -0,0 +1,224 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+
+use crate::{
+    best_run::{best_run_save_system, BestRunRecorder},
+    hud::format_level_time,
+    level::CurrentLevel,
+    progress::{level_complete_system, LevelStats},
+    settings::Settings,
+    state::AppState,
+};
+
+pub mod client;
+
+pub use client::{LeaderboardEntry, LeaderboardError, Submission, TopTimes};
+
+// Frames of the spinner shown while loading, a quarter of a second each
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const SPINNER_FRAME_TIME: f32 = 0.25;
+
+// Submits each completed level's time to an online leaderboard, and shows the level's top times
+// on the level complete screen. Only once the player's opted in (Settings::leaderboard_opt_in)
+// and there's an endpoint (Settings::leaderboard_url). Requests run on the IO task pool, so the
+// frame never waits on the network; without one, the screen says the leaderboard's offline.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Leaderboard>()
+            .init_resource::<LevelStats>()
+            .add_system(
+                leaderboard_submit_system
+                    .after(level_complete_system)
+                    .before(best_run_save_system)
+                    .in_schedule(OnEnter(AppState::LevelComplete)),
+            )
+            .add_system(leaderboard_panel_setup.in_schedule(OnEnter(AppState::LevelComplete)))
+            .add_system(leaderboard_panel_cleanup.in_schedule(OnExit(AppState::LevelComplete)))
+            .add_system(leaderboard_poll_system)
+            .add_system(
+                leaderboard_panel_system
+                    .after(leaderboard_poll_system)
+                    .run_if(in_state(AppState::LevelComplete)),
+            );
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum LeaderboardStatus {
+    // Nothing was requested, e.g. without opting in
+    #[default]
+    Idle,
+    Loading,
+    Loaded(Vec<LeaderboardEntry>),
+    Offline(String),
+}
+
+// Where a request's task leaves what it fetched
+type ResultSlot = Arc<Mutex<Option<Result<TopTimes, LeaderboardError>>>>;
+
+// The last request's progress. Its result is put in `pending` by the task, on whichever thread
+// it runs, and picked up by leaderboard_poll_system.
+#[derive(Resource, Default)]
+pub struct Leaderboard {
+    pub status: LeaderboardStatus,
+    pending: Option<ResultSlot>,
+}
+
+impl Leaderboard {
+    // Submits `submission` to `endpoint` and fetches the level's top times after it, so they
+    // include it. A failed submission still shows the top times.
+    pub fn request(&mut self, endpoint: &str, submission: Submission) {
+        let endpoint = endpoint.to_string();
+        let slot = Arc::new(Mutex::new(None));
+        let result = slot.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                if let Err(error) = client::submit(&endpoint, &submission).await {
+                    warn!(
+                        "Failed to submit the time of level {}: {error}",
+                        submission.level
+                    );
+                }
+                let top = client::fetch_top(&endpoint, submission.level).await;
+                *result.lock().unwrap() = Some(top);
+            })
+            .detach();
+        self.pending = Some(slot);
+        self.status = LeaderboardStatus::Loading;
+    }
+}
+
+fn leaderboard_submit_system(
+    settings: Res<Settings>,
+    level: Res<CurrentLevel>,
+    stats: Res<LevelStats>,
+    recorder: Option<Res<BestRunRecorder>>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if !settings.leaderboard_opt_in || settings.leaderboard_url.is_empty() {
+        leaderboard.status = LeaderboardStatus::Idle;
+        return;
+    }
+    let replay_hash = recorder
+        .and_then(|recorder| recorder.attempt.as_ref().map(|attempt| attempt.hash()))
+        .map(|hash| format!("{hash:016x}"));
+    leaderboard.request(
+        &settings.leaderboard_url,
+        Submission {
+            level: level.0,
+            time: stats.time,
+            replay_hash,
+        },
+    );
+}
+
+// Picks up the result of the request once its task has finished
+pub fn leaderboard_poll_system(mut leaderboard: ResMut<Leaderboard>) {
+    let Some(result) = leaderboard
+        .pending
+        .as_ref()
+        .and_then(|slot| slot.lock().unwrap().take())
+    else {
+        return;
+    };
+    leaderboard.pending = None;
+    leaderboard.status = match result {
+        Ok(top) => LeaderboardStatus::Loaded(top.entries),
+        Err(error) => {
+            warn!("Leaderboard unavailable: {error}");
+            LeaderboardStatus::Offline(error.to_string())
+        }
+    };
+}
+
+// The panel's lines for `status`, `elapsed` seconds after it started loading
+pub fn leaderboard_lines(status: &LeaderboardStatus, elapsed: f32) -> Vec<String> {
+    match status {
+        LeaderboardStatus::Idle => Vec::new(),
+        LeaderboardStatus::Loading => {
+            let frame = (elapsed / SPINNER_FRAME_TIME) as usize % SPINNER.len();
+            vec![format!("Top times {}", SPINNER[frame])]
+        }
+        LeaderboardStatus::Loaded(entries) if entries.is_empty() => {
+            vec!["Top times".to_string(), "No times yet".to_string()]
+        }
+        LeaderboardStatus::Loaded(entries) => {
+            let mut lines = vec!["Top times".to_string()];
+            lines.extend(
+                entries
+                    .iter()
+                    .take(client::TOP_COUNT)
+                    .enumerate()
+                    .map(|(i, entry)| {
+                        format!(
+                            "{:>2}. {:<12} {}",
+                            i + 1,
+                            entry.name,
+                            format_level_time(entry.time)
+                        )
+                    }),
+            );
+            lines
+        }
+        LeaderboardStatus::Offline(_) => {
+            vec!["Top times".to_string(), "Offline".to_string()]
+        }
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardPanel {
+    opened: f32,
+}
+
+fn leaderboard_panel_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(30.0),
+                top: Val::Px(30.0),
+                ..default()
+            },
+            ..default()
+        }),
+        LeaderboardPanel {
+            opened: time.elapsed_seconds(),
+        },
+    ));
+}
+
+fn leaderboard_panel_cleanup(mut commands: Commands, query: Query<Entity, With<LeaderboardPanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn leaderboard_panel_system(
+    time: Res<Time>,
+    leaderboard: Res<Leaderboard>,
+    mut query: Query<(&LeaderboardPanel, &mut Text)>,
+) {
+    for (panel, mut text) in &mut query {
+        let lines = leaderboard_lines(&leaderboard.status, time.elapsed_seconds() - panel.opened);
+        let value = lines.join("\n");
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
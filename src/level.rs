@@ -0,0 +1,1156 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    boost::BoostPad,
+    crumble::{Crumbling, CRUMBLING_TILE_WIDTH},
+    elevator::{spawn_elevator, spawn_elevator_pair, Elevator},
+    fluid::FluidVolume,
+    hills::HillsChunk,
+    physics::{
+        disk_moment_of_inertia,
+        joints::{DistanceJoint, RevoluteJoint},
+        sleep::StaticBody,
+        Collider, Gravity, PhysObj, PhysicsConfig, PhysicsStep,
+    },
+    player::{Dash, Player, PLAYER_RADIUS},
+    portal::{spawn_portal_pair, Portal},
+    powerup::{SizeChange, SizePickup},
+    rope::{spawn_rope, RopeAnchor},
+    runner::Obstacle,
+    saw::SawBlade,
+    settings::{InputAction, Settings},
+    shapes::FidgetSpinner,
+    state::AppState,
+    status::{StatusEffect, StatusSensor},
+    trigger::{Coin, Door, TriggerEntry, TriggerZone},
+};
+
+const FLOOR_WIDTH: f32 = 10_000.0;
+const GOAL_X: f32 = 2000.0;
+const PICKUP_X: f32 = 800.0;
+const STICKY_X: f32 = 1400.0;
+const STICKY_WIDTH: f32 = 200.0;
+const BOOST_X: f32 = 1100.0;
+const SAW_X: f32 = 1700.0;
+const CRUMBLING_X: f32 = 500.0;
+const CRUMBLING_WIDTH: f32 = 160.0;
+const ROPE_X: f32 = 300.0;
+// Far enough apart that each pad's boost lands on the other
+const BOOST_PAD_LEFT_X: f32 = -800.0;
+const BOOST_PAD_RIGHT_X: f32 = -400.0;
+const BOOST_PAD_STRENGTH: f32 = 9000.0;
+// A trampoline next to a platform too high to jump onto
+const TRAMPOLINE_X: f32 = -1500.0;
+const TRAMPOLINE_WIDTH: f32 = 100.0;
+const PLATFORM_X: f32 = -1300.0;
+const ICE_X: f32 = 950.0;
+const ICE_WIDTH: f32 = 200.0;
+
+// Spawns the Level when play starts, and despawns it on the main menu. Only the simulated parts;
+// VisualsPlugin makes them visible.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Level>()
+            .init_resource::<CurrentLevel>()
+            .init_resource::<Settings>()
+            .add_event::<RestartLevelEvent>()
+            .add_system(level_spawn_system.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(level_despawn_system.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_system(
+                respawn_key_system
+                    .before(level_restart_system)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(level_restart_system.before(PhysicsStep))
+            .add_system(
+                goal_system
+                    .after(PhysicsStep)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(floor_height_system);
+    }
+}
+
+// Puts the level back the way it started
+pub struct RestartLevelEvent;
+
+// Everything a level is made of, including bodies that were replaced by loading a save
+type LevelEntities = Or<(
+    With<Floor>,
+    With<Goal>,
+    With<SizePickup>,
+    With<StatusSensor>,
+    With<Portal>,
+    With<SawBlade>,
+    With<Elevator>,
+    With<RopeAnchor>,
+    With<FluidVolume>,
+    With<Obstacle>,
+    With<PhysObj>,
+    With<DistanceJoint>,
+    With<RevoluteJoint>,
+    // Or only takes so many at once
+    Or<(
+        With<Coin>,
+        With<Door>,
+        With<TriggerZone>,
+        With<BoostPad>,
+        With<HillsChunk>,
+    )>,
+)>;
+
+// Keeps the floor at the bottom edge of the window as it's resized (e.g. a canvas sized by CSS on
+// WASM). Needs a window, so it's left out of headless apps.
+pub struct WindowBoundsPlugin;
+
+impl Plugin for WindowBoundsPlugin {
+    fn build(&self, app: &mut App) {
+        // The floors move in the same frame
+        app.add_system(
+            window_bounds_system
+                .before(floor_height_system)
+                .before(PhysicsStep),
+        );
+    }
+}
+
+// Number of the level being played
+#[derive(Resource, Default)]
+pub struct CurrentLevel(pub usize);
+
+// Static ground surface: everything below the entity's y is solid.
+// The physics still uses PhysicsConfig::floor_y, which floors are kept at, and finds out what a
+// floor is made of only if it's a StaticBody.
+#[derive(Component)]
+pub struct Floor {
+    pub width: f32,
+}
+
+// A vertical finish line. The player reaching it completes the level.
+#[derive(Component)]
+pub struct Goal;
+
+// What the level's triggers call something, e.g. "door1"
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct LevelName(pub String);
+
+#[derive(Component, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum SurfaceMaterial {
+    #[default]
+    Normal,
+    // Multiplies the friction of balls on it by `friction_multiplier`, so spinning mostly spins
+    // them in place and they slide on where they were going
+    Ice {
+        friction_multiplier: f32,
+    },
+    // Bounces balls off with `restitution_override` instead of their own restitution, and at least
+    // `min_launch_speed` upwards, so that even rolling onto it throws them up. Over 1, each bounce
+    // is higher than the last, up to PhysicsConfig::max_bounce_speed.
+    Trampoline {
+        restitution_override: f32,
+        min_launch_speed: f32,
+    },
+    Hazard,
+    // Holds on to balls with up to `strength` of force (see StickyPlugin)
+    Sticky {
+        strength: f32,
+    },
+}
+
+impl SurfaceMaterial {
+    pub fn color(self) -> Color {
+        match self {
+            SurfaceMaterial::Normal => Color::DARK_GRAY,
+            SurfaceMaterial::Ice { .. } => Color::rgb(0.7, 0.9, 1.0),
+            SurfaceMaterial::Trampoline { .. } => Color::GREEN,
+            SurfaceMaterial::Hazard => Color::RED,
+            SurfaceMaterial::Sticky { .. } => Color::rgb(0.9, 0.6, 0.1),
+        }
+    }
+}
+
+// What a level is made of. Entries that don't make sense are skipped with a warning when it's
+// spawned, so one bad entry doesn't take the whole level down.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct Level {
+    pub floors: Vec<FloorEntry>,
+    pub balls: Vec<BallEntry>,
+    // Horizontal position of the Goal. Levels without one can't be completed.
+    #[serde(default)]
+    pub goal: Option<f32>,
+    #[serde(default)]
+    pub pickups: Vec<PickupEntry>,
+    #[serde(default)]
+    pub sensors: Vec<SensorEntry>,
+    #[serde(default)]
+    pub portals: Vec<PortalEntry>,
+    #[serde(default)]
+    pub saws: Vec<SawEntry>,
+    // Stretches of Crumbling floor tiles, which should be left out of `floors`
+    #[serde(default)]
+    pub crumbling: Vec<CrumblingEntry>,
+    #[serde(default)]
+    pub elevators: Vec<ElevatorEntry>,
+    #[serde(default)]
+    pub ropes: Vec<RopeEntry>,
+    // Pools of water and lava
+    #[serde(default)]
+    pub fluids: Vec<FluidEntry>,
+    #[serde(default)]
+    pub coins: Vec<Vec2>,
+    #[serde(default)]
+    pub doors: Vec<DoorEntry>,
+    // Named TriggerZones, for the triggers to watch
+    #[serde(default)]
+    pub zones: Vec<ZoneEntry>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerEntry>,
+    #[serde(default)]
+    pub boost_pads: Vec<BoostPadEntry>,
+}
+
+impl Level {
+    // A level with nothing in it
+    pub fn empty() -> Self {
+        Self {
+            floors: Vec::new(),
+            balls: Vec::new(),
+            goal: None,
+            pickups: Vec::new(),
+            sensors: Vec::new(),
+            portals: Vec::new(),
+            saws: Vec::new(),
+            crumbling: Vec::new(),
+            elevators: Vec::new(),
+            ropes: Vec::new(),
+            fluids: Vec::new(),
+            coins: Vec::new(),
+            doors: Vec::new(),
+            zones: Vec::new(),
+            triggers: Vec::new(),
+            boost_pads: Vec::new(),
+        }
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            floors: floor_with_patches(&[
+                (
+                    TRAMPOLINE_X,
+                    TRAMPOLINE_WIDTH,
+                    Some(SurfaceMaterial::Trampoline {
+                        restitution_override: 1.2,
+                        min_launch_speed: 1400.0,
+                    }),
+                ),
+                (CRUMBLING_X, CRUMBLING_WIDTH, None),
+                (
+                    ICE_X,
+                    ICE_WIDTH,
+                    Some(SurfaceMaterial::Ice {
+                        friction_multiplier: 0.05,
+                    }),
+                ),
+                (
+                    STICKY_X,
+                    STICKY_WIDTH,
+                    Some(SurfaceMaterial::Sticky { strength: 60_000.0 }),
+                ),
+            ]),
+            balls: vec![BallEntry {
+                position: Vec2::ZERO,
+                radius: PLAYER_RADIUS,
+                mass: 10.0,
+                coef_of_restitution: 0.3,
+                kinetic_friction: 0.5,
+                com_offset: Vec2::ZERO,
+                player: true,
+            }],
+            goal: Some(GOAL_X),
+            pickups: vec![PickupEntry {
+                position: Vec2::new(PICKUP_X, -330.0),
+                change: SizeChange {
+                    target_radius: 2.0 * PLAYER_RADIUS,
+                    duration: 8.0,
+                    scale_jump_impulse: true,
+                },
+            }],
+            sensors: vec![SensorEntry {
+                position: Vec2::new(BOOST_X, -330.0),
+                sensor: StatusSensor {
+                    effect: StatusEffect::SpeedBoost,
+                    duration: 5.0,
+                    radius: 15.0,
+                    pickup: true,
+                },
+            }],
+            portals: Vec::new(),
+            // Going up and down, to pass under when it's up
+            saws: vec![SawEntry {
+                position: Vec2::new(SAW_X, -280.0),
+                blade: SawBlade {
+                    radius: 40.0,
+                    angular_speed: -12.0,
+                    path: Some((Vec2::new(SAW_X, -280.0), Vec2::new(SAW_X, 0.0), 1.5)),
+                },
+            }],
+            crumbling: vec![CrumblingEntry {
+                x: CRUMBLING_X,
+                width: CRUMBLING_WIDTH,
+                crumbling: Crumbling {
+                    delay: 0.4,
+                    respawn: Some(3.0),
+                },
+            }],
+            // Takes more than the player to sink it, with a coin on top
+            elevators: vec![ElevatorEntry {
+                x: PLATFORM_X,
+                elevator: Elevator {
+                    top: 0.0,
+                    bottom: -200.0,
+                    speed: 40.0,
+                    width: 200.0,
+                    threshold: 50.0,
+                },
+                counterweight: None,
+                name: None,
+            }],
+            // Hanging low enough to grab at the top of a jump
+            ropes: vec![RopeEntry {
+                position: Vec2::new(ROPE_X, 100.0),
+                anchor: RopeAnchor {
+                    segments: 8,
+                    segment_length: 25.0,
+                },
+            }],
+            fluids: Vec::new(),
+            coins: vec![Vec2::new(PLATFORM_X, 30.0)],
+            doors: Vec::new(),
+            zones: Vec::new(),
+            triggers: Vec::new(),
+            boost_pads: vec![
+                BoostPadEntry {
+                    x: BOOST_PAD_LEFT_X,
+                    pad: BoostPad {
+                        direction: Vec2::new(1.0, 1.0),
+                        strength: BOOST_PAD_STRENGTH,
+                    },
+                },
+                BoostPadEntry {
+                    x: BOOST_PAD_RIGHT_X,
+                    pad: BoostPad {
+                        direction: Vec2::new(-1.0, 1.0),
+                        strength: BOOST_PAD_STRENGTH,
+                    },
+                },
+            ],
+        }
+    }
+}
+
+// Floors are always at PhysicsConfig::floor_y
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FloorEntry {
+    pub x: f32,
+    pub width: f32,
+    pub material: SurfaceMaterial,
+}
+
+impl FloorEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.x.is_finite() {
+            return Err(format!("position {} isn't finite", self.x));
+        }
+        if !(self.width.is_finite() && self.width > 0.0) {
+            return Err(format!("width {} isn't positive", self.width));
+        }
+        if let SurfaceMaterial::Ice {
+            friction_multiplier,
+        } = self.material
+        {
+            if !(friction_multiplier.is_finite() && friction_multiplier >= 0.0) {
+                return Err(format!(
+                    "ice friction multiplier {friction_multiplier} is negative or not finite"
+                ));
+            }
+        }
+        if let SurfaceMaterial::Sticky { strength } = self.material {
+            if !(strength.is_finite() && strength >= 0.0) {
+                return Err(format!(
+                    "sticky strength {strength} is negative or not finite"
+                ));
+            }
+        }
+        if let SurfaceMaterial::Trampoline {
+            restitution_override,
+            min_launch_speed,
+        } = self.material
+        {
+            for (name, value) in [
+                ("trampoline restitution", restitution_override),
+                ("trampoline launch speed", min_launch_speed),
+            ] {
+                if !(value.is_finite() && value >= 0.0) {
+                    return Err(format!("{name} {value} is negative or not finite"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BallEntry {
+    pub position: Vec2,
+    pub radius: f32,
+    pub mass: f32,
+    pub coef_of_restitution: f32,
+    pub kinetic_friction: f32,
+    // Where its weight sits relative to its center, for balls that wobble as they roll
+    #[serde(default)]
+    pub com_offset: Vec2,
+    // Whether this is the ball the player controls
+    pub player: bool,
+}
+
+impl BallEntry {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        for (name, value) in [("radius", self.radius), ("mass", self.mass)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("{name} {value} isn't positive"));
+            }
+        }
+        for (name, value) in [
+            ("restitution", self.coef_of_restitution),
+            ("friction", self.kinetic_friction),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!("{name} {value} is negative"));
+            }
+        }
+        if !(self.com_offset.is_finite() && self.com_offset.length() < self.radius) {
+            return Err(format!(
+                "center of mass offset {} isn't inside the ball",
+                self.com_offset
+            ));
+        }
+        Ok(())
+    }
+}
+
+// A SizePickup, floating where it's put
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PickupEntry {
+    pub position: Vec2,
+    pub change: SizeChange,
+}
+
+impl PickupEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        let SizeChange {
+            target_radius,
+            duration,
+            ..
+        } = self.change;
+        for (name, value) in [("target radius", target_radius), ("duration", duration)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("{name} {value} isn't positive"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// A StatusSensor where it's put
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensorEntry {
+    pub position: Vec2,
+    pub sensor: StatusSensor,
+}
+
+impl SensorEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        let StatusSensor {
+            duration, radius, ..
+        } = self.sensor;
+        for (name, value) in [("duration", duration), ("radius", radius)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("{name} {value} isn't positive"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// A pair of portals, at `a` facing `a_angle` and at `b` facing `b_angle` (counterclockwise from the
+// x axis)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PortalEntry {
+    pub id: u32,
+    pub a: Vec2,
+    pub a_angle: f32,
+    pub b: Vec2,
+    pub b_angle: f32,
+}
+
+impl PortalEntry {
+    fn validate(&self) -> Result<(), String> {
+        for position in [self.a, self.b] {
+            if !position.is_finite() {
+                return Err(format!("position {position} isn't finite"));
+            }
+        }
+        for angle in [self.a_angle, self.b_angle] {
+            if !angle.is_finite() {
+                return Err(format!("angle {angle} isn't finite"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// A SawBlade where it's put
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SawEntry {
+    pub position: Vec2,
+    pub blade: SawBlade,
+}
+
+impl SawEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        let SawBlade {
+            radius,
+            angular_speed,
+            path,
+        } = self.blade;
+        if !(radius.is_finite() && radius > 0.0) {
+            return Err(format!("radius {radius} isn't positive"));
+        }
+        if !angular_speed.is_finite() {
+            return Err(format!("angular speed {angular_speed} isn't finite"));
+        }
+        if let Some((from, to, duration)) = path {
+            if !(from.is_finite() && to.is_finite()) {
+                return Err(format!("path from {from} to {to} isn't finite"));
+            }
+            if !(duration.is_finite() && duration > 0.0) {
+                return Err(format!("path duration {duration} isn't positive"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// An Elevator at `x`, starting at the top. With a `counterweight`, it's one of a Counterweight pair
+// with another just like it at that x, starting at the bottom.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ElevatorEntry {
+    pub x: f32,
+    pub elevator: Elevator,
+    #[serde(default)]
+    pub counterweight: Option<f32>,
+    // For the triggers, see TriggerAction::MovePlatformTo
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl ElevatorEntry {
+    fn validate(&self) -> Result<(), String> {
+        for x in std::iter::once(self.x).chain(self.counterweight) {
+            if !x.is_finite() {
+                return Err(format!("position {x} isn't finite"));
+            }
+        }
+        let Elevator {
+            top,
+            bottom,
+            speed,
+            width,
+            threshold,
+        } = self.elevator;
+        if !(top.is_finite() && bottom.is_finite() && bottom < top) {
+            return Err(format!("bottom {bottom} isn't below top {top}"));
+        }
+        for (name, value) in [("speed", speed), ("width", width), ("threshold", threshold)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("{name} {value} isn't positive"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// A rope hanging from where it's put
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RopeEntry {
+    pub position: Vec2,
+    pub anchor: RopeAnchor,
+}
+
+impl RopeEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        let RopeAnchor {
+            segments,
+            segment_length,
+        } = self.anchor;
+        if segments == 0 {
+            return Err("it has no segments".to_string());
+        }
+        if !(segment_length.is_finite() && segment_length > 0.0) {
+            return Err(format!("segment length {segment_length} isn't positive"));
+        }
+        Ok(())
+    }
+}
+
+// A FluidVolume centered on `position`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FluidEntry {
+    pub position: Vec2,
+    pub fluid: FluidVolume,
+}
+
+impl FluidEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        let FluidVolume {
+            size,
+            density,
+            drag,
+            ..
+        } = self.fluid;
+        if !(size.is_finite() && size.cmpgt(Vec2::ZERO).all()) {
+            return Err(format!("size {size} isn't positive"));
+        }
+        for (name, value) in [("density", density), ("drag", drag)] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!("{name} {value} is negative"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// A closed Door standing on the floor at `x`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DoorEntry {
+    pub name: String,
+    pub x: f32,
+    pub height: f32,
+}
+
+impl DoorEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.x.is_finite() {
+            return Err(format!("position {} isn't finite", self.x));
+        }
+        if !(self.height.is_finite() && self.height > 0.0) {
+            return Err(format!("height {} isn't positive", self.height));
+        }
+        Ok(())
+    }
+}
+
+// A TriggerZone centered on `position`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ZoneEntry {
+    pub name: String,
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+impl ZoneEntry {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("it has no name".to_string());
+        }
+        if !self.position.is_finite() {
+            return Err(format!("position {} isn't finite", self.position));
+        }
+        if !(self.radius.is_finite() && self.radius > 0.0) {
+            return Err(format!("radius {} isn't positive", self.radius));
+        }
+        Ok(())
+    }
+}
+
+// A BoostPad lying on the floor at `x`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BoostPadEntry {
+    pub x: f32,
+    pub pad: BoostPad,
+}
+
+impl BoostPadEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.x.is_finite() {
+            return Err(format!("position {} isn't finite", self.x));
+        }
+        let BoostPad {
+            direction,
+            strength,
+        } = self.pad;
+        if !(direction.is_finite() && direction != Vec2::ZERO) {
+            return Err(format!("direction {direction} isn't a direction"));
+        }
+        if !(strength.is_finite() && strength > 0.0) {
+            return Err(format!("strength {strength} isn't positive"));
+        }
+        Ok(())
+    }
+}
+
+// Floor from `x - width / 2` to `x + width / 2`, made of tiles about CRUMBLING_TILE_WIDTH wide
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CrumblingEntry {
+    pub x: f32,
+    pub width: f32,
+    pub crumbling: Crumbling,
+}
+
+impl CrumblingEntry {
+    fn validate(&self) -> Result<(), String> {
+        if !self.x.is_finite() {
+            return Err(format!("position {} isn't finite", self.x));
+        }
+        if !(self.width.is_finite() && self.width > 0.0) {
+            return Err(format!("width {} isn't positive", self.width));
+        }
+        let Crumbling { delay, respawn } = self.crumbling;
+        for (name, value) in [("delay", Some(delay)), ("respawn time", respawn)] {
+            match value {
+                Some(value) if !(value.is_finite() && value >= 0.0) => {
+                    return Err(format!("{name} {value} is negative"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // The tiles' centers and width
+    fn tiles(&self) -> impl Iterator<Item = (f32, f32)> {
+        let count = (self.width / CRUMBLING_TILE_WIDTH).round().max(1.0);
+        let width = self.width / count;
+        let left = self.x - 0.5 * self.width;
+        (0..count as usize).map(move |i| (left + (i as f32 + 0.5) * width, width))
+    }
+}
+
+// Only spawns the level if there isn't one yet, as play also starts again after pausing
+fn level_spawn_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    level: Res<Level>,
+    existing: Query<(), LevelEntities>,
+) {
+    if existing.is_empty() {
+        spawn_level(&mut commands, &config, &level);
+    }
+}
+
+fn level_despawn_system(mut commands: Commands, query: Query<Entity, LevelEntities>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// The respawn key restarts the level, as dying does
+fn respawn_key_system(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut restarts: EventWriter<RestartLevelEvent>,
+) {
+    if settings.input.just_pressed(&keys, InputAction::Respawn) {
+        restarts.send(RestartLevelEvent);
+    }
+}
+
+pub(crate) fn level_restart_system(
+    mut commands: Commands,
+    mut restarts: EventReader<RestartLevelEvent>,
+    config: Res<PhysicsConfig>,
+    level: Res<Level>,
+    query: Query<Entity, LevelEntities>,
+) {
+    if restarts.iter().last().is_none() {
+        return;
+    }
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_level(&mut commands, &config, &level);
+}
+
+// The default floor, with patches at `x` of `width` instead of the normal surface: of another
+// material, or left out (None) for something else to fill in. Patches go from left to right and
+// don't overlap.
+fn floor_with_patches(patches: &[(f32, f32, Option<SurfaceMaterial>)]) -> Vec<FloorEntry> {
+    let normal = |left: f32, right: f32| FloorEntry {
+        x: 0.5 * (left + right),
+        width: right - left,
+        material: SurfaceMaterial::Normal,
+    };
+    let mut floors = Vec::new();
+    let mut left = -0.5 * FLOOR_WIDTH;
+    for &(x, width, material) in patches {
+        floors.push(normal(left, x - 0.5 * width));
+        if let Some(material) = material {
+            floors.push(FloorEntry { x, width, material });
+        }
+        left = x + 0.5 * width;
+    }
+    floors.push(normal(left, 0.5 * FLOOR_WIDTH));
+    floors
+}
+
+pub(crate) fn spawn_level(commands: &mut Commands, config: &PhysicsConfig, level: &Level) {
+    match level.goal {
+        Some(x) if !x.is_finite() => warn!("Skipping the goal of the level: {x} isn't finite"),
+        Some(x) => {
+            commands.spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(x, config.floor_y, -1.0)),
+                Goal,
+            ));
+        }
+        None => {}
+    }
+
+    for (i, floor) in level.floors.iter().enumerate() {
+        if let Err(reason) = floor.validate() {
+            warn!("Skipping floor {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(floor.x, config.floor_y, -1.0)),
+            Floor { width: floor.width },
+            floor.material,
+            StaticBody,
+        ));
+    }
+
+    for (i, ball) in level.balls.iter().enumerate() {
+        if let Err(reason) = ball.validate() {
+            warn!("Skipping ball {i} of the level: {reason}");
+            continue;
+        }
+        spawn_ball(commands, config, ball);
+    }
+
+    for (i, pickup) in level.pickups.iter().enumerate() {
+        if let Err(reason) = pickup.validate() {
+            warn!("Skipping pickup {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                pickup.position.extend(-0.5),
+            )),
+            SizePickup(pickup.change),
+        ));
+    }
+
+    for (i, sensor) in level.sensors.iter().enumerate() {
+        if let Err(reason) = sensor.validate() {
+            warn!("Skipping sensor {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                sensor.position.extend(-0.5),
+            )),
+            sensor.sensor,
+        ));
+    }
+
+    for (i, portals) in level.portals.iter().enumerate() {
+        if let Err(reason) = portals.validate() {
+            warn!("Skipping portals {i} of the level: {reason}");
+            continue;
+        }
+        spawn_portal_pair(
+            commands,
+            portals.id,
+            (portals.a, portals.a_angle),
+            (portals.b, portals.b_angle),
+        );
+    }
+
+    for (i, crumbling) in level.crumbling.iter().enumerate() {
+        if let Err(reason) = crumbling.validate() {
+            warn!("Skipping crumbling floor {i} of the level: {reason}");
+            continue;
+        }
+        for (x, width) in crumbling.tiles() {
+            commands.spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(x, config.floor_y, -1.0)),
+                Floor { width },
+                SurfaceMaterial::Normal,
+                crumbling.crumbling,
+                StaticBody,
+            ));
+        }
+    }
+
+    for (i, elevator) in level.elevators.iter().enumerate() {
+        if let Err(reason) = elevator.validate() {
+            warn!("Skipping elevator {i} of the level: {reason}");
+            continue;
+        }
+        let entities = match elevator.counterweight {
+            Some(b) => spawn_elevator_pair(commands, elevator.elevator, elevator.x, b).to_vec(),
+            None => vec![spawn_elevator(commands, elevator.x, elevator.elevator)],
+        };
+        if let Some(name) = &elevator.name {
+            for entity in entities {
+                commands.entity(entity).insert(LevelName(name.clone()));
+            }
+        }
+    }
+
+    for (i, rope) in level.ropes.iter().enumerate() {
+        if let Err(reason) = rope.validate() {
+            warn!("Skipping rope {i} of the level: {reason}");
+            continue;
+        }
+        spawn_rope(commands, config, rope.position, rope.anchor);
+    }
+
+    for (i, fluid) in level.fluids.iter().enumerate() {
+        if let Err(reason) = fluid.validate() {
+            warn!("Skipping fluid {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(fluid.position.extend(0.5))),
+            fluid.fluid,
+        ));
+    }
+
+    for (i, &position) in level.coins.iter().enumerate() {
+        if !position.is_finite() {
+            warn!("Skipping coin {i} of the level: position {position} isn't finite");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position.extend(-0.5))),
+            Coin,
+        ));
+    }
+
+    for (i, door) in level.doors.iter().enumerate() {
+        if let Err(reason) = door.validate() {
+            warn!("Skipping door {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(door.x, config.floor_y, -0.5)),
+            Door {
+                height: door.height,
+                open: false,
+            },
+            LevelName(door.name.clone()),
+        ));
+    }
+
+    for (i, zone) in level.zones.iter().enumerate() {
+        if let Err(reason) = zone.validate() {
+            warn!("Skipping zone {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(zone.position.extend(-0.5))),
+            TriggerZone {
+                radius: zone.radius,
+                occupied: false,
+            },
+            LevelName(zone.name.clone()),
+        ));
+    }
+
+    for (i, entry) in level.boost_pads.iter().enumerate() {
+        if let Err(reason) = entry.validate() {
+            warn!("Skipping boost pad {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(entry.x, config.floor_y, -0.5)),
+            entry.pad,
+        ));
+    }
+
+    for (i, saw) in level.saws.iter().enumerate() {
+        if let Err(reason) = saw.validate() {
+            warn!("Skipping saw {i} of the level: {reason}");
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(saw.position.extend(-0.5))),
+            saw.blade,
+        ));
+    }
+}
+
+// Spawns a ball as the level would. The entry should be valid (see BallEntry::validate).
+pub fn spawn_ball(commands: &mut Commands, config: &PhysicsConfig, ball: &BallEntry) -> Entity {
+    let mut entity = commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(ball.position.extend(0.0))),
+        PhysObj {
+            mass: ball.mass,
+            vel: Vec2::ZERO,
+            acc: Vec2::ZERO,
+            acc_prev: Vec2::ZERO,
+            moment_of_inertia: disk_moment_of_inertia(ball.mass, ball.radius),
+            angular_vel: 0.0,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: ball.com_offset,
+        },
+        Gravity(config.gravity),
+        Collider::Ball {
+            radius: ball.radius,
+            coef_of_restitution: ball.coef_of_restitution,
+            // A ball placed on the floor starts out resting there, rather than landing on it
+            touching_ground: ball.position.y - ball.radius <= config.floor_y
+                && !config.over_gap(ball.position.x),
+            kinetic_friction: ball.kinetic_friction,
+            friction_acc: 0.0,
+            friction_acc_prev: 0.0,
+        },
+        FidgetSpinner::new(ball.radius),
+    ));
+    if ball.player {
+        entity.insert((Player::default(), Dash::default()));
+    }
+    entity.id()
+}
+
+// The level is complete once a player's ball touches the finish line
+fn goal_system(
+    goals: Query<&Transform, With<Goal>>,
+    players: Query<(&Transform, &Collider), With<Player>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let reached = players
+        .iter()
+        .any(|(player, &Collider::Ball { radius, .. })| {
+            goals
+                .iter()
+                .any(|goal| (player.translation.x - goal.translation.x).abs() <= radius)
+        });
+    if reached {
+        next_state.set(AppState::LevelComplete);
+    }
+}
+
+// Moves the floors (and goals, doors and boost pads, which are on them) along when the configured
+// floor height changes
+#[allow(clippy::type_complexity)]
+fn floor_height_system(
+    config: Res<PhysicsConfig>,
+    mut query: Query<&mut Transform, Or<(With<Floor>, With<Goal>, With<Door>, With<BoostPad>)>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    for mut transform in &mut query {
+        transform.translation.y = config.floor_y;
+    }
+}
+
+// One world unit is a pixel with the default camera, so the window's bottom edge is half its height
+// below the origin. Bodies the floor moved up into are lifted back onto it.
+fn window_bounds_system(
+    mut resized: EventReader<WindowResized>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut config: ResMut<PhysicsConfig>,
+    mut bodies: Query<(&mut Transform, &Collider)>,
+) {
+    let Ok(primary) = windows.get_single() else {
+        return;
+    };
+    let Some(resize) = resized
+        .iter()
+        .filter(|resize| resize.window == primary)
+        .last()
+    else {
+        return;
+    };
+
+    let floor_y = -0.5 * resize.height;
+    if config.floor_y == floor_y {
+        return;
+    }
+    config.floor_y = floor_y;
+    for (mut transform, &Collider::Ball { radius, .. }) in &mut bodies {
+        transform.translation.y = transform.translation.y.max(floor_y + radius);
+    }
+}
+
+// Height of the highest floor at or below `position`, if there is one
+pub fn floor_below<'a>(
+    position: Vec2,
+    floors: impl IntoIterator<Item = (&'a Transform, &'a Floor)>,
+) -> Option<f32> {
+    floors
+        .into_iter()
+        .filter(|(transform, floor)| is_below(position, transform, floor))
+        .map(|(transform, _)| transform.translation.y)
+        .reduce(f32::max)
+}
+
+// Material of the highest floor at or below `position`, if there is one
+pub fn surface_below<'a>(
+    position: Vec2,
+    floors: impl IntoIterator<Item = (&'a Transform, &'a Floor, Option<&'a SurfaceMaterial>)>,
+) -> Option<SurfaceMaterial> {
+    floors
+        .into_iter()
+        .filter(|(transform, floor, _)| is_below(position, transform, floor))
+        .max_by(|(a, ..), (b, ..)| a.translation.y.total_cmp(&b.translation.y))
+        .map(|(_, _, material)| material.copied().unwrap_or_default())
+}
+
+// What the friction of a ball at `position` with the floor below it is multiplied by: the ice's
+// multiplier on ice, and 1 anywhere else
+pub fn surface_friction_scale<'a>(
+    position: Vec2,
+    floors: impl IntoIterator<Item = (&'a Transform, &'a Floor, Option<&'a SurfaceMaterial>)>,
+) -> f32 {
+    match surface_below(position, floors) {
+        Some(SurfaceMaterial::Ice {
+            friction_multiplier,
+        }) => friction_multiplier,
+        _ => 1.0,
+    }
+}
+
+fn is_below(position: Vec2, transform: &Transform, floor: &Floor) -> bool {
+    (position.x - transform.translation.x).abs() <= 0.5 * floor.width
+        && transform.translation.y <= position.y
+}
@@ -0,0 +1,125 @@
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod best_run;
+pub mod blob;
+pub mod boost;
+pub mod crumble;
+pub mod debug;
+pub mod elevator;
+pub mod features;
+pub mod fluid;
+pub mod fullscreen;
+pub mod grapple;
+pub mod hazard;
+pub mod help;
+pub mod hills;
+pub mod hud;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+pub mod launch;
+#[cfg(feature = "ldtk")]
+pub mod ldtk;
+#[cfg(feature = "leaderboard")]
+pub mod leaderboard;
+pub mod level;
+pub mod menu;
+pub mod mesh_cache;
+#[cfg(all(feature = "netplay", not(target_arch = "wasm32")))]
+pub mod netplay;
+pub mod physics;
+pub mod player;
+pub mod portal;
+pub mod powerup;
+pub mod progress;
+pub mod replay;
+pub mod rewind;
+pub mod rng;
+pub mod rope;
+pub mod runner;
+pub mod sandbox;
+pub mod save;
+pub mod saw;
+pub mod scenario;
+pub mod screenshot;
+pub mod settings;
+pub mod shapes;
+pub mod slingshot;
+pub mod state;
+pub mod status;
+pub mod sticky;
+pub mod storage;
+pub mod stress;
+pub mod testing;
+pub mod toast;
+pub mod trigger;
+pub mod visuals;
+pub mod web;
+
+// Everything needed to build the game's App or write a system against its components
+pub mod prelude {
+    pub use crate::{
+        best_run::BestRunPlugin,
+        blob::BlobPlugin,
+        boost::{BoostPad, BoostPadPlugin},
+        crumble::{Crumbling, CrumblingPlugin},
+        debug::lines::{DebugLines, DebugLinesPlugin},
+        elevator::{Elevator, ElevatorPlugin},
+        features::{ActivePlugins, FeaturePlugins},
+        fluid::{FluidPlugin, FluidVolume},
+        fullscreen::FullscreenPlugin,
+        grapple::{GrappleConfig, GrapplePlugin, Grappled},
+        hazard::{DeathEvent, HazardPlugin},
+        help::HelpPlugin,
+        hills::{HillsPlugin, HillsTerrain},
+        hud::GameHudPlugin,
+        launch::{LaunchOptions, LaunchPlugin},
+        level::{
+            CurrentLevel, Floor, Level, LevelName, LevelPlugin, RestartLevelEvent, SurfaceMaterial,
+            WindowBoundsPlugin,
+        },
+        menu::MenuPlugin,
+        mesh_cache::MeshCache,
+        physics::{
+            anomaly::PhysicsAnomaly,
+            forces::{CustomForces, ForceFn, ForceProvider},
+            heightfield::Heightfield,
+            joints::{DistanceJoint, JointMotor, RevoluteJoint},
+            sleep::{Sleeping, StaticBody},
+            BounceEvent, Collider, CollisionEvent, Gravity, GravitySuppressed, LandedEvent,
+            PhysObj, PhysicsConfig, PhysicsPlugin, PhysicsSchedule, PhysicsSet, PhysicsStep,
+            PhysicsTime, SpawnOrder,
+        },
+        player::{
+            ControlMode, Dash, DirectDriveConfig, GlideConfig, GlideEvent, Gliding, JumpEvent,
+            OverheatEvent, Player, PlayerInput, PlayerPlugin, RemotePlayer, SlamConfig, SlamEvent,
+            Slamming, SpinHeat, SpinHeatConfig,
+        },
+        portal::{Portal, PortalPlugin},
+        powerup::{PowerUpPlugin, SizeChange, SizePickup},
+        progress::{LevelTimer, Progress, ProgressPlugin, Score},
+        replay::{Replay, ReplayPlugin},
+        rewind::{RewindConfig, RewindPlugin},
+        rng::GameRng,
+        rope::{RopeAnchor, RopePlugin},
+        runner::{RunnerDifficulty, RunnerPlugin},
+        sandbox::{SandboxBall, SandboxConfig, SandboxPlugin},
+        save::{SaveGame, SavePlugin},
+        saw::{SawBlade, SawBladePlugin},
+        screenshot::ScreenshotPlugin,
+        settings::{Settings, SettingsPlugin},
+        shapes::{AnimatedSpinner, FidgetSpinner, ShapesPlugin},
+        slingshot::{SlingshotConfig, SlingshotDrag, SlingshotPlugin},
+        state::{AppState, AppStatePlugin, FocusPausePlugin, StateScreensPlugin},
+        status::{StatusEffect, StatusEffects, StatusEffectsPlugin, StatusSensor},
+        sticky::StickyPlugin,
+        storage::{Storage, StorageBackend, StorageError},
+        stress::StressPlugin,
+        toast::{ToastEvent, ToastPlugin},
+        trigger::{Door, TriggerPlugin, TriggerState, TriggerZone},
+        visuals::VisualsPlugin,
+        web::WebPlugin,
+    };
+
+    #[cfg(feature = "debug-tools")]
+    pub use crate::debug::DebugToolsPlugin;
+}
@@ -0,0 +1,330 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    hud::format_level_time,
+    level::RestartLevelEvent,
+    progress::{level_complete_system, LevelStats},
+    state::{AfterLoading, AppState},
+};
+
+pub mod settings;
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const SELECTED_COLOR: Color = Color::rgb(0.3, 0.3, 0.6);
+
+// The main menu shown after loading, the pause menu, the level complete screen, and the settings
+// screen they lead to. Up/down
+// (or W/S, or the D-pad) move the selection and Enter, Space or the gamepad's south button choose
+// it; the mouse works too.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AfterLoading(AppState::MainMenu))
+            .init_resource::<MenuSelection>()
+            .init_resource::<SettingsReturn>()
+            .init_resource::<LevelStats>()
+            .add_event::<RestartLevelEvent>()
+            .add_system(menu_setup.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_system(menu_setup.in_schedule(OnEnter(AppState::Paused)))
+            .add_system(
+                menu_setup
+                    .after(level_complete_system)
+                    .in_schedule(OnEnter(AppState::LevelComplete)),
+            )
+            .add_system(menu_cleanup.in_schedule(OnExit(AppState::MainMenu)))
+            .add_system(menu_cleanup.in_schedule(OnExit(AppState::Paused)))
+            .add_system(menu_cleanup.in_schedule(OnExit(AppState::LevelComplete)))
+            .add_system(menu_cleanup.in_schedule(OnExit(AppState::SettingsMenu)))
+            .add_system(menu_navigation_system.run_if(in_menu))
+            .add_system(menu_highlight_system.after(menu_navigation_system))
+            .add_plugin(settings::SettingsMenuPlugin);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Play,
+    Settings,
+    Quit,
+    Resume,
+    RestartLevel,
+    // Restarting from the level complete screen
+    Retry,
+    MainMenu,
+}
+
+impl MenuItem {
+    pub fn label(self) -> &'static str {
+        match self {
+            MenuItem::Play => "Play",
+            MenuItem::Settings => "Settings",
+            MenuItem::Quit => "Quit",
+            MenuItem::Resume => "Resume",
+            MenuItem::RestartLevel => "Restart Level",
+            MenuItem::Retry => "Retry",
+            MenuItem::MainMenu => "Main Menu",
+        }
+    }
+
+    // The state choosing the item goes to, if it changes the state
+    pub fn next_state(self) -> Option<AppState> {
+        match self {
+            MenuItem::Play | MenuItem::Resume | MenuItem::RestartLevel | MenuItem::Retry => {
+                Some(AppState::Playing)
+            }
+            MenuItem::Settings => Some(AppState::SettingsMenu),
+            MenuItem::MainMenu => Some(AppState::MainMenu),
+            MenuItem::Quit => None,
+        }
+    }
+}
+
+// The items of the menu shown in `state`, top to bottom. There's no quitting on WASM; the tab is
+// closed instead.
+pub fn menu_items(state: AppState) -> Vec<MenuItem> {
+    match state {
+        AppState::MainMenu if cfg!(target_arch = "wasm32") => {
+            vec![MenuItem::Play, MenuItem::Settings]
+        }
+        AppState::MainMenu => vec![MenuItem::Play, MenuItem::Settings, MenuItem::Quit],
+        AppState::Paused => vec![
+            MenuItem::Resume,
+            MenuItem::RestartLevel,
+            MenuItem::Settings,
+            MenuItem::MainMenu,
+        ],
+        AppState::LevelComplete => vec![MenuItem::Retry, MenuItem::MainMenu],
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuInput {
+    Up,
+    Down,
+    // Left and right change the selected setting
+    Left,
+    Right,
+    Choose,
+}
+
+// The selection after moving it from `selected` in a menu of `len` items. It wraps around at
+// either end.
+pub fn navigate(selected: usize, len: usize, input: MenuInput) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    match input {
+        MenuInput::Up => (selected + len - 1) % len,
+        MenuInput::Down => (selected + 1) % len,
+        MenuInput::Left | MenuInput::Right | MenuInput::Choose => selected,
+    }
+}
+
+// Index of the selected item of the open menu
+#[derive(Resource, Default)]
+pub struct MenuSelection(pub usize);
+
+// The menu the settings screen goes back to
+#[derive(Resource)]
+pub struct SettingsReturn(pub AppState);
+
+impl Default for SettingsReturn {
+    fn default() -> Self {
+        Self(AppState::MainMenu)
+    }
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+// Selected by MenuSelection when it has the same index
+#[derive(Component)]
+struct MenuButton(usize);
+
+fn in_menu(state: Res<State<AppState>>) -> bool {
+    !menu_items(state.0).is_empty()
+}
+
+// The lines under the level complete screen's title
+pub fn level_stats_lines(stats: &LevelStats) -> Vec<String> {
+    let mut lines = vec![format!("Time {}", format_level_time(stats.time))];
+    match stats.previous_best {
+        _ if stats.new_best => lines.push("New best time!".to_string()),
+        Some(best) => lines.push(format!("Best {}", format_level_time(best))),
+        None => {}
+    }
+    lines.push(format!("Score {}", stats.score));
+    lines.push(format!("Deaths {}", stats.deaths));
+    lines
+}
+
+fn menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<AppState>>,
+    stats: Res<LevelStats>,
+    mut selection: ResMut<MenuSelection>,
+) {
+    selection.0 = 0;
+    let (title, background) = match state.0 {
+        AppState::Paused => ("Paused", Color::rgba(0.0, 0.0, 0.0, 0.6)),
+        AppState::LevelComplete => ("Level complete!", Color::rgba(0.0, 0.0, 0.0, 0.6)),
+        _ => ("bevy_game", Color::BLACK),
+    };
+    let info = match state.0 {
+        AppState::LevelComplete => level_stats_lines(&stats),
+        _ => Vec::new(),
+    };
+    let font = asset_server.load("fonts/DejaVuSansMono.ttf");
+    let text_style = |font_size| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::all(Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: background.into(),
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(title, text_style(48.0)));
+            for line in info {
+                parent.spawn(TextBundle::from_section(line, text_style(24.0)));
+            }
+            for (i, item) in menu_items(state.0).into_iter().enumerate() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(260.0), Val::Px(50.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        MenuButton(i),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(item.label(), text_style(28.0)));
+                    });
+            }
+        });
+}
+
+fn menu_cleanup(mut commands: Commands, query: Query<Entity, With<MenuRoot>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn menu_input(
+    keys: &Input<KeyCode>,
+    gamepad_buttons: &Input<GamepadButton>,
+    gamepads: &Gamepads,
+) -> Option<MenuInput> {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    if keys.any_just_pressed([KeyCode::Up, KeyCode::W])
+        || gamepad_pressed(GamepadButtonType::DPadUp)
+    {
+        Some(MenuInput::Up)
+    } else if keys.any_just_pressed([KeyCode::Down, KeyCode::S])
+        || gamepad_pressed(GamepadButtonType::DPadDown)
+    {
+        Some(MenuInput::Down)
+    } else if keys.any_just_pressed([KeyCode::Left, KeyCode::A])
+        || gamepad_pressed(GamepadButtonType::DPadLeft)
+    {
+        Some(MenuInput::Left)
+    } else if keys.any_just_pressed([KeyCode::Right, KeyCode::D])
+        || gamepad_pressed(GamepadButtonType::DPadRight)
+    {
+        Some(MenuInput::Right)
+    } else if keys.any_just_pressed([KeyCode::Return, KeyCode::Space])
+        || gamepad_pressed(GamepadButtonType::South)
+    {
+        Some(MenuInput::Choose)
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_navigation_system(
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    state: Res<State<AppState>>,
+    mut selection: ResMut<MenuSelection>,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut settings_return: ResMut<SettingsReturn>,
+    mut restarts: EventWriter<RestartLevelEvent>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let items = menu_items(state.0);
+    let mut chosen = None;
+    if let Some(input) = menu_input(&keys, &gamepad_buttons, &gamepads) {
+        selection.0 = navigate(selection.0, items.len(), input);
+        if input == MenuInput::Choose {
+            chosen = Some(selection.0);
+        }
+    }
+    for (interaction, &MenuButton(i)) in &buttons {
+        match interaction {
+            Interaction::Hovered => selection.0 = i,
+            Interaction::Clicked => chosen = Some(i),
+            Interaction::None => {}
+        }
+    }
+
+    let Some(&item) = chosen.and_then(|i| items.get(i)) else {
+        return;
+    };
+    match item {
+        MenuItem::RestartLevel | MenuItem::Retry => restarts.send(RestartLevelEvent),
+        MenuItem::Settings => settings_return.0 = state.0,
+        MenuItem::Quit => exit.send(AppExit),
+        _ => {}
+    }
+    if let Some(state) = item.next_state() {
+        next_state.set(state);
+    }
+}
+
+fn menu_highlight_system(
+    selection: Res<MenuSelection>,
+    mut buttons: Query<(&MenuButton, &mut BackgroundColor)>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    for (&MenuButton(i), mut color) in &mut buttons {
+        *color = if i == selection.0 {
+            SELECTED_COLOR
+        } else {
+            BUTTON_COLOR
+        }
+        .into();
+    }
+}
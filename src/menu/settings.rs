@@ -0,0 +1,430 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::{
+    menu_input, navigate, MenuButton, MenuInput, MenuRoot, MenuSelection, SettingsReturn,
+    BUTTON_COLOR,
+};
+use crate::{
+    help::key_names,
+    settings::{InputAction, Rebind, Settings},
+    state::AppState,
+    storage::Storage,
+};
+
+// Left and right change volumes by this much
+const VOLUME_STEP: f32 = 0.1;
+const MAX_SUBSTEPS: u32 = 8;
+const SLIDER_SIZE: Vec2 = Vec2::new(200.0, 12.0);
+
+// Edits the Settings. Changes apply right away, and are saved when leaving the screen.
+pub(super) struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_system(settings_menu_setup.in_schedule(OnEnter(AppState::SettingsMenu)))
+            .add_system(settings_menu_exit.in_schedule(OnExit(AppState::SettingsMenu)))
+            .add_systems(
+                (
+                    settings_menu_input_system,
+                    slider_drag_system,
+                    settings_menu_refresh_system,
+                )
+                    .chain()
+                    .distributive_run_if(in_state(AppState::SettingsMenu)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsRow {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    Vsync,
+    PhysicsSubsteps,
+    LandingAssist,
+    #[cfg(feature = "leaderboard")]
+    Leaderboard,
+    Binding(InputAction),
+    Back,
+}
+
+impl SettingsRow {
+    // Top to bottom
+    pub fn all() -> Vec<SettingsRow> {
+        let mut rows = vec![
+            SettingsRow::MasterVolume,
+            SettingsRow::SfxVolume,
+            SettingsRow::MusicVolume,
+            SettingsRow::Vsync,
+            SettingsRow::PhysicsSubsteps,
+            SettingsRow::LandingAssist,
+            #[cfg(feature = "leaderboard")]
+            SettingsRow::Leaderboard,
+        ];
+        rows.extend(InputAction::ALL.map(SettingsRow::Binding));
+        rows.push(SettingsRow::Back);
+        rows
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsRow::MasterVolume => "Volume",
+            SettingsRow::SfxVolume => "Sound effects",
+            SettingsRow::MusicVolume => "Music",
+            SettingsRow::Vsync => "VSync",
+            SettingsRow::PhysicsSubsteps => "Physics steps",
+            SettingsRow::LandingAssist => "Landing assist",
+            #[cfg(feature = "leaderboard")]
+            SettingsRow::Leaderboard => "Submit times online",
+            SettingsRow::Binding(action) => action.label(),
+            SettingsRow::Back => "Back",
+        }
+    }
+
+    // The volume the row's slider shows, if it has one
+    pub fn volume(self, settings: &Settings) -> Option<f32> {
+        match self {
+            SettingsRow::MasterVolume => Some(settings.master_volume),
+            SettingsRow::SfxVolume => Some(settings.sfx_volume),
+            SettingsRow::MusicVolume => Some(settings.music_volume),
+            _ => None,
+        }
+    }
+
+    pub fn volume_mut(self, settings: &mut Settings) -> Option<&mut f32> {
+        match self {
+            SettingsRow::MasterVolume => Some(&mut settings.master_volume),
+            SettingsRow::SfxVolume => Some(&mut settings.sfx_volume),
+            SettingsRow::MusicVolume => Some(&mut settings.music_volume),
+            _ => None,
+        }
+    }
+
+    // Changes the row's setting one step in `direction` (-1 or 1). Toggles go either way.
+    pub fn adjust(self, settings: &mut Settings, direction: i32) {
+        if let Some(volume) = self.volume_mut(settings) {
+            let steps = (*volume / VOLUME_STEP).round() + direction as f32;
+            *volume = (steps * VOLUME_STEP).clamp(0.0, 1.0);
+            return;
+        }
+        match self {
+            SettingsRow::Vsync => settings.vsync = !settings.vsync,
+            SettingsRow::LandingAssist => settings.landing_assist = !settings.landing_assist,
+            #[cfg(feature = "leaderboard")]
+            SettingsRow::Leaderboard => {
+                settings.leaderboard_opt_in = !settings.leaderboard_opt_in;
+            }
+            SettingsRow::PhysicsSubsteps => {
+                settings.physics_substeps = (settings.physics_substeps as i32 + direction)
+                    .clamp(1, MAX_SUBSTEPS as i32)
+                    as u32;
+            }
+            _ => {}
+        }
+    }
+
+    fn value_text(self, settings: &Settings, rebinding: Rebinding) -> String {
+        match (self, rebinding) {
+            (SettingsRow::MasterVolume, _) => format!("{:.0}%", settings.master_volume * 100.0),
+            (SettingsRow::SfxVolume, _) => format!("{:.0}%", settings.sfx_volume * 100.0),
+            (SettingsRow::MusicVolume, _) => format!("{:.0}%", settings.music_volume * 100.0),
+            (SettingsRow::Vsync, _) => if settings.vsync { "On" } else { "Off" }.to_string(),
+            (SettingsRow::PhysicsSubsteps, _) => settings.physics_substeps.to_string(),
+            (SettingsRow::LandingAssist, _) => {
+                if settings.landing_assist { "On" } else { "Off" }.to_string()
+            }
+            #[cfg(feature = "leaderboard")]
+            (SettingsRow::Leaderboard, _) => if settings.leaderboard_opt_in {
+                "On"
+            } else {
+                "Off"
+            }
+            .to_string(),
+            (SettingsRow::Binding(action), Rebinding::Waiting(waiting)) if action == waiting => {
+                "Press a key (Escape cancels)".to_string()
+            }
+            (
+                SettingsRow::Binding(action),
+                Rebinding::Conflict {
+                    action: conflicting,
+                    key,
+                    other,
+                },
+            ) if action == conflicting => {
+                format!("{key:?} is {}: Enter swaps, Escape cancels", other.label())
+            }
+            (SettingsRow::Binding(action), _) => key_names(&settings.input, action),
+            (SettingsRow::Back, _) => String::new(),
+        }
+    }
+}
+
+// Rebinding an action waits for the next key press. A key that's already bound to another action
+// is only taken after confirming that the two actions swap keys.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rebinding {
+    #[default]
+    Idle,
+    Waiting(InputAction),
+    Conflict {
+        action: InputAction,
+        key: KeyCode,
+        other: InputAction,
+    },
+}
+
+impl Rebinding {
+    // Handles `key` being pressed while rebinding
+    pub fn press(self, settings: &mut Settings, key: KeyCode) -> Rebinding {
+        match self {
+            Rebinding::Idle => Rebinding::Idle,
+            Rebinding::Waiting(action) => match settings.input.rebind(action, key) {
+                Rebind::Bind => {
+                    *settings.input.key_mut(action) = key;
+                    Rebinding::Idle
+                }
+                Rebind::Conflict(other) => Rebinding::Conflict { action, key, other },
+                Rebind::Cancel => Rebinding::Idle,
+            },
+            Rebinding::Conflict {
+                action,
+                key: conflicting_key,
+                ..
+            } if key_confirms(key) => {
+                settings.input.bind_swapping(action, conflicting_key);
+                Rebinding::Idle
+            }
+            Rebinding::Conflict { .. } if key == KeyCode::Escape => Rebinding::Idle,
+            Rebinding::Conflict { .. } => self,
+        }
+    }
+}
+
+fn key_confirms(key: KeyCode) -> bool {
+    matches!(key, KeyCode::Return | KeyCode::NumpadEnter)
+}
+
+// Text showing a row's value
+#[derive(Component)]
+struct SettingsValue(SettingsRow);
+
+// Sets a volume to where it's clicked or dragged
+#[derive(Component)]
+struct Slider(SettingsRow);
+
+// The filled part of a Slider, as wide as the volume
+#[derive(Component)]
+struct SliderFill(SettingsRow);
+
+fn settings_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings_return: Res<SettingsReturn>,
+    mut selection: ResMut<MenuSelection>,
+) {
+    selection.0 = 0;
+    // Over the game when opened from the pause menu
+    let background = match settings_return.0 {
+        AppState::Paused => Color::rgba(0.0, 0.0, 0.0, 0.6),
+        _ => Color::BLACK,
+    };
+    let font = asset_server.load("fonts/DejaVuSansMono.ttf");
+    let text_style = |font_size| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::all(Val::Percent(100.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: background.into(),
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Settings", text_style(40.0)));
+            for (i, row) in SettingsRow::all().into_iter().enumerate() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(640.0), Val::Px(36.0)),
+                                justify_content: JustifyContent::SpaceBetween,
+                                align_items: AlignItems::Center,
+                                padding: UiRect::horizontal(Val::Px(12.0)),
+                                gap: Size::all(Val::Px(12.0)),
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        MenuButton(i),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(row.label(), text_style(22.0)));
+                        if row.volume(&Settings::default()).is_some() {
+                            spawn_slider(button, row);
+                        }
+                        button.spawn((
+                            TextBundle::from_section("", text_style(22.0)),
+                            SettingsValue(row),
+                        ));
+                    });
+            }
+        });
+}
+
+fn spawn_slider(parent: &mut ChildBuilder, row: SettingsRow) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(SLIDER_SIZE.x), Val::Px(SLIDER_SIZE.y)),
+                    margin: UiRect::left(Val::Auto),
+                    ..default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..default()
+            },
+            Interaction::default(),
+            Slider(row),
+        ))
+        .with_children(|slider| {
+            slider.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                        ..default()
+                    },
+                    background_color: Color::WHITE.into(),
+                    ..default()
+                },
+                SliderFill(row),
+            ));
+        });
+}
+
+fn settings_menu_exit(
+    settings: Res<Settings>,
+    mut storage: ResMut<Storage>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    *rebinding = Rebinding::Idle;
+    if let Err(error) = settings.save(storage.0.as_mut()) {
+        error!("Failed to save the settings: {error}");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn settings_menu_input_system(
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    settings_return: Res<SettingsReturn>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut selection: ResMut<MenuSelection>,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // While rebinding, every key goes to the rebinding
+    if *rebinding != Rebinding::Idle {
+        if let Some(&key) = keys.get_just_pressed().next() {
+            *rebinding = rebinding.press(&mut settings, key);
+        }
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(settings_return.0);
+        return;
+    }
+
+    let rows = SettingsRow::all();
+    let mut input = menu_input(&keys, &gamepad_buttons, &gamepads);
+    for (interaction, &MenuButton(i)) in &buttons {
+        match interaction {
+            Interaction::Hovered => selection.0 = i,
+            Interaction::Clicked => {
+                selection.0 = i;
+                input = Some(MenuInput::Choose);
+            }
+            Interaction::None => {}
+        }
+    }
+    let Some(input) = input else {
+        return;
+    };
+
+    selection.0 = navigate(selection.0, rows.len(), input);
+    let Some(&row) = rows.get(selection.0) else {
+        return;
+    };
+    match (input, row) {
+        (MenuInput::Left, _) => row.adjust(&mut settings, -1),
+        (MenuInput::Right, _) => row.adjust(&mut settings, 1),
+        (MenuInput::Choose, SettingsRow::Vsync | SettingsRow::LandingAssist) => {
+            row.adjust(&mut settings, 1)
+        }
+        #[cfg(feature = "leaderboard")]
+        (MenuInput::Choose, SettingsRow::Leaderboard) => row.adjust(&mut settings, 1),
+        (MenuInput::Choose, SettingsRow::Binding(action)) => {
+            *rebinding = Rebinding::Waiting(action);
+        }
+        (MenuInput::Choose, SettingsRow::Back) => next_state.set(settings_return.0),
+        _ => {}
+    }
+}
+
+fn slider_drag_system(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut settings: ResMut<Settings>,
+    sliders: Query<(&Interaction, &Node, &GlobalTransform, &Slider)>,
+) {
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    for (interaction, node, transform, &Slider(row)) in &sliders {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        // Only x is needed, which is the same for the cursor and the UI
+        let width = node.size().x;
+        let left = transform.translation().x - 0.5 * width;
+        if let Some(volume) = row.volume_mut(&mut settings) {
+            *volume = ((cursor.x - left) / width).clamp(0.0, 1.0);
+        }
+    }
+}
+
+fn settings_menu_refresh_system(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    mut values: Query<(&mut Text, &SettingsValue)>,
+    mut fills: Query<(&mut Style, &SliderFill)>,
+    added: Query<(), Added<SettingsValue>>,
+) {
+    if !settings.is_changed() && !rebinding.is_changed() && added.is_empty() {
+        return;
+    }
+    for (mut text, &SettingsValue(row)) in &mut values {
+        text.sections[0].value = row.value_text(&settings, *rebinding);
+    }
+    for (mut style, &SliderFill(row)) in &mut fills {
+        if let Some(volume) = row.volume(&settings) {
+            style.size.width = Val::Percent(volume * 100.0);
+        }
+    }
+}
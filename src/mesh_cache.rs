@@ -0,0 +1,139 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::shapes::{FidgetSpinner, SpinnerProfile};
+
+// Sizes are rounded to this before they're used as keys, so that sizes that only differ by
+// rounding errors share a mesh
+const SIZE_STEP: f32 = 0.1;
+
+// Shared meshes and materials, so that spawning many bodies of the same shape doesn't add an
+// identical asset for each. Handles are cloned out of it, so an asset lives at least as long as the
+// cache; `clear` lets go of them (e.g. when changing levels).
+//
+// Entities that change their mesh or material in place (animated spinners, fading shadows) need
+// their own copy instead.
+#[derive(Resource, Default)]
+pub struct MeshCache {
+    meshes: HashMap<MeshKey, Handle<Mesh>>,
+    materials: HashMap<[u32; 4], Handle<ColorMaterial>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MeshKey {
+    Spinner {
+        radius: i32,
+        bump_size: i32,
+        bumps: u32,
+        vertices: usize,
+        profile: SpinnerProfile,
+    },
+    Circle {
+        radius: i32,
+    },
+    Triangle {
+        radius: i32,
+    },
+    Quad {
+        width: i32,
+        height: i32,
+    },
+}
+
+fn quantize(size: f32) -> i32 {
+    (size / SIZE_STEP).round() as i32
+}
+
+fn dequantize(size: i32) -> f32 {
+    size as f32 * SIZE_STEP
+}
+
+impl MeshCache {
+    pub fn spinner(&mut self, meshes: &mut Assets<Mesh>, spinner: FidgetSpinner) -> Handle<Mesh> {
+        let key = MeshKey::Spinner {
+            radius: quantize(spinner.radius),
+            bump_size: quantize(spinner.bump_size),
+            bumps: spinner.bumps,
+            vertices: spinner.vertices,
+            profile: spinner.profile,
+        };
+        self.mesh(meshes, key)
+    }
+
+    pub fn circle(&mut self, meshes: &mut Assets<Mesh>, radius: f32) -> Handle<Mesh> {
+        self.mesh(
+            meshes,
+            MeshKey::Circle {
+                radius: quantize(radius),
+            },
+        )
+    }
+
+    // Equilateral, pointing up, with its corners `radius` from its center
+    pub fn triangle(&mut self, meshes: &mut Assets<Mesh>, radius: f32) -> Handle<Mesh> {
+        self.mesh(
+            meshes,
+            MeshKey::Triangle {
+                radius: quantize(radius),
+            },
+        )
+    }
+
+    pub fn quad(&mut self, meshes: &mut Assets<Mesh>, size: Vec2) -> Handle<Mesh> {
+        self.mesh(
+            meshes,
+            MeshKey::Quad {
+                width: quantize(size.x),
+                height: quantize(size.y),
+            },
+        )
+    }
+
+    pub fn material(
+        &mut self,
+        materials: &mut Assets<ColorMaterial>,
+        color: Color,
+    ) -> Handle<ColorMaterial> {
+        let key = color.as_rgba_f32().map(f32::to_bits);
+        self.materials
+            .entry(key)
+            .or_insert_with(|| materials.add(color.into()))
+            .clone()
+    }
+
+    pub fn clear(&mut self) {
+        self.meshes.clear();
+        self.materials.clear();
+    }
+
+    // Built from the rounded sizes, so a mesh doesn't depend on which size asked for it first
+    fn mesh(&mut self, meshes: &mut Assets<Mesh>, key: MeshKey) -> Handle<Mesh> {
+        self.meshes
+            .entry(key)
+            .or_insert_with(|| {
+                meshes.add(match key {
+                    MeshKey::Spinner {
+                        radius,
+                        bump_size,
+                        bumps,
+                        vertices,
+                        profile,
+                    } => FidgetSpinner {
+                        radius: dequantize(radius),
+                        bump_size: dequantize(bump_size),
+                        bumps,
+                        vertices,
+                        profile,
+                    }
+                    .into(),
+                    MeshKey::Circle { radius } => shape::Circle::new(dequantize(radius)).into(),
+                    MeshKey::Triangle { radius } => {
+                        shape::RegularPolygon::new(dequantize(radius), 3).into()
+                    }
+                    MeshKey::Quad { width, height } => {
+                        shape::Quad::new(Vec2::new(dequantize(width), dequantize(height))).into()
+                    }
+                })
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use crate::player::PlayerInput;
+pub use crate::replay::state_hash;
+
+// Both players' inputs by tick, for simulating in lockstep. Local inputs are for `delay` ticks
+// after the one being simulated, which gives them that long to reach the other side before it
+// needs them. A tick is only simulated once both players' inputs for it are in. The first `delay`
+// ticks have no input on either side.
+pub struct LockstepBuffer {
+    delay: u32,
+    // The tick `advance` returns next
+    next_tick: u32,
+    // The tick the next local input is for
+    next_local: u32,
+    local: BTreeMap<u32, PlayerInput>,
+    remote: BTreeMap<u32, PlayerInput>,
+}
+
+impl LockstepBuffer {
+    pub fn new(delay: u32) -> Self {
+        let empty = (0..delay).map(|tick| (tick, PlayerInput::default()));
+        Self {
+            delay,
+            next_tick: 0,
+            next_local: delay,
+            local: empty.clone().collect(),
+            remote: empty.collect(),
+        }
+    }
+
+    pub fn delay(&self) -> u32 {
+        self.delay
+    }
+
+    pub fn next_tick(&self) -> u32 {
+        self.next_tick
+    }
+
+    // Schedules this frame's local input, returning the tick it's for, to send to the other
+    // player. While the other player's late, the buffer fills up and there's None, which drops
+    // the input rather than letting the delay grow.
+    pub fn add_local(&mut self, input: PlayerInput) -> Option<u32> {
+        if self.next_local > self.next_tick + self.delay {
+            return None;
+        }
+        let tick = self.next_local;
+        self.local.insert(tick, input);
+        self.next_local += 1;
+        Some(tick)
+    }
+
+    // The other player's input for `tick`. Ticks that were already simulated are ignored.
+    pub fn add_remote(&mut self, tick: u32, input: PlayerInput) {
+        if tick >= self.next_tick {
+            self.remote.insert(tick, input);
+        }
+    }
+
+    // The next tick with its local and remote inputs, once both are in
+    pub fn advance(&mut self) -> Option<(u32, PlayerInput, PlayerInput)> {
+        let tick = self.next_tick;
+        if !(self.local.contains_key(&tick) && self.remote.contains_key(&tick)) {
+            return None;
+        }
+        let local = self.local.remove(&tick)?;
+        let remote = self.remote.remove(&tick)?;
+        self.next_tick += 1;
+        Some((tick, local, remote))
+    }
+}
+
+// The two sides' state hashes on the same tick didn't match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Desync {
+    pub tick: u32,
+    pub local: u64,
+    pub remote: u64,
+}
+
+// Pairs up both sides' state hashes by tick, whichever arrives first
+#[derive(Default)]
+pub struct DesyncDetector {
+    local: BTreeMap<u32, u64>,
+    remote: BTreeMap<u32, u64>,
+}
+
+impl DesyncDetector {
+    pub fn record_local(&mut self, tick: u32, hash: u64) -> Option<Desync> {
+        self.local.insert(tick, hash);
+        self.check(tick)
+    }
+
+    pub fn record_remote(&mut self, tick: u32, hash: u64) -> Option<Desync> {
+        self.remote.insert(tick, hash);
+        self.check(tick)
+    }
+
+    fn check(&mut self, tick: u32) -> Option<Desync> {
+        let (&local, &remote) = (self.local.get(&tick)?, self.remote.get(&tick)?);
+        self.local.remove(&tick);
+        self.remote.remove(&tick);
+        (local != remote).then_some(Desync {
+            tick,
+            local,
+            remote,
+        })
+    }
+}
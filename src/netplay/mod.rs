@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::{spawn_ball, Level, RestartLevelEvent},
+    physics::{
+        physics_time_system, simulation_running, PhysObj, PhysicsConfig, PhysicsStep, PhysicsTime,
+        SimulationControl, STEP_DT,
+    },
+    player::{player_input_system, Player, PlayerInput, RemotePlayer},
+    state::AppState,
+    toast::ToastEvent,
+};
+
+pub mod lockstep;
+pub mod socket;
+
+use lockstep::{state_hash, DesyncDetector, LockstepBuffer};
+use socket::{Connection, NetEvent};
+
+// Both sides have to run the same build; bumped whenever the messages or the simulation change
+const PROTOCOL_VERSION: u32 = 1;
+// Ticks between a local input and the tick it's simulated on. At 60 ticks a second, 100 ms for
+// the input to reach the other side.
+pub const INPUT_DELAY: u32 = 6;
+// Ticks between state hash exchanges
+pub const HASH_INTERVAL: u32 = 30;
+// How far to the right of the level's player the second player starts
+const SEAT_SPACING: f32 = 60.0;
+
+// Two players on two machines, connected by a WebSocket: `--host <port>` waits for the other
+// player, and `--join ws://<address>:<port>` connects to one. Each side simulates both players
+// from the same inputs, in lockstep: every tick's inputs are exchanged, and a tick is simulated
+// once both are in, so neither side ever has to correct anything. The simulation is held
+// whenever the other player's input is late, and paused with a message if the connection drops
+// or the two simulations drift apart, which periodic state hashes catch.
+//
+// Both sides need the same level. Anything else that changes the world on one side only
+// (loading saves, rewinding, the inspector) makes them drift apart.
+pub struct NetplayPlugin {
+    pub role: NetRole,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetRole {
+    Host { port: u16 },
+    Join { url: String },
+}
+
+impl Plugin for NetplayPlugin {
+    fn build(&self, app: &mut App) {
+        let (connection, seat) = match &self.role {
+            NetRole::Host { port } => (Connection::host(*port), 0),
+            NetRole::Join { url } => (Connection::join(url.clone()), 1),
+        };
+        if matches!(self.role, NetRole::Host { .. }) {
+            connection.send(NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+                input_delay: INPUT_DELAY,
+            });
+        }
+        app.insert_resource(connection)
+            .insert_resource(Netplay::new(seat))
+            .add_startup_system(netplay_setup)
+            .add_systems(
+                (
+                    netplay_receive_system,
+                    netplay_seat_system,
+                    netplay_tick_system,
+                )
+                    .chain()
+                    .after(player_input_system)
+                    .after(physics_time_system)
+                    .before(PhysicsStep),
+            )
+            .add_system(
+                netplay_hash_system
+                    .after(PhysicsStep)
+                    .run_if(simulation_running),
+            );
+    }
+}
+
+// What the two sides tell each other
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NetMessage {
+    // The host's greeting, with the input delay both sides use
+    Hello { version: u32, input_delay: u32 },
+    // The joiner's answer. Both sides start from tick 0 from there.
+    Ready,
+    Input { tick: u32, input: PlayerInput },
+    Hash { tick: u32, hash: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetPhase {
+    // Until the handshake's done
+    Connecting,
+    Running,
+    // The connection dropped or the sides drifted apart. The simulation stays held.
+    Stopped,
+}
+
+// Which player each side is: the host's is seat 0, the level's own player, and the joiner's seat
+// 1, next to it
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetSeat(pub u8);
+
+#[derive(Resource)]
+pub struct Netplay {
+    pub seat: u8,
+    pub phase: NetPhase,
+    pub buffer: LockstepBuffer,
+    pub desync: DesyncDetector,
+    // The tick simulated this frame, if there was one
+    pub simulated: Option<u32>,
+}
+
+impl Netplay {
+    pub fn new(seat: u8) -> Self {
+        Self {
+            seat,
+            phase: NetPhase::Connecting,
+            buffer: LockstepBuffer::new(INPUT_DELAY),
+            desync: DesyncDetector::default(),
+            simulated: None,
+        }
+    }
+}
+
+// `--host <port>` or `--join <url>` on the command line
+pub fn netplay_from_args() -> Option<NetplayPlugin> {
+    let args: Vec<String> = std::env::args().collect();
+    let value = |flag: &str| {
+        let at = args.iter().position(|arg| arg == flag)?;
+        args.get(at + 1).cloned()
+    };
+    if let Some(port) = value("--host") {
+        let Ok(port) = port.parse() else {
+            error!("Not hosting netplay: {port} isn't a port");
+            return None;
+        };
+        return Some(NetplayPlugin {
+            role: NetRole::Host { port },
+        });
+    }
+    value("--join").map(|url| NetplayPlugin {
+        role: NetRole::Join { url },
+    })
+}
+
+// Lockstep only works if both sides step the bodies the same way
+fn netplay_setup(mut config: ResMut<PhysicsConfig>) {
+    config.serial = true;
+}
+
+fn netplay_receive_system(
+    connection: Res<Connection>,
+    mut netplay: ResMut<Netplay>,
+    mut toasts: EventWriter<ToastEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for event in connection.receive() {
+        if netplay.phase == NetPhase::Stopped {
+            return;
+        }
+        let message = match event {
+            NetEvent::Message(message) => message,
+            NetEvent::Disconnected(reason) => {
+                stop(
+                    &mut netplay,
+                    &mut toasts,
+                    &mut next_state,
+                    format!("Disconnected: {reason}"),
+                );
+                continue;
+            }
+        };
+        match message {
+            NetMessage::Hello {
+                version,
+                input_delay,
+            } => {
+                if version != PROTOCOL_VERSION {
+                    let reason = format!("The host runs version {version}, not {PROTOCOL_VERSION}");
+                    stop(&mut netplay, &mut toasts, &mut next_state, reason);
+                    continue;
+                }
+                netplay.buffer = LockstepBuffer::new(input_delay);
+                netplay.phase = NetPhase::Running;
+                connection.send(NetMessage::Ready);
+                toasts.send(ToastEvent("Connected to the host".to_string()));
+            }
+            NetMessage::Ready => {
+                netplay.phase = NetPhase::Running;
+                toasts.send(ToastEvent("The other player joined".to_string()));
+            }
+            NetMessage::Input { tick, input } => netplay.buffer.add_remote(tick, input),
+            NetMessage::Hash { tick, hash } => {
+                if let Some(desync) = netplay.desync.record_remote(tick, hash) {
+                    let reason = format!("Out of sync since tick {}", desync.tick);
+                    stop(&mut netplay, &mut toasts, &mut next_state, reason);
+                }
+            }
+        }
+    }
+}
+
+fn stop(
+    netplay: &mut Netplay,
+    toasts: &mut EventWriter<ToastEvent>,
+    next_state: &mut NextState<AppState>,
+    reason: String,
+) {
+    error!("Netplay stopped: {reason}");
+    netplay.phase = NetPhase::Stopped;
+    toasts.send(ToastEvent(reason));
+    next_state.set(AppState::Paused);
+}
+
+// The level's player is seat 0, and seat 1 is spawned next to it, whenever the level's (re)spawned
+fn netplay_seat_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    level: Res<Level>,
+    netplay: Res<Netplay>,
+    unseated: Query<Entity, (With<Player>, Without<NetSeat>)>,
+) {
+    if unseated.is_empty() {
+        return;
+    }
+    let remote = |seat: u8| (seat != netplay.seat).then_some(RemotePlayer);
+    for entity in &unseated {
+        let mut entity = commands.entity(entity);
+        entity.insert((NetSeat(0), PlayerInput::default()));
+        if let Some(remote) = remote(0) {
+            entity.insert(remote);
+        }
+    }
+    if let Some(ball) = level.balls.iter().find(|ball| ball.player) {
+        let mut ball = ball.clone();
+        ball.position.x += SEAT_SPACING;
+        let entity = spawn_ball(&mut commands, &config, &ball);
+        let mut entity = commands.entity(entity);
+        entity.insert((NetSeat(1), PlayerInput::default()));
+        if let Some(remote) = remote(1) {
+            entity.insert(remote);
+        }
+    }
+}
+
+// Sends this frame's input, and simulates the next tick if both inputs for it are in. Otherwise
+// the simulation's held for the frame.
+#[allow(clippy::too_many_arguments)]
+fn netplay_tick_system(
+    state: Res<State<AppState>>,
+    connection: Res<Connection>,
+    input: Res<PlayerInput>,
+    config: Res<PhysicsConfig>,
+    mut netplay: ResMut<Netplay>,
+    mut control: ResMut<SimulationControl>,
+    mut physics_time: ResMut<PhysicsTime>,
+    mut restarts: EventReader<RestartLevelEvent>,
+    unseated: Query<(), (With<Player>, Without<NetSeat>)>,
+    mut players: Query<(&NetSeat, &mut PlayerInput)>,
+) {
+    control.held = true;
+    netplay.simulated = None;
+    // A restart, or seats being handed out, only takes effect at the end of the frame, and
+    // stepping the old bodies until then would happen on one side but not the other
+    let restarting = restarts.iter().last().is_some();
+    if netplay.phase != NetPhase::Running
+        || state.0 != AppState::Playing
+        || restarting
+        || !unseated.is_empty()
+    {
+        return;
+    }
+
+    if let Some(tick) = netplay.buffer.add_local(*input) {
+        connection.send(NetMessage::Input {
+            tick,
+            input: *input,
+        });
+    }
+    let Some((tick, local, remote)) = netplay.buffer.advance() else {
+        return;
+    };
+    for (seat, mut player_input) in &mut players {
+        *player_input = if seat.0 == netplay.seat {
+            local
+        } else {
+            remote
+        };
+    }
+    // The same steps on both sides, however long the frames take
+    physics_time.delta = STEP_DT / config.substeps.max(1) as f32;
+    control.held = false;
+    netplay.simulated = Some(tick);
+}
+
+fn netplay_hash_system(
+    connection: Res<Connection>,
+    mut netplay: ResMut<Netplay>,
+    mut toasts: EventWriter<ToastEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    bodies: Query<(&Transform, &PhysObj)>,
+) {
+    let Some(tick) = netplay.simulated else {
+        return;
+    };
+    if tick % HASH_INTERVAL != 0 {
+        return;
+    }
+    let hash = state_hash(&bodies);
+    connection.send(NetMessage::Hash { tick, hash });
+    if let Some(desync) = netplay.desync.record_local(tick, hash) {
+        let reason = format!("Out of sync since tick {}", desync.tick);
+        stop(&mut netplay, &mut toasts, &mut next_state, reason);
+    }
+}
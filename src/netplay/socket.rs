@@ -0,0 +1,148 @@
+use std::{
+    io,
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use tungstenite::{Error as WsError, Message, WebSocket};
+
+use super::NetMessage;
+
+// How long the transport thread waits for a message before sending what's queued up
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+// Something that happened on the connection, for the game to handle on its next frame
+pub enum NetEvent {
+    Message(NetMessage),
+    Disconnected(String),
+}
+
+// A WebSocket connection to the other player, run on its own thread so the game never waits on
+// the network. Messages are sent as RON text.
+#[derive(Resource)]
+pub struct Connection {
+    outgoing: Mutex<Sender<NetMessage>>,
+    incoming: Mutex<Receiver<NetEvent>>,
+}
+
+impl Connection {
+    // Waits for the other player on `port`, in the background
+    pub fn host(port: u16) -> Self {
+        Self::spawn(move || {
+            let listener =
+                TcpListener::bind(("0.0.0.0", port)).map_err(|error| error.to_string())?;
+            info!("Waiting for the other player on port {port}");
+            let (stream, address) = listener.accept().map_err(|error| error.to_string())?;
+            info!("{address} joined");
+            tungstenite::accept(stream).map_err(|error| error.to_string())
+        })
+    }
+
+    // Connects to a host at `url` (ws://<address>:<port>), in the background
+    pub fn join(url: String) -> Self {
+        Self::spawn(move || {
+            let address = url
+                .strip_prefix("ws://")
+                .ok_or_else(|| format!("{url} isn't a ws:// URL"))?;
+            let address = address.split('/').next().unwrap_or(address);
+            let stream = TcpStream::connect(address).map_err(|error| error.to_string())?;
+            let (socket, _) =
+                tungstenite::client(url.as_str(), stream).map_err(|error| error.to_string())?;
+            Ok(socket)
+        })
+    }
+
+    fn spawn(open: impl FnOnce() -> Result<WebSocket<TcpStream>, String> + Send + 'static) -> Self {
+        let (outgoing, to_send) = mpsc::channel();
+        let (received, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let reason = match open() {
+                Ok(socket) => run(socket, &to_send, &received),
+                Err(error) => error,
+            };
+            received.send(NetEvent::Disconnected(reason)).ok();
+        });
+        Self {
+            outgoing: Mutex::new(outgoing),
+            incoming: Mutex::new(incoming),
+        }
+    }
+
+    pub fn send(&self, message: NetMessage) {
+        // Once the thread's gone, the Disconnected event says why
+        self.outgoing.lock().unwrap().send(message).ok();
+    }
+
+    // Everything that's arrived since the last call
+    pub fn receive(&self) -> Vec<NetEvent> {
+        let incoming = self.incoming.lock().unwrap();
+        let mut events = Vec::new();
+        loop {
+            match incoming.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    events.push(NetEvent::Disconnected("the connection closed".to_string()));
+                    break;
+                }
+            }
+        }
+        events
+    }
+}
+
+// Moves messages between the socket and the channels until either side goes away. Returns why.
+fn run(
+    mut socket: WebSocket<TcpStream>,
+    to_send: &Receiver<NetMessage>,
+    received: &Sender<NetEvent>,
+) -> String {
+    if let Err(error) = socket.get_mut().set_read_timeout(Some(POLL_INTERVAL)) {
+        return error.to_string();
+    }
+    socket.get_mut().set_nodelay(true).ok();
+    loop {
+        loop {
+            let message = match to_send.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    socket.close(None).ok();
+                    return "the game closed the connection".to_string();
+                }
+            };
+            let text = ron::to_string(&message).expect("net messages serialize");
+            if let Err(error) = socket.send(Message::Text(text)) {
+                return error.to_string();
+            }
+        }
+
+        match socket.read() {
+            Ok(Message::Text(text)) => match ron::from_str(&text) {
+                Ok(message) => {
+                    if received.send(NetEvent::Message(message)).is_err() {
+                        return "the game closed the connection".to_string();
+                    }
+                }
+                Err(error) => warn!("Skipping a message that isn't one: {error}"),
+            },
+            Ok(Message::Close(_)) => return "the other player left".to_string(),
+            Ok(_) => {}
+            Err(WsError::Io(error))
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(WsError::ConnectionClosed | WsError::AlreadyClosed) => {
+                return "the other player left".to_string();
+            }
+            Err(error) => return error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+
+use super::{Collider, PhysObj, PhysicsConfig};
+
+// Speeds above this are reported as anomalies
+const MAX_EXPECTED_SPEED: f32 = 20_000.0;
+// Penetration into the floor left after collision resolution that is reported as an anomaly
+const MAX_PENETRATION: f32 = 1.0;
+
+// Something that should never happen in the simulation. Reported instead of panicking so the game
+// keeps running.
+#[derive(Debug, Clone, Copy)]
+pub enum PhysicsAnomaly {
+    // Crossed into the floor after the step started, e.g. in a step of negative length
+    NegativeCollisionDt { entity: Entity, dt: f32 },
+    // In the floor, without having crossed into it during the step
+    MissedCrossing { entity: Entity },
+    NaNVelocity { entity: Entity },
+    PenetrationUnresolved { entity: Entity, depth: f32 },
+    ExcessiveSpeed { entity: Entity, speed: f32 },
+}
+
+impl PhysicsAnomaly {
+    pub fn entity(&self) -> Entity {
+        match *self {
+            PhysicsAnomaly::NegativeCollisionDt { entity, .. }
+            | PhysicsAnomaly::MissedCrossing { entity }
+            | PhysicsAnomaly::NaNVelocity { entity }
+            | PhysicsAnomaly::PenetrationUnresolved { entity, .. }
+            | PhysicsAnomaly::ExcessiveSpeed { entity, .. } => entity,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct AnomalySettings {
+    // Turns anomalies back into panics, e.g. for CI. Defaults to whether the
+    // PANIC_ON_PHYSICS_ANOMALY environment variable is set.
+    pub panic: bool,
+}
+
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        Self {
+            panic: std::env::var_os("PANIC_ON_PHYSICS_ANOMALY").is_some(),
+        }
+    }
+}
+
+// Whether bodies are checked for non-finite values every step. On by default in debug builds.
+#[derive(Resource)]
+pub struct PhysicsValidation {
+    pub enabled: bool,
+}
+
+impl Default for PhysicsValidation {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+        }
+    }
+}
+
+// The most recent state of a body that had only finite values, restored if it gets poisoned
+#[derive(Component)]
+pub struct LastGoodState {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub phys_obj: PhysObj,
+}
+
+pub(super) fn physics_validation_enabled(validation: Res<PhysicsValidation>) -> bool {
+    validation.enabled
+}
+
+pub fn is_finite(transform: &Transform, phys_obj: &PhysObj) -> bool {
+    transform.translation.is_finite()
+        && transform.rotation.is_finite()
+        && phys_obj.vel.is_finite()
+        && phys_obj.acc.is_finite()
+        && phys_obj.angular_vel.is_finite()
+        && phys_obj.angular_acc.is_finite()
+}
+
+// Once a NaN gets into a body it spreads to everything derived from it, so poisoned bodies are
+// rolled back to their last good state right after integration. One poisoned in its first step has
+// no good state to go back to, so it's sanitized instead: stopped, and put back in bounds.
+#[allow(clippy::type_complexity)]
+pub(super) fn validation_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    mut anomalies: EventWriter<PhysicsAnomaly>,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &mut PhysObj,
+        Option<&Collider>,
+        Option<&mut LastGoodState>,
+    )>,
+) {
+    for (entity, mut transform, mut phys_obj, collider, last_good_state) in &mut query {
+        let finite = is_finite(&transform, &phys_obj);
+        match (finite, last_good_state) {
+            (true, Some(mut last_good_state)) => {
+                last_good_state.translation = transform.translation;
+                last_good_state.rotation = transform.rotation;
+                last_good_state.phys_obj = phys_obj.clone();
+            }
+            (true, None) => {
+                commands.entity(entity).insert(LastGoodState {
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    phys_obj: phys_obj.clone(),
+                });
+            }
+            (false, last_good_state) => {
+                anomalies.send(PhysicsAnomaly::NaNVelocity { entity });
+                match last_good_state {
+                    Some(last_good_state) => {
+                        transform.translation = last_good_state.translation;
+                        transform.rotation = last_good_state.rotation;
+                        *phys_obj = last_good_state.phys_obj.clone();
+                    }
+                    None => {
+                        sanitize(&mut phys_obj);
+                        phys_obj.vel = Vec2::ZERO;
+                        phys_obj.angular_vel = 0.0;
+                        clamp_into_bounds(&config, &mut transform, collider);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Non-finite coordinates of a body's place go to 0 and a non-finite rotation to none, and it's
+// lifted out of the floor
+fn clamp_into_bounds(
+    config: &PhysicsConfig,
+    transform: &mut Transform,
+    collider: Option<&Collider>,
+) {
+    let finite_or_zero = |x: f32| if x.is_finite() { x } else { 0.0 };
+    let radius = collider.map_or(0.0, |&Collider::Ball { radius, .. }| radius);
+    let translation = &mut transform.translation;
+    translation.x = finite_or_zero(translation.x);
+    translation.y = finite_or_zero(translation.y).max(config.floor_y + radius);
+    translation.z = finite_or_zero(translation.z);
+    if !transform.rotation.is_finite() {
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+pub(super) fn anomaly_detection_system(
+    config: Res<PhysicsConfig>,
+    mut anomalies: EventWriter<PhysicsAnomaly>,
+    query: Query<(Entity, &Transform, &PhysObj, Option<&Collider>)>,
+) {
+    for (entity, transform, phys_obj, collider) in &query {
+        if !phys_obj.vel.is_finite() || !phys_obj.angular_vel.is_finite() {
+            anomalies.send(PhysicsAnomaly::NaNVelocity { entity });
+            continue;
+        }
+
+        let speed = phys_obj.vel.length();
+        if speed > MAX_EXPECTED_SPEED {
+            anomalies.send(PhysicsAnomaly::ExcessiveSpeed { entity, speed });
+        }
+
+        // Balls over a gap in the floor are free to fall through it
+        if let Some(Collider::Ball { radius, .. }) = collider {
+            if config.over_gap(transform.translation.x) {
+                continue;
+            }
+            let depth = config.floor_y - (transform.translation.y - radius);
+            if depth > MAX_PENETRATION {
+                anomalies.send(PhysicsAnomaly::PenetrationUnresolved { entity, depth });
+            }
+        }
+    }
+}
+
+pub(super) fn anomaly_handler_system(
+    settings: Res<AnomalySettings>,
+    mut anomalies: EventReader<PhysicsAnomaly>,
+    mut query: Query<&mut PhysObj>,
+) {
+    for anomaly in anomalies.iter() {
+        if settings.panic {
+            panic!("Physics anomaly: {anomaly:?}");
+        }
+        warn!("Physics anomaly: {anomaly:?}");
+
+        if let PhysicsAnomaly::NaNVelocity { .. } = anomaly {
+            if let Ok(mut phys_obj) = query.get_mut(anomaly.entity()) {
+                sanitize(&mut phys_obj);
+            }
+        }
+    }
+}
+
+// Zeroes every non-finite value of a body
+pub fn sanitize(phys_obj: &mut PhysObj) {
+    let sanitize_vec = |v: Vec2| {
+        Vec2::new(
+            if v.x.is_finite() { v.x } else { 0.0 },
+            if v.y.is_finite() { v.y } else { 0.0 },
+        )
+    };
+    let sanitize_f32 = |x: f32| if x.is_finite() { x } else { 0.0 };
+
+    phys_obj.vel = sanitize_vec(phys_obj.vel);
+    phys_obj.acc = sanitize_vec(phys_obj.acc);
+    phys_obj.acc_prev = sanitize_vec(phys_obj.acc_prev);
+    phys_obj.angular_vel = sanitize_f32(phys_obj.angular_vel);
+    phys_obj.angular_acc = sanitize_f32(phys_obj.angular_acc);
+    phys_obj.angular_acc_prev = sanitize_f32(phys_obj.angular_acc_prev);
+}
@@ -0,0 +1,708 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::level::SurfaceMaterial;
+
+use super::{
+    anomaly::PhysicsAnomaly,
+    contacts::BallPairs,
+    integrator::integrate_simple,
+    sleep::{Sleeping, StaticFloors},
+    timings::{record_timing, PhysicsTimings},
+    BounceEvent, Collider, CollisionEvent, LandedEvent, PhysObj, PhysicsConfig, PhysicsTime,
+};
+
+// Buffers the collision pipeline fills every step. They're cleared rather than dropped, so once
+// they've grown to fit the scene, steps don't allocate.
+#[derive(Resource, Default)]
+pub struct CollisionScratch {
+    pub contacts: FloorContacts,
+    pub pairs: BallPairs,
+}
+
+// Which bodies the floor affects this step, in query order
+#[derive(Default)]
+pub struct FloorContacts {
+    // At or below the floor
+    pub touching: Vec<Entity>,
+    // Above the floor, but touching it during the last step
+    pub left: Vec<Entity>,
+}
+
+// Sorts the bodies by whether they reach the floor at `floor_y`. Writes into `contacts`, replacing
+// what was there before.
+pub fn find_floor_contacts<'a>(
+    floor_y: f32,
+    bodies: impl IntoIterator<Item = (Entity, &'a Transform, &'a Collider)>,
+    contacts: &mut FloorContacts,
+) {
+    contacts.touching.clear();
+    contacts.left.clear();
+    for (entity, transform, collider) in bodies {
+        let Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        } = *collider;
+        if transform.translation.y - radius <= floor_y {
+            contacts.touching.push(entity);
+        } else if touching_ground {
+            contacts.left.push(entity);
+        }
+    }
+}
+
+pub fn collision_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut scratch: ResMut<CollisionScratch>,
+    mut writers: CollisionWriters,
+    mut query: Query<(Entity, &mut Transform, &mut PhysObj, &mut Collider), Without<Sleeping>>,
+    floors: StaticFloors,
+) {
+    let _span = info_span!("physics_narrow_phase").entered();
+    let start = timings.start();
+
+    let contacts = &mut scratch.contacts;
+    let over_gap = |transform: &Transform| config.over_gap(transform.translation.x);
+    find_floor_contacts(
+        config.floor_y,
+        query
+            .iter()
+            .filter(|(_, transform, ..)| !over_gap(transform))
+            .map(|(entity, transform, _, collider)| (entity, transform, collider)),
+        contacts,
+    );
+    // Over a gap there's nothing to touch, so balls that were touching the floor have left it
+    if !config.floor_gaps.is_empty() {
+        for (entity, transform, _, collider) in &query {
+            let Collider::Ball {
+                touching_ground, ..
+            } = *collider;
+            if touching_ground && over_gap(transform) {
+                contacts.left.push(entity);
+            }
+        }
+    }
+
+    let dt = time.delta;
+    for &entity in &contacts.touching {
+        let Ok((entity, mut transform, mut phys_obj, mut collider)) = query.get_mut(entity) else {
+            continue;
+        };
+        let Collider::Ball {
+            touching_ground: was_touching_ground,
+            ..
+        } = *collider;
+        let trampoline = match floors.surface_below(transform.translation.truncate()) {
+            Some(SurfaceMaterial::Trampoline {
+                restitution_override,
+                min_launch_speed,
+            }) => Some(Trampoline {
+                restitution: restitution_override,
+                min_launch_speed,
+            }),
+            _ => None,
+        };
+        let mut events = CollisionEvents {
+            entity,
+            writers: &mut writers,
+            first_impact_speed: None,
+        };
+        while resolve_collision(
+            dt,
+            &config,
+            trampoline,
+            &mut transform,
+            &mut phys_obj,
+            &mut collider,
+            &mut events,
+        ) {}
+
+        // Sent once at the end, however many bounces it took to land. Trampolines throw balls back
+        // up instead.
+        let Collider::Ball {
+            touching_ground, ..
+        } = *collider;
+        if let (false, true, Some(impact_speed)) = (
+            was_touching_ground,
+            touching_ground,
+            events.first_impact_speed,
+        ) {
+            writers.landed.send(LandedEvent {
+                entity,
+                impact_speed,
+                surface_y: config.floor_y,
+            });
+        }
+    }
+
+    for &entity in &contacts.left {
+        if let Ok((_, _, _, mut collider)) = query.get_mut(entity) {
+            let Collider::Ball {
+                touching_ground, ..
+            } = &mut *collider;
+            *touching_ground = false;
+        }
+    }
+
+    record_timing(start, &mut timings.narrow_phase);
+}
+
+#[derive(SystemParam)]
+pub struct CollisionWriters<'w> {
+    anomalies: EventWriter<'w, PhysicsAnomaly>,
+    collisions: EventWriter<'w, CollisionEvent>,
+    bounces: EventWriter<'w, BounceEvent>,
+    landed: EventWriter<'w, LandedEvent>,
+}
+
+// Where the collision code reports what happened to the body it's resolving
+struct CollisionEvents<'a, 'w> {
+    entity: Entity,
+    writers: &'a mut CollisionWriters<'w>,
+    first_impact_speed: Option<f32>,
+}
+
+impl CollisionEvents<'_, '_> {
+    fn impact(&mut self, mass: f32, impact_speed: f32, normal_impulse: f32, restitution: f32) {
+        self.first_impact_speed.get_or_insert(impact_speed);
+        self.writers.collisions.send(CollisionEvent {
+            entity: self.entity,
+            impulse: mass * normal_impulse,
+        });
+        self.writers.bounces.send(BounceEvent {
+            entity: self.entity,
+            impact_speed,
+            restitution_applied: restitution,
+        });
+    }
+}
+
+// A trampoline under a ball, see SurfaceMaterial::Trampoline
+#[derive(Clone, Copy)]
+struct Trampoline {
+    restitution: f32,
+    min_launch_speed: f32,
+}
+
+impl Trampoline {
+    // How fast a ball leaving the trampoline at `vel_y` goes up, at most `max_speed`
+    fn launch(self, vel_y: f32, max_speed: f32) -> f32 {
+        vel_y.max(self.min_launch_speed).min(max_speed)
+    }
+}
+
+// The floor as seen by a single ball, with the coefficients of both combined
+struct Contact {
+    floor_y: f32,
+    // See PhysicsConfig::floor_velocity
+    surface_velocity: f32,
+    restitution: f32,
+    friction: f32,
+    restitution_velocity_threshold: f32,
+    // Throws the ball back up, at most `max_bounce_speed`
+    trampoline: Option<Trampoline>,
+    max_bounce_speed: f32,
+}
+
+impl Contact {
+    // Impacts slower than the threshold don't bounce at all
+    fn restitution(&self, impact_vel: f32) -> f32 {
+        if impact_vel.abs() < self.restitution_velocity_threshold {
+            0.0
+        } else {
+            self.restitution
+        }
+    }
+
+    // resolve_contact in the frame of the floor's surface, and only if the ball's coming towards
+    // it. A ball that's already leaving the floor (one that was in it without crossing into it)
+    // would otherwise be pulled back in. Trampolines launch the ball as it bounces, so it doesn't
+    // go any faster than that for the rest of the step.
+    fn resolve(&self, phys_obj: &mut PhysObj, point: &ContactPoint, restitution: f32) -> f32 {
+        let contact_vel = phys_obj.vel + phys_obj.angular_vel * point.offset.perp();
+        if contact_vel.dot(point.normal) >= 0.0 {
+            return 0.0;
+        }
+        phys_obj.vel.x -= self.surface_velocity;
+        let normal_impulse = resolve_contact(phys_obj, point, restitution, self.friction);
+        phys_obj.vel.x += self.surface_velocity;
+        if let Some(trampoline) = self.trampoline {
+            phys_obj.vel.y = trampoline.launch(phys_obj.vel.y, self.max_bounce_speed);
+        }
+        normal_impulse
+    }
+}
+
+// Where a body touches something immovable
+pub struct ContactPoint {
+    // From the body's center of mass to the point of contact
+    pub offset: Vec2,
+    // Unit normal of the surface, pointing towards the body
+    pub normal: Vec2,
+}
+
+impl ContactPoint {
+    // The bottom of a ball resting on the floor
+    pub fn below(radius: f32) -> Self {
+        Self {
+            offset: Vec2::new(0.0, -radius),
+            normal: Vec2::Y,
+        }
+    }
+
+    // Measures the offset from a center of mass `com_arm` away from the point it was measured from
+    pub fn with_com_offset(self, com_arm: Vec2) -> Self {
+        Self {
+            offset: self.offset - com_arm,
+            ..self
+        }
+    }
+}
+
+// Resolves an impact at `contact`: an impulse along the normal that leaves the contact point
+// separating at `restitution` times the speed it approached with, then a friction impulse of up
+// to `friction` times that, which stops the contact point from sliding if it's enough. Both act
+// through the contact point, so impacts off the center of mass also change the angular velocity.
+// Returns the normal impulse divided by the body's mass.
+//
+// For a ball on the floor the offset is parallel to the normal, so the normal impulse doesn't spin
+// the ball and this reduces to a plain bounce plus apply_friction_impulse.
+pub fn resolve_contact(
+    phys_obj: &mut PhysObj,
+    contact: &ContactPoint,
+    restitution: f32,
+    friction: f32,
+) -> f32 {
+    let ContactPoint { offset, normal } = *contact;
+    let tangent = Vec2::new(normal.y, -normal.x);
+    let contact_vel = |phys_obj: &PhysObj| phys_obj.vel + phys_obj.angular_vel * offset.perp();
+    // Velocity change of the contact point per unit of velocity change of the center of mass, for
+    // an impulse along `direction`
+    let response = |phys_obj: &PhysObj, direction: Vec2| {
+        1.0 + phys_obj.mass * offset.perp_dot(direction).powi(2) / phys_obj.moment_of_inertia
+    };
+    let apply = |phys_obj: &mut PhysObj, direction: Vec2, impulse: f32| {
+        phys_obj.vel += impulse * direction;
+        phys_obj.angular_vel +=
+            offset.perp_dot(direction) * impulse * phys_obj.mass / phys_obj.moment_of_inertia;
+    };
+
+    let normal_vel = contact_vel(phys_obj).dot(normal);
+    let normal_impulse = -normal_vel * (1.0 + restitution) / response(phys_obj, normal);
+    apply(phys_obj, normal, normal_impulse);
+
+    let tangent_vel = contact_vel(phys_obj).dot(tangent);
+    let stopping_impulse = tangent_vel.abs() / response(phys_obj, tangent);
+    let friction_impulse =
+        f32::min(normal_impulse * friction, stopping_impulse).copysign(-tangent_vel);
+    apply(phys_obj, tangent, friction_impulse);
+
+    normal_impulse
+}
+
+fn resolve_collision(
+    dt: f32,
+    config: &PhysicsConfig,
+    trampoline: Option<Trampoline>,
+    transform: &mut Transform,
+    phys_obj: &mut PhysObj,
+    collider: &mut Collider,
+    events: &mut CollisionEvents,
+) -> bool {
+    // Rolled onto a trampoline from the floor next to it
+    if let (
+        Some(trampoline),
+        Collider::Ball {
+            radius,
+            touching_ground: true,
+            ..
+        },
+    ) = (trampoline, *collider)
+    {
+        transform.translation.y = config.floor_y + radius;
+        phys_obj.vel.y = trampoline.launch(phys_obj.vel.y, config.max_bounce_speed);
+        let Collider::Ball {
+            touching_ground, ..
+        } = collider;
+        *touching_ground = false;
+        return false;
+    }
+
+    match *collider {
+        Collider::Ball {
+            radius,
+            touching_ground: true,
+            ..
+        } => {
+            transform.translation.y = config.floor_y + radius;
+            // The ball's center stays on the floor, so a center of mass off to one side rises and
+            // falls as it turns
+            let arm = phys_obj.com_arm(transform.rotation);
+            phys_obj.vel.y = if arm == Vec2::ZERO {
+                0.0
+            } else {
+                phys_obj.angular_vel * arm.x
+            };
+            false
+        }
+        Collider::Ball {
+            radius,
+            coef_of_restitution,
+            ref mut touching_ground,
+            kinetic_friction,
+            ..
+        } => {
+            // Bouncing off a trampoline doesn't land the ball on it
+            *touching_ground = trampoline.is_none();
+            let contact = Contact {
+                floor_y: config.floor_y,
+                surface_velocity: config.floor_velocity,
+                restitution: match trampoline {
+                    Some(trampoline) => trampoline.restitution,
+                    None => config
+                        .restitution_combine
+                        .combine(coef_of_restitution, config.floor_restitution),
+                },
+                friction: config.friction(kinetic_friction),
+                restitution_velocity_threshold: config.restitution_velocity_threshold,
+                trampoline,
+                max_bounce_speed: config.max_bounce_speed,
+            };
+            bounce(dt, transform, phys_obj, radius, &contact, events)
+        }
+    }
+}
+
+fn bounce(
+    dt: f32,
+    transform: &mut Transform,
+    phys_obj: &mut PhysObj,
+    radius: f32,
+    contact: &Contact,
+    events: &mut CollisionEvents,
+) -> bool {
+    // A ball in the floor got there somehow, so not finding when it crossed into it is a solver
+    // bug, as is finding that it crossed after the step started. It gets reported and the collision
+    // is resolved where the ball currently is.
+    let mut check_collision_dt = |collision_dt: Option<f32>| {
+        let anomaly = match collision_dt {
+            Some(collision_dt) if collision_dt >= 0.0 => return collision_dt,
+            Some(collision_dt) => PhysicsAnomaly::NegativeCollisionDt {
+                entity: events.entity,
+                dt: collision_dt,
+            },
+            None => PhysicsAnomaly::MissedCrossing {
+                entity: events.entity,
+            },
+        };
+        events.writers.anomalies.send(anomaly);
+        0.0
+    };
+
+    let (s, v, a) = (
+        (transform.translation.y - radius) - contact.floor_y,
+        phys_obj.vel.y,
+        phys_obj.acc.y,
+    );
+    let collision_dt = calculate_collision_dt(s, v, a);
+
+    if collision_dt.is_none_or(|collision_dt| collision_dt > 0.5 * dt) {
+        integrate_simple(-0.5 * dt, transform, phys_obj);
+
+        let (s, v, a) = (
+            (transform.translation.y - radius) - contact.floor_y,
+            phys_obj.vel.y,
+            phys_obj.acc_prev.y,
+        );
+        // It can't have crossed into the floor before the step started. If it was in the floor
+        // already, the collision's resolved at the start of the step instead.
+        let collision_dt2 = check_collision_dt(
+            calculate_collision_dt(s, v, a).map(|collision_dt| collision_dt.min(0.5 * dt)),
+        );
+
+        (phys_obj.acc, phys_obj.acc_prev) = (phys_obj.acc_prev, phys_obj.acc); // Don't try this at home (bad code)
+        integrate_simple(-collision_dt2, transform, phys_obj);
+
+        let impact_speed = phys_obj.vel.y.abs();
+        let restitution = contact.restitution(phys_obj.vel.y);
+        let point =
+            ContactPoint::below(radius).with_com_offset(phys_obj.com_arm(transform.rotation));
+        let normal_impulse = contact.resolve(phys_obj, &point, restitution);
+        events.impact(phys_obj.mass, impact_speed, normal_impulse, restitution);
+
+        integrate_simple(collision_dt2, transform, phys_obj);
+        (phys_obj.acc, phys_obj.acc_prev) = (phys_obj.acc_prev, phys_obj.acc); // Don't try this at home (bad code)
+
+        integrate_simple(0.5 * dt, transform, phys_obj);
+    } else {
+        let collision_dt = check_collision_dt(collision_dt);
+        integrate_simple(-collision_dt, transform, phys_obj);
+
+        let impact_speed = phys_obj.vel.y.abs();
+        let restitution = contact.restitution(phys_obj.vel.y);
+        let point =
+            ContactPoint::below(radius).with_com_offset(phys_obj.com_arm(transform.rotation));
+        let normal_impulse = contact.resolve(phys_obj, &point, restitution);
+        events.impact(phys_obj.mass, impact_speed, normal_impulse, restitution);
+
+        integrate_simple(collision_dt, transform, phys_obj);
+    }
+
+    // A bounce too weak to get back out of the floor by the end of the step leaves the ball on it
+    if transform.translation.y - radius < contact.floor_y {
+        transform.translation.y = contact.floor_y + radius;
+        phys_obj.vel.y = phys_obj.vel.y.max(0.0);
+    }
+    false // TODO: Calculate time until bouncing stops and proceed as follows:
+          //    - If that time is less than the time step, approximate behavior that results in
+          //        the ball laying/sliding on the ground at the end of the frame.
+          //    - Otherwise, bounce once and return whether another bounce will happen during the frame.
+          // Written out this seems like a bad way to do it... That's a problem for another day.
+}
+
+// How long ago a body `s` above the floor, moving at `v` and accelerating at `a`, was last at the
+// floor, i.e. the smallest t >= 0 with s - v·t + a·t²/2 = 0. None if it never was.
+pub fn calculate_collision_dt(s: f32, v: f32, a: f32) -> Option<f32> {
+    let discriminant = v.powi(2) - 2.0 * a * s;
+    if discriminant < 0.0 {
+        return None;
+    }
+    // The roots are q / a and 2s / q. Written like this neither subtracts two nearly equal numbers,
+    // and a = 0 or q = 0 just makes one of them infinite or NaN.
+    let q = v + discriminant.sqrt().copysign(v);
+    [q / a, 2.0 * s / q]
+        .into_iter()
+        .filter(|t| *t >= 0.0 && t.is_finite())
+        .min_by(f32::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::physics::CombineRule;
+
+    const DT: f32 = 1.0 / 60.0;
+    const GRAVITY: f32 = -1000.0;
+    const RADIUS: f32 = 10.0;
+
+    fn ball(restitution: f32) -> Collider {
+        Collider::Ball {
+            radius: RADIUS,
+            coef_of_restitution: restitution,
+            touching_ground: false,
+            kinetic_friction: 0.5,
+            friction_acc: 0.0,
+            friction_acc_prev: 0.0,
+        }
+    }
+
+    // Where a ball `height` above the floor at `vel` is a step later, with gravity and no floor
+    fn falling(config: &PhysicsConfig, height: f32, vel: f32) -> (Transform, PhysObj) {
+        let t = DT;
+        let transform = Transform::from_xyz(
+            0.0,
+            config.floor_y + RADIUS + height + vel * t + 0.5 * GRAVITY * t * t,
+            0.0,
+        );
+        let phys_obj = PhysObj {
+            mass: 1.0,
+            vel: Vec2::new(0.0, vel + GRAVITY * t),
+            acc: Vec2::new(0.0, GRAVITY),
+            acc_prev: Vec2::new(0.0, GRAVITY),
+            moment_of_inertia: 0.5 * RADIUS * RADIUS,
+            angular_vel: 0.0,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: Vec2::ZERO,
+        };
+        (transform, phys_obj)
+    }
+
+    // A world with just the events the collision code sends
+    fn event_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<PhysicsAnomaly>>();
+        world.init_resource::<Events<CollisionEvent>>();
+        world.init_resource::<Events<BounceEvent>>();
+        world.init_resource::<Events<LandedEvent>>();
+        world
+    }
+
+    fn resolve(
+        world: &mut World,
+        config: &PhysicsConfig,
+        transform: &mut Transform,
+        phys_obj: &mut PhysObj,
+        collider: &mut Collider,
+    ) {
+        let mut state = SystemState::<CollisionWriters>::new(world);
+        let mut writers = state.get_mut(world);
+        let mut events = CollisionEvents {
+            entity: Entity::from_raw(0),
+            writers: &mut writers,
+            first_impact_speed: None,
+        };
+        while resolve_collision(DT, config, None, transform, phys_obj, collider, &mut events) {}
+    }
+
+    // The first t >= 0 at which s - v·t + a·t²/2 crosses zero
+    #[test]
+    fn collision_dt_is_the_earliest_root() {
+        let close = |found: Option<f32>, expected: f32| {
+            let found = found.unwrap();
+            assert!((found - expected).abs() < 1e-5, "{found} {expected}");
+        };
+        // Falling onto the floor: one root in the past, one in the future
+        close(
+            calculate_collision_dt(5.0, -10.0, -20.0),
+            (10.0 + 300f32.sqrt()) / 20.0,
+        );
+        // Both in the past: 2·(t - 1)·(t - 2) / 2
+        close(calculate_collision_dt(2.0, 3.0, 2.0), 1.0);
+        // Starting from rest in the floor
+        close(calculate_collision_dt(-1.0, 0.0, 2.0), 1.0);
+        // Never at the floor
+        assert_eq!(calculate_collision_dt(5.0, 0.0, 20.0), None);
+        assert_eq!(calculate_collision_dt(5.0, -1.0, 0.0), None);
+    }
+
+    // With no acceleration the quadratic degenerates to s - v·t = 0, and q / a is infinite or NaN
+    #[test]
+    fn collision_dt_without_acceleration() {
+        assert_eq!(calculate_collision_dt(10.0, 5.0, 0.0), Some(2.0));
+        assert_eq!(calculate_collision_dt(0.0, 5.0, 0.0), Some(0.0));
+        assert_eq!(calculate_collision_dt(0.0, 0.0, 0.0), None);
+        for a in [1e-12, -1e-12, 1e-30] {
+            let found = calculate_collision_dt(10.0, 5.0, a).unwrap();
+            assert!((found - 2.0).abs() < 1e-5, "{a}: {found}");
+        }
+    }
+
+    // A dead ball that fell into the floor during the step ends it resting on the floor, however
+    // far into the step it hit
+    #[test]
+    fn dead_ball_lands_on_the_floor() {
+        let config = PhysicsConfig::default();
+        for height in [1.0, 3.0] {
+            let mut world = event_world();
+            let (mut transform, mut phys_obj) = falling(&config, height, -200.0);
+            assert!(transform.translation.y - RADIUS < config.floor_y);
+            let mut collider = ball(0.0);
+            resolve(
+                &mut world,
+                &config,
+                &mut transform,
+                &mut phys_obj,
+                &mut collider,
+            );
+
+            assert_eq!(transform.translation.y, config.floor_y + RADIUS);
+            assert_eq!(phys_obj.vel.y, 0.0);
+            let Collider::Ball {
+                touching_ground, ..
+            } = collider;
+            assert!(touching_ground);
+            assert!(world.resource::<Events<PhysicsAnomaly>>().is_empty());
+        }
+    }
+
+    // A step of negative length has the ball cross into the floor after it started. That's
+    // reported, and the ball is still left on the floor rather than integrated back through it.
+    #[test]
+    fn negative_collision_dt_is_reported() {
+        let config = PhysicsConfig::default();
+        let mut world = event_world();
+        let (mut transform, mut phys_obj) = falling(&config, 1.0, -200.0);
+        let mut collider = ball(0.5);
+        let mut state = SystemState::<CollisionWriters>::new(&mut world);
+        let mut writers = state.get_mut(&mut world);
+        let mut events = CollisionEvents {
+            entity: Entity::from_raw(0),
+            writers: &mut writers,
+            first_impact_speed: None,
+        };
+        resolve_collision(
+            -DT,
+            &config,
+            None,
+            &mut transform,
+            &mut phys_obj,
+            &mut collider,
+            &mut events,
+        );
+
+        let anomalies: Vec<_> = world
+            .resource_mut::<Events<PhysicsAnomaly>>()
+            .drain()
+            .collect();
+        assert!(
+            matches!(
+                anomalies[..],
+                [PhysicsAnomaly::NegativeCollisionDt { dt, .. }] if dt < 0.0
+            ),
+            "{anomalies:?}"
+        );
+        assert!(transform.translation.y - RADIUS >= config.floor_y);
+        assert!(phys_obj.vel.is_finite());
+    }
+
+    // A bounce mid-step comes out where the closed form of free fall puts it: down to the floor,
+    // reflected at the restitution, and back up for the rest of the step. Hitting the floor 1 up
+    // is early in the step and 3 up late, which bounce handles separately.
+    #[test]
+    fn bounce_follows_the_closed_form() {
+        let config = PhysicsConfig {
+            floor_restitution: 1.0,
+            restitution_combine: CombineRule::Multiply,
+            ..default()
+        };
+        for (height, restitution) in [(1.0, 1.0), (1.0, 0.5), (3.0, 1.0), (3.0, 0.5)] {
+            let mut world = event_world();
+            let vel = -200.0;
+            let (mut transform, mut phys_obj) = falling(&config, height, vel);
+            let mut collider = ball(restitution);
+            resolve(
+                &mut world,
+                &config,
+                &mut transform,
+                &mut phys_obj,
+                &mut collider,
+            );
+
+            // height + vel·t + g·t²/2 = 0
+            let g = GRAVITY;
+            let t = (-vel - (vel * vel - 2.0 * g * height).sqrt()) / g;
+            let impact_vel = vel + g * t;
+            let rest = DT - t;
+            let up = -restitution * impact_vel;
+            let expected_height = up * rest + 0.5 * g * rest * rest;
+            let expected_vel = up + g * rest;
+            let bottom = transform.translation.y - RADIUS - config.floor_y;
+            assert!(
+                (bottom - expected_height).abs() < 1e-3,
+                "{height} {restitution}: {bottom} {expected_height}"
+            );
+            assert!(
+                (phys_obj.vel.y - expected_vel).abs() < 1e-2,
+                "{height} {restitution}: {} {expected_vel}",
+                phys_obj.vel.y
+            );
+
+            let bounces: Vec<_> = world
+                .resource_mut::<Events<BounceEvent>>()
+                .drain()
+                .collect();
+            assert_eq!(bounces.len(), 1);
+            assert!((bounces[0].impact_speed + impact_vel).abs() < 1e-2);
+            assert_eq!(bounces[0].restitution_applied, restitution);
+            let Collider::Ball {
+                touching_ground, ..
+            } = collider;
+            assert!(touching_ground);
+        }
+    }
+}
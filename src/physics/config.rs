@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Tunables of the simulation. Read by the physics systems every step, so changes take effect on
+// the next frame. Missing fields (e.g. in older replays) get their defaults.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    // Height of the ground surface
+    pub floor_y: f32,
+    // Gravity given to new bodies
+    pub gravity: f32,
+    // Physics steps per frame, each advancing an equal part of the frame time
+    pub substeps: u32,
+    // Longest frame time that is simulated. Longer frames (e.g. after a hitch) are slowed down
+    // instead of taking one huge step.
+    pub max_dt: f32,
+    // Impacts slower than this don't bounce
+    pub restitution_velocity_threshold: f32,
+    pub floor_restitution: f32,
+    pub floor_friction: f32,
+    // Horizontal velocity of the floor's surface, like a conveyor belt. The floor stays where it
+    // is, but friction works against the ball's motion relative to the surface.
+    pub floor_velocity: f32,
+    // Stretches of the floor that aren't there, from their left edge to their right. Balls whose
+    // center is over one fall through.
+    pub floor_gaps: Vec<(f32, f32)>,
+    // How a body's coefficients are combined with the floor's
+    pub restitution_combine: CombineRule,
+    pub friction_combine: CombineRule,
+    // Resistance to rolling on the floor, as a fraction of the normal force acting at the ball's
+    // radius. Zero lets balls roll forever.
+    pub rolling_resistance: f32,
+    // Trampolines (see SurfaceMaterial::Trampoline) never throw balls up faster than this, so
+    // bounces that add energy can't run away
+    pub max_bounce_speed: f32,
+    // Times per step the velocities of touching balls are resolved. More let impulses travel
+    // further through piles.
+    pub contact_iterations: u32,
+    // Fraction of the overlap between balls removed by each position correction, and the times
+    // per step it's applied. Overlaps up to `penetration_slop` are left alone, so that resting
+    // contacts stay touching instead of jittering.
+    pub position_correction: f32,
+    pub position_iterations: u32,
+    pub penetration_slop: f32,
+    // Times per step all the joints are solved. More keep long chains from stretching.
+    pub joint_iterations: u32,
+    // Runs the integrator and gravity on one thread. Their results don't depend on it, but
+    // determinism-sensitive runs (replays) use it to rule out the thread pool.
+    pub serial: bool,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            floor_y: -360.0,
+            gravity: 2000.0,
+            substeps: 1,
+            max_dt: f32::INFINITY,
+            restitution_velocity_threshold: 0.0,
+            floor_restitution: 1.0,
+            floor_friction: 1.0,
+            floor_velocity: 0.0,
+            floor_gaps: Vec::new(),
+            restitution_combine: CombineRule::Multiply,
+            friction_combine: CombineRule::Multiply,
+            rolling_resistance: 0.0,
+            max_bounce_speed: 1800.0,
+            contact_iterations: 4,
+            position_correction: 0.7,
+            position_iterations: 8,
+            penetration_slop: 0.5,
+            joint_iterations: 16,
+            serial: false,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    // Friction coefficient between the floor and a body with `kinetic_friction`
+    pub fn friction(&self, kinetic_friction: f32) -> f32 {
+        self.friction_combine
+            .combine(kinetic_friction, self.floor_friction)
+    }
+
+    // Whether there's no floor under `x` (see `floor_gaps`)
+    pub fn over_gap(&self, x: f32) -> bool {
+        self.floor_gaps
+            .iter()
+            .any(|&(left, right)| (left..=right).contains(&x))
+    }
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum CombineRule {
+    Average,
+    Min,
+    Max,
+    #[default]
+    Multiply,
+}
+
+impl CombineRule {
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            CombineRule::Average => 0.5 * (a + b),
+            CombineRule::Min => a.min(b),
+            CombineRule::Max => a.max(b),
+            CombineRule::Multiply => a * b,
+        }
+    }
+}
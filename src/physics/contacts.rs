@@ -0,0 +1,415 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    collision::CollisionScratch,
+    sleep::Sleeping,
+    timings::{record_timing, PhysicsTimings},
+    Collider, PhysObj, PhysicsConfig,
+};
+
+// Overlapping pairs of balls, found by sorting the balls along x and sweeping over them, and the
+// contacts between them
+#[derive(Default)]
+pub struct BallPairs {
+    // Ball extents along x, sorted by their left edge
+    pub sweep: Vec<SweepEntry>,
+    pub pairs: Vec<(Entity, Entity)>,
+    pub contacts: Vec<BallContact>,
+    // The impulses each contact ended the last step with, to start the next step's solve from
+    pub warm_start: HashMap<ContactKey, AccumulatedImpulse>,
+}
+
+// Identifies a contact across steps: the bodies in a fixed order, and which of their features
+// touch. Balls only have the one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ContactKey {
+    pub a: Entity,
+    pub b: Entity,
+    pub feature: u32,
+}
+
+impl ContactKey {
+    pub fn new(a: Entity, b: Entity, feature: u32) -> Self {
+        let (a, b) = if a.to_bits() <= b.to_bits() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        Self { a, b, feature }
+    }
+}
+
+// Impulses applied at a contact so far, along the normal and the tangent
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccumulatedImpulse {
+    pub normal: f32,
+    pub tangent: f32,
+}
+
+// A contact between two balls, set up once per step and then solved iteratively
+pub struct BallContact {
+    pub key: ContactKey,
+    pub a: Entity,
+    pub b: Entity,
+    // From `a` to `b`
+    pub normal: Vec2,
+    pub radius_a: f32,
+    pub radius_b: f32,
+    // Each ball's center of mass relative to its center, in world space (see PhysObj::com_arm)
+    pub arm_a: Vec2,
+    pub arm_b: Vec2,
+    // Zero for a ball that can't be pushed this way (see inverse_mass)
+    pub inverse_mass_a: f32,
+    pub inverse_mass_b: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    // Normal velocity the contact should separate with, from restitution
+    pub target_normal_vel: f32,
+    pub impulse: AccumulatedImpulse,
+}
+
+// Adds `delta` to an accumulated impulse, keeping the total within `min..=max`. Returns the part of
+// `delta` that was actually added. Clamping the total rather than each iteration's delta lets later
+// iterations take back what earlier ones overdid.
+pub fn accumulate_impulse(accumulated: &mut f32, delta: f32, min: f32, max: f32) -> f32 {
+    let previous = *accumulated;
+    *accumulated = (previous + delta).clamp(min, max);
+    *accumulated - previous
+}
+
+#[derive(Clone, Copy)]
+pub struct SweepEntry {
+    pub entity: Entity,
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl SweepEntry {
+    fn left(&self) -> f32 {
+        self.center.x - self.radius
+    }
+}
+
+// Finds the overlapping pairs among `balls` (entity, center, radius). Writes into `buffers`,
+// replacing what was there before.
+pub fn find_ball_pairs(
+    balls: impl IntoIterator<Item = (Entity, Vec2, f32)>,
+    buffers: &mut BallPairs,
+) {
+    let BallPairs { sweep, pairs, .. } = buffers;
+    sweep.clear();
+    pairs.clear();
+    sweep.extend(
+        balls
+            .into_iter()
+            .map(|(entity, center, radius)| SweepEntry {
+                entity,
+                center,
+                radius,
+            }),
+    );
+    sweep.sort_unstable_by(|a, b| a.left().total_cmp(&b.left()));
+
+    for (i, a) in sweep.iter().enumerate() {
+        for b in &sweep[i + 1..] {
+            if b.left() > a.center.x + a.radius {
+                break;
+            }
+            if a.center.distance_squared(b.center) < (a.radius + b.radius).powi(2) {
+                pairs.push((a.entity, b.entity));
+            }
+        }
+    }
+}
+
+// A ball resting on the floor can't be pushed into it, so it acts as if it had infinite mass
+// against pushes from above
+fn inverse_mass(phys_obj: &PhysObj, collider: &Collider, push: Vec2) -> f32 {
+    let Collider::Ball {
+        touching_ground, ..
+    } = *collider;
+    if touching_ground && push.y < 0.0 {
+        0.0
+    } else {
+        1.0 / phys_obj.mass
+    }
+}
+
+// Collisions between balls, solved with sequential impulses: every contact of the step is gathered
+// first, then they're all solved in turn `contact_iterations` times, each clamping its accumulated
+// impulse rather than the iteration's. Contacts start from the impulses they ended the last step
+// with, so resting piles don't have to rebuild them from zero every step. What's left of the
+// overlap is then pushed apart without adding velocity (see PhysicsConfig::position_correction).
+//
+// Pairs where both balls sleep are left alone; a sleeping ball that's hit wakes up, as its
+// PhysObj changes.
+pub(super) fn ball_contact_system(
+    config: Res<PhysicsConfig>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut scratch: ResMut<CollisionScratch>,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &mut PhysObj,
+        &Collider,
+        Option<&Sleeping>,
+    )>,
+) {
+    let _span = info_span!("physics_contacts").entered();
+
+    let buffers = &mut scratch.pairs;
+    {
+        let _span = info_span!("broad_phase").entered();
+        let start = timings.start();
+        find_ball_pairs(
+            query.iter().map(
+                |(entity, transform, _, &Collider::Ball { radius, .. }, _)| {
+                    (entity, transform.translation.truncate(), radius)
+                },
+            ),
+            buffers,
+        );
+        record_timing(start, &mut timings.broad_phase);
+    }
+
+    let start = timings.start();
+
+    let BallPairs {
+        pairs,
+        contacts,
+        warm_start,
+        ..
+    } = buffers;
+    contacts.clear();
+    for &(a, b) in pairs.iter() {
+        let Ok([a, b]) = query.get_many_mut([a, b]) else {
+            continue;
+        };
+        if a.4.is_some() && b.4.is_some() {
+            continue;
+        }
+        if let Some(mut contact) = ball_contact(&config, &a, &b) {
+            contact.impulse = warm_start.get(&contact.key).copied().unwrap_or_default();
+            contacts.push(contact);
+        }
+    }
+
+    for contact in contacts.iter() {
+        if let Ok([a, b]) = query.get_many_mut([contact.a, contact.b]) {
+            let AccumulatedImpulse { normal, tangent } = contact.impulse;
+            apply_contact_impulse(contact, a, b, normal, tangent);
+        }
+    }
+    for _ in 0..config.contact_iterations {
+        for contact in contacts.iter_mut() {
+            if let Ok([a, b]) = query.get_many_mut([contact.a, contact.b]) {
+                solve_contact(contact, a, b);
+            }
+        }
+    }
+
+    warm_start.clear();
+    warm_start.extend(
+        contacts
+            .iter()
+            .map(|contact| (contact.key, contact.impulse)),
+    );
+
+    for _ in 0..config.position_iterations {
+        for contact in contacts.iter() {
+            if let Ok([a, b]) = query.get_many_mut([contact.a, contact.b]) {
+                correct_ball_positions(&config, contact, a, b);
+            }
+        }
+    }
+
+    record_timing(start, &mut timings.narrow_phase);
+}
+
+type BallItem<'a> = (
+    Entity,
+    Mut<'a, Transform>,
+    Mut<'a, PhysObj>,
+    &'a Collider,
+    Option<&'a Sleeping>,
+);
+
+// Unit normal from `a` to `b`, and how far they overlap
+fn ball_overlap(a: &BallItem, b: &BallItem) -> Option<(Vec2, f32)> {
+    let (
+        Collider::Ball {
+            radius: radius_a, ..
+        },
+        Collider::Ball {
+            radius: radius_b, ..
+        },
+    ) = (a.3, b.3);
+    let offset = (b.1.translation - a.1.translation).truncate();
+    let normal = offset.try_normalize()?;
+    Some((normal, radius_a + radius_b - offset.length()))
+}
+
+fn ball_contact(config: &PhysicsConfig, a: &BallItem, b: &BallItem) -> Option<BallContact> {
+    let (normal, _) = ball_overlap(a, b)?;
+    let (
+        &Collider::Ball {
+            radius: radius_a,
+            coef_of_restitution: restitution_a,
+            kinetic_friction: friction_a,
+            ..
+        },
+        &Collider::Ball {
+            radius: radius_b,
+            coef_of_restitution: restitution_b,
+            kinetic_friction: friction_b,
+            ..
+        },
+    ) = (a.3, b.3);
+
+    let mut contact = BallContact {
+        key: ContactKey::new(a.0, b.0, 0),
+        a: a.0,
+        b: b.0,
+        normal,
+        radius_a,
+        radius_b,
+        arm_a: a.2.com_arm(a.1.rotation),
+        arm_b: b.2.com_arm(b.1.rotation),
+        inverse_mass_a: inverse_mass(&a.2, a.3, -normal),
+        inverse_mass_b: inverse_mass(&b.2, b.3, normal),
+        restitution: config
+            .restitution_combine
+            .combine(restitution_a, restitution_b),
+        friction: config.friction_combine.combine(friction_a, friction_b),
+        target_normal_vel: 0.0,
+        impulse: default(),
+    };
+    if contact.inverse_mass_a + contact.inverse_mass_b == 0.0 {
+        return None;
+    }
+    // Restitution is decided by how fast the balls approached at the start of the step
+    let approach_vel = contact.relative_vel(a, b).dot(normal);
+    if -approach_vel >= config.restitution_velocity_threshold && approach_vel < 0.0 {
+        contact.target_normal_vel = -contact.restitution * approach_vel;
+    }
+    Some(contact)
+}
+
+impl BallContact {
+    // From each ball's center of mass to the contact point
+    fn offsets(&self) -> (Vec2, Vec2) {
+        (
+            self.normal * self.radius_a - self.arm_a,
+            -self.normal * self.radius_b - self.arm_b,
+        )
+    }
+
+    // Velocity of `b`'s contact point relative to `a`'s
+    fn relative_vel(&self, a: &BallItem, b: &BallItem) -> Vec2 {
+        let (offset_a, offset_b) = self.offsets();
+        (b.2.vel + b.2.angular_vel * offset_b.perp())
+            - (a.2.vel + a.2.angular_vel * offset_a.perp())
+    }
+
+    // Impulse needed per unit of relative velocity change along `direction`
+    fn effective_mass(&self, a: &BallItem, b: &BallItem, direction: Vec2) -> f32 {
+        let (offset_a, offset_b) = self.offsets();
+        let angular = |offset: Vec2, inverse_mass: f32, phys_obj: &PhysObj| {
+            if inverse_mass == 0.0 {
+                0.0
+            } else {
+                offset.perp_dot(direction).powi(2) / phys_obj.moment_of_inertia
+            }
+        };
+        let inverse = self.inverse_mass_a
+            + self.inverse_mass_b
+            + angular(offset_a, self.inverse_mass_a, &a.2)
+            + angular(offset_b, self.inverse_mass_b, &b.2);
+        1.0 / inverse
+    }
+}
+
+// Pushes `a` and `b` apart along the normal and the tangent. Immovable balls aren't written to, as
+// that would wake them if they're asleep.
+fn apply_contact_impulse(
+    contact: &BallContact,
+    mut a: BallItem,
+    mut b: BallItem,
+    normal_impulse: f32,
+    tangent_impulse: f32,
+) {
+    if normal_impulse == 0.0 && tangent_impulse == 0.0 {
+        return;
+    }
+    let (offset_a, offset_b) = contact.offsets();
+    let impulse = normal_impulse * contact.normal + tangent_impulse * contact.normal.perp();
+    if contact.inverse_mass_a > 0.0 {
+        a.2.vel -= impulse * contact.inverse_mass_a;
+        let angular = offset_a.perp_dot(-impulse) / a.2.moment_of_inertia;
+        a.2.angular_vel += angular;
+    }
+    if contact.inverse_mass_b > 0.0 {
+        b.2.vel += impulse * contact.inverse_mass_b;
+        let angular = offset_b.perp_dot(impulse) / b.2.moment_of_inertia;
+        b.2.angular_vel += angular;
+    }
+}
+
+// One iteration for one contact: the normal impulse can only push, and friction can't exceed
+// `friction` times the normal impulse accumulated so far
+fn solve_contact(contact: &mut BallContact, a: BallItem, b: BallItem) {
+    let normal_vel = contact.relative_vel(&a, &b).dot(contact.normal);
+    let normal_impulse =
+        (contact.target_normal_vel - normal_vel) * contact.effective_mass(&a, &b, contact.normal);
+    let normal_delta = accumulate_impulse(
+        &mut contact.impulse.normal,
+        normal_impulse,
+        0.0,
+        f32::INFINITY,
+    );
+
+    let tangent = contact.normal.perp();
+    let (offset_a, offset_b) = contact.offsets();
+    let tangent_vel = {
+        let normal_impulse = normal_delta * contact.normal;
+        // The normal impulse doesn't turn balls, so it only changes the linear velocities
+        let vel_a = a.2.vel - normal_impulse * contact.inverse_mass_a;
+        let vel_b = b.2.vel + normal_impulse * contact.inverse_mass_b;
+        ((vel_b + b.2.angular_vel * offset_b.perp()) - (vel_a + a.2.angular_vel * offset_a.perp()))
+            .dot(tangent)
+    };
+    let max_friction = contact.friction * contact.impulse.normal;
+    let tangent_impulse = -tangent_vel * contact.effective_mass(&a, &b, tangent);
+    let tangent_delta = accumulate_impulse(
+        &mut contact.impulse.tangent,
+        tangent_impulse,
+        -max_friction,
+        max_friction,
+    );
+
+    apply_contact_impulse(contact, a, b, normal_delta, tangent_delta);
+}
+
+// Moves the balls apart by a part of the overlap beyond the slop, without touching the velocities
+fn correct_ball_positions(
+    config: &PhysicsConfig,
+    contact: &BallContact,
+    mut a: BallItem,
+    mut b: BallItem,
+) {
+    let Some((normal, overlap)) = ball_overlap(&a, &b) else {
+        return;
+    };
+    if overlap <= config.penetration_slop {
+        return;
+    }
+    let (inverse_mass_a, inverse_mass_b) = (contact.inverse_mass_a, contact.inverse_mass_b);
+    let correction = config.position_correction * (overlap - config.penetration_slop)
+        / (inverse_mass_a + inverse_mass_b);
+    if inverse_mass_a > 0.0 {
+        a.1.translation -= (correction * inverse_mass_a * normal).extend(0.0);
+    }
+    if inverse_mass_b > 0.0 {
+        b.1.translation += (correction * inverse_mass_b * normal).extend(0.0);
+    }
+}
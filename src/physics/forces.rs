@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use super::{
+    sleep::Sleeping,
+    timings::{record_timing, PhysicsTimings},
+    PhysObj, PhysicsTime,
+};
+
+// A force defined outside the physics module. Like the built-in forces it adds to `acc` and
+// `angular_acc` instead of setting them, so providers don't depend on running in any order.
+pub trait ForceProvider {
+    fn apply(&self, dt: f32, body: &mut PhysObj, transform: &Transform);
+}
+
+impl<F: Fn(f32, &mut PhysObj, &Transform)> ForceProvider for F {
+    fn apply(&self, dt: f32, body: &mut PhysObj, transform: &Transform) {
+        self(dt, body, transform)
+    }
+}
+
+// Forces applied to a body every step, in addition to gravity and the other built-in ones
+#[derive(Component, Default)]
+pub struct CustomForces(pub Vec<Box<dyn ForceProvider + Send + Sync>>);
+
+impl CustomForces {
+    pub fn with(mut self, force: impl ForceProvider + Send + Sync + 'static) -> Self {
+        self.0.push(Box::new(force));
+        self
+    }
+}
+
+// A single closure as a force, for quick experiments
+#[derive(Component)]
+#[allow(clippy::type_complexity)]
+pub struct ForceFn(pub Box<dyn Fn(f32, &mut PhysObj, &Transform) + Send + Sync>);
+
+impl ForceFn {
+    pub fn new(force: impl Fn(f32, &mut PhysObj, &Transform) + Send + Sync + 'static) -> Self {
+        Self(Box::new(force))
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn custom_forces_system(
+    time: Res<PhysicsTime>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut query: Query<
+        (
+            &mut PhysObj,
+            &Transform,
+            Option<&CustomForces>,
+            Option<&ForceFn>,
+        ),
+        Without<Sleeping>,
+    >,
+) {
+    let _span = info_span!("physics_forces").entered();
+    let start = timings.start();
+
+    let dt = time.delta;
+    for (mut phys_obj, transform, custom_forces, force_fn) in &mut query {
+        if let Some(CustomForces(forces)) = custom_forces {
+            for force in forces {
+                force.apply(dt, &mut phys_obj, transform);
+            }
+        }
+        if let Some(ForceFn(force)) = force_fn {
+            force(dt, &mut phys_obj, transform);
+        }
+    }
+
+    record_timing(start, &mut timings.forces);
+}
@@ -0,0 +1,387 @@
+use bevy::prelude::*;
+
+use crate::status::StatusEffects;
+
+use super::{
+    heightfield::HeightfieldContacts,
+    sleep::{Sleeping, StaticFloors},
+    timings::{record_timing, PhysicsTimings},
+    Collider, PhysObj, PhysicsConfig, PhysicsTime,
+};
+
+pub fn apply_friction_impulse(
+    phys_obj: &mut PhysObj,
+    radius: f32,
+    normal_impulse: f32,
+    kinetic_friction: f32,
+    applied_friction: f32, // friction that has already been applied earlier in the frame
+) {
+    let relative_speed = phys_obj.vel.x + phys_obj.angular_vel * radius;
+    // Once the friction applied already is as much as there can be, there's none left. Without the
+    // clamp, min() below would pick the negative cap and copysign() turn it into a push forwards.
+    let max_impulse = f32::max(
+        normal_impulse * kinetic_friction + applied_friction * relative_speed.signum(),
+        0.0,
+    );
+    let stopping_impulse = phys_obj.moment_of_inertia * relative_speed.abs()
+        / (phys_obj.mass * radius.powi(2) + phys_obj.moment_of_inertia);
+    let impulse = f32::min(max_impulse, stopping_impulse).copysign(-relative_speed);
+
+    phys_obj.vel.x += impulse;
+    phys_obj.angular_vel += impulse * phys_obj.mass * radius / phys_obj.moment_of_inertia;
+}
+
+// Directions along a surface and out of it. Bodies are turned into it so that the friction code,
+// written for the floor, works on slopes: along the surface is x and out of it is y.
+#[derive(Clone, Copy)]
+struct SurfaceFrame {
+    tangent: Vec2,
+    normal: Vec2,
+}
+
+impl SurfaceFrame {
+    fn new(normal: Vec2) -> Self {
+        Self {
+            tangent: Vec2::new(normal.y, -normal.x),
+            normal,
+        }
+    }
+
+    fn to_local(self, v: Vec2) -> Vec2 {
+        Vec2::new(v.dot(self.tangent), v.dot(self.normal))
+    }
+
+    fn to_world(self, v: Vec2) -> Vec2 {
+        v.x * self.tangent + v.y * self.normal
+    }
+
+    fn turn(phys_obj: &mut PhysObj, turn: impl Fn(Vec2) -> Vec2) {
+        phys_obj.vel = turn(phys_obj.vel);
+        phys_obj.acc = turn(phys_obj.acc);
+        phys_obj.acc_prev = turn(phys_obj.acc_prev);
+    }
+
+    fn enter(self, phys_obj: &mut PhysObj) {
+        Self::turn(phys_obj, |v| self.to_local(v));
+    }
+
+    fn leave(self, phys_obj: &mut PhysObj) {
+        Self::turn(phys_obj, |v| self.to_world(v));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn friction_impulse_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    heightfields: Res<HeightfieldContacts>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut PhysObj,
+            &Collider,
+            Option<&StatusEffects>,
+        ),
+        Without<Sleeping>,
+    >,
+    floors: StaticFloors,
+) {
+    let _span = info_span!("physics_friction").entered();
+    let start = timings.start();
+
+    let dt = time.delta;
+    for (entity, transform, mut phys_obj, collider, effects) in &mut query {
+        if let Collider::Ball {
+            radius,
+            touching_ground: true,
+            kinetic_friction,
+            friction_acc,
+            friction_acc_prev,
+            ..
+        } = *collider
+        {
+            let surface = heightfields.normal(entity).map(SurfaceFrame::new);
+            let arm = phys_obj.com_arm(transform.rotation);
+            let (arm, resting, surface_velocity, surface_friction) = match surface {
+                // Landings got their friction along with the bounce
+                Some(surface) => (
+                    surface.to_local(arm),
+                    heightfields.resting(entity),
+                    0.0,
+                    1.0,
+                ),
+                // Resting, i.e. the ball's center isn't moving up or down
+                None => (
+                    arm,
+                    phys_obj.vel.y == phys_obj.angular_vel * arm.x,
+                    config.floor_velocity,
+                    floors.friction_scale(transform.translation.truncate()),
+                ),
+            };
+            if resting {
+                // The contact is `lever` below the center of mass
+                let lever = radius + arm.y;
+                if let Some(surface) = surface {
+                    surface.enter(&mut phys_obj);
+                }
+                let normal_impulse = -(phys_obj.acc.y + phys_obj.acc_prev.y) * 0.5 * dt;
+                let applied_friction = (friction_acc + friction_acc_prev) * 0.5 * dt;
+                // In the frame of the surface
+                phys_obj.vel.x -= surface_velocity;
+                apply_friction_impulse(
+                    &mut phys_obj,
+                    lever,
+                    normal_impulse,
+                    config.friction(kinetic_friction)
+                        * StatusEffects::friction_scale(effects)
+                        * surface_friction,
+                    applied_friction,
+                );
+                phys_obj.vel.x += surface_velocity;
+                if let Some(surface) = surface {
+                    surface.leave(&mut phys_obj);
+                }
+            }
+        }
+    }
+
+    record_timing(start, &mut timings.friction);
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn friction_force_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    heightfields: Res<HeightfieldContacts>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut PhysObj,
+            &mut Collider,
+            Option<&StatusEffects>,
+        ),
+        Without<Sleeping>,
+    >,
+    floors: StaticFloors,
+) {
+    let _span = info_span!("physics_friction").entered();
+    let start = timings.start();
+
+    let dt = time.delta;
+    for (entity, transform, mut phys_obj, mut collider, effects) in &mut query {
+        if let Collider::Ball {
+            radius,
+            touching_ground: true,
+            kinetic_friction,
+            ref mut friction_acc,
+            ref mut friction_acc_prev,
+            ..
+        } = *collider
+        {
+            // On a heightfield, in the frame of its surface where the ball touched it last step
+            let surface = heightfields.normal(entity).map(SurfaceFrame::new);
+            let mut arm = phys_obj.com_arm(transform.rotation);
+            let surface_friction = match surface {
+                Some(surface) => {
+                    surface.enter(&mut phys_obj);
+                    arm = surface.to_local(arm);
+                    1.0
+                }
+                None => floors.friction_scale(transform.translation.truncate()),
+            };
+            let normal_force = -phys_obj.acc.y;
+            // The surface pushes out under the center, which turns a ball whose center of mass is
+            // off to one side
+            if arm.x != 0.0 {
+                phys_obj.angular_acc -=
+                    arm.x * normal_force * phys_obj.mass / phys_obj.moment_of_inertia;
+            }
+            if config.rolling_resistance > 0.0 {
+                apply_rolling_resistance(
+                    &mut phys_obj,
+                    radius,
+                    normal_force * config.rolling_resistance,
+                    dt,
+                );
+            }
+            apply_friction_force(
+                &mut phys_obj,
+                radius + arm.y,
+                normal_force,
+                config.friction(kinetic_friction)
+                    * StatusEffects::friction_scale(effects)
+                    * surface_friction,
+                friction_acc,
+                friction_acc_prev,
+            );
+            if let Some(surface) = surface {
+                surface.leave(&mut phys_obj);
+            }
+        }
+    }
+
+    record_timing(start, &mut timings.friction);
+}
+
+pub fn apply_friction_force(
+    phys_obj: &mut PhysObj,
+    radius: f32,
+    normal_force: f32,
+    kinetic_friction: f32,
+    friction_acc: &mut f32,
+    friction_acc_prev: &mut f32,
+) {
+    let relative_acceleration = phys_obj.acc.x + phys_obj.angular_acc * radius;
+    let max_force = normal_force * kinetic_friction;
+    let stopping_force = phys_obj.moment_of_inertia * relative_acceleration.abs()
+        / (phys_obj.mass * radius.powi(2) + phys_obj.moment_of_inertia);
+    let force = f32::min(max_force, stopping_force).copysign(-relative_acceleration);
+
+    *friction_acc_prev = *friction_acc;
+    *friction_acc = force;
+
+    phys_obj.acc.x += force;
+    phys_obj.angular_acc += force * phys_obj.mass * radius / phys_obj.moment_of_inertia;
+}
+
+// Slows the ball's turning with a torque of `resistance` (per unit mass) at `radius`, but never
+// enough to turn it the other way
+pub fn apply_rolling_resistance(phys_obj: &mut PhysObj, radius: f32, resistance: f32, dt: f32) {
+    let max_angular_acc = phys_obj.angular_vel.abs() / dt;
+    let angular_acc = resistance * phys_obj.mass * radius / phys_obj.moment_of_inertia;
+    phys_obj.angular_acc -= f32::min(angular_acc, max_angular_acc).copysign(phys_obj.angular_vel);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RADIUS: f32 = 10.0;
+
+    // A uniform disk, sliding at `vel` and spinning at `angular_vel`
+    fn disk(vel: f32, angular_vel: f32) -> PhysObj {
+        let mass = 2.0;
+        PhysObj {
+            mass,
+            vel: Vec2::new(vel, 0.0),
+            acc: Vec2::ZERO,
+            acc_prev: Vec2::ZERO,
+            moment_of_inertia: 0.5 * mass * RADIUS * RADIUS,
+            angular_vel,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: Vec2::ZERO,
+        }
+    }
+
+    // How fast the bottom of the disk slides along the floor
+    fn slip(phys_obj: &PhysObj) -> f32 {
+        phys_obj.vel.x + phys_obj.angular_vel * RADIUS
+    }
+
+    // Enough friction stops the slip in one go. For a disk a third of it comes off the speed and
+    // the rest goes into the spin.
+    #[test]
+    fn ample_friction_impulse_starts_rolling() {
+        for (vel, angular_vel) in [(30.0, 0.0), (-30.0, 0.0), (0.0, 3.0), (10.0, -4.0)] {
+            let mut phys_obj = disk(vel, angular_vel);
+            let before = slip(&phys_obj);
+            apply_friction_impulse(&mut phys_obj, RADIUS, 100.0, 1.0, 0.0);
+            assert!(slip(&phys_obj).abs() < 1e-4, "{vel} {angular_vel}");
+            assert!((phys_obj.vel.x - (vel - before / 3.0)).abs() < 1e-4);
+        }
+    }
+
+    // Friction that isn't enough to stop the slip takes off as much as it can each step, opposing
+    // the slip, and never overshoots into slipping the other way
+    #[test]
+    fn limited_friction_impulse_converges_to_rolling() {
+        for vel in [30.0, -30.0] {
+            let mut phys_obj = disk(vel, 0.0);
+            let mut steps = 0;
+            while slip(&phys_obj).abs() > 1e-3 {
+                let before = slip(&phys_obj);
+                apply_friction_impulse(&mut phys_obj, RADIUS, 0.7, 1.0, 0.0);
+                let after = slip(&phys_obj);
+                assert!(after.abs() < before.abs(), "{before} {after}");
+                assert!(after * before >= 0.0, "{before} {after}");
+                steps += 1;
+                assert!(steps < 100);
+            }
+            // Each full impulse takes three times itself off the slip
+            assert_eq!(steps, (30.0f32 / (3.0 * 0.7)).ceil() as i32);
+        }
+    }
+
+    // Friction already applied as a force this step counts against the impulse's limit. It opposed
+    // the slip, so it's of the opposite sign.
+    #[test]
+    fn friction_already_applied_leaves_none() {
+        for vel in [30.0, -30.0] {
+            let mut phys_obj = disk(vel, 0.0);
+            apply_friction_impulse(&mut phys_obj, RADIUS, 1.0, 1.0, -2.0 * vel.signum());
+            assert_eq!(phys_obj.vel.x, vel);
+            assert_eq!(phys_obj.angular_vel, 0.0);
+
+            let mut phys_obj = disk(vel, 0.0);
+            apply_friction_impulse(&mut phys_obj, RADIUS, 1.0, 1.0, -0.5 * vel.signum());
+            assert_eq!(phys_obj.vel.x, vel - 0.5 * vel.signum());
+        }
+    }
+
+    // The force opposes the slip the other forces would cause, stopping it if it's strong enough
+    #[test]
+    fn friction_force_opposes_relative_acceleration() {
+        for acc in [30.0, -30.0] {
+            let (mut friction_acc, mut friction_acc_prev) = (0.5, 0.0);
+            let mut phys_obj = disk(0.0, 0.0);
+            phys_obj.acc.x = acc;
+            apply_friction_force(
+                &mut phys_obj,
+                RADIUS,
+                100.0,
+                1.0,
+                &mut friction_acc,
+                &mut friction_acc_prev,
+            );
+            let relative_acc = phys_obj.acc.x + phys_obj.angular_acc * RADIUS;
+            assert!(relative_acc.abs() < 1e-4, "{acc}");
+            assert!((friction_acc + acc / 3.0).abs() < 1e-4);
+            assert_eq!(friction_acc_prev, 0.5);
+
+            let mut phys_obj = disk(0.0, 0.0);
+            phys_obj.acc.x = acc;
+            apply_friction_force(
+                &mut phys_obj,
+                RADIUS,
+                2.0,
+                1.0,
+                &mut friction_acc,
+                &mut friction_acc_prev,
+            );
+            assert_eq!(friction_acc, -2.0 * acc.signum());
+            assert_eq!(phys_obj.acc.x, acc - 2.0 * acc.signum());
+        }
+    }
+
+    #[test]
+    fn rolling_resistance_stops_but_never_reverses_the_spin() {
+        let dt = 1.0 / 60.0;
+        for angular_vel in [1.0, -1.0] {
+            let mut phys_obj = disk(0.0, angular_vel);
+            apply_rolling_resistance(&mut phys_obj, RADIUS, 1000.0, dt);
+            assert!((angular_vel + phys_obj.angular_acc * dt).abs() < 1e-6);
+
+            let mut phys_obj = disk(0.0, angular_vel);
+            apply_rolling_resistance(&mut phys_obj, RADIUS, 0.1, dt);
+            assert!(phys_obj.angular_acc * angular_vel < 0.0);
+        }
+        let mut phys_obj = disk(0.0, 0.0);
+        apply_rolling_resistance(&mut phys_obj, RADIUS, 1000.0, dt);
+        assert_eq!(phys_obj.angular_acc, 0.0);
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+
+use super::{
+    collision::{resolve_contact, ContactPoint},
+    sleep::Sleeping,
+    Collider, LandedEvent, PhysObj, PhysicsConfig,
+};
+
+// Ground shaped like the line through `heights`, which are `spacing` apart going right from the
+// Transform's position and measured up from it. Everything below the line is solid, so a ball
+// whose center got below it (however fast it fell) is pushed back out on top. Static like the
+// floor, with the floor's restitution and friction. Only the native solver knows about them.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Heightfield {
+    pub spacing: f32,
+    pub heights: Vec<f32>,
+}
+
+impl Heightfield {
+    // From the first height to the last
+    pub fn width(&self) -> f32 {
+        self.spacing * self.heights.len().saturating_sub(1) as f32
+    }
+
+    // Height of the surface `x` right of the first height, if the heightfield goes that far
+    pub fn height_at(&self, x: f32) -> Option<f32> {
+        if self.heights.len() < 2 || !(0.0..=self.width()).contains(&x) {
+            return None;
+        }
+        let i = ((x / self.spacing) as usize).min(self.heights.len() - 2);
+        let t = x / self.spacing - i as f32;
+        Some(self.heights[i] + t * (self.heights[i + 1] - self.heights[i]))
+    }
+
+    // Where a ball of `radius` at `center` (measured from the first height) is deepest in the
+    // surface: the unit normal pushing it out, and how far it has to go. None if it isn't touching.
+    pub fn contact(&self, center: Vec2, radius: f32) -> Option<(Vec2, f32)> {
+        if self.heights.len() < 2 {
+            return None;
+        }
+        let last_segment = (self.heights.len() - 2) as f32;
+        let first = ((center.x - radius) / self.spacing)
+            .floor()
+            .clamp(0.0, last_segment) as usize;
+        let last = ((center.x + radius) / self.spacing)
+            .floor()
+            .clamp(0.0, last_segment) as usize;
+        let point = |i: usize| Vec2::new(i as f32 * self.spacing, self.heights[i]);
+
+        let mut deepest: Option<(Vec2, f32)> = None;
+        for i in first..=last {
+            let (a, b) = (point(i), point(i + 1));
+            let along = b - a;
+            let up = along.perp().normalize();
+            let above = (center - a).dot(up);
+            let (normal, depth) = if (a.x..=b.x).contains(&center.x) && above <= 0.0 {
+                // Under the surface, so out the way the surface faces
+                (up, radius - above)
+            } else {
+                let t = ((center - a).dot(along) / along.length_squared()).clamp(0.0, 1.0);
+                let offset = center - (a + t * along);
+                let distance = offset.length();
+                if distance >= radius || distance == 0.0 {
+                    continue;
+                }
+                (offset / distance, radius - distance)
+            };
+            if deepest.is_none_or(|(_, deepest)| depth > deepest) {
+                deepest = Some((normal, depth));
+            }
+        }
+        deepest
+    }
+}
+
+// The balls touching a heightfield in the last step and in this one, with the surface normal where
+// they touch it. Friction pushes along that surface instead of along the floor.
+#[derive(Resource, Default)]
+pub(super) struct HeightfieldContacts {
+    previous: Vec<(Entity, Vec2)>,
+    current: Vec<(Entity, Vec2)>,
+}
+
+impl HeightfieldContacts {
+    // The normal of the heightfield `entity` is touching, if it is
+    pub(super) fn normal(&self, entity: Entity) -> Option<Vec2> {
+        self.current
+            .iter()
+            .find_map(|&(touching, normal)| (touching == entity).then_some(normal))
+    }
+
+    // Whether `entity` was already touching a heightfield before this step, rather than landing on
+    // one in it
+    pub(super) fn resting(&self, entity: Entity) -> bool {
+        self.previous
+            .iter()
+            .any(|&(touching, _)| touching == entity)
+    }
+}
+
+// Pushes balls out of heightfields, bouncing them off as the floor would. Balls that were already
+// touching one don't bounce, so that they can rest on slopes, and their friction is left to
+// friction_impulse_system. What lands on one sends a LandedEvent.
+pub(super) fn heightfield_contact_system(
+    config: Res<PhysicsConfig>,
+    mut contacts: ResMut<HeightfieldContacts>,
+    mut landed: EventWriter<LandedEvent>,
+    heightfields: Query<(&Transform, &Heightfield), Without<PhysObj>>,
+    mut balls: Query<(Entity, &mut Transform, &mut PhysObj, &mut Collider), Without<Sleeping>>,
+) {
+    let contacts = &mut *contacts;
+    std::mem::swap(&mut contacts.previous, &mut contacts.current);
+    contacts.current.clear();
+    if heightfields.is_empty() {
+        return;
+    }
+
+    for (entity, mut transform, mut phys_obj, mut collider) in &mut balls {
+        let Collider::Ball {
+            radius,
+            coef_of_restitution,
+            kinetic_friction,
+            ..
+        } = *collider;
+        let position = transform.translation.truncate();
+        let Some((normal, depth)) = heightfields
+            .iter()
+            .filter_map(|(origin, heightfield)| {
+                heightfield.contact(position - origin.translation.truncate(), radius)
+            })
+            .reduce(|a, b| if b.1 > a.1 { b } else { a })
+        else {
+            continue;
+        };
+
+        contacts.current.push((entity, normal));
+        let Collider::Ball {
+            touching_ground, ..
+        } = &mut *collider;
+        *touching_ground = true;
+        transform.translation += (depth * normal).extend(0.0);
+
+        let point = ContactPoint {
+            offset: -radius * normal,
+            normal,
+        }
+        .with_com_offset(phys_obj.com_arm(transform.rotation));
+        // Only if they're coming together
+        let impact_speed = -(phys_obj.vel + phys_obj.angular_vel * point.offset.perp()).dot(normal);
+        if impact_speed <= 0.0 {
+            continue;
+        }
+        if contacts.resting(entity) {
+            resolve_contact(&mut phys_obj, &point, 0.0, 0.0);
+            continue;
+        }
+        let restitution = if impact_speed >= config.restitution_velocity_threshold {
+            config
+                .restitution_combine
+                .combine(coef_of_restitution, config.floor_restitution)
+        } else {
+            0.0
+        };
+        resolve_contact(
+            &mut phys_obj,
+            &point,
+            restitution,
+            config.friction(kinetic_friction),
+        );
+        landed.send(LandedEvent {
+            entity,
+            impact_speed,
+            surface_y: transform.translation.y - radius * normal.y,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Up from 0 to 100 over the first 100, then flat
+    fn ramp() -> Heightfield {
+        Heightfield {
+            spacing: 100.0,
+            heights: vec![0.0, 100.0, 100.0],
+        }
+    }
+
+    #[test]
+    fn heights_between_points_are_on_the_line() {
+        let ramp = ramp();
+        assert_eq!(ramp.width(), 200.0);
+        assert_eq!(ramp.height_at(0.0), Some(0.0));
+        assert_eq!(ramp.height_at(25.0), Some(25.0));
+        assert_eq!(ramp.height_at(150.0), Some(100.0));
+        assert_eq!(ramp.height_at(200.0), Some(100.0));
+        assert_eq!(ramp.height_at(-1.0), None);
+        assert_eq!(ramp.height_at(201.0), None);
+    }
+
+    #[test]
+    fn balls_are_pushed_out_along_the_slope() {
+        let ramp = ramp();
+        let (normal, depth) = ramp.contact(Vec2::new(50.0, 50.0), 10.0).unwrap();
+        assert!(
+            (normal - Vec2::new(-1.0, 1.0).normalize()).length() < 1e-5,
+            "{normal}"
+        );
+        assert!((depth - 10.0).abs() < 1e-4, "{depth}");
+
+        let (normal, depth) = ramp.contact(Vec2::new(150.0, 105.0), 10.0).unwrap();
+        assert_eq!(normal, Vec2::Y);
+        assert!((depth - 5.0).abs() < 1e-4, "{depth}");
+
+        assert!(ramp.contact(Vec2::new(150.0, 111.0), 10.0).is_none());
+        assert!(ramp.contact(Vec2::new(-20.0, 0.0), 10.0).is_none());
+    }
+
+    // Falling through the surface in one step doesn't get a ball out the other side
+    #[test]
+    fn balls_below_the_surface_come_out_on_top() {
+        let (normal, depth) = ramp().contact(Vec2::new(150.0, 40.0), 10.0).unwrap();
+        assert_eq!(normal, Vec2::Y);
+        assert!((depth - 70.0).abs() < 1e-4, "{depth}");
+    }
+
+    // Over the crest, the corner is closer than either line
+    #[test]
+    fn corners_push_balls_away_from_them() {
+        let peak = Heightfield {
+            spacing: 100.0,
+            heights: vec![0.0, 100.0, 0.0],
+        };
+        let (normal, depth) = peak.contact(Vec2::new(100.0, 108.0), 10.0).unwrap();
+        assert_eq!(normal, Vec2::Y);
+        assert!((depth - 2.0).abs() < 1e-4, "{depth}");
+    }
+}
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use super::{
+    sleep::Sleeping,
+    timings::{record_timing, PhysicsTimings},
+    PhysObj, PhysicsConfig, PhysicsTime,
+};
+
+// The part of the integrator that runs before applying forces. Bodies are independent, so they're
+// integrated in parallel unless the config asks otherwise.
+pub(super) fn integrator_before_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut query: Query<(&mut Transform, &mut PhysObj), Without<Sleeping>>,
+) {
+    let _span = info_span!("physics_integrator").entered();
+    let start = timings.start();
+
+    let dt = time.delta;
+    if config.serial {
+        for (mut transform, mut phys_obj) in &mut query {
+            integrate_before(dt, &mut transform, &mut phys_obj);
+        }
+    } else {
+        query
+            .par_iter_mut()
+            .for_each_mut(|(mut transform, mut phys_obj)| {
+                integrate_before(dt, &mut transform, &mut phys_obj);
+            });
+    }
+
+    record_timing(start, &mut timings.integrator);
+}
+
+// The part of the integrator that runs after applying forces
+pub(super) fn integrator_after_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut query: Query<&mut PhysObj, Without<Sleeping>>,
+) {
+    let _span = info_span!("physics_integrator").entered();
+    let start = timings.start();
+
+    let dt = time.delta;
+    if config.serial {
+        for mut phys_obj in &mut query {
+            integrate_after(dt, &mut phys_obj);
+        }
+    } else {
+        query
+            .par_iter_mut()
+            .for_each_mut(|mut phys_obj| integrate_after(dt, &mut phys_obj));
+    }
+
+    record_timing(start, &mut timings.integrator);
+}
+
+// The part of the integrator that runs before applying forces
+pub fn integrate_before(dt: f32, transform: &mut Transform, phys_obj: &mut PhysObj) {
+    let dv = 0.5 * phys_obj.acc * dt;
+    phys_obj.vel += dv;
+    let dx = phys_obj.vel * dt;
+    transform.translation += dx.extend(0.0);
+    phys_obj.acc_prev = phys_obj.acc;
+    // Functions that calculate acceleration simply add to it so it must be reset every iteration.
+    phys_obj.acc = Vec2::ZERO;
+
+    let dav = 0.5 * phys_obj.angular_acc * dt;
+    phys_obj.angular_vel += dav;
+    let angle = phys_obj.angular_vel * dt;
+    rotate_about_com(transform, phys_obj, angle);
+    phys_obj.angular_acc_prev = phys_obj.angular_acc;
+    // Functions that calculate acceleration simply add to it so it must be reset every iteration.
+    phys_obj.angular_acc = 0.0;
+}
+
+// The part of the integrator that runs after applying forces
+pub fn integrate_after(dt: f32, phys_obj: &mut PhysObj) {
+    let dv = 0.5 * phys_obj.acc * dt;
+    phys_obj.vel += dv;
+
+    let dav = 0.5 * phys_obj.angular_acc * dt;
+    phys_obj.angular_vel += dav;
+}
+
+// Integrator for when acceleration is assumed constant (used in collision resolving)
+pub fn integrate_simple(dt: f32, transform: &mut Transform, phys_obj: &mut PhysObj) {
+    let dv = phys_obj.acc * dt;
+    let dx = (phys_obj.vel + 0.5 * dv) * dt;
+    transform.translation += dx.extend(0.0);
+    phys_obj.vel += dv;
+
+    let dav = phys_obj.angular_acc * dt;
+    let angle = (phys_obj.angular_vel + 0.5 * dav) * dt;
+    rotate_about_com(transform, phys_obj, angle);
+    phys_obj.angular_vel += dav;
+}
+
+// Turns the body by `angle` about its center of mass, which moves the origin around it unless
+// they're the same point
+pub fn rotate_about_com(transform: &mut Transform, phys_obj: &PhysObj, angle: f32) {
+    let arm = phys_obj.com_arm(transform.rotation);
+    transform.rotate_z(angle);
+    if arm != Vec2::ZERO {
+        transform.translation += (arm - phys_obj.com_arm(transform.rotation)).extend(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.1;
+    const GRAVITY: Vec2 = Vec2::new(0.0, -10.0);
+
+    fn body(vel: Vec2, angular_vel: f32) -> PhysObj {
+        PhysObj {
+            mass: 2.0,
+            vel,
+            acc: Vec2::ZERO,
+            acc_prev: Vec2::ZERO,
+            moment_of_inertia: 1.0,
+            angular_vel,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: Vec2::ZERO,
+        }
+    }
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-5, "{a} {b}");
+    }
+
+    // One step the way the physics takes it: start, forces, end
+    fn step(transform: &mut Transform, phys_obj: &mut PhysObj, angular_acc: f32) {
+        integrate_before(DT, transform, phys_obj);
+        phys_obj.acc += GRAVITY;
+        phys_obj.angular_acc += angular_acc;
+        integrate_after(DT, phys_obj);
+    }
+
+    #[test]
+    fn start_kicks_with_the_last_acceleration_then_drifts() {
+        let mut transform = Transform::default();
+        let mut phys_obj = body(Vec2::new(1.0, 2.0), 0.5);
+        phys_obj.acc = GRAVITY;
+        phys_obj.angular_acc = 2.0;
+        integrate_before(DT, &mut transform, &mut phys_obj);
+
+        assert_close(phys_obj.vel, Vec2::new(1.0, 1.5));
+        assert_close(transform.translation.truncate(), Vec2::new(0.1, 0.15));
+        assert_eq!(phys_obj.acc_prev, GRAVITY);
+        assert_eq!(phys_obj.acc, Vec2::ZERO);
+        assert!((phys_obj.angular_vel - 0.6).abs() < 1e-6);
+        let (_, angle) = transform.rotation.to_axis_angle();
+        assert!((angle - 0.06).abs() < 1e-6, "{angle}");
+        assert_eq!(phys_obj.angular_acc_prev, 2.0);
+        assert_eq!(phys_obj.angular_acc, 0.0);
+    }
+
+    #[test]
+    fn end_kicks_with_the_new_acceleration_only() {
+        let mut phys_obj = body(Vec2::new(1.0, 2.0), 0.5);
+        phys_obj.acc = GRAVITY;
+        phys_obj.angular_acc = 2.0;
+        integrate_after(DT, &mut phys_obj);
+
+        assert_close(phys_obj.vel, Vec2::new(1.0, 1.5));
+        assert!((phys_obj.angular_vel - 0.6).abs() < 1e-6);
+        // Left for the next step's start to move on
+        assert_eq!(phys_obj.acc, GRAVITY);
+    }
+
+    // Under a constant acceleration, velocity Verlet lands exactly on the closed form
+    #[test]
+    fn steps_follow_the_closed_form_under_constant_acceleration() {
+        let mut transform = Transform::default();
+        let (vel, angular_vel, angular_acc) = (Vec2::new(3.0, 20.0), 1.0, -0.5);
+        let mut phys_obj = body(vel, angular_vel);
+        // The forces of the step before the first
+        phys_obj.acc = GRAVITY;
+        phys_obj.angular_acc = angular_acc;
+        for n in 1..=50 {
+            step(&mut transform, &mut phys_obj, angular_acc);
+            let t = n as f32 * DT;
+            assert_close(phys_obj.vel, vel + GRAVITY * t);
+            let expected = vel * t + 0.5 * GRAVITY * t * t;
+            assert!(
+                (transform.translation.truncate() - expected).length() < 1e-3,
+                "{n}: {} {expected}",
+                transform.translation
+            );
+            assert!((phys_obj.angular_vel - (angular_vel + angular_acc * t)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn simple_integration_runs_backwards_to_where_it_started() {
+        let start = Transform::from_xyz(1.0, 2.0, 0.0);
+        let mut transform = start;
+        let mut phys_obj = body(Vec2::new(3.0, 4.0), 0.5);
+        phys_obj.acc = GRAVITY;
+        phys_obj.angular_acc = 1.0;
+        integrate_simple(DT, &mut transform, &mut phys_obj);
+        assert_close(
+            transform.translation.truncate(),
+            Vec2::new(1.3, 2.0 + 0.4 - 0.05),
+        );
+        assert_close(phys_obj.vel, Vec2::new(3.0, 3.0));
+
+        integrate_simple(-DT, &mut transform, &mut phys_obj);
+        assert_close(transform.translation.truncate(), Vec2::new(1.0, 2.0));
+        assert_close(phys_obj.vel, Vec2::new(3.0, 4.0));
+        assert!((phys_obj.angular_vel - 0.5).abs() < 1e-6);
+        assert!(transform.rotation.angle_between(start.rotation) < 1e-5);
+    }
+
+    #[test]
+    fn turning_keeps_the_center_of_mass_in_place() {
+        let mut transform = Transform::from_xyz(5.0, 0.0, 0.0);
+        let mut phys_obj = body(Vec2::ZERO, 0.0);
+        phys_obj.com_offset = Vec2::new(1.0, 0.0);
+        let com = |transform: &Transform, phys_obj: &PhysObj| {
+            transform.translation.truncate() + phys_obj.com_arm(transform.rotation)
+        };
+        let before = com(&transform, &phys_obj);
+        rotate_about_com(&mut transform, &phys_obj, 1.0);
+        assert_close(com(&transform, &phys_obj), before);
+        assert!(transform.translation.truncate().distance(before) > 0.5);
+    }
+}
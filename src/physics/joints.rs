@@ -0,0 +1,326 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{contacts::accumulate_impulse, PhysObj, PhysicsConfig, PhysicsTime};
+
+// Keeps the centers of `a` and `b` `length` apart. Rigid joints hold that distance both ways, like
+// a rod; the others only stop it from being exceeded, like a rope. Either end can be a static
+// anchor: an entity with a Transform but no PhysObj, which the joint never moves.
+//
+// Joints are entities of their own, so a body can have any number of them.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DistanceJoint {
+    pub a: Entity,
+    pub b: Entity,
+    pub length: f32,
+    pub rigid: bool,
+}
+
+// Pins a point on `a` to a point on `b`, leaving them free to turn relative to each other. The
+// anchors are in each body's local frame, so they turn with it. As with DistanceJoint, either
+// body can be a static anchor.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RevoluteJoint {
+    pub a: Entity,
+    pub b: Entity,
+    pub anchor_on_a: Vec2,
+    pub anchor_on_b: Vec2,
+    pub motor: Option<JointMotor>,
+}
+
+// Drives the angular velocity of `b` relative to `a` towards `target_speed` (counter-clockwise in
+// radians per second), with at most `max_torque`. Under a heavier load it slips.
+#[derive(Clone, Copy, Debug)]
+pub struct JointMotor {
+    pub target_speed: f32,
+    pub max_torque: f32,
+}
+
+// Impulses a revolute joint applied during a step, to start the next step's solve from
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RevoluteImpulse {
+    pub point: Vec2,
+    pub motor: f32,
+}
+
+// Joint state kept between steps
+#[derive(Resource, Default)]
+pub struct JointScratch {
+    // By joint entity
+    pub warm_start: HashMap<Entity, RevoluteImpulse>,
+    // Impulse along each distance joint pulling its ends together, by joint entity
+    pub distance_warm_start: HashMap<Entity, f32>,
+}
+
+// Solves every joint in turn `joint_iterations` times, so chains converge instead of each link
+// undoing its neighbours.
+//
+// Distance joints move their bodies back to the right distance, split by their inverse masses,
+// then remove their relative velocity along the joint with impulses accumulated like the revolute
+// joints', starting from the last step's (ropes only ever pull, and a slack one starts from
+// nothing). Without that, a hanging chain never gets the weight of what hangs below each link
+// back out of its velocities within the iterations. They pull on the bodies' centers, so they
+// don't turn them.
+//
+// Revolute joints are solved with sequential impulses like ball contacts: each starts from the
+// impulses it ended the last step with, accumulates them over the iterations (clamping the
+// motor's to its torque limit), and then has whatever drift is left pushed out of the positions.
+//
+// The impulses are equal and opposite, so joints don't change a system's momentum.
+pub(super) fn joint_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut scratch: ResMut<JointScratch>,
+    distance_joints: Query<(Entity, &DistanceJoint)>,
+    revolute_joints: Query<(Entity, &RevoluteJoint)>,
+    mut bodies: Query<(&mut Transform, Option<&mut PhysObj>)>,
+) {
+    let _span = info_span!("physics_joints").entered();
+
+    let JointScratch {
+        warm_start,
+        distance_warm_start,
+    } = &mut *scratch;
+    warm_start.retain(|&entity, _| revolute_joints.contains(entity));
+    for (entity, joint) in &revolute_joints {
+        if let Ok([a, b]) = bodies.get_many_mut([joint.a, joint.b]) {
+            let impulse = *warm_start.entry(entity).or_default();
+            apply_revolute_impulse(joint, a, b, impulse.point, impulse.motor);
+        }
+    }
+    distance_warm_start.retain(|&entity, _| distance_joints.contains(entity));
+    for (entity, joint) in &distance_joints {
+        if let Ok([a, b]) = bodies.get_many_mut([joint.a, joint.b]) {
+            let impulse = distance_warm_start.entry(entity).or_default();
+            warm_start_distance_joint(joint, impulse, a, b);
+        }
+    }
+
+    for _ in 0..config.joint_iterations {
+        for (entity, joint) in &revolute_joints {
+            if let (Ok([a, b]), Some(impulse)) = (
+                bodies.get_many_mut([joint.a, joint.b]),
+                warm_start.get_mut(&entity),
+            ) {
+                solve_revolute_joint(joint, impulse, time.delta, a, b);
+            }
+        }
+        for (entity, joint) in &distance_joints {
+            if let (Ok([a, b]), Some(impulse)) = (
+                bodies.get_many_mut([joint.a, joint.b]),
+                distance_warm_start.get_mut(&entity),
+            ) {
+                solve_distance_joint(joint, impulse, a, b);
+            }
+        }
+    }
+
+    for _ in 0..config.joint_iterations {
+        for (_, joint) in &revolute_joints {
+            if let Ok([a, b]) = bodies.get_many_mut([joint.a, joint.b]) {
+                correct_revolute_position(joint, a, b);
+            }
+        }
+    }
+}
+
+type JointBody<'a> = (Mut<'a, Transform>, Option<Mut<'a, PhysObj>>);
+
+fn inverse_mass(body: &JointBody) -> f32 {
+    body.1.as_ref().map_or(0.0, |phys_obj| 1.0 / phys_obj.mass)
+}
+
+fn inverse_inertia(body: &JointBody) -> f32 {
+    body.1
+        .as_ref()
+        .map_or(0.0, |phys_obj| 1.0 / phys_obj.moment_of_inertia)
+}
+
+fn angular_vel(body: &JointBody) -> f32 {
+    body.1.as_ref().map_or(0.0, |phys_obj| phys_obj.angular_vel)
+}
+
+// An anchor in the body's local frame, relative to its center in world space
+fn world_offset(body: &JointBody, anchor: Vec2) -> Vec2 {
+    (body.0.rotation * anchor.extend(0.0)).truncate()
+}
+
+fn point_vel(body: &JointBody, offset: Vec2) -> Vec2 {
+    body.1.as_ref().map_or(Vec2::ZERO, |phys_obj| {
+        phys_obj.vel + phys_obj.angular_vel * offset.perp()
+    })
+}
+
+// Applies `impulse` at `offset` from the center, and an angular impulse
+fn apply_impulse(body: &mut JointBody, offset: Vec2, impulse: Vec2, angular_impulse: f32) {
+    if let Some(phys_obj) = &mut body.1 {
+        let mass = phys_obj.mass;
+        phys_obj.vel += impulse / mass;
+        phys_obj.angular_vel +=
+            (offset.perp_dot(impulse) + angular_impulse) / phys_obj.moment_of_inertia;
+    }
+}
+
+// The unit axis from `a` to `b`, how far they are from the joint's length along it, and their
+// total inverse mass. None if there's nothing for the joint to do.
+fn distance_axis(joint: &DistanceJoint, a: &JointBody, b: &JointBody) -> Option<(Vec2, f32, f32)> {
+    let total_inverse_mass = inverse_mass(a) + inverse_mass(b);
+    if total_inverse_mass == 0.0 {
+        return None;
+    }
+    let offset = (b.0.translation - a.0.translation).truncate();
+    let axis = offset.try_normalize()?;
+    let error = offset.length() - joint.length;
+    if !joint.rigid && error <= 0.0 {
+        return None;
+    }
+    Some((axis, error, total_inverse_mass))
+}
+
+// Pulls `a` and `b` together along `axis` with `impulse`, split by their inverse masses
+fn apply_distance_impulse(axis: Vec2, impulse: f32, a: &mut JointBody, b: &mut JointBody) {
+    let (inverse_mass_a, inverse_mass_b) = (inverse_mass(a), inverse_mass(b));
+    if let Some(phys_obj) = &mut a.1 {
+        phys_obj.vel += axis * impulse * inverse_mass_a;
+    }
+    if let Some(phys_obj) = &mut b.1 {
+        phys_obj.vel -= axis * impulse * inverse_mass_b;
+    }
+}
+
+fn warm_start_distance_joint(
+    joint: &DistanceJoint,
+    impulse: &mut f32,
+    mut a: JointBody,
+    mut b: JointBody,
+) {
+    match distance_axis(joint, &a, &b) {
+        Some((axis, ..)) => apply_distance_impulse(axis, *impulse, &mut a, &mut b),
+        None => *impulse = 0.0,
+    }
+}
+
+fn solve_distance_joint<'a>(
+    joint: &DistanceJoint,
+    impulse: &mut f32,
+    mut a: JointBody<'a>,
+    mut b: JointBody<'a>,
+) {
+    let Some((axis, error, total_inverse_mass)) = distance_axis(joint, &a, &b) else {
+        return;
+    };
+
+    let correction = axis * error / total_inverse_mass;
+    for (body, correction) in [(&mut a, correction), (&mut b, -correction)] {
+        let inverse_mass = inverse_mass(body);
+        body.0.translation += (correction * inverse_mass).extend(0.0);
+    }
+
+    let vel = |body: &JointBody| body.1.as_ref().map_or(Vec2::ZERO, |phys_obj| phys_obj.vel);
+    let separating_vel = (vel(&b) - vel(&a)).dot(axis);
+    let min = if joint.rigid { f32::NEG_INFINITY } else { 0.0 };
+    let delta = accumulate_impulse(
+        impulse,
+        separating_vel / total_inverse_mass,
+        min,
+        f32::INFINITY,
+    );
+    apply_distance_impulse(axis, delta, &mut a, &mut b);
+}
+
+// How the relative velocity of the pinned points responds to an impulse there, inverted
+fn pin_mass(a: &JointBody, b: &JointBody, offset_a: Vec2, offset_b: Vec2) -> Option<Mat2> {
+    let inverse_mass = inverse_mass(a) + inverse_mass(b);
+    let (inverse_inertia_a, inverse_inertia_b) = (inverse_inertia(a), inverse_inertia(b));
+    let xx = inverse_mass
+        + inverse_inertia_a * offset_a.y * offset_a.y
+        + inverse_inertia_b * offset_b.y * offset_b.y;
+    let xy =
+        -inverse_inertia_a * offset_a.x * offset_a.y - inverse_inertia_b * offset_b.x * offset_b.y;
+    let yy = inverse_mass
+        + inverse_inertia_a * offset_a.x * offset_a.x
+        + inverse_inertia_b * offset_b.x * offset_b.x;
+    let inverse = Mat2::from_cols(Vec2::new(xx, xy), Vec2::new(xy, yy));
+    (inverse.determinant() > 0.0).then(|| inverse.inverse())
+}
+
+// Applies `point_impulse` to `b` at the pin and its opposite to `a`, and the motor's angular
+// impulse the same way
+fn apply_revolute_impulse(
+    joint: &RevoluteJoint,
+    mut a: JointBody,
+    mut b: JointBody,
+    point_impulse: Vec2,
+    motor_impulse: f32,
+) {
+    let offset_a = world_offset(&a, joint.anchor_on_a);
+    let offset_b = world_offset(&b, joint.anchor_on_b);
+    apply_impulse(&mut a, offset_a, -point_impulse, -motor_impulse);
+    apply_impulse(&mut b, offset_b, point_impulse, motor_impulse);
+}
+
+// One iteration for one revolute joint: the motor first, then the pin, so that the pin has the
+// last word
+fn solve_revolute_joint(
+    joint: &RevoluteJoint,
+    impulse: &mut RevoluteImpulse,
+    dt: f32,
+    mut a: JointBody,
+    mut b: JointBody,
+) {
+    let mut motor_delta = 0.0;
+    if let Some(motor) = joint.motor {
+        let inverse_inertia = inverse_inertia(&a) + inverse_inertia(&b);
+        if inverse_inertia > 0.0 {
+            let relative_vel = angular_vel(&b) - angular_vel(&a);
+            let max_impulse = motor.max_torque * dt;
+            motor_delta = accumulate_impulse(
+                &mut impulse.motor,
+                (motor.target_speed - relative_vel) / inverse_inertia,
+                -max_impulse,
+                max_impulse,
+            );
+        }
+    }
+    let offset_a = world_offset(&a, joint.anchor_on_a);
+    let offset_b = world_offset(&b, joint.anchor_on_b);
+    apply_impulse(&mut a, offset_a, Vec2::ZERO, -motor_delta);
+    apply_impulse(&mut b, offset_b, Vec2::ZERO, motor_delta);
+
+    let Some(mass) = pin_mass(&a, &b, offset_a, offset_b) else {
+        return;
+    };
+    let relative_vel = point_vel(&b, offset_b) - point_vel(&a, offset_a);
+    let point_delta = mass * -relative_vel;
+    impulse.point += point_delta;
+    apply_impulse(&mut a, offset_a, -point_delta, 0.0);
+    apply_impulse(&mut b, offset_b, point_delta, 0.0);
+}
+
+// Moves and turns the bodies so the pinned points meet, without touching the velocities
+fn correct_revolute_position<'a>(
+    joint: &RevoluteJoint,
+    mut a: JointBody<'a>,
+    mut b: JointBody<'a>,
+) {
+    let offset_a = world_offset(&a, joint.anchor_on_a);
+    let offset_b = world_offset(&b, joint.anchor_on_b);
+    let error = (b.0.translation.truncate() + offset_b) - (a.0.translation.truncate() + offset_a);
+    if error == Vec2::ZERO {
+        return;
+    }
+    let Some(mass) = pin_mass(&a, &b, offset_a, offset_b) else {
+        return;
+    };
+    let correction = mass * -error;
+    for (body, offset, correction) in [
+        (&mut a, offset_a, -correction),
+        (&mut b, offset_b, correction),
+    ] {
+        if let Some(phys_obj) = &body.1 {
+            let (mass, moment_of_inertia) = (phys_obj.mass, phys_obj.moment_of_inertia);
+            body.0.translation += (correction / mass).extend(0.0);
+            body.0
+                .rotate_z(offset.perp_dot(correction) / moment_of_inertia);
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use std::f32::consts::PI;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub mod anomaly;
+pub mod collision;
+mod config;
+pub mod contacts;
+pub mod forces;
+pub mod friction;
+pub mod heightfield;
+pub mod integrator;
+pub mod joints;
+#[cfg(feature = "rapier-backend")]
+pub mod rapier;
+pub mod raycast;
+pub mod sleep;
+pub mod timings;
+
+use crate::{
+    settings::{InputAction, Settings},
+    state::AppState,
+};
+use anomaly::{
+    anomaly_detection_system, anomaly_handler_system, physics_validation_enabled,
+    validation_system, AnomalySettings, PhysicsAnomaly, PhysicsValidation,
+};
+use collision::{collision_system, CollisionScratch};
+pub use config::{CombineRule, PhysicsConfig};
+use contacts::ball_contact_system;
+use forces::custom_forces_system;
+use friction::{friction_force_system, friction_impulse_system};
+use heightfield::{heightfield_contact_system, HeightfieldContacts};
+use integrator::{integrator_after_system, integrator_before_system};
+use joints::{joint_system, JointScratch};
+use sleep::{sleep_system, static_hash_system, wake_system, Sleeping, StaticHash};
+use timings::{
+    physics_profiling_enabled, physics_timings_diagnostics_system, physics_timings_reset_system,
+    physics_timings_setup, record_timing, PhysicsTimings,
+};
+
+const SLOW_MOTION_SCALE: f32 = 0.1;
+// Real seconds it takes to go from normal speed to slow motion and back
+const SLOW_MOTION_RAMP_TIME: f32 = 0.2;
+// Impacts with a larger impulse trigger slow motion when auto slow motion is on
+const SLOW_MOTION_IMPULSE: f32 = 15_000.0;
+const SLOW_MOTION_AUTO_DURATION: f32 = 0.5;
+const SLOW_MOTION_AUTO_COOLDOWN: f32 = 2.0;
+// Length of a single physics step taken while paused, and of netplay's fixed ticks
+pub const STEP_DT: f32 = 1.0 / 60.0;
+
+#[cfg(feature = "rapier-backend")]
+pub use rapier::PhysicsBackend;
+
+#[derive(Default)]
+pub struct PhysicsPlugin {
+    config: PhysicsConfig,
+    #[cfg(feature = "rapier-backend")]
+    backend: PhysicsBackend,
+}
+
+impl PhysicsPlugin {
+    pub fn with_config(config: PhysicsConfig) -> Self {
+        Self {
+            config,
+            #[cfg(feature = "rapier-backend")]
+            backend: default(),
+        }
+    }
+
+    #[cfg(feature = "rapier-backend")]
+    pub fn with_backend(self, backend: PhysicsBackend) -> Self {
+        Self { backend, ..self }
+    }
+}
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .init_resource::<Settings>()
+            .init_resource::<SimulationControl>()
+            .init_resource::<PhysicsTime>()
+            .init_resource::<PhysicsTimings>()
+            .init_resource::<CollisionScratch>()
+            .init_resource::<JointScratch>()
+            .init_resource::<AnomalySettings>()
+            .init_resource::<PhysicsValidation>()
+            .init_resource::<SlowMotion>()
+            .init_resource::<NextSpawnOrder>()
+            .add_event::<CollisionEvent>()
+            .add_event::<BounceEvent>()
+            .add_event::<LandedEvent>()
+            .add_event::<PhysicsAnomaly>()
+            .add_startup_system(physics_timings_setup)
+            .add_systems(
+                (
+                    simulation_control_system,
+                    slow_motion_system,
+                    physics_time_system,
+                    physics_timings_reset_system,
+                )
+                    .chain()
+                    .before(PhysicsStep),
+            )
+            .add_system(spawn_order_system.before(PhysicsStep))
+            .add_system(
+                physics_step_system
+                    .in_set(PhysicsStep)
+                    .run_if(simulation_running),
+            )
+            .add_system(
+                physics_timings_diagnostics_system
+                    .after(PhysicsStep)
+                    .run_if(physics_profiling_enabled),
+            )
+            .add_schedule(PhysicsSchedule, Schedule::new())
+            .edit_schedule(PhysicsSchedule, |schedule| {
+                schedule.configure_sets(
+                    (
+                        PhysicsSet::ApplyImpulses,
+                        PhysicsSet::IntegrateStart,
+                        PhysicsSet::ApplyForces,
+                        PhysicsSet::IntegrateEnd,
+                        PhysicsSet::ResolveCollisions,
+                        PhysicsSet::SolveConstraints,
+                        PhysicsSet::PostSolve,
+                    )
+                        .chain(),
+                );
+            })
+            .add_system(
+                custom_forces_system
+                    .in_set(PhysicsSet::ApplyForces)
+                    .in_schedule(PhysicsSchedule),
+            );
+
+        #[cfg(feature = "rapier-backend")]
+        if self.backend == PhysicsBackend::Rapier {
+            rapier::add_rapier_solver(app);
+            return;
+        }
+        add_native_solver(app);
+    }
+}
+
+// The hand-rolled solver: integration, collisions, friction, joints and sleeping
+fn add_native_solver(app: &mut App) {
+    app.init_resource::<HeightfieldContacts>()
+        .init_resource::<StaticHash>()
+        .add_system(static_hash_system.before(PhysicsStep))
+        .add_systems(
+            (wake_system, sleep_system)
+                .chain()
+                .after(PhysicsStep)
+                .distributive_run_if(simulation_running),
+        )
+        .add_systems(
+            (
+                integrator_before_system.in_set(PhysicsSet::IntegrateStart),
+                gravity_system.in_set(PhysicsSet::ApplyForces),
+            )
+                .in_schedule(PhysicsSchedule),
+        )
+        .add_systems(
+            (friction_force_system, integrator_after_system)
+                .chain()
+                .in_set(PhysicsSet::IntegrateEnd)
+                .in_schedule(PhysicsSchedule),
+        )
+        .add_systems(
+            (
+                validation_system.run_if(physics_validation_enabled),
+                collision_system,
+                ball_contact_system,
+                heightfield_contact_system,
+                friction_impulse_system,
+            )
+                .chain()
+                .in_set(PhysicsSet::ResolveCollisions)
+                .in_schedule(PhysicsSchedule),
+        )
+        .add_system(
+            joint_system
+                .in_set(PhysicsSet::SolveConstraints)
+                .in_schedule(PhysicsSchedule),
+        )
+        .add_systems(
+            (anomaly_detection_system, anomaly_handler_system)
+                .chain()
+                .in_set(PhysicsSet::PostSolve)
+                .in_schedule(PhysicsSchedule),
+        );
+}
+
+// A single physics step. It's run `substeps` times a frame from PhysicsStep, so systems that take
+// part in the simulation are added to it rather than the main schedule.
+//
+// The same bodies given the same inputs have to end up bit for bit in the same state, for replays,
+// netplay and comparing headless runs across machines (see tests/determinism.rs). So systems that
+// take part in the simulation:
+// - go by PhysicsTime, never Time or a clock, which follow how long frames really took
+// - draw any randomness from a GameRng stream, never from the time or the system
+// - don't let the order they iterate a HashMap or HashSet in change the result; those are for
+//   lookups, and what's iterated is kept in a Vec sorted by something stable, or a BTreeMap
+// - only use par_iter for updates of each body on its own, never for sums or other reductions
+//   across bodies, whose floating point result depends on the order they're added in
+// - tell bodies apart by SpawnOrder rather than Entity where the order matters beyond a single
+//   run, as entity ids depend on what was despawned before
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsSchedule;
+
+// Where the physics steps run in the main schedule. Only runs while Playing, except for single
+// steps while Paused. Systems that use the result of the frame's physics go after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsStep;
+
+// The stages of a physics step in PhysicsSchedule, in the order they run
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    // Instantaneous changes in velocity, e.g. jumping
+    ApplyImpulses,
+    // First half of the velocity Verlet step; clears the accelerations
+    IntegrateStart,
+    // Systems that add to `acc`/`angular_acc`. Forces accumulate, so their order doesn't matter.
+    ApplyForces,
+    // Friction (which depends on the other forces) and the second half of the step
+    IntegrateEnd,
+    ResolveCollisions,
+    // Joints, after collisions so that they have the last word on where their bodies are
+    SolveConstraints,
+    // Runs on the final state of the step
+    PostSolve,
+}
+
+fn physics_step_system(world: &mut World) {
+    for _ in 0..world.resource::<PhysicsConfig>().substeps.max(1) {
+        world.run_schedule(PhysicsSchedule);
+    }
+}
+
+// N advances a single step while paused. `held` stops the simulation while playing, e.g. while
+// netplay waits for the other player's input.
+#[derive(Resource, Default)]
+pub struct SimulationControl {
+    pub step_requested: bool,
+    pub held: bool,
+}
+
+// Time step of each of this frame's physics steps. `scale` slows down (or speeds up) the
+// simulation relative to real time.
+#[derive(Resource)]
+pub struct PhysicsTime {
+    pub delta: f32,
+    pub scale: f32,
+}
+
+impl Default for PhysicsTime {
+    fn default() -> Self {
+        Self {
+            delta: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+// Holding the slow motion key (Tab by default) slows the simulation down. With `auto` on (toggled
+// with O by default), big impacts also trigger a short slow motion, at most once per cooldown.
+#[derive(Resource, Default)]
+pub struct SlowMotion {
+    pub auto: bool,
+    // Real seconds left of an automatically triggered slow motion
+    pub auto_remaining: f32,
+    pub auto_cooldown: f32,
+}
+
+// A body hit the floor. `impulse` is the magnitude of the normal impulse.
+pub struct CollisionEvent {
+    pub entity: Entity,
+    pub impulse: f32,
+}
+
+// A body bounced off the floor. Sent for every bounce, along with a CollisionEvent.
+pub struct BounceEvent {
+    pub entity: Entity,
+    pub impact_speed: f32,
+    // Coefficient of restitution used for the bounce, after combining and thresholds
+    pub restitution_applied: f32,
+}
+
+// A body started touching the ground. Sent once per landing, with the speed of its first impact.
+pub struct LandedEvent {
+    pub entity: Entity,
+    pub impact_speed: f32,
+    // Height of what it landed on: the floor, or an elevator
+    pub surface_y: f32,
+}
+
+pub(crate) fn simulation_running(
+    state: Res<State<AppState>>,
+    control: Res<SimulationControl>,
+) -> bool {
+    (state.0 == AppState::Playing || control.step_requested) && !control.held
+}
+
+fn simulation_control_system(
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    state: Res<State<AppState>>,
+    mut control: ResMut<SimulationControl>,
+) {
+    // A step only lasts for the frame it was requested in
+    control.step_requested =
+        state.0 == AppState::Paused && settings.input.just_pressed(&input, InputAction::Step);
+}
+
+pub(crate) fn physics_time_system(
+    time: Res<Time>,
+    config: Res<PhysicsConfig>,
+    control: Res<SimulationControl>,
+    mut physics_time: ResMut<PhysicsTime>,
+) {
+    // Steps advance by a fixed amount regardless of how long the frame took
+    let frame_dt = if control.step_requested {
+        STEP_DT
+    } else {
+        (time.delta_seconds() * physics_time.scale).min(config.max_dt)
+    };
+    physics_time.delta = frame_dt / config.substeps.max(1) as f32;
+}
+
+// Moves `scale` linearly towards `target`, taking SLOW_MOTION_RAMP_TIME for the full range
+fn ramp_time_scale(scale: f32, target: f32, real_dt: f32) -> f32 {
+    let max_change = (1.0 - SLOW_MOTION_SCALE) / SLOW_MOTION_RAMP_TIME * real_dt;
+    scale + (target - scale).clamp(-max_change, max_change)
+}
+
+fn slow_motion_system(
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut physics_time: ResMut<PhysicsTime>,
+) {
+    let real_dt = time.delta_seconds();
+    if settings
+        .input
+        .just_pressed(&input, InputAction::AutoSlowMotion)
+    {
+        slow_motion.auto = !slow_motion.auto;
+    }
+
+    slow_motion.auto_remaining = (slow_motion.auto_remaining - real_dt).max(0.0);
+    slow_motion.auto_cooldown = (slow_motion.auto_cooldown - real_dt).max(0.0);
+    let big_impact = collisions
+        .iter()
+        .any(|collision| collision.impulse > SLOW_MOTION_IMPULSE);
+    if slow_motion.auto && big_impact && slow_motion.auto_cooldown == 0.0 {
+        slow_motion.auto_remaining = SLOW_MOTION_AUTO_DURATION;
+        slow_motion.auto_cooldown = SLOW_MOTION_AUTO_COOLDOWN;
+    }
+
+    let target = if settings.input.pressed(&input, InputAction::SlowMotion)
+        || slow_motion.auto_remaining > 0.0
+    {
+        SLOW_MOTION_SCALE
+    } else {
+        1.0
+    };
+    physics_time.scale = ramp_time_scale(physics_time.scale, target, real_dt);
+}
+
+// `vel` and `acc` are those of the center of mass, and the body turns about it. `moment_of_inertia`
+// is about the center of mass too.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct PhysObj {
+    pub mass: f32,
+    pub vel: Vec2,
+    pub acc: Vec2,
+    pub acc_prev: Vec2,
+    pub moment_of_inertia: f32,
+    pub angular_vel: f32,
+    pub angular_acc: f32,
+    pub angular_acc_prev: f32,
+    // From the Transform's origin (the center of the collider) to the center of mass, in the body's
+    // own frame. Nonzero for weighted bodies, which wobble as they roll.
+    #[serde(default)]
+    pub com_offset: Vec2,
+}
+
+// Mass of a solid disk of `radius`, with `density` in mass per unit area
+pub fn disk_mass(density: f32, radius: f32) -> f32 {
+    density * PI * radius * radius
+}
+
+// About the disk's center
+pub fn disk_moment_of_inertia(mass: f32, radius: f32) -> f32 {
+    0.5 * mass * radius * radius
+}
+
+impl PhysObj {
+    // `com_offset` in world space, for a body turned by `rotation`. Exactly zero for bodies without
+    // an offset, so that they take the same path through the math as before offsets existed.
+    pub fn com_arm(&self, rotation: Quat) -> Vec2 {
+        if self.com_offset == Vec2::ZERO {
+            Vec2::ZERO
+        } else {
+            (rotation * self.com_offset.extend(0.0)).truncate()
+        }
+    }
+}
+
+// Which body this was to be spawned, counting from 0. Given to each new PhysObj in the frame it
+// appears, in query order, which is the same for the same spawns.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpawnOrder(pub u64);
+
+#[derive(Resource, Default)]
+struct NextSpawnOrder(u64);
+
+fn spawn_order_system(
+    mut commands: Commands,
+    mut next: ResMut<NextSpawnOrder>,
+    bodies: Query<Entity, (With<PhysObj>, Without<SpawnOrder>)>,
+) {
+    for entity in &bodies {
+        commands.entity(entity).insert(SpawnOrder(next.0));
+        next.0 += 1;
+    }
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Gravity(pub f32);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Gravity(PhysicsConfig::default().gravity)
+    }
+}
+
+// Turns a body's Gravity off while set, e.g. for a dash. It's set in place rather than inserted and
+// removed, so it takes effect on the step it's set, and the Gravity is left as it was.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GravitySuppressed(pub bool);
+
+impl GravitySuppressed {
+    pub fn is_set(suppressed: Option<&GravitySuppressed>) -> bool {
+        suppressed.is_some_and(|suppressed| suppressed.0)
+    }
+}
+
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub enum Collider {
+    Ball {
+        radius: f32,
+        coef_of_restitution: f32,
+        touching_ground: bool,
+        kinetic_friction: f32,
+        friction_acc: f32,
+        friction_acc_prev: f32,
+    },
+}
+
+fn gravity_system(
+    config: Res<PhysicsConfig>,
+    mut timings: ResMut<PhysicsTimings>,
+    mut query: Query<(&mut PhysObj, &Gravity, Option<&GravitySuppressed>), Without<Sleeping>>,
+) {
+    let _span = info_span!("physics_forces").entered();
+    let start = timings.start();
+
+    let apply =
+        |phys_obj: &mut PhysObj, gravity: &Gravity, suppressed: Option<&GravitySuppressed>| {
+            if !GravitySuppressed::is_set(suppressed) {
+                phys_obj.acc += Vec2::NEG_Y * gravity.0;
+            }
+        };
+    if config.serial {
+        for (mut phys_obj, gravity, suppressed) in &mut query {
+            apply(&mut phys_obj, gravity, suppressed);
+        }
+    } else {
+        query
+            .par_iter_mut()
+            .for_each_mut(|(mut phys_obj, gravity, suppressed)| {
+                apply(&mut phys_obj, gravity, suppressed)
+            });
+    }
+
+    record_timing(start, &mut timings.forces);
+}
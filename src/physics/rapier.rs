@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    plugin::PhysicsSet as RapierSet,
+    prelude::{
+        ActiveEvents, CoefficientCombineRule, Collider as RapierCollider, ColliderMassProperties,
+        CollisionEvent as RapierCollisionEvent, ExternalForce, ExternalImpulse, Friction,
+        GravityScale, MassProperties, NoUserData, RapierConfiguration, RapierPhysicsPlugin,
+        Restitution, RigidBody, TimestepMode, Velocity,
+    },
+};
+
+use super::{
+    Collider, CombineRule, Gravity, GravitySuppressed, LandedEvent, PhysObj, PhysicsConfig,
+    PhysicsSchedule, PhysicsSet, PhysicsTime,
+};
+
+// The floor is a box this wide, and this thick under PhysicsConfig::floor_y, rather than
+// infinite. Wider than any level.
+const FLOOR_HALF_WIDTH: f32 = 1_000_000.0;
+const FLOOR_HALF_THICKNESS: f32 = 500.0;
+
+// Which solver PhysicsPlugin runs. Only there with the `rapier-backend` feature, which makes
+// Rapier the default; the native solver stays available for comparing the two.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhysicsBackend {
+    Native,
+    #[default]
+    Rapier,
+}
+
+// Steps the bodies with bevy_rapier instead of the native solver. The game keeps writing PhysObj,
+// Collider and Gravity, and a sync layer translates them every step:
+// - new bodies, and ones whose mass or radius changed, get a rapier RigidBody, Collider and mass
+// - Gravity becomes a GravityScale (rapier's gravity is one unit down, so it's the strength)
+// - `acc`/`angular_acc` from the forces become an ExternalForce, and changes to `vel` and
+//   `angular_vel` since the last step (jumps, saws) an ExternalImpulse
+// - after the step, rapier's Velocity is copied back, and `touching_ground` follows its contact
+//   events with the floor
+// Joints, floor gaps, materials and the native sleeping aren't translated, so levels that rely on
+// them behave differently.
+pub(super) fn add_rapier_solver(app: &mut App) {
+    // Rapier places new bodies by their GlobalTransform
+    if !app.is_plugin_added::<TransformPlugin>() {
+        app.add_plugin(TransformPlugin);
+    }
+    app.add_plugin(
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(1.0).with_default_system_setup(false),
+    )
+    .insert_resource(RapierConfiguration {
+        gravity: Vec2::NEG_Y,
+        timestep_mode: TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        },
+        ..default()
+    })
+    .add_startup_system(rapier_floor_setup)
+    .add_systems(
+        (rapier_clear_forces_system, rapier_floor_system)
+            .in_set(PhysicsSet::IntegrateStart)
+            .in_schedule(PhysicsSchedule),
+    )
+    .add_system(
+        rapier_push_system
+            .in_set(PhysicsSet::IntegrateEnd)
+            .in_schedule(PhysicsSchedule),
+    )
+    .add_systems(
+        (
+            RapierPhysicsPlugin::<NoUserData>::get_systems(RapierSet::SyncBackend),
+            RapierPhysicsPlugin::<NoUserData>::get_systems(RapierSet::SyncBackendFlush),
+            RapierPhysicsPlugin::<NoUserData>::get_systems(RapierSet::StepSimulation),
+            RapierPhysicsPlugin::<NoUserData>::get_systems(RapierSet::Writeback),
+        )
+            .chain()
+            .in_set(PhysicsSet::ResolveCollisions)
+            .in_schedule(PhysicsSchedule),
+    )
+    .add_system(
+        rapier_pull_system
+            .after(RapierSet::Writeback)
+            .in_set(PhysicsSet::ResolveCollisions)
+            .in_schedule(PhysicsSchedule),
+    );
+}
+
+// The infinite floor at PhysicsConfig::floor_y
+#[derive(Component)]
+pub struct RapierFloor;
+
+// What the sync layer last gave rapier, or got back from it
+#[derive(Component, Clone, Copy)]
+pub struct RapierSynced {
+    mass: f32,
+    radius: Option<f32>,
+    vel: Vec2,
+    angular_vel: f32,
+}
+
+fn rapier_floor_setup(mut commands: Commands, config: Res<PhysicsConfig>) {
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(
+            0.0,
+            config.floor_y - FLOOR_HALF_THICKNESS,
+            0.0,
+        )),
+        RigidBody::Fixed,
+        RapierCollider::cuboid(FLOOR_HALF_WIDTH, FLOOR_HALF_THICKNESS),
+        RapierFloor,
+    ));
+}
+
+// Keeps the floor where the config says, as slippery and bouncy as it says
+fn rapier_floor_system(
+    config: Res<PhysicsConfig>,
+    mut floors: Query<(Entity, &mut Transform), With<RapierFloor>>,
+    mut commands: Commands,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    for (entity, mut transform) in &mut floors {
+        transform.translation.y = config.floor_y - FLOOR_HALF_THICKNESS;
+        commands.entity(entity).insert((
+            Restitution {
+                coefficient: config.floor_restitution,
+                combine_rule: combine_rule(config.restitution_combine),
+            },
+            Friction {
+                coefficient: config.floor_friction,
+                combine_rule: combine_rule(config.friction_combine),
+            },
+        ));
+    }
+}
+
+fn combine_rule(rule: CombineRule) -> CoefficientCombineRule {
+    match rule {
+        CombineRule::Average => CoefficientCombineRule::Average,
+        CombineRule::Min => CoefficientCombineRule::Min,
+        CombineRule::Max => CoefficientCombineRule::Max,
+        CombineRule::Multiply => CoefficientCombineRule::Multiply,
+    }
+}
+
+// Forces are added up from scratch every step, as in the native integrator
+fn rapier_clear_forces_system(mut query: Query<&mut PhysObj>) {
+    for mut phys_obj in &mut query {
+        phys_obj.acc_prev = phys_obj.acc;
+        phys_obj.acc = Vec2::ZERO;
+        phys_obj.angular_acc_prev = phys_obj.angular_acc;
+        phys_obj.angular_acc = 0.0;
+    }
+}
+
+// Hands this step's bodies, forces and impulses to rapier
+fn rapier_push_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut query: Query<(
+        Entity,
+        &PhysObj,
+        Option<&Collider>,
+        Option<&Gravity>,
+        Option<&GravitySuppressed>,
+        Option<&RapierSynced>,
+        Option<(&mut ExternalForce, &mut ExternalImpulse, &mut GravityScale)>,
+    )>,
+) {
+    rapier_config.timestep_mode = TimestepMode::Fixed {
+        dt: time.delta,
+        substeps: 1,
+    };
+
+    for (entity, phys_obj, collider, gravity, suppressed, synced, drive) in &mut query {
+        let radius = collider.map(|&Collider::Ball { radius, .. }| radius);
+        let gravity_scale = match gravity {
+            Some(gravity) if !GravitySuppressed::is_set(suppressed) => gravity.0,
+            _ => 0.0,
+        };
+        let force = ExternalForce {
+            force: phys_obj.acc * phys_obj.mass,
+            torque: phys_obj.angular_acc * phys_obj.moment_of_inertia,
+        };
+        match (synced, drive) {
+            (Some(synced), Some((mut external_force, mut impulse, mut scale)))
+                if synced.mass == phys_obj.mass && synced.radius == radius =>
+            {
+                *external_force = force;
+                *impulse = ExternalImpulse {
+                    impulse: (phys_obj.vel - synced.vel) * phys_obj.mass,
+                    torque_impulse: (phys_obj.angular_vel - synced.angular_vel)
+                        * phys_obj.moment_of_inertia,
+                };
+                scale.0 = gravity_scale;
+            }
+            // New, or changed size (see SizeChange) or mass: (re)built with its velocity as it is
+            // now, and pushed from the next step
+            _ => {
+                let mut entity_commands = commands.entity(entity);
+                entity_commands.insert((
+                    RigidBody::Dynamic,
+                    Velocity {
+                        linvel: phys_obj.vel,
+                        angvel: phys_obj.angular_vel,
+                    },
+                    ColliderMassProperties::MassProperties(MassProperties {
+                        local_center_of_mass: phys_obj.com_offset,
+                        mass: phys_obj.mass,
+                        principal_inertia: phys_obj.moment_of_inertia,
+                    }),
+                    force,
+                    ExternalImpulse::default(),
+                    GravityScale(gravity_scale),
+                    RapierSynced {
+                        mass: phys_obj.mass,
+                        radius,
+                        vel: phys_obj.vel,
+                        angular_vel: phys_obj.angular_vel,
+                    },
+                ));
+                if let Some(&Collider::Ball {
+                    radius,
+                    coef_of_restitution,
+                    kinetic_friction,
+                    ..
+                }) = collider
+                {
+                    entity_commands.insert((
+                        RapierCollider::ball(radius),
+                        Restitution {
+                            coefficient: coef_of_restitution,
+                            combine_rule: combine_rule(config.restitution_combine),
+                        },
+                        Friction {
+                            coefficient: kinetic_friction,
+                            combine_rule: combine_rule(config.friction_combine),
+                        },
+                        ActiveEvents::COLLISION_EVENTS,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// Keeps `touching_ground` up to date from rapier's contacts with the floor, and copies its
+// velocities back
+fn rapier_pull_system(
+    config: Res<PhysicsConfig>,
+    mut contacts: EventReader<RapierCollisionEvent>,
+    mut landed: EventWriter<LandedEvent>,
+    floors: Query<(), With<RapierFloor>>,
+    mut query: Query<(
+        &Velocity,
+        &mut PhysObj,
+        Option<&mut Collider>,
+        &mut RapierSynced,
+    )>,
+) {
+    for contact in contacts.iter() {
+        let (a, b, touching) = match *contact {
+            RapierCollisionEvent::Started(a, b, _) => (a, b, true),
+            RapierCollisionEvent::Stopped(a, b, _) => (a, b, false),
+        };
+        let body = match (floors.contains(a), floors.contains(b)) {
+            (true, false) => b,
+            (false, true) => a,
+            _ => continue,
+        };
+        let Ok((_, phys_obj, Some(mut collider), _)) = query.get_mut(body) else {
+            continue;
+        };
+        let Collider::Ball {
+            touching_ground, ..
+        } = &mut *collider;
+        // Still the velocity it had going into the step
+        if touching && !*touching_ground {
+            landed.send(LandedEvent {
+                entity: body,
+                impact_speed: phys_obj.vel.y.abs(),
+                surface_y: config.floor_y,
+            });
+        }
+        *touching_ground = touching;
+    }
+
+    for (velocity, mut phys_obj, _, mut synced) in &mut query {
+        phys_obj.vel = velocity.linvel;
+        phys_obj.angular_vel = velocity.angvel;
+        synced.vel = velocity.linvel;
+        synced.angular_vel = velocity.angvel;
+    }
+}
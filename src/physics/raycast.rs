@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+// Where a ray first met a segment, `distance` along it from its origin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub point: Vec2,
+    pub distance: f32,
+}
+
+// The first of `segments` (each an entity and the two ends of one of its edges) that a ray from
+// `origin` along the unit vector `direction` crosses within `max_distance`. Segments running along
+// the ray aren't hit.
+pub fn raycast(
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    segments: impl IntoIterator<Item = (Entity, Vec2, Vec2)>,
+) -> Option<RayHit> {
+    segments
+        .into_iter()
+        .filter_map(|(entity, start, end)| {
+            let edge = end - start;
+            let denominator = direction.perp_dot(edge);
+            if denominator == 0.0 {
+                return None;
+            }
+            let to_start = start - origin;
+            let distance = to_start.perp_dot(edge) / denominator;
+            let along = to_start.perp_dot(direction) / denominator;
+            let hit = (0.0..=max_distance).contains(&distance) && (0.0..=1.0).contains(&along);
+            hit.then(|| RayHit {
+                entity,
+                point: origin + distance * direction,
+                distance,
+            })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
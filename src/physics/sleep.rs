@@ -0,0 +1,196 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+
+use crate::level::{surface_below, surface_friction_scale, Floor, SurfaceMaterial};
+
+use super::{
+    forces::{CustomForces, ForceFn},
+    Collider, Gravity, PhysObj, PhysicsConfig, PhysicsTime,
+};
+
+// Bodies slower than these, resting on the floor for SLEEP_TIME, fall asleep
+const SLEEP_SPEED: f32 = 2.0;
+const SLEEP_ANGULAR_SPEED: f32 = 0.05;
+const SLEEP_TIME: f32 = 1.0;
+
+// A body at rest that the physics leaves alone until something moves it. The physics systems
+// filter these out, so sleeping bodies cost nothing per step. Writing to a sleeping body's PhysObj
+// or Gravity (e.g. an impulse) wakes it up.
+#[derive(Component)]
+pub struct Sleeping;
+
+// A floor or platform: no PhysObj, and only moved by something outside the physics. The physics
+// finds them through the StaticHash rather than going through all of them for every ball.
+#[derive(Component)]
+pub struct StaticBody;
+
+// Width of the columns of the StaticHash
+pub const STATIC_CELL_WIDTH: f32 = 200.0;
+
+// The StaticBody floors by the columns of STATIC_CELL_WIDTH they're over. Static bodies hardly
+// ever move, so it's kept from step to step, and only rebuilt when one of them moves, comes or
+// goes.
+#[derive(Resource, Default)]
+pub struct StaticHash {
+    cells: HashMap<i32, Vec<Entity>>,
+    // How many times it's been rebuilt
+    pub rebuilds: u32,
+}
+
+impl StaticHash {
+    // The static bodies that might be at `x`
+    pub fn near(&self, x: f32) -> &[Entity] {
+        self.cells.get(&static_cell(x)).map_or(&[], Vec::as_slice)
+    }
+
+    // Replaces what's in the hash with `bodies`, each an entity and the x of its center and its
+    // width
+    pub fn rebuild(&mut self, bodies: impl IntoIterator<Item = (Entity, f32, f32)>) {
+        self.cells.clear();
+        for (entity, x, width) in bodies {
+            for cell in static_cell(x - 0.5 * width)..=static_cell(x + 0.5 * width) {
+                self.cells.entry(cell).or_default().push(entity);
+            }
+        }
+        self.rebuilds += 1;
+    }
+}
+
+// The StaticBody floors, looked up through the StaticHash
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct StaticFloors<'w, 's> {
+    hash: Res<'w, StaticHash>,
+    floors: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            &'static Floor,
+            Option<&'static SurfaceMaterial>,
+        ),
+        (With<StaticBody>, Without<PhysObj>),
+    >,
+}
+
+impl StaticFloors<'_, '_> {
+    // See surface_below
+    pub fn surface_below(&self, position: Vec2) -> Option<SurfaceMaterial> {
+        surface_below(position, self.floors.iter_many(self.hash.near(position.x)))
+    }
+
+    // See surface_friction_scale
+    pub fn friction_scale(&self, position: Vec2) -> f32 {
+        surface_friction_scale(position, self.floors.iter_many(self.hash.near(position.x)))
+    }
+}
+
+fn static_cell(x: f32) -> i32 {
+    (x / STATIC_CELL_WIDTH).floor() as i32
+}
+
+// Keeps a body from falling asleep, e.g. one held in shape by forces from other bodies
+#[derive(Component)]
+pub struct KeepAwake;
+
+// How long a body has been at rest
+#[derive(Component, Default)]
+pub struct RestTime(pub f32);
+
+// Writes from outside the physics are the only changes sleeping bodies see, as the physics
+// doesn't touch them. Changing the config (e.g. moving the floor) wakes everything.
+#[allow(clippy::type_complexity)]
+pub(super) fn wake_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    all: Query<Entity, With<Sleeping>>,
+    changed: Query<Entity, (With<Sleeping>, Or<(Changed<PhysObj>, Changed<Gravity>)>)>,
+) {
+    let mut wake = |entity| {
+        commands
+            .entity(entity)
+            .remove::<Sleeping>()
+            .insert(RestTime(0.0));
+    };
+    if config.is_changed() {
+        all.iter().for_each(&mut wake);
+    } else {
+        changed.iter().for_each(&mut wake);
+    }
+}
+
+// Runs after wake_system in the same frame, so that it doesn't see the last steps' writes as
+// coming from outside
+#[allow(clippy::type_complexity)]
+pub(super) fn sleep_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut query: Query<
+        (Entity, &mut PhysObj, &Collider, Option<&mut RestTime>),
+        (
+            Without<Sleeping>,
+            Without<KeepAwake>,
+            Without<CustomForces>,
+            Without<ForceFn>,
+        ),
+    >,
+) {
+    // The frame's simulated time, so that bodies fall asleep after the same steps however long
+    // the frames took
+    let dt = time.delta * config.substeps.max(1) as f32;
+    // Nothing on a moving floor is at rest
+    let still_floor = config.floor_velocity == 0.0;
+    for (entity, mut phys_obj, collider, rest_time) in &mut query {
+        let Collider::Ball {
+            touching_ground, ..
+        } = *collider;
+        let at_rest = still_floor
+            && touching_ground
+            && phys_obj.vel.length() < SLEEP_SPEED
+            && phys_obj.angular_vel.abs() < SLEEP_ANGULAR_SPEED;
+        match (at_rest, rest_time) {
+            (true, Some(mut rest_time)) => {
+                rest_time.0 += dt;
+                if rest_time.0 >= SLEEP_TIME {
+                    // Not a change from outside, so it mustn't wake the body right away
+                    let phys_obj = phys_obj.bypass_change_detection();
+                    phys_obj.vel = Vec2::ZERO;
+                    phys_obj.angular_vel = 0.0;
+                    commands.entity(entity).insert(Sleeping);
+                }
+            }
+            (true, None) => {
+                commands.entity(entity).insert(RestTime(dt));
+            }
+            (false, Some(mut rest_time)) if rest_time.0 > 0.0 => rest_time.0 = 0.0,
+            (false, _) => {}
+        }
+    }
+}
+
+// Rebuilds the StaticHash when a static body has moved, changed size, or been added or removed
+// since the last frame, and leaves it be otherwise
+#[allow(clippy::type_complexity)]
+pub(super) fn static_hash_system(
+    mut hash: ResMut<StaticHash>,
+    changed: Query<
+        (),
+        (
+            With<StaticBody>,
+            Or<(Changed<Transform>, Changed<Floor>, Added<StaticBody>)>,
+        ),
+    >,
+    mut removed: RemovedComponents<StaticBody>,
+    bodies: Query<(Entity, &Transform, &Floor), With<StaticBody>>,
+) {
+    // Read every frame, so that old removals don't count again
+    let removed = removed.iter().count() > 0;
+    if changed.is_empty() && !removed {
+        return;
+    }
+    hash.rebuild(
+        bodies
+            .iter()
+            .map(|(entity, transform, floor)| (entity, transform.translation.x, floor.width)),
+    );
+}
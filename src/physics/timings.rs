@@ -0,0 +1,101 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::*,
+    utils::{Duration, Instant},
+};
+
+// How long each part of the physics took during the last frame. Only measured while `enabled`
+// (toggled with F4), otherwise the durations stay zero.
+#[derive(Resource, Default)]
+pub struct PhysicsTimings {
+    pub enabled: bool,
+    pub integrator: Duration,
+    pub forces: Duration,
+    // Finding the pairs of balls that overlap
+    pub broad_phase: Duration,
+    pub narrow_phase: Duration,
+    pub friction: Duration,
+}
+
+impl PhysicsTimings {
+    pub(crate) fn start(&self) -> Option<Instant> {
+        self.enabled.then(Instant::now)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.integrator + self.forces + self.broad_phase + self.narrow_phase + self.friction
+    }
+}
+
+// Adds the time since `start` to a phase, if it was measured
+pub(crate) fn record_timing(start: Option<Instant>, phase: &mut Duration) {
+    if let Some(start) = start {
+        *phase += start.elapsed();
+    }
+}
+
+pub const PHYSICS_INTEGRATOR_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x6a0e_6d1f_2c4b_4f5e_9a63_1d4c_0b7e_5a01);
+pub const PHYSICS_FORCES_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x6a0e_6d1f_2c4b_4f5e_9a63_1d4c_0b7e_5a02);
+pub const PHYSICS_NARROW_PHASE_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x6a0e_6d1f_2c4b_4f5e_9a63_1d4c_0b7e_5a03);
+pub const PHYSICS_FRICTION_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x6a0e_6d1f_2c4b_4f5e_9a63_1d4c_0b7e_5a04);
+pub const PHYSICS_TOTAL_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x6a0e_6d1f_2c4b_4f5e_9a63_1d4c_0b7e_5a05);
+pub const PHYSICS_BROAD_PHASE_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x6a0e_6d1f_2c4b_4f5e_9a63_1d4c_0b7e_5a06);
+
+// Diagnostics are missing from headless apps, where the timings are only kept in PhysicsTimings
+pub(super) fn physics_timings_setup(diagnostics: Option<ResMut<Diagnostics>>) {
+    let Some(mut diagnostics) = diagnostics else {
+        return;
+    };
+    for (id, name) in [
+        (PHYSICS_INTEGRATOR_TIME, "physics_integrator_ms"),
+        (PHYSICS_FORCES_TIME, "physics_forces_ms"),
+        (PHYSICS_BROAD_PHASE_TIME, "physics_broad_phase_ms"),
+        (PHYSICS_NARROW_PHASE_TIME, "physics_narrow_phase_ms"),
+        (PHYSICS_FRICTION_TIME, "physics_friction_ms"),
+        (PHYSICS_TOTAL_TIME, "physics_total_ms"),
+    ] {
+        diagnostics.add(Diagnostic::new(id, name, 20).with_suffix("ms"));
+    }
+}
+
+pub(super) fn physics_profiling_enabled(timings: Res<PhysicsTimings>) -> bool {
+    timings.enabled
+}
+
+pub(super) fn physics_timings_reset_system(
+    input: Res<Input<KeyCode>>,
+    mut timings: ResMut<PhysicsTimings>,
+) {
+    if input.just_pressed(KeyCode::F4) {
+        timings.enabled = !timings.enabled;
+    }
+    *timings = PhysicsTimings {
+        enabled: timings.enabled,
+        ..default()
+    };
+}
+
+pub(super) fn physics_timings_diagnostics_system(
+    timings: Res<PhysicsTimings>,
+    diagnostics: Option<ResMut<Diagnostics>>,
+) {
+    let Some(mut diagnostics) = diagnostics else {
+        return;
+    };
+    for (id, duration) in [
+        (PHYSICS_INTEGRATOR_TIME, timings.integrator),
+        (PHYSICS_FORCES_TIME, timings.forces),
+        (PHYSICS_BROAD_PHASE_TIME, timings.broad_phase),
+        (PHYSICS_NARROW_PHASE_TIME, timings.narrow_phase),
+        (PHYSICS_FRICTION_TIME, timings.friction),
+        (PHYSICS_TOTAL_TIME, timings.total()),
+    ] {
+        diagnostics.add_measurement(id, || duration.as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,1178 @@
+use bevy::{prelude::*, utils::Duration};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blob::Blob,
+    debug::lines::DebugLines,
+    elevator::elevator_contact_system,
+    grapple::Grappled,
+    level::{floor_below, surface_friction_scale, Floor, SurfaceMaterial},
+    physics::{
+        BounceEvent, Collider, Gravity, GravitySuppressed, LandedEvent, PhysObj, PhysicsConfig,
+        PhysicsSchedule, PhysicsSet, PhysicsStep, PhysicsTime,
+    },
+    portal::{crosses_portal, Portal},
+    rope::Grabbing,
+    settings::{InputAction, Settings},
+    status::StatusEffects,
+    toast::ToastEvent,
+};
+
+pub const PLAYER_RADIUS: f32 = 25.0;
+const TRAJECTORY_SAMPLES: usize = 24;
+const TRAJECTORY_MAX_TIME: f32 = 3.0;
+// The landing assist acts this long before the predicted landing, with at most this torque
+const LANDING_ASSIST_TIME: f32 = 0.15;
+const LANDING_ASSIST_MAX_TORQUE: f32 = 1_000_000.0;
+// Seed of the overheated spinner's wobble, so that runs stay deterministic
+const WOBBLE_SEED: u32 = 0x9e37_79b9;
+// For players saved before slams
+const DEFAULT_SLAM_SPEED: f32 = 1500.0;
+// Moving slower than this sideways, a dash goes the way the player last rolled
+const DASH_MIN_SPEED: f32 = 20.0;
+
+// Player controls
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<GlideConfig>()
+            .init_resource::<SpinHeatConfig>()
+            .init_resource::<SlamConfig>()
+            .init_resource::<SlamsStarted>()
+            .init_resource::<JumpChargeConfig>()
+            .init_resource::<ControlMode>()
+            .init_resource::<DirectDriveConfig>()
+            .add_event::<JumpEvent>()
+            .add_event::<GlideEvent>()
+            .add_event::<OverheatEvent>()
+            .add_event::<SlamEvent>()
+            .add_event::<LandedEvent>()
+            .add_event::<ToastEvent>()
+            .add_system(player_input_system.before(PhysicsStep))
+            .add_systems(
+                (control_mode_toggle_system, control_mode_switch_system)
+                    .chain()
+                    .before(PhysicsStep),
+            )
+            .add_systems(
+                (
+                    player_impulse_system,
+                    dash_system,
+                    glide_system,
+                    slam_system,
+                    landing_assist_system,
+                )
+                    .chain()
+                    .in_set(PhysicsSet::ApplyImpulses)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_systems(
+                (
+                    slam_landing_system
+                        .after(PhysicsSet::ResolveCollisions)
+                        .after(elevator_contact_system)
+                        .before(PhysicsSet::SolveConstraints),
+                    spin_heat_system.in_set(PhysicsSet::ApplyForces),
+                    player_force_system
+                        .after(spin_heat_system)
+                        .in_set(PhysicsSet::ApplyForces)
+                        .run_if(resource_equals(ControlMode::Torque)),
+                    direct_drive_system
+                        .after(spin_heat_system)
+                        .in_set(PhysicsSet::ApplyForces)
+                        .run_if(resource_equals(ControlMode::Direct)),
+                )
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(
+                glide_force_system
+                    .in_set(PhysicsSet::ApplyForces)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+// What the player asked for this frame. Filled from the keyboard, so anything that wants to
+// control the player instead (e.g. replays) overrides it after player_input_system. As a component
+// on a player, it's that player's input instead, e.g. for the other player in netplay.
+#[derive(Resource, Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInput {
+    pub jump: bool,
+    pub spin_left: bool,
+    pub spin_right: bool,
+    // Gravity is off while this is held
+    pub zero_gravity: bool,
+    // See RopePlugin. Defaulted, so that replays from before ropes still load.
+    #[serde(default)]
+    pub climb_up: bool,
+    #[serde(default)]
+    pub climb_down: bool,
+    #[serde(default)]
+    pub dash: bool,
+}
+
+// The player jumped off the ground
+pub struct JumpEvent {
+    pub entity: Entity,
+    pub impulse: f32,
+}
+
+// The player started gliding
+pub struct GlideEvent {
+    pub entity: Entity,
+}
+
+// The player landed a slam, or bounced off a trampoline with one, coming down at `impact_speed`.
+// Sent on top of the landing's BounceEvent.
+pub struct SlamEvent {
+    pub entity: Entity,
+    pub impact_speed: f32,
+}
+
+// A slam landing shoves the balls within `radius` of the player straight away from it, with an
+// impulse of `impulse` that falls off linearly to nothing at `radius`
+#[derive(Resource, Clone)]
+pub struct SlamConfig {
+    pub radius: f32,
+    pub impulse: f32,
+}
+
+impl Default for SlamConfig {
+    fn default() -> Self {
+        Self {
+            radius: 200.0,
+            impulse: 5000.0,
+        }
+    }
+}
+
+// How the player glides: holding jump while falling slows the fall to `fall_speed`, slows the
+// ball down sideways with `drag` (per second), and turns its spin into sideways drift with
+// `magnus` (drift acceleration per unit of spin and fall speed). Like a real ball's Magnus effect,
+// counter-clockwise spin drifts right while falling.
+#[derive(Resource, Clone)]
+pub struct GlideConfig {
+    pub fall_speed: f32,
+    pub drag: f32,
+    pub magnus: f32,
+}
+
+impl Default for GlideConfig {
+    fn default() -> Self {
+        Self {
+            fall_speed: 200.0,
+            drag: 1.5,
+            magnus: 0.15,
+        }
+    }
+}
+
+// Holding jump on the ground winds the player up for up to `max_time` seconds, and letting go
+// jumps. A full charge jumps with `max_scale` times the player's jump impulse, and turns
+// `spin_transfer` of its spin into speed along the ground, the way it would roll.
+#[derive(Resource, Clone)]
+pub struct JumpChargeConfig {
+    pub max_time: f32,
+    pub max_scale: f32,
+    pub spin_transfer: f32,
+}
+
+impl Default for JumpChargeConfig {
+    fn default() -> Self {
+        Self {
+            max_time: 1.0,
+            max_scale: 2.0,
+            spin_transfer: 0.5,
+        }
+    }
+}
+
+impl JumpChargeConfig {
+    // How far along a charge of `time` seconds is, from 0 to 1
+    pub fn fraction(&self, time: f32) -> f32 {
+        (time / self.max_time).clamp(0.0, 1.0)
+    }
+
+    // What a charge of `time` seconds multiplies the jump impulse by
+    pub fn scale(&self, time: f32) -> f32 {
+        1.0 + (self.max_scale - 1.0) * self.fraction(time)
+    }
+
+    // The change in velocity and in angular velocity of a ball of `radius` jumping with a charge of
+    // `time` seconds
+    pub fn jump(&self, player: &Player, phys_obj: &PhysObj, radius: f32, time: f32) -> (Vec2, f32) {
+        let impulse = player.jump_impulse * self.scale(time);
+        // Spinning counter-clockwise rolls left
+        let spin = self.spin_transfer * self.fraction(time) * phys_obj.angular_vel;
+        (Vec2::new(-spin * radius, impulse / phys_obj.mass), -spin)
+    }
+}
+
+// Where the player's jump is at: it starts charging when jump is pressed on the ground, and jumps
+// on the step it's released. Leaving the ground (or being kept from jumping) while charging drops
+// the charge.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub enum JumpCharge {
+    #[default]
+    Idle,
+    // For this many seconds
+    Charging(f32),
+    Released(f32),
+}
+
+impl JumpCharge {
+    // Where it's at after a step of `dt` with jump `held`
+    pub fn next(self, config: &JumpChargeConfig, held: bool, can_jump: bool, dt: f32) -> Self {
+        match self {
+            JumpCharge::Charging(_) if !can_jump => JumpCharge::Idle,
+            JumpCharge::Charging(time) if held => {
+                JumpCharge::Charging((time + dt).min(config.max_time))
+            }
+            JumpCharge::Charging(time) => JumpCharge::Released(time),
+            _ if held && can_jump => JumpCharge::Charging(0.0),
+            _ => JumpCharge::Idle,
+        }
+    }
+
+    // Seconds of charge so far
+    pub fn time(self) -> f32 {
+        match self {
+            JumpCharge::Idle => 0.0,
+            JumpCharge::Charging(time) | JumpCharge::Released(time) => time,
+        }
+    }
+}
+
+// The player overheated its spinner
+pub struct OverheatEvent {
+    pub entity: Entity,
+}
+
+// Spinning the player faster than `max_safe_spin` (radians per second) for longer than
+// `overheat_time` seconds overheats it: its torque is cut for `cooldown` seconds, while random
+// torques of up to `wobble_torque` shake it. Below the safe spin, the heat goes back down at
+// `decay` seconds' worth per second.
+#[derive(Resource, Clone)]
+pub struct SpinHeatConfig {
+    pub max_safe_spin: f32,
+    pub overheat_time: f32,
+    pub decay: f32,
+    pub cooldown: f32,
+    pub wobble_torque: f32,
+}
+
+impl Default for SpinHeatConfig {
+    fn default() -> Self {
+        Self {
+            max_safe_spin: 50.0,
+            overheat_time: 1.0,
+            decay: 0.5,
+            cooldown: 2.0,
+            wobble_torque: 40_000.0,
+        }
+    }
+}
+
+// How hot the player's spinner is. Added to the player the first time it's simulated.
+#[derive(Component, Clone, Copy)]
+pub struct SpinHeat {
+    // Seconds spent over the safe spin, less what's cooled off since
+    pub heat: f32,
+    // Seconds left until an overheated spinner answers to the keys again
+    pub cooldown: f32,
+    wobble: u32,
+}
+
+impl Default for SpinHeat {
+    fn default() -> Self {
+        Self {
+            heat: 0.0,
+            cooldown: 0.0,
+            wobble: WOBBLE_SEED,
+        }
+    }
+}
+
+impl SpinHeat {
+    pub fn overheated(&self) -> bool {
+        self.cooldown > 0.0
+    }
+
+    // Heats or cools the spinner over `dt` of spinning at `angular_vel`, and returns whether that
+    // overheated it. Only spin the player is `driving` (holding the key that spins it that way)
+    // heats it up, so spin from anything else, like a bounce off the floor, never overheats it.
+    pub fn update(
+        &mut self,
+        config: &SpinHeatConfig,
+        angular_vel: f32,
+        driving: bool,
+        dt: f32,
+    ) -> bool {
+        if self.overheated() {
+            self.cooldown = (self.cooldown - dt).max(0.0);
+            return false;
+        }
+        if driving && angular_vel.abs() > config.max_safe_spin {
+            self.heat += dt;
+        } else {
+            self.heat = (self.heat - config.decay * dt).max(0.0);
+        }
+        if self.heat <= config.overheat_time {
+            return false;
+        }
+        self.heat = 0.0;
+        self.cooldown = config.cooldown;
+        true
+    }
+
+    // Uniform in [-1, 1]
+    fn next_wobble(&mut self) -> f32 {
+        self.wobble ^= self.wobble << 13;
+        self.wobble ^= self.wobble >> 17;
+        self.wobble ^= self.wobble << 5;
+        self.wobble as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+// On the player while it glides
+#[derive(Component)]
+pub struct Gliding;
+
+// Slamming down, from pressing climb down in the air until landing, bouncing off a trampoline,
+// grabbing a rope or jumping
+#[derive(Component)]
+pub struct Slamming;
+
+// The players that started slamming this step. Slamming is only inserted at the end of the step,
+// and a fast enough slam has landed by then.
+#[derive(Resource, Default)]
+struct SlamsStarted(Vec<Entity>);
+
+// Draws where a jump would take the player. Toggled with the trajectory key (T by default), and
+// also shown while jump is held.
+#[derive(Resource, Default)]
+pub struct TrajectoryPrediction {
+    pub enabled: bool,
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub jump_impulse: f32,
+    pub torque: f32,
+    // How fast a slam heads down
+    #[serde(default = "default_slam_speed")]
+    pub slam_speed: f32,
+}
+
+fn default_slam_speed() -> f32 {
+    DEFAULT_SLAM_SPEED
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            jump_impulse: 10_000.0,
+            torque: 200_000.0,
+            slam_speed: DEFAULT_SLAM_SPEED,
+        }
+    }
+}
+
+// Dashes the player sideways with `impulse`, with its gravity off for `duration` seconds so that
+// the dash goes straight. It can't dash again until the `cooldown` is done, and only once in the
+// air until it's landed again.
+#[derive(Component, Clone)]
+pub struct Dash {
+    pub impulse: f32,
+    pub duration: f32,
+    pub cooldown: Timer,
+    pub gravity_suppressed: bool,
+    // Seconds left of the current dash's suppressed gravity
+    pub remaining: f32,
+    // Dashed since it left the ground
+    pub air_dashed: bool,
+    // The way the player last asked to roll, 1 for right and -1 for left, for dashes from a
+    // standstill
+    pub facing: f32,
+    // Dash was held on the last step, so that holding it dashes once
+    held: bool,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        // Ready to go
+        let mut cooldown = Timer::from_seconds(1.0, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        Self {
+            impulse: 8000.0,
+            duration: 0.15,
+            cooldown,
+            gravity_suppressed: false,
+            remaining: 0.0,
+            air_dashed: false,
+            facing: 1.0,
+            held: false,
+        }
+    }
+}
+
+impl Dash {
+    pub fn cooling_down(&self) -> bool {
+        !self.cooldown.finished()
+    }
+}
+
+// How the spin keys move the player, switched with InputAction::ControlMode. With Torque, they spin
+// the ball, and it rolls along by friction with the floor. With Direct, they push it along, and
+// it's turned to roll with it (see DirectDriveConfig).
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlMode {
+    #[default]
+    Torque,
+    Direct,
+}
+
+// In ControlMode::Direct, the spin keys accelerate the player along the ground at `acceleration`,
+// though no more than friction could push it along with, and at `air_scale` of that in the air
+#[derive(Resource, Clone)]
+pub struct DirectDriveConfig {
+    pub acceleration: f32,
+    pub air_scale: f32,
+}
+
+impl Default for DirectDriveConfig {
+    fn default() -> Self {
+        Self {
+            acceleration: 1500.0,
+            air_scale: 0.3,
+        }
+    }
+}
+
+// What the control mode's forces added to the player's accelerations on the last step
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct DriveAcc {
+    pub acc: Vec2,
+    pub angular_acc: f32,
+}
+
+// A player controlled from somewhere else, like the other side of a netplay game. The HUD, help
+// and debug tools are about the local player and leave it out.
+#[derive(Component)]
+pub struct RemotePlayer;
+
+// Filters for the player this game controls, and for players that are a ball rather than a blob
+pub type LocalPlayer = (With<Player>, Without<RemotePlayer>);
+pub type PlayerBall = (With<Player>, Without<Blob>);
+
+// Samples the ballistic path from `start` with velocity `vel` under downward acceleration
+// `gravity`, ending where the ball's center reaches `landing_y` (or after TRAJECTORY_MAX_TIME).
+pub fn predict_trajectory(
+    start: Vec2,
+    vel: Vec2,
+    gravity: f32,
+    landing_y: Option<f32>,
+    samples: usize,
+) -> Vec<Vec2> {
+    let position = |t: f32| start + vel * t + 0.5 * Vec2::NEG_Y * gravity * t.powi(2);
+
+    let flight_time = landing_y
+        .and_then(|landing_y| time_to_land(start.y, vel.y, gravity, landing_y))
+        .map_or(TRAJECTORY_MAX_TIME, |t| t.min(TRAJECTORY_MAX_TIME));
+
+    (0..=samples)
+        .map(|i| position(flight_time * i as f32 / samples as f32))
+        .collect()
+}
+
+// Time until a ballistic path from height `y` with vertical velocity `vel_y` comes down to
+// `landing_y`, or None if it never does
+pub fn time_to_land(y: f32, vel_y: f32, gravity: f32, landing_y: f32) -> Option<f32> {
+    // Later root of y(t) = landing_y
+    let s = y - landing_y;
+    let t = if gravity == 0.0 {
+        -s / vel_y
+    } else {
+        (vel_y + f32::sqrt(vel_y.powi(2) + 2.0 * gravity * s)) / gravity
+    };
+    (t.is_finite() && t >= 0.0).then_some(t)
+}
+
+// The path is that of a jump with the charge so far
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn trajectory_prediction_system(
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    charge_config: Res<JumpChargeConfig>,
+    mut prediction: ResMut<TrajectoryPrediction>,
+    mut lines: ResMut<DebugLines>,
+    floors: Query<(&Transform, &Floor), Without<Player>>,
+    portals: Query<&Transform, (With<Portal>, Without<Player>)>,
+    query: Query<
+        (
+            &Transform,
+            &Player,
+            &PhysObj,
+            &Collider,
+            Option<&Gravity>,
+            Option<&JumpCharge>,
+        ),
+        Without<RemotePlayer>,
+    >,
+) {
+    if settings.input.just_pressed(&input, InputAction::Trajectory) {
+        prediction.enabled = !prediction.enabled;
+    }
+    if !prediction.enabled && !settings.input.pressed(&input, InputAction::Jump) {
+        return;
+    }
+
+    for (transform, player, phys_obj, collider, gravity, charge) in &query {
+        let Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        } = *collider;
+        if !touching_ground {
+            continue;
+        }
+
+        let start = transform.translation.truncate();
+        let charged = charge.copied().unwrap_or_default().time();
+        let jump_vel = phys_obj.vel + charge_config.jump(player, phys_obj, radius, charged).0;
+        let landing_y = floor_below(start, &floors).map(|floor_y| floor_y + radius);
+        let mut points = predict_trajectory(
+            start,
+            jump_vel,
+            gravity.map_or(0.0, |gravity| gravity.0),
+            landing_y,
+            TRAJECTORY_SAMPLES,
+        );
+        // Where it goes after a portal isn't along the same path, so the preview stops there
+        let portal = points.windows(2).position(|segment| {
+            portals
+                .iter()
+                .any(|portal| crosses_portal(segment[0], segment[1], portal))
+        });
+        if let Some(i) = portal {
+            points.truncate(i + 2);
+        }
+
+        lines.dotted_line_strip(&points, Color::YELLOW);
+        if let (None, Some(&landing)) = (portal, points.last()) {
+            lines.circle(landing, radius, Color::YELLOW);
+        }
+    }
+}
+
+pub(crate) fn player_input_system(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let pressed = |action| settings.input.pressed(&keys, action);
+    *input = PlayerInput {
+        jump: pressed(InputAction::Jump),
+        spin_left: pressed(InputAction::SpinLeft),
+        spin_right: pressed(InputAction::SpinRight),
+        zero_gravity: pressed(InputAction::ZeroGravity),
+        climb_up: pressed(InputAction::ClimbUp),
+        climb_down: pressed(InputAction::ClimbDown),
+        dash: pressed(InputAction::Dash),
+    };
+}
+
+#[allow(clippy::type_complexity)]
+fn player_impulse_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    charge_config: Res<JumpChargeConfig>,
+    input: Res<PlayerInput>,
+    mut jumps: EventWriter<JumpEvent>,
+    mut query: Query<
+        (
+            Entity,
+            &Player,
+            &mut PhysObj,
+            &Collider,
+            Option<&Gravity>,
+            Option<&StatusEffects>,
+            Option<&PlayerInput>,
+            Option<&mut JumpCharge>,
+        ),
+        Without<Blob>,
+    >,
+) {
+    for (
+        entity,
+        player,
+        mut phys_obj,
+        &Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        },
+        gravity,
+        effects,
+        own_input,
+        charge,
+    ) in &mut query
+    {
+        let input = own_input.unwrap_or(&input);
+        let can_jump = touching_ground && StatusEffects::can_jump(effects);
+        let state = charge.as_deref().copied().unwrap_or_default().next(
+            &charge_config,
+            input.jump,
+            can_jump,
+            time.delta,
+        );
+        if let JumpCharge::Released(charged) = state {
+            let (dv, angular_dv) = charge_config.jump(player, &phys_obj, radius, charged);
+            phys_obj.vel += dv;
+            phys_obj.angular_vel += angular_dv;
+            jumps.send(JumpEvent {
+                entity,
+                impulse: player.jump_impulse * charge_config.scale(charged),
+            });
+        }
+        match charge {
+            Some(mut charge) => *charge = state,
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+
+        // Follows the key's state rather than presses and releases, so a release that's missed
+        // (e.g. while the window wasn't focused) can't leave gravity off
+        match (input.zero_gravity, gravity.is_some()) {
+            (true, true) => {
+                commands.entity(entity).remove::<Gravity>();
+            }
+            (false, false) => {
+                commands.entity(entity).insert(Gravity(config.gravity));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn control_mode_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut mode: ResMut<ControlMode>,
+    mut toasts: EventWriter<ToastEvent>,
+) {
+    if !settings.input.just_pressed(&keys, InputAction::ControlMode) {
+        return;
+    }
+    *mode = match *mode {
+        ControlMode::Torque => ControlMode::Direct,
+        ControlMode::Direct => ControlMode::Torque,
+    };
+    toasts.send(ToastEvent(format!("{:?} control", *mode)));
+}
+
+// The accelerations a step leaves behind are integrated again at the start of the next one, so on
+// a switch, what the old mode added is taken back out rather than pushing the player on for a step
+fn control_mode_switch_system(
+    mode: Res<ControlMode>,
+    mut query: Query<(&mut PhysObj, &mut DriveAcc)>,
+) {
+    if !mode.is_changed() || mode.is_added() {
+        return;
+    }
+    for (mut phys_obj, mut drive) in &mut query {
+        phys_obj.acc -= drive.acc;
+        phys_obj.angular_acc -= drive.angular_acc;
+        *drive = DriveAcc::default();
+    }
+}
+
+fn record_drive(
+    commands: &mut Commands,
+    entity: Entity,
+    recorded: Option<Mut<DriveAcc>>,
+    drive: DriveAcc,
+) {
+    match recorded {
+        Some(mut recorded) => *recorded = drive,
+        None => {
+            commands.entity(entity).insert(drive);
+        }
+    }
+}
+
+// ControlMode::Torque. Blobs jump and spin by themselves, see blob.rs
+#[allow(clippy::type_complexity)]
+fn player_force_system(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut query: Query<
+        (
+            Entity,
+            &Player,
+            &mut PhysObj,
+            Option<&mut DriveAcc>,
+            Option<&SpinHeat>,
+            Option<&StatusEffects>,
+            Option<&PlayerInput>,
+        ),
+        Without<Blob>,
+    >,
+) {
+    for (entity, player, mut phys_obj, recorded, heat, effects, own_input) in &mut query {
+        let mut drive = DriveAcc::default();
+        if !heat.is_some_and(SpinHeat::overheated) {
+            let input = own_input.unwrap_or(&input);
+            let (spin_left, spin_right) = StatusEffects::spin_input(effects, input);
+            let torque = player.torque * StatusEffects::torque_scale(effects);
+            if spin_left {
+                drive.angular_acc += torque / phys_obj.moment_of_inertia;
+            }
+            if spin_right {
+                drive.angular_acc -= torque / phys_obj.moment_of_inertia;
+            }
+        }
+        phys_obj.angular_acc += drive.angular_acc;
+        record_drive(&mut commands, entity, recorded, drive);
+    }
+}
+
+// ControlMode::Direct. On the ground, the ball is turned along with the push and kept rolling
+// without slipping, so friction has nothing to take up and the spin looks right.
+#[allow(clippy::type_complexity)]
+fn direct_drive_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    drive_config: Res<DirectDriveConfig>,
+    input: Res<PlayerInput>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut PhysObj,
+            &Collider,
+            Option<&Gravity>,
+            Option<&mut DriveAcc>,
+            Option<&SpinHeat>,
+            Option<&StatusEffects>,
+            Option<&PlayerInput>,
+        ),
+        PlayerBall,
+    >,
+    floors: Query<(&Transform, &Floor, Option<&SurfaceMaterial>), Without<PhysObj>>,
+) {
+    for (
+        entity,
+        transform,
+        mut phys_obj,
+        &Collider::Ball {
+            radius,
+            touching_ground,
+            kinetic_friction,
+            ..
+        },
+        gravity,
+        recorded,
+        heat,
+        effects,
+        own_input,
+    ) in &mut query
+    {
+        let input = own_input.unwrap_or(&input);
+        let direction = match StatusEffects::spin_input(effects, input) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        let mut drive = DriveAcc::default();
+        if direction != 0.0 && !heat.is_some_and(SpinHeat::overheated) {
+            let acc = direction * drive_config.acceleration * StatusEffects::torque_scale(effects);
+            if touching_ground {
+                // Friction can push with at most μ·N
+                let friction = config.friction(kinetic_friction)
+                    * StatusEffects::friction_scale(effects)
+                    * surface_friction_scale(transform.translation.truncate(), &floors);
+                let max_acc = friction * gravity.map_or(0.0, |gravity| gravity.0);
+                drive.acc.x = acc.clamp(-max_acc, max_acc);
+                drive.angular_acc = -drive.acc.x / radius;
+                phys_obj.angular_vel = -(phys_obj.vel.x - config.floor_velocity) / radius;
+            } else {
+                drive.acc.x = acc * drive_config.air_scale;
+            }
+        }
+        phys_obj.acc += drive.acc;
+        phys_obj.angular_acc += drive.angular_acc;
+        record_drive(&mut commands, entity, recorded, drive);
+    }
+}
+
+// Heats up the player's spinner while it's spun too fast, and wobbles it while it's overheated
+#[allow(clippy::type_complexity)]
+fn spin_heat_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    config: Res<SpinHeatConfig>,
+    input: Res<PlayerInput>,
+    mut overheats: EventWriter<OverheatEvent>,
+    mut query: Query<
+        (
+            Entity,
+            &mut PhysObj,
+            Option<&mut SpinHeat>,
+            Option<&StatusEffects>,
+            Option<&PlayerInput>,
+        ),
+        PlayerBall,
+    >,
+) {
+    for (entity, mut phys_obj, heat, effects, own_input) in &mut query {
+        // Spinning left is counter-clockwise
+        let driving = match StatusEffects::spin_input(effects, own_input.unwrap_or(&input)) {
+            (true, false) => phys_obj.angular_vel > 0.0,
+            (false, true) => phys_obj.angular_vel < 0.0,
+            _ => false,
+        };
+        let mut state = heat.as_deref().copied().unwrap_or_default();
+        if state.update(&config, phys_obj.angular_vel, driving, time.delta) {
+            overheats.send(OverheatEvent { entity });
+        }
+        if state.overheated() {
+            phys_obj.angular_acc +=
+                config.wobble_torque * state.next_wobble() / phys_obj.moment_of_inertia;
+        }
+
+        match heat {
+            Some(mut heat) => *heat = state,
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+    }
+}
+
+// Dashes on presses of dash, along the way the player is moving or else the way it last rolled.
+// Gravity is suppressed through GravitySuppressed, which takes effect on this step.
+#[allow(clippy::type_complexity)]
+pub(crate) fn dash_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    input: Res<PlayerInput>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Dash,
+            &mut PhysObj,
+            &Collider,
+            Option<&mut GravitySuppressed>,
+            Option<&StatusEffects>,
+            Option<&PlayerInput>,
+        ),
+        PlayerBall,
+    >,
+) {
+    let dt = time.delta;
+    for (
+        entity,
+        mut dash,
+        mut phys_obj,
+        &Collider::Ball {
+            touching_ground, ..
+        },
+        suppressed,
+        effects,
+        own_input,
+    ) in &mut query
+    {
+        let input = own_input.unwrap_or(&input);
+        dash.cooldown.tick(Duration::from_secs_f32(dt));
+        if touching_ground {
+            dash.air_dashed = false;
+        }
+        match StatusEffects::spin_input(effects, input) {
+            (true, false) => dash.facing = -1.0,
+            (false, true) => dash.facing = 1.0,
+            _ => {}
+        }
+        if dash.gravity_suppressed {
+            dash.remaining -= dt;
+            dash.gravity_suppressed = dash.remaining > 0.0;
+        }
+
+        let pressed = input.dash && !dash.held;
+        dash.held = input.dash;
+        if pressed && !dash.cooling_down() && !dash.air_dashed {
+            let direction = if phys_obj.vel.x.abs() > DASH_MIN_SPEED {
+                phys_obj.vel.x.signum()
+            } else {
+                dash.facing
+            };
+            phys_obj.vel.x += direction * dash.impulse / phys_obj.mass;
+            dash.cooldown.reset();
+            dash.air_dashed = !touching_ground;
+            dash.gravity_suppressed = true;
+            dash.remaining = dash.duration;
+        }
+
+        match suppressed {
+            Some(mut suppressed) => suppressed.0 = dash.gravity_suppressed,
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(GravitySuppressed(dash.gravity_suppressed));
+            }
+        }
+    }
+}
+
+// Climb down in the air (off ropes, where it climbs) slams the player straight down at its
+// `slam_speed`. Holding it down slams again as soon as the player is back in the air.
+#[allow(clippy::type_complexity)]
+fn slam_system(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut started: ResMut<SlamsStarted>,
+    mut jumps: EventReader<JumpEvent>,
+    mut query: Query<
+        (
+            Entity,
+            &Player,
+            &mut PhysObj,
+            &Collider,
+            Option<&Slamming>,
+            Option<&PlayerInput>,
+        ),
+        (Without<Blob>, Without<Grabbing>, Without<Grappled>),
+    >,
+) {
+    let jumped: Vec<Entity> = jumps.iter().map(|jump| jump.entity).collect();
+    started.0.clear();
+    for (
+        entity,
+        player,
+        mut phys_obj,
+        Collider::Ball {
+            touching_ground, ..
+        },
+        slamming,
+        own_input,
+    ) in &mut query
+    {
+        if jumped.contains(&entity) {
+            if slamming.is_some() {
+                commands.entity(entity).remove::<Slamming>();
+            }
+            continue;
+        }
+        let input = own_input.unwrap_or(&input);
+        if input.climb_down && !touching_ground && slamming.is_none() {
+            phys_obj.vel = Vec2::NEG_Y * player.slam_speed;
+            commands.entity(entity).insert(Slamming).remove::<Gliding>();
+            started.0.push(entity);
+        }
+    }
+}
+
+// A slam lands dead, whatever the ball's restitution, and shoves the balls around it away. Runs
+// on the step's collisions, like sticky surfaces, including landings on elevators. Trampolines
+// throw the ball back up rather than land it, which ends the slam without stopping it.
+#[allow(clippy::too_many_arguments)]
+fn slam_landing_system(
+    mut commands: Commands,
+    slam: Res<SlamConfig>,
+    mut landings: EventReader<LandedEvent>,
+    mut bounces: EventReader<BounceEvent>,
+    mut slams: EventWriter<SlamEvent>,
+    started: Res<SlamsStarted>,
+    slamming: Query<(), With<Slamming>>,
+    mut balls: Query<(Entity, &mut Transform, &mut PhysObj, &mut Collider)>,
+) {
+    let is_slamming = |entity| slamming.contains(entity) || started.0.contains(&entity);
+    let mut centers: Vec<(Entity, Vec2)> = Vec::new();
+    for landing in landings.iter() {
+        // Once per slam, even if it landed on an elevator and the floor in the same step
+        if !is_slamming(landing.entity)
+            || centers.iter().any(|&(entity, _)| entity == landing.entity)
+        {
+            continue;
+        }
+        let Ok((entity, mut transform, mut phys_obj, mut collider)) = balls.get_mut(landing.entity)
+        else {
+            continue;
+        };
+        let Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        } = &mut *collider;
+        phys_obj.vel.y = phys_obj.vel.y.min(0.0);
+        transform.translation.y = transform.translation.y.min(landing.surface_y + *radius);
+        *touching_ground = true;
+        commands.entity(entity).remove::<Slamming>();
+        slams.send(SlamEvent {
+            entity,
+            impact_speed: landing.impact_speed,
+        });
+        centers.push((entity, transform.translation.truncate()));
+    }
+    // Every bounce that isn't off a trampoline lands the ball, so these were handled above
+    for bounce in bounces.iter() {
+        if !is_slamming(bounce.entity) || centers.iter().any(|&(entity, _)| entity == bounce.entity)
+        {
+            continue;
+        }
+        let Ok((entity, transform, ..)) = balls.get(bounce.entity) else {
+            continue;
+        };
+        commands.entity(entity).remove::<Slamming>();
+        slams.send(SlamEvent {
+            entity,
+            impact_speed: bounce.impact_speed,
+        });
+        centers.push((entity, transform.translation.truncate()));
+    }
+
+    for (center_entity, center) in centers {
+        for (entity, transform, mut phys_obj, _) in &mut balls {
+            let offset = transform.translation.truncate() - center;
+            let distance = offset.length();
+            if entity == center_entity || distance >= slam.radius || distance == 0.0 {
+                continue;
+            }
+            // Writing to a sleeping ball wakes it
+            let impulse = slam.impulse * (1.0 - distance / slam.radius);
+            let mass = phys_obj.mass;
+            phys_obj.vel += offset / distance * impulse / mass;
+        }
+    }
+}
+
+// Gliding starts when the player holds jump while falling, and lasts until jump is released or the
+// ball touches the ground (or grabs a rope). It doesn't use up the jump: still holding it on
+// landing jumps again. There's no gliding while Slamming, and slamming stops a glide.
+#[allow(clippy::type_complexity)]
+fn glide_system(
+    mut commands: Commands,
+    glide: Res<GlideConfig>,
+    input: Res<PlayerInput>,
+    mut glides: EventWriter<GlideEvent>,
+    mut query: Query<
+        (
+            Entity,
+            &mut PhysObj,
+            &Collider,
+            Option<&Gliding>,
+            Option<&PlayerInput>,
+        ),
+        (
+            With<Player>,
+            Without<Blob>,
+            Without<Grabbing>,
+            Without<Slamming>,
+        ),
+    >,
+) {
+    for (
+        entity,
+        mut phys_obj,
+        Collider::Ball {
+            touching_ground, ..
+        },
+        gliding,
+        own_input,
+    ) in &mut query
+    {
+        let jump = own_input.unwrap_or(&input).jump;
+        let glide_now = jump && !touching_ground && (gliding.is_some() || phys_obj.vel.y < 0.0);
+        match (glide_now, gliding.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(Gliding);
+                glides.send(GlideEvent { entity });
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Gliding>();
+            }
+            _ => {}
+        }
+        if glide_now && phys_obj.vel.y < -glide.fall_speed {
+            phys_obj.vel.y = -glide.fall_speed;
+        }
+    }
+}
+
+// Holds a gliding player's fall at the capped speed, and slows and steers it sideways
+#[allow(clippy::type_complexity)]
+fn glide_force_system(
+    glide: Res<GlideConfig>,
+    mut query: Query<(&mut PhysObj, Option<&Gravity>), (With<Gliding>, Without<Blob>)>,
+) {
+    for (mut phys_obj, gravity) in &mut query {
+        if let Some(gravity) = gravity {
+            if phys_obj.vel.y <= -glide.fall_speed {
+                phys_obj.acc.y += gravity.0;
+            }
+        }
+        let fall_speed = (-phys_obj.vel.y).max(0.0);
+        phys_obj.acc.x +=
+            glide.magnus * phys_obj.angular_vel * fall_speed - glide.drag * phys_obj.vel.x;
+    }
+}
+
+// While the player spins in the air, turns the ball towards rolling without slipping at its
+// horizontal speed just before it lands, so that it lands rolling rather than skidding. Only with
+// Settings::landing_assist.
+#[allow(clippy::type_complexity)]
+fn landing_assist_system(
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    settings: Res<Settings>,
+    input: Res<PlayerInput>,
+    mut query: Query<
+        (
+            &Transform,
+            &mut PhysObj,
+            &Collider,
+            Option<&Gravity>,
+            Option<&PlayerInput>,
+        ),
+        PlayerBall,
+    >,
+) {
+    if !settings.landing_assist {
+        return;
+    }
+    for (
+        transform,
+        mut phys_obj,
+        &Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        },
+        gravity,
+        own_input,
+    ) in &mut query
+    {
+        let input = own_input.unwrap_or(&input);
+        if touching_ground || !(input.spin_left || input.spin_right) {
+            continue;
+        }
+
+        let landing_in = time_to_land(
+            transform.translation.y,
+            phys_obj.vel.y,
+            gravity.map_or(0.0, |gravity| gravity.0),
+            config.floor_y + radius,
+        );
+        if !landing_in.is_some_and(|t| t <= LANDING_ASSIST_TIME) {
+            continue;
+        }
+        let rolling = -phys_obj.vel.x / radius;
+        let max_change = LANDING_ASSIST_MAX_TORQUE / phys_obj.moment_of_inertia * time.delta;
+        phys_obj.angular_vel += (rolling - phys_obj.angular_vel).clamp(-max_change, max_change);
+    }
+}
@@ -0,0 +1,169 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::physics::{PhysObj, PhysicsSchedule, PhysicsSet, PhysicsTime};
+
+// Length of a portal's opening
+pub const PORTAL_WIDTH: f32 = 120.0;
+// Seconds after going through a portal before a body can go through one again, so that it doesn't
+// bounce straight back through the one it came out of
+pub const PORTAL_IMMUNITY: f32 = 0.1;
+
+// Pairs of portals: a body whose center crosses a portal from the front comes out of the other,
+// moving the same speed out of it as it was going in. Works for any PhysObj, so things thrown
+// through go through too. Only the simulated parts; VisualsPlugin makes the portals visible.
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PortalEvent>().add_systems(
+            (portal_immunity_system, portal_system)
+                .chain()
+                .in_set(PhysicsSet::PostSolve)
+                .in_schedule(PhysicsSchedule),
+        );
+    }
+}
+
+// One end of a pair of portals, an opening PORTAL_WIDTH long across the entity's position. Its
+// front faces the way the Transform's x axis points, and things only go in from the front.
+//
+// `exit_rotation` is how far round things are turned going through to `exit` (see
+// Portal::rotation_between). A rotation keeps the way they spin, so angular velocities are left
+// alone.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Portal {
+    // Shared by both ends of a pair
+    pub id: u32,
+    pub exit: Entity,
+    pub exit_rotation: f32,
+}
+
+impl Portal {
+    // Rotation taking things going into a portal facing `entry_angle` to coming out of one facing
+    // `exit_angle`, angles counterclockwise from the x axis
+    pub fn rotation_between(entry_angle: f32, exit_angle: f32) -> f32 {
+        exit_angle - entry_angle + PI
+    }
+
+    // A vector through the portal, like a velocity
+    pub fn rotate(&self, v: Vec2) -> Vec2 {
+        Vec2::from_angle(self.exit_rotation).rotate(v)
+    }
+
+    // Where a point at `position` relative to `entry` comes out relative to `exit`. Something just
+    // behind the entry comes out just as far in front of the exit.
+    pub fn carry(&self, entry: &Transform, exit: &Transform, position: Vec2) -> Vec2 {
+        exit.translation.truncate() + self.rotate(position - entry.translation.truncate())
+    }
+}
+
+// The way the portal at `transform` faces
+pub fn portal_normal(transform: &Transform) -> Vec2 {
+    (transform.rotation * Vec3::X).truncate()
+}
+
+// Whether going from `from` to `to` goes into the portal at `transform`: from in front of it to
+// behind it, through the opening
+pub fn crosses_portal(from: Vec2, to: Vec2, transform: &Transform) -> bool {
+    let center = transform.translation.truncate();
+    let normal = portal_normal(transform);
+    let (before, after) = ((from - center).dot(normal), (to - center).dot(normal));
+    if !(before > 0.0 && after <= 0.0) {
+        return false;
+    }
+    // Where it crossed, along the opening
+    let crossing = from.lerp(to, before / (before - after));
+    (crossing - center).dot(normal.perp()).abs() <= 0.5 * PORTAL_WIDTH
+}
+
+// Spawns a pair of portals with id `id`, at positions facing the given angles (counterclockwise
+// from the x axis)
+pub fn spawn_portal_pair(
+    commands: &mut Commands,
+    id: u32,
+    (a, a_angle): (Vec2, f32),
+    (b, b_angle): (Vec2, f32),
+) -> [Entity; 2] {
+    let ends = [commands.spawn_empty().id(), commands.spawn_empty().id()];
+    for (end, (position, angle), exit, exit_angle) in [
+        (ends[0], (a, a_angle), ends[1], b_angle),
+        (ends[1], (b, b_angle), ends[0], a_angle),
+    ] {
+        commands.entity(end).insert((
+            SpatialBundle::from_transform(
+                Transform::from_translation(position.extend(-0.5))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ),
+            Portal {
+                id,
+                exit,
+                exit_rotation: Portal::rotation_between(angle, exit_angle),
+            },
+        ));
+    }
+    ends
+}
+
+// A body that's just gone through a portal, and can't go through another for `remaining` seconds
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PortalImmunity {
+    pub remaining: f32,
+}
+
+// A body went through the portal `entry` and came out of `exit`
+pub struct PortalEvent {
+    pub entity: Entity,
+    pub entry: Entity,
+    pub exit: Entity,
+}
+
+fn portal_immunity_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut query: Query<(Entity, &mut PortalImmunity)>,
+) {
+    for (entity, mut immunity) in &mut query {
+        immunity.remaining -= time.delta;
+        if immunity.remaining <= 0.0 {
+            commands.entity(entity).remove::<PortalImmunity>();
+        }
+    }
+}
+
+fn portal_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut events: EventWriter<PortalEvent>,
+    portals: Query<(Entity, &Transform, &Portal), Without<PhysObj>>,
+    mut bodies: Query<(Entity, &mut Transform, &mut PhysObj), Without<PortalImmunity>>,
+) {
+    for (entity, mut transform, mut phys_obj) in &mut bodies {
+        let position = transform.translation.truncate();
+        // Where it was at the start of the step, near enough
+        let last = position - phys_obj.vel * time.delta;
+        let Some((entry, entry_transform, portal)) = portals
+            .iter()
+            .find(|(_, portal_transform, _)| crosses_portal(last, position, portal_transform))
+        else {
+            continue;
+        };
+        let Ok((_, exit_transform, _)) = portals.get(portal.exit) else {
+            continue;
+        };
+
+        let carried = portal.carry(entry_transform, exit_transform, position);
+        transform.translation = carried.extend(transform.translation.z);
+        transform.rotate_z(portal.exit_rotation);
+        phys_obj.vel = portal.rotate(phys_obj.vel);
+        commands.entity(entity).insert(PortalImmunity {
+            remaining: PORTAL_IMMUNITY,
+        });
+        events.send(PortalEvent {
+            entity,
+            entry,
+            exit: portal.exit,
+        });
+    }
+}
@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blob::Blob,
+    physics::{
+        sleep::{KeepAwake, Sleeping},
+        Collider, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsTime,
+    },
+    player::{Player, PlayerBall},
+};
+
+pub const PICKUP_RADIUS: f32 = 15.0;
+// Time a size change takes to grow or shrink the whole way
+pub const SIZE_CHANGE_TIME: f32 = 0.3;
+
+// Pickups that change the player when touched. Only the simulated parts; VisualsPlugin makes
+// pickups visible.
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickupEvent>()
+            .add_systems(
+                (size_change_start_system, size_change_system)
+                    .chain()
+                    .in_set(PhysicsSet::ApplyImpulses)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(
+                pickup_system
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+// Makes a ball `target_radius` big for `duration` seconds, then back to its old size. Its mass
+// grows with its area and its moment of inertia along with it. With `scale_jump_impulse`, the
+// player jumps as high as before, so a big ball isn't just a slow one.
+//
+// Growing takes SIZE_CHANGE_TIME, and waits while the bigger ball wouldn't fit (see
+// size_change_system). Giving a ball another SizeChange while one is running restarts the timer
+// with the new one.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SizeChange {
+    pub target_radius: f32,
+    pub duration: f32,
+    #[serde(default)]
+    pub scale_jump_impulse: bool,
+}
+
+// A sensor that gives the player its SizeChange when they touch it, and disappears
+#[derive(Component, Clone, Copy)]
+pub struct SizePickup(pub SizeChange);
+
+// The player took a pickup, which has been despawned
+pub struct PickupEvent {
+    pub player: Entity,
+    pub transform: Transform,
+    pub pickup: SizePickup,
+}
+
+// What a ball was before its SizeChange, to scale from and go back to
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SizeChangeState {
+    pub radius: f32,
+    pub mass: f32,
+    pub moment_of_inertia: f32,
+    pub jump_impulse: Option<f32>,
+    // Time left at the target size
+    pub remaining: f32,
+}
+
+fn pickup_system(
+    mut commands: Commands,
+    mut events: EventWriter<PickupEvent>,
+    pickups: Query<(Entity, &Transform, &SizePickup)>,
+    players: Query<(Entity, &Transform, &Collider), PlayerBall>,
+) {
+    for (player, player_transform, &Collider::Ball { radius, .. }) in &players {
+        for (pickup, transform, &SizePickup(change)) in &pickups {
+            let distance = player_transform
+                .translation
+                .truncate()
+                .distance(transform.translation.truncate());
+            if distance < radius + PICKUP_RADIUS {
+                commands.entity(player).insert(change);
+                commands.entity(pickup).despawn_recursive();
+                events.send(PickupEvent {
+                    player,
+                    transform: *transform,
+                    pickup: SizePickup(change),
+                });
+            }
+        }
+    }
+}
+
+// Remembers what a ball was like when its SizeChange started, or restarts the timer if it's
+// already changed
+#[allow(clippy::type_complexity)]
+fn size_change_start_system(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &SizeChange,
+            &PhysObj,
+            &Collider,
+            Option<&Player>,
+            Option<&mut SizeChangeState>,
+        ),
+        (Changed<SizeChange>, Without<Blob>),
+    >,
+) {
+    for (entity, change, phys_obj, collider, player, state) in &mut query {
+        if let Some(mut state) = state {
+            state.remaining = change.duration;
+            continue;
+        }
+        let Collider::Ball { radius, .. } = *collider;
+        // The ball is moved about as it changes, which the sleep system doesn't know about
+        commands.entity(entity).remove::<Sleeping>().insert((
+            SizeChangeState {
+                radius,
+                mass: phys_obj.mass,
+                moment_of_inertia: phys_obj.moment_of_inertia,
+                jump_impulse: player.map(|player| player.jump_impulse),
+                remaining: change.duration,
+            },
+            KeepAwake,
+        ));
+    }
+}
+
+// Moves the radius of changing balls towards where it should be, growing only when the bigger ball
+// fits. A ball on the floor grows and shrinks about its bottom, so it stays on the floor; in the
+// air it does so about its center, and doesn't grow into the floor or other balls.
+#[allow(clippy::type_complexity)]
+fn size_change_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut changing: Query<
+        (
+            Entity,
+            &SizeChange,
+            &mut SizeChangeState,
+            &mut PhysObj,
+            Option<&mut Player>,
+        ),
+        Without<Blob>,
+    >,
+    mut bodies: Query<(Entity, &mut Transform, &mut Collider)>,
+) {
+    let dt = time.delta;
+    for (entity, change, mut state, mut phys_obj, player) in &mut changing {
+        state.remaining -= dt;
+        let target = if state.remaining > 0.0 {
+            change.target_radius
+        } else {
+            state.radius
+        };
+
+        let Ok((
+            _,
+            transform,
+            &Collider::Ball {
+                radius,
+                touching_ground,
+                ..
+            },
+        )) = bodies.get(entity)
+        else {
+            continue;
+        };
+        let max_change = (change.target_radius - state.radius).abs() / SIZE_CHANGE_TIME * dt;
+        let new_radius = if (target - radius).abs() <= max_change {
+            target
+        } else {
+            radius + max_change.copysign(target - radius)
+        };
+        let mut center = transform.translation.truncate();
+        if touching_ground {
+            center.y += new_radius - radius;
+        }
+        if new_radius > radius && !fits(&config, entity, center, new_radius, radius, &bodies) {
+            continue;
+        }
+
+        if let Ok((_, mut transform, mut collider)) = bodies.get_mut(entity) {
+            let Collider::Ball { radius, .. } = &mut *collider;
+            *radius = new_radius;
+            transform.translation = center.extend(transform.translation.z);
+            let scale = new_radius / state.radius;
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+        // Exactly what it was once it's back
+        let mass_scale = (new_radius / state.radius).powi(2);
+        if new_radius == state.radius {
+            phys_obj.mass = state.mass;
+            phys_obj.moment_of_inertia = state.moment_of_inertia;
+        } else {
+            phys_obj.mass = state.mass * mass_scale;
+            // Same shape, so the same fraction of mass times radius squared
+            phys_obj.moment_of_inertia = state.moment_of_inertia * mass_scale * mass_scale;
+        }
+        if let (Some(mut player), Some(jump_impulse)) = (player, state.jump_impulse) {
+            player.jump_impulse = if change.scale_jump_impulse {
+                jump_impulse * phys_obj.mass / state.mass
+            } else {
+                jump_impulse
+            };
+        }
+
+        if state.remaining <= 0.0 && new_radius == state.radius {
+            commands
+                .entity(entity)
+                .remove::<(SizeChange, SizeChangeState, KeepAwake)>();
+        }
+    }
+}
+
+// Whether a ball `entity` at `center` with `radius` stays out of the floor and the other balls.
+// Overlaps it already had at `old_radius` (e.g. resting on another ball) don't count, as long as
+// they don't get deeper.
+fn fits(
+    config: &PhysicsConfig,
+    entity: Entity,
+    center: Vec2,
+    radius: f32,
+    old_radius: f32,
+    bodies: &Query<(Entity, &mut Transform, &mut Collider)>,
+) -> bool {
+    if center.y - radius < config.floor_y - config.penetration_slop {
+        return false;
+    }
+    let Ok((_, transform, _)) = bodies.get(entity) else {
+        return false;
+    };
+    let old_center = transform.translation.truncate();
+    bodies.iter().all(|(other, transform, collider)| {
+        let Collider::Ball {
+            radius: other_radius,
+            ..
+        } = *collider;
+        let position = transform.translation.truncate();
+        let overlap = radius + other_radius - center.distance(position);
+        let old_overlap = old_radius + other_radius - old_center.distance(position);
+        other == entity || overlap <= config.penetration_slop.max(old_overlap)
+    })
+}
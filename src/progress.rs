@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::{CurrentLevel, RestartLevelEvent},
+    state::AppState,
+    storage::{load_ron, save_ron, Storage, StorageBackend, StorageError},
+    toast::ToastEvent,
+};
+
+const PROGRESS_NAME: &str = "progress";
+
+// Keeps the player's Progress between sessions. Running with `--reset-progress`, or pressing
+// Ctrl+Shift+Delete, starts over.
+pub struct ProgressPlugin;
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Storage>();
+        let mut storage = app.world.resource_mut::<Storage>();
+        let progress = if std::env::args().any(|arg| arg == "--reset-progress") {
+            info!("Resetting the progress");
+            let progress = Progress::default();
+            if let Err(error) = progress.save(storage.0.as_mut()) {
+                error!("Failed to save the progress: {error}");
+            }
+            progress
+        } else {
+            Progress::load(storage.0.as_ref())
+        };
+        app.insert_resource(progress)
+            .init_resource::<LevelTimer>()
+            .init_resource::<Score>()
+            .init_resource::<LevelStats>()
+            .add_event::<RestartLevelEvent>()
+            .add_event::<ToastEvent>()
+            .add_system(level_timer_system.in_set(OnUpdate(AppState::Playing)))
+            .add_system(level_timer_reset_system.in_schedule(OnExit(AppState::MainMenu)))
+            .add_system(
+                // So the restarted level's first frame is timed from zero
+                level_timer_reset_system
+                    .after(level_timer_system)
+                    .run_if(on_event::<RestartLevelEvent>()),
+            )
+            .add_system(level_complete_system.in_schedule(OnEnter(AppState::LevelComplete)))
+            .add_system(progress_reset_system);
+    }
+}
+
+#[derive(Resource, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    // Indexed by level number
+    pub levels: Vec<LevelProgress>,
+    pub deaths: u32,
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelProgress {
+    // None until the level is completed
+    pub best_time: Option<f32>,
+    // Bit i is set once collectible i has been collected
+    pub collectibles: u64,
+}
+
+impl Progress {
+    // Unreadable progress is treated as none
+    pub fn load(storage: &dyn StorageBackend) -> Self {
+        match load_ron(storage, PROGRESS_NAME) {
+            Some(Ok(progress)) => progress,
+            Some(Err(error)) => {
+                warn!("Discarding the progress, it couldn't be parsed: {error}");
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn StorageBackend) -> Result<(), StorageError> {
+        save_ron(storage, PROGRESS_NAME, self)
+    }
+
+    pub fn level(&self, level: usize) -> Option<&LevelProgress> {
+        self.levels.get(level)
+    }
+
+    pub fn is_completed(&self, level: usize) -> bool {
+        self.level(level)
+            .is_some_and(|progress| progress.best_time.is_some())
+    }
+
+    // Records a completion of `level` in `time` seconds with the `collectibles` collected on the
+    // way. Collectibles add to those of earlier runs. Returns whether the time is a new best.
+    pub fn record_completion(&mut self, level: usize, time: f32, collectibles: u64) -> bool {
+        if self.levels.len() <= level {
+            self.levels.resize(level + 1, default());
+        }
+        let progress = &mut self.levels[level];
+        progress.collectibles |= collectibles;
+        let new_best = progress.best_time.is_none_or(|best| time < best);
+        if new_best {
+            progress.best_time = Some(time);
+        }
+        new_best
+    }
+}
+
+// Time spent playing the current level
+#[derive(Resource, Default)]
+pub struct LevelTimer {
+    pub elapsed: f32,
+}
+
+// Points scored in the current level
+#[derive(Resource, Default)]
+pub struct Score(pub u32);
+
+// How the last completed level went, for the level complete screen
+#[derive(Resource, Default)]
+pub struct LevelStats {
+    pub time: f32,
+    // The best time before this run, None if the level hadn't been completed
+    pub previous_best: Option<f32>,
+    pub new_best: bool,
+    pub score: u32,
+    pub deaths: u32,
+}
+
+pub(crate) fn level_timer_system(time: Res<Time>, mut timer: ResMut<LevelTimer>) {
+    timer.elapsed += time.delta_seconds();
+}
+
+// Starting the level over also starts the score over
+fn level_timer_reset_system(mut timer: ResMut<LevelTimer>, mut score: ResMut<Score>) {
+    timer.elapsed = 0.0;
+    score.0 = 0;
+}
+
+pub(crate) fn level_complete_system(
+    level: Res<CurrentLevel>,
+    timer: Res<LevelTimer>,
+    score: Res<Score>,
+    mut progress: ResMut<Progress>,
+    mut stats: ResMut<LevelStats>,
+    mut storage: ResMut<Storage>,
+    mut toasts: EventWriter<ToastEvent>,
+) {
+    let previous_best = progress.level(level.0).and_then(|level| level.best_time);
+    let new_best = progress.record_completion(level.0, timer.elapsed, 0);
+    *stats = LevelStats {
+        time: timer.elapsed,
+        previous_best,
+        new_best,
+        score: score.0,
+        deaths: progress.deaths,
+    };
+    if new_best {
+        info!("New best time for level {}: {:.2}s", level.0, timer.elapsed);
+        toasts.send(ToastEvent("New best time!".to_string()));
+    }
+    if let Err(error) = progress.save(storage.0.as_mut()) {
+        error!("Failed to save the progress: {error}");
+    }
+}
+
+fn progress_reset_system(
+    input: Res<Input<KeyCode>>,
+    mut progress: ResMut<Progress>,
+    mut storage: ResMut<Storage>,
+) {
+    let ctrl = input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if ctrl && shift && input.just_pressed(KeyCode::Delete) {
+        info!("Resetting the progress");
+        *progress = Progress::default();
+        if let Err(error) = progress.save(storage.0.as_mut()) {
+            error!("Failed to save the progress: {error}");
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    physics::{
+        physics_time_system, simulation_running, PhysObj, PhysicsConfig, PhysicsStep, PhysicsTime,
+        SpawnOrder,
+    },
+    player::{player_input_system, Player, PlayerInput, PLAYER_RADIUS},
+    progress::LevelTimer,
+    save::SaveGame,
+    shapes::FidgetSpinner,
+    state::AppState,
+    storage::{load_ron, save_ron, Storage, StorageBackend, StorageError},
+    testing::test_app,
+};
+
+const REPLAY_NAME: &str = "replay";
+
+// F6 starts and stops recording, F7 plays the last recording back as a ghost next to the player
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Storage>()
+            .init_resource::<ReplayRecorder>()
+            .add_system(replay_control_system.before(PhysicsStep))
+            .add_system(
+                replay_record_system
+                    .after(PhysicsStep)
+                    .run_if(simulation_running),
+            );
+    }
+}
+
+// A recorded run: where everything started and what the player did on every simulated frame.
+// Playing it back with the same build reproduces the run exactly.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub config: PhysicsConfig,
+    pub initial: SaveGame,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub input: PlayerInput,
+    // Length of each of the frame's physics steps
+    pub delta: f32,
+    // LevelTimer once the frame was played, to keep a ghost in time with the level
+    #[serde(default)]
+    pub elapsed: f32,
+}
+
+impl Replay {
+    pub fn save(&self, storage: &mut dyn StorageBackend) -> Result<(), StorageError> {
+        save_ron(storage, REPLAY_NAME, self)
+    }
+
+    pub fn load(storage: &dyn StorageBackend) -> Option<Self> {
+        load_ron(storage, REPLAY_NAME)?
+            .map_err(|error| error!("Failed to parse the replay: {error}"))
+            .ok()
+    }
+
+    // Identifies the run, e.g. for a leaderboard to tell submitted runs apart. The same for the
+    // same replay on any build or platform.
+    pub fn hash(&self) -> u64 {
+        fnv1a(ron::to_string(self).unwrap_or_default().bytes())
+    }
+
+    // A headless App that plays the replay, one recorded frame per update. The simulation starts
+    // on the second update, like in any `test_app`.
+    pub fn playback_app(&self) -> App {
+        let mut app = test_app();
+        app.insert_resource(PhysicsConfig {
+            serial: true,
+            ..self.config.clone()
+        })
+        .insert_resource(ReplayPlayback {
+            frames: self.frames.clone(),
+            next: 0,
+        })
+        .add_system(
+            replay_feed_system
+                .after(player_input_system)
+                .after(physics_time_system)
+                .before(PhysicsStep)
+                .run_if(in_state(AppState::Playing)),
+        );
+        self.initial.restore(&mut app.world);
+        app
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub recording: Option<Replay>,
+    pub last: Option<Replay>,
+}
+
+// The frames left to play in a playback App
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub frames: Vec<ReplayFrame>,
+    pub next: usize,
+}
+
+impl ReplayPlayback {
+    pub fn finished(&self) -> bool {
+        self.next >= self.frames.len()
+    }
+}
+
+// 64-bit FNV-1a, which is stable across builds and platforms, unlike std's DefaultHasher
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// A hash of where the bodies are and how they're moving, the same for the same bodies in any
+// order (queries don't iterate in the same order everywhere). Exact, so any drift between two
+// simulations changes it.
+pub fn state_hash<'a>(bodies: impl IntoIterator<Item = (&'a Transform, &'a PhysObj)>) -> u64 {
+    bodies.into_iter().fold(0, |hash, (transform, phys_obj)| {
+        hash.wrapping_add(fnv1a(body_bytes(transform, phys_obj)))
+    })
+}
+
+// Like state_hash, but hashed in spawn order with each body's SpawnOrder, so two bodies swapping
+// states changes it too. The same on any machine that spawned the same bodies in the same order,
+// whatever their entity ids; it's what headless runs print.
+pub fn ordered_state_hash<'a>(
+    bodies: impl IntoIterator<Item = (&'a SpawnOrder, &'a Transform, &'a PhysObj)>,
+) -> u64 {
+    let mut bodies: Vec<_> = bodies.into_iter().collect();
+    bodies.sort_by_key(|(order, ..)| **order);
+    fnv1a(bodies.into_iter().flat_map(|(order, transform, phys_obj)| {
+        order
+            .0
+            .to_le_bytes()
+            .into_iter()
+            .chain(body_bytes(transform, phys_obj))
+    }))
+}
+
+fn body_bytes(transform: &Transform, phys_obj: &PhysObj) -> impl Iterator<Item = u8> {
+    let values = [
+        transform.translation.x,
+        transform.translation.y,
+        transform.rotation.z,
+        transform.rotation.w,
+        phys_obj.vel.x,
+        phys_obj.vel.y,
+        phys_obj.angular_vel,
+    ];
+    values
+        .into_iter()
+        .flat_map(|value| value.to_bits().to_le_bytes())
+}
+
+// Translucent copy of the player following a replay
+#[derive(Component)]
+pub struct Ghost;
+
+// The playback App behind a ghost. Not Send, so kept as a non-send resource.
+pub(crate) struct GhostPlayback {
+    app: App,
+    ghost: Entity,
+}
+
+impl GhostPlayback {
+    // Spawns a ghost shaped like the replay's player, hidden until it's played its first frame
+    pub(crate) fn spawn(world: &mut World, replay: &Replay) -> Self {
+        let shape = replay
+            .initial
+            .bodies
+            .iter()
+            .find(|body| body.player.is_some())
+            .and_then(|body| body.shape)
+            .unwrap_or_else(|| FidgetSpinner::new(PLAYER_RADIUS));
+        let ghost = world
+            .spawn((
+                SpatialBundle {
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                shape,
+                Ghost,
+            ))
+            .id();
+        Self {
+            app: replay.playback_app(),
+            ghost,
+        }
+    }
+
+    // The frame the next update plays, None once the replay's finished
+    pub(crate) fn next_frame(&self) -> Option<&ReplayFrame> {
+        let playback = self.app.world.resource::<ReplayPlayback>();
+        playback.frames.get(playback.next)
+    }
+
+    // Updates the playback App once, and puts the ghost where its player got to
+    pub(crate) fn update(&mut self, world: &mut World) {
+        self.app.update();
+        let transform = self
+            .app
+            .world
+            .query_filtered::<&Transform, With<Player>>()
+            .get_single(&self.app.world)
+            .ok()
+            .copied();
+        if let (Some(transform), Some(mut ghost)) = (transform, world.get_entity_mut(self.ghost)) {
+            ghost.insert((transform, Visibility::Inherited));
+        }
+    }
+
+    pub(crate) fn despawn(self, world: &mut World) {
+        world.despawn(self.ghost);
+    }
+}
+
+fn replay_control_system(world: &mut World) {
+    let input = world.resource::<Input<KeyCode>>();
+    let (record, play) = (
+        input.just_pressed(KeyCode::F6),
+        input.just_pressed(KeyCode::F7),
+    );
+
+    if record {
+        toggle_recording(world);
+    }
+    if play {
+        start_playback(world);
+    }
+    advance_playback(world);
+}
+
+fn toggle_recording(world: &mut World) {
+    match world.resource_mut::<ReplayRecorder>().recording.take() {
+        Some(replay) => {
+            info!("Recorded a replay of {} frames", replay.frames.len());
+            if let Err(error) = replay.save(world.resource_mut::<Storage>().0.as_mut()) {
+                error!("Failed to save the replay: {error}");
+            }
+            world.resource_mut::<ReplayRecorder>().last = Some(replay);
+        }
+        None => {
+            let replay = Replay {
+                config: world.resource::<PhysicsConfig>().clone(),
+                initial: SaveGame::capture(world),
+                frames: Vec::new(),
+            };
+            world.resource_mut::<ReplayRecorder>().recording = Some(replay);
+            info!("Recording a replay");
+        }
+    }
+}
+
+fn start_playback(world: &mut World) {
+    let Some(replay) = world
+        .resource::<ReplayRecorder>()
+        .last
+        .clone()
+        .or_else(|| Replay::load(world.resource::<Storage>().0.as_ref()))
+    else {
+        warn!("There is no replay to play");
+        return;
+    };
+
+    if let Some(playback) = world.remove_non_send_resource::<GhostPlayback>() {
+        playback.despawn(world);
+    }
+    let playback = GhostPlayback::spawn(world, &replay);
+    world.insert_non_send_resource(playback);
+}
+
+fn advance_playback(world: &mut World) {
+    if world.resource::<State<AppState>>().0 != AppState::Playing {
+        return;
+    }
+    let Some(mut playback) = world.remove_non_send_resource::<GhostPlayback>() else {
+        return;
+    };
+
+    playback.update(world);
+    if playback.next_frame().is_none() {
+        info!("Replay finished");
+        playback.despawn(world);
+    } else {
+        world.insert_non_send_resource(playback);
+    }
+}
+
+fn replay_feed_system(
+    mut playback: ResMut<ReplayPlayback>,
+    mut input: ResMut<PlayerInput>,
+    mut time: ResMut<PhysicsTime>,
+) {
+    let Some(&frame) = playback.frames.get(playback.next) else {
+        return;
+    };
+    *input = frame.input;
+    time.delta = frame.delta;
+    playback.next += 1;
+}
+
+fn replay_record_system(
+    input: Res<PlayerInput>,
+    time: Res<PhysicsTime>,
+    timer: Option<Res<LevelTimer>>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if let Some(replay) = &mut recorder.recording {
+        replay.frames.push(ReplayFrame {
+            input: *input,
+            delta: time.delta,
+            elapsed: timer.map_or(0.0, |timer| timer.elapsed),
+        });
+    }
+}
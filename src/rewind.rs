@@ -0,0 +1,315 @@
+use std::mem::size_of;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+
+use crate::{
+    level::RestartLevelEvent,
+    physics::{
+        sleep::{RestTime, Sleeping},
+        Collider, PhysObj, PhysicsStep,
+    },
+    player::Player,
+    powerup::{PickupEvent, SizePickup},
+    state::AppState,
+};
+
+pub const REWIND_KEY: KeyCode = KeyCode::Back;
+// Frames of history undone per frame while rewinding
+pub const REWIND_SPEED: usize = 2;
+// Pickups taken in a single frame that a rewind can put back; more than this are lost
+const MAX_PICKUPS_PER_FRAME: usize = 4;
+
+// Holding Backspace rewinds the player (or everything, see RewindConfig) through the last few
+// seconds, at REWIND_SPEED times real time. The rewound bodies are asleep while it's held, and
+// carry on from where they were put when it's let go.
+#[derive(Default)]
+pub struct RewindPlugin {
+    config: RewindConfig,
+}
+
+impl RewindPlugin {
+    pub fn with_config(config: RewindConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .insert_resource(RewindBuffer::new(&self.config))
+            .add_event::<PickupEvent>()
+            .add_event::<RestartLevelEvent>()
+            .add_system(
+                rewind_system
+                    .before(PhysicsStep)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_system(
+                rewind_clear_system
+                    .after(rewind_system)
+                    .run_if(on_event::<RestartLevelEvent>()),
+            )
+            .add_system(rewind_clear_system.in_schedule(OnEnter(AppState::MainMenu)));
+    }
+}
+
+// Fixed when the plugin is built, as the buffer is allocated up front
+#[derive(Resource, Clone)]
+pub struct RewindConfig {
+    // Frames of history kept, one per frame played. 300 is 5 seconds at 60 fps.
+    pub frames: usize,
+    // Rewinds every body rather than just the player
+    pub all_bodies: bool,
+    // Bodies kept per frame with `all_bodies`; any more aren't rewound
+    pub max_bodies: usize,
+    // Puts back the pickups taken during the rewound time. The player keeps what they gave it.
+    pub restore_pickups: bool,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            frames: 300,
+            all_bodies: false,
+            max_bodies: 256,
+            restore_pickups: false,
+        }
+    }
+}
+
+// A body as it was at the start of a frame, with everything the physics carries over to the next
+#[derive(Clone)]
+pub struct BodySnapshot {
+    pub entity: Entity,
+    pub transform: Transform,
+    pub phys_obj: PhysObj,
+    pub collider: Option<Collider>,
+    pub rest_time: Option<f32>,
+    pub asleep: bool,
+}
+
+#[derive(Default)]
+pub struct RewindFrame {
+    pub bodies: Vec<BodySnapshot>,
+    // Pickups taken between the frame before and this one
+    pub pickups: Vec<(Transform, SizePickup)>,
+}
+
+// A ring of the last frames, all allocated when it's made. Recording a frame reuses the oldest
+// one's memory, so the buffer never grows.
+#[derive(Resource)]
+pub struct RewindBuffer {
+    frames: Vec<RewindFrame>,
+    // Index of the oldest frame
+    start: usize,
+    len: usize,
+    max_bodies: usize,
+    // Whether the last frame was rewound
+    rewinding: bool,
+}
+
+impl RewindBuffer {
+    pub fn new(config: &RewindConfig) -> Self {
+        let max_bodies = if config.all_bodies {
+            config.max_bodies
+        } else {
+            1
+        };
+        Self {
+            frames: (0..config.frames.max(1))
+                .map(|_| RewindFrame {
+                    bodies: Vec::with_capacity(max_bodies),
+                    pickups: Vec::with_capacity(MAX_PICKUPS_PER_FRAME),
+                })
+                .collect(),
+            start: 0,
+            len: 0,
+            max_bodies,
+            rewinding: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.frames.len()
+    }
+
+    // An empty frame after the newest, which replaces the oldest once the buffer is full
+    pub fn push(&mut self) -> &mut RewindFrame {
+        if self.len == self.capacity() {
+            self.start = (self.start + 1) % self.capacity();
+        } else {
+            self.len += 1;
+        }
+        let index = (self.start + self.len - 1) % self.capacity();
+        let frame = &mut self.frames[index];
+        frame.bodies.clear();
+        frame.pickups.clear();
+        frame
+    }
+
+    pub fn newest(&self) -> Option<&RewindFrame> {
+        (!self.is_empty()).then(|| &self.frames[(self.start + self.len - 1) % self.capacity()])
+    }
+
+    // Takes the newest frame off the buffer
+    pub fn pop(&mut self) -> Option<&RewindFrame> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        Some(&self.frames[(self.start + self.len) % self.capacity()])
+    }
+
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+        self.rewinding = false;
+    }
+
+    // Everything the buffer holds on to, which doesn't change as it's used
+    pub fn memory_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.frames.capacity() * size_of::<RewindFrame>()
+            + self
+                .frames
+                .iter()
+                .map(|frame| {
+                    frame.bodies.capacity() * size_of::<BodySnapshot>()
+                        + frame.pickups.capacity() * size_of::<(Transform, SizePickup)>()
+                })
+                .sum::<usize>()
+    }
+}
+
+type RewindQuery = (
+    Entity,
+    &'static Transform,
+    &'static PhysObj,
+    Option<&'static Collider>,
+    Option<&'static RestTime>,
+    Option<&'static Sleeping>,
+    Option<&'static Player>,
+);
+
+// Runs at the start of every frame played: records where the bodies are, or while REWIND_KEY is
+// held, puts them back where they were a few frames ago and keeps the physics off them until it's
+// let go. Exclusive so that they're asleep before this frame's physics runs.
+fn rewind_system(
+    world: &mut World,
+    mut bodies: Local<QueryState<RewindQuery>>,
+    mut pickup_events: Local<ManualEventReader<PickupEvent>>,
+    mut taken: Local<Vec<(Transform, SizePickup)>>,
+) {
+    taken.clear();
+    taken.extend(
+        pickup_events
+            .iter(world.resource::<Events<PickupEvent>>())
+            .map(|event| (event.transform, event.pickup)),
+    );
+    let held = world.resource::<Input<KeyCode>>().pressed(REWIND_KEY);
+    let config = world.resource::<RewindConfig>().clone();
+
+    world.resource_scope(|world, mut buffer: Mut<RewindBuffer>| {
+        if held {
+            if config.restore_pickups {
+                // Taken last frame, which is already behind where this rewinds to
+                spawn_pickups(world, &taken);
+            }
+            for _ in 0..REWIND_SPEED {
+                if buffer.len() <= 1 {
+                    break;
+                }
+                let frame = buffer.pop().unwrap();
+                if config.restore_pickups {
+                    spawn_pickups(world, &frame.pickups);
+                }
+            }
+            buffer.rewinding = true;
+            if let Some(frame) = buffer.newest() {
+                restore(world, frame, true);
+            }
+        } else if buffer.rewinding {
+            // Again, as the player's systems may have pushed the bodies since; and it's already
+            // the newest frame, so it isn't recorded twice
+            buffer.rewinding = false;
+            if let Some(frame) = buffer.newest() {
+                restore(world, frame, false);
+            }
+        } else {
+            let max_bodies = buffer.max_bodies;
+            let frame = buffer.push();
+            let recorded = bodies
+                .iter(world)
+                .filter(|&(.., player)| config.all_bodies || player.is_some())
+                .take(max_bodies);
+            for (entity, transform, phys_obj, collider, rest_time, sleeping, _) in recorded {
+                frame.bodies.push(BodySnapshot {
+                    entity,
+                    transform: *transform,
+                    phys_obj: phys_obj.clone(),
+                    collider: collider.copied(),
+                    rest_time: rest_time.map(|rest_time| rest_time.0),
+                    asleep: sleeping.is_some(),
+                });
+            }
+            frame
+                .pickups
+                .extend(taken.iter().copied().take(MAX_PICKUPS_PER_FRAME));
+        }
+    });
+}
+
+// Puts the bodies back as they were in `frame`. `hold` leaves them asleep, to be rewound further;
+// otherwise they wake up (or not) as they were.
+fn restore(world: &mut World, frame: &RewindFrame, hold: bool) {
+    for body in &frame.bodies {
+        let Some(mut entity) = world.get_entity_mut(body.entity) else {
+            continue;
+        };
+        if let Some(mut transform) = entity.get_mut::<Transform>() {
+            *transform = body.transform;
+        }
+        if let Some(mut phys_obj) = entity.get_mut::<PhysObj>() {
+            // Not a push from outside, which would wake the body
+            phys_obj
+                .bypass_change_detection()
+                .clone_from(&body.phys_obj);
+        }
+        if let (Some(mut collider), Some(snapshot)) = (entity.get_mut::<Collider>(), body.collider)
+        {
+            *collider = snapshot;
+        }
+        if hold || body.asleep {
+            entity.insert(Sleeping);
+        } else {
+            entity.remove::<Sleeping>();
+        }
+        match body.rest_time {
+            Some(rest_time) => {
+                entity.insert(RestTime(rest_time));
+            }
+            None => {
+                entity.remove::<RestTime>();
+            }
+        }
+    }
+}
+
+fn spawn_pickups(world: &mut World, pickups: &[(Transform, SizePickup)]) {
+    for &(transform, pickup) in pickups {
+        world.spawn((SpatialBundle::from_transform(transform), pickup));
+    }
+}
+
+// The bodies in the buffer are gone after a restart or going back to the menu
+fn rewind_clear_system(mut buffer: ResMut<RewindBuffer>) {
+    buffer.clear();
+}
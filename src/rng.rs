@@ -0,0 +1,121 @@
+use std::ops::Range;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{launch::LaunchOptions, replay::fnv1a};
+
+// A small, fast pseudo-random number generator (xoshiro256++). Not for anything secret, but the
+// same seed gives the same numbers on every platform.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn from_seed(seed: u64) -> Self {
+        // splitmix64 spreads the seed over the state, which mustn't be all zeroes
+        let mut seed = seed;
+        let mut next = || {
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Self {
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let [a, b, c, d] = &mut self.state;
+        let result = a.wrapping_add(*d).rotate_left(23).wrapping_add(*a);
+        let t = *b << 17;
+        *c ^= *a;
+        *d ^= *b;
+        *b ^= *c;
+        *a ^= *d;
+        *c ^= t;
+        *d = d.rotate_left(45);
+        result
+    }
+
+    // Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // Uniform in [-1, 1)
+    pub fn signed(&mut self) -> f32 {
+        2.0 * self.next_f32() - 1.0
+    }
+
+    pub fn range(&mut self, range: Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next_f32()
+    }
+}
+
+// Where all of the game's randomness comes from: seeded from LaunchOptions (`--seed 42`), or from
+// the system if there isn't one, and the seed's logged so that a run can be repeated.
+//
+// Each use draws from its own stream, named by a label: a stream's numbers depend only on the seed,
+// the label and how much that stream has been drawn from, so systems running in another order or
+// a new use of randomness don't change what everything else gets.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<String, Rng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // The stream named `label`, carrying on from where it was last drawn from
+    pub fn stream(&mut self, label: &str) -> &mut Rng {
+        if !self.streams.contains_key(label) {
+            let rng = self.fork(label);
+            self.streams.insert(label.to_string(), rng);
+        }
+        self.streams.get_mut(label).unwrap()
+    }
+
+    // A new generator for `label`, at the start of its stream, for anything keeping its own
+    pub fn fork(&self, label: &str) -> Rng {
+        Rng::from_seed(self.seed ^ fnv1a(label.bytes()))
+    }
+}
+
+impl FromWorld for GameRng {
+    fn from_world(world: &mut World) -> Self {
+        let seed = world
+            .get_resource::<LaunchOptions>()
+            .and_then(|options| options.seed)
+            .unwrap_or_else(entropy);
+        info!("Random seed: {seed} (`--seed {seed}` repeats it)");
+        Self::new(seed)
+    }
+}
+
+// A seed that's different every run
+fn entropy() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::hash::{BuildHasher, Hasher};
+        std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        (js_sys::Math::random() * u64::MAX as f64) as u64
+    }
+}
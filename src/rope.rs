@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blob::Blob,
+    physics::{
+        joints::DistanceJoint, Collider, Gravity, PhysObj, PhysicsConfig, PhysicsSchedule,
+        PhysicsSet, PhysicsTime,
+    },
+    player::{Gliding, JumpEvent, Player, PlayerInput, Slamming},
+};
+
+// Segments are this big, for grabbing them and for looking at them
+pub const ROPE_SEGMENT_RADIUS: f32 = 6.0;
+pub const ROPE_SEGMENT_MASS: f32 = 2.0;
+// Holding climb up or down moves the player one segment along this often
+pub const ROPE_CLIMB_TIME: f32 = 0.15;
+// A player that's just grabbed the rope is pulled in to it this fast, rather than all at once
+pub const ROPE_PULL_IN_SPEED: f32 = 200.0;
+// How long after letting go of a rope the player can't grab one again
+pub const ROPE_REGRAB_TIME: f32 = 0.3;
+
+// Ropes hanging from anchors, which the player can grab in the air (holding climb up or jump),
+// climb, and jump off. Grabbing joins the player to a segment with a DistanceJoint that starts out
+// as long as they are apart, so nothing is yanked, and then pulls in. Only the simulated parts;
+// VisualsPlugin makes the ropes visible.
+pub struct RopePlugin;
+
+impl Plugin for RopePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .add_event::<JumpEvent>()
+            .add_system(
+                rope_grab_system
+                    .in_set(PhysicsSet::ApplyImpulses)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+// Where a rope of `segments` segments, each `segment_length` apart, hangs from
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct RopeAnchor {
+    pub segments: u32,
+    pub segment_length: f32,
+}
+
+// On the anchor: its segments, from the top down
+#[derive(Component, Clone, Debug)]
+pub struct Rope {
+    pub segments: Vec<Entity>,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RopeSegment {
+    pub rope: Entity,
+    pub index: usize,
+}
+
+// On the player while it holds on to `segment`, through `joint`
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Grabbing {
+    pub segment: Entity,
+    pub joint: Entity,
+    // Seconds climb up or down has been held since the last segment change
+    pub climbing: f32,
+    // Whether jump was held in the last step, as only a fresh press lets go
+    pub jump_held: bool,
+}
+
+// On the player for `remaining` seconds after letting go of a rope
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RopeRelease {
+    pub remaining: f32,
+}
+
+// Spawns a rope hanging straight down from `position`
+pub fn spawn_rope(
+    commands: &mut Commands,
+    config: &PhysicsConfig,
+    position: Vec2,
+    anchor: RopeAnchor,
+) -> Entity {
+    let rope = commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position.extend(-0.5))),
+            anchor,
+        ))
+        .id();
+    let mut above = rope;
+    let segments = (0..anchor.segments as usize)
+        .map(|index| {
+            let below = position - Vec2::Y * anchor.segment_length * (index + 1) as f32;
+            let segment = commands
+                .spawn((
+                    SpatialBundle::from_transform(Transform::from_translation(below.extend(-0.5))),
+                    PhysObj {
+                        mass: ROPE_SEGMENT_MASS,
+                        vel: Vec2::ZERO,
+                        acc: Vec2::ZERO,
+                        acc_prev: Vec2::ZERO,
+                        moment_of_inertia: ROPE_SEGMENT_MASS
+                            * 0.5
+                            * ROPE_SEGMENT_RADIUS
+                            * ROPE_SEGMENT_RADIUS,
+                        angular_vel: 0.0,
+                        angular_acc: 0.0,
+                        angular_acc_prev: 0.0,
+                        com_offset: Vec2::ZERO,
+                    },
+                    Gravity(config.gravity),
+                    RopeSegment { rope, index },
+                ))
+                .id();
+            commands.spawn(DistanceJoint {
+                a: above,
+                b: segment,
+                length: anchor.segment_length,
+                rigid: false,
+            });
+            above = segment;
+            segment
+        })
+        .collect();
+    commands.entity(rope).insert(Rope { segments });
+    rope
+}
+
+// The segment closest to `position` of those within `reach` of it
+pub fn nearest_segment(
+    position: Vec2,
+    reach: f32,
+    segments: impl IntoIterator<Item = (Entity, Vec2)>,
+) -> Option<Entity> {
+    segments
+        .into_iter()
+        .map(|(entity, at)| (entity, at.distance(position)))
+        .filter(|&(_, distance)| distance <= reach)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+// Jumping off a rope goes the way the segment was going, plus the jump
+pub fn release_velocity(segment_vel: Vec2, player: &Player, mass: f32) -> Vec2 {
+    segment_vel + Vec2::Y * player.jump_impulse / mass
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn rope_grab_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    input: Res<PlayerInput>,
+    mut jumps: EventWriter<JumpEvent>,
+    ropes: Query<&Rope>,
+    segments: Query<(Entity, &Transform, &PhysObj, &RopeSegment), Without<Player>>,
+    mut joints: Query<&mut DistanceJoint>,
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &Player,
+            &mut PhysObj,
+            &Collider,
+            Option<&mut Grabbing>,
+            Option<&mut RopeRelease>,
+            Option<&PlayerInput>,
+        ),
+        Without<Blob>,
+    >,
+) {
+    for (entity, transform, player, mut phys_obj, collider, grabbing, release, own_input) in
+        &mut players
+    {
+        let input = own_input.unwrap_or(&input);
+        let dt = time.delta;
+        let position = transform.translation.truncate();
+        let Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        } = *collider;
+
+        if let Some(mut release) = release {
+            release.remaining -= dt;
+            if release.remaining <= 0.0 {
+                commands.entity(entity).remove::<RopeRelease>();
+            }
+            continue;
+        }
+
+        let Some(mut grabbing) = grabbing else {
+            if touching_ground || !(input.climb_up || input.jump) {
+                continue;
+            }
+            let reach = radius + ROPE_SEGMENT_RADIUS;
+            let nearby = segments
+                .iter()
+                .map(|(segment, transform, ..)| (segment, transform.translation.truncate()));
+            let Some(segment) = nearest_segment(position, reach, nearby) else {
+                continue;
+            };
+            let Ok((_, segment_transform, ..)) = segments.get(segment) else {
+                continue;
+            };
+            let at = segment_transform.translation.truncate();
+            let joint = hold(&mut commands, (segment, at), (entity, position), radius);
+            commands
+                .entity(entity)
+                .remove::<(Gliding, Slamming)>()
+                .insert(Grabbing {
+                    segment,
+                    joint,
+                    climbing: 0.0,
+                    jump_held: input.jump,
+                });
+            continue;
+        };
+
+        let Ok((_, _, segment_obj, &RopeSegment { rope, index })) = segments.get(grabbing.segment)
+        else {
+            // The rope's gone, and maybe the joint with it
+            if let Some(mut joint) = commands.get_entity(grabbing.joint) {
+                joint.despawn();
+            }
+            commands.entity(entity).remove::<Grabbing>();
+            continue;
+        };
+
+        if input.jump && !grabbing.jump_held {
+            commands.entity(grabbing.joint).despawn();
+            commands
+                .entity(entity)
+                .remove::<Grabbing>()
+                .insert(RopeRelease {
+                    remaining: ROPE_REGRAB_TIME,
+                });
+            phys_obj.vel = release_velocity(segment_obj.vel, player, phys_obj.mass);
+            jumps.send(JumpEvent {
+                entity,
+                impulse: player.jump_impulse,
+            });
+            continue;
+        }
+        grabbing.jump_held = input.jump;
+
+        // Up the rope is towards the anchor, the first segment
+        let climb = match (input.climb_up, input.climb_down) {
+            (true, false) => index.checked_sub(1),
+            (false, true) => Some(index + 1),
+            _ => None,
+        };
+        let next = climb.and_then(|next| {
+            let next = *ropes.get(rope).ok()?.segments.get(next)?;
+            let (_, transform, ..) = segments.get(next).ok()?;
+            Some((next, transform.translation.truncate()))
+        });
+        match next {
+            Some(next) if grabbing.climbing + dt >= ROPE_CLIMB_TIME => {
+                commands.entity(grabbing.joint).despawn();
+                grabbing.joint = hold(&mut commands, next, (entity, position), radius);
+                grabbing.segment = next.0;
+                grabbing.climbing = 0.0;
+            }
+            Some(_) => grabbing.climbing += dt,
+            // Ready to move as soon as it's held again
+            None => grabbing.climbing = ROPE_CLIMB_TIME,
+        }
+
+        if let Ok(mut joint) = joints.get_mut(grabbing.joint) {
+            joint.length = (joint.length - ROPE_PULL_IN_SPEED * dt).max(radius);
+        }
+    }
+}
+
+// Spawns the joint that has the player at `position` hold on to `segment` at `at`, as long as
+// they're apart so that it doesn't pull yet
+fn hold(
+    commands: &mut Commands,
+    (segment, at): (Entity, Vec2),
+    (player, position): (Entity, Vec2),
+    radius: f32,
+) -> Entity {
+    commands
+        .spawn(DistanceJoint {
+            a: segment,
+            b: player,
+            length: at.distance(position).max(radius),
+            rigid: false,
+        })
+        .id()
+}
@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+
+use crate::{
+    level::{spawn_ball, BallEntry, FloorEntry, Level, RestartLevelEvent, SurfaceMaterial},
+    physics::{Collider, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsTime},
+    player::{PlayerBall, PLAYER_RADIUS},
+    progress::Score,
+    settings::{InputAction, Settings},
+    state::AppState,
+    storage::{load_ron, save_ron, Storage},
+    toast::ToastEvent,
+};
+
+// The run ends when the player is pushed back past this
+pub const RUNNER_KILL_X: f32 = -600.0;
+// Obstacles come in from here, off the right of the screen, and go once they're past
+// RUNNER_DESPAWN_X, off the left
+pub const RUNNER_SPAWN_X: f32 = 900.0;
+pub const RUNNER_DESPAWN_X: f32 = -900.0;
+// The distance run, in the meters shown to the player, is the scroll distance over this
+pub const PIXELS_PER_METER: f32 = 100.0;
+pub const SPIKES_SIZE: Vec2 = Vec2::new(60.0, 30.0);
+const BOULDER_RADIUS: f32 = 35.0;
+const RUNNER_FLOOR_WIDTH: f32 = 4000.0;
+const RUNNER_BEST_NAME: &str = "runner_best";
+// The obstacles, in the order they come round
+const OBSTACLE_PATTERN: [ObstacleTemplate; 5] = [
+    ObstacleTemplate::Spikes,
+    ObstacleTemplate::Spikes,
+    ObstacleTemplate::Boulder,
+    ObstacleTemplate::Spikes,
+    ObstacleTemplate::Boulder,
+];
+
+// An endless run: the world scrolls left at a growing speed, and the player jumps and rolls to keep
+// up and get past the obstacles coming at them. The floor moves (see
+// PhysicsConfig::floor_velocity) and takes everything on it along, so a ball that isn't rolling
+// right at the world's speed drifts back towards the kill wall. Touching spikes or being pushed
+// past the wall ends the run, and the respawn key (R by default) starts another. The distance run
+// is the score, and the best is kept between sessions.
+//
+// There are no gaps to fall into, as the physics' floor goes on forever. Replaces the Level, so
+// needs the LevelPlugin to spawn it. Enabled with `--runner`, or `?runner` on WASM.
+pub struct RunnerPlugin;
+
+impl Plugin for RunnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Storage>();
+        let best = load_ron(app.world.resource::<Storage>().0.as_ref(), RUNNER_BEST_NAME)
+            .and_then(|best| {
+                best.map_err(|error| warn!("Discarding the best run distance: {error}"))
+                    .ok()
+            })
+            .unwrap_or_default();
+        app.insert_resource(runner_level())
+            .insert_resource(RunnerBest(best))
+            .init_resource::<RunnerDifficulty>()
+            .init_resource::<Runner>()
+            .init_resource::<Score>()
+            .init_resource::<Settings>()
+            .add_event::<RestartLevelEvent>()
+            .add_event::<RunnerOverEvent>()
+            .add_event::<ToastEvent>()
+            .add_systems(
+                (runner_scroll_system, runner_spawn_system)
+                    .chain()
+                    .in_set(PhysicsSet::ApplyImpulses)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_systems(
+                (runner_despawn_system, runner_over_system)
+                    .in_set(PhysicsSet::PostSolve)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_system(runner_restart_system.in_set(OnUpdate(AppState::Playing)))
+            .add_system(runner_reset_system.run_if(on_event::<RestartLevelEvent>()))
+            .add_system(runner_reset_system.in_schedule(OnEnter(AppState::MainMenu)));
+    }
+}
+
+// How the run gets harder: the world scrolls at `start_speed`, gaining `acceleration` every second
+// up to `max_speed`, and obstacles start `start_spacing` apart, closing up by `spacing_decay` for
+// every unit of distance run down to `min_spacing`
+#[derive(Resource, Clone)]
+pub struct RunnerDifficulty {
+    pub start_speed: f32,
+    pub acceleration: f32,
+    pub max_speed: f32,
+    pub start_spacing: f32,
+    pub spacing_decay: f32,
+    pub min_spacing: f32,
+}
+
+impl Default for RunnerDifficulty {
+    fn default() -> Self {
+        Self {
+            start_speed: 200.0,
+            acceleration: 10.0,
+            max_speed: 800.0,
+            start_spacing: 900.0,
+            spacing_decay: 0.01,
+            min_spacing: 350.0,
+        }
+    }
+}
+
+impl RunnerDifficulty {
+    // Scroll speed `time` seconds into the run
+    pub fn speed(&self, time: f32) -> f32 {
+        (self.start_speed + self.acceleration * time).min(self.max_speed)
+    }
+
+    // Distance from an obstacle due at `distance` to the next one
+    pub fn spacing(&self, distance: f32) -> f32 {
+        (self.start_spacing - self.spacing_decay * distance).max(self.min_spacing)
+    }
+}
+
+// The run in progress
+#[derive(Resource, Clone, Default, Debug)]
+pub struct Runner {
+    pub time: f32,
+    // How far the world has scrolled
+    pub distance: f32,
+    // Distance at which the last obstacle came in, or the start
+    pub last_obstacle: f32,
+    pub obstacles: usize,
+    pub over: bool,
+}
+
+// Longest distance run, in meters
+#[derive(Resource, Default)]
+pub struct RunnerBest(pub f32);
+
+// The run ended
+pub struct RunnerOverEvent {
+    pub meters: f32,
+    pub new_best: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObstacleTemplate {
+    // SPIKES_SIZE, on the floor, that end the run when touched
+    Spikes,
+    // A heavy ball rolling along with the floor, to jump over or get pushed back by
+    Boulder,
+}
+
+// Something in the player's way, which came in when the run reached `due`
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Obstacle {
+    pub template: ObstacleTemplate,
+    pub due: f32,
+}
+
+// Spikes aren't bodies, so they're moved along with the floor
+#[derive(Component)]
+pub struct Spikes;
+
+// `--runner` on the command line, or `runner` in the page's URL query on WASM
+pub fn runner_requested() -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::env::args().any(|arg| arg == "--runner");
+    #[cfg(target_arch = "wasm32")]
+    return web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .map_or(false, |query| {
+            query
+                .trim_start_matches('?')
+                .split('&')
+                .any(|pair| pair == "runner")
+        });
+}
+
+// A floor as wide as the screen, with the player on it
+fn runner_level() -> Level {
+    Level {
+        floors: vec![FloorEntry {
+            x: 0.0,
+            width: RUNNER_FLOOR_WIDTH,
+            material: SurfaceMaterial::Normal,
+        }],
+        balls: vec![BallEntry {
+            position: Vec2::ZERO,
+            radius: PLAYER_RADIUS,
+            mass: 10.0,
+            coef_of_restitution: 0.3,
+            kinetic_friction: 0.5,
+            com_offset: Vec2::ZERO,
+            player: true,
+        }],
+        ..Level::empty()
+    }
+}
+
+// Speeds the world up, and moves the floor and spikes along with it
+fn runner_scroll_system(
+    time: Res<PhysicsTime>,
+    difficulty: Res<RunnerDifficulty>,
+    mut runner: ResMut<Runner>,
+    mut config: ResMut<PhysicsConfig>,
+    mut score: ResMut<Score>,
+    mut spikes: Query<&mut Transform, With<Spikes>>,
+) {
+    let speed = if runner.over {
+        0.0
+    } else {
+        difficulty.speed(runner.time)
+    };
+    // Only when it changes, as changing the config wakes every body
+    if config.floor_velocity != -speed {
+        config.floor_velocity = -speed;
+    }
+    if runner.over {
+        return;
+    }
+
+    let dt = time.delta;
+    runner.time += dt;
+    runner.distance += speed * dt;
+    for mut transform in &mut spikes {
+        transform.translation.x -= speed * dt;
+    }
+    let meters = (runner.distance / PIXELS_PER_METER) as u32;
+    if score.0 != meters {
+        score.0 = meters;
+    }
+}
+
+// Brings in the obstacles that are due, each where it would be had it come in right on time
+fn runner_spawn_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    difficulty: Res<RunnerDifficulty>,
+    mut runner: ResMut<Runner>,
+) {
+    while !runner.over {
+        let due = runner.last_obstacle + difficulty.spacing(runner.last_obstacle);
+        if due > runner.distance {
+            break;
+        }
+        let template = OBSTACLE_PATTERN[runner.obstacles % OBSTACLE_PATTERN.len()];
+        let x = RUNNER_SPAWN_X - (runner.distance - due);
+        spawn_obstacle(&mut commands, &config, template, x, due);
+        runner.obstacles += 1;
+        runner.last_obstacle = due;
+    }
+}
+
+fn spawn_obstacle(
+    commands: &mut Commands,
+    config: &PhysicsConfig,
+    template: ObstacleTemplate,
+    x: f32,
+    due: f32,
+) {
+    let obstacle = Obstacle { template, due };
+    match template {
+        ObstacleTemplate::Spikes => {
+            commands.spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(
+                    x,
+                    config.floor_y + 0.5 * SPIKES_SIZE.y,
+                    -0.5,
+                )),
+                Spikes,
+                obstacle,
+            ));
+        }
+        ObstacleTemplate::Boulder => {
+            let ball = BallEntry {
+                position: Vec2::new(x, config.floor_y + BOULDER_RADIUS),
+                radius: BOULDER_RADIUS,
+                mass: 40.0,
+                coef_of_restitution: 0.2,
+                kinetic_friction: 1.0,
+                com_offset: Vec2::ZERO,
+                player: false,
+            };
+            let entity = spawn_ball(commands, config, &ball);
+            // Already moving along with the floor
+            commands.entity(entity).insert((
+                PhysObj {
+                    mass: ball.mass,
+                    vel: Vec2::new(config.floor_velocity, 0.0),
+                    acc: Vec2::ZERO,
+                    acc_prev: Vec2::ZERO,
+                    moment_of_inertia: ball.mass * 0.5 * ball.radius.powi(2),
+                    angular_vel: 0.0,
+                    angular_acc: 0.0,
+                    angular_acc_prev: 0.0,
+                    com_offset: Vec2::ZERO,
+                },
+                obstacle,
+            ));
+        }
+    }
+}
+
+fn runner_despawn_system(
+    mut commands: Commands,
+    obstacles: Query<(Entity, &Transform), With<Obstacle>>,
+) {
+    for (entity, transform) in &obstacles {
+        if transform.translation.x < RUNNER_DESPAWN_X {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Ends the run when the player touches spikes or is pushed past the kill wall
+fn runner_over_system(
+    mut runner: ResMut<Runner>,
+    mut best: ResMut<RunnerBest>,
+    mut storage: ResMut<Storage>,
+    mut events: EventWriter<RunnerOverEvent>,
+    mut toasts: EventWriter<ToastEvent>,
+    players: Query<(&Transform, &Collider), PlayerBall>,
+    spikes: Query<&Transform, With<Spikes>>,
+) {
+    if runner.over {
+        return;
+    }
+    let Ok((transform, &Collider::Ball { radius, .. })) = players.get_single() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let spiked = spikes.iter().any(|spikes| {
+        let center = spikes.translation.truncate();
+        let closest = position.clamp(center - 0.5 * SPIKES_SIZE, center + 0.5 * SPIKES_SIZE);
+        closest.distance(position) < radius
+    });
+    if !spiked && position.x - radius > RUNNER_KILL_X {
+        return;
+    }
+
+    runner.over = true;
+    let meters = runner.distance / PIXELS_PER_METER;
+    let new_best = meters > best.0;
+    if new_best {
+        best.0 = meters;
+        if let Err(error) = save_ron(storage.0.as_mut(), RUNNER_BEST_NAME, &meters) {
+            error!("Failed to save the best run distance: {error}");
+        }
+    }
+    info!("Run over after {meters:.0} m");
+    toasts.send(ToastEvent(format!(
+        "Ran {meters:.0} m{}, best {:.0} m. R to run again",
+        if new_best { " (new best!)" } else { "" },
+        best.0
+    )));
+    events.send(RunnerOverEvent { meters, new_best });
+}
+
+fn runner_restart_system(
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    runner: Res<Runner>,
+    mut restarts: EventWriter<RestartLevelEvent>,
+) {
+    if runner.over && settings.input.just_pressed(&input, InputAction::Respawn) {
+        restarts.send(RestartLevelEvent);
+    }
+}
+
+// The level's restart takes the obstacles with the rest of the level
+fn runner_reset_system(mut runner: ResMut<Runner>) {
+    *runner = default();
+}
@@ -0,0 +1,165 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    level::{spawn_ball, BallEntry},
+    physics::{disk_mass, Collider, PhysObj, PhysicsConfig, PhysicsStep},
+    player::Player,
+    rng::GameRng,
+    slingshot::cursor_world_position,
+    state::AppState,
+    toast::ToastEvent,
+    visuals::BodyColor,
+};
+
+// For playing around with the physics: Ctrl + right click spawns a ball at the mouse, and middle
+// click removes the nearest one that isn't a player. A plain right click is the grapple's.
+pub struct SandboxPlugin;
+
+impl Plugin for SandboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SandboxConfig>()
+            .init_resource::<GameRng>()
+            .add_event::<SandboxEvent>()
+            .add_event::<ToastEvent>()
+            .add_systems(
+                (sandbox_input_system, sandbox_system)
+                    .chain()
+                    .before(PhysicsStep)
+                    .distributive_run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct SandboxConfig {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    // Mass per unit area, so bigger balls are heavier
+    pub density: f32,
+    pub min_restitution: f32,
+    pub max_restitution: f32,
+    // Spawning stops at this many sandbox balls, to keep the frame rate
+    pub max_balls: usize,
+    // How close to the mouse a ball has to be to be removed
+    pub despawn_distance: f32,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            min_radius: 10.0,
+            max_radius: 40.0,
+            density: 0.005,
+            min_restitution: 0.1,
+            max_restitution: 0.9,
+            max_balls: 100,
+            despawn_distance: 50.0,
+        }
+    }
+}
+
+// At a point in the world
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SandboxEvent {
+    Spawn(Vec2),
+    Despawn(Vec2),
+}
+
+// A ball spawned with the mouse
+#[derive(Component)]
+pub struct SandboxBall;
+
+// A ball at `position` of random size, bounciness and color
+pub fn random_ball(
+    config: &SandboxConfig,
+    rng: &mut GameRng,
+    position: Vec2,
+) -> (BallEntry, Color) {
+    let rng = rng.stream("sandbox");
+    let radius = rng.range(config.min_radius..config.max_radius);
+    let ball = BallEntry {
+        position,
+        radius,
+        mass: disk_mass(config.density, radius),
+        coef_of_restitution: rng.range(config.min_restitution..config.max_restitution),
+        kinetic_friction: 0.5,
+        com_offset: Vec2::ZERO,
+        player: false,
+    };
+    (ball, Color::hsl(rng.range(0.0..360.0), 0.7, 0.55))
+}
+
+fn sandbox_input_system(
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut events: EventWriter<SandboxEvent>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let spawn = ctrl && buttons.just_pressed(MouseButton::Right);
+    let despawn = buttons.just_pressed(MouseButton::Middle);
+    if !spawn && !despawn {
+        return;
+    }
+    let Some(position) = cursor_world_position(&windows, &cameras) else {
+        return;
+    };
+    if spawn {
+        events.send(SandboxEvent::Spawn(position));
+    }
+    if despawn {
+        events.send(SandboxEvent::Despawn(position));
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn sandbox_system(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    sandbox: Res<SandboxConfig>,
+    mut rng: ResMut<GameRng>,
+    mut events: EventReader<SandboxEvent>,
+    mut toasts: EventWriter<ToastEvent>,
+    spawned: Query<(), With<SandboxBall>>,
+    balls: Query<(Entity, &Transform), (With<PhysObj>, With<Collider>, Without<Player>)>,
+) {
+    // Spawns only show up in `spawned` next frame
+    let mut count = spawned.iter().count();
+    let mut despawned = Vec::new();
+    for &event in events.iter() {
+        match event {
+            SandboxEvent::Spawn(_) if count >= sandbox.max_balls => {
+                toasts.send(ToastEvent(format!(
+                    "No more than {} balls",
+                    sandbox.max_balls
+                )));
+            }
+            SandboxEvent::Spawn(position) => {
+                let (ball, color) = random_ball(&sandbox, &mut rng, position);
+                let entity = spawn_ball(&mut commands, &config, &ball);
+                commands
+                    .entity(entity)
+                    .insert((SandboxBall, BodyColor(color)));
+                count += 1;
+            }
+            SandboxEvent::Despawn(position) => {
+                let nearest = balls
+                    .iter()
+                    .filter(|(entity, _)| !despawned.contains(entity))
+                    .map(|(entity, transform)| {
+                        (entity, transform.translation.truncate().distance(position))
+                    })
+                    .filter(|&(_, distance)| distance <= sandbox.despawn_distance)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                if let Some((entity, _)) = nearest {
+                    commands.entity(entity).despawn_recursive();
+                    despawned.push(entity);
+                    if spawned.contains(entity) {
+                        count -= 1;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    physics::{Collider, Gravity, PhysObj},
+    player::Player,
+    shapes::FidgetSpinner,
+    storage::{load_ron, save_ron, Storage},
+    toast::ToastEvent,
+};
+
+const SAVE_NAME: &str = "savegame";
+
+// Quicksave (F5) and quickload (F9)
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Storage>().add_system(quicksave_system);
+    }
+}
+
+// Everything needed to pick up where a save left off. Only descriptors are stored (e.g. the
+// FidgetSpinner, not its mesh handle); VisualsPlugin recreates the visuals when loading.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub bodies: Vec<SavedBody>,
+}
+
+// A simulated body. The full physics state is kept so that a body saved mid-air continues on
+// exactly the same path after loading.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedBody {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub phys_obj: PhysObj,
+    pub collider: Option<Collider>,
+    pub gravity: Option<Gravity>,
+    pub player: Option<Player>,
+    pub shape: Option<FidgetSpinner>,
+}
+
+impl SavedBody {
+    pub fn capture_all(world: &mut World) -> Vec<SavedBody> {
+        let mut query = world.query::<(
+            &Transform,
+            &PhysObj,
+            Option<&Collider>,
+            Option<&Gravity>,
+            Option<&Player>,
+            Option<&FidgetSpinner>,
+        )>();
+        query
+            .iter(world)
+            .map(
+                |(transform, phys_obj, collider, gravity, player, shape)| SavedBody {
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    phys_obj: phys_obj.clone(),
+                    collider: collider.copied(),
+                    gravity: gravity.cloned(),
+                    player: player.cloned(),
+                    shape: shape.copied(),
+                },
+            )
+            .collect()
+    }
+
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        let mut entity = world.spawn((
+            SpatialBundle::from_transform(Transform {
+                translation: self.translation,
+                rotation: self.rotation,
+                ..default()
+            }),
+            self.phys_obj.clone(),
+        ));
+        if let Some(collider) = self.collider {
+            entity.insert(collider);
+        }
+        if let Some(gravity) = &self.gravity {
+            entity.insert(gravity.clone());
+        }
+        if let Some(player) = &self.player {
+            entity.insert(player.clone());
+        }
+        if let Some(shape) = self.shape {
+            entity.insert(shape);
+        }
+        entity.id()
+    }
+}
+
+impl SaveGame {
+    pub fn capture(world: &mut World) -> Self {
+        Self {
+            bodies: SavedBody::capture_all(world),
+        }
+    }
+
+    // Replaces the bodies in `world` with the saved ones
+    pub fn restore(&self, world: &mut World) {
+        let bodies: Vec<Entity> = world
+            .query_filtered::<Entity, With<PhysObj>>()
+            .iter(world)
+            .collect();
+        for entity in bodies {
+            world.despawn(entity);
+        }
+        for body in &self.bodies {
+            body.spawn(world);
+        }
+    }
+}
+
+fn quicksave_system(world: &mut World) {
+    let input = world.resource::<Input<KeyCode>>();
+    let (save, load) = (
+        input.just_pressed(KeyCode::F5),
+        input.just_pressed(KeyCode::F9),
+    );
+
+    let message = if save {
+        let save = SaveGame::capture(world);
+        match save_ron(world.resource_mut::<Storage>().0.as_mut(), SAVE_NAME, &save) {
+            Ok(()) => "Saved the game".to_string(),
+            Err(error) => format!("Failed to save the game: {error}"),
+        }
+    } else if load {
+        let save = load_ron::<SaveGame>(world.resource::<Storage>().0.as_ref(), SAVE_NAME);
+        match save {
+            Some(Ok(save)) => {
+                save.restore(world);
+                "Loaded the save".to_string()
+            }
+            Some(Err(error)) => format!("Failed to load the save: {error}"),
+            None => "There is no save to load".to_string(),
+        }
+    } else {
+        return;
+    };
+
+    info!("{message}");
+    // Shown in game too if there's a ToastPlugin
+    if let Some(mut toasts) = world.get_resource_mut::<Events<ToastEvent>>() {
+        toasts.send(ToastEvent(message));
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hazard::DeathEvent,
+    physics::{
+        collision::{resolve_contact, ContactPoint},
+        Collider, PhysObj, PhysicsSchedule, PhysicsSet, PhysicsTime,
+    },
+    player::Player,
+};
+
+// Friction between a blade's teeth and what they hit. High, so that the rim flings things along
+// with it.
+pub const SAW_FRICTION: f32 = 1.0;
+
+// Spinning saw blades. They're kinematic: moved along their path and never pushed back, as if
+// they had infinite mass. The player dies touching one (see HazardPlugin), and other balls are
+// knocked away by the rim as well as bounced off it. Only the simulated parts; VisualsPlugin
+// makes the blades visible.
+pub struct SawBladePlugin;
+
+impl Plugin for SawBladePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DeathEvent>().add_systems(
+            (saw_motion_system, saw_contact_system)
+                .chain()
+                .after(PhysicsSet::ResolveCollisions)
+                .before(PhysicsSet::SolveConstraints)
+                .in_schedule(PhysicsSchedule),
+        );
+    }
+}
+
+// A circular saw of `radius`, spinning at `angular_speed` (radians per second, counterclockwise).
+// With a `path` it slides from the first point to the second and back, taking the given seconds
+// each way, and starts from the first whatever its Transform says.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SawBlade {
+    pub radius: f32,
+    pub angular_speed: f32,
+    pub path: Option<(Vec2, Vec2, f32)>,
+}
+
+impl SawBlade {
+    // Where a blade on a path is `elapsed` seconds in, and its velocity there
+    pub fn path_state(&self, elapsed: f32) -> Option<(Vec2, Vec2)> {
+        let (from, to, duration) = self.path?;
+        // 0 to 1 is the way out, 1 to 2 the way back
+        let phase = (elapsed / duration).rem_euclid(2.0);
+        let (along, direction) = if phase < 1.0 {
+            (phase, 1.0)
+        } else {
+            (2.0 - phase, -1.0)
+        };
+        Some((from.lerp(to, along), direction * (to - from) / duration))
+    }
+
+    // Velocity of the rim `offset` from the center of a blade moving at `vel`
+    pub fn rim_velocity(&self, vel: Vec2, offset: Vec2) -> Vec2 {
+        vel + self.angular_speed * offset.perp()
+    }
+}
+
+// How far along its path a blade is, and how fast it's moving
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct SawBladeMotion {
+    pub elapsed: f32,
+    pub vel: Vec2,
+}
+
+fn saw_motion_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    mut blades: Query<(
+        Entity,
+        &SawBlade,
+        &mut Transform,
+        Option<&mut SawBladeMotion>,
+    )>,
+) {
+    let dt = time.delta;
+    for (entity, blade, mut transform, motion) in &mut blades {
+        let mut state = motion.as_deref().copied().unwrap_or_default();
+        state.elapsed += dt;
+        if let Some((position, vel)) = blade.path_state(state.elapsed) {
+            transform.translation = position.extend(transform.translation.z);
+            state.vel = vel;
+        }
+        transform.rotate_z(blade.angular_speed * dt);
+
+        match motion {
+            Some(mut motion) => *motion = state,
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+    }
+}
+
+fn saw_contact_system(
+    mut deaths: EventWriter<DeathEvent>,
+    blades: Query<(&Transform, &SawBlade, Option<&SawBladeMotion>), Without<PhysObj>>,
+    mut balls: Query<(
+        Entity,
+        &mut Transform,
+        &mut PhysObj,
+        &Collider,
+        Option<&Player>,
+    )>,
+) {
+    for (
+        entity,
+        mut transform,
+        mut phys_obj,
+        &Collider::Ball {
+            radius,
+            coef_of_restitution,
+            ..
+        },
+        player,
+    ) in &mut balls
+    {
+        for (blade_transform, blade, motion) in &blades {
+            let center = blade_transform.translation.truncate();
+            let apart = transform.translation.truncate() - center;
+            let distance = apart.length();
+            if distance >= blade.radius + radius {
+                continue;
+            }
+            if player.is_some() {
+                deaths.send(DeathEvent { player: entity });
+                continue;
+            }
+
+            let normal = apart.try_normalize().unwrap_or(Vec2::Y);
+            // Out to where it touches, as the blade won't give way
+            let position = center + normal * (blade.radius + radius);
+            transform.translation = position.extend(transform.translation.z);
+
+            let vel = motion.map_or(Vec2::ZERO, |motion| motion.vel);
+            let rim_vel = blade.rim_velocity(vel, normal * blade.radius);
+            let point = ContactPoint {
+                offset: -normal * radius,
+                normal,
+            }
+            .with_com_offset(phys_obj.com_arm(transform.rotation));
+            // In the frame of the rim where they touch, and only if they're coming together
+            let contact_vel = phys_obj.vel + phys_obj.angular_vel * point.offset.perp() - rim_vel;
+            if contact_vel.dot(normal) >= 0.0 {
+                continue;
+            }
+            phys_obj.vel -= rim_vel;
+            resolve_contact(&mut phys_obj, &point, coef_of_restitution, SAW_FRICTION);
+            phys_obj.vel += rim_vel;
+        }
+    }
+}
@@ -0,0 +1,671 @@
+use std::fmt;
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    level::{spawn_ball, BallEntry},
+    physics::{
+        heightfield::Heightfield, BounceEvent, Collider, Gravity, PhysObj, PhysicsConfig,
+        PhysicsStep,
+    },
+    player::player_input_system,
+    settings::{InputAction, Settings},
+    state::AppState,
+    testing::{test_app, TEST_DT},
+};
+
+// A body slower than this, and turning slower than this at its edge, is at rest
+const REST_SPEED: f32 = 5.0;
+
+// A regression test for the solver written as data: some balls and the world they're in (with any
+// slopes), the keys
+// pressed along the way, how long it runs for, and what should be true of the run. The shipped
+// ones are in `scenarios/`, which tests/scenarios.rs runs, and `--scenario <file>` runs one and
+// prints how each assertion went.
+#[derive(Deserialize)]
+pub struct Scenario {
+    // Simulated seconds
+    pub duration: f32,
+    #[serde(default)]
+    pub world: WorldEntry,
+    #[serde(default)]
+    pub heightfields: Vec<HeightfieldEntry>,
+    pub bodies: Vec<BodyEntry>,
+    #[serde(default)]
+    pub inputs: Vec<InputEntry>,
+    pub assertions: Vec<Assertion>,
+}
+
+// The PhysicsConfig the scenario runs with. What isn't given is the default.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct WorldEntry {
+    pub gravity: f32,
+    pub floor_y: f32,
+    pub substeps: u32,
+    pub floor_restitution: f32,
+    pub floor_friction: f32,
+    pub floor_velocity: f32,
+    pub rolling_resistance: f32,
+}
+
+impl Default for WorldEntry {
+    fn default() -> Self {
+        let config = PhysicsConfig::default();
+        Self {
+            gravity: config.gravity,
+            floor_y: config.floor_y,
+            substeps: config.substeps,
+            floor_restitution: config.floor_restitution,
+            floor_friction: config.floor_friction,
+            floor_velocity: config.floor_velocity,
+            rolling_resistance: config.rolling_resistance,
+        }
+    }
+}
+
+impl WorldEntry {
+    fn apply(&self, config: &mut PhysicsConfig) {
+        config.gravity = self.gravity;
+        config.floor_y = self.floor_y;
+        config.substeps = self.substeps;
+        config.floor_restitution = self.floor_restitution;
+        config.floor_friction = self.floor_friction;
+        config.floor_velocity = self.floor_velocity;
+        config.rolling_resistance = self.rolling_resistance;
+    }
+}
+
+// Ground for the balls to roll on besides the floor, see Heightfield. `position` is where its first
+// height is measured from.
+#[derive(Deserialize)]
+pub struct HeightfieldEntry {
+    pub position: Vec2,
+    pub spacing: f32,
+    pub heights: Vec<f32>,
+}
+
+impl HeightfieldEntry {
+    fn heightfield(&self) -> Heightfield {
+        Heightfield {
+            spacing: self.spacing,
+            heights: self.heights.clone(),
+        }
+    }
+}
+
+// A ball, named so that assertions can refer to it
+#[derive(Deserialize)]
+pub struct BodyEntry {
+    pub name: String,
+    pub position: Vec2,
+    #[serde(default)]
+    pub velocity: Vec2,
+    // Angular velocity, counterclockwise
+    #[serde(default)]
+    pub spin: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    // Whether gravity pulls it down
+    #[serde(default = "falls_by_default")]
+    pub falls: bool,
+    // Whether the inputs control it
+    #[serde(default)]
+    pub player: bool,
+}
+
+fn falls_by_default() -> bool {
+    true
+}
+
+impl BodyEntry {
+    fn ball(&self) -> BallEntry {
+        BallEntry {
+            position: self.position,
+            radius: self.radius,
+            mass: self.mass,
+            coef_of_restitution: self.restitution,
+            kinetic_friction: self.friction,
+            com_offset: Vec2::ZERO,
+            player: self.player,
+        }
+    }
+}
+
+// From `at` seconds in, the keys of the actions in `hold` are held down, until the next entry
+#[derive(Deserialize)]
+pub struct InputEntry {
+    pub at: f32,
+    pub hold: Vec<InputAction>,
+}
+
+// What should be true of a run
+#[derive(Clone, Debug, Deserialize)]
+pub enum Assertion {
+    // Where `body` is at the end, to within `within`
+    FinalPosition {
+        body: String,
+        position: Vec2,
+        within: f32,
+    },
+    // How fast `body` is moving at the end, to within `within`
+    FinalVelocity {
+        body: String,
+        velocity: Vec2,
+        within: f32,
+    },
+    // The highest `body` gets from `after` seconds in, to within `within`
+    MaxHeight {
+        body: String,
+        after: f32,
+        height: f32,
+        within: f32,
+    },
+    // How far any ball was ever into the floor or another ball, at the end of a frame
+    MaxPenetration(f32),
+    // `body` is at rest (see REST_SPEED) from `time` seconds in to the end
+    AtRestBy {
+        body: String,
+        time: f32,
+    },
+    // The fraction of the energy the bodies started with that they ever gain or lose
+    EnergyDrift(f32),
+    // From `after` seconds in to the end, `body` never gets more than `within` away from where it
+    // was then, across or up and down
+    StaysPut {
+        body: String,
+        after: f32,
+        within: f32,
+    },
+    // `body` is touching the ground at the end of every frame from `after` seconds in
+    OnGround {
+        body: String,
+        after: f32,
+    },
+    // `body` doesn't bounce from `after` seconds in
+    NoBounces {
+        body: String,
+        after: f32,
+    },
+    // The friction force's impulses on `body` from `after` seconds in, added up, come to at most
+    // `max`
+    FrictionImpulse {
+        body: String,
+        after: f32,
+        max: f32,
+    },
+}
+
+impl Assertion {
+    fn body(&self) -> Option<&str> {
+        match self {
+            Assertion::FinalPosition { body, .. }
+            | Assertion::FinalVelocity { body, .. }
+            | Assertion::MaxHeight { body, .. }
+            | Assertion::AtRestBy { body, .. }
+            | Assertion::StaysPut { body, .. }
+            | Assertion::OnGround { body, .. }
+            | Assertion::NoBounces { body, .. }
+            | Assertion::FrictionImpulse { body, .. } => Some(body),
+            Assertion::MaxPenetration(_) | Assertion::EnergyDrift(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScenarioError::Io(error) => write!(f, "couldn't read the scenario: {error}"),
+            ScenarioError::Ron(error) => write!(f, "not a scenario: {error}"),
+            ScenarioError::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl Scenario {
+    pub fn parse(ron: &str) -> Result<Self, ScenarioError> {
+        let scenario: Scenario = ron::from_str(ron).map_err(ScenarioError::Ron)?;
+        scenario.validate().map_err(ScenarioError::Invalid)?;
+        Ok(scenario)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &str) -> Result<Self, ScenarioError> {
+        Self::parse(&std::fs::read_to_string(path).map_err(ScenarioError::Io)?)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(self.duration.is_finite() && self.duration > 0.0) {
+            return Err(format!("duration {} isn't positive", self.duration));
+        }
+        if self.world.substeps == 0 {
+            return Err("there has to be at least one substep".to_string());
+        }
+        for heightfield in &self.heightfields {
+            if !(heightfield.spacing.is_finite() && heightfield.spacing > 0.0) {
+                return Err(format!(
+                    "heightfield spacing {} isn't positive",
+                    heightfield.spacing
+                ));
+            }
+            if heightfield.heights.len() < 2 {
+                return Err("a heightfield needs at least two heights".to_string());
+            }
+        }
+        for (i, body) in self.bodies.iter().enumerate() {
+            if self.bodies[..i].iter().any(|other| other.name == body.name) {
+                return Err(format!("there's more than one body called {}", body.name));
+            }
+            body.ball()
+                .validate()
+                .map_err(|reason| format!("body {}: {reason}", body.name))?;
+        }
+        for assertion in &self.assertions {
+            if let Some(body) = assertion.body() {
+                if self.body(body).is_none() {
+                    return Err(format!("{assertion:?} is about a body that isn't there"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn body(&self, name: &str) -> Option<usize> {
+        self.bodies.iter().position(|body| body.name == name)
+    }
+
+    // Everything's energy, kinetic and potential, for bodies in the state `states`
+    fn energy(&self, config: &PhysicsConfig, states: &[BodyState]) -> f32 {
+        self.bodies
+            .iter()
+            .zip(states)
+            .map(|(body, state)| {
+                let moment_of_inertia = 0.5 * body.mass * body.radius.powi(2);
+                let height = state.position.y - config.floor_y;
+                0.5 * body.mass * state.velocity.length_squared()
+                    + 0.5 * moment_of_inertia * state.spin.powi(2)
+                    + body.mass * state.gravity * height
+            })
+            .sum()
+    }
+
+    // How far the balls in the state `states` are into the floor, the heightfields or each other
+    fn penetration(&self, config: &PhysicsConfig, states: &[BodyState]) -> f32 {
+        let mut penetration = 0.0_f32;
+        for (i, (body, state)) in self.bodies.iter().zip(states).enumerate() {
+            if !config.over_gap(state.position.x) {
+                let bottom = state.position.y - body.radius;
+                penetration = penetration.max(config.floor_y - bottom);
+            }
+            for heightfield in &self.heightfields {
+                let contact = heightfield
+                    .heightfield()
+                    .contact(state.position - heightfield.position, body.radius);
+                if let Some((_, depth)) = contact {
+                    penetration = penetration.max(depth);
+                }
+            }
+            for (other, other_state) in self.bodies[..i].iter().zip(states) {
+                let distance = state.position.distance(other_state.position);
+                penetration = penetration.max(body.radius + other.radius - distance);
+            }
+        }
+        penetration
+    }
+}
+
+// A body as it was at the end of a frame
+#[derive(Clone, Copy)]
+struct BodyState {
+    position: Vec2,
+    velocity: Vec2,
+    spin: f32,
+    gravity: f32,
+    touching_ground: bool,
+    // During the frame
+    bounces: usize,
+    friction_impulse: f32,
+}
+
+struct Frame {
+    time: f32,
+    // In the scenario's order
+    states: Vec<BodyState>,
+}
+
+// The run in progress: its bodies, in the scenario's order, and every frame so far
+#[derive(Resource)]
+struct ScenarioRun {
+    entities: Vec<Entity>,
+    inputs: Vec<(f32, Vec<InputAction>)>,
+    elapsed: f32,
+    frames: Vec<Frame>,
+}
+
+// A headless App that plays out the scenario, like `test_app`. Its bodies are spawned straight
+// away, and it's done once ScenarioRun's elapsed time reaches the duration.
+fn scenario_app(scenario: &Scenario) -> App {
+    let mut app = test_app();
+    let mut config = app.world.resource::<PhysicsConfig>().clone();
+    scenario.world.apply(&mut config);
+    app.insert_resource(config.clone());
+
+    let mut queue = CommandQueue::default();
+    let entities: Vec<Entity> = {
+        let mut commands = Commands::new(&mut queue, &app.world);
+        scenario
+            .bodies
+            .iter()
+            .map(|body| {
+                let entity = spawn_ball(&mut commands, &config, &body.ball());
+                if !body.falls {
+                    commands.entity(entity).remove::<Gravity>();
+                }
+                entity
+            })
+            .collect()
+    };
+    for heightfield in &scenario.heightfields {
+        app.world.spawn((
+            Transform::from_translation(heightfield.position.extend(0.0)),
+            heightfield.heightfield(),
+        ));
+    }
+    queue.apply(&mut app.world);
+    for (body, &entity) in scenario.bodies.iter().zip(&entities) {
+        let mut phys_obj = app.world.get_mut::<PhysObj>(entity).unwrap();
+        phys_obj.vel = body.velocity;
+        phys_obj.angular_vel = body.spin;
+    }
+
+    let inputs = scenario
+        .inputs
+        .iter()
+        .map(|input| (input.at, input.hold.clone()))
+        .collect();
+    app.insert_resource(ScenarioRun {
+        entities,
+        inputs,
+        elapsed: 0.0,
+        frames: Vec::new(),
+    })
+    .add_systems(
+        (
+            scenario_input_system
+                .before(player_input_system)
+                .before(PhysicsStep),
+            scenario_record_system.after(PhysicsStep),
+        )
+            .in_set(OnUpdate(AppState::Playing)),
+    );
+    app
+}
+
+// Holds down the keys of the latest input entry, and lets go of the rest
+fn scenario_input_system(
+    run: Res<ScenarioRun>,
+    settings: Res<Settings>,
+    mut keys: ResMut<Input<KeyCode>>,
+) {
+    let hold = run
+        .inputs
+        .iter()
+        .rfind(|(at, _)| *at <= run.elapsed)
+        .map_or(&[][..], |(_, hold)| hold);
+    for action in InputAction::ALL {
+        let key = settings.input.key(action);
+        if hold.contains(&action) {
+            keys.press(key);
+        } else {
+            keys.release(key);
+        }
+    }
+}
+
+fn scenario_record_system(
+    time: Res<Time>,
+    mut run: ResMut<ScenarioRun>,
+    mut bounce_events: EventReader<BounceEvent>,
+    bodies: Query<(&Transform, &PhysObj, &Collider, Option<&Gravity>)>,
+) {
+    run.elapsed += time.delta_seconds();
+    let bounced: Vec<Entity> = bounce_events.iter().map(|bounce| bounce.entity).collect();
+    let states = run
+        .entities
+        .iter()
+        .map(|&entity| {
+            let (transform, phys_obj, collider, gravity) = bodies.get(entity).unwrap();
+            let Collider::Ball {
+                touching_ground,
+                friction_acc,
+                ..
+            } = *collider;
+            BodyState {
+                position: transform.translation.truncate(),
+                velocity: phys_obj.vel,
+                spin: phys_obj.angular_vel,
+                gravity: gravity.map_or(0.0, |gravity| gravity.0),
+                touching_ground,
+                bounces: bounced.iter().filter(|&&bounced| bounced == entity).count(),
+                friction_impulse: phys_obj.mass * friction_acc * time.delta_seconds(),
+            }
+        })
+        .collect();
+    let time = run.elapsed;
+    run.frames.push(Frame { time, states });
+}
+
+// How an assertion went, with what was measured
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    pub passed: bool,
+    pub observed: String,
+}
+
+pub struct ScenarioReport {
+    pub results: Vec<AssertionResult>,
+}
+
+impl ScenarioReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+}
+
+impl fmt::Display for ScenarioReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in &self.results {
+            let verdict = if result.passed { "pass" } else { "FAIL" };
+            writeln!(f, "{verdict}: {:?} ({})", result.assertion, result.observed)?;
+        }
+        let passed = self.results.iter().filter(|result| result.passed).count();
+        write!(f, "{passed} of {} assertions passed", self.results.len())
+    }
+}
+
+// Plays out the scenario and checks each of its assertions against the run
+pub fn run_scenario(scenario: &Scenario) -> ScenarioReport {
+    let mut app = scenario_app(scenario);
+    // The physics doesn't start right away, but it's started well before this
+    let max_updates = (scenario.duration / TEST_DT).ceil() as usize + 10;
+    for _ in 0..max_updates {
+        if app.world.resource::<ScenarioRun>().elapsed >= scenario.duration - 0.5 * TEST_DT {
+            break;
+        }
+        app.update();
+    }
+
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let frames = &app.world.resource::<ScenarioRun>().frames;
+    let start: Vec<BodyState> = scenario
+        .bodies
+        .iter()
+        .map(|body| BodyState {
+            position: body.position,
+            velocity: body.velocity,
+            spin: body.spin,
+            gravity: if body.falls { config.gravity } else { 0.0 },
+            touching_ground: false,
+            bounces: 0,
+            friction_impulse: 0.0,
+        })
+        .collect();
+    let results = scenario
+        .assertions
+        .iter()
+        .map(|assertion| {
+            let (passed, observed) = check(scenario, &config, &start, frames, assertion);
+            AssertionResult {
+                assertion: assertion.clone(),
+                passed,
+                observed,
+            }
+        })
+        .collect();
+    ScenarioReport { results }
+}
+
+fn check(
+    scenario: &Scenario,
+    config: &PhysicsConfig,
+    start: &[BodyState],
+    frames: &[Frame],
+    assertion: &Assertion,
+) -> (bool, String) {
+    let Some(last) = frames.last() else {
+        return (false, "the physics never ran".to_string());
+    };
+    let body = |name: &str| scenario.body(name).unwrap();
+    let from = |after: f32| frames.iter().filter(move |frame| frame.time >= after);
+    match assertion {
+        Assertion::FinalPosition {
+            body: name,
+            position,
+            within,
+        } => {
+            let actual = last.states[body(name)].position;
+            let passed = actual.distance(*position) <= *within;
+            (passed, format!("ended at {actual}"))
+        }
+        Assertion::FinalVelocity {
+            body: name,
+            velocity,
+            within,
+        } => {
+            let actual = last.states[body(name)].velocity;
+            let passed = actual.distance(*velocity) <= *within;
+            (passed, format!("ended moving at {actual}"))
+        }
+        Assertion::MaxHeight {
+            body: name,
+            after,
+            height,
+            within,
+        } => {
+            let i = body(name);
+            let highest = frames
+                .iter()
+                .filter(|frame| frame.time >= *after)
+                .map(|frame| frame.states[i].position.y)
+                .fold(f32::NEG_INFINITY, f32::max);
+            let passed = (highest - height).abs() <= *within;
+            (passed, format!("got up to {highest}"))
+        }
+        Assertion::MaxPenetration(max) => {
+            let deepest = frames
+                .iter()
+                .map(|frame| scenario.penetration(config, &frame.states))
+                .fold(0.0, f32::max);
+            (deepest <= *max, format!("{deepest} deep at most"))
+        }
+        Assertion::AtRestBy { body: name, time } => {
+            let i = body(name);
+            let radius = scenario.bodies[i].radius;
+            let moving = frames.iter().rev().find(|frame| {
+                let state = frame.states[i];
+                state.velocity.length() >= REST_SPEED || state.spin.abs() * radius >= REST_SPEED
+            });
+            match moving {
+                Some(frame) => (frame.time < *time, format!("moving until {}s", frame.time)),
+                None => (true, "never moving".to_string()),
+            }
+        }
+        Assertion::EnergyDrift(max) => {
+            let initial = scenario.energy(config, start);
+            let drift = frames
+                .iter()
+                .map(|frame| (scenario.energy(config, &frame.states) - initial).abs())
+                .fold(0.0, f32::max)
+                / initial.abs().max(f32::EPSILON);
+            (drift <= *max, format!("drifted by {drift} at most"))
+        }
+        Assertion::StaysPut {
+            body: name,
+            after,
+            within,
+        } => {
+            let i = body(name);
+            let then = frames
+                .iter()
+                .rev()
+                .find(|frame| frame.time <= *after)
+                .map_or(start[i], |frame| frame.states[i])
+                .position;
+            let furthest = from(*after)
+                .map(|frame| (frame.states[i].position - then).abs().max_element())
+                .fold(0.0, f32::max);
+            (furthest <= *within, format!("got {furthest} away"))
+        }
+        Assertion::OnGround { body: name, after } => {
+            let i = body(name);
+            match from(*after).find(|frame| !frame.states[i].touching_ground) {
+                Some(frame) => (false, format!("off the ground at {}s", frame.time)),
+                None => (true, "always on the ground".to_string()),
+            }
+        }
+        Assertion::NoBounces { body: name, after } => {
+            let i = body(name);
+            let bounces: usize = from(*after).map(|frame| frame.states[i].bounces).sum();
+            (bounces == 0, format!("bounced {bounces} times"))
+        }
+        Assertion::FrictionImpulse {
+            body: name,
+            after,
+            max,
+        } => {
+            let i = body(name);
+            let total: f32 = from(*after)
+                .map(|frame| frame.states[i].friction_impulse.abs())
+                .sum();
+            (total <= *max, format!("{total} in all"))
+        }
+    }
+}
+
+// `--scenario <file>`: runs it and prints the report. Returns whether everything passed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_scenario_file(path: &str) -> bool {
+    match Scenario::load(path) {
+        Ok(scenario) => {
+            let report = run_scenario(&scenario);
+            println!("{path}:\n{report}");
+            report.passed()
+        }
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            false
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::toast::ToastEvent;
+
+// F10 saves a screenshot. Only supported on WASM for now: Bevy 0.10 can't read back a rendered
+// frame, but the browser can export the canvas.
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(screenshot_system.in_base_set(CoreSet::Last));
+    }
+}
+
+pub fn screenshot_file_name(timestamp: u64) -> String {
+    format!("screenshot_{timestamp}.png")
+}
+
+fn screenshot_system(input: Res<Input<KeyCode>>, mut toasts: EventWriter<ToastEvent>) {
+    if !input.just_pressed(KeyCode::F10) {
+        return;
+    }
+    match capture_screenshot() {
+        Ok(file_name) => toasts.send(ToastEvent(format!("Saved {file_name}"))),
+        Err(error) => {
+            warn!("Couldn't take a screenshot: {error}");
+            toasts.send(ToastEvent(format!("Couldn't take a screenshot: {error}")));
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn capture_screenshot() -> Result<String, String> {
+    Err("not supported outside the browser yet".to_string())
+}
+
+// Downloads the contents of the game's canvas. The capture is synchronous, so there's never one in
+// flight when the next is requested.
+#[cfg(target_arch = "wasm32")]
+fn capture_screenshot() -> Result<String, String> {
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("there's no document")?;
+    let canvas = document
+        .query_selector("canvas")
+        .ok()
+        .flatten()
+        .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        .ok_or("there's no canvas")?;
+    let url = canvas
+        .to_data_url_with_type("image/png")
+        .map_err(|error| format!("{error:?}"))?;
+
+    let file_name = screenshot_file_name(js_sys::Date::now() as u64);
+    let anchor = document
+        .create_element("a")
+        .ok()
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .ok_or("couldn't create a download link")?;
+    anchor.set_href(&url);
+    anchor.set_download(&file_name);
+    anchor.click();
+    Ok(file_name)
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    help::Hint,
+    physics::PhysicsConfig,
+    storage::{load_ron, save_ron, Storage, StorageBackend, StorageError},
+};
+
+const SETTINGS_NAME: &str = "settings";
+// Changes are written once the settings have been left alone for this long, so that dragging a
+// slider doesn't write a file every frame
+const SAVE_DELAY: f32 = 1.0;
+
+// Loads the Settings when it's added, so add it before the plugins that read them
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Storage>();
+        let settings = Settings::load(app.world.resource_mut::<Storage>().0.as_mut());
+        app.insert_resource(settings)
+            .add_systems((settings_apply_system, settings_save_system));
+    }
+}
+
+// Player preferences, kept between sessions. Systems that change them only need to write to the
+// resource; it's saved shortly after.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub input: InputMap,
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    // Silences everything, toggled with the mute key
+    pub muted: bool,
+    pub vsync: bool,
+    // Physics steps per frame (see PhysicsConfig::substeps)
+    pub physics_substeps: u32,
+    // Helps spin the player's ball to match its speed just before it lands, while it's being spun
+    pub landing_assist: bool,
+    // Whether the physics overlay starts out shown
+    pub debug_overlay: bool,
+    // Whether the game resumes by itself when the window is focused again after losing focus
+    // paused it, instead of staying on the pause screen
+    pub resume_on_focus: bool,
+    // The controls help is shown by itself until it's been seen once
+    pub controls_help_seen: bool,
+    // Hints are only ever shown once
+    pub hints_shown: Vec<Hint>,
+    // Level times are only submitted to `leaderboard_url` once the player's opted in. Only used
+    // with the `leaderboard` feature.
+    pub leaderboard_opt_in: bool,
+    pub leaderboard_url: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            input: default(),
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 0.5,
+            muted: false,
+            vsync: true,
+            physics_substeps: PhysicsConfig::default().substeps,
+            landing_assist: false,
+            debug_overlay: false,
+            resume_on_focus: false,
+            controls_help_seen: false,
+            hints_shown: Vec::new(),
+            leaderboard_opt_in: false,
+            leaderboard_url: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputMap {
+    pub jump: KeyCode,
+    pub spin_left: KeyCode,
+    pub spin_right: KeyCode,
+    // Turns gravity off while held
+    pub zero_gravity: KeyCode,
+    // Along a rope the player is holding on to. Climbing down in the air slams instead.
+    pub climb_up: KeyCode,
+    pub climb_down: KeyCode,
+    pub dash: KeyCode,
+    // Escape always pauses as well
+    pub pause: KeyCode,
+    // Restarts the level, as dying does
+    pub respawn: KeyCode,
+    // Between torque and direct control (see ControlMode)
+    pub control_mode: KeyCode,
+    // Shows where a jump would go, as holding jump does
+    pub trajectory: KeyCode,
+    // Slows the game down while held
+    pub slow_motion: KeyCode,
+    // Turns slowing down for big impacts on and off
+    pub auto_slow_motion: KeyCode,
+    // Runs one physics step while paused
+    pub step: KeyCode,
+    pub mute: KeyCode,
+    // Starts and stops the spinners turning by themselves
+    pub animate_spinner: KeyCode,
+    // Shows and hides the controls help
+    pub controls_help: KeyCode,
+    // Holds the physics overlay's history plot still
+    pub freeze_history: KeyCode,
+    // More keys for the same actions, e.g. the arrow keys as well as A and D. The fields above are
+    // each action's main key, the one the menus show and rebind.
+    pub extra: HashMap<InputAction, Vec<KeyCode>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            jump: KeyCode::Space,
+            spin_left: KeyCode::A,
+            spin_right: KeyCode::D,
+            zero_gravity: KeyCode::K,
+            climb_up: KeyCode::W,
+            climb_down: KeyCode::S,
+            dash: KeyCode::LShift,
+            pause: KeyCode::P,
+            respawn: KeyCode::R,
+            control_mode: KeyCode::C,
+            trajectory: KeyCode::T,
+            slow_motion: KeyCode::Tab,
+            auto_slow_motion: KeyCode::O,
+            step: KeyCode::N,
+            mute: KeyCode::M,
+            animate_spinner: KeyCode::B,
+            controls_help: KeyCode::H,
+            freeze_history: KeyCode::F,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+// What a key in the InputMap does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    Jump,
+    SpinLeft,
+    SpinRight,
+    ZeroGravity,
+    ClimbUp,
+    ClimbDown,
+    Dash,
+    Pause,
+    Respawn,
+    ControlMode,
+    Trajectory,
+    SlowMotion,
+    AutoSlowMotion,
+    Step,
+    Mute,
+    AnimateSpinner,
+    ControlsHelp,
+    FreezeHistory,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 18] = [
+        InputAction::Jump,
+        InputAction::SpinLeft,
+        InputAction::SpinRight,
+        InputAction::ZeroGravity,
+        InputAction::ClimbUp,
+        InputAction::ClimbDown,
+        InputAction::Dash,
+        InputAction::Pause,
+        InputAction::Respawn,
+        InputAction::ControlMode,
+        InputAction::Trajectory,
+        InputAction::SlowMotion,
+        InputAction::AutoSlowMotion,
+        InputAction::Step,
+        InputAction::Mute,
+        InputAction::AnimateSpinner,
+        InputAction::ControlsHelp,
+        InputAction::FreezeHistory,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::Jump => "Jump",
+            InputAction::SpinLeft => "Spin left",
+            InputAction::SpinRight => "Spin right",
+            InputAction::ZeroGravity => "Zero gravity",
+            InputAction::ClimbUp => "Grab rope / climb up",
+            InputAction::ClimbDown => "Climb down / slam",
+            InputAction::Dash => "Dash",
+            InputAction::Pause => "Pause",
+            InputAction::Respawn => "Respawn",
+            InputAction::ControlMode => "Torque / direct control",
+            InputAction::Trajectory => "Show jump path",
+            InputAction::SlowMotion => "Slow motion",
+            InputAction::AutoSlowMotion => "Slow motion on impacts",
+            InputAction::Step => "Step while paused",
+            InputAction::Mute => "Mute",
+            InputAction::AnimateSpinner => "Animate spinners",
+            InputAction::ControlsHelp => "Show these controls",
+            InputAction::FreezeHistory => "Freeze physics history",
+        }
+    }
+
+    // Whether it moves the player, rather than pausing, restarting or changing how the game runs
+    pub fn moves_player(self) -> bool {
+        matches!(
+            self,
+            InputAction::Jump
+                | InputAction::SpinLeft
+                | InputAction::SpinRight
+                | InputAction::ZeroGravity
+                | InputAction::ClimbUp
+                | InputAction::ClimbDown
+                | InputAction::Dash
+        )
+    }
+}
+
+// What pressing a key while rebinding an action should do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rebind {
+    // The key is free (or already the action's)
+    Bind,
+    // The key is bound to another action, which could get this action's key instead
+    Conflict(InputAction),
+    // Escape is reserved for cancelling
+    Cancel,
+}
+
+impl InputMap {
+    // The default map with the given keys instead. An action's first key becomes its main key and
+    // the rest extra ones; actions that aren't given keep their default key.
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (InputAction, KeyCode)>) -> Self {
+        let mut map = Self::default();
+        let mut given = Vec::new();
+        for (action, key) in pairs {
+            if given.contains(&action) {
+                map.extra.entry(action).or_default().push(key);
+            } else {
+                *map.key_mut(action) = key;
+                given.push(action);
+            }
+        }
+        map
+    }
+
+    pub fn key(&self, action: InputAction) -> KeyCode {
+        match action {
+            InputAction::Jump => self.jump,
+            InputAction::SpinLeft => self.spin_left,
+            InputAction::SpinRight => self.spin_right,
+            InputAction::ZeroGravity => self.zero_gravity,
+            InputAction::ClimbUp => self.climb_up,
+            InputAction::ClimbDown => self.climb_down,
+            InputAction::Dash => self.dash,
+            InputAction::Pause => self.pause,
+            InputAction::Respawn => self.respawn,
+            InputAction::ControlMode => self.control_mode,
+            InputAction::Trajectory => self.trajectory,
+            InputAction::SlowMotion => self.slow_motion,
+            InputAction::AutoSlowMotion => self.auto_slow_motion,
+            InputAction::Step => self.step,
+            InputAction::Mute => self.mute,
+            InputAction::AnimateSpinner => self.animate_spinner,
+            InputAction::ControlsHelp => self.controls_help,
+            InputAction::FreezeHistory => self.freeze_history,
+        }
+    }
+
+    // The action's main key, then its extra ones
+    pub fn keys(&self, action: InputAction) -> impl Iterator<Item = KeyCode> + '_ {
+        let extra = self.extra.get(&action).map_or(&[][..], Vec::as_slice);
+        std::iter::once(self.key(action)).chain(extra.iter().copied())
+    }
+
+    pub fn pressed(&self, input: &Input<KeyCode>, action: InputAction) -> bool {
+        input.any_pressed(self.keys(action))
+    }
+
+    pub fn just_pressed(&self, input: &Input<KeyCode>, action: InputAction) -> bool {
+        input.any_just_pressed(self.keys(action))
+    }
+
+    // Adds `key` as one more key for `action`, unless it's taken (see `rebind`)
+    pub fn add_key(&mut self, action: InputAction, key: KeyCode) -> Rebind {
+        let rebind = self.rebind(action, key);
+        if rebind == Rebind::Bind && !self.keys(action).any(|bound| bound == key) {
+            self.extra.entry(action).or_default().push(key);
+        }
+        rebind
+    }
+
+    pub fn key_mut(&mut self, action: InputAction) -> &mut KeyCode {
+        match action {
+            InputAction::Jump => &mut self.jump,
+            InputAction::SpinLeft => &mut self.spin_left,
+            InputAction::SpinRight => &mut self.spin_right,
+            InputAction::ZeroGravity => &mut self.zero_gravity,
+            InputAction::ClimbUp => &mut self.climb_up,
+            InputAction::ClimbDown => &mut self.climb_down,
+            InputAction::Dash => &mut self.dash,
+            InputAction::Pause => &mut self.pause,
+            InputAction::Respawn => &mut self.respawn,
+            InputAction::ControlMode => &mut self.control_mode,
+            InputAction::Trajectory => &mut self.trajectory,
+            InputAction::SlowMotion => &mut self.slow_motion,
+            InputAction::AutoSlowMotion => &mut self.auto_slow_motion,
+            InputAction::Step => &mut self.step,
+            InputAction::Mute => &mut self.mute,
+            InputAction::AnimateSpinner => &mut self.animate_spinner,
+            InputAction::ControlsHelp => &mut self.controls_help,
+            InputAction::FreezeHistory => &mut self.freeze_history,
+        }
+    }
+
+    pub fn rebind(&self, action: InputAction, key: KeyCode) -> Rebind {
+        if key == KeyCode::Escape {
+            return Rebind::Cancel;
+        }
+        match InputAction::ALL
+            .into_iter()
+            .find(|&other| other != action && self.keys(other).any(|bound| bound == key))
+        {
+            Some(other) => Rebind::Conflict(other),
+            None => Rebind::Bind,
+        }
+    }
+
+    // Binds `key` to `action`. Whatever action had the key before gets the action's old key, so no
+    // key does two things.
+    pub fn bind_swapping(&mut self, action: InputAction, key: KeyCode) {
+        let old_key = self.key(action);
+        for other in InputAction::ALL {
+            if self.key(other) == key {
+                *self.key_mut(other) = old_key;
+            }
+        }
+        for keys in self.extra.values_mut() {
+            for bound in keys.iter_mut().filter(|bound| **bound == key) {
+                *bound = old_key;
+            }
+        }
+        *self.key_mut(action) = key;
+        // It may have been one of the action's extra keys
+        if let Some(keys) = self.extra.get_mut(&action) {
+            keys.retain(|&bound| bound != key);
+        }
+    }
+}
+
+impl Settings {
+    // Missing or unreadable settings are replaced by a clean file with the defaults
+    pub fn load(storage: &mut dyn StorageBackend) -> Self {
+        match load_ron(storage, SETTINGS_NAME) {
+            Some(Ok(settings)) => return settings,
+            Some(Err(error)) => warn!("Resetting the settings, they couldn't be parsed: {error}"),
+            None => info!("No settings found, using the defaults"),
+        }
+        let settings = Self::default();
+        if let Err(error) = settings.save(storage) {
+            error!("Failed to save the settings: {error}");
+        }
+        settings
+    }
+
+    pub fn save(&self, storage: &mut dyn StorageBackend) -> Result<(), StorageError> {
+        save_ron(storage, SETTINGS_NAME, self)
+    }
+}
+
+fn settings_apply_system(
+    settings: Res<Settings>,
+    config: Option<ResMut<PhysicsConfig>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Some(mut config) = config {
+        config.substeps = settings.physics_substeps;
+    }
+    for mut window in &mut windows {
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+// Saves the settings once they've stopped changing for SAVE_DELAY
+fn settings_save_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut storage: ResMut<Storage>,
+    mut changed_at: Local<Option<f32>>,
+) {
+    let now = time.elapsed_seconds();
+    // Inserting the resource counts as a change, but what was loaded doesn't need saving
+    if settings.is_changed() && !settings.is_added() {
+        *changed_at = Some(now);
+    }
+    if changed_at.is_some_and(|changed_at| now - changed_at >= SAVE_DELAY) {
+        if let Err(error) = settings.save(storage.0.as_mut()) {
+            error!("Failed to save the settings: {error}");
+        }
+        *changed_at = None;
+    }
+}
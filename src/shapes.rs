@@ -0,0 +1,214 @@
+use bevy::{prelude::*, render::mesh::VertexAttributeValues, sprite::Mesh2dHandle};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mesh_cache::MeshCache,
+    player::PlayerBall,
+    settings::{InputAction, Settings},
+};
+
+// Rippling of the player's spinner, toggled with the animate spinners key (B by default)
+pub struct ShapesPlugin;
+
+impl Plugin for ShapesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshCache>()
+            .init_resource::<Settings>()
+            .add_systems((spinner_animation_system, spinner_animation_toggle_system).chain());
+    }
+}
+
+// Also kept as a component on entities using the mesh so the mesh can be animated
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct FidgetSpinner {
+    pub radius: f32,
+    pub bump_size: f32,
+    pub bumps: u32,
+    pub vertices: usize,
+    #[serde(default)]
+    pub profile: SpinnerProfile,
+}
+
+// Shape of the bumps around the rim
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpinnerProfile {
+    // Rounded, rising and falling like a cosine
+    #[default]
+    Bumps,
+    // Straight-sided points. Sharpest with two vertices a bump, one at each tip and valley.
+    Star,
+}
+
+impl SpinnerProfile {
+    // Height of the profile, from -1 to 1, `angle` radians into a bump with its peak at 0
+    fn height(self, angle: f32) -> f32 {
+        match self {
+            SpinnerProfile::Bumps => angle.cos(),
+            SpinnerProfile::Star => {
+                let along = (angle / std::f32::consts::TAU).rem_euclid(1.0);
+                4.0 * (along - 0.5).abs() - 1.0
+            }
+        }
+    }
+}
+
+impl Default for FidgetSpinner {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            bump_size: 0.5 / 16.0,
+            bumps: 12,
+            vertices: 24,
+            profile: SpinnerProfile::Bumps,
+        }
+    }
+}
+
+impl FidgetSpinner {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            bump_size: radius / 16.0,
+            ..default()
+        }
+    }
+
+    // Rim vertex positions with the bumps shifted by `phase` and made `amplitude` taller
+    pub fn rim_positions(&self, phase: f32, amplitude: f32) -> impl Iterator<Item = [f32; 3]> {
+        let FidgetSpinner {
+            radius,
+            bump_size,
+            bumps,
+            vertices,
+            profile,
+        } = *self;
+
+        let step = std::f32::consts::TAU / vertices as f32;
+        (0..vertices).map(move |i| {
+            let theta = i as f32 * step;
+            let (sin, cos) = theta.sin_cos();
+            let offset = (bump_size + amplitude) * profile.height(bumps as f32 * theta - phase);
+
+            [cos * (radius + offset), sin * (radius + offset), 0.0]
+        })
+    }
+
+    // Rewrites the rim of a mesh made from this spinner in place
+    pub fn update_mesh(&self, mesh: &mut Mesh, phase: f32, amplitude: f32) {
+        set_rim_positions(mesh, self.rim_positions(phase, amplitude));
+    }
+}
+
+// Moves the rim vertices of a mesh made from a FidgetSpinner, in place. The center vertex stays
+// put, so the rim has to stay visible from it for the triangle fan to hold together.
+pub fn set_rim_positions(mesh: &mut Mesh, rim: impl IntoIterator<Item = [f32; 3]>) {
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    {
+        for (position, rim_position) in positions[1..].iter_mut().zip(rim) {
+            *position = rim_position;
+        }
+    }
+}
+
+// Makes the bumps of an entity's FidgetSpinner mesh ripple around the rim. Visual only, the
+// collider keeps its nominal radius. The mesh is changed in place, so it mustn't be shared (see
+// MeshCache).
+#[derive(Component)]
+pub struct AnimatedSpinner {
+    pub speed: f32,
+    pub amplitude: f32,
+}
+
+impl Default for AnimatedSpinner {
+    fn default() -> Self {
+        Self {
+            speed: 8.0,
+            amplitude: 1.5,
+        }
+    }
+}
+
+impl From<FidgetSpinner> for Mesh {
+    fn from(shape: FidgetSpinner) -> Self {
+        let vertices = shape.vertices;
+        assert!(
+            vertices >= 3,
+            "a FidgetSpinner needs at least 3 rim vertices"
+        );
+
+        let mut positions = Vec::with_capacity(vertices + 1);
+        positions.push([0.0, 0.0, 0.0]);
+        positions.extend(shape.rim_positions(0.0, 0.0));
+        let normals = vec![[0.0, 0.0, 1.0]; vertices + 1];
+
+        // Fan around the center vertex. Rim vertices go counter-clockwise, so (center, i, i + 1)
+        // is counter-clockwise as well; the last triangle wraps back to the first rim vertex.
+        let rim = vertices as u32;
+        let mut indices = Vec::with_capacity(vertices * 3);
+        for i in 0..rim {
+            indices.extend_from_slice(&[0, i + 1, (i + 1) % rim + 1]);
+        }
+
+        let mut mesh = Mesh::new(bevy::render::mesh::PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.set_indices(Some(bevy::render::mesh::Indices::U32(indices)));
+        mesh
+    }
+}
+
+// Animated spinners get their own copy of the mesh, and go back to the shared one when stopped
+fn spinner_animation_toggle_system(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<
+        (
+            Entity,
+            &FidgetSpinner,
+            &Mesh2dHandle,
+            Option<&AnimatedSpinner>,
+        ),
+        // A blob's mesh is already rewritten every frame
+        PlayerBall,
+    >,
+) {
+    if !settings
+        .input
+        .just_pressed(&input, InputAction::AnimateSpinner)
+    {
+        return;
+    }
+    for (entity, spinner, mesh, animated) in &query {
+        if animated.is_some() {
+            commands
+                .entity(entity)
+                .remove::<AnimatedSpinner>()
+                .insert(Mesh2dHandle(cache.spinner(&mut meshes, *spinner)));
+        } else {
+            let Some(copy) = meshes.get(&mesh.0).cloned() else {
+                continue;
+            };
+            commands
+                .entity(entity)
+                .insert((AnimatedSpinner::default(), Mesh2dHandle(meshes.add(copy))));
+        }
+    }
+}
+
+fn spinner_animation_system(
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<(&FidgetSpinner, &AnimatedSpinner, &Mesh2dHandle)>,
+) {
+    let t = time.elapsed_seconds();
+    for (spinner, animated, mesh) in &query {
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            let phase = (animated.speed * t).rem_euclid(std::f32::consts::TAU);
+            spinner.update_mesh(mesh, phase, animated.amplitude);
+        }
+    }
+}
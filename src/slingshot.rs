@@ -0,0 +1,228 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    debug::lines::DebugLines,
+    physics::{Collider, GravitySuppressed, PhysObj, PhysicsSchedule, PhysicsSet, PhysicsStep},
+    player::{dash_system, LocalPlayer},
+    state::AppState,
+};
+
+const AIM_COLOR: Color = Color::ORANGE;
+// Length of the aim indicator at SlingshotConfig::max_impulse
+const AIM_LENGTH: f32 = 200.0;
+
+// Launching the player with the mouse: press the left button on the ball, drag away from it and
+// let go to fling it the other way. The ball's held still while aiming.
+pub struct SlingshotPlugin;
+
+impl Plugin for SlingshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SlingshotConfig>()
+            .init_resource::<SlingshotCursor>()
+            .init_resource::<SlingshotDrag>()
+            .init_resource::<DebugLines>()
+            .add_systems(
+                (slingshot_cursor_system, slingshot_drag_system)
+                    .chain()
+                    .before(PhysicsStep)
+                    .distributive_run_if(in_state(AppState::Playing)),
+            )
+            .add_system(slingshot_aim_system.after(PhysicsStep))
+            // After the dash, which also decides whether the player has gravity
+            .add_system(
+                slingshot_system
+                    .in_set(PhysicsSet::ApplyImpulses)
+                    .after(dash_system)
+                    .in_schedule(PhysicsSchedule),
+            );
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct SlingshotConfig {
+    // Impulse per unit of drag
+    pub strength: f32,
+    pub max_impulse: f32,
+    // Shorter drags are let go without launching
+    pub min_drag: f32,
+}
+
+impl Default for SlingshotConfig {
+    fn default() -> Self {
+        Self {
+            strength: 60.0,
+            max_impulse: 15_000.0,
+            min_drag: 10.0,
+        }
+    }
+}
+
+impl SlingshotConfig {
+    // Away from the drag, and stronger the further it goes
+    pub fn impulse(&self, drag: Vec2) -> Vec2 {
+        (-drag * self.strength).clamp_length_max(self.max_impulse)
+    }
+}
+
+// The mouse in world coordinates and whether its left button is held. Filled in from the window
+// by slingshot_cursor_system; without a window (e.g. in tests) it's left to whoever sets it.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SlingshotCursor {
+    pub position: Option<Vec2>,
+    pub held: bool,
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum SlingshotDrag {
+    #[default]
+    Idle,
+    // Held on `entity`, with the cursor `drag` away from its center
+    Aiming {
+        entity: Entity,
+        drag: Vec2,
+    },
+    // Let go, to be launched on the next physics step, or just dropped if the drag was too short
+    Released {
+        entity: Entity,
+        drag: Vec2,
+    },
+}
+
+// Where the mouse is in the world seen by the active camera, if it's over the window
+pub fn cursor_world_position(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    let (camera, transform) = cameras.iter().find(|(camera, _)| camera.is_active)?;
+    camera
+        .viewport_to_world(transform, cursor)
+        .map(|ray| ray.origin.truncate())
+}
+
+fn slingshot_cursor_system(
+    buttons: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut cursor: ResMut<SlingshotCursor>,
+) {
+    if windows.is_empty() {
+        return;
+    }
+    *cursor = SlingshotCursor {
+        position: cursor_world_position(&windows, &cameras),
+        held: buttons.pressed(MouseButton::Left),
+    };
+}
+
+fn slingshot_drag_system(
+    cursor: Res<SlingshotCursor>,
+    mut drag: ResMut<SlingshotDrag>,
+    mut was_held: Local<bool>,
+    players: Query<(Entity, &Transform, &Collider), LocalPlayer>,
+) {
+    let pressed = cursor.held && !*was_held;
+    *was_held = cursor.held;
+    match *drag {
+        SlingshotDrag::Idle if pressed => {
+            let Some(position) = cursor.position else {
+                return;
+            };
+            let grabbed = players.iter().find(|(_, transform, collider)| {
+                let Collider::Ball { radius, .. } = **collider;
+                transform.translation.truncate().distance(position) <= radius
+            });
+            if let Some((entity, transform, _)) = grabbed {
+                *drag = SlingshotDrag::Aiming {
+                    entity,
+                    drag: position - transform.translation.truncate(),
+                };
+            }
+        }
+        SlingshotDrag::Aiming { entity, drag: held } => {
+            let Ok((_, transform, _)) = players.get(entity) else {
+                *drag = SlingshotDrag::Idle;
+                return;
+            };
+            let current = cursor
+                .position
+                .map_or(held, |position| position - transform.translation.truncate());
+            *drag = if cursor.held {
+                SlingshotDrag::Aiming {
+                    entity,
+                    drag: current,
+                }
+            } else {
+                SlingshotDrag::Released {
+                    entity,
+                    drag: current,
+                }
+            };
+        }
+        _ => {}
+    }
+}
+
+// Holds the aimed ball still, without gravity, and launches it once it's let go, or lets it fall
+// again if the drag was too short. Like the jump, the launch is an impulse applied before the
+// integrator.
+fn slingshot_system(
+    mut commands: Commands,
+    config: Res<SlingshotConfig>,
+    mut drag: ResMut<SlingshotDrag>,
+    mut query: Query<(&mut PhysObj, Option<&mut GravitySuppressed>)>,
+) {
+    let (SlingshotDrag::Aiming { entity, .. } | SlingshotDrag::Released { entity, .. }) = *drag
+    else {
+        return;
+    };
+    let Ok((mut phys_obj, suppressed)) = query.get_mut(entity) else {
+        *drag = SlingshotDrag::Idle;
+        return;
+    };
+    let aiming = match *drag {
+        SlingshotDrag::Released { drag: released, .. } => {
+            if released.length() >= config.min_drag {
+                let mass = phys_obj.mass;
+                phys_obj.vel += config.impulse(released) / mass;
+            }
+            *drag = SlingshotDrag::Idle;
+            false
+        }
+        _ => {
+            // Including what the last step left to integrate
+            phys_obj.vel = Vec2::ZERO;
+            phys_obj.acc = Vec2::ZERO;
+            phys_obj.angular_vel = 0.0;
+            phys_obj.angular_acc = 0.0;
+            true
+        }
+    };
+    match suppressed {
+        Some(mut suppressed) => suppressed.0 = aiming,
+        None => {
+            commands.entity(entity).insert(GravitySuppressed(aiming));
+        }
+    }
+}
+
+// A band from the ball to the cursor, and dots the way the ball will be launched
+fn slingshot_aim_system(
+    config: Res<SlingshotConfig>,
+    drag: Res<SlingshotDrag>,
+    mut lines: ResMut<DebugLines>,
+    transforms: Query<&Transform>,
+) {
+    let SlingshotDrag::Aiming { entity, drag } = *drag else {
+        return;
+    };
+    let Ok(transform) = transforms.get(entity) else {
+        return;
+    };
+    let center = transform.translation.truncate();
+    let aim = config.impulse(drag) / config.max_impulse * AIM_LENGTH;
+    lines.line(center, center + drag, AIM_COLOR);
+    if drag.length() >= config.min_drag {
+        lines.dotted_line_strip(&[center, center + aim], AIM_COLOR);
+    }
+}
@@ -0,0 +1,210 @@
+use bevy::{asset::LoadState, prelude::*, window::WindowFocused};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{InputAction, Settings};
+
+// The physics only runs while Playing
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AppState {
+    // Waiting for the assets in AssetLoadState
+    #[default]
+    Loading,
+    // A required asset failed to load, see AssetLoadState::error
+    AssetError,
+    // Shown after loading when there's a MenuPlugin. The level is only spawned once play starts.
+    MainMenu,
+    // Reached from the main and pause menus, and goes back to the one it came from
+    SettingsMenu,
+    Playing,
+    // Escape or the pause key (P by default) pauses. Everything but the physics keeps running, and
+    // the step key (N by default) steps the simulation.
+    Paused,
+    LevelComplete,
+}
+
+// Assets that have to finish loading before the game starts
+#[derive(Resource, Default)]
+pub struct AssetLoadState {
+    pending: Vec<HandleUntyped>,
+    pub error: Option<AssetLoadError>,
+}
+
+impl AssetLoadState {
+    pub fn track(&mut self, handle: HandleUntyped) {
+        self.pending.push(handle);
+    }
+}
+
+// The state the game goes to once everything has loaded
+#[derive(Resource)]
+pub struct AfterLoading(pub AppState);
+
+impl Default for AfterLoading {
+    fn default() -> Self {
+        Self(AppState::Playing)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetLoadError {
+    pub path: String,
+    pub reason: String,
+}
+
+pub struct AppStatePlugin;
+
+impl Plugin for AppStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AppState>()
+            .init_resource::<Settings>()
+            .init_resource::<AssetLoadState>()
+            .init_resource::<AfterLoading>()
+            .add_system(loading_system.run_if(in_state(AppState::Loading)))
+            .add_system(pause_system);
+    }
+}
+
+// Headless apps have no asset server, so there's nothing to wait for
+fn loading_system(
+    asset_server: Option<Res<AssetServer>>,
+    after_loading: Res<AfterLoading>,
+    mut load_state: ResMut<AssetLoadState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(asset_server) = asset_server else {
+        next_state.set(after_loading.0);
+        return;
+    };
+
+    let mut loaded = true;
+    for handle in &load_state.pending {
+        let reason = match asset_server.get_load_state(handle) {
+            LoadState::Loaded => continue,
+            LoadState::NotLoaded | LoadState::Loading => {
+                loaded = false;
+                continue;
+            }
+            // The asset server logs the details
+            LoadState::Failed => "failed to load",
+            LoadState::Unloaded => "was unloaded before it finished loading",
+        };
+        let path = asset_server.get_handle_path(handle).map_or_else(
+            || "<unknown>".to_string(),
+            |path| path.path().display().to_string(),
+        );
+        error!("Required asset {path} {reason}");
+        load_state.error = Some(AssetLoadError {
+            path,
+            reason: reason.to_string(),
+        });
+        next_state.set(AppState::AssetError);
+        return;
+    }
+
+    if loaded {
+        load_state.pending.clear();
+        next_state.set(after_loading.0);
+    }
+}
+
+// The rest of the pause screen is the MenuPlugin's pause menu
+fn pause_system(
+    input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !input.just_pressed(KeyCode::Escape)
+        && !settings.input.just_pressed(&input, InputAction::Pause)
+    {
+        return;
+    }
+    match state.0 {
+        AppState::Playing => next_state.set(AppState::Paused),
+        AppState::Paused => next_state.set(AppState::Playing),
+        _ => {}
+    }
+}
+
+// Pauses the game when its window loses focus, e.g. when the browser tab is hidden. Keys are
+// released, as the window won't hear about them being let go in the meantime.
+pub struct FocusPausePlugin;
+
+impl Plugin for FocusPausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_system(focus_pause_system.before(pause_system));
+    }
+}
+
+fn focus_pause_system(
+    mut focus_events: EventReader<WindowFocused>,
+    settings: Res<Settings>,
+    mut keys: ResMut<Input<KeyCode>>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    // Whether the current pause was caused by losing focus
+    mut paused_by_focus: Local<bool>,
+) {
+    let Some(focus) = focus_events.iter().last() else {
+        return;
+    };
+
+    if !focus.focused {
+        keys.reset_all();
+        if state.0 == AppState::Playing {
+            next_state.set(AppState::Paused);
+            *paused_by_focus = true;
+        }
+    } else if *paused_by_focus {
+        *paused_by_focus = false;
+        if settings.resume_on_focus && state.0 == AppState::Paused {
+            next_state.set(AppState::Playing);
+        }
+    }
+}
+
+// Text shown when loading failed. The level complete screen is a menu (see MenuPlugin).
+pub struct StateScreensPlugin;
+
+impl Plugin for StateScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(state_screen_setup.in_schedule(OnEnter(AppState::AssetError)));
+    }
+}
+
+fn state_screen_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    load_state: Res<AssetLoadState>,
+    state: Res<State<AppState>>,
+) {
+    let text = match (state.0, &load_state.error) {
+        (AppState::AssetError, Some(error)) => {
+            format!("Couldn't start the game:\n{} {}", error.path, error.reason)
+        }
+        _ => return,
+    };
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+        font_size: 32.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::width(Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(text, style).with_text_alignment(TextAlignment::Center),
+            );
+        });
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    physics::{Collider, PhysicsSchedule, PhysicsSet, PhysicsTime},
+    player::{PlayerBall, PlayerInput},
+};
+
+// The player's torque is multiplied by this with a SpeedBoost
+pub const SPEED_BOOST_TORQUE: f32 = 1.5;
+// The player's friction with the floor is multiplied by this with LowFriction
+pub const LOW_FRICTION_SCALE: f32 = 0.2;
+
+// Timed effects on the player, given by sensors in the level. Only the simulated parts; the
+// player systems and friction check a body's StatusEffects for what they change, VisualsPlugin
+// makes the sensors visible and the game HUD lists the effects.
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (status_tick_system, status_sensor_system)
+                .chain()
+                .in_set(PhysicsSet::PostSolve)
+                .in_schedule(PhysicsSchedule),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusEffect {
+    // Spin left and spin right swap
+    InvertedControls,
+    // See LOW_FRICTION_SCALE
+    LowFriction,
+    // Jumping does nothing
+    NoJump,
+    // See SPEED_BOOST_TORQUE
+    SpeedBoost,
+}
+
+impl StatusEffect {
+    pub fn label(self) -> &'static str {
+        match self {
+            StatusEffect::InvertedControls => "Inverted",
+            StatusEffect::LowFriction => "Slippery",
+            StatusEffect::NoJump => "No jump",
+            StatusEffect::SpeedBoost => "Boost",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            StatusEffect::InvertedControls => Color::FUCHSIA,
+            StatusEffect::LowFriction => Color::rgb(0.7, 0.9, 1.0),
+            StatusEffect::NoJump => Color::GRAY,
+            StatusEffect::SpeedBoost => Color::LIME_GREEN,
+        }
+    }
+}
+
+// The effects a body is under, with the seconds each has left. Getting an effect it's already
+// under doesn't stack: it just lasts as long as the longer of the two.
+#[derive(Component, Clone, Default, Debug)]
+pub struct StatusEffects {
+    effects: Vec<(StatusEffect, f32)>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect, duration: f32) {
+        match self.effects.iter_mut().find(|(other, _)| *other == effect) {
+            Some((_, remaining)) => *remaining = remaining.max(duration),
+            None => self.effects.push((effect, duration)),
+        }
+    }
+
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.remaining(effect).is_some()
+    }
+
+    pub fn remaining(&self, effect: StatusEffect) -> Option<f32> {
+        self.effects
+            .iter()
+            .find(|(other, _)| *other == effect)
+            .map(|&(_, remaining)| remaining)
+    }
+
+    // In the order they were first applied
+    pub fn iter(&self) -> impl Iterator<Item = (StatusEffect, f32)> + '_ {
+        self.effects.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    // Counts the effects down by `dt`, and drops the ones that have run out
+    pub fn tick(&mut self, dt: f32) {
+        for (_, remaining) in &mut self.effects {
+            *remaining -= dt;
+        }
+        self.effects.retain(|&(_, remaining)| remaining > 0.0);
+    }
+
+    // Which way the player's asking to spin, left then right
+    pub fn spin_input(effects: Option<&Self>, input: &PlayerInput) -> (bool, bool) {
+        if effects.is_some_and(|effects| effects.has(StatusEffect::InvertedControls)) {
+            (input.spin_right, input.spin_left)
+        } else {
+            (input.spin_left, input.spin_right)
+        }
+    }
+
+    pub fn torque_scale(effects: Option<&Self>) -> f32 {
+        match effects {
+            Some(effects) if effects.has(StatusEffect::SpeedBoost) => SPEED_BOOST_TORQUE,
+            _ => 1.0,
+        }
+    }
+
+    pub fn friction_scale(effects: Option<&Self>) -> f32 {
+        match effects {
+            Some(effects) if effects.has(StatusEffect::LowFriction) => LOW_FRICTION_SCALE,
+            _ => 1.0,
+        }
+    }
+
+    pub fn can_jump(effects: Option<&Self>) -> bool {
+        !effects.is_some_and(|effects| effects.has(StatusEffect::NoJump))
+    }
+}
+
+// A sensor that gives the player `effect` for `duration` seconds while they're within `radius` of
+// it. Pickups disappear once they're touched; hazards stay, and keep the effect going while the
+// player stays in them.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StatusSensor {
+    pub effect: StatusEffect,
+    pub duration: f32,
+    pub radius: f32,
+    pub pickup: bool,
+}
+
+fn status_tick_system(time: Res<PhysicsTime>, mut query: Query<&mut StatusEffects>) {
+    for mut effects in &mut query {
+        if !effects.is_empty() {
+            effects.tick(time.delta);
+        }
+    }
+}
+
+fn status_sensor_system(
+    mut commands: Commands,
+    sensors: Query<(Entity, &Transform, &StatusSensor)>,
+    mut players: Query<(Entity, &Transform, &Collider, Option<&mut StatusEffects>), PlayerBall>,
+) {
+    for (player, player_transform, &Collider::Ball { radius, .. }, effects) in &mut players {
+        let position = player_transform.translation.truncate();
+        let touching: Vec<_> = sensors
+            .iter()
+            .filter(|(_, transform, sensor)| {
+                position.distance(transform.translation.truncate()) < radius + sensor.radius
+            })
+            .collect();
+        if touching.is_empty() {
+            continue;
+        }
+
+        let mut added = StatusEffects::default();
+        let effects = match effects {
+            Some(effects) => effects.into_inner(),
+            None => &mut added,
+        };
+        for (sensor_entity, _, sensor) in touching {
+            effects.apply(sensor.effect, sensor.duration);
+            if sensor.pickup {
+                commands.entity(sensor_entity).despawn_recursive();
+            }
+        }
+        if !added.is_empty() {
+            commands.entity(player).insert(added);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::{
+    level::{surface_below, Floor, SurfaceMaterial},
+    physics::{
+        sleep::Sleeping, Collider, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsTime,
+    },
+    player::JumpEvent,
+};
+
+// A ball let go of comes off at this speed on top of what pulled it off, so that it's clear of
+// the surface straight away
+pub const STICKY_POP_SPEED: f32 = 60.0;
+
+// Balls touching a SurfaceMaterial::Sticky surface stay on it: they don't bounce off, and pulling
+// away takes more than the surface's strength for longer than a frame. Jumping always lets go.
+// Letting go pops the ball off the surface, and it's no longer touching it.
+//
+// The floor is the only surface the physics has, so it's the only thing to stick to, but the
+// holding works along any surface normal (see holding_force).
+pub struct StickyPlugin;
+
+impl Plugin for StickyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<JumpEvent>().add_system(
+            sticky_system
+                .after(PhysicsSet::ResolveCollisions)
+                .before(PhysicsSet::SolveConstraints)
+                .in_schedule(PhysicsSchedule),
+        );
+    }
+}
+
+// A ball touching a sticky surface, until it leaves it
+#[derive(Component, Clone, Copy, Default)]
+pub struct Stuck {
+    // Physics steps in a row it's taken more than the surface's strength to hold on
+    pub over_steps: u32,
+    // Let go by a jump or by pulling too hard. The surface doesn't hold on again until the ball
+    // has left it.
+    pub released: bool,
+}
+
+// Force (along `normal`, the unit normal of the surface pointing towards the body) that keeps a
+// body of `mass` on the surface through the next step of `dt`: what cancels its acceleration
+// away from the surface, and stops it moving away.
+pub fn holding_force(normal: Vec2, mass: f32, vel: Vec2, acc: Vec2, dt: f32) -> f32 {
+    mass * (acc.dot(normal).max(0.0) + vel.dot(normal).max(0.0) / dt)
+}
+
+#[allow(clippy::type_complexity)]
+fn sticky_system(
+    mut commands: Commands,
+    time: Res<PhysicsTime>,
+    config: Res<PhysicsConfig>,
+    mut jumps: EventReader<JumpEvent>,
+    floors: Query<(&Transform, &Floor, Option<&SurfaceMaterial>), Without<PhysObj>>,
+    mut balls: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut PhysObj,
+            &mut Collider,
+            Option<&mut Stuck>,
+        ),
+        Without<Sleeping>,
+    >,
+) {
+    let jumped: Vec<Entity> = jumps.iter().map(|jump| jump.entity).collect();
+    let dt = time.delta;
+    let normal = Vec2::Y;
+    for (entity, mut transform, mut phys_obj, mut collider, stuck) in &mut balls {
+        let Collider::Ball {
+            radius,
+            touching_ground,
+            ..
+        } = &mut *collider;
+        let position = transform.translation.truncate();
+        // Including balls that have just bounced off it this step
+        let touching =
+            *touching_ground || position.y - *radius <= config.floor_y + config.penetration_slop;
+        let strength = match surface_below(position, &floors) {
+            Some(SurfaceMaterial::Sticky { strength }) if touching => strength,
+            _ => {
+                if stuck.is_some() {
+                    commands.entity(entity).remove::<Stuck>();
+                }
+                continue;
+            }
+        };
+
+        let mut state = stuck.as_deref().copied().unwrap_or_default();
+        let was_released = state.released;
+        if jumped.contains(&entity) {
+            state.released = true;
+        }
+        if !state.released {
+            let force = holding_force(normal, phys_obj.mass, phys_obj.vel, phys_obj.acc, dt);
+            if force > strength {
+                state.over_steps += 1;
+            } else {
+                state.over_steps = 0;
+            }
+            // Landing is a single step over, so a frame's grace keeps balls from bouncing off
+            state.released = state.over_steps > config.substeps.max(1);
+        }
+        if !state.released {
+            let away_vel = phys_obj.vel.dot(normal).max(0.0);
+            let away_acc = phys_obj.acc.dot(normal).max(0.0);
+            phys_obj.vel -= away_vel * normal;
+            phys_obj.acc -= away_acc * normal;
+            // Back where it touched, if it bounced
+            transform.translation.y = transform.translation.y.min(config.floor_y + *radius);
+            // So the player can jump off it, whichever way it faces
+            *touching_ground = true;
+        } else if !was_released {
+            phys_obj.vel += STICKY_POP_SPEED * normal;
+            *touching_ground = false;
+        }
+
+        match stuck {
+            Some(mut stuck) => *stuck = state,
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+// Where settings, progress, saves and replays are kept. Defaults to files natively and local
+// storage on WASM; insert another one before adding the plugins that use it (e.g. a
+// MemoryBackend in tests).
+#[derive(Resource, Deref, DerefMut)]
+pub struct Storage(pub Box<dyn StorageBackend>);
+
+impl Default for Storage {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        Self(Box::new(FileBackend::new(".")))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        Self(Box::new(LocalStorageBackend))
+    }
+}
+
+// Small named blobs that outlive the session
+pub trait StorageBackend: Send + Sync {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&mut self, key: &str, value: &str) -> Result<(), StorageError>;
+    fn remove(&mut self, key: &str) -> Result<(), StorageError>;
+}
+
+// Stores `value` as RON under `key`
+pub fn save_ron<T: Serialize>(
+    storage: &mut dyn StorageBackend,
+    key: &str,
+    value: &T,
+) -> Result<(), StorageError> {
+    let value = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| StorageError::Other(error.to_string()))?;
+    storage.set(key, &value)
+}
+
+// The value stored under `key`, if there's one. Err if it isn't valid RON for a T.
+pub fn load_ron<T: DeserializeOwned>(
+    storage: &dyn StorageBackend,
+    key: &str,
+) -> Option<Result<T, ron::error::SpannedError>> {
+    storage.get(key).map(|value| ron::from_str(&value))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    QuotaExceeded,
+    PermissionDenied,
+    Unavailable,
+    Other(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::QuotaExceeded => write!(f, "out of storage space"),
+            StorageError::PermissionDenied => write!(f, "not allowed to write"),
+            StorageError::Unavailable => write!(f, "storage is unavailable"),
+            StorageError::Other(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<std::io::Error> for StorageError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::PermissionDenied => StorageError::PermissionDenied,
+            _ => StorageError::Other(error.to_string()),
+        }
+    }
+}
+
+// `<key>.ron` files in a directory. Writes go to a temporary file that then replaces the old one,
+// so an interrupted write leaves the previous contents intact.
+pub struct FileBackend {
+    pub dir: PathBuf,
+}
+
+impl FileBackend {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.ron"))
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(self.path(key)).ok()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), StorageError> {
+        let path = self.path(key);
+        let temp_path = path.with_extension("ron.tmp");
+        std::fs::write(&temp_path, value)?;
+        std::fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        match std::fs::remove_file(self.path(key)) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// The browser's local storage, as there's no file system on WASM
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorageBackend;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorageBackend {
+    fn storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or(StorageError::Unavailable)
+    }
+
+    fn error(error: wasm_bindgen::JsValue) -> StorageError {
+        use wasm_bindgen::JsCast;
+
+        match error.dyn_ref::<js_sys::Error>() {
+            Some(error) if error.name() == "QuotaExceededError" => StorageError::QuotaExceeded,
+            Some(error) if error.name() == "SecurityError" => StorageError::PermissionDenied,
+            _ => StorageError::Other(format!("{error:?}")),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl StorageBackend for LocalStorageBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        Self::storage().ok()?.get_item(key).ok().flatten()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), StorageError> {
+        Self::storage()?.set_item(key, value).map_err(Self::error)
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        Self::storage()?.remove_item(key).map_err(Self::error)
+    }
+}
+
+// Keeps everything in memory, e.g. for tests and headless runs
+#[derive(Default)]
+pub struct MemoryBackend {
+    pub entries: HashMap<String, String>,
+}
+
+impl StorageBackend for MemoryBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.entries.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.entries.remove(key);
+        Ok(())
+    }
+}
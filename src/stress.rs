@@ -0,0 +1,126 @@
+use bevy::{prelude::*, utils::Duration};
+
+use crate::{
+    level::{spawn_ball, BallEntry},
+    physics::{timings::PhysicsTimings, PhysicsConfig, PhysicsStep},
+    state::AppState,
+};
+
+pub const DEFAULT_STRESS_BALLS: usize = 1000;
+const STRESS_BALL_RADIUS: f32 = 8.0;
+const STRESS_BALL_SPACING: f32 = 2.5 * STRESS_BALL_RADIUS;
+// Height of the grid's bottom row above the floor
+const STRESS_GRID_HEIGHT: f32 = 100.0;
+
+// A scene for profiling the physics: keeps `count` dynamic balls in a grid above the floor while
+// playing, and shows how long each phase of the physics took. Enabled with `--stress [N]`, or
+// `?stress=N` on WASM (see LaunchOptions).
+pub struct StressPlugin {
+    pub count: usize,
+}
+
+impl Plugin for StressPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StressScene { count: self.count })
+            .init_resource::<PhysicsTimings>()
+            .add_startup_system(stress_setup)
+            .add_system(stress_spawn_system.in_set(OnUpdate(AppState::Playing)))
+            .add_system(stress_timings_text_system.after(PhysicsStep));
+    }
+}
+
+#[derive(Resource)]
+pub struct StressScene {
+    pub count: usize,
+}
+
+#[derive(Component)]
+pub struct StressBall;
+
+#[derive(Component)]
+struct StressTimingsText;
+
+// Where ball `i` of `count` starts: rows centered on x = 0, filled from the bottom up
+pub fn stress_ball_position(i: usize, count: usize, floor_y: f32) -> Vec2 {
+    let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+    let (row, column) = (i / columns, i % columns);
+    Vec2::new(
+        (column as f32 - 0.5 * (columns - 1) as f32) * STRESS_BALL_SPACING,
+        floor_y + STRESS_GRID_HEIGHT + row as f32 * STRESS_BALL_SPACING,
+    )
+}
+
+// Headless apps have no asset server, and no text to show the timings in
+fn stress_setup(
+    mut commands: Commands,
+    asset_server: Option<Res<AssetServer>>,
+    mut timings: ResMut<PhysicsTimings>,
+) {
+    timings.enabled = true;
+    let Some(asset_server) = asset_server else {
+        return;
+    };
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+        font_size: 20.0,
+        color: Color::YELLOW,
+    };
+    commands.spawn((
+        TextBundle::from_section("", style).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        }),
+        StressTimingsText,
+    ));
+}
+
+// Spawns the balls when play starts, and again after a restart despawned them with the level
+fn stress_spawn_system(
+    mut commands: Commands,
+    scene: Res<StressScene>,
+    config: Res<PhysicsConfig>,
+    existing: Query<(), With<StressBall>>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+    for i in 0..scene.count {
+        let ball = BallEntry {
+            position: stress_ball_position(i, scene.count, config.floor_y),
+            radius: STRESS_BALL_RADIUS,
+            mass: 1.0,
+            coef_of_restitution: 0.5,
+            kinetic_friction: 0.5,
+            com_offset: Vec2::ZERO,
+            player: false,
+        };
+        let entity = spawn_ball(&mut commands, &config, &ball);
+        commands.entity(entity).insert(StressBall);
+    }
+}
+
+fn stress_timings_text_system(
+    scene: Res<StressScene>,
+    timings: Res<PhysicsTimings>,
+    mut texts: Query<&mut Text, With<StressTimingsText>>,
+) {
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    for mut text in &mut texts {
+        text.sections[0].value = format!(
+            "{} balls\nphysics {:.2} ms\nintegrator {:.2} ms\nforces {:.2} ms\n\
+             broad phase {:.2} ms\nnarrow phase {:.2} ms\nfriction {:.2} ms",
+            scene.count,
+            ms(timings.total()),
+            ms(timings.integrator),
+            ms(timings.forces),
+            ms(timings.broad_phase),
+            ms(timings.narrow_phase),
+            ms(timings.friction),
+        );
+    }
+}
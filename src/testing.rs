@@ -0,0 +1,134 @@
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState, InputPlugin},
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+    utils::{Duration, Instant},
+};
+
+use crate::{
+    physics::{disk_moment_of_inertia, Collider, Gravity, PhysObj, PhysicsConfig, PhysicsPlugin},
+    player::{Dash, Player, PlayerPlugin},
+    state::AppStatePlugin,
+};
+
+// Length of a frame in apps made by `test_app`
+pub const TEST_DT: f32 = 1.0 / 60.0;
+// Mass of the balls spawned by `spawn_test_ball`
+pub const TEST_BALL_MASS: f32 = 10.0;
+
+// A headless App running the simulation, for tests and CI. Every `app.update()` advances time by
+// exactly TEST_DT, so runs are deterministic. Keys are pressed with press_key. Nothing is spawned;
+// tests set up the bodies they need, e.g. with spawn_test_ball. With nothing to load, the physics
+// starts running on the second update.
+pub fn test_app() -> App {
+    let physics = PhysicsPlugin::default();
+    // The tests are written against the native solver
+    #[cfg(feature = "rapier-backend")]
+    let physics = physics.with_backend(crate::physics::PhysicsBackend::Native);
+    test_app_with(physics)
+}
+
+// A `test_app` with its own PhysicsPlugin, e.g. one with another config or backend
+pub fn test_app_with(physics: PhysicsPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugin(InputPlugin);
+    add_test_clock(&mut app)
+        .add_plugin(AppStatePlugin)
+        .add_plugin(physics)
+        .add_plugin(PlayerPlugin);
+    app
+}
+
+// Makes every update of `app` advance its Time by exactly TEST_DT. Bevy's ManualDuration strategy
+// adds the duration to the real time of the update, so it would still depend on how long the
+// updates really took.
+pub fn add_test_clock(app: &mut App) -> &mut App {
+    app.insert_resource(TimeUpdateStrategy::ManualInstant(Instant::now()))
+        .add_system(
+            advance_test_clock
+                .in_base_set(CoreSet::First)
+                .before(TimeSystem),
+        )
+}
+
+fn advance_test_clock(mut strategy: ResMut<TimeUpdateStrategy>) {
+    if let TimeUpdateStrategy::ManualInstant(instant) = &mut *strategy {
+        *instant += Duration::from_secs_f32(TEST_DT);
+    }
+}
+
+// Presses or releases `key` in the next update, the way a keyboard does. Pressing it on the
+// Input<KeyCode> resource would keep it held, but InputPlugin clears just_pressed and
+// just_released before any system of the update gets to see them.
+pub fn press_key(app: &mut App, key: KeyCode, pressed: bool) {
+    app.world.send_event(KeyboardInput {
+        scan_code: 0,
+        key_code: Some(key),
+        state: if pressed {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Released
+        },
+    });
+}
+
+// Spawns a ball at `position` with gravity, no bounce and the usual friction with the floor. Like
+// in a level, a ball placed on the floor starts out resting there. Tests change whatever else they
+// need on its PhysObj and Collider.
+pub fn spawn_test_ball(app: &mut App, position: Vec2, radius: f32) -> Entity {
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    app.world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            PhysObj {
+                mass: TEST_BALL_MASS,
+                vel: Vec2::ZERO,
+                acc: Vec2::ZERO,
+                acc_prev: Vec2::ZERO,
+                moment_of_inertia: disk_moment_of_inertia(TEST_BALL_MASS, radius),
+                angular_vel: 0.0,
+                angular_acc: 0.0,
+                angular_acc_prev: 0.0,
+                com_offset: Vec2::ZERO,
+            },
+            Gravity(config.gravity),
+            Collider::Ball {
+                radius,
+                coef_of_restitution: 0.0,
+                touching_ground: position.y - radius <= config.floor_y
+                    && !config.over_gap(position.x),
+                kinetic_friction: 0.5,
+                friction_acc: 0.0,
+                friction_acc_prev: 0.0,
+            },
+        ))
+        .id()
+}
+
+// Spawns a ball like spawn_test_ball, as the player a level would spawn there, with the default
+// Player and Dash. Tests change whatever else they need on those.
+pub fn spawn_test_player(app: &mut App, position: Vec2, radius: f32) -> Entity {
+    let player = spawn_test_ball(app, position, radius);
+    app.world
+        .entity_mut(player)
+        .insert((Player::default(), Dash::default()));
+    player
+}
+
+// Sets the coefficient of restitution of a ball from spawn_test_ball
+pub fn set_test_ball_restitution(app: &mut App, ball: Entity, restitution: f32) {
+    let mut collider = app.world.get_mut::<Collider>(ball).unwrap();
+    let Collider::Ball {
+        coef_of_restitution,
+        ..
+    } = &mut *collider;
+    *coef_of_restitution = restitution;
+}
+
+// Sets the mass of a ball from spawn_test_ball, and its moment of inertia to match
+pub fn set_test_ball_mass(app: &mut App, ball: Entity, mass: f32) {
+    let Collider::Ball { radius, .. } = *app.world.get::<Collider>(ball).unwrap();
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.mass = mass;
+    phys_obj.moment_of_inertia = disk_moment_of_inertia(mass, radius);
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+#[cfg(debug_assertions)]
+use crate::physics::anomaly::PhysicsAnomaly;
+
+// How long a toast stays on screen
+const TOAST_DURATION: f32 = 2.0;
+// Toasts fade out over the end of their duration
+const TOAST_FADE_TIME: f32 = 0.5;
+// More toasts than this push the oldest off the screen early
+const MAX_TOASTS: usize = 4;
+
+// Shows ToastEvents as short-lived messages at the bottom of the screen. Newer ones stack on top
+// of those still showing.
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToastEvent>()
+            .add_startup_system(toast_setup)
+            .add_systems((toast_queue_system, toast_render_system).chain());
+        #[cfg(debug_assertions)]
+        app.add_system(anomaly_toast_system.before(toast_queue_system));
+    }
+}
+
+// A message for the player, e.g. "Saved screenshot_1700000000.png"
+pub struct ToastEvent(pub String);
+
+// The toasts on screen, oldest first
+#[derive(Component, Default)]
+pub struct ToastQueue {
+    pub toasts: VecDeque<Toast>,
+}
+
+pub struct Toast {
+    pub text: String,
+    pub remaining: f32,
+}
+
+impl ToastQueue {
+    pub fn push(&mut self, text: String) {
+        self.toasts.push_back(Toast {
+            text,
+            remaining: TOAST_DURATION,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    // Returns whether any toasts expired
+    pub fn tick(&mut self, dt: f32) -> bool {
+        let count = self.toasts.len();
+        for toast in &mut self.toasts {
+            toast.remaining -= dt;
+        }
+        self.toasts.retain(|toast| toast.remaining > 0.0);
+        self.toasts.len() != count
+    }
+}
+
+impl Toast {
+    pub fn alpha(&self) -> f32 {
+        (self.remaining / TOAST_FADE_TIME).clamp(0.0, 1.0)
+    }
+}
+
+// Physics anomalies are only logged otherwise, which is easy to miss while playing
+#[cfg(debug_assertions)]
+fn anomaly_toast_system(
+    mut anomalies: EventReader<PhysicsAnomaly>,
+    mut toasts: EventWriter<ToastEvent>,
+) {
+    if let Some(anomaly) = anomalies.iter().last() {
+        toasts.send(ToastEvent(format!("Physics anomaly: {anomaly:?}")));
+    }
+}
+
+fn toast_setup(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                // Newest at the top
+                flex_direction: FlexDirection::ColumnReverse,
+                gap: Size::all(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        },
+        ToastQueue::default(),
+    ));
+}
+
+fn toast_queue_system(
+    time: Res<Time>,
+    mut events: EventReader<ToastEvent>,
+    mut queues: Query<&mut ToastQueue>,
+) {
+    let Ok(mut queue) = queues.get_single_mut() else {
+        return;
+    };
+    // Only marked as changed when toasts come or go, not every time they tick
+    let expired = queue.bypass_change_detection().tick(time.delta_seconds());
+    if expired {
+        queue.set_changed();
+    }
+    for ToastEvent(text) in events.iter() {
+        queue.push(text.clone());
+    }
+}
+
+// The text entities are rebuilt when the toasts change, and only faded otherwise
+fn toast_render_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    queues: Query<(Entity, Ref<ToastQueue>, Option<&Children>)>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok((entity, queue, children)) = queues.get_single() else {
+        return;
+    };
+
+    if queue.is_changed() {
+        let style = TextStyle {
+            font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+            font_size: 20.0,
+            color: Color::WHITE,
+        };
+        commands.entity(entity).despawn_descendants();
+        commands.entity(entity).with_children(|parent| {
+            for toast in &queue.toasts {
+                parent.spawn(TextBundle::from_section(toast.text.clone(), style.clone()));
+            }
+        });
+        return;
+    }
+
+    let children = children.map_or(&[][..], |children| &children[..]);
+    for (toast, &child) in queue.toasts.iter().zip(children) {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.sections[0].style.color.set_a(toast.alpha());
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    elevator::{Counterweight, Elevator},
+    level::{
+        level_restart_system, spawn_level, BallEntry, Level, LevelName, PickupEntry,
+        RestartLevelEvent, SawEntry, SensorEntry,
+    },
+    physics::{
+        collision::{resolve_contact, ContactPoint},
+        Collider, Gravity, PhysObj, PhysicsConfig, PhysicsSchedule, PhysicsSet, PhysicsStep,
+    },
+    player::PlayerBall,
+    progress::{level_timer_system, LevelTimer, Score},
+    state::AppState,
+    toast::ToastEvent,
+};
+
+pub const COIN_RADIUS: f32 = 10.0;
+pub const DOOR_THICKNESS: f32 = 20.0;
+
+// Runs the Level's triggers: when something happens in the level (a player entering a named
+// TriggerZone, enough coins collected, enough time gone by), do something to it (open a named
+// Door, move a named elevator, flip gravity...). Each trigger fires once per attempt. Also the
+// doors and coins triggers work with. Only the simulated parts; VisualsPlugin makes them visible.
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Level>()
+            .init_resource::<TriggerState>()
+            .init_resource::<LevelTimer>()
+            .init_resource::<Score>()
+            .add_event::<SensorEvent>()
+            .add_event::<PlaySoundEvent>()
+            .add_event::<ToastEvent>()
+            .add_event::<RestartLevelEvent>()
+            .add_system(
+                door_contact_system
+                    .after(PhysicsSet::ResolveCollisions)
+                    .before(PhysicsSet::SolveConstraints)
+                    .in_schedule(PhysicsSchedule),
+            )
+            .add_systems(
+                (coin_system, trigger_zone_system, trigger_system)
+                    .chain()
+                    .after(PhysicsStep)
+                    .after(level_timer_system)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(
+                trigger_reset_system
+                    .before(level_restart_system)
+                    .run_if(on_event::<RestartLevelEvent>()),
+            )
+            .add_system(trigger_reset_system.in_schedule(OnEnter(AppState::MainMenu)));
+    }
+}
+
+// A player's ball went in or out of the TriggerZone with this name
+#[derive(Clone, Debug, PartialEq)]
+pub enum SensorEvent {
+    Entered(String),
+    Exited(String),
+}
+
+// A PlaySound action, for the audio to play the sound named by (see Sound::named)
+pub struct PlaySoundEvent(pub String);
+
+// A trigger of the level: once `when` is met, everything in `do` is done in order
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TriggerEntry {
+    pub when: TriggerCondition,
+    #[serde(rename = "do")]
+    pub actions: Vec<TriggerAction>,
+}
+
+impl TriggerEntry {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.when {
+            TriggerCondition::SensorEntered(name) | TriggerCondition::SensorExited(name)
+                if name.is_empty() =>
+            {
+                return Err("its sensor has no name".to_string());
+            }
+            &TriggerCondition::TimerExceeds(seconds) if !seconds.is_finite() => {
+                return Err(format!("time {seconds} isn't finite"));
+            }
+            _ => {}
+        }
+        for action in &self.actions {
+            match action {
+                &TriggerAction::MovePlatformTo(_, height) if !height.is_finite() => {
+                    return Err(format!("platform height {height} isn't finite"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TriggerCondition {
+    // A player entered or left the TriggerZone with this name
+    SensorEntered(String),
+    SensorExited(String),
+    // The Score, which each coin adds a point to, is at least this
+    CoinsCollectedAtLeast(u32),
+    // The level's been played for longer than this many seconds
+    TimerExceeds(f32),
+}
+
+impl TriggerCondition {
+    // Whether it's met with these sensor events this frame, at this score and level time
+    pub fn is_met(&self, events: &[&SensorEvent], score: u32, elapsed: f32) -> bool {
+        match self {
+            TriggerCondition::SensorEntered(name) => events
+                .iter()
+                .any(|event| matches!(event, SensorEvent::Entered(entered) if entered == name)),
+            TriggerCondition::SensorExited(name) => events
+                .iter()
+                .any(|event| matches!(event, SensorEvent::Exited(exited) if exited == name)),
+            &TriggerCondition::CoinsCollectedAtLeast(count) => score >= count,
+            &TriggerCondition::TimerExceeds(seconds) => elapsed > seconds,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum TriggerAction {
+    // Opens every Door with this name for the rest of the attempt
+    OpenDoor(String),
+    // Spawns something, as the level would have
+    SpawnEntity(EntityTemplate),
+    // Moves the travel of every elevator with this name so its top is at this height. It gets
+    // there at its speed, carrying what's on it. Counterweight pairs stay where they are.
+    MovePlatformTo(String, f32),
+    // Turns gravity upside down, or back
+    GravityFlip,
+    CompleteLevel,
+    // A sound by its name, e.g. "boing"
+    PlaySound(String),
+    ShowToast(String),
+}
+
+// What a SpawnEntity action spawns, each just like its entry in the Level
+#[derive(Clone, Serialize, Deserialize)]
+pub enum EntityTemplate {
+    Ball(BallEntry),
+    Pickup(PickupEntry),
+    Sensor(SensorEntry),
+    Saw(SawEntry),
+    Coin(Vec2),
+}
+
+impl EntityTemplate {
+    // A level with only this in it
+    fn level(&self) -> Level {
+        let mut level = Level::empty();
+        match self {
+            EntityTemplate::Ball(ball) => level.balls.push(ball.clone()),
+            EntityTemplate::Pickup(pickup) => level.pickups.push(pickup.clone()),
+            EntityTemplate::Sensor(sensor) => level.sensors.push(sensor.clone()),
+            EntityTemplate::Saw(saw) => level.saws.push(saw.clone()),
+            &EntityTemplate::Coin(position) => level.coins.push(position),
+        }
+        level
+    }
+}
+
+// Which of the Level's triggers have fired in this attempt, and whether gravity's been flipped by
+// one. Set up again by trigger_system when the level's triggers change.
+#[derive(Resource, Default)]
+pub struct TriggerState {
+    pub fired: Vec<bool>,
+    pub gravity_flipped: bool,
+}
+
+// A wall standing `height` tall on the floor, which balls can't get through until it's opened
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Door {
+    pub height: f32,
+    pub open: bool,
+}
+
+// A circle that sends SensorEvents as players go in and out of it. Named with a LevelName.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TriggerZone {
+    pub radius: f32,
+    // Whether a player was in it on the last frame
+    pub occupied: bool,
+}
+
+// Taken by touching it, for a point of the Score
+#[derive(Component)]
+pub struct Coin;
+
+// Balls are pushed out of closed doors on the side their center is on, and bounce off them like
+// off a wall
+fn door_contact_system(
+    config: Res<PhysicsConfig>,
+    doors: Query<(&Transform, &Door), Without<PhysObj>>,
+    mut balls: Query<(&mut Transform, &mut PhysObj, &Collider)>,
+) {
+    for (door_transform, door) in &doors {
+        if door.open {
+            continue;
+        }
+        let door_position = door_transform.translation;
+        for (
+            mut transform,
+            mut phys_obj,
+            &Collider::Ball {
+                radius,
+                coef_of_restitution,
+                kinetic_friction,
+                ..
+            },
+        ) in &mut balls
+        {
+            let position = transform.translation;
+            let offset = position.x - door_position.x;
+            let reach = radius + 0.5 * DOOR_THICKNESS;
+            if offset.abs() >= reach
+                || position.y - radius > door_position.y + door.height
+                || position.y + radius < door_position.y
+            {
+                continue;
+            }
+
+            let side = if offset < 0.0 { -1.0 } else { 1.0 };
+            transform.translation.x = door_position.x + side * reach;
+            let point = ContactPoint {
+                offset: Vec2::new(-side * radius, 0.0),
+                normal: Vec2::new(side, 0.0),
+            }
+            .with_com_offset(phys_obj.com_arm(transform.rotation));
+            // Only if they're coming together
+            let contact_vel = phys_obj.vel + phys_obj.angular_vel * point.offset.perp();
+            if contact_vel.dot(point.normal) < 0.0 {
+                resolve_contact(
+                    &mut phys_obj,
+                    &point,
+                    coef_of_restitution,
+                    config.friction(kinetic_friction),
+                );
+            }
+        }
+    }
+}
+
+fn coin_system(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    coins: Query<(Entity, &Transform), With<Coin>>,
+    players: Query<(&Transform, &Collider), PlayerBall>,
+) {
+    for (coin, coin_transform) in &coins {
+        let position = coin_transform.translation.truncate();
+        let touched = players
+            .iter()
+            .any(|(transform, &Collider::Ball { radius, .. })| {
+                position.distance(transform.translation.truncate()) < radius + COIN_RADIUS
+            });
+        if touched {
+            commands.entity(coin).despawn_recursive();
+            score.0 += 1;
+        }
+    }
+}
+
+fn trigger_zone_system(
+    mut events: EventWriter<SensorEvent>,
+    mut zones: Query<(&Transform, &mut TriggerZone, &LevelName)>,
+    players: Query<(&Transform, &Collider), PlayerBall>,
+) {
+    for (zone_transform, mut zone, name) in &mut zones {
+        let position = zone_transform.translation.truncate();
+        let occupied = players
+            .iter()
+            .any(|(transform, &Collider::Ball { radius, .. })| {
+                position.distance(transform.translation.truncate()) < radius + zone.radius
+            });
+        if occupied == zone.occupied {
+            continue;
+        }
+        zone.occupied = occupied;
+        events.send(if occupied {
+            SensorEvent::Entered(name.0.clone())
+        } else {
+            SensorEvent::Exited(name.0.clone())
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trigger_system(
+    mut commands: Commands,
+    level: Res<Level>,
+    timer: Res<LevelTimer>,
+    score: Res<Score>,
+    mut config: ResMut<PhysicsConfig>,
+    mut state: ResMut<TriggerState>,
+    mut sensor_events: EventReader<SensorEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut toasts: EventWriter<ToastEvent>,
+    mut sounds: EventWriter<PlaySoundEvent>,
+    named: Query<(Entity, &LevelName)>,
+    mut doors: Query<&mut Door>,
+    mut elevators: Query<(&mut Elevator, Option<&Counterweight>)>,
+    mut gravities: Query<&mut Gravity>,
+) {
+    // Invalid triggers never fire
+    if state.fired.len() != level.triggers.len() {
+        state.fired = level
+            .triggers
+            .iter()
+            .enumerate()
+            .map(|(i, trigger)| match trigger.validate() {
+                Ok(()) => false,
+                Err(reason) => {
+                    warn!("Skipping trigger {i} of the level: {reason}");
+                    true
+                }
+            })
+            .collect();
+    }
+
+    let events: Vec<_> = sensor_events.iter().collect();
+    let entities_named = |name: &str| -> Vec<Entity> {
+        named
+            .iter()
+            .filter(|(_, other)| other.0 == name)
+            .map(|(entity, _)| entity)
+            .collect()
+    };
+    for (i, trigger) in level.triggers.iter().enumerate() {
+        if state.fired[i] || !trigger.when.is_met(&events, score.0, timer.elapsed) {
+            continue;
+        }
+        state.fired[i] = true;
+        for action in &trigger.actions {
+            match action {
+                TriggerAction::OpenDoor(name) => {
+                    for entity in entities_named(name) {
+                        if let Ok(mut door) = doors.get_mut(entity) {
+                            door.open = true;
+                        }
+                    }
+                }
+                TriggerAction::SpawnEntity(template) => {
+                    spawn_level(&mut commands, &config, &template.level());
+                }
+                &TriggerAction::MovePlatformTo(ref name, height) => {
+                    for entity in entities_named(name) {
+                        match elevators.get_mut(entity) {
+                            Ok((mut elevator, None)) => {
+                                let travel = elevator.top - elevator.bottom;
+                                elevator.top = height;
+                                elevator.bottom = height - travel;
+                            }
+                            Ok((_, Some(_))) => {
+                                warn!("Not moving elevator {name:?}, it has a counterweight");
+                            }
+                            Err(_) => {}
+                        }
+                    }
+                }
+                TriggerAction::GravityFlip => {
+                    config.gravity = -config.gravity;
+                    state.gravity_flipped = !state.gravity_flipped;
+                    for mut gravity in &mut gravities {
+                        gravity.0 = -gravity.0;
+                    }
+                }
+                TriggerAction::CompleteLevel => next_state.set(AppState::LevelComplete),
+                TriggerAction::PlaySound(name) => sounds.send(PlaySoundEvent(name.clone())),
+                TriggerAction::ShowToast(text) => toasts.send(ToastEvent(text.clone())),
+            }
+        }
+    }
+}
+
+// Starting the level over arms its triggers again and puts gravity back, before the level's
+// respawned with it
+fn trigger_reset_system(mut config: ResMut<PhysicsConfig>, mut state: ResMut<TriggerState>) {
+    if state.gravity_flipped {
+        config.gravity = -config.gravity;
+    }
+    *state = TriggerState::default();
+}
@@ -0,0 +1,956 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use crate::{
+    boost::{BoostPad, BoostPadState, BOOST_FLASH_TIME, BOOST_PAD_SIZE},
+    crumble::{CrumbleState, Crumbling},
+    elevator::{Elevator, ELEVATOR_THICKNESS},
+    fluid::{BurnedEvent, FluidVolume, Scorching, LAVA_KILL_TIME},
+    level::{floor_below, Floor, Goal, SurfaceMaterial},
+    mesh_cache::MeshCache,
+    physics::{heightfield::Heightfield, Collider, PhysicsStep},
+    player::{
+        trajectory_prediction_system, Dash, JumpCharge, JumpChargeConfig, Player, RemotePlayer,
+        SpinHeat, TrajectoryPrediction,
+    },
+    portal::{Portal, PORTAL_WIDTH},
+    powerup::{SizePickup, PICKUP_RADIUS},
+    replay::Ghost,
+    rng::{GameRng, Rng},
+    rope::{RopeSegment, ROPE_SEGMENT_RADIUS},
+    runner::{Spikes, SPIKES_SIZE},
+    saw::SawBlade,
+    settings::Settings,
+    shapes::{FidgetSpinner, SpinnerProfile},
+    status::StatusSensor,
+    trigger::{Coin, Door, TriggerZone, COIN_RADIUS, DOOR_THICKNESS},
+};
+
+const FLOOR_THICKNESS: f32 = 40.0;
+const SHADOW_MAX_HEIGHT: f32 = 500.0;
+const SHADOW_ALPHA: f32 = 0.5;
+const BODY_COLOR: Color = Color::BLUE;
+const GHOST_COLOR: Color = Color::rgba(0.0, 0.0, 1.0, 0.3);
+const REMOTE_PLAYER_COLOR: Color = Color::rgb(0.1, 0.65, 0.3);
+const OVERHEAT_COLOR: Color = Color::rgb(1.0, 0.3, 0.1);
+const GOAL_SIZE: Vec2 = Vec2::new(8.0, 300.0);
+const GOAL_COLOR: Color = Color::GOLD;
+const PICKUP_COLOR: Color = Color::PURPLE;
+const PORTAL_THICKNESS: f32 = 8.0;
+const SAW_COLOR: Color = Color::SILVER;
+const ELEVATOR_COLOR: Color = Color::rgb(0.4, 0.45, 0.6);
+const ROPE_COLOR: Color = Color::rgb(0.6, 0.45, 0.25);
+const WATER_COLOR: Color = Color::rgba(0.2, 0.45, 0.9, 0.4);
+const LAVA_COLOR: Color = Color::rgba(1.0, 0.35, 0.0, 0.8);
+// A player in lava glows this color, more the closer it is to burning up
+const LAVA_GLOW_COLOR: Color = Color::rgb(1.0, 0.55, 0.1);
+const LAVA_GLOW_STEPS: f32 = 8.0;
+// A charging jump squashes the player by up to this fraction and turns it this color, in steps
+const CHARGE_SQUASH: f32 = 0.12;
+const CHARGE_COLOR: Color = Color::rgb(0.55, 0.2, 0.9);
+const CHARGE_COLOR_STEPS: f32 = 8.0;
+// A player that can't dash yet is tinted this color, fading as the cooldown runs out
+const DASH_COOLDOWN_COLOR: Color = Color::rgb(0.45, 0.7, 1.0);
+const DASH_COOLDOWN_STEPS: f32 = 4.0;
+// Embers fly off a player in lava every EMBER_INTERVAL seconds, and EMBER_BURST at once off
+// anything that burns up. Each shrinks away over EMBER_LIFETIME.
+const EMBER_INTERVAL: f32 = 0.05;
+const EMBER_BURST: u32 = 12;
+const EMBER_LIFETIME: f32 = 0.6;
+const EMBER_RADIUS: f32 = 3.0;
+const EMBER_SPEED: f32 = 150.0;
+const EMBER_COLOR: Color = Color::rgb(1.0, 0.7, 0.2);
+// Crumbling tiles shake this far either way, this many times a second, and redden as they go
+const CRUMBLE_SHAKE: f32 = 2.0;
+const CRUMBLE_SHAKE_RATE: f32 = 25.0;
+const CRUMBLE_COLOR: Color = Color::rgb(0.55, 0.25, 0.15);
+const CRUMBLE_FADE_TIME: f32 = 0.3;
+// Colors change in this many steps, so that crumbling only needs a few materials
+const CRUMBLE_COLOR_STEPS: f32 = 8.0;
+const SAW_TEETH: u32 = 16;
+// Hazards are the area they cover, which things are seen through
+const HAZARD_ALPHA: f32 = 0.35;
+const COIN_COLOR: Color = Color::GOLD;
+const DOOR_COLOR: Color = Color::rgb(0.45, 0.3, 0.2);
+const ZONE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+// Boost pads have an arrow the way they boost, and flash white when they do, fading in steps
+const BOOST_PAD_COLOR: Color = Color::rgb(0.3, 0.6, 1.0);
+const BOOST_FLASH_COLOR: Color = Color::WHITE;
+const BOOST_FLASH_STEPS: f32 = 4.0;
+const BOOST_ARROW_LENGTH: f32 = 30.0;
+const BOOST_ARROW_WIDTH: f32 = 6.0;
+const BOOST_ARROW_HEAD: f32 = 10.0;
+
+// Everything that needs a renderer: the camera, meshes for the level's entities, shadows and the
+// trajectory preview. Left out of headless apps.
+pub struct VisualsPlugin;
+
+impl Plugin for VisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<TrajectoryPrediction>()
+            .init_resource::<JumpChargeConfig>()
+            .init_resource::<MeshCache>()
+            .init_resource::<GameRng>()
+            .add_event::<BurnedEvent>()
+            .add_startup_system(camera_setup)
+            .add_systems((
+                static_collider_visuals_system,
+                goal_visuals_system,
+                pickup_visuals_system,
+                sensor_visuals_system,
+                spikes_visuals_system,
+                portal_visuals_system,
+                saw_visuals_system,
+                elevator_visuals_system,
+                rope_visuals_system,
+                fluid_visuals_system,
+                coin_visuals_system,
+                door_visuals_system,
+                zone_visuals_system,
+                boost_pad_visuals_system,
+            ))
+            .add_systems((
+                body_visuals_system,
+                player_tint_system,
+                charge_squash_system,
+            ))
+            .add_system(heightfield_visuals_system)
+            .add_system(ember_spawn_system.after(PhysicsStep))
+            .add_system(ember_system)
+            .add_system(crumbling_visuals_system.after(PhysicsStep))
+            .add_system(door_open_visuals_system.after(PhysicsStep))
+            .add_system(boost_flash_visuals_system.after(PhysicsStep))
+            .add_system(shadow_system.after(PhysicsStep))
+            .add_system(trajectory_prediction_system.after(PhysicsStep));
+    }
+}
+
+// Blob shadow drawn on the surface below `target`
+#[derive(Component)]
+pub struct Shadow {
+    pub target: Entity,
+}
+
+// A body's own color, instead of BODY_COLOR
+#[derive(Component, Clone, Copy)]
+pub struct BodyColor(pub Color);
+
+fn camera_setup(mut commands: Commands) {
+    // 2D orthographic camera
+    commands.spawn(Camera2dBundle::default());
+}
+
+// Gives static colliders something to look at. The visuals are children so that they follow the
+// collider if it is ever moved.
+fn static_collider_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &Floor, Option<&SurfaceMaterial>), Added<Floor>>,
+) {
+    for (entity, floor, surface_material) in &query {
+        let color = surface_material.copied().unwrap_or_default().color();
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache
+                    .quad(&mut meshes, Vec2::new(floor.width, FLOOR_THICKNESS))
+                    .into(),
+                material: cache.material(&mut materials, color),
+                // The top edge of the quad is the surface
+                transform: Transform::from_xyz(0.0, -0.5 * FLOOR_THICKNESS, 0.0),
+                ..default()
+            });
+        });
+    }
+}
+
+// Heightfields are solid down to FLOOR_THICKNESS below their bottom, like the floor. Each is its
+// own shape, so their meshes aren't cached.
+fn heightfield_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &Heightfield), Added<Heightfield>>,
+) {
+    for (entity, heightfield) in &query {
+        commands.entity(entity).insert((
+            Mesh2dHandle(meshes.add(heightfield_mesh(heightfield))),
+            cache.material(&mut materials, SurfaceMaterial::Normal.color()),
+        ));
+    }
+}
+
+// A strip of quads, one under each segment of the surface
+fn heightfield_mesh(heightfield: &Heightfield) -> Mesh {
+    let mut positions = Vec::with_capacity(2 * heightfield.heights.len());
+    for (i, &height) in heightfield.heights.iter().enumerate() {
+        let x = i as f32 * heightfield.spacing;
+        positions.push([x, height, 0.0]);
+        positions.push([x, -FLOOR_THICKNESS, 0.0]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    // Counter-clockwise: top left, bottom left, bottom right, then top left, bottom right, top
+    // right
+    let segments = heightfield.heights.len().saturating_sub(1) as u32;
+    let mut indices = Vec::with_capacity(6 * segments as usize);
+    for i in 0..segments {
+        let (top, bottom) = (2 * i, 2 * i + 1);
+        indices.extend_from_slice(&[top, bottom, bottom + 2, top, bottom + 2, top + 2]);
+    }
+
+    let mut mesh = Mesh::new(bevy::render::mesh::PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_indices(Some(bevy::render::mesh::Indices::U32(indices)));
+    mesh
+}
+
+// Shakes crumbling tiles, and fades out the ones that have gone
+fn crumbling_visuals_system(
+    mut cache: ResMut<MeshCache>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    tiles: Query<
+        (
+            &CrumbleState,
+            &Crumbling,
+            Option<&SurfaceMaterial>,
+            &Children,
+        ),
+        Changed<CrumbleState>,
+    >,
+    mut quads: Query<(&mut Transform, &mut Handle<ColorMaterial>)>,
+) {
+    for (&state, crumbling, surface_material, children) in &tiles {
+        let base = surface_material.copied().unwrap_or_default().color();
+        let (shake, color) = match state {
+            CrumbleState::Solid => (0.0, base),
+            CrumbleState::Shaking { elapsed } => {
+                let t =
+                    (elapsed / crumbling.delay * CRUMBLE_COLOR_STEPS).floor() / CRUMBLE_COLOR_STEPS;
+                let color = mix(base, CRUMBLE_COLOR, t);
+                let shake = (elapsed * CRUMBLE_SHAKE_RATE * std::f32::consts::TAU).sin();
+                (CRUMBLE_SHAKE * shake, color)
+            }
+            CrumbleState::Gone { elapsed } => {
+                let faded = (elapsed / CRUMBLE_FADE_TIME * CRUMBLE_COLOR_STEPS).ceil()
+                    / CRUMBLE_COLOR_STEPS;
+                let mut color = CRUMBLE_COLOR;
+                color.set_a(1.0 - faded.min(1.0));
+                (0.0, color)
+            }
+        };
+        for &child in children {
+            if let Ok((mut transform, mut material)) = quads.get_mut(child) {
+                transform.translation.x = shake;
+                *material = cache.material(&mut materials, color);
+            }
+        }
+    }
+}
+
+// `t` of the way from `from` to `to`, keeping the alpha of `from`
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    let [r, g, b, a] = from.as_rgba_f32();
+    let [to_r, to_g, to_b, _] = to.as_rgba_f32();
+    Color::rgba(
+        r + (to_r - r) * t,
+        g + (to_g - g) * t,
+        b + (to_b - b) * t,
+        a,
+    )
+}
+
+// A pole standing on the floor
+fn goal_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<Entity, Added<Goal>>,
+) {
+    for entity in &query {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache.quad(&mut meshes, GOAL_SIZE).into(),
+                material: cache.material(&mut materials, GOAL_COLOR),
+                transform: Transform::from_xyz(0.0, 0.5 * GOAL_SIZE.y, 0.0),
+                ..default()
+            });
+        });
+    }
+}
+
+fn pickup_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<Entity, Added<SizePickup>>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.circle(&mut meshes, PICKUP_RADIUS)),
+            cache.material(&mut materials, PICKUP_COLOR),
+        ));
+    }
+}
+
+// Sensors are circles the color of their effect
+fn sensor_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &StatusSensor), Added<StatusSensor>>,
+) {
+    for (entity, sensor) in &query {
+        let mut color = sensor.effect.color();
+        if !sensor.pickup {
+            color.set_a(HAZARD_ALPHA);
+        }
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.circle(&mut meshes, sensor.radius)),
+            cache.material(&mut materials, color),
+        ));
+    }
+}
+
+fn spikes_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<Entity, Added<Spikes>>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.quad(&mut meshes, SPIKES_SIZE)),
+            cache.material(&mut materials, SurfaceMaterial::Hazard.color()),
+        ));
+    }
+}
+
+// Both ends of a pair are the same color, so it's clear which goes where
+fn portal_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &Portal), Added<Portal>>,
+) {
+    for (entity, portal) in &query {
+        // Golden angle steps, so that pairs in a row look different
+        let hue = (portal.id as f32 * 137.5) % 360.0;
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.quad(&mut meshes, Vec2::new(PORTAL_THICKNESS, PORTAL_WIDTH))),
+            cache.material(&mut materials, Color::hsl(hue, 0.8, 0.6)),
+        ));
+    }
+}
+
+// A star-shaped spinner, with the tips of its teeth at the edge of the blade
+fn saw_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &SawBlade), Added<SawBlade>>,
+) {
+    for (entity, blade) in &query {
+        let tooth = 0.1 * blade.radius;
+        let shape = FidgetSpinner {
+            radius: blade.radius - tooth,
+            bump_size: tooth,
+            bumps: SAW_TEETH,
+            vertices: 2 * SAW_TEETH as usize,
+            profile: SpinnerProfile::Star,
+        };
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.spinner(&mut meshes, shape)),
+            cache.material(&mut materials, SAW_COLOR),
+        ));
+    }
+}
+
+// A slab under the elevator's surface, as thick as the part that's solid
+fn elevator_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &Elevator), Added<Elevator>>,
+) {
+    for (entity, elevator) in &query {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache
+                    .quad(&mut meshes, Vec2::new(elevator.width, ELEVATOR_THICKNESS))
+                    .into(),
+                material: cache.material(&mut materials, ELEVATOR_COLOR),
+                transform: Transform::from_xyz(0.0, -0.5 * ELEVATOR_THICKNESS, 0.0),
+                ..default()
+            });
+        });
+    }
+}
+
+// A bead for each segment of a rope
+fn rope_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<Entity, Added<RopeSegment>>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.circle(&mut meshes, ROPE_SEGMENT_RADIUS)),
+            cache.material(&mut materials, ROPE_COLOR),
+        ));
+    }
+}
+
+// The whole pool, which what's in it shows through
+fn fluid_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &FluidVolume), Added<FluidVolume>>,
+) {
+    for (entity, fluid) in &query {
+        let color = if fluid.hostile {
+            LAVA_COLOR
+        } else {
+            WATER_COLOR
+        };
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.quad(&mut meshes, fluid.size)),
+            cache.material(&mut materials, color),
+        ));
+    }
+}
+
+fn coin_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<Entity, Added<Coin>>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.circle(&mut meshes, COIN_RADIUS)),
+            cache.material(&mut materials, COIN_COLOR),
+        ));
+    }
+}
+
+// A slab standing on the floor, like the goal
+fn door_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &Door), Added<Door>>,
+) {
+    for (entity, door) in &query {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache
+                    .quad(&mut meshes, Vec2::new(DOOR_THICKNESS, door.height))
+                    .into(),
+                material: cache.material(&mut materials, DOOR_COLOR),
+                transform: Transform::from_xyz(0.0, 0.5 * door.height, 0.0),
+                ..default()
+            });
+        });
+    }
+}
+
+// Open doors are gone
+fn door_open_visuals_system(mut query: Query<(&Door, &mut Visibility), Changed<Door>>) {
+    for (door, mut visibility) in &mut query {
+        *visibility = if door.open {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+// Faint, so the level shows where something will happen without giving it all away
+fn zone_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &TriggerZone), Added<TriggerZone>>,
+) {
+    for (entity, zone) in &query {
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.circle(&mut meshes, zone.radius)),
+            cache.material(&mut materials, ZONE_COLOR),
+        ));
+    }
+}
+
+// A slab on the floor, with an arrow standing on it the way it boosts
+fn boost_pad_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &BoostPad), Added<BoostPad>>,
+) {
+    for (entity, pad) in &query {
+        let direction = pad.direction.normalize_or_zero();
+        let angle = direction.y.atan2(direction.x);
+        let start = Vec2::new(0.0, BOOST_PAD_SIZE.y);
+        let material = cache.material(&mut materials, BOOST_PAD_COLOR);
+        let shaft =
+            Transform::from_translation((start + 0.5 * BOOST_ARROW_LENGTH * direction).extend(0.0))
+                .with_rotation(Quat::from_rotation_z(angle));
+        // The triangle points up
+        let head =
+            Transform::from_translation((start + BOOST_ARROW_LENGTH * direction).extend(0.0))
+                .with_rotation(Quat::from_rotation_z(angle - std::f32::consts::FRAC_PI_2));
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache.quad(&mut meshes, BOOST_PAD_SIZE).into(),
+                material: material.clone(),
+                transform: Transform::from_xyz(0.0, 0.5 * BOOST_PAD_SIZE.y, 0.0),
+                ..default()
+            });
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache
+                    .quad(
+                        &mut meshes,
+                        Vec2::new(BOOST_ARROW_LENGTH, BOOST_ARROW_WIDTH),
+                    )
+                    .into(),
+                material: material.clone(),
+                transform: shaft,
+                ..default()
+            });
+            parent.spawn(ColorMesh2dBundle {
+                mesh: cache.triangle(&mut meshes, BOOST_ARROW_HEAD).into(),
+                material,
+                transform: head,
+                ..default()
+            });
+        });
+    }
+}
+
+// Flashes the whole pad after it boosts something
+fn boost_flash_visuals_system(
+    mut cache: ResMut<MeshCache>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pads: Query<(&BoostPadState, &Children), Changed<BoostPadState>>,
+    mut parts: Query<&mut Handle<ColorMaterial>>,
+) {
+    for (state, children) in &pads {
+        let t = (state.flash / BOOST_FLASH_TIME * BOOST_FLASH_STEPS).ceil() / BOOST_FLASH_STEPS;
+        let handle = cache.material(&mut materials, mix(BOOST_PAD_COLOR, BOOST_FLASH_COLOR, t));
+        for &child in children {
+            if let Ok(mut material) = parts.get_mut(child) {
+                if *material != handle {
+                    *material = handle.clone();
+                }
+            }
+        }
+    }
+}
+
+// Meshes for bodies shaped like a FidgetSpinner, plus a shadow if they have a collider. Replay
+// ghosts are translucent. Shadows fade by changing their material, so each gets its own.
+#[allow(clippy::type_complexity)]
+fn body_visuals_system(
+    mut commands: Commands,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<
+        (
+            Entity,
+            &FidgetSpinner,
+            Option<&Collider>,
+            Option<&Ghost>,
+            Option<&BodyColor>,
+        ),
+        Added<FidgetSpinner>,
+    >,
+) {
+    for (entity, spinner, collider, ghost, body_color) in &query {
+        let color = if ghost.is_some() {
+            GHOST_COLOR
+        } else {
+            body_color.map_or(BODY_COLOR, |body_color| body_color.0)
+        };
+        commands.entity(entity).insert((
+            Mesh2dHandle(cache.spinner(&mut meshes, *spinner)),
+            cache.material(&mut materials, color),
+        ));
+
+        let Some(&Collider::Ball { radius, .. }) = collider else {
+            continue;
+        };
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh: cache.circle(&mut meshes, radius).into(),
+                material: materials.add(Color::rgba(0.0, 0.0, 0.0, SHADOW_ALPHA).into()),
+                transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Shadow { target: entity },
+        ));
+    }
+}
+
+// An overheated player glows until it's cooled down, one in lava glows as it heats up, one
+// charging a jump shifts color as it winds up, and one that's just dashed is tinted until it can
+// dash again. A remote player is another color, so the two can be told apart.
+#[allow(clippy::type_complexity)]
+fn player_tint_system(
+    mut cache: ResMut<MeshCache>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    charge_config: Res<JumpChargeConfig>,
+    mut query: Query<
+        (
+            Option<&SpinHeat>,
+            Option<&Scorching>,
+            Option<&RemotePlayer>,
+            Option<&JumpCharge>,
+            Option<&Dash>,
+            &mut Handle<ColorMaterial>,
+        ),
+        (With<Player>, Without<Ghost>),
+    >,
+) {
+    for (spin_heat, scorching, remote, charge, dash, mut material) in &mut query {
+        let base = if remote.is_some() {
+            REMOTE_PLAYER_COLOR
+        } else {
+            BODY_COLOR
+        };
+        let color = if spin_heat.is_some_and(SpinHeat::overheated) {
+            OVERHEAT_COLOR
+        } else if let Some(scorching) = scorching {
+            let t = (scorching.heat / LAVA_KILL_TIME * LAVA_GLOW_STEPS).ceil() / LAVA_GLOW_STEPS;
+            mix(base, LAVA_GLOW_COLOR, t.min(1.0))
+        } else if let Some(&JumpCharge::Charging(time)) = charge {
+            let t = (charge_config.fraction(time) * CHARGE_COLOR_STEPS).ceil() / CHARGE_COLOR_STEPS;
+            mix(base, CHARGE_COLOR, t)
+        } else if let Some(dash) = dash.filter(|dash| dash.cooling_down()) {
+            let t =
+                (dash.cooldown.percent_left() * DASH_COOLDOWN_STEPS).ceil() / DASH_COOLDOWN_STEPS;
+            mix(base, DASH_COOLDOWN_COLOR, t)
+        } else {
+            base
+        };
+        let handle = cache.material(&mut materials, color);
+        if *material != handle {
+            *material = handle;
+        }
+    }
+}
+
+// Squashes a player charging a jump, on top of any scaling for its size (see SizeChange)
+fn charge_squash_system(
+    charge_config: Res<JumpChargeConfig>,
+    mut query: Query<(&JumpCharge, &FidgetSpinner, &Collider, &mut Transform), With<Player>>,
+) {
+    for (&charge, spinner, &Collider::Ball { radius, .. }, mut transform) in &mut query {
+        let squash = match charge {
+            JumpCharge::Charging(time) => 1.0 - CHARGE_SQUASH * charge_config.fraction(time),
+            _ => 1.0,
+        };
+        let scale = radius / spinner.radius * squash;
+        if transform.scale.x != scale {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+}
+
+// A spark flying off something burning, shrinking away over EMBER_LIFETIME
+#[derive(Component)]
+struct Ember {
+    vel: Vec2,
+    remaining: f32,
+}
+
+// How fast an ember flies off: any way sideways, always a bit upwards, and at up to EMBER_SPEED
+pub fn ember_velocity(rng: &mut Rng) -> Vec2 {
+    let angle = rng.range(0.0..std::f32::consts::TAU);
+    let speed = rng.range(0.5..1.0) * EMBER_SPEED;
+    speed * Vec2::new(angle.cos(), 0.5 + 0.5 * angle.sin().abs())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn ember_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cache: ResMut<MeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<GameRng>,
+    mut burns: EventReader<BurnedEvent>,
+    mut since: Local<f32>,
+    players: Query<(&Transform, &Scorching), Without<Ghost>>,
+) {
+    let mut spawn = |commands: &mut Commands, position: Vec2| {
+        let vel = ember_velocity(rng.stream("embers"));
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh: cache.circle(&mut meshes, EMBER_RADIUS).into(),
+                material: cache.material(&mut materials, EMBER_COLOR),
+                transform: Transform::from_translation(position.extend(1.0)),
+                ..default()
+            },
+            Ember {
+                vel,
+                remaining: EMBER_LIFETIME,
+            },
+        ));
+    };
+
+    for burned in burns.iter() {
+        for _ in 0..EMBER_BURST {
+            spawn(&mut commands, burned.position);
+        }
+    }
+
+    *since += time.delta_seconds();
+    if *since < EMBER_INTERVAL {
+        return;
+    }
+    *since = 0.0;
+    for (transform, scorching) in &players {
+        if scorching.touching {
+            spawn(&mut commands, transform.translation.truncate());
+        }
+    }
+}
+
+fn ember_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut embers: Query<(Entity, &mut Ember, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut ember, mut transform) in &mut embers {
+        ember.remaining -= dt;
+        if ember.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (ember.vel * dt).extend(0.0);
+        transform.scale = Vec3::splat(ember.remaining / EMBER_LIFETIME);
+    }
+}
+
+// Scale and alpha of a shadow cast from `height` above a surface, or None if it's too high to cast one
+fn shadow_falloff(height: f32) -> Option<(f32, f32)> {
+    if height > SHADOW_MAX_HEIGHT {
+        return None;
+    }
+    let t = (height / SHADOW_MAX_HEIGHT).clamp(0.0, 1.0);
+    Some((1.0 - 0.6 * t, SHADOW_ALPHA * (1.0 - t)))
+}
+
+// The height of the highest surface below a ball of `radius` at `position`, and shadow_falloff for
+// it. None if there's no surface close enough below to cast a shadow on.
+fn cast_shadow<'a>(
+    position: Vec2,
+    radius: f32,
+    floors: impl IntoIterator<Item = (&'a Transform, &'a Floor)>,
+) -> Option<(f32, (f32, f32))> {
+    let floor_y = floor_below(position, floors)?;
+    Some((floor_y, shadow_falloff(position.y - radius - floor_y)?))
+}
+
+fn shadow_system(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    targets: Query<(&Transform, &Collider), Without<Shadow>>,
+    floors: Query<(&Transform, &Floor), Without<Shadow>>,
+    mut shadows: Query<(
+        Entity,
+        &Shadow,
+        &mut Transform,
+        &mut Visibility,
+        &Handle<ColorMaterial>,
+    )>,
+) {
+    for (entity, shadow, mut transform, mut visibility, material) in &mut shadows {
+        let Ok((target_transform, Collider::Ball { radius, .. })) = targets.get(shadow.target)
+        else {
+            // The body is gone (e.g. replaced by loading a save)
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let position = target_transform.translation.truncate();
+        let Some((floor_y, (scale, alpha))) = cast_shadow(position, *radius, &floors) else {
+            // Nothing to cast a shadow on
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        // Sits slightly above the surface so that it isn't hidden by the floor edge
+        transform.translation.x = position.x;
+        transform.translation.y = floor_y + 2.0;
+        // Bodies that changed size (see SizeChange) are scaled, and so are their shadows
+        let scale = scale * target_transform.scale.x;
+        transform.scale = Vec3::new(scale, 0.25 * scale, 1.0);
+        if let Some(material) = materials.get_mut(material) {
+            material.color.set_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+
+    fn surface_materials() -> [SurfaceMaterial; 5] {
+        [
+            SurfaceMaterial::Normal,
+            SurfaceMaterial::Ice {
+                friction_multiplier: 0.1,
+            },
+            SurfaceMaterial::Trampoline {
+                restitution_override: 1.2,
+                min_launch_speed: 500.0,
+            },
+            SurfaceMaterial::Hazard,
+            SurfaceMaterial::Sticky { strength: 100.0 },
+        ]
+    }
+
+    // Each floor gets exactly one quad as its child, once, colored after what it's made of
+    #[test]
+    fn one_quad_per_static_collider() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_asset::<ColorMaterial>()
+            .init_resource::<MeshCache>()
+            .add_system(static_collider_visuals_system);
+
+        let mut floors: Vec<_> = surface_materials()
+            .into_iter()
+            .map(|material| {
+                let floor = app.world.spawn((Floor { width: 200.0 }, material)).id();
+                (floor, material)
+            })
+            .collect();
+        // Without a material it's a plain floor
+        let plain = app.world.spawn(Floor { width: 200.0 }).id();
+        floors.push((plain, SurfaceMaterial::Normal));
+
+        for _ in 0..3 {
+            app.update();
+            for &(floor, material) in &floors {
+                let children = app.world.get::<Children>(floor).unwrap();
+                assert_eq!(children.len(), 1);
+                let handle = app.world.get::<Handle<ColorMaterial>>(children[0]).unwrap();
+                let color = app
+                    .world
+                    .resource::<Assets<ColorMaterial>>()
+                    .get(handle)
+                    .unwrap()
+                    .color;
+                assert_eq!(color, material.color());
+            }
+        }
+    }
+
+    // Every kind of surface looks different from every other
+    #[test]
+    fn surface_materials_have_distinct_colors() {
+        let colors = surface_materials().map(SurfaceMaterial::color);
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        assert_eq!(
+            SurfaceMaterial::default().color(),
+            SurfaceMaterial::Normal.color()
+        );
+    }
+
+    // Right on the surface the shadow is at full size and strength
+    #[test]
+    fn shadow_is_full_at_the_floor() {
+        assert_eq!(shadow_falloff(0.0), Some((1.0, SHADOW_ALPHA)));
+        // Sunk into the floor, e.g. mid-bounce, it's no bigger
+        assert_eq!(shadow_falloff(-10.0), Some((1.0, SHADOW_ALPHA)));
+    }
+
+    #[test]
+    fn shadow_fades_and_shrinks_with_height() {
+        let mut last = shadow_falloff(0.0).unwrap();
+        for i in 1..=100 {
+            let height = i as f32 / 100.0 * SHADOW_MAX_HEIGHT;
+            let (scale, alpha) = shadow_falloff(height).unwrap();
+            assert!(scale < last.0 && alpha < last.1, "{height}");
+            assert!(scale > 0.0 && alpha >= 0.0, "{height}");
+            last = (scale, alpha);
+        }
+    }
+
+    // Gone at the maximum height, and not cast at all above it
+    #[test]
+    fn shadow_stops_at_the_max_height() {
+        let (scale, alpha) = shadow_falloff(SHADOW_MAX_HEIGHT).unwrap();
+        assert!((scale - 0.4).abs() < 1e-6);
+        assert_eq!(alpha, 0.0);
+        assert_eq!(shadow_falloff(SHADOW_MAX_HEIGHT + 0.01), None);
+        assert_eq!(shadow_falloff(f32::INFINITY), None);
+    }
+
+    #[test]
+    fn shadow_falls_on_the_highest_surface_below() {
+        let floors = [
+            (
+                Transform::from_xyz(0.0, -100.0, 0.0),
+                Floor { width: 1000.0 },
+            ),
+            (Transform::from_xyz(200.0, 0.0, 0.0), Floor { width: 100.0 }),
+        ];
+        let floors = || floors.iter().map(|(transform, floor)| (transform, floor));
+
+        let (floor_y, falloff) = cast_shadow(Vec2::new(200.0, 110.0), 10.0, floors()).unwrap();
+        assert_eq!(floor_y, 0.0);
+        assert_eq!(Some(falloff), shadow_falloff(100.0));
+        let (floor_y, _) = cast_shadow(Vec2::new(0.0, 110.0), 10.0, floors()).unwrap();
+        assert_eq!(floor_y, -100.0);
+    }
+
+    // Past the end of every floor, or below all of them, there's nothing to cast a shadow on
+    #[test]
+    fn no_shadow_without_a_surface() {
+        let floors = [(Transform::from_xyz(0.0, 0.0, 0.0), Floor { width: 100.0 })];
+        let floors = || floors.iter().map(|(transform, floor)| (transform, floor));
+
+        assert_eq!(
+            cast_shadow(Vec2::new(0.0, 50.0), 10.0, std::iter::empty()),
+            None
+        );
+        assert_eq!(cast_shadow(Vec2::new(60.0, 50.0), 10.0, floors()), None);
+        assert_eq!(cast_shadow(Vec2::new(0.0, -50.0), 10.0, floors()), None);
+        assert_eq!(
+            cast_shadow(Vec2::new(0.0, SHADOW_MAX_HEIGHT + 20.0), 10.0, floors()),
+            None
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+// Browser integration: keeps the page from reacting to the game's keys and the canvas focused
+pub struct WebPlugin;
+
+impl Plugin for WebPlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(target_arch = "wasm32")]
+        _app.add_system(canvas_setup_system);
+    }
+}
+
+// The text of the panic overlay. Kept to plain string operations so the hook doesn't pull in
+// formatting machinery.
+pub fn panic_overlay_message(payload: Option<&str>, location: Option<(&str, u32)>) -> String {
+    let mut message = String::from("The game crashed: ");
+    message.push_str(payload.unwrap_or("unknown error"));
+    if let Some((file, line)) = location {
+        message.push_str(" (");
+        message.push_str(file);
+        message.push(':');
+        message.push_str(&line.to_string());
+        message.push(')');
+    }
+    message
+}
+
+// Panics go to the browser console and an overlay over the game, as otherwise players only see the
+// canvas freeze. Install before creating the App.
+#[cfg(target_arch = "wasm32")]
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str));
+        let location = info
+            .location()
+            .map(|location| (location.file(), location.line()));
+        show_panic_overlay(&panic_overlay_message(payload, location));
+    }));
+}
+
+// Covers the canvas's parent if there's a canvas, the page otherwise
+#[cfg(target_arch = "wasm32")]
+fn show_panic_overlay(message: &str) -> Option<()> {
+    let document = web_sys::window()?.document()?;
+    let parent = document
+        .query_selector("canvas")
+        .ok()
+        .flatten()
+        .and_then(|canvas| canvas.parent_element())
+        .or_else(|| document.body().map(Into::into))?;
+
+    let overlay = document.create_element("div").ok()?;
+    overlay
+        .set_attribute(
+            "style",
+            "position: fixed; inset: 0; z-index: 1000; display: flex; flex-direction: column; \
+             align-items: center; justify-content: center; gap: 1em; padding: 2em; \
+             background: rgba(0, 0, 0, 0.85); color: white; font-family: monospace;",
+        )
+        .ok()?;
+    let text = document.create_element("p").ok()?;
+    text.set_text_content(Some(message));
+    let reload = document.create_element("button").ok()?;
+    reload.set_text_content(Some("Reload"));
+    reload.set_attribute("onclick", "location.reload()").ok()?;
+
+    overlay.append_child(&text).ok()?;
+    overlay.append_child(&reload).ok()?;
+    parent.append_child(&overlay).ok()?;
+    Some(())
+}
+
+// Keys (KeyboardEvent.code) the browser would act on itself, e.g. Space and the arrows scrolling
+// the page, or Backspace going back in some browsers. Anything with a modifier is left to the
+// browser so shortcuts like Ctrl+R still work.
+const CAPTURED_KEYS: [&str; 7] = [
+    "Space",
+    "ArrowUp",
+    "ArrowDown",
+    "ArrowLeft",
+    "ArrowRight",
+    "Tab",
+    "Backspace",
+];
+
+pub fn captures_key(code: &str, ctrl: bool, alt: bool, meta: bool) -> bool {
+    !(ctrl || alt || meta) && CAPTURED_KEYS.contains(&code)
+}
+
+// The canvas only exists once the window has been created, so this waits for it and then installs
+// its event listeners once
+#[cfg(target_arch = "wasm32")]
+fn canvas_setup_system(mut done: Local<bool>) {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    if *done {
+        return;
+    }
+    let Some(canvas) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.query_selector("canvas").ok().flatten())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+    else {
+        return;
+    };
+    *done = true;
+
+    let on_key =
+        Closure::<dyn FnMut(web_sys::KeyboardEvent)>::new(|event: web_sys::KeyboardEvent| {
+            if captures_key(
+                &event.code(),
+                event.ctrl_key(),
+                event.alt_key(),
+                event.meta_key(),
+            ) {
+                event.prevent_default();
+            }
+        });
+    // Right mouse button is a game control, not the context menu
+    let on_context_menu = Closure::<dyn FnMut(web_sys::Event)>::new(|event: web_sys::Event| {
+        event.prevent_default();
+    });
+    let focus_canvas = canvas.clone();
+    let on_mouse_down = Closure::<dyn FnMut(web_sys::MouseEvent)>::new(move |_| {
+        let _ = focus_canvas.focus();
+    });
+
+    // Makes the canvas focusable, so that it receives the key events
+    canvas.set_tab_index(0);
+    for (event, listener) in [
+        ("keydown", on_key.as_ref()),
+        ("contextmenu", on_context_menu.as_ref()),
+        ("mousedown", on_mouse_down.as_ref()),
+    ] {
+        if let Err(error) = canvas.add_event_listener_with_callback(event, listener.unchecked_ref())
+        {
+            warn!("Failed to listen to {event} events: {error:?}");
+        }
+    }
+    // The listeners live as long as the page
+    on_key.forget();
+    on_context_menu.forget();
+    on_mouse_down.forget();
+    let _ = canvas.focus();
+}
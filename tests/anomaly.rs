@@ -0,0 +1,197 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{
+        anomaly::{
+            is_finite, sanitize, AnomalySettings, LastGoodState, PhysicsAnomaly, PhysicsValidation,
+        },
+        PhysObj, PhysicsConfig,
+    },
+    testing::{spawn_test_ball, test_app},
+};
+
+// A test app checking bodies every step, with a ball falling from well above the floor
+fn validated_app() -> (App, Entity) {
+    let mut app = test_app();
+    app.insert_resource(PhysicsValidation { enabled: true });
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 500.0), 10.0);
+    (app, ball)
+}
+
+fn anomalies(app: &App, reader: &mut ManualEventReader<PhysicsAnomaly>) -> Vec<PhysicsAnomaly> {
+    reader
+        .iter(app.world.resource::<Events<PhysicsAnomaly>>())
+        .copied()
+        .collect()
+}
+
+// A NaN in a body that has been checked before is reported, and the body goes back to where it
+// was then
+#[test]
+fn poisoned_bodies_roll_back_to_their_last_good_state() {
+    let (mut app, ball) = validated_app();
+    for _ in 0..5 {
+        app.update();
+    }
+    let last_good = app.world.get::<LastGoodState>(ball).unwrap().translation;
+    let mut reader = ManualEventReader::<PhysicsAnomaly>::default();
+    anomalies(&app, &mut reader);
+
+    app.world.get_mut::<PhysObj>(ball).unwrap().vel.y = f32::NAN;
+    app.update();
+
+    let anomalies = anomalies(&app, &mut reader);
+    assert!(
+        matches!(anomalies[..], [PhysicsAnomaly::NaNVelocity { entity }, ..] if entity == ball),
+        "{anomalies:?}"
+    );
+    let position = app.world.get::<Transform>(ball).unwrap().translation;
+    assert!(position.is_finite());
+    assert!(app.world.get::<PhysObj>(ball).unwrap().vel.is_finite());
+    // Back where it was, give or take the rest of the step
+    assert!(
+        position.distance(last_good) < 10.0,
+        "{position} {last_good}"
+    );
+
+    // And it carries on falling from there
+    app.update();
+    assert!(app.world.get::<Transform>(ball).unwrap().translation.y < position.y);
+}
+
+// A body poisoned before it was ever checked has nowhere to go back to, so it's reported, stopped
+// and put back in bounds, and carries on from there
+#[test]
+fn poisoned_bodies_without_a_good_state_are_recovered() {
+    let (mut app, ball) = validated_app();
+    let start = app.world.get::<Transform>(ball).unwrap().translation;
+    {
+        let mut ball = app.world.entity_mut(ball);
+        ball.get_mut::<Transform>().unwrap().translation.x = f32::NAN;
+        ball.get_mut::<PhysObj>().unwrap().vel = Vec2::new(f32::INFINITY, 300.0);
+    }
+    let mut reader = ManualEventReader::<PhysicsAnomaly>::default();
+    app.update();
+    app.update();
+
+    let anomalies = anomalies(&app, &mut reader);
+    assert!(
+        anomalies.iter().any(
+            |anomaly| matches!(anomaly, PhysicsAnomaly::NaNVelocity { entity } if *entity == ball)
+        ),
+        "{anomalies:?}"
+    );
+    let position = app.world.get::<Transform>(ball).unwrap().translation;
+    let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+    assert!(is_finite(
+        app.world.get::<Transform>(ball).unwrap(),
+        phys_obj
+    ));
+    assert_eq!(position.x, 0.0);
+    // Stopped where it was, give or take the rest of the step
+    assert!((position.y - start.y).abs() < 10.0, "{position} {start}");
+    assert!(phys_obj.vel.length() < 10.0, "{}", phys_obj.vel);
+
+    // And it carries on falling from there, checked like any other body
+    app.update();
+    assert!(app.world.get::<Transform>(ball).unwrap().translation.y < position.y);
+    assert!(app.world.get::<LastGoodState>(ball).is_some());
+}
+
+// Whatever part of the body is poisoned, it's back to finite values by the end of the step
+#[test]
+fn every_poisoned_field_recovers_within_a_step() {
+    type Poison = fn(&mut Transform, &mut PhysObj);
+    let poisons: [Poison; 4] = [
+        |transform, _| transform.translation.y = f32::NAN,
+        |transform, _| transform.rotation.z = f32::INFINITY,
+        |_, phys_obj| phys_obj.acc.x = f32::NAN,
+        |_, phys_obj| phys_obj.angular_vel = f32::NEG_INFINITY,
+    ];
+    for (i, poison) in poisons.into_iter().enumerate() {
+        let (mut app, ball) = validated_app();
+        for _ in 0..3 {
+            app.update();
+        }
+        let mut entity = app.world.entity_mut(ball);
+        let mut transform = *entity.get::<Transform>().unwrap();
+        let mut phys_obj = entity.get::<PhysObj>().unwrap().clone();
+        poison(&mut transform, &mut phys_obj);
+        entity.insert((transform, phys_obj));
+
+        let mut reader = ManualEventReader::<PhysicsAnomaly>::default();
+        anomalies(&app, &mut reader);
+        app.update();
+        let transform = app.world.get::<Transform>(ball).unwrap();
+        let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+        assert!(is_finite(transform, phys_obj), "{i}");
+        assert!(!anomalies(&app, &mut reader).is_empty(), "{i}");
+    }
+}
+
+// Checking can be turned off, e.g. in release builds, and then nothing is kept for it
+#[test]
+fn validation_can_be_turned_off() {
+    let (mut app, ball) = validated_app();
+    app.insert_resource(PhysicsValidation { enabled: false });
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world.get::<LastGoodState>(ball).is_none());
+}
+
+#[test]
+fn excessive_speed_is_reported() {
+    let (mut app, ball) = validated_app();
+    app.update();
+    app.world.get_mut::<PhysObj>(ball).unwrap().vel.x = 50_000.0;
+    let mut reader = ManualEventReader::<PhysicsAnomaly>::default();
+    app.update();
+
+    let anomalies = anomalies(&app, &mut reader);
+    assert!(
+        matches!(
+            anomalies[..],
+            [PhysicsAnomaly::ExcessiveSpeed { entity, speed }] if entity == ball && speed > 50_000.0
+        ),
+        "{anomalies:?}"
+    );
+}
+
+// The panic happens on one of the executor's threads, and reaches the test as the executor giving up
+#[test]
+#[should_panic]
+fn anomalies_panic_when_asked_to() {
+    let (mut app, ball) = validated_app();
+    app.insert_resource(AnomalySettings { panic: true });
+    app.update();
+    app.world.get_mut::<PhysObj>(ball).unwrap().vel.y = f32::NAN;
+    app.update();
+}
+
+#[test]
+fn sanitize_zeroes_only_what_is_not_finite() {
+    let mut phys_obj = PhysObj {
+        mass: 1.0,
+        vel: Vec2::new(f32::NAN, 3.0),
+        acc: Vec2::new(f32::INFINITY, f32::NEG_INFINITY),
+        acc_prev: Vec2::new(1.0, 2.0),
+        moment_of_inertia: 1.0,
+        angular_vel: f32::NAN,
+        angular_acc: 4.0,
+        angular_acc_prev: f32::INFINITY,
+        com_offset: Vec2::ZERO,
+    };
+    sanitize(&mut phys_obj);
+    assert_eq!(phys_obj.vel, Vec2::new(0.0, 3.0));
+    assert_eq!(phys_obj.acc, Vec2::ZERO);
+    assert_eq!(phys_obj.acc_prev, Vec2::new(1.0, 2.0));
+    assert_eq!(
+        (
+            phys_obj.angular_vel,
+            phys_obj.angular_acc,
+            phys_obj.angular_acc_prev
+        ),
+        (0.0, 4.0, 0.0)
+    );
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{PhysicsSchedule, PhysicsSet},
+    state::{AfterLoading, AppState},
+    testing::{press_key, test_app},
+};
+
+// Physics steps run so far
+#[derive(Resource, Default)]
+struct Steps(usize);
+
+fn count_steps(mut steps: ResMut<Steps>) {
+    steps.0 += 1;
+}
+
+fn probed_app() -> App {
+    let mut app = test_app();
+    app.init_resource::<Steps>().add_system(
+        count_steps
+            .in_set(PhysicsSet::ApplyImpulses)
+            .in_schedule(PhysicsSchedule),
+    );
+    app
+}
+
+fn steps(app: &App) -> usize {
+    app.world.resource::<Steps>().0
+}
+
+fn state(app: &App) -> AppState {
+    app.world.resource::<State<AppState>>().0
+}
+
+fn tap(app: &mut App, key: KeyCode) {
+    press_key(app, key, true);
+    app.update();
+    press_key(app, key, false);
+    app.update();
+}
+
+// Steps taken in `updates` updates
+fn steps_in(app: &mut App, updates: usize) -> usize {
+    let before = steps(app);
+    for _ in 0..updates {
+        app.update();
+    }
+    steps(app) - before
+}
+
+// Loading goes to Playing by itself in a headless app, Escape pauses and resumes, and the physics
+// only runs while Playing
+#[test]
+fn physics_only_runs_while_playing() {
+    let mut app = probed_app();
+    app.update();
+    assert_eq!(steps(&app), 0);
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+    assert_eq!(steps_in(&mut app, 10), 10);
+
+    tap(&mut app, KeyCode::Escape);
+    assert_eq!(state(&app), AppState::Paused);
+    assert_eq!(steps_in(&mut app, 10), 0);
+
+    tap(&mut app, KeyCode::Escape);
+    assert_eq!(state(&app), AppState::Playing);
+    assert_eq!(steps_in(&mut app, 10), 10);
+}
+
+// No other state runs the physics either
+#[test]
+fn physics_is_frozen_outside_playing() {
+    for frozen in [
+        AppState::MainMenu,
+        AppState::SettingsMenu,
+        AppState::LevelComplete,
+        AppState::AssetError,
+    ] {
+        let mut app = probed_app();
+        app.update();
+        app.update();
+        app.world.insert_resource(NextState(Some(frozen)));
+        app.update();
+        assert_eq!(state(&app), frozen);
+        assert_eq!(steps_in(&mut app, 10), 0, "{frozen:?}");
+
+        // And pausing only works from Playing
+        tap(&mut app, KeyCode::Escape);
+        assert_eq!(state(&app), frozen);
+    }
+}
+
+// Games with a main menu wait there after loading instead of starting to play
+#[test]
+fn loading_goes_to_the_state_after_loading() {
+    let mut app = probed_app();
+    app.insert_resource(AfterLoading(AppState::MainMenu));
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::MainMenu);
+    assert_eq!(steps_in(&mut app, 10), 0);
+}
@@ -0,0 +1,145 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use bevy_game::{
+    level::{BallEntry, Floor, FloorEntry, Level, LevelPlugin, SurfaceMaterial},
+    physics::PhysObj,
+    state::{AppState, AssetLoadState, StateScreensPlugin},
+    testing::test_app,
+};
+
+fn state(app: &App) -> AppState {
+    app.world.resource::<State<AppState>>().0
+}
+
+// Updates until the app leaves Loading, giving the asset server's threads time to get there
+fn finish_loading(app: &mut App) {
+    for _ in 0..200 {
+        app.update();
+        if state(app) != AppState::Loading {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    panic!("still loading");
+}
+
+// A required asset that fails to load stops the game on the error screen, which says which one
+#[test]
+fn failed_assets_show_the_error_screen() {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_plugin(StateScreensPlugin);
+    let missing: Handle<Mesh> = app.world.resource::<AssetServer>().load("missing.mesh");
+    app.world
+        .resource_mut::<AssetLoadState>()
+        .track(missing.clone_untyped());
+
+    finish_loading(&mut app);
+    assert_eq!(state(&app), AppState::AssetError);
+    let error = app
+        .world
+        .resource::<AssetLoadState>()
+        .error
+        .clone()
+        .unwrap();
+    assert_eq!(error.path, "missing.mesh");
+    assert_eq!(error.reason, "failed to load");
+
+    app.update();
+    let texts: Vec<String> = app
+        .world
+        .query::<&Text>()
+        .iter(&app.world)
+        .map(|text| text.sections[0].value.clone())
+        .collect();
+    assert_eq!(
+        texts,
+        ["Couldn't start the game:\nmissing.mesh failed to load"]
+    );
+    // And stays there
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(state(&app), AppState::AssetError);
+}
+
+#[test]
+fn nothing_to_load_starts_playing() {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default());
+    finish_loading(&mut app);
+    assert_eq!(state(&app), AppState::Playing);
+    assert!(app.world.resource::<AssetLoadState>().error.is_none());
+}
+
+fn ball(x: f32) -> BallEntry {
+    BallEntry {
+        position: Vec2::new(x, 0.0),
+        radius: 20.0,
+        mass: 10.0,
+        coef_of_restitution: 0.5,
+        kinetic_friction: 0.5,
+        com_offset: Vec2::ZERO,
+        player: false,
+    }
+}
+
+// Broken entries of a level are left out, and the rest of it is spawned
+#[test]
+fn broken_level_entries_are_skipped() {
+    let mut app = test_app();
+    let floor = |x, width| FloorEntry {
+        x,
+        width,
+        material: SurfaceMaterial::Normal,
+    };
+    app.insert_resource(Level {
+        floors: vec![
+            floor(0.0, 500.0),
+            floor(600.0, -10.0),
+            floor(f32::NAN, 100.0),
+        ],
+        balls: vec![
+            ball(0.0),
+            BallEntry {
+                radius: 0.0,
+                ..ball(100.0)
+            },
+            BallEntry {
+                position: Vec2::new(f32::INFINITY, 0.0),
+                ..ball(200.0)
+            },
+            BallEntry {
+                com_offset: Vec2::new(30.0, 0.0),
+                ..ball(300.0)
+            },
+            ball(400.0),
+        ],
+        goal: Some(f32::NAN),
+        ..Level::empty()
+    })
+    .add_plugin(LevelPlugin);
+    app.update();
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+
+    let floors: Vec<f32> = app
+        .world
+        .query_filtered::<&Transform, With<Floor>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation.x)
+        .collect();
+    assert_eq!(floors, [0.0]);
+    let mut balls: Vec<f32> = app
+        .world
+        .query_filtered::<&Transform, With<PhysObj>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation.x)
+        .collect();
+    balls.sort_by(f32::total_cmp);
+    assert_eq!(balls.len(), 2);
+    assert!(
+        balls[0].abs() < 1.0 && (balls[1] - 400.0).abs() < 1.0,
+        "{balls:?}"
+    );
+}
@@ -0,0 +1,255 @@
+#![cfg(feature = "audio")]
+
+use bevy::{asset::AssetPlugin, prelude::*, window::WindowFocused};
+use bevy_game::{
+    audio::{
+        category_volume,
+        music::{duck_target, fade_towards, Music},
+        rolling::{rolling_sound, LoopFade},
+        sfx::{impact_volume, merged_volume, SoundCooldown},
+        AudioFocus, GameAudioPlugin, VolumeCategory,
+    },
+    settings::Settings,
+    state::AppState,
+    testing::{press_key, test_app, TEST_DT},
+};
+
+// Resting contact and gentle touches make no sound at all
+#[test]
+fn soft_impacts_are_silent() {
+    for speed in [0.0, 1.0, 50.0, 149.0, -500.0, f32::NAN] {
+        assert_eq!(impact_volume(speed), None, "{speed}");
+    }
+    assert!(impact_volume(150.0).is_some());
+}
+
+// From the threshold up, harder impacts are louder, starting quiet but audible and capped at full
+// volume
+#[test]
+fn impact_volume_rises_with_speed_up_to_full() {
+    let quietest = impact_volume(150.0).unwrap();
+    assert!(quietest > 0.0 && quietest < 0.5, "{quietest}");
+
+    let mut last = quietest;
+    for speed in (200..1500).step_by(50) {
+        let volume = impact_volume(speed as f32).unwrap();
+        assert!(volume > last, "{speed}");
+        last = volume;
+    }
+    for speed in [1500.0, 2000.0, 1e6, f32::INFINITY] {
+        assert_eq!(impact_volume(speed), Some(1.0), "{speed}");
+    }
+}
+
+fn volume(settings: &Settings, focused: bool, category: VolumeCategory) -> f32 {
+    category_volume(settings, &AudioFocus { focused }, category)
+}
+
+// The master volume scales the category's, and muting or losing focus silences everything
+#[test]
+fn sfx_volume_follows_the_settings() {
+    let mut settings = Settings {
+        master_volume: 0.5,
+        sfx_volume: 0.8,
+        ..Default::default()
+    };
+    assert_eq!(volume(&settings, true, VolumeCategory::Sfx), 0.4);
+    assert_eq!(volume(&settings, false, VolumeCategory::Sfx), 0.0);
+    settings.muted = true;
+    assert_eq!(volume(&settings, true, VolumeCategory::Sfx), 0.0);
+}
+
+// Too slow to hear, then louder and higher the faster the ball rolls, up to a limit
+#[test]
+fn rolling_gets_louder_and_higher_with_speed() {
+    for speed in [0.0, 10.0, 29.0, f32::NAN] {
+        assert_eq!(rolling_sound(speed), None, "{speed}");
+    }
+    let (mut last_volume, mut last_pitch) = rolling_sound(30.0).unwrap();
+    assert!(last_volume > 0.0 && last_pitch < 1.0);
+    for speed in (100..1200).step_by(100) {
+        let (volume, pitch) = rolling_sound(speed as f32).unwrap();
+        assert!(volume > last_volume && pitch > last_pitch, "{speed}");
+        (last_volume, last_pitch) = (volume, pitch);
+    }
+    let fastest = rolling_sound(1200.0).unwrap();
+    assert_eq!(fastest.0, 1.0);
+    assert!(fastest.1 > 1.0);
+    assert_eq!(rolling_sound(5000.0), Some(fastest));
+}
+
+// Loops fade in and out over a fifth of a second instead of clicking on and off, and turning back
+// halfway carries on from where the fade was
+#[test]
+fn loops_fade_in_and_out() {
+    let dt = 0.02;
+    let mut fade = LoopFade::default();
+    let mut levels = Vec::new();
+    for _ in 0..12 {
+        levels.push(fade.update(true, dt));
+    }
+    assert!(levels.windows(2).all(|pair| pair[1] >= pair[0]));
+    assert!((levels[4] - 0.5).abs() < 1e-5, "{levels:?}");
+    assert!(levels[8] < 1.0 && levels[9] == 1.0, "{levels:?}");
+    assert_eq!(levels[11], 1.0);
+
+    // Lifting off halfway back in
+    let mut fade = LoopFade { level: 0.5 };
+    assert!((fade.update(false, dt) - 0.4).abs() < 1e-5);
+    assert!((fade.update(true, dt) - 0.5).abs() < 1e-5);
+    for _ in 0..5 {
+        fade.update(false, dt);
+    }
+    assert!(fade.level.abs() < 1e-5, "{}", fade.level);
+    assert_eq!(fade.update(false, dt), 0.0);
+}
+
+// The first event plays straight away. Events during the cooldown wait for it to end and then
+// play as one sound.
+#[test]
+fn events_in_the_cooldown_are_merged() {
+    let dt = 0.02;
+    let mut cooldown = SoundCooldown::new(0.1);
+    assert_eq!(cooldown.update(dt, 0), None);
+    // Three coins in one frame
+    assert_eq!(cooldown.update(dt, 3), Some(3));
+    assert_eq!(cooldown.update(dt, 1), None);
+    assert_eq!(cooldown.update(dt, 0), None);
+    assert_eq!(cooldown.update(dt, 2), None);
+    assert_eq!(cooldown.update(dt, 0), None);
+    assert_eq!(cooldown.update(dt, 0), Some(3));
+    // Nothing left over
+    for _ in 0..10 {
+        assert_eq!(cooldown.update(dt, 0), None);
+    }
+    assert_eq!(cooldown.update(dt, 1), Some(1));
+}
+
+// Without a cooldown every frame's events play, still merged within the frame
+#[test]
+fn no_cooldown_plays_every_frame() {
+    let mut cooldown = SoundCooldown::new(0.0);
+    for events in [1, 2, 0, 5] {
+        let played = cooldown.update(1.0 / 60.0, events);
+        assert_eq!(played, (events > 0).then_some(events));
+    }
+}
+
+// A merged sound is a bit louder than a single one, but never a blast
+#[test]
+fn merged_sounds_are_only_slightly_louder() {
+    assert_eq!(merged_volume(0), 1.0);
+    assert_eq!(merged_volume(1), 1.0);
+    let mut last = 1.0;
+    for count in 2..5 {
+        let volume = merged_volume(count);
+        assert!(volume > last, "{count}");
+        last = volume;
+    }
+    assert_eq!(merged_volume(100), merged_volume(1000));
+    assert!(merged_volume(1000) <= 1.5);
+}
+
+// Full volume in the menus and while playing, quieter on the pause and level complete screens,
+// silent until the game has loaded or when it couldn't be
+#[test]
+fn music_is_ducked_by_state() {
+    for (state, target) in [
+        (AppState::Loading, 0.0),
+        (AppState::AssetError, 0.0),
+        (AppState::MainMenu, 1.0),
+        (AppState::SettingsMenu, 1.0),
+        (AppState::Playing, 1.0),
+        (AppState::Paused, 0.3),
+        (AppState::LevelComplete, 0.3),
+    ] {
+        assert_eq!(duck_target(state), target, "{state:?}");
+    }
+}
+
+// Fading takes a second over the full range, at a steady rate either way, and stops at the target
+#[test]
+fn music_fades_over_time() {
+    assert!((fade_towards(0.0, 1.0, 0.25) - 0.25).abs() < 1e-6);
+    assert!((fade_towards(1.0, 0.3, 0.25) - 0.75).abs() < 1e-6);
+    assert_eq!(fade_towards(0.9, 1.0, 0.25), 1.0);
+    assert_eq!(fade_towards(0.35, 0.3, 0.25), 0.3);
+    assert_eq!(fade_towards(0.3, 0.3, 0.25), 0.3);
+}
+
+#[test]
+fn music_volume_is_master_times_music() {
+    let settings = Settings {
+        master_volume: 0.5,
+        music_volume: 0.6,
+        ..Default::default()
+    };
+    assert!((volume(&settings, true, VolumeCategory::Music) - 0.3).abs() < 1e-6);
+    assert_eq!(volume(&settings, false, VolumeCategory::Music), 0.0);
+}
+
+// The game's audio in a headless app, queueing sounds that nothing plays
+fn audio_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_asset::<AudioSource>()
+        .add_asset::<AudioSink>()
+        .init_resource::<Audio>()
+        .add_event::<WindowFocused>()
+        .add_plugin(GameAudioPlugin);
+    app
+}
+
+fn tap(app: &mut App, key: KeyCode) {
+    press_key(app, key, true);
+    app.update();
+    press_key(app, key, false);
+    app.update();
+}
+
+fn music_level(app: &App) -> f32 {
+    app.world.resource::<Music>().level
+}
+
+// Pausing and resuming over and over never jumps the music's volume, it only moves a frame's worth
+// of fade at a time, towards wherever the current state wants it
+#[test]
+fn flapping_between_states_fades_smoothly() {
+    let mut app = audio_app();
+    let mut last = music_level(&app);
+    let max_step = TEST_DT + 1e-5;
+    for _ in 0..90 {
+        app.update();
+        let level = music_level(&app);
+        assert!((level - last).abs() <= max_step, "{last} {level}");
+        last = level;
+    }
+    assert_eq!(last, 1.0);
+
+    for _ in 0..20 {
+        tap(&mut app, KeyCode::Escape);
+        let level = music_level(&app);
+        assert!((level - last).abs() <= 2.0 * max_step, "{last} {level}");
+        last = level;
+    }
+    assert!(last > 0.3, "{last}");
+
+    tap(&mut app, KeyCode::Escape);
+    assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Paused);
+    for _ in 0..90 {
+        app.update();
+    }
+    assert!((music_level(&app) - 0.3).abs() < 1e-5);
+}
+
+// M mutes and unmutes, through the Settings, so that it's kept
+#[test]
+fn m_toggles_mute_in_the_settings() {
+    let mut app = audio_app();
+    app.update();
+    let mute = app.world.resource::<Settings>().input.mute;
+    tap(&mut app, mute);
+    assert!(app.world.resource::<Settings>().muted);
+    tap(&mut app, mute);
+    assert!(!app.world.resource::<Settings>().muted);
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use bevy_game::{
+    best_run::{load_best_run, BestRunPlugin},
+    level::RestartLevelEvent,
+    physics::PhysicsConfig,
+    player::Player,
+    progress::ProgressPlugin,
+    replay::Ghost,
+    state::AppState,
+    storage::{MemoryBackend, Storage},
+    testing::{set_test_ball_restitution, spawn_test_player, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+// Updates of the run, after the first one which only starts the level
+const RUN: usize = 120;
+
+fn best_run_app(storage: Storage) -> App {
+    let mut app = test_app();
+    app.insert_resource(storage)
+        .add_plugin(ProgressPlugin)
+        .add_plugin(BestRunPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.5);
+    app
+}
+
+// Rolls right, jumps, and rolls back left, one update at a time
+fn play(app: &mut App, update: usize) {
+    let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+    keys.release_all();
+    match update {
+        0..=39 => keys.press(KeyCode::D),
+        40..=45 => keys.press(KeyCode::Space),
+        _ => keys.press(KeyCode::A),
+    }
+    app.update();
+}
+
+fn player_transform(app: &mut App) -> Transform {
+    *app.world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+}
+
+fn ghost(app: &mut App) -> Option<(Entity, Transform, Visibility)> {
+    app.world
+        .query_filtered::<(Entity, &Transform, &Visibility), With<Ghost>>()
+        .get_single(&app.world)
+        .ok()
+        .map(|(entity, transform, visibility)| (entity, *transform, *visibility))
+}
+
+// Plays the run and finishes the level with it, which as the first completion is the best
+fn record_best_run() -> (Storage, Vec<Transform>) {
+    let mut app = best_run_app(Storage(Box::<MemoryBackend>::default()));
+    app.update();
+    let mut trajectory = Vec::new();
+    for update in 0..RUN {
+        play(&mut app, update);
+        trajectory.push(player_transform(&mut app));
+    }
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::LevelComplete);
+    app.update();
+
+    let storage = app.world.remove_resource::<Storage>().unwrap();
+    let best = load_best_run(storage.0.as_ref(), 0).expect("no best run was saved");
+    assert_eq!(best.frames.len(), RUN);
+    (storage, trajectory)
+}
+
+#[test]
+fn ghost_follows_the_best_run_without_touching_the_player() {
+    let (storage, best) = record_best_run();
+
+    let mut app = best_run_app(storage);
+    app.update();
+    for (update, expected) in best.iter().enumerate() {
+        play(&mut app, update);
+        let (_, transform, visibility) = ghost(&mut app).expect("no ghost");
+        assert_eq!(visibility, Visibility::Inherited);
+        assert_eq!(transform, *expected, "update {update}");
+        // Playing the same way, the player goes the same way as if there were no ghost
+        assert_eq!(player_transform(&mut app), *expected, "update {update}");
+    }
+    // The ghost's crossed the line
+    app.update();
+    assert!(ghost(&mut app).is_none());
+}
+
+#[test]
+fn ghost_pauses_with_the_game_and_goes_on_restart() {
+    let (storage, best) = record_best_run();
+
+    let mut app = best_run_app(storage);
+    app.update();
+    for update in 0..30 {
+        play(&mut app, update);
+    }
+    let (ghost_entity, transform, _) = ghost(&mut app).unwrap();
+    assert_eq!(transform, best[29]);
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Paused);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(ghost(&mut app).unwrap().1, best[29]);
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Playing);
+    play(&mut app, 30);
+    assert_eq!(ghost(&mut app).unwrap().1, best[30]);
+
+    // A restart races a new ghost from the start of the best run
+    app.world.send_event(RestartLevelEvent);
+    app.update();
+    assert!(app.world.get_entity(ghost_entity).is_none());
+    app.update();
+    let (new_ghost, transform, _) = ghost(&mut app).unwrap();
+    assert_ne!(new_ghost, ghost_entity);
+    assert_eq!(transform, best[0]);
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy_game::{
+    blob::{ball_to_blob, blob_to_ball, Blob, BlobParticle, BlobPlugin, BLOB_PARTICLES},
+    physics::{joints::DistanceJoint, Collider, Gravity, PhysObj, PhysicsConfig},
+    testing::{set_test_ball_restitution, spawn_test_ball, test_app, TEST_BALL_MASS},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+
+fn blob_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(BlobPlugin);
+    app
+}
+
+// Mass, center of mass and velocity of the center of mass of everything with a PhysObj
+fn center_of_mass(world: &mut World) -> (f32, Vec2, Vec2) {
+    let mut query = world.query::<(&Transform, &PhysObj)>();
+    let (mass, moment, momentum) = query.iter(world).fold(
+        (0.0, Vec2::ZERO, Vec2::ZERO),
+        |(mass, moment, momentum), (transform, phys_obj)| {
+            (
+                mass + phys_obj.mass,
+                moment + phys_obj.mass * transform.translation.truncate(),
+                momentum + phys_obj.mass * phys_obj.vel,
+            )
+        },
+    );
+    (mass, moment / mass, momentum / mass)
+}
+
+fn area(app: &App, entity: Entity) -> (f32, f32) {
+    let blob = app.world.get::<Blob>(entity).unwrap();
+    let area = blob.area(|particle| {
+        app.world
+            .get::<Transform>(particle)
+            .map(|transform| transform.translation.truncate())
+    });
+    (area, blob.rest_area)
+}
+
+#[test]
+fn conversion_round_trip_keeps_the_motion() {
+    let mut app = blob_app();
+    let vel = Vec2::new(300.0, 150.0);
+    let ball = spawn_test_ball(&mut app, Vec2::ZERO, RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.vel = vel;
+    phys_obj.angular_vel = 2.0;
+    app.world.entity_mut(ball).remove::<Gravity>();
+
+    ball_to_blob(&mut app.world, ball);
+    let particles = app
+        .world
+        .query::<&BlobParticle>()
+        .iter(&app.world)
+        .filter(|particle| particle.blob == ball)
+        .count();
+    assert_eq!(particles, BLOB_PARTICLES);
+    let (mass, center, blob_vel) = center_of_mass(&mut app.world);
+    assert!((mass - MASS).abs() < 1e-4);
+    assert!(center.length() < 1e-3, "{center}");
+    assert!((blob_vel - vel).length() < 1e-3, "{blob_vel}");
+
+    // Some way into the flight, the ball comes back moving like the blob did
+    for _ in 0..20 {
+        app.update();
+    }
+    let (_, center, blob_vel) = center_of_mass(&mut app.world);
+    blob_to_ball(&mut app.world, ball);
+
+    let transform = app.world.get::<Transform>(ball).unwrap();
+    assert!((transform.translation.truncate() - center).length() < 1e-3);
+    let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+    assert!((phys_obj.vel - blob_vel).length() < 1e-3);
+    assert_eq!(phys_obj.mass, MASS);
+    assert_eq!(phys_obj.moment_of_inertia, MASS * 0.5 * RADIUS * RADIUS);
+    assert!(matches!(
+        app.world.get::<Collider>(ball),
+        Some(Collider::Ball { radius, .. }) if *radius == RADIUS
+    ));
+    assert!(app.world.get::<Blob>(ball).is_none());
+    assert_eq!(
+        app.world.query::<&BlobParticle>().iter(&app.world).count(),
+        0
+    );
+    assert_eq!(
+        app.world.query::<&DistanceJoint>().iter(&app.world).count(),
+        0
+    );
+}
+
+#[test]
+fn resting_blob_keeps_its_area() {
+    let mut app = blob_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    app.world.get_mut::<PhysObj>(ball).unwrap().angular_vel = 2.0;
+    ball_to_blob(&mut app.world, ball);
+
+    for _ in 0..3 * 60 {
+        app.update();
+    }
+
+    let (area, rest_area) = area(&app, ball);
+    assert!(
+        (area / rest_area - 1.0).abs() < 0.1,
+        "{area} vs {rest_area}"
+    );
+}
+
+#[test]
+fn blob_squishes_on_landing_and_recovers() {
+    let mut app = blob_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 300.0), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    app.world.get_mut::<PhysObj>(ball).unwrap().angular_vel = 2.0;
+    ball_to_blob(&mut app.world, ball);
+
+    let mut smallest = f32::INFINITY;
+    for _ in 0..3 * 60 {
+        app.update();
+        let (area, rest_area) = area(&app, ball);
+        smallest = smallest.min(area / rest_area);
+    }
+
+    assert!(smallest < 0.95, "never squished: {smallest}");
+    let (area, rest_area) = area(&app, ball);
+    assert!(
+        (area / rest_area - 1.0).abs() < 0.1,
+        "{area} vs {rest_area}"
+    );
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_game::{
+    boost::{BoostPad, BoostPadPlugin, BoostPadState, BOOST_FLASH_TIME, BOOST_PAD_SIZE},
+    level::{BallEntry, Level, LevelPlugin},
+    physics::{Gravity, PhysObj, PhysicsConfig},
+    testing::{spawn_test_ball, test_app, TEST_BALL_MASS, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+
+fn boost_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(BoostPadPlugin);
+    app
+}
+
+fn spawn_pad(app: &mut App, x: f32, pad: BoostPad) -> Entity {
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_xyz(x, floor_y, 0.0)),
+            pad,
+        ))
+        .id()
+}
+
+// Without gravity, so it only moves if it's boosted
+fn spawn_floating_ball(app: &mut App, position: Vec2) -> Entity {
+    let ball = spawn_test_ball(app, position, RADIUS);
+    app.world.entity_mut(ball).remove::<Gravity>();
+    ball
+}
+
+fn vel(app: &App, entity: Entity) -> Vec2 {
+    app.world.get::<PhysObj>(entity).unwrap().vel
+}
+
+#[test]
+fn boosts_once_until_the_body_leaves_the_pad() {
+    let mut app = boost_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    // Slow enough to stay on the pad
+    let pad = spawn_pad(
+        &mut app,
+        0.0,
+        BoostPad {
+            direction: Vec2::new(2.0, 0.0),
+            strength: 10.0 * MASS,
+        },
+    );
+    let on_pad = Vec2::new(0.0, floor_y + BOOST_PAD_SIZE.y + RADIUS - 5.0);
+    let ball = spawn_floating_ball(&mut app, on_pad);
+    // The physics starts on the second update
+    app.update();
+    app.update();
+    assert!((vel(&app, ball) - Vec2::new(10.0, 0.0)).length() < 1e-3);
+    let state = app.world.get::<BoostPadState>(pad).unwrap();
+    assert_eq!(state.touching, vec![ball]);
+    assert!(state.flash > 0.0);
+
+    let updates = (BOOST_FLASH_TIME / TEST_DT).ceil() as usize + 1;
+    for _ in 0..updates {
+        app.update();
+    }
+    assert!((vel(&app, ball) - Vec2::new(10.0, 0.0)).length() < 1e-3);
+    assert_eq!(app.world.get::<BoostPadState>(pad).unwrap().flash, 0.0);
+
+    // Off the pad and back on it
+    app.world.get_mut::<Transform>(ball).unwrap().translation.y += 200.0;
+    app.update();
+    assert!(app
+        .world
+        .get::<BoostPadState>(pad)
+        .unwrap()
+        .touching
+        .is_empty());
+    app.world.get_mut::<Transform>(ball).unwrap().translation = on_pad.extend(0.0);
+    app.update();
+    assert!((vel(&app, ball) - Vec2::new(20.0, 0.0)).length() < 1e-3);
+}
+
+#[test]
+fn the_default_level_pads_chain() {
+    let mut app = boost_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let pads = Level::default().boost_pads;
+    let (left, right) = (pads[0].x, pads[1].x);
+    assert!(left < right);
+    app.insert_resource(Level {
+        boost_pads: pads,
+        balls: vec![BallEntry {
+            position: Vec2::new(right, floor_y + RADIUS),
+            radius: RADIUS,
+            mass: MASS,
+            coef_of_restitution: 0.3,
+            kinetic_friction: 0.5,
+            com_offset: Vec2::ZERO,
+            player: false,
+        }],
+        ..Level::empty()
+    })
+    .add_plugin(LevelPlugin);
+    app.update();
+    app.update();
+
+    let mut pads = app.world.query::<(Entity, &Transform, &BoostPad)>();
+    let left_pad = pads
+        .iter(&app.world)
+        .find(|(_, transform, _)| transform.translation.x == left)
+        .unwrap()
+        .0;
+    let ball = app
+        .world
+        .query_filtered::<Entity, With<PhysObj>>()
+        .single(&app.world);
+    // Sent up and to the left, towards the other pad
+    app.update();
+    let boosted = vel(&app, ball);
+    assert!(boosted.x < 0.0 && boosted.y > 0.0, "{boosted}");
+
+    // Lands on it, and gets boosted by it
+    let mut landed = false;
+    for _ in 0..120 {
+        app.update();
+        let state = app.world.get::<BoostPadState>(left_pad).unwrap();
+        if state.touching.contains(&ball) {
+            assert!(state.flash > 0.0);
+            landed = true;
+            break;
+        }
+    }
+    assert!(landed);
+}
@@ -0,0 +1,149 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::{JumpCharge, JumpChargeConfig, JumpEvent, Player},
+    testing::{spawn_test_player, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const JUMP_IMPULSE: f32 = 10_000.0;
+
+fn hold_jump(app: &mut App, held: bool) {
+    let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+    if held {
+        keys.press(KeyCode::Space);
+    } else {
+        keys.release(KeyCode::Space);
+    }
+}
+
+// Holds jump for `updates` updates after the physics has started, lets go, and returns the jumps
+// that went off and the player's PhysObj before and after letting go
+fn charge_and_release(
+    app: &mut App,
+    player: Entity,
+    updates: usize,
+) -> (Vec<f32>, PhysObj, PhysObj) {
+    let mut reader = ManualEventReader::<JumpEvent>::default();
+    // The physics starts on the second update
+    app.update();
+    hold_jump(app, true);
+    for _ in 0..updates {
+        app.update();
+    }
+    let before = app.world.get::<PhysObj>(player).unwrap().clone();
+    hold_jump(app, false);
+    app.update();
+    let after = app.world.get::<PhysObj>(player).unwrap().clone();
+    let jumps = reader
+        .iter(app.world.resource::<Events<JumpEvent>>())
+        .map(|jump| jump.impulse)
+        .collect();
+    (jumps, before, after)
+}
+
+#[test]
+fn charge_goes_from_idle_through_charging_to_released() {
+    let config = JumpChargeConfig::default();
+    let step = |charge: JumpCharge, held, can_jump| charge.next(&config, held, can_jump, 0.25);
+
+    // A tap jumps as high as ever
+    let charging = step(JumpCharge::Idle, true, true);
+    assert_eq!(charging, JumpCharge::Charging(0.0));
+    assert_eq!(step(charging, false, true), JumpCharge::Released(0.0));
+    assert_eq!(config.scale(0.0), 1.0);
+
+    // Held in the air, nothing happens
+    assert_eq!(step(JumpCharge::Idle, true, false), JumpCharge::Idle);
+
+    // The charge stops growing at max_time, and leaving the ground drops it
+    let mut charge = charging;
+    for _ in 0..10 {
+        charge = step(charge, true, true);
+    }
+    assert_eq!(charge, JumpCharge::Charging(config.max_time));
+    assert_eq!(config.scale(charge.time()), config.max_scale);
+    assert_eq!(step(charge, true, false), JumpCharge::Idle);
+    assert_eq!(step(charge, false, false), JumpCharge::Idle);
+    assert_eq!(
+        step(JumpCharge::Released(1.0), false, false),
+        JumpCharge::Idle
+    );
+}
+
+#[test]
+fn full_charge_jumps_harder_and_launches_the_spin() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: JUMP_IMPULSE,
+        torque: 0.0,
+        ..Default::default()
+    });
+    let (taps, _, tapped) = charge_and_release(&mut app, player, 1);
+    assert_eq!(taps, vec![JUMP_IMPULSE]);
+
+    let mut app = test_app();
+    let config = app.world.resource::<JumpChargeConfig>().clone();
+    // Rolling right
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: JUMP_IMPULSE,
+        torque: 0.0,
+        ..Default::default()
+    });
+    let mut phys_obj = app.world.get_mut::<PhysObj>(player).unwrap();
+    phys_obj.vel = Vec2::new(20.0 * RADIUS, 0.0);
+    phys_obj.angular_vel = -20.0;
+    let updates = (config.max_time / TEST_DT).ceil() as usize + 10;
+    let (jumps, before, after) = charge_and_release(&mut app, player, updates);
+    assert_eq!(jumps, vec![JUMP_IMPULSE * config.max_scale]);
+    assert!(
+        after.vel.y > 1.9 * tapped.vel.y,
+        "{} {}",
+        after.vel.y,
+        tapped.vel.y
+    );
+
+    // Half of the spin went into speed along the ground, the way it was rolling
+    let launch = -config.spin_transfer * before.angular_vel * RADIUS;
+    let gained = after.vel.x - before.vel.x;
+    assert!(launch > 0.0);
+    assert!((gained - launch).abs() < 0.1 * launch, "{gained} {launch}");
+    assert!(after.angular_vel.abs() < before.angular_vel.abs());
+}
+
+#[test]
+fn leaving_the_ground_drops_the_charge() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: JUMP_IMPULSE,
+        torque: 0.0,
+        ..Default::default()
+    });
+    app.update();
+    hold_jump(&mut app, true);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(matches!(
+        app.world.get::<JumpCharge>(player),
+        Some(JumpCharge::Charging(_))
+    ));
+
+    // Knocked up off the floor mid-charge
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.y = 800.0;
+    app.update();
+    app.update();
+    assert_eq!(app.world.get::<JumpCharge>(player), Some(&JumpCharge::Idle));
+
+    let mut reader = ManualEventReader::<JumpEvent>::default();
+    hold_jump(&mut app, false);
+    app.update();
+    let jumps = app.world.resource::<Events<JumpEvent>>();
+    assert_eq!(reader.iter(jumps).count(), 0);
+}
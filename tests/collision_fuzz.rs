@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{
+        anomaly::PhysicsAnomaly,
+        collision::{collision_system, CollisionScratch},
+        sleep::StaticHash,
+        timings::PhysicsTimings,
+        BounceEvent, Collider, CollisionEvent, LandedEvent, PhysObj, PhysicsConfig, PhysicsTime,
+    },
+    rng::Rng,
+};
+
+const SEEDS: u64 = 20_000;
+// How far a ball may end up in the floor, for rounding
+const SLOP: f32 = 0.01;
+
+// A ball at the floor at the end of a step's integration, not yet resolved
+#[derive(Clone, Copy, Debug)]
+struct Case {
+    // Of the ball's bottom above the floor
+    height: f32,
+    vel: Vec2,
+    acc: Vec2,
+    acc_prev: Vec2,
+    radius: f32,
+    mass: f32,
+    restitution: f32,
+    friction: f32,
+    touching_ground: bool,
+    dt: f32,
+}
+
+// Mostly around `typical`, sometimes a thousand times that
+fn magnitude(rng: &mut Rng, typical: f32) -> f32 {
+    let extreme = rng.next_f32() < 0.1;
+    rng.signed() * if extreme { 1000.0 * typical } else { typical }
+}
+
+// In [0, max], with the ends a fair share of the time
+fn coefficient(rng: &mut Rng, max: f32) -> f32 {
+    match rng.next_f32() {
+        x if x < 0.1 => 0.0,
+        x if x < 0.2 => max,
+        _ => rng.range(0.0..max),
+    }
+}
+
+fn random_case(seed: u64) -> Case {
+    let mut rng = Rng::from_seed(seed);
+    Case {
+        height: magnitude(&mut rng, 20.0) - 20.0,
+        vel: Vec2::new(magnitude(&mut rng, 1000.0), magnitude(&mut rng, 2000.0)),
+        acc: Vec2::new(magnitude(&mut rng, 500.0), magnitude(&mut rng, 3000.0)),
+        acc_prev: Vec2::new(magnitude(&mut rng, 500.0), magnitude(&mut rng, 3000.0)),
+        radius: rng.range(1.0..50.0),
+        mass: rng.range(0.1..10.0),
+        restitution: coefficient(&mut rng, 1.0),
+        friction: coefficient(&mut rng, 2.0),
+        touching_ground: rng.next_f32() < 0.2,
+        // In (0, 0.1]
+        dt: 0.1 * (1.0 - rng.next_f32()),
+    }
+}
+
+const ORDINARY: Case = Case {
+    height: -5.0,
+    vel: Vec2::new(0.0, -300.0),
+    acc: Vec2::new(0.0, -2000.0),
+    acc_prev: Vec2::new(0.0, -2000.0),
+    radius: 20.0,
+    mass: 1.0,
+    restitution: 0.5,
+    friction: 0.5,
+    touching_ground: false,
+    dt: 1.0 / 60.0,
+};
+
+// Cases that went wrong before the fixes that came with this harness
+const CORPUS: [Case; 6] = [
+    // In the floor without having crossed into it, on its way out: it was pulled back in
+    Case {
+        height: -10.0,
+        vel: Vec2::new(0.0, 100.0),
+        ..ORDINARY
+    },
+    // Crossed into the floor long before the step, pushed up: rewound to the crossing and back
+    // again, it gained speed
+    Case {
+        height: -30.0,
+        vel: Vec2::new(0.0, -100.0),
+        acc: Vec2::new(0.0, 2000.0),
+        acc_prev: Vec2::new(0.0, 2000.0),
+        restitution: 1.0,
+        ..ORDINARY
+    },
+    // Landing dead under a huge acceleration: gravity carried it on deep into the floor
+    Case {
+        height: -500.0,
+        vel: Vec2::new(0.0, -10_000.0),
+        acc: Vec2::new(0.0, -100_000.0),
+        acc_prev: Vec2::new(0.0, -100_000.0),
+        restitution: 0.0,
+        dt: 0.1,
+        ..ORDINARY
+    },
+    // In the floor, and not moving or accelerating at all: 0/0 in the crossing time
+    Case {
+        vel: Vec2::ZERO,
+        acc: Vec2::ZERO,
+        acc_prev: Vec2::ZERO,
+        ..ORDINARY
+    },
+    // Tunneled far into the floor at a silly speed, sliding and grippy
+    Case {
+        height: -100_000.0,
+        vel: Vec2::new(1.0e6, -1.0e7),
+        friction: 2.0,
+        restitution: 1.0,
+        ..ORDINARY
+    },
+    // A tiny step
+    Case {
+        height: -1.0e-6,
+        vel: Vec2::new(5.0, -1.0),
+        dt: 1.0e-6,
+        ..ORDINARY
+    },
+];
+
+// A world with just what collision_system needs, to run it on one ball at a time
+fn collision_world() -> (World, Schedule) {
+    let mut world = World::new();
+    world.insert_resource(PhysicsConfig::default());
+    world.init_resource::<PhysicsTime>();
+    world.init_resource::<PhysicsTimings>();
+    world.init_resource::<CollisionScratch>();
+    world.init_resource::<StaticHash>();
+    world.init_resource::<Events<PhysicsAnomaly>>();
+    world.init_resource::<Events<CollisionEvent>>();
+    world.init_resource::<Events<BounceEvent>>();
+    world.init_resource::<Events<LandedEvent>>();
+    let mut schedule = Schedule::new();
+    schedule.add_system(collision_system);
+    (world, schedule)
+}
+
+// Runs a step of collision_system on the case, and checks what it did with the ball
+fn check(world: &mut World, schedule: &mut Schedule, case: &Case) -> Result<(), String> {
+    let floor_y = world.resource::<PhysicsConfig>().floor_y;
+    world.resource_mut::<PhysicsTime>().delta = case.dt;
+    let ball = world
+        .spawn((
+            Transform::from_xyz(0.0, floor_y + case.radius + case.height, 0.0),
+            PhysObj {
+                mass: case.mass,
+                vel: case.vel,
+                acc: case.acc,
+                acc_prev: case.acc_prev,
+                moment_of_inertia: case.mass * 0.5 * case.radius.powi(2),
+                angular_vel: 0.0,
+                angular_acc: 0.0,
+                angular_acc_prev: 0.0,
+                com_offset: Vec2::ZERO,
+            },
+            Collider::Ball {
+                radius: case.radius,
+                coef_of_restitution: case.restitution,
+                touching_ground: case.touching_ground,
+                kinetic_friction: case.friction,
+                friction_acc: 0.0,
+                friction_acc_prev: 0.0,
+            },
+        ))
+        .id();
+    schedule.run(world);
+
+    let transform = *world.get::<Transform>(ball).unwrap();
+    let phys_obj = world.get::<PhysObj>(ball).unwrap().clone();
+    world.despawn(ball);
+
+    let fields = [
+        transform.translation.x,
+        transform.translation.y,
+        transform.rotation.z,
+        transform.rotation.w,
+        phys_obj.vel.x,
+        phys_obj.vel.y,
+        phys_obj.acc.x,
+        phys_obj.acc.y,
+        phys_obj.acc_prev.x,
+        phys_obj.acc_prev.y,
+        phys_obj.angular_vel,
+    ];
+    if fields.iter().any(|field| !field.is_finite()) {
+        return Err(format!("not finite: {transform:?} {:?}", phys_obj.vel));
+    }
+    let height = transform.translation.y - case.radius - floor_y;
+    if height < -SLOP * (1.0 + case.height.abs()) {
+        return Err(format!("left {} into the floor", -height));
+    }
+    // Bounces give back at most what the ball came in with and friction only takes away, so the
+    // most it can gain is what the accelerations add over the step and its rewinds
+    let speed = case.vel.length();
+    let max_speed = speed + (case.acc.length() + case.acc_prev.length()) * case.dt;
+    let speed_after = phys_obj.vel.length();
+    if speed_after > max_speed * (1.0 + 1e-4) + 1e-3 {
+        return Err(format!("sped up from {speed} to {speed_after}"));
+    }
+    Ok(())
+}
+
+// For a seed that fails, `check` on `random_case(seed)` reproduces it, and the case belongs in
+// CORPUS once it's fixed
+#[test]
+fn random_collisions_stay_sane() {
+    let (mut world, mut schedule) = collision_world();
+    let failed: Vec<String> = (0..SEEDS)
+        .filter_map(|seed| {
+            let case = random_case(seed);
+            let result = check(&mut world, &mut schedule, &case);
+            result
+                .err()
+                .map(|error| format!("seed {seed}: {error}\n  {case:?}"))
+        })
+        .take(10)
+        .collect();
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
+
+#[test]
+fn corpus_collisions_stay_sane() {
+    let (mut world, mut schedule) = collision_world();
+    for case in CORPUS {
+        if let Err(error) = check(&mut world, &mut schedule, &case) {
+            panic!("{error}\n  {case:?}");
+        }
+    }
+}
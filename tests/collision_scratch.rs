@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy_game::physics::{
+    collision::{find_floor_contacts, FloorContacts},
+    Collider,
+};
+
+const FLOOR_Y: f32 = 0.0;
+
+fn ball(radius: f32, touching_ground: bool) -> Collider {
+    Collider::Ball {
+        radius,
+        coef_of_restitution: 0.5,
+        touching_ground,
+        kinetic_friction: 0.5,
+        friction_acc: 0.0,
+        friction_acc_prev: 0.0,
+    }
+}
+
+// A frame's worth of bodies: some below the floor, some above it, some of those still marked as
+// touching it
+fn bodies(frame: u32, count: u32) -> Vec<(Entity, Transform, Collider)> {
+    (0..count)
+        .map(|i| {
+            let y = ((i * 7 + frame * 13) % 40) as f32 - 10.0;
+            (
+                Entity::from_raw(i),
+                Transform::from_xyz(i as f32, y, 0.0),
+                ball(5.0, (i + frame).is_multiple_of(3)),
+            )
+        })
+        .collect()
+}
+
+// The obvious version, allocating new lists every time
+fn naive_floor_contacts(bodies: &[(Entity, Transform, Collider)]) -> (Vec<Entity>, Vec<Entity>) {
+    let touching = bodies
+        .iter()
+        .filter(|(_, transform, collider)| {
+            let Collider::Ball { radius, .. } = *collider;
+            transform.translation.y - radius <= FLOOR_Y
+        })
+        .map(|(entity, ..)| *entity)
+        .collect();
+    let left = bodies
+        .iter()
+        .filter(|(_, transform, collider)| {
+            let Collider::Ball {
+                radius,
+                touching_ground,
+                ..
+            } = *collider;
+            transform.translation.y - radius > FLOOR_Y && touching_ground
+        })
+        .map(|(entity, ..)| *entity)
+        .collect();
+    (touching, left)
+}
+
+fn find(bodies: &[(Entity, Transform, Collider)], contacts: &mut FloorContacts) {
+    find_floor_contacts(
+        FLOOR_Y,
+        bodies
+            .iter()
+            .map(|(entity, transform, collider)| (*entity, transform, collider)),
+        contacts,
+    );
+}
+
+#[test]
+fn floor_contacts_match_naive_version() {
+    let mut contacts = FloorContacts::default();
+    for frame in 0..20 {
+        let bodies = bodies(frame, 100);
+        find(&bodies, &mut contacts);
+        let (touching, left) = naive_floor_contacts(&bodies);
+        assert_eq!(contacts.touching, touching, "frame {frame}");
+        assert_eq!(contacts.left, left, "frame {frame}");
+    }
+}
+
+#[test]
+fn floor_contacts_reuse_buffers() {
+    let mut contacts = FloorContacts::default();
+    find(&bodies(0, 100), &mut contacts);
+    // Every body could end up in either list
+    contacts.touching.reserve(100);
+    contacts.left.reserve(100);
+    let capacity = (contacts.touching.capacity(), contacts.left.capacity());
+
+    for frame in 1..50 {
+        find(&bodies(frame, 100), &mut contacts);
+        assert_eq!(
+            (contacts.touching.capacity(), contacts.left.capacity()),
+            capacity,
+            "frame {frame}"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{
+        integrator::{integrate_before, integrate_simple},
+        PhysObj, PhysicsConfig,
+    },
+    testing::{spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = 10.0;
+
+fn body(com_offset: Vec2) -> PhysObj {
+    PhysObj {
+        mass: MASS,
+        vel: Vec2::new(120.0, -45.0),
+        acc: Vec2::new(3.0, -2000.0),
+        acc_prev: Vec2::new(-1.5, -1990.0),
+        moment_of_inertia: MASS * 0.5 * RADIUS * RADIUS,
+        angular_vel: -7.25,
+        angular_acc: 31.0,
+        angular_acc_prev: 12.5,
+        com_offset,
+    }
+}
+
+fn bits(transform: &Transform, phys_obj: &PhysObj) -> Vec<u32> {
+    [
+        transform.translation.to_array().as_slice(),
+        transform.rotation.to_array().as_slice(),
+        phys_obj.vel.to_array().as_slice(),
+        phys_obj.acc.to_array().as_slice(),
+        phys_obj.acc_prev.to_array().as_slice(),
+        &[
+            phys_obj.angular_vel,
+            phys_obj.angular_acc,
+            phys_obj.angular_acc_prev,
+        ],
+    ]
+    .concat()
+    .into_iter()
+    .map(f32::to_bits)
+    .collect()
+}
+
+// The integrator as it was written before bodies had a center of mass offset
+fn reference_before(dt: f32, transform: &mut Transform, phys_obj: &mut PhysObj) {
+    phys_obj.vel += 0.5 * phys_obj.acc * dt;
+    transform.translation += (phys_obj.vel * dt).extend(0.0);
+    phys_obj.acc_prev = phys_obj.acc;
+    phys_obj.acc = Vec2::ZERO;
+
+    phys_obj.angular_vel += 0.5 * phys_obj.angular_acc * dt;
+    transform.rotate_z(phys_obj.angular_vel * dt);
+    phys_obj.angular_acc_prev = phys_obj.angular_acc;
+    phys_obj.angular_acc = 0.0;
+}
+
+fn reference_simple(dt: f32, transform: &mut Transform, phys_obj: &mut PhysObj) {
+    let dv = phys_obj.acc * dt;
+    transform.translation += ((phys_obj.vel + 0.5 * dv) * dt).extend(0.0);
+    phys_obj.vel += dv;
+
+    let dav = phys_obj.angular_acc * dt;
+    transform.rotate_z((phys_obj.angular_vel + 0.5 * dav) * dt);
+    phys_obj.angular_vel += dav;
+}
+
+#[test]
+fn zero_offset_integrates_exactly_as_before() {
+    for dt in [1.0 / 60.0, 1.0 / 240.0, -0.003] {
+        let start =
+            Transform::from_xyz(13.0, -200.0, 0.0).with_rotation(Quat::from_rotation_z(0.7));
+
+        let (mut expected, mut expected_body) = (start, body(Vec2::ZERO));
+        let (mut actual, mut actual_body) = (start, body(Vec2::ZERO));
+        reference_before(dt, &mut expected, &mut expected_body);
+        integrate_before(dt, &mut actual, &mut actual_body);
+        assert_eq!(bits(&actual, &actual_body), bits(&expected, &expected_body));
+
+        let (mut expected, mut expected_body) = (start, body(Vec2::ZERO));
+        let (mut actual, mut actual_body) = (start, body(Vec2::ZERO));
+        reference_simple(dt, &mut expected, &mut expected_body);
+        integrate_simple(dt, &mut actual, &mut actual_body);
+        assert_eq!(bits(&actual, &actual_body), bits(&expected, &expected_body));
+    }
+}
+
+// Turning moves the origin around the center of mass, which itself only moves with `vel`
+#[test]
+fn offset_body_turns_about_its_center_of_mass() {
+    let offset = Vec2::new(10.0, 4.0);
+    let mut transform = Transform::from_xyz(13.0, -200.0, 0.0);
+    let mut phys_obj = body(offset);
+    phys_obj.acc = Vec2::ZERO;
+    let com = |transform: &Transform, phys_obj: &PhysObj| {
+        transform.translation.truncate() + phys_obj.com_arm(transform.rotation)
+    };
+    let start = com(&transform, &phys_obj);
+
+    integrate_simple(0.1, &mut transform, &mut phys_obj);
+
+    let expected = start + phys_obj.vel * 0.1;
+    assert!((com(&transform, &phys_obj) - expected).length() < 1e-3);
+    assert!(transform.translation.truncate().distance(start) > 1.0);
+}
+
+// Where the center of mass sits relative to straight below the center, in radians
+fn deviation(transform: &Transform, phys_obj: &PhysObj) -> f32 {
+    Vec2::NEG_Y.angle_between(phys_obj.com_arm(transform.rotation))
+}
+
+#[test]
+fn weighted_ball_wobbles_to_rest_on_its_heavy_side() {
+    let mut app = test_app();
+    app.insert_resource(PhysicsConfig {
+        rolling_resistance: 0.05,
+        ..default()
+    });
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    // Heavy on the right, and released with its weight level with the center
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, config.floor_y + RADIUS), RADIUS);
+    app.world.get_mut::<PhysObj>(ball).unwrap().com_offset = Vec2::new(10.0, 0.0);
+
+    let state = |app: &App| {
+        let transform = *app.world.get::<Transform>(ball).unwrap();
+        let phys_obj = app.world.get::<PhysObj>(ball).unwrap().clone();
+        (transform, phys_obj)
+    };
+
+    for _ in 0..11 {
+        app.update();
+    }
+    // Rolling right means turning clockwise
+    let (transform, phys_obj) = state(&app);
+    assert!(transform.translation.x > 0.0, "{}", transform.translation);
+    assert!(phys_obj.angular_vel < 0.0, "{}", phys_obj.angular_vel);
+
+    // Each time it stops turning, it's got less far past the equilibrium than the last time
+    let mut turning_points = Vec::new();
+    let mut last_angular_vel = phys_obj.angular_vel;
+    for _ in 0..10 * 60 {
+        app.update();
+        let (transform, phys_obj) = state(&app);
+        assert!(
+            (transform.translation.y - (config.floor_y + RADIUS)).abs() < 1e-3,
+            "left the floor at {}",
+            transform.translation
+        );
+        // Once it's all but settled, rounding can flip what little turning is left
+        let deviation = deviation(&transform, &phys_obj).abs();
+        if phys_obj.angular_vel * last_angular_vel < 0.0 && deviation > 0.05 {
+            turning_points.push(deviation);
+        }
+        last_angular_vel = phys_obj.angular_vel;
+    }
+    assert!(turning_points.len() >= 3, "{turning_points:?}");
+    assert!(turning_points[0] < FRAC_PI_2, "{turning_points:?}");
+    for pair in turning_points.windows(2) {
+        assert!(pair[1] < pair[0], "{turning_points:?}");
+    }
+
+    // Settled with its weight at the bottom, a quarter turn along the floor
+    let (transform, phys_obj) = state(&app);
+    assert!(deviation(&transform, &phys_obj).abs() < 0.05);
+    assert!(
+        (transform.translation.x - RADIUS * FRAC_PI_2).abs() < 2.0,
+        "{}",
+        transform.translation
+    );
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_game::physics::{
+    collision::{resolve_contact, ContactPoint},
+    friction::apply_friction_impulse,
+    PhysObj,
+};
+
+fn body(mass: f32, moment_of_inertia: f32, vel: Vec2, angular_vel: f32) -> PhysObj {
+    PhysObj {
+        mass,
+        vel,
+        acc: Vec2::ZERO,
+        acc_prev: Vec2::ZERO,
+        moment_of_inertia,
+        angular_vel,
+        angular_acc: 0.0,
+        angular_acc_prev: 0.0,
+        com_offset: Vec2::ZERO,
+    }
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() <= 1e-4 * expected.abs().max(1.0),
+        "{actual} != {expected}"
+    );
+}
+
+// The bounce as it was written before contacts went through resolve_contact
+#[test]
+fn ball_bounce_is_unchanged() {
+    for (vel, angular_vel, restitution, friction) in [
+        (Vec2::new(0.0, -300.0), 0.0, 0.8, 0.5),
+        (Vec2::new(150.0, -700.0), -4.0, 0.5, 1.0),
+        (Vec2::new(-80.0, -20.0), 12.0, 0.0, 0.2),
+    ] {
+        let (mass, radius) = (2.0, 20.0);
+        let moment_of_inertia = mass * 0.5 * radius * radius;
+        let mut expected = body(mass, moment_of_inertia, vel, angular_vel);
+        let expected_impulse = -expected.vel.y * (1.0 + restitution);
+        apply_friction_impulse(&mut expected, radius, expected_impulse, friction, 0.0);
+        expected.vel.y *= -restitution;
+
+        let mut actual = body(mass, moment_of_inertia, vel, angular_vel);
+        let impulse = resolve_contact(
+            &mut actual,
+            &ContactPoint::below(radius),
+            restitution,
+            friction,
+        );
+
+        assert_close(impulse, expected_impulse);
+        assert_close(actual.vel.x, expected.vel.x);
+        assert_close(actual.vel.y, expected.vel.y);
+        assert_close(actual.angular_vel, expected.angular_vel);
+    }
+}
+
+// A horizontal capsule falling flat and landing on its left end starts turning clockwise, as its
+// right end keeps falling
+#[test]
+fn capsule_landing_on_one_end_rotates() {
+    let (mass, length, radius) = (1.0, 100.0, 10.0);
+    // Rod along its length, which dominates
+    let moment_of_inertia = mass * length * length / 12.0;
+    let mut capsule = body(mass, moment_of_inertia, Vec2::new(0.0, -200.0), 0.0);
+    let contact = ContactPoint {
+        offset: Vec2::new(-0.5 * length, -radius),
+        normal: Vec2::Y,
+    };
+
+    resolve_contact(&mut capsule, &contact, 0.0, 0.0);
+
+    // Without restitution the end stops: v_y + ω · (-length / 2) = 0
+    assert!(capsule.angular_vel < 0.0);
+    assert_close(capsule.vel.y - capsule.angular_vel * 0.5 * length, 0.0);
+    // The rod's effective mass at its end is a quarter of its mass
+    assert_close(capsule.vel.y, -200.0 + 200.0 / 4.0);
+    assert_close(capsule.angular_vel, -150.0 / (0.5 * length));
+}
+
+// j = (1 + e) v / (1/m + (r × n)² / I) and Δω = (r × n) j / I
+#[test]
+fn box_corner_impact_matches_analytic_angular_velocity() {
+    let (mass, width, height) = (3.0, 60.0, 40.0);
+    let moment_of_inertia = mass * (width * width + height * height) / 12.0;
+    let (speed, restitution) = (250.0, 0.5);
+    let mut obb = body(mass, moment_of_inertia, Vec2::new(0.0, -speed), 0.0);
+    let contact = ContactPoint {
+        offset: Vec2::new(0.5 * width, -0.5 * height),
+        normal: Vec2::Y,
+    };
+
+    resolve_contact(&mut obb, &contact, restitution, 0.0);
+
+    let r_cross_n = 0.5 * width;
+    let impulse =
+        (1.0 + restitution) * speed / (1.0 / mass + r_cross_n * r_cross_n / moment_of_inertia);
+    assert_close(obb.angular_vel, r_cross_n * impulse / moment_of_inertia);
+    assert_close(obb.vel.y, -speed + impulse / mass);
+    // The corner separates at e times the speed it hit with
+    let corner_vel = obb.vel + obb.angular_vel * contact.offset.perp();
+    assert_close(corner_vel.y, restitution * speed);
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{
+        contacts::{accumulate_impulse, ContactKey},
+        Collider, Gravity, PhysObj, PhysicsConfig,
+    },
+    testing::{set_test_ball_mass, set_test_ball_restitution, spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 10.0;
+
+#[test]
+fn accumulated_impulse_is_clamped_as_a_whole() {
+    let mut accumulated = 0.0;
+    assert_eq!(
+        accumulate_impulse(&mut accumulated, 5.0, 0.0, f32::INFINITY),
+        5.0
+    );
+    // A later iteration can take back part of what was applied, but never pull
+    assert_eq!(
+        accumulate_impulse(&mut accumulated, -3.0, 0.0, f32::INFINITY),
+        -3.0
+    );
+    assert_eq!(
+        accumulate_impulse(&mut accumulated, -4.0, 0.0, f32::INFINITY),
+        -2.0
+    );
+    assert_eq!(accumulated, 0.0);
+
+    // Friction stays within its cone either way
+    let mut friction = 0.0;
+    assert_eq!(accumulate_impulse(&mut friction, 8.0, -2.5, 2.5), 2.5);
+    assert_eq!(accumulate_impulse(&mut friction, -10.0, -2.5, 2.5), -5.0);
+    assert_eq!(friction, -2.5);
+}
+
+#[test]
+fn warm_start_keys_match_regardless_of_order() {
+    let (a, b) = (Entity::from_raw(3), Entity::from_raw(7));
+    assert_eq!(ContactKey::new(a, b, 0), ContactKey::new(b, a, 0));
+    assert_ne!(ContactKey::new(a, b, 0), ContactKey::new(a, b, 1));
+    assert_ne!(
+        ContactKey::new(a, b, 0),
+        ContactKey::new(a, Entity::from_raw(8), 0)
+    );
+}
+
+// A single head-on contact is solved in one go, as before the solver iterated
+#[test]
+fn head_on_collision_conserves_momentum() {
+    let mut app = test_app();
+    let y = 0.0;
+    let a = spawn_test_ball(&mut app, Vec2::new(-30.0, y), RADIUS);
+    let b = spawn_test_ball(&mut app, Vec2::new(30.0, y), RADIUS);
+    for (ball, vel) in [(a, 600.0), (b, -200.0)] {
+        set_test_ball_mass(&mut app, ball, 1.0);
+        set_test_ball_restitution(&mut app, ball, 1.0);
+        app.world.get_mut::<PhysObj>(ball).unwrap().vel = Vec2::new(vel, 0.0);
+        app.world.entity_mut(ball).remove::<Gravity>();
+    }
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let vel = |entity| app.world.get::<PhysObj>(entity).unwrap().vel;
+    // Equal masses swap velocities in an elastic collision
+    assert!((vel(a).x - -200.0).abs() < 1e-3, "{}", vel(a));
+    assert!((vel(b).x - 600.0).abs() < 1e-3, "{}", vel(b));
+}
+
+// The middle balls of a stack have two contacts each. Once settled, none of them should keep
+// moving.
+#[test]
+fn stack_comes_to_rest_without_vibrating() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let stack: Vec<Entity> = (0..4)
+        .map(|i| {
+            let y = floor_y + RADIUS + 2.0 * RADIUS * i as f32 + 5.0 * i as f32;
+            let ball = spawn_test_ball(&mut app, Vec2::new(0.0, y), RADIUS);
+            set_test_ball_mass(&mut app, ball, 1.0);
+            let mut collider = app.world.get_mut::<Collider>(ball).unwrap();
+            let Collider::Ball {
+                touching_ground, ..
+            } = &mut *collider;
+            *touching_ground = false;
+            ball
+        })
+        .collect();
+    for _ in 0..240 {
+        app.update();
+    }
+
+    let heights = |app: &App| -> Vec<f32> {
+        stack
+            .iter()
+            .map(|&entity| app.world.get::<Transform>(entity).unwrap().translation.y)
+            .collect()
+    };
+    let settled = heights(&app);
+    for _ in 0..60 {
+        app.update();
+        for (height, settled) in heights(&app).into_iter().zip(&settled) {
+            assert!(
+                (height - settled).abs() < 0.5,
+                "{height} moved from {settled}"
+            );
+        }
+    }
+}
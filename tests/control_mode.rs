@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::{ControlMode, DirectDriveConfig, Player},
+    settings::Settings,
+    testing::{press_key, spawn_test_player, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+// The kinetic friction of the balls from spawn_test_ball
+const KINETIC_FRICTION: f32 = 0.5;
+
+fn phys_obj(app: &App, player: Entity) -> PhysObj {
+    app.world.get::<PhysObj>(player).unwrap().clone()
+}
+
+// The player has no torque, so only the direct drive can move it
+#[test]
+fn direct_drive_rolls_without_slipping_and_no_faster_than_friction_allows() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let drive = app.world.resource::<DirectDriveConfig>().clone();
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, config.floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    // The physics starts on the second update
+    app.update();
+    press_key(&mut app, Settings::default().input.spin_right, true);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(phys_obj(&app, player).vel, Vec2::ZERO);
+
+    *app.world.resource_mut::<ControlMode>() = ControlMode::Direct;
+    let updates = 30;
+    for _ in 0..updates {
+        app.update();
+        let phys_obj = phys_obj(&app, player);
+        let slip = phys_obj.vel.x - config.floor_velocity + phys_obj.angular_vel * RADIUS;
+        assert!(slip.abs() < 1.0, "{slip}");
+    }
+
+    let max_acc = config.friction(KINETIC_FRICTION) * config.gravity;
+    let expected = drive.acceleration.min(max_acc) * updates as f32 * TEST_DT;
+    let vel = phys_obj(&app, player).vel.x;
+    assert!(vel > 0.9 * expected, "{vel} {expected}");
+    assert!(vel <= max_acc * updates as f32 * TEST_DT * 1.01, "{vel}");
+}
+
+// What the direct drive pushed on the last step isn't integrated again after switching to torque
+#[test]
+fn switching_mode_leaves_no_stale_acceleration() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 5000.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    app.update();
+    press_key(&mut app, KeyCode::C, true);
+    app.update();
+    assert_eq!(*app.world.resource::<ControlMode>(), ControlMode::Direct);
+    press_key(&mut app, KeyCode::C, false);
+
+    let spin_right = Settings::default().input.spin_right;
+    press_key(&mut app, spin_right, true);
+    for _ in 0..10 {
+        app.update();
+    }
+    let pushed = phys_obj(&app, player);
+    assert!(pushed.vel.x > 0.0);
+    assert!(pushed.acc.x > 0.0);
+
+    press_key(&mut app, spin_right, false);
+    press_key(&mut app, KeyCode::C, true);
+    app.update();
+    assert_eq!(*app.world.resource::<ControlMode>(), ControlMode::Torque);
+    let switched = phys_obj(&app, player);
+    assert_eq!(switched.vel.x, pushed.vel.x);
+    assert_eq!(switched.acc.x, 0.0);
+}
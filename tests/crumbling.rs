@@ -0,0 +1,178 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    crumble::{CrumbleState, Crumbling, CrumblingPlugin, CRUMBLING_TILE_WIDTH},
+    hazard::{HazardPlugin, FALL_DEATH_DEPTH},
+    level::{Floor, RestartLevelEvent, SurfaceMaterial},
+    physics::PhysicsConfig,
+    player::Player,
+    progress::Progress,
+    testing::{set_test_ball_mass, spawn_test_ball, spawn_test_player, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 10.0;
+const MASS: f32 = 1.0;
+const DELAY: f32 = 0.25;
+
+fn crumbling_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(CrumblingPlugin);
+    app
+}
+
+fn floor_y(app: &App) -> f32 {
+    app.world.resource::<PhysicsConfig>().floor_y
+}
+
+fn spawn_tile(app: &mut App, x: f32, respawn: Option<f32>) -> Entity {
+    let floor_y = floor_y(app);
+    app.world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_xyz(x, floor_y, 0.0)),
+            Floor {
+                width: CRUMBLING_TILE_WIDTH,
+            },
+            SurfaceMaterial::Normal,
+            Crumbling {
+                delay: DELAY,
+                respawn,
+            },
+        ))
+        .id()
+}
+
+fn height(app: &App, entity: Entity) -> f32 {
+    app.world.get::<Transform>(entity).unwrap().translation.y
+}
+
+fn gaps(app: &App) -> Vec<(f32, f32)> {
+    app.world.resource::<PhysicsConfig>().floor_gaps.clone()
+}
+
+// Nothing happens to a tile that's never touched, and one that's landed on holds up whatever's on
+// it for `delay` seconds from then, however long it stays on it
+#[test]
+fn tiles_crumble_a_delay_after_they_are_first_touched() {
+    let mut app = crumbling_app();
+    let tile = spawn_tile(&mut app, 0.0, None);
+    for _ in 0..60 {
+        app.update();
+    }
+    assert!(app.world.get::<CrumbleState>(tile).is_none());
+    assert!(gaps(&app).is_empty());
+
+    let floor_y = floor_y(&app);
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 1.0), RADIUS);
+    set_test_ball_mass(&mut app, ball, MASS);
+    let mut touched = None;
+    let mut gone = None;
+    for update in 0..60 {
+        app.update();
+        match app.world.get::<CrumbleState>(tile) {
+            Some(CrumbleState::Shaking { .. }) => {
+                touched.get_or_insert(update);
+                // Still held up
+                assert!(height(&app, ball) > floor_y, "{update}");
+            }
+            Some(CrumbleState::Gone { .. }) => {
+                gone.get_or_insert(update);
+            }
+            _ => {}
+        }
+    }
+    let (touched, gone) = (touched.expect("never touched"), gone.expect("never gone"));
+    let seconds = (gone - touched) as f32 * TEST_DT;
+    assert!((seconds - DELAY).abs() <= TEST_DT + 1e-3, "{seconds}");
+
+    // It isn't coming back, and what was on it fell through
+    assert_eq!(gaps(&app), vec![(-20.0, 20.0)]);
+    assert!(height(&app, ball) < floor_y - RADIUS);
+}
+
+// A tile comes back `respawn` seconds after it crumbled, and holds things up again
+#[test]
+fn tiles_with_a_respawn_time_come_back() {
+    let mut app = crumbling_app();
+    let floor_y = floor_y(&app);
+    let tile = spawn_tile(&mut app, 0.0, Some(0.5));
+    let first = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 1.0), RADIUS);
+    set_test_ball_mass(&mut app, first, MASS);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(matches!(
+        app.world.get::<CrumbleState>(tile),
+        Some(CrumbleState::Gone { .. })
+    ));
+    assert!(height(&app, first) < floor_y - RADIUS);
+    // Gone for good, as the fall would have killed it. The floor coming back would push it up.
+    app.world.despawn(first);
+
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(
+        app.world.get::<CrumbleState>(tile),
+        Some(&CrumbleState::Solid)
+    );
+    assert!(gaps(&app).is_empty());
+
+    // Checked before it crumbles under this one too
+    let second = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 1.0), RADIUS);
+    set_test_ball_mass(&mut app, second, MASS);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!((height(&app, second) - (floor_y + RADIUS)).abs() < 1.0);
+}
+
+// The gap a tile left is filled in again when it's despawned, like when the level restarts
+#[test]
+fn despawned_tiles_leave_no_gap() {
+    let mut app = crumbling_app();
+    let floor_y = floor_y(&app);
+    let tile = spawn_tile(&mut app, 0.0, None);
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 1.0), RADIUS);
+    set_test_ball_mass(&mut app, ball, MASS);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(gaps(&app).len(), 1);
+
+    app.world.despawn(tile);
+    app.update();
+    assert!(gaps(&app).is_empty());
+}
+
+// The player falling through a gap in the floor dies
+#[test]
+fn falling_through_the_floor_kills_the_player() {
+    let mut app = crumbling_app();
+    app.add_plugin(HazardPlugin).init_resource::<Progress>();
+    let floor_y = floor_y(&app);
+    app.world
+        .resource_mut::<PhysicsConfig>()
+        .floor_gaps
+        .push((-20.0, 20.0));
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y), RADIUS);
+    set_test_ball_mass(&mut app, player, MASS);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: 1000.0,
+        torque: 20_000.0,
+        ..Default::default()
+    });
+    let mut restarts = ManualEventReader::<RestartLevelEvent>::default();
+
+    for _ in 0..120 {
+        app.update();
+        if restarts
+            .iter(app.world.resource::<Events<RestartLevelEvent>>())
+            .next()
+            .is_some()
+        {
+            assert_eq!(app.world.resource::<Progress>().deaths, 1);
+            assert!(height(&app, player) < floor_y - FALL_DEATH_DEPTH);
+            return;
+        }
+    }
+    panic!("never died");
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{
+        forces::{CustomForces, ForceFn, ForceProvider},
+        PhysObj, PhysicsConfig,
+    },
+    testing::{spawn_test_ball, test_app, TEST_DT},
+};
+
+// A steady push to the right, as a wind would give
+struct Wind(f32);
+
+impl ForceProvider for Wind {
+    fn apply(&self, _dt: f32, body: &mut PhysObj, _transform: &Transform) {
+        body.acc.x += self.0;
+    }
+}
+
+// Runs `seconds` with a ball high above the floor given `forces`, and returns where it got to
+fn fly(seconds: f32, forces: impl Bundle) -> Vec2 {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 10_000.0), 10.0);
+    app.world.entity_mut(ball).insert(forces);
+    app.update();
+    for _ in 0..(seconds / TEST_DT).round() as usize {
+        app.update();
+    }
+    app.world
+        .get::<Transform>(ball)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+// The provider's acceleration shows up in the path, on top of gravity's
+#[test]
+fn providers_accelerate_bodies() {
+    let (seconds, wind) = (1.0, 300.0);
+    let still = fly(seconds, ());
+    let blown = fly(seconds, CustomForces::default().with(Wind(wind)));
+    assert_eq!(still.x, 0.0);
+    let expected = 0.5 * wind * seconds * seconds;
+    // Less the half step the integrator starts with
+    assert!(
+        (blown.x - expected).abs() <= wind * TEST_DT * seconds,
+        "{} {expected}",
+        blown.x
+    );
+    // Falling just as it would without
+    assert!((blown.y - still.y).abs() < 1e-3, "{blown} {still}");
+}
+
+// Forces only add up, so gravity and any number of providers and closures give the same path in
+// whatever order they come
+#[test]
+fn forces_compose_with_gravity_and_each_other() {
+    let gravity = PhysicsConfig::default().gravity;
+    // Cancelling out gravity between them
+    let hovering = fly(
+        1.0,
+        (
+            CustomForces::default().with(Wind(100.0)).with(
+                move |_: f32, body: &mut PhysObj, _: &Transform| body.acc.y += 0.25 * gravity,
+            ),
+            ForceFn::new(move |_, body, _| body.acc += Vec2::new(-100.0, 0.75 * gravity)),
+        ),
+    );
+    let start = fly(0.0, ());
+    assert!((hovering - start).length() < 1e-2, "{hovering} {start}");
+
+    let a = fly(
+        0.5,
+        CustomForces::default().with(Wind(100.0)).with(Wind(-250.0)),
+    );
+    let b = fly(0.5, ForceFn::new(|_, body, _| body.acc.x -= 150.0));
+    assert!((a - b).length() < 1e-3, "{a} {b}");
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysObj, PhysicsConfig},
+    player::{Dash, Player},
+    settings::Settings,
+    testing::{spawn_test_player, test_app, TEST_BALL_MASS, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+
+// Taps `key` for one update
+fn tap(app: &mut App, key: KeyCode) {
+    app.world.resource_mut::<Input<KeyCode>>().press(key);
+    app.update();
+    app.world.resource_mut::<Input<KeyCode>>().release(key);
+}
+
+fn vel(app: &App, player: Entity) -> Vec2 {
+    app.world.get::<PhysObj>(player).unwrap().vel
+}
+
+fn update_for(app: &mut App, seconds: f32) {
+    for _ in 0..(seconds / TEST_DT).round() as usize {
+        app.update();
+    }
+}
+
+// From a standstill in the air, a dash goes the way the player last rolled, level until gravity
+// comes back
+#[test]
+fn dash_goes_the_last_rolled_way_and_flat() {
+    let mut app = test_app();
+    let map = Settings::default().input;
+    let dash = Dash::default();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 5000.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    // The physics starts on the second update
+    app.update();
+    tap(&mut app, map.spin_left);
+
+    tap(&mut app, map.dash);
+    let dashed = vel(&app, player);
+    assert_eq!(dashed.x, -dash.impulse / MASS);
+    update_for(&mut app, dash.duration - 2.0 * TEST_DT);
+    assert_eq!(vel(&app, player), dashed);
+
+    update_for(&mut app, 0.1);
+    assert!(vel(&app, player).y < dashed.y);
+    assert_eq!(vel(&app, player).x, dashed.x);
+}
+
+// Once in the air until it lands, and not again until the cooldown's done
+#[test]
+fn one_air_dash_refreshed_on_landing() {
+    let mut app = test_app();
+    let dash_key = Settings::default().input.dash;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    // About 2.2 s of falling
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 5000.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(100.0, 0.0);
+    app.update();
+
+    tap(&mut app, dash_key);
+    let dashed = vel(&app, player).x;
+    assert!(dashed > 100.0);
+    assert!(app.world.get::<Dash>(player).unwrap().cooling_down());
+
+    // Cooled down, but still in the same air
+    update_for(&mut app, 1.2);
+    assert!(!app.world.get::<Dash>(player).unwrap().cooling_down());
+    tap(&mut app, dash_key);
+    assert_eq!(vel(&app, player).x, dashed);
+
+    update_for(&mut app, 1.5);
+    let Collider::Ball {
+        touching_ground, ..
+    } = *app.world.get::<Collider>(player).unwrap();
+    assert!(touching_ground);
+    let rolling = vel(&app, player).x;
+    tap(&mut app, dash_key);
+    assert!(vel(&app, player).x > rolling + 0.5 * dashed);
+}
+
+// Gravity being off doesn't stop the floor: a dash heading down into it lands on it
+#[test]
+fn dash_into_the_floor_lands() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let player = spawn_test_player(
+        &mut app,
+        Vec2::new(0.0, config.floor_y + RADIUS + 20.0),
+        RADIUS,
+    );
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(0.0, -600.0);
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(Settings::default().input.dash);
+    for _ in 0..10 {
+        app.update();
+        let y = app.world.get::<Transform>(player).unwrap().translation.y;
+        assert!(y >= config.floor_y + RADIUS - 0.01, "{y}");
+    }
+    let Collider::Ball {
+        touching_ground, ..
+    } = *app.world.get::<Collider>(player).unwrap();
+    assert!(touching_ground);
+    assert!(vel(&app, player).x > 0.0);
+}
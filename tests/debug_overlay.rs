@@ -0,0 +1,72 @@
+#![cfg(feature = "debug-tools")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    debug::{
+        lines::DebugLines,
+        overlay::{PhysicsDebug, PhysicsDebugPlugin},
+    },
+    physics::PhysicsConfig,
+    settings::Settings,
+    testing::{press_key, spawn_test_ball, test_app},
+};
+
+fn overlay_app(bodies: usize) -> App {
+    let mut app = test_app();
+    app.init_resource::<DebugLines>()
+        .add_plugin(PhysicsDebugPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    for i in 0..bodies {
+        let position = Vec2::new(
+            (i % 20) as f32 * 50.0,
+            floor_y + 100.0 + (i / 20) as f32 * 50.0,
+        );
+        spawn_test_ball(&mut app, position, 20.0);
+    }
+    app
+}
+
+// Lines the overlay drew in the next update
+fn lines_drawn(app: &mut App) -> usize {
+    let before = app.world.resource::<DebugLines>().len();
+    app.update();
+    app.world.resource::<DebugLines>().len() - before
+}
+
+// Nothing is drawn until F3 turns the overlay on, then every body gets its vectors and outline,
+// until F3 turns it off again
+#[test]
+fn overlay_draws_only_while_toggled_on() {
+    for bodies in [0, 1, 100] {
+        let mut app = overlay_app(bodies);
+        app.update();
+        assert_eq!(lines_drawn(&mut app), 0, "{bodies}");
+
+        press_key(&mut app, KeyCode::F3, true);
+        let drawn = lines_drawn(&mut app);
+        assert!(app.world.resource::<PhysicsDebug>().enabled);
+        // At least the velocity, the acceleration and a circle's worth of segments each
+        assert!(drawn >= bodies * 34, "{bodies}: {drawn}");
+        press_key(&mut app, KeyCode::F3, false);
+        for _ in 0..10 {
+            assert!(lines_drawn(&mut app) >= bodies * 34);
+        }
+
+        press_key(&mut app, KeyCode::F3, true);
+        lines_drawn(&mut app);
+        assert!(!app.world.resource::<PhysicsDebug>().enabled);
+        assert_eq!(lines_drawn(&mut app), 0, "{bodies}");
+    }
+}
+
+#[test]
+fn overlay_starts_shown_when_the_settings_say_so() {
+    let mut app = test_app();
+    app.insert_resource(Settings {
+        debug_overlay: true,
+        ..default()
+    })
+    .init_resource::<DebugLines>()
+    .add_plugin(PhysicsDebugPlugin);
+    assert!(app.world.resource::<PhysicsDebug>().enabled);
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use bevy_game::{
+    launch::{headless_app, LaunchOptions},
+    physics::{PhysObj, SpawnOrder},
+    replay::ordered_state_hash,
+    settings::Settings,
+};
+
+const TICKS: usize = 600;
+const SNAPSHOT_INTERVAL: usize = 60;
+
+// Every body's state, bit for bit, in spawn order
+type Snapshot = Vec<(u64, Vec<u32>)>;
+
+fn snapshot(app: &mut App) -> Snapshot {
+    let mut bodies: Snapshot = app
+        .world
+        .query::<(&SpawnOrder, &Transform, &PhysObj)>()
+        .iter(&app.world)
+        .map(|(order, transform, phys_obj)| {
+            let values = [
+                transform.translation.x,
+                transform.translation.y,
+                transform.rotation.z,
+                transform.rotation.w,
+                phys_obj.vel.x,
+                phys_obj.vel.y,
+                phys_obj.acc.x,
+                phys_obj.acc.y,
+                phys_obj.acc_prev.x,
+                phys_obj.acc_prev.y,
+                phys_obj.angular_vel,
+                phys_obj.angular_acc,
+                phys_obj.angular_acc_prev,
+            ];
+            (
+                order.0,
+                values.iter().map(|value| value.to_bits()).collect(),
+            )
+        })
+        .collect();
+    bodies.sort_by_key(|(order, _)| *order);
+    bodies
+}
+
+// The keys held down on `tick`: rolling right, a jump, then rolling back left
+fn script(tick: usize) -> Vec<KeyCode> {
+    let map = Settings::default().input;
+    let mut keys = Vec::new();
+    if (60..240).contains(&tick) {
+        keys.push(map.spin_right);
+    }
+    if (300..306).contains(&tick) {
+        keys.push(map.jump);
+    }
+    if (360..480).contains(&tick) {
+        keys.push(map.spin_left);
+    }
+    keys
+}
+
+// A headless run with a pile of balls to bump into, played with the script, and a snapshot
+// every SNAPSHOT_INTERVAL ticks
+fn run() -> (Vec<Snapshot>, u64) {
+    let options = LaunchOptions {
+        seed: Some(42),
+        stress: Some(100),
+        ..default()
+    };
+    let mut app = headless_app(&options, f32::INFINITY);
+    let mut snapshots = Vec::new();
+    for tick in 1..=TICKS {
+        let held = script(tick);
+        let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+        keys.release_all();
+        for key in held {
+            keys.press(key);
+        }
+        app.update();
+        if tick % SNAPSHOT_INTERVAL == 0 {
+            snapshots.push(snapshot(&mut app));
+        }
+    }
+    let mut bodies = app.world.query::<(&SpawnOrder, &Transform, &PhysObj)>();
+    let hash = ordered_state_hash(bodies.iter(&app.world));
+    (snapshots, hash)
+}
+
+#[test]
+fn same_inputs_give_bit_identical_runs() {
+    let (first, first_hash) = run();
+    let (second, second_hash) = run();
+    assert_eq!(first.len(), TICKS / SNAPSHOT_INTERVAL);
+    // Something happened: the pile is there, and moved
+    assert!(first[0].len() >= 100);
+    assert_ne!(first[0], first[first.len() - 1]);
+    for (i, (first, second)) in first.iter().zip(&second).enumerate() {
+        assert!(
+            first == second,
+            "the runs differ at tick {}",
+            (i + 1) * SNAPSHOT_INTERVAL
+        );
+    }
+    assert_eq!(first_hash, second_hash);
+}
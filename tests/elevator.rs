@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use bevy_game::{
+    elevator::{Counterweight, Elevator, ElevatorMotion, ElevatorPlugin, ElevatorState},
+    testing::{set_test_ball_mass, set_test_ball_restitution, spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 10.0;
+const ELEVATOR: Elevator = Elevator {
+    top: 0.0,
+    bottom: -200.0,
+    speed: 100.0,
+    width: 100.0,
+    threshold: 5.0,
+};
+// The balls are dropped from just above the elevators' tops
+const DROP_Y: f32 = ELEVATOR.top + RADIUS + 1.0;
+
+fn elevator_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(ElevatorPlugin);
+    app
+}
+
+fn spawn_elevator(app: &mut App, x: f32) -> Entity {
+    app.world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_xyz(x, ELEVATOR.top, 0.0)),
+            ELEVATOR,
+        ))
+        .id()
+}
+
+// A Counterweight pair, the one at `a` at the top and the one at `b` at the bottom
+fn spawn_pair(app: &mut App, a: f32, b: f32) -> [Entity; 2] {
+    let ends = [app.world.spawn_empty().id(), app.world.spawn_empty().id()];
+    for (end, x, height, other) in [
+        (ends[0], a, ELEVATOR.top, ends[1]),
+        (ends[1], b, ELEVATOR.bottom, ends[0]),
+    ] {
+        app.world.entity_mut(end).insert((
+            TransformBundle::from_transform(Transform::from_xyz(x, height, 0.0)),
+            ELEVATOR,
+            Counterweight(other),
+        ));
+    }
+    ends
+}
+
+fn height(app: &App, entity: Entity) -> f32 {
+    app.world.get::<Transform>(entity).unwrap().translation.y
+}
+
+fn run(app: &mut App, updates: usize) {
+    for _ in 0..updates {
+        app.update();
+    }
+}
+
+#[test]
+fn elevators_sink_while_loaded_and_rise_when_not() {
+    use ElevatorMotion::*;
+    let middle = -100.0;
+    for (height, load, motion) in [
+        (ELEVATOR.top, 0.0, Still),
+        (ELEVATOR.top, 10.0, Descending),
+        (middle, 10.0, Descending),
+        (ELEVATOR.bottom, 10.0, Still),
+        (ELEVATOR.bottom, 0.0, Rising),
+        (middle, 0.0, Rising),
+    ] {
+        assert_eq!(ELEVATOR.motion(height, load), motion, "{height} {load}");
+    }
+    // Never past either end
+    assert_eq!(ELEVATOR.step(-199.0, Descending, 1.0), ELEVATOR.bottom);
+    assert_eq!(ELEVATOR.step(-1.0, Rising, 1.0), ELEVATOR.top);
+    assert_eq!(ELEVATOR.step(middle, Still, 1.0), middle);
+
+    // Carrying the ball down, and going back up once it's gone
+    let mut app = elevator_app();
+    let elevator = spawn_elevator(&mut app, 0.0);
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, DROP_Y), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    run(&mut app, 30);
+    let state = app.world.get::<ElevatorState>(elevator).unwrap();
+    assert_eq!(state.motion, Descending);
+    assert_eq!(state.riders, vec![ball]);
+    assert!(height(&app, elevator) < ELEVATOR.top);
+    assert!((height(&app, ball) - (height(&app, elevator) + RADIUS)).abs() < 1.0);
+
+    run(&mut app, 150);
+    assert_eq!(height(&app, elevator), ELEVATOR.bottom);
+    assert!((height(&app, ball) - (ELEVATOR.bottom + RADIUS)).abs() < 1.0);
+
+    app.world.despawn(ball);
+    run(&mut app, 30);
+    let state = app.world.get::<ElevatorState>(elevator).unwrap();
+    assert_eq!(state.motion, Rising);
+    assert!(state.riders.is_empty());
+    run(&mut app, 150);
+    assert_eq!(height(&app, elevator), ELEVATOR.top);
+}
+
+// A ball lighter than the threshold rests on an elevator without moving it, until another one
+// brings the load on it up to the threshold
+#[test]
+fn elevators_need_enough_mass_on_them_to_sink() {
+    let mut app = elevator_app();
+    let elevator = spawn_elevator(&mut app, 0.0);
+    let light = spawn_test_ball(&mut app, Vec2::new(-30.0, DROP_Y), RADIUS);
+    set_test_ball_restitution(&mut app, light, 0.5);
+    set_test_ball_mass(&mut app, light, 2.0);
+    run(&mut app, 90);
+    assert_eq!(height(&app, elevator), ELEVATOR.top);
+    assert!((height(&app, light) - (ELEVATOR.top + RADIUS)).abs() < 1.0);
+
+    let ball = spawn_test_ball(&mut app, Vec2::new(30.0, DROP_Y), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    set_test_ball_mass(&mut app, ball, 4.0);
+    run(&mut app, 30);
+    let state = app.world.get::<ElevatorState>(elevator).unwrap();
+    assert_eq!(state.riders.len(), 2);
+    assert!((state.load - 6.0).abs() < 1e-3);
+    assert!(height(&app, elevator) < ELEVATOR.top - 10.0);
+    // The first went down with it too
+    assert!((height(&app, light) - (height(&app, elevator) + RADIUS)).abs() < 1.0);
+}
+
+// As one of a pair sinks the other rises, and their heights always add up the same. Equal loads
+// leave them where they are.
+#[test]
+fn counterweights_move_opposite_ways() {
+    let mut app = elevator_app();
+    let [a, b] = spawn_pair(&mut app, 0.0, 300.0);
+    let sum = ELEVATOR.top + ELEVATOR.bottom;
+    let check = |app: &App| {
+        let heights = height(app, a) + height(app, b);
+        assert!((heights - sum).abs() < 1e-3, "{heights}");
+    };
+
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, DROP_Y), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    for _ in 0..180 {
+        app.update();
+        check(&app);
+    }
+    assert_eq!(height(&app, a), ELEVATOR.bottom);
+    assert_eq!(height(&app, b), ELEVATOR.top);
+
+    // Just as heavy, so neither goes anywhere
+    let ball = spawn_test_ball(&mut app, Vec2::new(300.0, DROP_Y), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    run(&mut app, 30);
+    assert_eq!(height(&app, a), ELEVATOR.bottom);
+    assert_eq!(height(&app, b), ELEVATOR.top);
+
+    // Now heavier, so it goes down and brings the other back up
+    let ball = spawn_test_ball(&mut app, Vec2::new(330.0, DROP_Y), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    for _ in 0..60 {
+        app.update();
+        check(&app);
+    }
+    assert!(height(&app, b) < ELEVATOR.top - 10.0);
+    let state = app.world.get::<ElevatorState>(a).unwrap();
+    assert_eq!(state.motion, ElevatorMotion::Rising);
+}
@@ -0,0 +1,26 @@
+// The inspector's egui needs the renderer, which test_app doesn't have, so builds with it leave
+// this out
+#![cfg(not(feature = "inspector"))]
+
+use bevy::asset::AssetPlugin;
+use bevy_game::{
+    features::{ActivePlugins, FeaturePlugins},
+    testing::test_app,
+};
+
+// Each feature's plugin is there exactly when the game is built with the feature. CI runs this
+// with `--no-default-features`, where none of them are.
+#[test]
+fn only_the_enabled_features_add_their_plugins() {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_plugin(FeaturePlugins);
+    let plugins = app.world.resource::<ActivePlugins>();
+    for (name, enabled) in [
+        ("DebugToolsPlugin", cfg!(feature = "debug-tools")),
+        ("InspectorPlugin", cfg!(feature = "inspector")),
+        ("GameAudioPlugin", cfg!(feature = "audio")),
+    ] {
+        assert_eq!(plugins.contains(name), enabled, "{name} in {plugins:?}");
+    }
+}
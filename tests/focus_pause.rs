@@ -0,0 +1,95 @@
+use bevy::{prelude::*, window::WindowFocused};
+use bevy_game::{
+    physics::{Gravity, PhysicsConfig},
+    player::Player,
+    settings::Settings,
+    state::{AppState, FocusPausePlugin},
+    testing::{press_key, spawn_test_player, test_app},
+};
+
+// Playing, with a player and a window to focus and unfocus
+fn focus_app(resume_on_focus: bool) -> (App, Entity) {
+    let mut app = test_app();
+    app.add_event::<WindowFocused>()
+        .insert_resource(Settings {
+            resume_on_focus,
+            ..default()
+        })
+        .add_plugin(FocusPausePlugin);
+    let window = app.world.spawn(Window::default()).id();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    spawn_test_player(&mut app, Vec2::new(0.0, floor_y + 100.0), 20.0);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+    (app, window)
+}
+
+fn state(app: &App) -> AppState {
+    app.world.resource::<State<AppState>>().0
+}
+
+fn focus(app: &mut App, window: Entity, focused: bool) {
+    app.world.send_event(WindowFocused { window, focused });
+    app.update();
+    app.update();
+}
+
+#[test]
+fn losing_focus_pauses() {
+    let (mut app, window) = focus_app(false);
+    focus(&mut app, window, false);
+    assert_eq!(state(&app), AppState::Paused);
+
+    // And the pause menu stays up when it comes back
+    focus(&mut app, window, true);
+    assert_eq!(state(&app), AppState::Paused);
+}
+
+#[test]
+fn focus_coming_back_can_resume() {
+    let (mut app, window) = focus_app(true);
+    focus(&mut app, window, false);
+    assert_eq!(state(&app), AppState::Paused);
+    focus(&mut app, window, true);
+    assert_eq!(state(&app), AppState::Playing);
+}
+
+// A game the player paused stays paused, even if it would resume after losing focus
+#[test]
+fn focus_never_resumes_a_pause_it_didnt_cause() {
+    let (mut app, window) = focus_app(true);
+    press_key(&mut app, KeyCode::Escape, true);
+    app.update();
+    press_key(&mut app, KeyCode::Escape, false);
+    app.update();
+    assert_eq!(state(&app), AppState::Paused);
+
+    focus(&mut app, window, false);
+    focus(&mut app, window, true);
+    assert_eq!(state(&app), AppState::Paused);
+}
+
+// Keys held when focus goes are let go, as their release won't be heard: zero gravity held then
+// doesn't stay on
+#[test]
+fn held_keys_are_released_when_focus_goes() {
+    let (mut app, window) = focus_app(true);
+    let zero_gravity = app.world.resource::<Settings>().input.zero_gravity;
+    press_key(&mut app, zero_gravity, true);
+    press_key(&mut app, KeyCode::Space, true);
+    app.update();
+    app.update();
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+    assert!(app.world.get::<Gravity>(player).is_none());
+
+    focus(&mut app, window, false);
+    let keys = app.world.resource::<Input<KeyCode>>();
+    assert_eq!(keys.get_pressed().count(), 0);
+    focus(&mut app, window, true);
+    app.update();
+    assert!(app.world.get::<Gravity>(player).is_some());
+}
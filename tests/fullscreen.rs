@@ -0,0 +1,58 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use bevy_game::{
+    fullscreen::{toggled_mode, FullscreenPlugin},
+    testing::{press_key, test_app},
+};
+
+// Going fullscreen and the browser leaving it on Escape need a real window to check by hand: press
+// F11, then Escape, then F11 again, and the game should go fullscreen each time.
+
+#[test]
+fn f11_switches_between_windowed_and_borderless() {
+    assert_eq!(
+        toggled_mode(WindowMode::Windowed),
+        WindowMode::BorderlessFullscreen
+    );
+    assert_eq!(
+        toggled_mode(WindowMode::BorderlessFullscreen),
+        WindowMode::Windowed
+    );
+    // Whatever fullscreen the window was put in some other way, F11 leaves it
+    assert_eq!(toggled_mode(WindowMode::Fullscreen), WindowMode::Windowed);
+    assert_eq!(
+        toggled_mode(WindowMode::SizedFullscreen),
+        WindowMode::Windowed
+    );
+}
+
+fn mode(app: &App, window: Entity) -> WindowMode {
+    app.world.get::<Window>(window).unwrap().mode
+}
+
+// Each press toggles the primary window, and only that one
+#[test]
+fn f11_toggles_the_primary_window() {
+    let mut app = test_app();
+    app.add_plugin(FullscreenPlugin);
+    let primary = app.world.spawn((Window::default(), PrimaryWindow)).id();
+    let other = app.world.spawn(Window::default()).id();
+
+    for expected in [
+        WindowMode::BorderlessFullscreen,
+        WindowMode::Windowed,
+        WindowMode::BorderlessFullscreen,
+    ] {
+        press_key(&mut app, KeyCode::F11, true);
+        app.update();
+        assert_eq!(mode(&app, primary), expected);
+        // Held down, it doesn't toggle again
+        app.update();
+        assert_eq!(mode(&app, primary), expected);
+        press_key(&mut app, KeyCode::F11, false);
+        app.update();
+        assert_eq!(mode(&app, other), WindowMode::Windowed);
+    }
+}
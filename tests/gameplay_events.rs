@@ -0,0 +1,179 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{BounceEvent, Collider, CollisionEvent, LandedEvent, PhysicsConfig},
+    player::{JumpEvent, Player},
+    settings::Settings,
+    testing::{press_key, spawn_test_ball, spawn_test_player, test_app},
+};
+
+const RADIUS: f32 = 20.0;
+
+// Readers for every event, counting them per update
+#[derive(Default)]
+struct Readers {
+    bounces: ManualEventReader<BounceEvent>,
+    landings: ManualEventReader<LandedEvent>,
+    collisions: ManualEventReader<CollisionEvent>,
+    jumps: ManualEventReader<JumpEvent>,
+    jump_impulses: Vec<f32>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Counts {
+    bounces: usize,
+    landings: usize,
+    collisions: usize,
+    jumps: usize,
+}
+
+impl Readers {
+    fn update(&mut self, app: &mut App) -> Counts {
+        app.update();
+        let world = &app.world;
+        Counts {
+            bounces: self.bounces.iter(world.resource()).count(),
+            landings: self.landings.iter(world.resource()).count(),
+            collisions: self.collisions.iter(world.resource()).count(),
+            jumps: self
+                .jumps
+                .iter(world.resource::<Events<JumpEvent>>())
+                .map(|jump| self.jump_impulses.push(jump.impulse))
+                .count(),
+        }
+    }
+}
+
+fn touching_ground(app: &App, ball: Entity) -> bool {
+    let Collider::Ball {
+        touching_ground, ..
+    } = *app.world.get::<Collider>(ball).unwrap();
+    touching_ground
+}
+
+// A ball dropped with some bounce to it: nothing while it falls, a bounce and a landing for the
+// first impact, a landing each time it comes back down after leaving the floor, and nothing once
+// it's resting
+#[test]
+fn drop_bounce_rest_sends_each_event_once() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 200.0), RADIUS);
+    let Collider::Ball {
+        coef_of_restitution,
+        ..
+    } = app.world.get_mut::<Collider>(ball).unwrap().into_inner();
+    *coef_of_restitution = 0.5;
+
+    let mut readers = Readers::default();
+    let mut first_impact = None;
+    let mut touchdowns = 0;
+    let mut total = Counts::default();
+    let mut was_touching = false;
+    for frame in 0..300 {
+        let counts = readers.update(&mut app);
+        let touching = touching_ground(&app, ball);
+        if touching && !was_touching {
+            touchdowns += 1;
+        }
+        was_touching = touching;
+
+        if first_impact.is_none() && counts != Counts::default() {
+            first_impact = Some(frame);
+            assert_eq!(
+                counts,
+                Counts {
+                    bounces: 1,
+                    landings: 1,
+                    collisions: 1,
+                    jumps: 0,
+                }
+            );
+        }
+        assert!(
+            counts.bounces <= 1 && counts.landings <= 1,
+            "{frame}: {counts:?}"
+        );
+        total.bounces += counts.bounces;
+        total.landings += counts.landings;
+        total.collisions += counts.collisions;
+    }
+    // 200 down at 2000 takes about 0.45 s
+    let first_impact = first_impact.unwrap();
+    assert!((25..=29).contains(&first_impact), "{first_impact}");
+    assert_eq!(total.landings, touchdowns);
+    assert!(total.landings > 1, "{total:?}");
+    assert!(total.bounces >= total.landings, "{total:?}");
+    assert_eq!(total.collisions, total.bounces);
+
+    // Resting now, so nothing more
+    assert!(touching_ground(&app, ball));
+    for _ in 0..60 {
+        let counts = readers.update(&mut app);
+        assert_eq!((counts.bounces, counts.landings), (0, 0));
+    }
+}
+
+#[test]
+fn bounces_report_their_speed_and_restitution() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let height = 200.0;
+    let ball = spawn_test_ball(
+        &mut app,
+        Vec2::new(0.0, config.floor_y + RADIUS + height),
+        RADIUS,
+    );
+    let Collider::Ball {
+        coef_of_restitution,
+        ..
+    } = app.world.get_mut::<Collider>(ball).unwrap().into_inner();
+    *coef_of_restitution = 0.5;
+
+    let mut reader = ManualEventReader::<BounceEvent>::default();
+    let bounce = loop {
+        app.update();
+        if let Some(bounce) = reader.iter(app.world.resource()).next() {
+            break (
+                bounce.entity,
+                bounce.impact_speed,
+                bounce.restitution_applied,
+            );
+        }
+    };
+    let expected_speed = (2.0 * config.gravity * height).sqrt();
+    assert_eq!(bounce.0, ball);
+    assert!(
+        (bounce.1 - expected_speed).abs() < 0.01 * expected_speed,
+        "{} {expected_speed}",
+        bounce.1
+    );
+    assert_eq!(bounce.2, 0.5 * config.floor_restitution);
+}
+
+// A jump sends one JumpEvent with its impulse, and coming back down one LandedEvent
+#[test]
+fn jumps_and_landings_are_sent_once() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..default()
+    });
+    let mut readers = Readers::default();
+    readers.update(&mut app);
+    readers.update(&mut app);
+
+    let jump = Settings::default().input.jump;
+    press_key(&mut app, jump, true);
+    let mut total = readers.update(&mut app);
+    press_key(&mut app, jump, false);
+    for _ in 0..120 {
+        let counts = readers.update(&mut app);
+        total.jumps += counts.jumps;
+        total.landings += counts.landings;
+    }
+    assert_eq!(total.jumps, 1);
+    assert_eq!(total.landings, 1);
+    assert_eq!(readers.jump_impulses, [Player::default().jump_impulse]);
+}
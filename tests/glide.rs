@@ -0,0 +1,116 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::{GlideConfig, GlideEvent, Gliding, JumpCharge, JumpEvent, Player},
+    testing::{spawn_test_player, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+
+fn hold_jump(app: &mut App, held: bool) {
+    let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+    if held {
+        keys.press(KeyCode::Space);
+    } else {
+        keys.release(KeyCode::Space);
+    }
+}
+
+fn phys_obj(app: &App, entity: Entity) -> &PhysObj {
+    app.world.get::<PhysObj>(entity).unwrap()
+}
+
+#[test]
+fn gliding_caps_the_fall_speed() {
+    let mut app = test_app();
+    let fall_speed = app.world.resource::<GlideConfig>().fall_speed;
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 5000.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    let mut reader = ManualEventReader::<GlideEvent>::default();
+    let mut glides = 0;
+    hold_jump(&mut app, true);
+
+    for _ in 0..3 * 60 {
+        app.update();
+        glides += reader
+            .iter(app.world.resource::<Events<GlideEvent>>())
+            .count();
+        let vel = phys_obj(&app, player).vel.y;
+        assert!(vel >= -fall_speed - 0.5 * gravity * TEST_DT, "{vel}");
+    }
+    assert_eq!(glides, 1);
+    assert!(app.world.get::<Gliding>(player).is_some());
+    // Held at the cap, not bobbing under it
+    assert!((phys_obj(&app, player).vel.y + fall_speed).abs() < 1e-3);
+
+    // Letting go falls freely again
+    hold_jump(&mut app, false);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(app.world.get::<Gliding>(player).is_none());
+    assert!(phys_obj(&app, player).vel.y < -2.0 * fall_speed);
+}
+
+#[test]
+fn spin_steers_the_glide() {
+    let drift = |angular_vel: f32| {
+        let mut app = test_app();
+        let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+        let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 5000.0), RADIUS);
+        app.world.entity_mut(player).insert(Player {
+            torque: 0.0,
+            ..Default::default()
+        });
+        app.world.get_mut::<PhysObj>(player).unwrap().angular_vel = angular_vel;
+        hold_jump(&mut app, true);
+        for _ in 0..60 {
+            app.update();
+        }
+        app.world.get::<Transform>(player).unwrap().translation.x
+    };
+
+    // Counter-clockwise spin drifts right while falling, like a ball with backspin rises
+    assert!(drift(10.0) > 10.0);
+    assert!(drift(-10.0) < -10.0);
+    assert_eq!(drift(0.0), 0.0);
+}
+
+// Jump held from the glide down to the ground charges a jump as soon as the ball lands, which goes
+// off when it's let go
+#[test]
+fn gliding_keeps_the_held_jump_for_landing() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 100.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    let mut reader = ManualEventReader::<JumpEvent>::default();
+    hold_jump(&mut app, true);
+
+    let mut glided = false;
+    for _ in 0..2 * 60 {
+        app.update();
+        glided |= app.world.get::<Gliding>(player).is_some();
+        if let Some(&JumpCharge::Charging(_)) = app.world.get::<JumpCharge>(player) {
+            break;
+        }
+    }
+    assert!(glided);
+    assert!(app.world.get::<Gliding>(player).is_none());
+    let jumps = app.world.resource::<Events<JumpEvent>>();
+    assert_eq!(reader.iter(jumps).count(), 0);
+
+    hold_jump(&mut app, false);
+    app.update();
+    let jumps = app.world.resource::<Events<JumpEvent>>();
+    assert_eq!(reader.iter(jumps).count(), 1);
+    assert!(phys_obj(&app, player).vel.y > 0.0);
+}
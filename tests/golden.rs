@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysObj, PhysicsConfig},
+    testing::{spawn_test_ball, test_app},
+};
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/golden_trajectory.ron"
+);
+const RADIUS: f32 = 25.0;
+const STEPS: usize = 240;
+const SAMPLE_INTERVAL: usize = 10;
+// Far below anything a change to the physics would move the ball by, far above the rounding that
+// differs between platforms
+const TOLERANCE: f32 = 1e-3;
+
+// x, y, angle, velocity and angular velocity
+type Sample = [f32; 6];
+
+// A ball thrown up and to the right, which bounces a few times and rolls away, sampled every
+// SAMPLE_INTERVAL updates at TEST_DT
+fn trajectory() -> Vec<Sample> {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(-300.0, floor_y + 200.0), RADIUS);
+    let mut collider = app.world.get_mut::<Collider>(ball).unwrap();
+    let Collider::Ball {
+        coef_of_restitution,
+        ..
+    } = &mut *collider;
+    *coef_of_restitution = 0.6;
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.vel = Vec2::new(200.0, 300.0);
+    phys_obj.angular_vel = 2.0;
+
+    let mut samples = Vec::new();
+    for step in 1..=STEPS {
+        app.update();
+        if step % SAMPLE_INTERVAL == 0 {
+            let transform = app.world.get::<Transform>(ball).unwrap();
+            let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+            let (axis, angle) = transform.rotation.to_axis_angle();
+            samples.push([
+                transform.translation.x,
+                transform.translation.y,
+                angle * axis.z.signum(),
+                phys_obj.vel.x,
+                phys_obj.vel.y,
+                phys_obj.angular_vel,
+            ]);
+        }
+    }
+    samples
+}
+
+// Guards the simulation against changing under refactors. After a deliberate change to the
+// physics, record the new trajectory with `BLESS_GOLDEN=1 cargo test --test golden` and commit the
+// fixture along with the change.
+#[test]
+fn trajectory_matches_the_recorded_one() {
+    let samples = trajectory();
+    if std::env::var_os("BLESS_GOLDEN").is_some() {
+        let recorded = ron::ser::to_string_pretty(&samples, default()).unwrap();
+        std::fs::write(FIXTURE, recorded).unwrap();
+        return;
+    }
+
+    let recorded = std::fs::read_to_string(FIXTURE).unwrap_or_else(|_| {
+        panic!("No trajectory recorded at {FIXTURE}, record it with BLESS_GOLDEN=1")
+    });
+    let recorded: Vec<Sample> = ron::from_str(&recorded).unwrap();
+    assert_eq!(samples.len(), recorded.len());
+    for (i, (sample, expected)) in samples.iter().zip(&recorded).enumerate() {
+        for (value, recorded_value) in sample.iter().zip(expected) {
+            assert!(
+                (value - recorded_value).abs() <= TOLERANCE * recorded_value.abs().max(1.0),
+                "Update {}: {sample:?}, recorded {expected:?}",
+                (i + 1) * SAMPLE_INTERVAL
+            );
+        }
+    }
+}
+
+// The same inputs take the same path every time, so the recording means something
+#[test]
+fn trajectory_is_repeatable() {
+    assert_eq!(trajectory(), trajectory());
+}
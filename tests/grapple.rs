@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy_game::{
+    grapple::{GrappleAim, GrappleConfig, GrapplePlugin, Grappled},
+    physics::{raycast::raycast, PhysObj, PhysicsConfig},
+    player::Player,
+    settings::Settings,
+    testing::{spawn_test_player, test_app, TEST_DT},
+    trigger::Door,
+};
+
+const RADIUS: f32 = 25.0;
+const DOOR_X: f32 = 300.0;
+
+#[test]
+fn raycast_hits_the_nearest_segment() {
+    let near = Entity::from_raw(1);
+    let far = Entity::from_raw(2);
+    let segments = [
+        (far, Vec2::new(-10.0, 200.0), Vec2::new(10.0, 200.0)),
+        (near, Vec2::new(-10.0, 100.0), Vec2::new(10.0, 100.0)),
+        // Along the ray
+        (near, Vec2::new(0.0, 10.0), Vec2::new(0.0, 50.0)),
+    ];
+    let hit = raycast(Vec2::ZERO, Vec2::Y, 500.0, segments).unwrap();
+    assert_eq!(hit.entity, near);
+    assert_eq!(hit.point, Vec2::new(0.0, 100.0));
+    assert_eq!(hit.distance, 100.0);
+
+    assert_eq!(raycast(Vec2::ZERO, Vec2::Y, 50.0, segments), None);
+    assert_eq!(raycast(Vec2::ZERO, Vec2::NEG_Y, 500.0, segments), None);
+    let wide = Vec2::new(0.2, 1.0).normalize();
+    assert_eq!(raycast(Vec2::ZERO, wide, 500.0, segments), None);
+}
+
+// A player in the air, and a door over to its right to hang from
+fn grapple_app() -> (App, Entity) {
+    let mut app = test_app();
+    app.add_plugin(GrapplePlugin);
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(DOOR_X, config.floor_y, 0.0)),
+        Door {
+            height: 800.0,
+            open: false,
+        },
+    ));
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, config.floor_y + 400.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    // The physics starts on the second update
+    app.update();
+    (app, player)
+}
+
+fn aim(app: &mut App, held: bool, target: Vec2) {
+    *app.world.resource_mut::<GrappleAim>() = GrappleAim {
+        held,
+        target: Some(target),
+    };
+}
+
+fn distance_to_anchor(app: &App, player: Entity) -> f32 {
+    let anchor = app.world.get::<Grappled>(player).unwrap().anchor;
+    let position = app.world.get::<Transform>(player).unwrap().translation;
+    position.truncate().distance(anchor)
+}
+
+#[test]
+fn swings_from_the_hit_and_keeps_the_swing_when_let_go() {
+    let (mut app, player) = grapple_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+
+    // Nothing up there
+    aim(&mut app, true, Vec2::new(0.0, floor_y + 2000.0));
+    app.update();
+    assert!(app.world.get::<Grappled>(player).is_none());
+    aim(&mut app, false, Vec2::ZERO);
+    app.update();
+
+    // The door's left side, higher up
+    aim(&mut app, true, Vec2::new(DOOR_X, floor_y + 600.0));
+    app.update();
+    let grappled = *app.world.get::<Grappled>(player).unwrap();
+    assert!(grappled.anchor.x < DOOR_X);
+    assert!(grappled.anchor.y > floor_y + 400.0);
+    for _ in 0..30 {
+        app.update();
+        assert!(distance_to_anchor(&app, player) <= grappled.length + 0.01);
+    }
+    let swing = app.world.get::<PhysObj>(player).unwrap().vel;
+    assert!(swing.x > 100.0, "{swing}");
+
+    aim(&mut app, false, Vec2::ZERO);
+    app.update();
+    assert!(app.world.get::<Grappled>(player).is_none());
+    let released = app.world.get::<PhysObj>(player).unwrap().vel;
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(app.world.get::<PhysObj>(player).unwrap().vel.x, released.x);
+}
+
+#[test]
+fn climbing_reels_the_line_in() {
+    let (mut app, player) = grapple_app();
+    let config = app.world.resource::<GrappleConfig>().clone();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    aim(&mut app, true, Vec2::new(DOOR_X, floor_y + 600.0));
+    app.update();
+    let length = app.world.get::<Grappled>(player).unwrap().length;
+
+    let climb_up = Settings::default().input.climb_up;
+    app.world.resource_mut::<Input<KeyCode>>().press(climb_up);
+    let updates = 30;
+    for _ in 0..updates {
+        app.update();
+    }
+    let reeled = app.world.get::<Grappled>(player).unwrap().length;
+    let expected = length - config.reel_speed * updates as f32 * TEST_DT;
+    assert!((reeled - expected).abs() < 1.0, "{reeled} {expected}");
+    assert!(distance_to_anchor(&app, player) <= reeled + 0.01);
+}
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, Gravity, PhysObj, PhysicsConfig},
+    player::Player,
+    settings::Settings,
+    testing::{
+        set_test_ball_restitution, spawn_test_ball, spawn_test_player, test_app, TEST_BALL_MASS,
+        TEST_DT,
+    },
+};
+
+const RADIUS: f32 = 25.0;
+
+fn height(app: &App, ball: Entity) -> f32 {
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.get::<Transform>(ball).unwrap().translation.y - RADIUS - floor_y
+}
+
+fn press(app: &mut App, key: KeyCode, pressed: bool) {
+    let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+    if pressed {
+        keys.press(key);
+    } else {
+        keys.release(key);
+    }
+}
+
+// The height of the next peak, once the ball is on its way up again
+fn next_peak(app: &mut App, ball: Entity) -> f32 {
+    let mut rising = false;
+    for _ in 0..600 {
+        app.update();
+        let vel = app.world.get::<PhysObj>(ball).unwrap().vel;
+        if vel.y > 0.0 {
+            rising = true;
+        } else if rising {
+            return height(app, ball);
+        }
+    }
+    panic!("no peak");
+}
+
+#[test]
+fn drop_bounces_back_to_the_restitution_squared_height() {
+    let mut app = test_app();
+    let dropped_from = 400.0;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(
+        &mut app,
+        Vec2::new(0.0, floor_y + RADIUS + dropped_from),
+        RADIUS,
+    );
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    let config = app.world.resource::<PhysicsConfig>();
+    let restitution = config
+        .restitution_combine
+        .combine(0.5, config.floor_restitution);
+
+    let peak = next_peak(&mut app, ball);
+    let expected = restitution.powi(2) * dropped_from;
+    assert!(
+        (peak - expected).abs() < 0.03 * expected,
+        "{peak} {expected}"
+    );
+}
+
+// A rolling disk loses the rolling resistance's power, r·g·m·v, from its kinetic energy of
+// ¾·m·v², so it slows down at ⅔·r·g and stops after ¾·v²/(r·g)
+#[test]
+fn rolling_resistance_stops_a_ball_at_the_expected_distance() {
+    let speed = 200.0;
+    let stopping_distance = |resistance: f32| {
+        let mut app = test_app();
+        app.world.resource_mut::<PhysicsConfig>().rolling_resistance = resistance;
+        let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+        let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+        let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+        phys_obj.vel.x = speed;
+        phys_obj.angular_vel = -speed / RADIUS;
+        for _ in 0..600 {
+            app.update();
+        }
+        let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+        assert!(phys_obj.vel.x.abs() < 1.0, "{}", phys_obj.vel);
+        app.world.get::<Transform>(ball).unwrap().translation.x
+    };
+
+    let gravity = PhysicsConfig::default().gravity;
+    for resistance in [0.05, 0.1] {
+        let expected = 0.75 * speed.powi(2) / (resistance * gravity);
+        let distance = stopping_distance(resistance);
+        assert!(
+            (distance - expected).abs() < 0.1 * expected,
+            "{resistance}: {distance} {expected}"
+        );
+    }
+}
+
+#[test]
+fn jump_reaches_the_apex_of_its_launch_speed() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    let jump = Settings::default().input.jump;
+    // The physics starts on the second update
+    app.update();
+    press(&mut app, jump, true);
+    app.update();
+    press(&mut app, jump, false);
+
+    let apex = next_peak(&mut app, player);
+    let launch_speed = Player::default().jump_impulse / TEST_BALL_MASS;
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let expected = launch_speed.powi(2) / (2.0 * gravity);
+    assert!(
+        (apex - expected).abs() < 0.02 * expected,
+        "{apex} {expected}"
+    );
+}
+
+#[test]
+fn ball_resting_on_the_floor_stays_put() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    for _ in 0..600 {
+        app.update();
+        assert!(height(&app, ball).abs() < 1e-3, "{}", height(&app, ball));
+        let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+        assert!(phys_obj.vel.length() < 1e-3, "{}", phys_obj.vel);
+        let Collider::Ball {
+            touching_ground, ..
+        } = *app.world.get::<Collider>(ball).unwrap();
+        assert!(touching_ground);
+    }
+}
+
+#[test]
+fn zero_gravity_holds_the_player_up_until_released() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 300.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    let key = Settings::default().input.zero_gravity;
+    press(&mut app, key, true);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world.get::<Gravity>(player).is_none());
+
+    // Whatever speed it had when gravity went off, it keeps
+    let vel = app.world.get::<PhysObj>(player).unwrap().vel;
+    let start = height(&app, player);
+    for step in 1..=30 {
+        app.update();
+        let expected = start + vel.y * step as f32 * TEST_DT;
+        assert!((height(&app, player) - expected).abs() < 0.01);
+    }
+
+    press(&mut app, key, false);
+    app.update();
+    assert!(app.world.get::<Gravity>(player).is_some());
+    let held_at = height(&app, player);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(height(&app, player) < held_at - 10.0);
+}
@@ -0,0 +1,152 @@
+use bevy::{asset::AssetPlugin, ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    help::{controls_text, latch_hint, HelpPlugin, Hint},
+    physics::{PhysObj, PhysicsConfig},
+    settings::{InputAction, Settings},
+    testing::{press_key, spawn_test_player, test_app, TEST_DT},
+    toast::ToastEvent,
+};
+
+// One line an action, with its keys as they're bound now, then the keys that can't be rebound
+#[test]
+fn the_controls_list_follows_the_bindings() {
+    let mut settings = Settings::default();
+    let text = controls_text(&settings.input);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), InputAction::ALL.len() + 3);
+    assert_eq!(lines[0], format!("Space: {}", InputAction::Jump.label()));
+    assert!(lines.contains(&"Escape: pause"), "{text}");
+
+    settings.input.bind_swapping(InputAction::Jump, KeyCode::W);
+    settings.input.add_key(InputAction::SpinLeft, KeyCode::Left);
+    let text = controls_text(&settings.input);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], format!("W: {}", InputAction::Jump.label()));
+    assert!(
+        lines.contains(&format!("Space: {}", InputAction::ClimbUp.label()).as_str()),
+        "{text}"
+    );
+    assert!(
+        lines.contains(&format!("A / Left: {}", InputAction::SpinLeft.label()).as_str()),
+        "{text}"
+    );
+}
+
+#[test]
+fn hints_latch_once() {
+    let mut shown = Vec::new();
+    assert!(latch_hint(&mut shown, Hint::Spin));
+    assert!(!latch_hint(&mut shown, Hint::Spin));
+    assert_eq!(shown, [Hint::Spin]);
+}
+
+// Playing, with the player resting on the floor
+fn help_app(settings: Settings) -> (App, Entity) {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .insert_resource(settings)
+        .add_plugin(HelpPlugin);
+    let radius = 20.0;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + radius), radius);
+    app.update();
+    app.update();
+    (app, player)
+}
+
+// Whether the controls overlay is up
+fn help_shown(app: &mut App) -> bool {
+    let visibility = app
+        .world
+        .query_filtered::<&Visibility, (With<Node>, Without<Parent>)>()
+        .single(&app.world);
+    *visibility != Visibility::Hidden
+}
+
+fn tap(app: &mut App, key: KeyCode) {
+    press_key(app, key, true);
+    app.update();
+    press_key(app, key, false);
+    app.update();
+}
+
+// Shown until the player first plays, which counts as having seen it, and brought back with H
+#[test]
+fn the_controls_show_on_the_first_launch() {
+    let (mut app, _) = help_app(Settings::default());
+    assert!(help_shown(&mut app));
+    tap(&mut app, KeyCode::M);
+    assert!(help_shown(&mut app));
+
+    tap(&mut app, KeyCode::Space);
+    assert!(!help_shown(&mut app));
+    assert!(app.world.resource::<Settings>().controls_help_seen);
+    app.update();
+    assert!(!help_shown(&mut app));
+
+    tap(&mut app, KeyCode::H);
+    assert!(help_shown(&mut app));
+    tap(&mut app, KeyCode::H);
+    assert!(!help_shown(&mut app));
+}
+
+#[test]
+fn the_controls_stay_hidden_once_seen() {
+    let (mut app, _) = help_app(Settings {
+        controls_help_seen: true,
+        ..default()
+    });
+    assert!(!help_shown(&mut app));
+}
+
+fn hints(app: &App, reader: &mut ManualEventReader<ToastEvent>) -> Vec<String> {
+    reader
+        .iter(app.world.resource::<Events<ToastEvent>>())
+        .map(|toast| toast.0.clone())
+        .collect()
+}
+
+// Standing still for three seconds shows the spin hint, once ever
+#[test]
+fn the_spin_hint_is_shown_once() {
+    let (mut app, _) = help_app(Settings::default());
+    let mut reader = ManualEventReader::<ToastEvent>::default();
+    let mut toasts = Vec::new();
+    let mut shown_after = None;
+    for frame in 0..(6.0 / TEST_DT) as usize {
+        app.update();
+        let new = hints(&app, &mut reader);
+        if !new.is_empty() && shown_after.is_none() {
+            shown_after = Some(frame as f32 * TEST_DT);
+        }
+        toasts.extend(new);
+    }
+    assert_eq!(toasts, ["Hold A or D to spin and roll"]);
+    let shown_after = shown_after.unwrap();
+    assert!((2.9..3.2).contains(&shown_after), "{shown_after}");
+    assert_eq!(app.world.resource::<Settings>().hints_shown, [Hint::Spin]);
+
+    // Not even in the next session
+    let (mut app, _) = help_app(Settings {
+        hints_shown: vec![Hint::Spin],
+        ..default()
+    });
+    let mut reader = ManualEventReader::<ToastEvent>::default();
+    for _ in 0..(6.0 / TEST_DT) as usize {
+        app.update();
+        assert!(hints(&app, &mut reader).is_empty());
+    }
+}
+
+// Moving resets the wait
+#[test]
+fn moving_puts_the_hint_off() {
+    let (mut app, player) = help_app(Settings::default());
+    for _ in 0..4 {
+        for _ in 0..(2.0 / TEST_DT) as usize {
+            app.update();
+        }
+        app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 100.0;
+    }
+    assert!(app.world.resource::<Settings>().hints_shown.is_empty());
+}
@@ -0,0 +1,327 @@
+use bevy::prelude::*;
+use bevy_game::{
+    hills::{
+        chunks_around, HillsChunk, HillsCoin, HillsPlugin, HillsRun, HillsTerrain, HILLS_AHEAD,
+        HILLS_BEHIND, HILLS_CHUNK_SEGMENTS, HILLS_CHUNK_WIDTH,
+    },
+    level::{LevelPlugin, RestartLevelEvent},
+    physics::{heightfield::Heightfield, Collider, PhysObj, PhysicsConfig},
+    player::Player,
+    progress::Score,
+    rng::GameRng,
+    testing::{spawn_test_ball, test_app, TEST_DT},
+};
+
+const SEED: u64 = 42;
+
+// The hills, with the player and a camera at the start
+fn hills_app() -> App {
+    let mut app = test_app();
+    app.insert_resource(GameRng::new(SEED))
+        .add_plugin(LevelPlugin)
+        .add_plugin(HillsPlugin);
+    app.world
+        .spawn((TransformBundle::default(), Camera2d::default()));
+    app.update();
+    app.update();
+    app.update();
+    app
+}
+
+fn despawn_players(app: &mut App) {
+    let players: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(&app.world)
+        .collect();
+    assert_eq!(players.len(), 1);
+    for player in players {
+        app.world.despawn(player);
+    }
+}
+
+// The chunks' heightfields, and where they start, from left to right
+fn ground(app: &mut App) -> Vec<(i64, Vec2, Heightfield)> {
+    let mut ground: Vec<_> = app
+        .world
+        .query::<(&HillsChunk, &Transform, &Heightfield)>()
+        .iter(&app.world)
+        .map(|(chunk, transform, heightfield)| {
+            (
+                chunk.0,
+                transform.translation.truncate(),
+                heightfield.clone(),
+            )
+        })
+        .collect();
+    ground.sort_by_key(|(index, ..)| *index);
+    ground
+}
+
+#[test]
+fn the_same_seed_makes_the_same_chunks() {
+    let terrain = HillsTerrain::new(SEED);
+    // Made in another order, from another HillsTerrain
+    let chunks: Vec<_> = (-3..20).rev().map(|index| terrain.chunk(index)).collect();
+    let again = HillsTerrain::new(SEED);
+    for (chunk, index) in chunks.iter().zip((-3..20).rev()) {
+        assert_eq!(*chunk, again.chunk(index), "{index}");
+        assert_eq!(chunk.heights.len(), HILLS_CHUNK_SEGMENTS + 1);
+    }
+    // There's something to collect and avoid
+    assert!(chunks.iter().any(|chunk| !chunk.coins.is_empty()));
+    assert!(chunks.iter().any(|chunk| !chunk.saws.is_empty()));
+
+    let other = HillsTerrain::new(SEED + 1);
+    assert_ne!(terrain.chunk(0).heights, other.chunk(0).heights);
+}
+
+// Each chunk ends at the height the next one starts at, so there are no steps or gaps at the
+// seams, and the chunks of the run line up with no space between them
+#[test]
+fn the_ground_is_continuous_across_chunks() {
+    let terrain = HillsTerrain::new(SEED);
+    for index in -5..50 {
+        let (chunk, next) = (terrain.chunk(index), terrain.chunk(index + 1));
+        assert_eq!(chunk.heights.last(), next.heights.first(), "{index}");
+    }
+
+    let mut app = hills_app();
+    let ground = ground(&mut app);
+    assert!(ground.len() > 2, "{}", ground.len());
+    for pair in ground.windows(2) {
+        let [(index, origin, heightfield), (next_index, next_origin, next)] = pair else {
+            unreachable!();
+        };
+        assert_eq!(*next_index, index + 1);
+        assert_eq!(origin.x + heightfield.width(), next_origin.x);
+        assert_eq!(origin.y, next_origin.y);
+        assert_eq!(heightfield.heights.last(), next.heights.first());
+    }
+}
+
+// As the camera goes right, chunks come in ahead of it and the ones far enough behind it go, with
+// their coins and saws. There's always ground all the way between the margins.
+#[test]
+fn chunks_come_in_ahead_and_go_beyond_the_trailing_margin() {
+    let mut app = hills_app();
+    despawn_players(&mut app);
+    let camera = app
+        .world
+        .query_filtered::<Entity, With<Camera2d>>()
+        .single(&app.world);
+
+    let mut x = 0.0;
+    let mut gone = false;
+    for _ in 0..300 {
+        x += 50.0;
+        app.world
+            .get_mut::<Transform>(camera)
+            .unwrap()
+            .translation
+            .x = x;
+        app.update();
+
+        let ground = ground(&mut app);
+        let indices: Vec<i64> = ground.iter().map(|(index, ..)| *index).collect();
+        let wanted: Vec<i64> = chunks_around(x).collect();
+        assert_eq!(indices, wanted, "{x}");
+        let (left, right) = (
+            ground[0].1.x,
+            ground.last().unwrap().1.x + HILLS_CHUNK_WIDTH,
+        );
+        assert!(left <= x - HILLS_BEHIND && right >= x + HILLS_AHEAD);
+        assert!(
+            left > x - HILLS_BEHIND - 2.0 * HILLS_CHUNK_WIDTH,
+            "{left} {x}"
+        );
+
+        let items: Vec<i64> = app
+            .world
+            .query_filtered::<&HillsChunk, Without<Heightfield>>()
+            .iter(&app.world)
+            .map(|chunk| chunk.0)
+            .collect();
+        gone |= !items.is_empty();
+        assert!(items.iter().all(|index| indices.contains(index)), "{x}");
+    }
+    assert!(gone);
+    assert!(x - HILLS_BEHIND > 10.0 * HILLS_CHUNK_WIDTH);
+}
+
+fn move_camera(app: &mut App, x: f32) {
+    let mut cameras = app.world.query_filtered::<&mut Transform, With<Camera2d>>();
+    cameras.single_mut(&mut app.world).translation.x = x;
+    app.update();
+}
+
+// Which coins of the chunk `index` are there, in order
+fn coins(app: &mut App, index: i64) -> Vec<usize> {
+    let mut coins: Vec<usize> = app
+        .world
+        .query::<(&HillsChunk, &HillsCoin)>()
+        .iter(&app.world)
+        .filter(|(chunk, _)| chunk.0 == index)
+        .map(|(_, coin)| coin.0)
+        .collect();
+    coins.sort();
+    coins
+}
+
+// Coins taken from a chunk stay taken when it goes out of reach and comes back, so they can't be
+// farmed by going back and forth. Restarting the run brings them all back.
+#[test]
+fn taken_coins_stay_taken_when_their_chunk_comes_back() {
+    let mut app = hills_app();
+    despawn_players(&mut app);
+    let terrain = app.world.resource::<HillsTerrain>().clone();
+    let index = chunks_around(0.0)
+        .find(|&index| terrain.chunk(index).coins.len() > 1)
+        .expect("a chunk with coins at the start");
+    let all: Vec<usize> = (0..terrain.chunk(index).coins.len()).collect();
+    assert_eq!(coins(&mut app, index), all);
+
+    // Taken like the TriggerPlugin takes them
+    let taken = app
+        .world
+        .query::<(Entity, &HillsChunk, &HillsCoin)>()
+        .iter(&app.world)
+        .find(|(_, chunk, coin)| chunk.0 == index && coin.0 == 1)
+        .unwrap()
+        .0;
+    app.world.despawn(taken);
+
+    let far = (index + 1) as f32 * HILLS_CHUNK_WIDTH + HILLS_BEHIND + HILLS_CHUNK_WIDTH;
+    move_camera(&mut app, far);
+    assert!(!chunks_around(far).contains(&index));
+    assert!(coins(&mut app, index).is_empty());
+    assert_eq!(app.world.resource::<HillsRun>().collected[&index], vec![1]);
+
+    move_camera(&mut app, 0.0);
+    let mut left = all.clone();
+    left.remove(1);
+    assert_eq!(coins(&mut app, index), left);
+    // Still only the one, going out of reach again
+    move_camera(&mut app, far);
+    move_camera(&mut app, 0.0);
+    assert_eq!(coins(&mut app, index), left);
+
+    app.world.send_event(RestartLevelEvent);
+    app.update();
+    app.update();
+    assert!(app.world.resource::<HillsRun>().collected.is_empty());
+    assert_eq!(coins(&mut app, index), all);
+}
+
+// A ball dropped on the hills comes to rest on them or rolls over them, never through them
+#[test]
+fn balls_stay_on_top_of_the_hills() {
+    let mut app = hills_app();
+    despawn_players(&mut app);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let radius = 20.0;
+    let balls: Vec<Entity> = (0..8)
+        .map(|i| {
+            let ball = spawn_test_ball(&mut app, Vec2::new(i as f32 * 150.0, 0.0), radius);
+            app.world.get_mut::<PhysObj>(ball).unwrap().vel.x = 100.0;
+            ball
+        })
+        .collect();
+
+    for _ in 0..300 {
+        app.update();
+        let ground = ground(&mut app);
+        for &ball in &balls {
+            let position = app.world.get::<Transform>(ball).unwrap().translation;
+            let surface = ground.iter().find_map(|(_, origin, heightfield)| {
+                heightfield
+                    .height_at(position.x - origin.x)
+                    .map(|height| origin.y + height)
+            });
+            let surface = surface.expect("there's ground under every ball");
+            assert!(surface > floor_y);
+            // A little into it at most, from gravity within the step
+            assert!(position.y - radius > surface - 1.0, "{position} {surface}");
+        }
+    }
+}
+
+// A ball left on a straight slope going down to the right, with `kinetic_friction`. Returns how
+// fast it's going down the slope after half a second, and how fast its bottom slips along it then.
+fn roll_down_slope(kinetic_friction: f32) -> (f32, f32) {
+    let mut app = test_app();
+    let radius = 20.0;
+    let slope = Heightfield {
+        spacing: 100.0,
+        heights: (0..=10).map(|i| i as f32 * -50.0).collect(),
+    };
+    let origin = Vec2::new(0.0, 200.0);
+    let normal = Vec2::new(1.0, 2.0).normalize();
+    let tangent = Vec2::new(normal.y, -normal.x);
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_translation(origin.extend(0.0))),
+        slope.clone(),
+    ));
+    let start = Vec2::new(200.0, origin.y + slope.height_at(200.0).unwrap()) + radius * normal;
+    let ball = spawn_test_ball(&mut app, start, radius);
+    let Collider::Ball {
+        kinetic_friction: friction,
+        ..
+    } = app.world.get_mut::<Collider>(ball).unwrap().into_inner();
+    *friction = kinetic_friction;
+
+    app.update();
+    for _ in 0..(0.5 / TEST_DT).round() as usize {
+        app.update();
+        // Neither sinking into it nor hopping off it
+        let position = app
+            .world
+            .get::<Transform>(ball)
+            .unwrap()
+            .translation
+            .truncate();
+        let distance = (position - start).dot(normal);
+        assert!(distance.abs() < 1.0, "{distance}");
+    }
+    let phys_obj = app.world.get::<PhysObj>(ball).unwrap();
+    let slip = (phys_obj.vel + phys_obj.angular_vel * (-radius * normal).perp()).dot(tangent);
+    (phys_obj.vel.dot(tangent), slip)
+}
+
+// Friction on a slope acts along it: a ball with enough friction rolls down without slipping, a
+// third slower than one without any, which slides down without turning
+#[test]
+fn balls_roll_down_slopes() {
+    let (sliding, slip) = roll_down_slope(0.0);
+    assert!(sliding > 0.0);
+    assert!((slip - sliding).abs() < 1e-3, "{slip} {sliding}");
+
+    let (rolling, slip) = roll_down_slope(0.5);
+    assert!(slip.abs() < 0.01 * rolling, "{slip} {rolling}");
+    assert!(
+        (rolling / sliding - 2.0 / 3.0).abs() < 0.02,
+        "{rolling} {sliding}"
+    );
+}
+
+// The distance the player gets to is scored a point a meter, once
+#[test]
+fn distance_run_is_scored() {
+    let mut app = hills_app();
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+    app.world.resource_mut::<Score>().0 = 0;
+    let mut run = app.world.resource_mut::<HillsRun>();
+    run.distance = 0.0;
+    run.meters = 0;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+
+    for x in [1000.0, 500.0, 1050.0] {
+        app.world.get_mut::<Transform>(player).unwrap().translation =
+            Vec3::new(x, floor_y + 500.0, 0.0);
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Score>().0, 10);
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "debug-tools")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    debug::{
+        lines::DebugLines,
+        overlay::{HistoryBuffer, PhysicsDebugPlugin, RingBuffer},
+    },
+    physics::PhysicsConfig,
+    testing::{press_key, spawn_test_player, test_app},
+};
+
+#[test]
+fn ring_buffer_keeps_the_newest_values_in_order() {
+    let mut buffer = RingBuffer::<4>::default();
+    assert!(buffer.is_empty());
+    for value in 1..=3 {
+        buffer.push(value as f32);
+    }
+    assert_eq!(buffer.iter().collect::<Vec<_>>(), [1.0, 2.0, 3.0]);
+
+    // Wrapping around more than once
+    for value in 4..=10 {
+        buffer.push(value as f32);
+        assert!(buffer.len() <= 4);
+    }
+    assert_eq!(buffer.len(), 4);
+    assert_eq!(buffer.iter().collect::<Vec<_>>(), [7.0, 8.0, 9.0, 10.0]);
+}
+
+fn history_app() -> (App, Entity) {
+    let mut app = test_app();
+    app.init_resource::<DebugLines>()
+        .add_plugin(PhysicsDebugPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + 300.0), 20.0);
+    (app, player)
+}
+
+fn recorded(app: &App) -> Vec<f32> {
+    app.world.resource::<HistoryBuffer>().y.iter().collect()
+}
+
+// Every step of the player's fall is recorded until F freezes the history, which then stays as it
+// was however long the game goes on, until F is pressed again
+#[test]
+fn freezing_stops_recording_until_unfrozen() {
+    let (mut app, player) = history_app();
+    for _ in 0..11 {
+        app.update();
+    }
+    let history = recorded(&app);
+    assert_eq!(history.len(), 10);
+    assert!(history.windows(2).all(|pair| pair[1] < pair[0]));
+    assert_eq!(
+        *history.last().unwrap(),
+        app.world.get::<Transform>(player).unwrap().translation.y
+    );
+
+    press_key(&mut app, KeyCode::F, true);
+    app.update();
+    press_key(&mut app, KeyCode::F, false);
+    assert!(app.world.resource::<HistoryBuffer>().frozen);
+    let frozen = recorded(&app);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(recorded(&app), frozen);
+    let csv = app.world.resource::<HistoryBuffer>().to_csv();
+    assert_eq!(csv.lines().count(), frozen.len() + 1);
+    assert!(csv.starts_with("step,y,vel_y\n0,"));
+
+    press_key(&mut app, KeyCode::F, true);
+    app.update();
+    assert!(!app.world.resource::<HistoryBuffer>().frozen);
+    app.update();
+    assert_eq!(recorded(&app).len(), frozen.len() + 2);
+}
+
+// The history holds the last 300 steps and no more
+#[test]
+fn history_keeps_the_last_steps() {
+    let (mut app, _) = history_app();
+    for _ in 0..400 {
+        app.update();
+    }
+    let history = app.world.resource::<HistoryBuffer>();
+    assert_eq!(history.y.len(), 300);
+    assert_eq!(history.vel_y.len(), 300);
+}
@@ -0,0 +1,126 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use bevy_game::{
+    hud::{format_level_time, GameHudPlugin},
+    physics::PhysicsConfig,
+    progress::{LevelTimer, Progress, Score},
+    state::AppState,
+    status::{StatusEffect, StatusEffects},
+    testing::{spawn_test_player, test_app},
+};
+
+// How many texts changed in the last update
+#[derive(Resource, Default)]
+struct ChangedTexts(usize);
+
+fn count_changed_texts(texts: Query<(), Changed<Text>>, mut changed: ResMut<ChangedTexts>) {
+    changed.0 = texts.iter().count();
+}
+
+fn hud_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .init_resource::<Score>()
+        .init_resource::<LevelTimer>()
+        .init_resource::<Progress>()
+        .init_resource::<ChangedTexts>()
+        .add_plugin(GameHudPlugin)
+        .add_system(count_changed_texts.in_base_set(CoreSet::PostUpdate));
+    app
+}
+
+fn changed_texts(app: &App) -> usize {
+    app.world.resource::<ChangedTexts>().0
+}
+
+// The text of the HUD field starting with `label`
+fn field(app: &mut App, label: &str) -> String {
+    app.world
+        .query::<&Text>()
+        .iter(&app.world)
+        .map(|text| text.sections[0].value.clone())
+        .find(|value| value.starts_with(label))
+        .unwrap_or_else(|| panic!("no {label} on the HUD"))
+}
+
+#[test]
+fn level_time_is_minutes_and_seconds() {
+    assert_eq!(format_level_time(0.0), "0:00.0");
+    assert_eq!(format_level_time(5.0), "0:05.0");
+    assert_eq!(format_level_time(75.3), "1:15.3");
+    assert_eq!(format_level_time(600.0), "10:00.0");
+}
+
+// Each field is only rewritten when what it shows changes
+#[test]
+fn hud_texts_only_change_with_what_they_show() {
+    let mut app = hud_app();
+    app.update();
+    assert_eq!(field(&mut app, "Score"), "Score 0");
+    assert_eq!(field(&mut app, "Time"), "Time 0:00.0");
+    assert_eq!(field(&mut app, "Deaths"), "Deaths 0");
+    for _ in 0..3 {
+        app.update();
+        assert_eq!(changed_texts(&app), 0);
+    }
+
+    app.world.resource_mut::<Score>().0 = 150;
+    app.update();
+    assert_eq!(changed_texts(&app), 1);
+    assert_eq!(field(&mut app, "Score"), "Score 150");
+
+    app.world.resource_mut::<LevelTimer>().elapsed = 61.5;
+    app.world.resource_mut::<Progress>().deaths = 2;
+    app.update();
+    assert_eq!(changed_texts(&app), 2);
+    assert_eq!(field(&mut app, "Time"), "Time 1:01.5");
+    assert_eq!(field(&mut app, "Deaths"), "Deaths 2");
+
+    // Touched, but showing the same
+    app.world.resource_mut::<Score>().set_changed();
+    app.update();
+    assert_eq!(changed_texts(&app), 0);
+}
+
+#[test]
+fn hud_lists_the_players_status_effects() {
+    let mut app = hud_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + 25.0), 25.0);
+    let mut effects = StatusEffects::default();
+    effects.apply(StatusEffect::NoJump, 2.5);
+    app.world.entity_mut(player).insert(effects);
+    app.update();
+    assert_eq!(field(&mut app, "No jump"), "No jump 3");
+    app.update();
+    assert_eq!(changed_texts(&app), 0);
+
+    let mut effects = app.world.get_mut::<StatusEffects>(player).unwrap();
+    effects.apply(StatusEffect::SpeedBoost, 10.0);
+    app.update();
+    assert_eq!(changed_texts(&app), 1);
+    assert_eq!(field(&mut app, "No jump"), "No jump 3 Boost 10");
+}
+
+// Shown while playing, and hidden on every other screen
+#[test]
+fn hud_only_shows_while_playing() {
+    let mut app = hud_app();
+    let visible = |app: &mut App| {
+        let mut roots = app
+            .world
+            .query_filtered::<&Visibility, (With<Node>, Without<Parent>)>();
+        roots
+            .iter(&app.world)
+            .all(|visibility| *visibility == Visibility::Inherited)
+    };
+    app.update();
+    app.update();
+    assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Playing);
+    assert!(visible(&mut app));
+
+    for state in [AppState::Paused, AppState::LevelComplete, AppState::Playing] {
+        app.world.resource_mut::<NextState<AppState>>().set(state);
+        app.update();
+        assert_eq!(visible(&mut app), state == AppState::Playing, "{state:?}");
+    }
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bevy_game::{
+    level::{surface_friction_scale, Floor, SurfaceMaterial},
+    physics::{sleep::StaticBody, PhysObj, PhysicsConfig},
+    testing::{spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+const SPEED: f32 = 400.0;
+
+fn ice(friction_multiplier: f32) -> SurfaceMaterial {
+    SurfaceMaterial::Ice {
+        friction_multiplier,
+    }
+}
+
+#[test]
+fn only_the_ice_under_the_ball_counts() {
+    let floors = [
+        (
+            Transform::from_xyz(0.0, 0.0, 0.0),
+            Floor { width: 200.0 },
+            ice(0.1),
+        ),
+        (
+            Transform::from_xyz(200.0, 0.0, 0.0),
+            Floor { width: 200.0 },
+            SurfaceMaterial::Normal,
+        ),
+        // A higher floor hides the ice under it
+        (
+            Transform::from_xyz(-50.0, 50.0, 0.0),
+            Floor { width: 100.0 },
+            ice(0.5),
+        ),
+    ];
+    let scale = |x: f32, y: f32| {
+        surface_friction_scale(
+            Vec2::new(x, y),
+            floors.iter().map(|(t, f, m)| (t, f, Some(m))),
+        )
+    };
+    assert_eq!(scale(50.0, 25.0), 0.1);
+    assert_eq!(scale(-50.0, 25.0), 0.1);
+    assert_eq!(scale(-50.0, 75.0), 0.5);
+    assert_eq!(scale(250.0, 25.0), 1.0);
+    assert_eq!(scale(1000.0, 25.0), 1.0);
+}
+
+// How far a ball sliding along the floor at SPEED, with the backspin to stop it dead once friction
+// has taken up the difference, gets in ten seconds
+fn stopping_distance(material: Option<SurfaceMaterial>) -> f32 {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    if let Some(material) = material {
+        app.world.spawn((
+            TransformBundle::from_transform(Transform::from_xyz(0.0, config.floor_y, 0.0)),
+            Floor { width: 20_000.0 },
+            material,
+            StaticBody,
+        ));
+    }
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, config.floor_y + RADIUS), RADIUS);
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.vel = Vec2::new(SPEED, 0.0);
+    phys_obj.angular_vel = SPEED * phys_obj.mass * RADIUS / phys_obj.moment_of_inertia;
+    for _ in 0..600 {
+        app.update();
+    }
+    let vel = app.world.get::<PhysObj>(ball).unwrap().vel;
+    assert!(vel.x.abs() < 5.0, "{vel}");
+    app.world.get::<Transform>(ball).unwrap().translation.x
+}
+
+#[test]
+fn balls_slide_much_further_on_ice() {
+    let normal = stopping_distance(None);
+    let ice_distance = stopping_distance(Some(ice(0.05)));
+    // With friction taking 0.5 g off the speed, about SPEED² / g
+    assert!(normal > 0.0 && normal < 150.0, "{normal}");
+    assert!(ice_distance > 10.0 * normal, "{normal} {ice_distance}");
+    // Nothing changes on a normal floor, or on ice that's no less grippy
+    assert!((stopping_distance(Some(SurfaceMaterial::Normal)) - normal).abs() < 1e-3);
+    assert!((stopping_distance(Some(ice(1.0))) - normal).abs() < 1e-3);
+}
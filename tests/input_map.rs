@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::PhysicsTime,
+    player::{ControlMode, PlayerInput},
+    settings::{InputAction, InputMap, Rebind, Settings},
+    testing::{press_key, test_app},
+};
+
+// Today's bindings, as pairs
+const DEFAULT_PAIRS: [(InputAction, KeyCode); 18] = [
+    (InputAction::Jump, KeyCode::Space),
+    (InputAction::SpinLeft, KeyCode::A),
+    (InputAction::SpinRight, KeyCode::D),
+    (InputAction::ZeroGravity, KeyCode::K),
+    (InputAction::ClimbUp, KeyCode::W),
+    (InputAction::ClimbDown, KeyCode::S),
+    (InputAction::Dash, KeyCode::LShift),
+    (InputAction::Pause, KeyCode::P),
+    (InputAction::Respawn, KeyCode::R),
+    (InputAction::ControlMode, KeyCode::C),
+    (InputAction::Trajectory, KeyCode::T),
+    (InputAction::SlowMotion, KeyCode::Tab),
+    (InputAction::AutoSlowMotion, KeyCode::O),
+    (InputAction::Step, KeyCode::N),
+    (InputAction::Mute, KeyCode::M),
+    (InputAction::AnimateSpinner, KeyCode::B),
+    (InputAction::ControlsHelp, KeyCode::H),
+    (InputAction::FreezeHistory, KeyCode::F),
+];
+
+fn arrows_map() -> InputMap {
+    InputMap::from_pairs(DEFAULT_PAIRS.into_iter().chain([
+        (InputAction::SpinLeft, KeyCode::Left),
+        (InputAction::SpinRight, KeyCode::Right),
+    ]))
+}
+
+#[test]
+fn from_pairs_builds_the_default_map_and_extra_keys() {
+    assert!(InputMap::from_pairs(DEFAULT_PAIRS) == InputMap::default());
+    assert!(InputMap::from_pairs([]) == InputMap::default());
+
+    let map = arrows_map();
+    assert_eq!(map.key(InputAction::SpinLeft), KeyCode::A);
+    let keys: Vec<_> = map.keys(InputAction::SpinLeft).collect();
+    assert_eq!(keys, vec![KeyCode::A, KeyCode::Left]);
+    assert_eq!(map.keys(InputAction::Jump).count(), 1);
+
+    // Extra keys are taken like main ones
+    assert_eq!(
+        map.rebind(InputAction::Jump, KeyCode::Right),
+        Rebind::Conflict(InputAction::SpinRight)
+    );
+    let mut map = map;
+    assert_eq!(
+        map.add_key(InputAction::Jump, KeyCode::Left),
+        Rebind::Conflict(InputAction::SpinLeft)
+    );
+    assert_eq!(map.add_key(InputAction::Jump, KeyCode::Up), Rebind::Bind);
+    assert_eq!(map.add_key(InputAction::Jump, KeyCode::Up), Rebind::Bind);
+    let keys: Vec<_> = map.keys(InputAction::Jump).collect();
+    assert_eq!(keys, vec![KeyCode::Space, KeyCode::Up]);
+
+    // Taking another action's extra key gives it the old main key instead
+    map.bind_swapping(InputAction::Jump, KeyCode::Left);
+    let keys: Vec<_> = map.keys(InputAction::SpinLeft).collect();
+    assert_eq!(keys, vec![KeyCode::A, KeyCode::Space]);
+    let keys: Vec<_> = map.keys(InputAction::Jump).collect();
+    assert_eq!(keys, vec![KeyCode::Left, KeyCode::Up]);
+}
+
+#[test]
+fn extra_keys_survive_saving() {
+    let settings = Settings {
+        input: arrows_map(),
+        ..default()
+    };
+    let saved = ron::to_string(&settings).unwrap();
+    let loaded: Settings = ron::from_str(&saved).unwrap();
+    assert!(loaded == settings);
+
+    // Saved before there were extra keys
+    let old = saved.replace("extra:", "unknown_extra:");
+    let loaded: Settings = ron::from_str(&old).unwrap();
+    assert_eq!(loaded.input.keys(InputAction::SpinLeft).count(), 1);
+}
+
+#[test]
+fn either_key_drives_the_player() {
+    let mut app = test_app();
+    app.world.resource_mut::<Settings>().input = arrows_map();
+    for key in [KeyCode::A, KeyCode::Left] {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+        assert!(app.world.resource::<PlayerInput>().spin_left, "{key:?}");
+        assert!(!app.world.resource::<PlayerInput>().spin_right);
+        app.world.resource_mut::<Input<KeyCode>>().release(key);
+        app.update();
+        assert!(!app.world.resource::<PlayerInput>().spin_left);
+    }
+}
+
+// The keys that change how the game runs go through the map like the player's
+#[test]
+fn rebound_keys_drive_the_game() {
+    let mut app = test_app();
+    app.world.resource_mut::<Settings>().input = InputMap::from_pairs([
+        (InputAction::ControlMode, KeyCode::F1),
+        (InputAction::SlowMotion, KeyCode::F2),
+    ]);
+    let tap = |app: &mut App, key: KeyCode| {
+        press_key(app, key, true);
+        app.update();
+        press_key(app, key, false);
+        app.update();
+    };
+
+    tap(&mut app, KeyCode::C);
+    assert_eq!(*app.world.resource::<ControlMode>(), ControlMode::Torque);
+    tap(&mut app, KeyCode::F1);
+    assert_eq!(*app.world.resource::<ControlMode>(), ControlMode::Direct);
+
+    press_key(&mut app, KeyCode::Tab, true);
+    app.update();
+    assert_eq!(app.world.resource::<PhysicsTime>().scale, 1.0);
+    press_key(&mut app, KeyCode::F2, true);
+    app.update();
+    assert!(app.world.resource::<PhysicsTime>().scale < 1.0);
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn listening_reaches_every_action() {
+    use bevy_game::debug::rebind::KeyListener;
+
+    let mut map = InputMap::default();
+    let mut listener = KeyListener::default();
+    for &action in InputAction::ALL.iter().chain([&InputAction::ALL[0]]) {
+        listener.press(&mut map, KeyCode::Insert);
+        assert_eq!(listener, KeyListener::Listening(action));
+    }
+    listener = KeyListener::Listening(InputAction::Respawn);
+    assert!(listener.press(&mut map, KeyCode::F3).is_some());
+    let keys: Vec<_> = map.keys(InputAction::Respawn).collect();
+    assert_eq!(keys, vec![KeyCode::R, KeyCode::F3]);
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn listening_adds_the_next_key() {
+    use bevy_game::debug::rebind::KeyListener;
+
+    let mut map = InputMap::default();
+    let mut listener = KeyListener::default();
+    assert_eq!(listener.press(&mut map, KeyCode::Left), None);
+
+    // Insert goes through the actions
+    assert!(listener.press(&mut map, KeyCode::Insert).is_some());
+    assert_eq!(listener, KeyListener::Listening(InputAction::ALL[0]));
+    listener.press(&mut map, KeyCode::Insert);
+    assert_eq!(listener, KeyListener::Listening(InputAction::SpinLeft));
+    assert!(listener.press(&mut map, KeyCode::Left).is_some());
+    assert_eq!(listener, KeyListener::Idle);
+    let keys: Vec<_> = map.keys(InputAction::SpinLeft).collect();
+    assert_eq!(keys, vec![KeyCode::A, KeyCode::Left]);
+
+    // A taken key isn't added, and Escape stops listening
+    for key in [KeyCode::D, KeyCode::Escape] {
+        listener.press(&mut map, KeyCode::Insert);
+        listener.press(&mut map, key);
+        assert_eq!(listener, KeyListener::Idle);
+        assert_eq!(map.keys(InputAction::Jump).count(), 1);
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "inspector")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    inspector::set_mass,
+    physics::{PhysObj, PhysicsConfig},
+    player::Player,
+    settings::Settings,
+    testing::{press_key, spawn_test_ball, spawn_test_player, test_app, TEST_DT},
+};
+
+#[test]
+fn mass_changes_keep_the_moment_of_inertia_of_a_disk() {
+    let mut app = test_app();
+    let ball = spawn_test_ball(&mut app, Vec2::ZERO, 20.0);
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    set_mass(&mut phys_obj, 20.0, 40.0);
+    assert_eq!(phys_obj.mass, 40.0);
+    assert_eq!(phys_obj.moment_of_inertia, 0.5 * 40.0 * 20.0 * 20.0);
+}
+
+// What the panel's sliders change is used by the very next step, e.g. by a jump right after
+#[test]
+fn edits_take_effect_on_the_next_step() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let radius = 20.0;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, config.floor_y + radius), radius);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..default()
+    });
+    app.update();
+    app.update();
+
+    let mut entity = app.world.entity_mut(player);
+    entity.get_mut::<Player>().unwrap().jump_impulse = 12_000.0;
+    set_mass(&mut entity.get_mut::<PhysObj>().unwrap(), radius, 20.0);
+
+    let jump = Settings::default().input.jump;
+    press_key(&mut app, jump, true);
+    app.update();
+    press_key(&mut app, jump, false);
+    app.update();
+    let vel = app.world.get::<PhysObj>(player).unwrap().vel.y;
+    // 12000 / 20, less up to two steps of gravity
+    let launch_speed = 12_000.0 / 20.0;
+    assert!(
+        vel < launch_speed && vel > launch_speed - 2.0 * config.gravity * TEST_DT,
+        "{vel}"
+    );
+}
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{
+        forces::ForceFn,
+        joints::{DistanceJoint, JointMotor, RevoluteJoint},
+        Gravity, PhysObj, PhysicsConfig,
+    },
+    testing::{set_test_ball_mass, set_test_ball_restitution, spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 5.0;
+const LINK_LENGTH: f32 = 30.0;
+const LINKS: usize = 5;
+
+// Links `LINKS` balls into a horizontal chain starting next to `first`
+fn spawn_chain(app: &mut App, first: Entity, rigid: bool, falls: bool) -> Vec<Entity> {
+    let start = app
+        .world
+        .get::<Transform>(first)
+        .unwrap()
+        .translation
+        .truncate();
+    let mut chain = vec![first];
+    for i in 1..=LINKS {
+        let ball = spawn_test_ball(app, start + Vec2::X * LINK_LENGTH * i as f32, RADIUS);
+        set_test_ball_mass(app, ball, 1.0);
+        set_test_ball_restitution(app, ball, 0.5);
+        if !falls {
+            app.world.entity_mut(ball).remove::<Gravity>();
+        }
+        app.world.spawn(DistanceJoint {
+            a: chain[i - 1],
+            b: ball,
+            length: LINK_LENGTH,
+            rigid,
+        });
+        chain.push(ball);
+    }
+    chain
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+fn vel(app: &App, entity: Entity) -> Vec2 {
+    app.world.get::<PhysObj>(entity).unwrap().vel
+}
+
+fn momentum(app: &App, chain: &[Entity]) -> Vec2 {
+    chain
+        .iter()
+        .map(|&entity| {
+            let phys_obj = app.world.get::<PhysObj>(entity).unwrap();
+            phys_obj.vel * phys_obj.mass
+        })
+        .sum()
+}
+
+#[test]
+fn hanging_chain_swings_without_stretching() {
+    let mut app = test_app();
+    let anchor = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            0.0, 200.0, 0.0,
+        )))
+        .id();
+    let chain = spawn_chain(&mut app, anchor, false, true);
+
+    // Released from horizontal, so it swings through the whole half circle
+    for _ in 0..30 * 60 {
+        app.update();
+        for link in chain.windows(2) {
+            let length = position(&app, link[0]).distance(position(&app, link[1]));
+            assert!(length <= LINK_LENGTH * 1.01, "link stretched to {length}");
+        }
+        assert_eq!(position(&app, anchor), Vec2::new(0.0, 200.0));
+    }
+}
+
+#[test]
+fn rigid_joints_hold_their_length_both_ways() {
+    let mut app = test_app();
+    let first = spawn_test_ball(&mut app, Vec2::ZERO, RADIUS);
+    set_test_ball_mass(&mut app, first, 1.0);
+    set_test_ball_restitution(&mut app, first, 0.5);
+    app.world.entity_mut(first).remove::<Gravity>();
+    let chain = spawn_chain(&mut app, first, true, false);
+    // Spin the chain, which the joints have to keep from flying apart
+    app.world.get_mut::<PhysObj>(chain[0]).unwrap().vel = Vec2::new(-200.0, 600.0);
+    app.world.get_mut::<PhysObj>(chain[LINKS]).unwrap().vel = Vec2::new(-200.0, -600.0);
+
+    for _ in 0..120 {
+        app.update();
+        for link in chain.windows(2) {
+            let length = position(&app, link[0]).distance(position(&app, link[1]));
+            assert!(
+                (length - LINK_LENGTH).abs() <= LINK_LENGTH * 0.01,
+                "link is {length} long"
+            );
+        }
+    }
+}
+
+// The player yanking the end of a free chain drags the rest along, but the joints only pass the
+// impulse on, so the chain's momentum is what the yank gave it
+#[test]
+fn yanking_a_chain_conserves_its_momentum() {
+    let mut app = test_app();
+    let first = spawn_test_ball(&mut app, Vec2::ZERO, RADIUS);
+    set_test_ball_mass(&mut app, first, 1.0);
+    set_test_ball_restitution(&mut app, first, 0.5);
+    app.world.entity_mut(first).remove::<Gravity>();
+    let chain = spawn_chain(&mut app, first, false, false);
+    app.update();
+
+    let yank = Vec2::new(900.0, 0.0);
+    app.world.get_mut::<PhysObj>(chain[LINKS]).unwrap().vel += yank;
+    for _ in 0..60 {
+        app.update();
+    }
+
+    let total = momentum(&app, &chain);
+    assert!(
+        (total - yank).length() < 1e-3 * yank.length(),
+        "{total} != {yank}"
+    );
+    // Every link was pulled along
+    for &ball in &chain {
+        assert!(vel(&app, ball).x > 0.0);
+    }
+}
+
+// Pushing the end of a rope towards the rest of it just gives it slack
+#[test]
+fn ropes_do_not_push() {
+    let mut app = test_app();
+    let first = spawn_test_ball(&mut app, Vec2::ZERO, RADIUS);
+    set_test_ball_mass(&mut app, first, 1.0);
+    set_test_ball_restitution(&mut app, first, 0.5);
+    app.world.entity_mut(first).remove::<Gravity>();
+    let chain = spawn_chain(&mut app, first, false, false);
+    app.update();
+
+    app.world.get_mut::<PhysObj>(chain[LINKS]).unwrap().vel = Vec2::new(-60.0, 0.0);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert!(vel(&app, chain[LINKS]).x < 0.0);
+    for &ball in &chain[..LINKS] {
+        assert_eq!(vel(&app, ball), Vec2::ZERO);
+    }
+}
+
+fn spawn_anchor(app: &mut App, position: Vec2) -> Entity {
+    app.world
+        .spawn(TransformBundle::from_transform(
+            Transform::from_translation(position.extend(0.0)),
+        ))
+        .id()
+}
+
+// A body without a collider, e.g. a bar or a wheel
+fn spawn_body(app: &mut App, position: Vec2, moment_of_inertia: f32) -> Entity {
+    app.world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            PhysObj {
+                mass: 1.0,
+                vel: Vec2::ZERO,
+                acc: Vec2::ZERO,
+                acc_prev: Vec2::ZERO,
+                moment_of_inertia,
+                angular_vel: 0.0,
+                angular_acc: 0.0,
+                angular_acc_prev: 0.0,
+                com_offset: Vec2::ZERO,
+            },
+        ))
+        .id()
+}
+
+fn pin_error(app: &App, joint: &RevoluteJoint) -> f32 {
+    let point = |entity, anchor: Vec2| {
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        transform.translation.truncate() + (transform.rotation * anchor.extend(0.0)).truncate()
+    };
+    point(joint.a, joint.anchor_on_a).distance(point(joint.b, joint.anchor_on_b))
+}
+
+fn angular_vel(app: &App, entity: Entity) -> f32 {
+    app.world.get::<PhysObj>(entity).unwrap().angular_vel
+}
+
+#[test]
+fn pinned_bar_swings_around_its_pin() {
+    let mut app = test_app();
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let anchor = spawn_anchor(&mut app, Vec2::new(0.0, 200.0));
+    // A 100 long bar pinned at one end, released horizontal
+    let bar = spawn_body(&mut app, Vec2::new(50.0, 200.0), 100.0 * 100.0 / 12.0);
+    app.world.entity_mut(bar).insert(Gravity(gravity));
+    let joint = RevoluteJoint {
+        a: anchor,
+        b: bar,
+        anchor_on_a: Vec2::ZERO,
+        anchor_on_b: Vec2::new(-50.0, 0.0),
+        motor: None,
+    };
+    app.world.spawn(joint);
+
+    let mut lowest = f32::INFINITY;
+    for _ in 0..10 * 60 {
+        app.update();
+        let error = pin_error(&app, &joint);
+        assert!(error < 1.0, "pin is {error} apart");
+        lowest = lowest.min(position(&app, bar).y);
+    }
+    // It swung down rather than just hanging there
+    assert!(lowest < 160.0, "{lowest}");
+}
+
+#[test]
+fn motor_reaches_its_target_speed_without_load() {
+    let mut app = test_app();
+    let anchor = spawn_anchor(&mut app, Vec2::ZERO);
+    let wheel = spawn_body(&mut app, Vec2::ZERO, 300.0);
+    app.world.spawn(RevoluteJoint {
+        a: anchor,
+        b: wheel,
+        anchor_on_a: Vec2::ZERO,
+        anchor_on_b: Vec2::ZERO,
+        motor: Some(JointMotor {
+            target_speed: 5.0,
+            max_torque: 10_000.0,
+        }),
+    });
+
+    for _ in 0..30 {
+        app.update();
+    }
+
+    assert!((angular_vel(&app, wheel) - 5.0).abs() < 1e-3);
+    assert_eq!(position(&app, wheel), Vec2::ZERO);
+}
+
+#[test]
+fn motor_slips_under_a_heavier_load() {
+    const MOMENT_OF_INERTIA: f32 = 300.0;
+    const MAX_TORQUE: f32 = 10_000.0;
+    const LOAD: f32 = 20_000.0;
+
+    let mut app = test_app();
+    let anchor = spawn_anchor(&mut app, Vec2::ZERO);
+    let wheel = spawn_body(&mut app, Vec2::ZERO, MOMENT_OF_INERTIA);
+    app.world
+        .entity_mut(wheel)
+        .insert(ForceFn::new(|_, body: &mut PhysObj, _: &Transform| {
+            body.angular_acc -= LOAD / body.moment_of_inertia;
+        }));
+    app.world.spawn(RevoluteJoint {
+        a: anchor,
+        b: wheel,
+        anchor_on_a: Vec2::ZERO,
+        anchor_on_b: Vec2::ZERO,
+        motor: Some(JointMotor {
+            target_speed: 5.0,
+            max_torque: MAX_TORQUE,
+        }),
+    });
+
+    for _ in 0..61 {
+        app.update();
+    }
+
+    // A second of the load, held back by the motor's full torque but no more
+    let expected = -(LOAD - MAX_TORQUE) / MOMENT_OF_INERTIA;
+    let angular_vel = angular_vel(&app, wheel);
+    assert!(
+        (angular_vel - expected).abs() < 0.05 * expected.abs(),
+        "{angular_vel} != {expected}"
+    );
+}
@@ -0,0 +1,69 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{LandedEvent, PhysObj, PhysicsConfig},
+    settings::Settings,
+    testing::{set_test_ball_restitution, spawn_test_player, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+
+// Drops the player from `height` while holding D, and returns its velocity and angular velocity
+// after each frame, up to and including the one it lands in
+fn drop_spinning(assist: bool, height: f32, frames: usize) -> Vec<(Vec2, f32)> {
+    let mut app = test_app();
+    app.world.resource_mut::<Settings>().landing_assist = assist;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + height), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.5);
+    let mut reader = ManualEventReader::<LandedEvent>::default();
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+
+    let mut history = Vec::new();
+    for _ in 0..frames {
+        app.update();
+        let phys_obj = app.world.get::<PhysObj>(player).unwrap();
+        history.push((phys_obj.vel, phys_obj.angular_vel));
+        if reader
+            .iter(app.world.resource::<Events<LandedEvent>>())
+            .next()
+            .is_some()
+        {
+            break;
+        }
+    }
+    history
+}
+
+// Spun clockwise without anything moving it sideways, the ball should land not turning at all.
+// Without the assist, the spin skids it off to the right.
+#[test]
+fn assist_cancels_the_skid_of_a_spinning_drop() {
+    let unassisted = drop_spinning(false, 100.0, 120);
+    let &(vel, _) = unassisted.last().unwrap();
+    assert!(vel.x > 50.0, "{vel}");
+
+    let assisted = drop_spinning(true, 100.0, 120);
+    let &(assisted_vel, _) = assisted.last().unwrap();
+    assert!(
+        assisted_vel.x.abs() < 0.15 * vel.x,
+        "{assisted_vel} vs {vel}"
+    );
+}
+
+// Off, spinning in the air is the player's torque and nothing else, right up to the landing
+#[test]
+fn no_assist_when_it_is_off() {
+    let landing = drop_spinning(false, 100.0, 120);
+    let before_landing = &landing[..landing.len() - 1];
+    // Spinning just as long high above the floor, where the assist never acts
+    let high = drop_spinning(true, 10_000.0, before_landing.len());
+
+    let spin = |history: &[(Vec2, f32)]| history.iter().map(|&(_, spin)| spin).collect::<Vec<_>>();
+    assert_eq!(spin(before_landing), spin(&high));
+    // Whereas on, the assist does act before this landing
+    let assisted = drop_spinning(true, 100.0, 120);
+    assert_ne!(
+        spin(&assisted[..before_landing.len()]),
+        spin(before_landing)
+    );
+}
@@ -0,0 +1,98 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_game::{
+    launch::{headless_app, HeadlessRun, LaunchOptions},
+    level::CurrentLevel,
+    stress::DEFAULT_STRESS_BALLS,
+};
+
+fn args(line: &str) -> LaunchOptions {
+    LaunchOptions::parse_args(line.split_whitespace().map(String::from))
+}
+
+#[test]
+fn command_line_sets_every_option() {
+    let line = "--level 2 --stress 500 --headless-seconds 10 --seed 42 --debug-overlay --hills";
+    assert_eq!(
+        args(&format!("{line} --scenario a.ron")),
+        LaunchOptions {
+            level: Some(2),
+            seed: Some(42),
+            stress: Some(500),
+            headless_seconds: Some(10.0),
+            scenario: Some("a.ron".to_string()),
+            debug_overlay: true,
+            hills: true,
+            warnings: Vec::new(),
+        }
+    );
+    assert_eq!(args(""), LaunchOptions::default());
+
+    // A count is optional, so what follows `--stress` can be the next flag
+    let options = args("--stress --level 3");
+    assert_eq!(options.stress, Some(DEFAULT_STRESS_BALLS));
+    assert_eq!(options.level, Some(3));
+}
+
+// What's not understood is a warning, and doesn't get in the way of the rest
+#[test]
+fn unknown_flags_warn() {
+    let options = args("--fly --level two --seed 7 stray --headless-seconds -1");
+    assert_eq!(options.seed, Some(7));
+    assert_eq!(options.level, None);
+    assert_eq!(options.headless_seconds, None);
+    assert_eq!(options.warnings.len(), 4, "{:?}", options.warnings);
+    assert!(options.warnings[0].contains("fly"));
+
+    // Other plugins' flags, and their values, are theirs
+    let options = args("--ldtk levels/a.ldtk Level_0 --host 4000 --runner --level 1");
+    assert_eq!(options.warnings, Vec::<String>::new());
+    assert_eq!(options.level, Some(1));
+}
+
+#[test]
+fn url_query_sets_options() {
+    let options = LaunchOptions::parse_query("?level=2&seed=42&stress&debug-overlay&runner&hills");
+    assert_eq!(
+        options,
+        LaunchOptions {
+            level: Some(2),
+            seed: Some(42),
+            stress: Some(DEFAULT_STRESS_BALLS),
+            debug_overlay: true,
+            hills: true,
+            ..default()
+        }
+    );
+    assert_eq!(LaunchOptions::parse_query(""), LaunchOptions::default());
+    assert_eq!(LaunchOptions::parse_query("?stress=500").stress, Some(500));
+
+    let options = LaunchOptions::parse_query("?level=x&zoom=3");
+    assert_eq!(options.level, None);
+    assert_eq!(options.warnings.len(), 2, "{:?}", options.warnings);
+}
+
+// Updates until the app asks to exit, returning the state hash it finished with
+fn run_to_exit(mut app: App) -> u64 {
+    for _ in 0..1000 {
+        app.update();
+        if !app.world.resource::<Events<AppExit>>().is_empty() {
+            let run = app.world.resource::<HeadlessRun>();
+            assert!(run.elapsed >= run.seconds);
+            return run.hash.unwrap();
+        }
+    }
+    panic!("The headless run never exited");
+}
+
+#[test]
+fn headless_run_exits_with_a_state_hash() {
+    let options = args("--headless-seconds 2 --level 3");
+    let app = headless_app(&options, 2.0);
+    let hash = run_to_exit(app);
+    // The same every time
+    assert_eq!(run_to_exit(headless_app(&options, 2.0)), hash);
+
+    let mut app = headless_app(&options, 2.0);
+    app.update();
+    assert_eq!(app.world.resource::<CurrentLevel>().0, 3);
+}
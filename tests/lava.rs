@@ -0,0 +1,161 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    fluid::{submerged_fraction, BurnedEvent, FluidPlugin, FluidVolume, Scorching},
+    hazard::DeathEvent,
+    physics::{Gravity, PhysObj, PhysicsConfig},
+    player::Player,
+    testing::{spawn_test_ball, spawn_test_player, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+// Resting on the floor, 400 wide and 200 deep
+const POOL_SIZE: Vec2 = Vec2::new(400.0, 200.0);
+const LAVA: FluidVolume = FluidVolume::lava(POOL_SIZE);
+const WATER: FluidVolume = FluidVolume::water(POOL_SIZE);
+
+fn fluid_app(fluid: FluidVolume) -> App {
+    let mut app = test_app();
+    app.add_plugin(FluidPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(0.0, floor_y + 0.5 * POOL_SIZE.y, 0.0)),
+        fluid,
+    ));
+    app
+}
+
+fn surface(app: &App) -> f32 {
+    app.world.resource::<PhysicsConfig>().floor_y + POOL_SIZE.y
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+// Updates `updates` times, counting the deaths along the way
+fn run(app: &mut App, deaths: &mut ManualEventReader<DeathEvent>, updates: usize) -> usize {
+    (0..updates)
+        .map(|_| {
+            app.update();
+            deaths
+                .iter(app.world.resource::<Events<DeathEvent>>())
+                .count()
+        })
+        .sum()
+}
+
+#[test]
+fn submerged_fraction_follows_the_depth() {
+    assert_eq!(submerged_fraction(RADIUS, -5.0), 0.0);
+    assert!((submerged_fraction(RADIUS, RADIUS) - 0.5).abs() < 1e-4);
+    assert_eq!(submerged_fraction(RADIUS, 3.0 * RADIUS), 1.0);
+    let quarter = submerged_fraction(RADIUS, 0.5 * RADIUS);
+    assert!(quarter > 0.0 && quarter < 0.5, "{quarter}");
+
+    // Only what's inside the volume counts
+    let center = Vec2::ZERO;
+    assert_eq!(WATER.submerged(center, Vec2::new(300.0, 0.0), RADIUS), 0.0);
+    assert_eq!(WATER.submerged(center, Vec2::new(0.0, 200.0), RADIUS), 0.0);
+    assert_eq!(WATER.submerged(center, Vec2::ZERO, RADIUS), 1.0);
+}
+
+// The player sinks slowly through lava, heating up, and dies a second after touching it
+#[test]
+fn lava_kills_the_player_after_a_second() {
+    let mut app = fluid_app(LAVA);
+    let mut deaths = ManualEventReader::default();
+    let start = Vec2::new(0.0, surface(&app) - 2.0 * RADIUS);
+    let player = spawn_test_player(&mut app, start, RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    // The physics runs from the second update
+    let touched = 1;
+    let kill_updates = (1.0 / TEST_DT).round() as usize;
+    assert_eq!(run(&mut app, &mut deaths, touched + kill_updates - 2), 0);
+
+    let scorching = *app.world.get::<Scorching>(player).unwrap();
+    assert!(scorching.touching);
+    assert!(scorching.heat > 0.9, "{}", scorching.heat);
+    // Going down, but far slower than falling
+    let vel = app.world.get::<PhysObj>(player).unwrap().vel;
+    assert!(vel.y < 0.0 && vel.y > -200.0, "{vel}");
+    assert!(position(&app, player).y < start.y);
+
+    // Just the once
+    assert_eq!(run(&mut app, &mut deaths, 3), 1);
+    assert_eq!(run(&mut app, &mut deaths, 30), 0);
+}
+
+// Skimming across a pool quickly is survivable, and the player cools off afterwards
+#[test]
+fn crossing_lava_quickly_is_survivable() {
+    let mut app = fluid_app(LAVA);
+    let mut deaths = ManualEventReader::default();
+    let y = surface(&app) - 0.5 * RADIUS;
+    let mut x = -300.0;
+    let player = spawn_test_player(&mut app, Vec2::new(x, y), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    app.world.entity_mut(player).remove::<Gravity>();
+    let mut max_heat: f32 = 0.0;
+    while x < 300.0 {
+        app.world.get_mut::<Transform>(player).unwrap().translation = Vec3::new(x, y, 0.0);
+        assert_eq!(run(&mut app, &mut deaths, 1), 0);
+        if let Some(scorching) = app.world.get::<Scorching>(player) {
+            max_heat = max_heat.max(scorching.heat);
+        }
+        x += 800.0 * TEST_DT;
+    }
+    assert!(max_heat > 0.2 && max_heat < 0.6, "{max_heat}");
+
+    // Out of it, the heat goes away
+    app.world.get_mut::<Transform>(player).unwrap().translation = Vec3::new(600.0, y, 0.0);
+    assert_eq!(run(&mut app, &mut deaths, 60), 0);
+    assert!(app.world.get::<Scorching>(player).is_none());
+}
+
+// Anything other than the player burns up as soon as it touches lava, while water floats it
+#[test]
+fn lava_burns_bodies_and_water_floats_them() {
+    let mut app = fluid_app(LAVA);
+    let above = Vec2::new(0.0, surface(&app) + 2.0 * RADIUS);
+    let debris = spawn_test_ball(&mut app, above, RADIUS);
+    let aside = spawn_test_ball(&mut app, Vec2::new(500.0, above.y), RADIUS);
+    let mut burns = ManualEventReader::<BurnedEvent>::default();
+    let mut burned = Vec::new();
+    for _ in 0..30 {
+        app.update();
+        burned.extend(
+            burns
+                .iter(app.world.resource::<Events<BurnedEvent>>())
+                .map(|burn| burn.position),
+        );
+    }
+    assert!(app.world.get_entity(debris).is_none());
+    assert_eq!(burned.len(), 1);
+    assert!(
+        (burned[0].y - surface(&app)).abs() < 2.0 * RADIUS,
+        "{}",
+        burned[0]
+    );
+    assert!(app.world.get_entity(aside).is_some());
+
+    let mut app = fluid_app(WATER);
+    let ball = spawn_test_ball(&mut app, above, RADIUS);
+    for _ in 0..240 {
+        app.update();
+    }
+    // Bobbing at about half under, not burned or sunk
+    let y = position(&app, ball).y;
+    assert!((y - surface(&app)).abs() < 0.5 * RADIUS, "{y}");
+    let vel = app.world.get::<PhysObj>(ball).unwrap().vel;
+    assert!(vel.length() < 20.0, "{vel}");
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "ldtk")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    ldtk::{import_ldtk, merge_tiles, LdtkError, TileRect},
+    level::SurfaceMaterial,
+};
+
+const FIXTURE: &str = include_str!("fixtures/fixture.ldtk");
+const SAMPLE: &str = include_str!("../assets/levels/sample.ldtk");
+const FLOOR_Y: f32 = -100.0;
+
+fn rect(x: u32, y: u32, width: u32, height: u32, value: i64) -> TileRect {
+    TileRect {
+        x,
+        y,
+        width,
+        height,
+        value,
+    }
+}
+
+// Runs of the same value on a row are one rectangle, and so are runs just like them on the rows
+// below
+#[test]
+fn tiles_merge_into_rectangles() {
+    #[rustfmt::skip]
+    let cells = [
+        0, 0, 0, 0, 0, 0,
+        0, 1, 1, 0, 0, 0,
+        1, 1, 0, 0, 3, 3,
+        1, 1, 4, 4, 3, 2,
+    ];
+    assert_eq!(
+        merge_tiles(&cells, 6),
+        vec![
+            rect(1, 1, 2, 1, 1),
+            rect(0, 2, 2, 2, 1),
+            rect(4, 2, 2, 1, 3),
+            rect(2, 3, 2, 1, 4),
+            rect(4, 3, 1, 1, 3),
+            rect(5, 3, 1, 1, 2),
+        ]
+    );
+
+    // A solid block is a single rectangle, however big
+    assert_eq!(
+        merge_tiles(&[1; 100 * 20], 100),
+        vec![rect(0, 0, 100, 20, 1)]
+    );
+    assert!(merge_tiles(&[0; 12], 4).is_empty());
+}
+
+#[test]
+fn fixture_maps_to_the_level() {
+    let level = import_ldtk(FIXTURE, "Fixture", FLOOR_Y).unwrap();
+
+    // The bottom row, but not the floating tiles above it
+    let floors: Vec<_> = level
+        .floors
+        .iter()
+        .map(|floor| (floor.x, floor.width, floor.material))
+        .collect();
+    assert_eq!(floors.len(), 3);
+    assert_eq!((floors[0].0, floors[0].1), (10.0, 20.0));
+    assert!(matches!(floors[0].2, SurfaceMaterial::Normal));
+    assert_eq!((floors[1].0, floors[1].1), (45.0, 10.0));
+    assert!(matches!(floors[1].2, SurfaceMaterial::Sticky { .. }));
+    assert_eq!((floors[2].0, floors[2].1), (55.0, 10.0));
+    assert!(matches!(floors[2].2, SurfaceMaterial::Hazard));
+    assert_eq!(level.crumbling.len(), 1);
+    assert_eq!(
+        (level.crumbling[0].x, level.crumbling[0].width),
+        (30.0, 20.0)
+    );
+
+    // The top of the bottom row is the floor, so y is up from FLOOR_Y
+    assert_eq!(level.balls.len(), 1);
+    assert!(level.balls[0].player);
+    assert_eq!(level.balls[0].position, Vec2::new(15.0, FLOOR_Y + 10.0));
+    assert_eq!(level.goal, Some(55.0));
+    assert_eq!(level.saws.len(), 1);
+    assert_eq!(level.saws[0].position, Vec2::new(30.0, FLOOR_Y + 20.0));
+    assert_eq!(level.saws[0].blade.radius, 5.0);
+    assert_eq!(level.saws[0].blade.angular_speed, 5.0);
+    assert_eq!(level.ropes.len(), 1);
+    assert_eq!(level.ropes[0].position, Vec2::new(40.0, FLOOR_Y + 30.0));
+    assert_eq!(level.ropes[0].anchor.segments, 1);
+    // The Coin isn't anything
+    assert!(level.pickups.is_empty() && level.fluids.is_empty());
+
+    assert!(matches!(
+        import_ldtk(FIXTURE, "Elsewhere", FLOOR_Y),
+        Err(LdtkError::MissingLevel(_))
+    ));
+    assert!(matches!(
+        import_ldtk("{}", "Fixture", FLOOR_Y),
+        Err(LdtkError::Json(_))
+    ));
+}
+
+// The sample map that ships with the game imports whole: a floor across the level, and everything
+// on it
+#[test]
+fn sample_map_imports() {
+    let level = import_ldtk(SAMPLE, "Sample", FLOOR_Y).unwrap();
+    let width: f32 = level
+        .floors
+        .iter()
+        .map(|floor| floor.width)
+        .chain(level.crumbling.iter().map(|crumbling| crumbling.width))
+        .sum();
+    assert_eq!(width, 1600.0);
+    assert_eq!(level.balls.len(), 1);
+    assert!(level.goal.is_some());
+    assert_eq!(level.fluids.len(), 2);
+    assert_eq!(level.ropes.len(), 1);
+    assert_eq!(level.saws.len(), 1);
+    assert_eq!(level.pickups.len(), 1);
+}
@@ -0,0 +1,195 @@
+#![cfg(all(feature = "leaderboard", not(target_arch = "wasm32")))]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use bevy::{prelude::*, tasks::block_on};
+use bevy_game::{
+    leaderboard::{
+        client::{fetch_top, submit},
+        leaderboard_lines, leaderboard_poll_system, Leaderboard, LeaderboardEntry,
+        LeaderboardError, LeaderboardStatus, Submission,
+    },
+    testing::test_app,
+};
+
+const TOP: &str =
+    r#"{"level":2,"entries":[{"name":"ada","time":31.5},{"name":"bo","time":40.25}]}"#;
+
+// Answers one request per response, in order, then returns the requests it got as
+// "<method> <path>" and the body
+fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<(String, String)>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                let lower = header.to_ascii_lowercase();
+                if let Some(value) = lower.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request_body = vec![0; length];
+            reader.read_exact(&mut request_body).unwrap();
+            let method_path = request_line.split_whitespace().take(2).collect::<Vec<_>>();
+            requests.push((
+                method_path.join(" "),
+                String::from_utf8(request_body).unwrap(),
+            ));
+
+            write!(
+                stream,
+                "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (endpoint, server)
+}
+
+// An endpoint nothing's listening on
+fn offline_endpoint() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+fn submission() -> Submission {
+    Submission {
+        level: 2,
+        time: 33.0,
+        replay_hash: Some("00ff00ff00ff00ff".to_string()),
+    }
+}
+
+#[test]
+fn client_submits_and_fetches_top_times() {
+    let (endpoint, server) = mock_server(vec![(201, ""), (200, TOP)]);
+    block_on(submit(&format!("{endpoint}/"), &submission())).unwrap();
+    let top = block_on(fetch_top(&endpoint, 2)).unwrap();
+    assert_eq!(
+        top.entries,
+        vec![
+            LeaderboardEntry {
+                name: "ada".to_string(),
+                time: 31.5,
+            },
+            LeaderboardEntry {
+                name: "bo".to_string(),
+                time: 40.25,
+            },
+        ]
+    );
+
+    let requests = server.join().unwrap();
+    assert_eq!(requests[0].0, "POST /times");
+    let body = &requests[0].1;
+    assert!(body.contains(r#""level":2"#), "{body}");
+    assert!(body.contains(r#""time":33.0"#), "{body}");
+    assert!(
+        body.contains(r#""replay_hash":"00ff00ff00ff00ff""#),
+        "{body}"
+    );
+    assert_eq!(requests[1].0, "GET /times/2?limit=10");
+}
+
+#[test]
+fn client_reports_failures() {
+    let (endpoint, server) = mock_server(vec![(500, "oops"), (200, "not json")]);
+    assert!(matches!(
+        block_on(submit(&endpoint, &submission())),
+        Err(LeaderboardError::Status(500))
+    ));
+    assert!(matches!(
+        block_on(fetch_top(&endpoint, 2)),
+        Err(LeaderboardError::Response(_))
+    ));
+    server.join().unwrap();
+
+    assert!(matches!(
+        block_on(fetch_top(&offline_endpoint(), 2)),
+        Err(LeaderboardError::Request(_))
+    ));
+}
+
+// Updates until the request's done, without blocking any of the updates
+fn poll_until_done(app: &mut App) -> LeaderboardStatus {
+    for _ in 0..500 {
+        app.update();
+        let status = &app.world.resource::<Leaderboard>().status;
+        if *status != LeaderboardStatus::Loading {
+            return status.clone();
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("The request never finished");
+}
+
+fn leaderboard_app() -> App {
+    let mut app = test_app();
+    app.init_resource::<Leaderboard>()
+        .add_system(leaderboard_poll_system);
+    app
+}
+
+#[test]
+fn polling_picks_up_finished_requests() {
+    let (endpoint, server) = mock_server(vec![(201, ""), (200, TOP)]);
+    let mut app = leaderboard_app();
+    app.world
+        .resource_mut::<Leaderboard>()
+        .request(&endpoint, submission());
+    assert_eq!(
+        app.world.resource::<Leaderboard>().status,
+        LeaderboardStatus::Loading
+    );
+    let LeaderboardStatus::Loaded(entries) = poll_until_done(&mut app) else {
+        panic!("The top times didn't load");
+    };
+    assert_eq!(entries.len(), 2);
+    assert_eq!(server.join().unwrap().len(), 2);
+
+    let lines = leaderboard_lines(&LeaderboardStatus::Loaded(entries), 0.0);
+    assert_eq!(lines.len(), 3);
+    assert!(
+        lines[1].contains("ada") && lines[1].contains("0:31.5"),
+        "{lines:?}"
+    );
+}
+
+// Without a connection, the screen says so instead of loading forever
+#[test]
+fn polling_falls_back_to_offline() {
+    let mut app = leaderboard_app();
+    app.world
+        .resource_mut::<Leaderboard>()
+        .request(&offline_endpoint(), submission());
+    assert!(matches!(
+        poll_until_done(&mut app),
+        LeaderboardStatus::Offline(_)
+    ));
+
+    // The spinner turns while loading
+    assert_ne!(
+        leaderboard_lines(&LeaderboardStatus::Loading, 0.0),
+        leaderboard_lines(&LeaderboardStatus::Loading, 0.3)
+    );
+}
@@ -0,0 +1,138 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use bevy_game::{
+    level::{Level, LevelPlugin},
+    menu::{level_stats_lines, MenuPlugin},
+    physics::PhysObj,
+    player::Player,
+    progress::{LevelStats, LevelTimer, Progress, ProgressPlugin, Score},
+    state::AppState,
+    storage::{MemoryBackend, Storage},
+    testing::test_app,
+};
+
+#[test]
+fn stats_lines_compare_with_the_best_time() {
+    let mut stats = LevelStats {
+        time: 83.25,
+        previous_best: Some(80.0),
+        new_best: false,
+        score: 12,
+        deaths: 3,
+    };
+    assert_eq!(
+        level_stats_lines(&stats),
+        ["Time 1:23.2", "Best 1:20.0", "Score 12", "Deaths 3"]
+    );
+    stats.new_best = true;
+    assert_eq!(level_stats_lines(&stats)[1], "New best time!");
+    // The first completion is a new best with nothing to compare to
+    stats.previous_best = None;
+    assert_eq!(level_stats_lines(&stats)[1], "New best time!");
+    stats.new_best = false;
+    assert_eq!(level_stats_lines(&stats).len(), 3);
+}
+
+fn state(app: &App) -> AppState {
+    app.world.resource::<State<AppState>>().0
+}
+
+// Playing the default level, with its goal a little way right of the player
+fn goal_app() -> (App, Entity) {
+    let mut app = test_app();
+    let mut level = Level::default();
+    let start = level
+        .balls
+        .iter()
+        .find(|ball| ball.player)
+        .unwrap()
+        .position;
+    level.goal = Some(start.x + 150.0);
+    app.add_plugin(AssetPlugin::default())
+        .insert_resource(Storage(Box::<MemoryBackend>::default()))
+        .insert_resource(level)
+        .add_plugin(LevelPlugin)
+        .add_plugin(ProgressPlugin)
+        .add_plugin(MenuPlugin);
+    app.update();
+    app.update();
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Playing);
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+    (app, player)
+}
+
+// Reaching the goal stops the clock and the physics on that frame, and the stats are what the
+// level's resources were then
+#[test]
+fn reaching_the_goal_snapshots_the_run() {
+    let (mut app, player) = goal_app();
+    app.world.resource_mut::<Score>().0 = 7;
+    app.world.resource_mut::<Progress>().deaths = 2;
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+
+    let mut last_time = 0.0;
+    for _ in 0..120 {
+        app.update();
+        if state(&app) == AppState::LevelComplete {
+            break;
+        }
+        last_time = app.world.resource::<LevelTimer>().elapsed;
+    }
+    assert_eq!(state(&app), AppState::LevelComplete);
+    assert!(last_time > 0.0);
+
+    let stats = app.world.resource::<LevelStats>();
+    assert_eq!(stats.time, last_time);
+    assert_eq!((stats.score, stats.deaths), (7, 2));
+    assert!(stats.new_best && stats.previous_best.is_none());
+    let best = app.world.resource::<Progress>().levels[0].best_time;
+    assert_eq!(best, Some(last_time));
+
+    // Frozen from then on
+    let position = app.world.get::<Transform>(player).unwrap().translation;
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<LevelTimer>().elapsed, last_time);
+    assert_eq!(
+        app.world.get::<Transform>(player).unwrap().translation,
+        position
+    );
+    assert_eq!(app.world.resource::<LevelStats>().time, last_time);
+
+    // And the screen shows them
+    let shown: Vec<String> = app
+        .world
+        .query::<&Text>()
+        .iter(&app.world)
+        .map(|text| text.sections[0].value.clone())
+        .collect();
+    let stats = app.world.resource::<LevelStats>();
+    for line in level_stats_lines(stats) {
+        assert!(shown.contains(&line), "{line} {shown:?}");
+    }
+}
+
+// Completing it again slower compares with the first time
+#[test]
+fn a_slower_run_shows_the_best_time() {
+    let (mut app, player) = goal_app();
+    app.world
+        .resource_mut::<Progress>()
+        .record_completion(0, 0.01, 0);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+    for _ in 0..120 {
+        app.update();
+    }
+    assert_eq!(state(&app), AppState::LevelComplete);
+    let stats = app.world.resource::<LevelStats>();
+    assert!(!stats.new_best);
+    assert_eq!(stats.previous_best, Some(0.01));
+    assert!(stats.time > 0.01);
+}
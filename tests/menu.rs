@@ -0,0 +1,277 @@
+use bevy::{app::AppExit, asset::AssetPlugin, prelude::*};
+use bevy_game::{
+    level::RestartLevelEvent,
+    menu::{
+        menu_items, navigate,
+        settings::{Rebinding, SettingsRow},
+        MenuInput, MenuItem, MenuPlugin, MenuSelection, SettingsReturn,
+    },
+    settings::{InputAction, Settings},
+    state::AppState,
+    storage::{load_ron, MemoryBackend, Storage},
+    testing::{press_key, test_app},
+};
+
+fn menu_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .insert_resource(Storage(Box::<MemoryBackend>::default()))
+        .add_plugin(MenuPlugin);
+    // Loads, then opens the main menu
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::MainMenu);
+    app
+}
+
+fn state(app: &App) -> AppState {
+    app.world.resource::<State<AppState>>().0
+}
+
+fn go_to(app: &mut App, state: AppState) {
+    app.world.resource_mut::<NextState<AppState>>().set(state);
+    app.update();
+}
+
+fn tap(app: &mut App, key: KeyCode) {
+    press_key(app, key, true);
+    app.update();
+    press_key(app, key, false);
+}
+
+fn selection(app: &App) -> usize {
+    app.world.resource::<MenuSelection>().0
+}
+
+#[test]
+fn selection_wraps_around() {
+    assert_eq!(navigate(0, 3, MenuInput::Down), 1);
+    assert_eq!(navigate(2, 3, MenuInput::Down), 0);
+    assert_eq!(navigate(0, 3, MenuInput::Up), 2);
+    assert_eq!(navigate(2, 3, MenuInput::Up), 1);
+    assert_eq!(navigate(0, 1, MenuInput::Down), 0);
+    for input in [MenuInput::Left, MenuInput::Right, MenuInput::Choose] {
+        assert_eq!(navigate(1, 3, input), 1);
+    }
+    // An empty menu has nothing to select but the top
+    for input in [MenuInput::Up, MenuInput::Down] {
+        assert_eq!(navigate(0, 0, input), 0);
+    }
+}
+
+#[test]
+fn each_menu_has_its_items() {
+    #[cfg(not(target_arch = "wasm32"))]
+    assert_eq!(
+        menu_items(AppState::MainMenu),
+        [MenuItem::Play, MenuItem::Settings, MenuItem::Quit]
+    );
+    assert_eq!(
+        menu_items(AppState::Paused),
+        [
+            MenuItem::Resume,
+            MenuItem::RestartLevel,
+            MenuItem::Settings,
+            MenuItem::MainMenu
+        ]
+    );
+    assert_eq!(
+        menu_items(AppState::LevelComplete),
+        [MenuItem::Retry, MenuItem::MainMenu]
+    );
+    for state in [AppState::Loading, AppState::Playing, AppState::SettingsMenu] {
+        assert!(menu_items(state).is_empty(), "{state:?}");
+    }
+}
+
+#[test]
+fn each_item_goes_to_its_state() {
+    let expected = [
+        (MenuItem::Play, Some(AppState::Playing)),
+        (MenuItem::Resume, Some(AppState::Playing)),
+        (MenuItem::RestartLevel, Some(AppState::Playing)),
+        (MenuItem::Retry, Some(AppState::Playing)),
+        (MenuItem::Settings, Some(AppState::SettingsMenu)),
+        (MenuItem::MainMenu, Some(AppState::MainMenu)),
+        (MenuItem::Quit, None),
+    ];
+    for (item, state) in expected {
+        assert_eq!(item.next_state(), state, "{item:?}");
+    }
+}
+
+// Up and down move the selection around the menu, which starts at the top each time it opens
+#[test]
+fn keys_move_the_selection() {
+    let mut app = menu_app();
+    assert_eq!(selection(&app), 0);
+    tap(&mut app, KeyCode::Down);
+    assert_eq!(selection(&app), 1);
+    tap(&mut app, KeyCode::S);
+    assert_eq!(selection(&app), 2);
+    tap(&mut app, KeyCode::Up);
+    tap(&mut app, KeyCode::W);
+    tap(&mut app, KeyCode::Up);
+    let len = menu_items(AppState::MainMenu).len();
+    assert_eq!(selection(&app), len - 1);
+    // Left and right are for settings
+    tap(&mut app, KeyCode::Left);
+    assert_eq!(selection(&app), len - 1);
+    assert_eq!(state(&app), AppState::MainMenu);
+
+    go_to(&mut app, AppState::Paused);
+    assert_eq!(selection(&app), 0);
+}
+
+// Choosing every item of every menu with the keyboard does what the item says
+#[test]
+fn choosing_an_item_changes_the_state() {
+    for menu in [
+        AppState::MainMenu,
+        AppState::Paused,
+        AppState::LevelComplete,
+    ] {
+        for (i, item) in menu_items(menu).into_iter().enumerate() {
+            let mut app = menu_app();
+            if menu != AppState::MainMenu {
+                go_to(&mut app, menu);
+            }
+            for _ in 0..i {
+                tap(&mut app, KeyCode::Down);
+            }
+            tap(&mut app, KeyCode::Return);
+
+            let restarted = !app.world.resource::<Events<RestartLevelEvent>>().is_empty();
+            assert_eq!(
+                restarted,
+                matches!(item, MenuItem::RestartLevel | MenuItem::Retry),
+                "{menu:?} {item:?}"
+            );
+            let quit = !app.world.resource::<Events<AppExit>>().is_empty();
+            assert_eq!(quit, item == MenuItem::Quit, "{menu:?} {item:?}");
+            if item == MenuItem::Settings {
+                assert_eq!(app.world.resource::<SettingsReturn>().0, menu);
+            }
+
+            app.update();
+            let expected = item.next_state().unwrap_or(menu);
+            assert_eq!(state(&app), expected, "{menu:?} {item:?}");
+        }
+    }
+}
+
+// A free key is bound straight away, a taken one only once the swap is confirmed, and Escape
+// cancels either way instead of being bound
+#[test]
+fn rebinding_asks_before_taking_a_bound_key() {
+    let mut settings = Settings::default();
+    let jump = Rebinding::Waiting(InputAction::Jump);
+    assert_eq!(jump.press(&mut settings, KeyCode::J), Rebinding::Idle);
+    assert_eq!(settings.input.jump, KeyCode::J);
+
+    let conflict = jump.press(&mut settings, KeyCode::W);
+    assert_eq!(
+        conflict,
+        Rebinding::Conflict {
+            action: InputAction::Jump,
+            key: KeyCode::W,
+            other: InputAction::ClimbUp,
+        }
+    );
+    assert_eq!(settings.input.jump, KeyCode::J);
+    // Other keys leave the question open
+    assert_eq!(conflict.press(&mut settings, KeyCode::Q), conflict);
+    assert_eq!(
+        conflict.press(&mut settings, KeyCode::Escape),
+        Rebinding::Idle
+    );
+    assert_eq!(settings.input.climb_up, KeyCode::W);
+
+    assert_eq!(
+        conflict.press(&mut settings, KeyCode::Return),
+        Rebinding::Idle
+    );
+    assert_eq!(settings.input.jump, KeyCode::W);
+    assert_eq!(settings.input.climb_up, KeyCode::J);
+
+    let before = settings.input.clone();
+    assert_eq!(jump.press(&mut settings, KeyCode::Escape), Rebinding::Idle);
+    assert!(settings.input == before);
+    assert_eq!(
+        Rebinding::Idle.press(&mut settings, KeyCode::X),
+        Rebinding::Idle
+    );
+}
+
+#[test]
+fn settings_rows_step_within_their_range() {
+    let mut settings = Settings::default();
+    SettingsRow::MasterVolume.adjust(&mut settings, 1);
+    assert_eq!(settings.master_volume, 1.0);
+    for _ in 0..3 {
+        SettingsRow::MasterVolume.adjust(&mut settings, -1);
+    }
+    assert!((settings.master_volume - 0.7).abs() < 1e-6);
+    for _ in 0..20 {
+        SettingsRow::MusicVolume.adjust(&mut settings, -1);
+        SettingsRow::PhysicsSubsteps.adjust(&mut settings, -1);
+    }
+    assert_eq!((settings.music_volume, settings.physics_substeps), (0.0, 1));
+    SettingsRow::Vsync.adjust(&mut settings, -1);
+    assert!(!settings.vsync);
+    SettingsRow::Vsync.adjust(&mut settings, 1);
+    assert!(settings.vsync);
+}
+
+fn saved_settings(app: &App) -> Option<Settings> {
+    load_ron::<Settings>(app.world.resource::<Storage>().0.as_ref(), "settings").map(Result::unwrap)
+}
+
+fn select_row(app: &mut App, row: SettingsRow) {
+    let index = SettingsRow::all()
+        .iter()
+        .position(|&other| other == row)
+        .unwrap();
+    while selection(app) != index {
+        tap(app, KeyCode::Down);
+    }
+}
+
+// Changes in the settings menu take effect straight away, and are saved when leaving it
+#[test]
+fn settings_apply_at_once_and_save_on_leaving() {
+    let mut app = menu_app();
+    go_to(&mut app, AppState::SettingsMenu);
+    assert_eq!(selection(&app), 0);
+    tap(&mut app, KeyCode::Left);
+    assert!((app.world.resource::<Settings>().master_volume - 0.9).abs() < 1e-6);
+
+    // Rebinding jump to W, which climbs, and confirming the swap
+    select_row(&mut app, SettingsRow::Binding(InputAction::Jump));
+    tap(&mut app, KeyCode::Return);
+    assert_eq!(
+        *app.world.resource::<Rebinding>(),
+        Rebinding::Waiting(InputAction::Jump)
+    );
+    tap(&mut app, KeyCode::W);
+    tap(&mut app, KeyCode::Return);
+    assert_eq!(*app.world.resource::<Rebinding>(), Rebinding::Idle);
+    assert_eq!(app.world.resource::<Settings>().input.jump, KeyCode::W);
+    assert!(saved_settings(&app).is_none());
+
+    // Escape while rebinding only cancels the rebinding
+    tap(&mut app, KeyCode::Return);
+    tap(&mut app, KeyCode::Escape);
+    app.update();
+    assert_eq!(state(&app), AppState::SettingsMenu);
+    assert_eq!(app.world.resource::<Settings>().input.jump, KeyCode::W);
+
+    tap(&mut app, KeyCode::Escape);
+    app.update();
+    assert_eq!(state(&app), AppState::MainMenu);
+    let saved = saved_settings(&app).expect("saved on leaving");
+    assert!(saved == *app.world.resource::<Settings>());
+    assert!((saved.master_volume - 0.9).abs() < 1e-6);
+    assert_eq!(saved.input.jump, KeyCode::W);
+    assert_eq!(saved.input.climb_up, KeyCode::Space);
+}
@@ -0,0 +1,38 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use bevy_game::{mesh_cache::MeshCache, shapes::FidgetSpinner};
+
+fn asset_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .init_resource::<MeshCache>();
+    app
+}
+
+#[test]
+fn identical_balls_share_a_mesh_and_material() {
+    let mut app = asset_app();
+    let world = &mut app.world;
+    world.resource_scope(|world, mut cache: Mut<MeshCache>| {
+        let mut handles = Vec::new();
+        world.resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
+            let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+            let (mesh_count, material_count) = (meshes.len(), materials.len());
+            for i in 0..500 {
+                // Differences smaller than the rounding step don't make a new mesh
+                let radius = 10.0 + (i % 3) as f32 * 0.001;
+                handles.push((
+                    cache.spinner(&mut meshes, FidgetSpinner::new(radius)),
+                    cache.material(&mut materials, Color::BLUE),
+                ));
+            }
+            assert_eq!(meshes.len(), mesh_count + 1);
+            assert_eq!(materials.len(), material_count + 1);
+
+            cache.spinner(&mut meshes, FidgetSpinner::new(12.0));
+            assert_eq!(meshes.len(), mesh_count + 2);
+        });
+    });
+}
@@ -0,0 +1,160 @@
+#![cfg(all(feature = "netplay", not(target_arch = "wasm32")))]
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use bevy_game::{
+    level::{spawn_ball, Level},
+    netplay::lockstep::{state_hash, Desync, DesyncDetector, LockstepBuffer},
+    physics::{PhysObj, PhysicsConfig},
+    player::PlayerInput,
+    testing::test_app,
+};
+
+fn input(jump: bool) -> PlayerInput {
+    PlayerInput { jump, ..default() }
+}
+
+// The first `delay` ticks run on empty inputs, and local inputs are for the ticks after them
+#[test]
+fn local_inputs_are_delayed() {
+    let mut buffer = LockstepBuffer::new(2);
+    assert_eq!(buffer.add_local(input(true)), Some(2));
+    for tick in 0..2 {
+        buffer.add_remote(tick, PlayerInput::default());
+        // Already there: the other side skips the same ticks
+        assert_eq!(
+            buffer.advance(),
+            Some((tick, PlayerInput::default(), PlayerInput::default()))
+        );
+    }
+    buffer.add_remote(2, input(false));
+    assert_eq!(buffer.advance(), Some((2, input(true), input(false))));
+    assert_eq!(buffer.next_tick(), 3);
+}
+
+// A tick waits for the other side's input, and the local side can't get more than the delay
+// ahead of it meanwhile
+#[test]
+fn ticks_wait_for_the_remote_input() {
+    let mut buffer = LockstepBuffer::new(2);
+    for tick in 0..2 {
+        buffer.add_remote(tick, PlayerInput::default());
+    }
+    assert_eq!(buffer.add_local(input(true)), Some(2));
+    assert!(buffer.advance().is_some());
+    assert!(buffer.advance().is_some());
+    assert_eq!(buffer.advance(), None);
+
+    assert_eq!(buffer.add_local(input(true)), Some(3));
+    assert_eq!(buffer.add_local(input(true)), Some(4));
+    // Full until tick 2's remote input arrives
+    assert_eq!(buffer.add_local(input(true)), None);
+    assert_eq!(buffer.advance(), None);
+
+    // Out of order, and twice
+    buffer.add_remote(3, input(false));
+    buffer.add_remote(2, input(true));
+    buffer.add_remote(2, input(true));
+    assert_eq!(buffer.advance(), Some((2, input(true), input(true))));
+    assert_eq!(buffer.advance(), Some((3, input(true), input(false))));
+    assert_eq!(buffer.add_local(input(false)), Some(5));
+
+    // Late duplicates of simulated ticks don't come back
+    buffer.add_remote(2, input(true));
+    assert_eq!(buffer.advance(), None);
+    assert_eq!(buffer.next_tick(), 4);
+}
+
+#[test]
+fn mismatched_hashes_are_a_desync() {
+    let mut detector = DesyncDetector::default();
+    assert_eq!(detector.record_local(0, 7), None);
+    assert_eq!(detector.record_remote(0, 7), None);
+    // Either side can be first
+    assert_eq!(detector.record_remote(30, 8), None);
+    assert_eq!(
+        detector.record_local(30, 9),
+        Some(Desync {
+            tick: 30,
+            local: 9,
+            remote: 8,
+        })
+    );
+    assert_eq!(detector.record_local(60, 1), None);
+}
+
+fn body(x: f32, vel: Vec2) -> (Transform, PhysObj) {
+    let transform = Transform::from_xyz(x, 10.0, 0.0).with_rotation(Quat::from_rotation_z(0.3));
+    let phys_obj = PhysObj {
+        mass: 10.0,
+        vel,
+        acc: Vec2::ZERO,
+        acc_prev: Vec2::ZERO,
+        moment_of_inertia: 100.0,
+        angular_vel: 2.0,
+        angular_acc: 0.0,
+        angular_acc_prev: 0.0,
+        com_offset: Vec2::ZERO,
+    };
+    (transform, phys_obj)
+}
+
+fn hash(bodies: &[(Transform, PhysObj)]) -> u64 {
+    state_hash(
+        bodies
+            .iter()
+            .map(|(transform, phys_obj)| (transform, phys_obj)),
+    )
+}
+
+#[test]
+fn state_hash_covers_every_body_in_any_order() {
+    let bodies = [
+        body(0.0, Vec2::X),
+        body(50.0, Vec2::Y),
+        body(-20.0, Vec2::ZERO),
+    ];
+    let reversed: Vec<_> = bodies.iter().rev().cloned().collect();
+    assert_eq!(hash(&bodies), hash(&reversed));
+    assert_ne!(hash(&bodies), hash(&bodies[..2]));
+
+    // The smallest drift changes it
+    let mut drifted = bodies.clone();
+    drifted[1].0.translation.x = f32::from_bits(drifted[1].0.translation.x.to_bits() + 1);
+    assert_ne!(hash(&bodies), hash(&drifted));
+    let mut drifted = bodies.clone();
+    drifted[2].1.angular_vel += 1e-6;
+    assert_ne!(hash(&bodies), hash(&drifted));
+    // Swapping two bodies' velocities isn't the same state
+    let mut swapped = bodies.clone();
+    swapped[0].1.vel = Vec2::Y;
+    swapped[1].1.vel = Vec2::X;
+    assert_ne!(hash(&bodies), hash(&swapped));
+}
+
+// A player with its own PlayerInput follows that instead of the keyboard, so two can be driven
+// separately
+#[test]
+fn players_follow_their_own_input() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let mut queue = CommandQueue::default();
+    let mut ball = Level::default().balls[0].clone();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let keyboard = spawn_ball(&mut commands, &config, &ball);
+    ball.position.x += 200.0;
+    let own = spawn_ball(&mut commands, &config, &ball);
+    queue.apply(&mut app.world);
+    app.world.entity_mut(own).insert(PlayerInput {
+        spin_left: true,
+        ..default()
+    });
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+
+    for _ in 0..30 {
+        app.update();
+    }
+    let angular_vel = |entity| app.world.get::<PhysObj>(entity).unwrap().angular_vel;
+    // Spinning left is counter-clockwise
+    assert!(angular_vel(keyboard) < -1.0, "{}", angular_vel(keyboard));
+    assert!(angular_vel(own) > 1.0, "{}", angular_vel(own));
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysObj, PhysicsConfig},
+    testing::{spawn_test_ball, test_app},
+};
+
+const BODIES: usize = 100;
+const FRAMES: usize = 300;
+
+// Positions and velocities of the bodies after FRAMES frames, in spawn order
+fn simulate(serial: bool) -> Vec<(Vec3, Quat, Vec2, f32)> {
+    let mut app = test_app();
+    app.insert_resource(PhysicsConfig {
+        serial,
+        ..default()
+    });
+    let entities: Vec<Entity> = (0..BODIES)
+        .map(|i| {
+            let i = i as f32;
+            let position = Vec2::new(30.0 * i - 1500.0, 100.0 + 7.0 * i);
+            let body = spawn_test_ball(&mut app, position, 10.0);
+            let mut phys_obj = app.world.get_mut::<PhysObj>(body).unwrap();
+            phys_obj.mass = 1.0 + 0.1 * i;
+            phys_obj.vel = Vec2::new(50.0 - i, 10.0 * i);
+            phys_obj.moment_of_inertia = 50.0;
+            phys_obj.angular_vel = 0.5 * i - 25.0;
+            let mut collider = app.world.get_mut::<Collider>(body).unwrap();
+            let Collider::Ball {
+                coef_of_restitution,
+                ..
+            } = &mut *collider;
+            *coef_of_restitution = 0.8;
+            body
+        })
+        .collect();
+
+    for _ in 0..FRAMES {
+        app.update();
+    }
+
+    entities
+        .into_iter()
+        .map(|entity| {
+            let entity = app.world.entity(entity);
+            let transform = entity.get::<Transform>().unwrap();
+            let phys_obj = entity.get::<PhysObj>().unwrap();
+            (
+                transform.translation,
+                transform.rotation,
+                phys_obj.vel,
+                phys_obj.angular_vel,
+            )
+        })
+        .collect()
+}
+
+// Each body is integrated on its own, so the thread pool can't change the results
+#[test]
+fn parallel_integration_matches_serial() {
+    assert_eq!(simulate(true), simulate(false));
+}
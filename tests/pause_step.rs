@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{PhysicsConfig, PhysicsPlugin, PhysicsSchedule, PhysicsSet, PhysicsTime, STEP_DT},
+    state::AppState,
+    testing::{press_key, spawn_test_ball, test_app, test_app_with},
+};
+
+// Physics steps run so far, counted from the integration stage
+#[derive(Resource, Default)]
+struct Steps(usize);
+
+fn count_steps(mut steps: ResMut<Steps>) {
+    steps.0 += 1;
+}
+
+fn counted(mut app: App) -> App {
+    app.init_resource::<Steps>().add_system(
+        count_steps
+            .in_set(PhysicsSet::IntegrateStart)
+            .in_schedule(PhysicsSchedule),
+    );
+    app.update();
+    app.update();
+    app
+}
+
+fn steps(app: &App) -> usize {
+    app.world.resource::<Steps>().0
+}
+
+fn tap(app: &mut App, key: KeyCode) {
+    press_key(app, key, true);
+    app.update();
+    press_key(app, key, false);
+}
+
+fn state(app: &App) -> AppState {
+    app.world.resource::<State<AppState>>().0
+}
+
+// While paused nothing moves, and each press of the step key runs exactly one step of STEP_DT
+#[test]
+fn each_step_request_runs_one_step() {
+    let mut app = counted(test_app());
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 300.0), 10.0);
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+
+    tap(&mut app, KeyCode::P);
+    app.update();
+    assert_eq!(state(&app), AppState::Paused);
+    let paused_at = steps(&app);
+    let height = |app: &App| app.world.get::<Transform>(ball).unwrap().translation.y;
+    let y = height(&app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(steps(&app), paused_at);
+    assert_eq!(height(&app), y);
+
+    for request in 1..=5 {
+        tap(&mut app, KeyCode::N);
+        assert_eq!(steps(&app), paused_at + request);
+        assert_eq!(app.world.resource::<PhysicsTime>().delta, STEP_DT);
+        // Holding it doesn't step again
+        app.update();
+        app.update();
+        assert_eq!(steps(&app), paused_at + request);
+    }
+    assert!(height(&app) < y);
+    assert_eq!(state(&app), AppState::Paused);
+
+    tap(&mut app, KeyCode::P);
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+    let resumed_at = steps(&app);
+    app.update();
+    assert_eq!(steps(&app), resumed_at + 1);
+}
+
+// A step is one frame's worth of physics, so with substeps it's that many shorter steps
+#[test]
+fn steps_run_every_substep() {
+    let config = PhysicsConfig {
+        substeps: 4,
+        ..default()
+    };
+    let mut app = counted(test_app_with(PhysicsPlugin::with_config(config)));
+    tap(&mut app, KeyCode::P);
+    app.update();
+    let paused_at = steps(&app);
+
+    tap(&mut app, KeyCode::N);
+    assert_eq!(steps(&app), paused_at + 4);
+    assert_eq!(app.world.resource::<PhysicsTime>().delta, STEP_DT / 4.0);
+}
+
+// The step key does nothing while playing
+#[test]
+fn step_requests_only_count_while_paused() {
+    let mut app = counted(test_app());
+    let before = steps(&app);
+    tap(&mut app, KeyCode::N);
+    app.update();
+    assert_eq!(steps(&app), before + 2);
+}
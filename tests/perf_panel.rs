@@ -0,0 +1,103 @@
+#![cfg(feature = "debug-tools")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    debug::{
+        lines::DebugLines,
+        overlay::RingBuffer,
+        perf::{frame_stats, FrameStats, PerfPanel, PerfPanelPlugin, PERF_PANEL_INTERVAL},
+    },
+    physics::timings::PhysicsTimings,
+    testing::{add_test_clock, TEST_DT},
+};
+
+#[test]
+fn stats_of_known_frames() {
+    let mut buffer = RingBuffer::<120>::default();
+    assert_eq!(frame_stats(&buffer), None);
+    for ms in 1..=100 {
+        buffer.push(ms as f32);
+    }
+    let stats = FrameStats {
+        min: 1.0,
+        avg: 50.5,
+        p99: 99.0,
+        frames: 100,
+    };
+    assert_eq!(frame_stats(&buffer), Some(stats));
+
+    // Once it's full, the oldest frames make way: 81 to 200 are left
+    for ms in 101..=200 {
+        buffer.push(ms as f32);
+    }
+    let stats = FrameStats {
+        min: 81.0,
+        avg: 140.5,
+        p99: 199.0,
+        frames: 120,
+    };
+    assert_eq!(frame_stats(&buffer), Some(stats));
+}
+
+// Of 120 frames, one slow frame is under 1% and doesn't count, but two are over
+#[test]
+fn p99_ignores_a_single_spike() {
+    let mut buffer = RingBuffer::<120>::default();
+    for _ in 0..119 {
+        buffer.push(10.0);
+    }
+    buffer.push(50.0);
+    assert_eq!(frame_stats(&buffer).unwrap().p99, 10.0);
+    buffer.push(50.0);
+    assert_eq!(frame_stats(&buffer).unwrap().p99, 50.0);
+}
+
+// The panel on its own, headless, with the timings being measured
+fn panel_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    add_test_clock(&mut app)
+        .insert_resource(PhysicsTimings {
+            enabled: true,
+            ..default()
+        })
+        .init_resource::<DebugLines>()
+        .add_plugin(PerfPanelPlugin);
+    app
+}
+
+// Which of `updates` updates redrew the panel, going by when its stats changed
+fn redraws(app: &mut App, updates: usize) -> Vec<usize> {
+    let mut redraws = Vec::new();
+    let mut last = app.world.resource::<PerfPanel>().frame_stats;
+    for update in 1..=updates {
+        app.update();
+        let stats = app.world.resource::<PerfPanel>().frame_stats;
+        if stats != last {
+            redraws.push(update);
+            last = stats;
+        }
+    }
+    redraws
+}
+
+#[test]
+fn redraws_are_throttled() {
+    let mut app = panel_app();
+    let interval = (PERF_PANEL_INTERVAL / TEST_DT).round() as usize;
+    let redraws = redraws(&mut app, 100);
+    assert!(redraws.len() >= 100 / interval - 1, "{redraws:?}");
+    assert!(redraws[0] + 1 >= interval, "{redraws:?}");
+    for pair in redraws.windows(2) {
+        assert_eq!(pair[1] - pair[0], interval, "{redraws:?}");
+    }
+}
+
+// With the timings off (F4), there's nothing to show and the panel's left as it was
+#[test]
+fn nothing_while_timings_are_off() {
+    let mut app = panel_app();
+    app.world.resource_mut::<PhysicsTimings>().enabled = false;
+    assert!(redraws(&mut app, 60).is_empty());
+    assert_eq!(app.world.resource::<PerfPanel>().frame_stats, None);
+}
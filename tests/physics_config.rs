@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{CombineRule, PhysObj, PhysicsConfig, PhysicsPlugin},
+    testing::{spawn_test_ball, test_app_with, TEST_DT},
+};
+
+const RADIUS: f32 = 10.0;
+
+fn bottom(app: &App, ball: Entity) -> f32 {
+    app.world.get::<Transform>(ball).unwrap().translation.y - RADIUS
+}
+
+fn settle(app: &mut App) {
+    for _ in 0..120 {
+        app.update();
+    }
+}
+
+// A ball dropped with a plugin made with another floor height comes to rest on that floor
+#[test]
+fn balls_rest_on_the_configured_floor() {
+    let config = PhysicsConfig {
+        floor_y: 100.0,
+        gravity: 1000.0,
+        ..default()
+    };
+    let mut app = test_app_with(PhysicsPlugin::with_config(config));
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, 300.0), RADIUS);
+    settle(&mut app);
+    assert!(
+        (bottom(&app, ball) - 100.0).abs() < 0.5,
+        "{}",
+        bottom(&app, ball)
+    );
+    assert!(app.world.get::<PhysObj>(ball).unwrap().vel.length() < 1.0);
+}
+
+// Moving the floor at runtime takes effect on the next frame, e.g. for a ball already falling
+#[test]
+fn floor_changes_take_effect_on_the_next_frame() {
+    let mut app = test_app_with(PhysicsPlugin::default());
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 300.0), RADIUS);
+    app.update();
+    app.update();
+
+    // Above the ball's bottom, which is pushed out on top of it right away
+    let raised = bottom(&app, ball) + 20.0;
+    app.world.resource_mut::<PhysicsConfig>().floor_y = raised;
+    app.update();
+    assert!(bottom(&app, ball) >= raised - 0.5, "{}", bottom(&app, ball));
+    settle(&mut app);
+    assert!(
+        (bottom(&app, ball) - raised).abs() < 0.5,
+        "{}",
+        bottom(&app, ball)
+    );
+}
+
+// Half of the restitution threshold's speed doesn't bounce, twice it does
+#[test]
+fn slow_impacts_below_the_threshold_do_not_bounce() {
+    let config = PhysicsConfig {
+        restitution_velocity_threshold: 200.0,
+        restitution_combine: CombineRule::Max,
+        ..default()
+    };
+    for (speed, bounces) in [(100.0, false), (400.0, true)] {
+        let mut app = test_app_with(PhysicsPlugin::with_config(config.clone()));
+        let ball = spawn_test_ball(
+            &mut app,
+            Vec2::new(0.0, config.floor_y + RADIUS + 1.0),
+            RADIUS,
+        );
+        app.world.get_mut::<PhysObj>(ball).unwrap().vel.y = -speed;
+        app.update();
+        app.update();
+        let vel = app.world.get::<PhysObj>(ball).unwrap().vel.y;
+        assert_eq!(vel > config.gravity * TEST_DT, bounces, "{speed}: {vel}");
+    }
+}
+
+#[test]
+fn combine_rules() {
+    assert_eq!(CombineRule::Average.combine(0.2, 0.6), 0.4);
+    assert_eq!(CombineRule::Min.combine(0.2, 0.6), 0.2);
+    assert_eq!(CombineRule::Max.combine(0.2, 0.6), 0.6);
+    assert!((CombineRule::Multiply.combine(0.2, 0.6) - 0.12).abs() < 1e-6);
+}
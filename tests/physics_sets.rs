@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{PhysicsConfig, PhysicsPlugin, PhysicsSchedule, PhysicsSet},
+    testing::{spawn_test_ball, test_app_with},
+};
+
+const SETS: [PhysicsSet; 7] = [
+    PhysicsSet::ApplyImpulses,
+    PhysicsSet::IntegrateStart,
+    PhysicsSet::ApplyForces,
+    PhysicsSet::IntegrateEnd,
+    PhysicsSet::ResolveCollisions,
+    PhysicsSet::SolveConstraints,
+    PhysicsSet::PostSolve,
+];
+
+// The sets that ran, in order
+#[derive(Resource, Default)]
+struct Ran(Vec<PhysicsSet>);
+
+fn probe(set: PhysicsSet) -> impl FnMut(ResMut<Ran>) {
+    move |mut ran: ResMut<Ran>| ran.0.push(set)
+}
+
+// Probes added from outside the crate, by set name only, the way a third-party force would be.
+// They're added in reverse so that only the sets' order can put them in the right one.
+fn probed_app(substeps: u32) -> App {
+    let mut app = test_app_with(PhysicsPlugin::with_config(PhysicsConfig {
+        substeps,
+        ..default()
+    }));
+    app.init_resource::<Ran>();
+    for set in SETS.into_iter().rev() {
+        app.add_system(probe(set).in_set(set).in_schedule(PhysicsSchedule));
+    }
+    spawn_test_ball(&mut app, Vec2::ZERO, 10.0);
+    app.update();
+    app
+}
+
+#[test]
+fn sets_run_in_order_every_step() {
+    for substeps in [1, 3] {
+        let mut app = probed_app(substeps);
+        for _ in 0..5 {
+            app.world.resource_mut::<Ran>().0.clear();
+            app.update();
+            let ran = &app.world.resource::<Ran>().0;
+            let expected: Vec<_> = (0..substeps).flat_map(|_| SETS).collect();
+            assert_eq!(*ran, expected);
+        }
+    }
+}
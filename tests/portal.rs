@@ -0,0 +1,173 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    portal::{Portal, PortalEvent, PortalImmunity, PortalPlugin, PORTAL_IMMUNITY},
+    testing::{set_test_ball_mass, set_test_ball_restitution, spawn_test_ball, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 10.0;
+const MASS: f32 = 1.0;
+// High above the floor
+const HEIGHT: f32 = 1000.0;
+
+fn portal_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(PortalPlugin);
+    app
+}
+
+// A pair of portals, at `a` and `b` facing the given angles
+fn spawn_portals(app: &mut App, (a, a_angle): (Vec2, f32), (b, b_angle): (Vec2, f32)) {
+    let ends = [app.world.spawn_empty().id(), app.world.spawn_empty().id()];
+    for (end, (position, angle), exit, exit_angle) in [
+        (ends[0], (a, a_angle), ends[1], b_angle),
+        (ends[1], (b, b_angle), ends[0], a_angle),
+    ] {
+        app.world.entity_mut(end).insert((
+            TransformBundle::from_transform(
+                Transform::from_translation(position.extend(0.0))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ),
+            Portal {
+                id: 0,
+                exit,
+                exit_rotation: Portal::rotation_between(angle, exit_angle),
+            },
+        ));
+    }
+}
+
+fn assert_near(a: Vec2, b: Vec2) {
+    assert!(a.distance(b) < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn velocities_turn_with_the_portals() {
+    let up = FRAC_PI_2;
+    let falling = Vec2::new(0.0, -300.0);
+    let portal = |entry_angle, exit_angle| Portal {
+        id: 0,
+        exit: Entity::PLACEHOLDER,
+        exit_rotation: Portal::rotation_between(entry_angle, exit_angle),
+    };
+
+    // Into the floor, and out of a wall facing right
+    let quarter = portal(up, 0.0);
+    assert_near(quarter.rotate(falling), Vec2::new(300.0, 0.0));
+    // Just behind the entry comes out just in front of the exit
+    let entry = Transform::from_xyz(0.0, 0.0, 0.0);
+    let exit = Transform::from_xyz(100.0, 50.0, 0.0);
+    assert_near(
+        quarter.carry(&entry, &exit, Vec2::new(0.0, -5.0)),
+        Vec2::new(105.0, 50.0),
+    );
+
+    // Into the floor and back out of another, the way it came
+    let half = portal(up, up);
+    assert_near(half.rotate(falling), Vec2::new(0.0, 300.0));
+    assert_near(
+        half.rotate(Vec2::new(100.0, -300.0)),
+        Vec2::new(-100.0, 300.0),
+    );
+    // Out of a ceiling, carrying on the same way
+    assert_near(portal(up, -up).rotate(falling), falling);
+
+    for entry_angle in [0.0, 0.3, up, 2.0, PI, -1.0] {
+        for exit_angle in [0.0, 1.1, -up, PI] {
+            let vel = Vec2::new(-120.0, 45.0);
+            let through = portal(entry_angle, exit_angle).rotate(vel);
+            assert!((through.length() - vel.length()).abs() < 1e-3);
+        }
+    }
+}
+
+// A ball dropped into a portal in the floor comes out of one facing right, going right as fast as
+// it went in, and still spinning the same way
+#[test]
+fn bodies_come_out_of_the_other_portal_at_the_same_speed() {
+    let mut app = portal_app();
+    let exit = Vec2::new(1000.0, HEIGHT);
+    spawn_portals(&mut app, (Vec2::new(0.0, HEIGHT), FRAC_PI_2), (exit, 0.0));
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, HEIGHT + 100.0), RADIUS);
+    set_test_ball_mass(&mut app, ball, MASS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.vel = Vec2::new(0.0, -600.0);
+    phys_obj.angular_vel = 3.0;
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let mut reader = ManualEventReader::<PortalEvent>::default();
+
+    let mut updates = 0;
+    loop {
+        let before = app.world.get::<PhysObj>(ball).unwrap().clone();
+        app.update();
+        updates += 1;
+        assert!(updates < 60, "never went through");
+        if reader
+            .iter(app.world.resource::<Events<PortalEvent>>())
+            .next()
+            .is_none()
+        {
+            continue;
+        }
+
+        let position = app.world.get::<Transform>(ball).unwrap().translation;
+        assert!(
+            position.x >= exit.x && position.x < exit.x + 50.0,
+            "{position}"
+        );
+        assert!((position.y - exit.y).abs() < 1.0, "{position}");
+        let after = app.world.get::<PhysObj>(ball).unwrap();
+        assert!(
+            after.vel.x > 0.0 && after.vel.y.abs() < gravity * TEST_DT,
+            "{}",
+            after.vel
+        );
+        // Up to a frame of gravity
+        let gained = after.vel.length() - before.vel.length();
+        assert!(gained.abs() <= gravity * TEST_DT + 1e-3, "{gained}");
+        assert!(after.angular_vel > 0.0);
+        break;
+    }
+}
+
+// A ball that comes out of a portal in the floor too slowly to get away falls straight back into
+// it. It's only just come out, so it falls past it instead of bouncing between the two.
+#[test]
+fn bodies_that_just_went_through_do_not_go_straight_back() {
+    let mut app = portal_app();
+    spawn_portals(
+        &mut app,
+        (Vec2::new(0.0, HEIGHT), FRAC_PI_2),
+        (Vec2::new(1000.0, HEIGHT), FRAC_PI_2),
+    );
+    // Falls in as soon as it starts, so it's hardly moving
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, HEIGHT + 0.1), RADIUS);
+    set_test_ball_mass(&mut app, ball, MASS);
+    set_test_ball_restitution(&mut app, ball, 0.5);
+    app.world.get_mut::<PhysObj>(ball).unwrap().angular_vel = 3.0;
+    let mut reader = ManualEventReader::<PortalEvent>::default();
+
+    let mut through = 0;
+    let mut immune_for = 0;
+    for _ in 0..30 {
+        app.update();
+        through += reader
+            .iter(app.world.resource::<Events<PortalEvent>>())
+            .count();
+        if app.world.get::<PortalImmunity>(ball).is_some() {
+            immune_for += 1;
+        }
+    }
+    assert_eq!(through, 1);
+    let seconds = immune_for as f32 * TEST_DT;
+    assert!(
+        (seconds - PORTAL_IMMUNITY).abs() <= TEST_DT + 1e-3,
+        "{seconds}"
+    );
+    let position = app.world.get::<Transform>(ball).unwrap().translation;
+    assert!((position.x - 1000.0).abs() < 1.0, "{position}");
+    assert!(position.y < HEIGHT - 50.0, "{position}");
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_game::{
+    level::CurrentLevel,
+    progress::{LevelProgress, LevelStats, Progress, ProgressPlugin},
+    state::AppState,
+    storage::{save_ron, MemoryBackend, Storage, StorageBackend},
+    testing::test_app,
+};
+
+#[test]
+fn only_faster_times_replace_the_best() {
+    let mut progress = Progress::default();
+    assert!(!progress.is_completed(0));
+    assert!(progress.record_completion(0, 30.0, 0));
+    assert!(progress.is_completed(0));
+
+    // Slower, or just as fast, keeps the old best
+    assert!(!progress.record_completion(0, 31.0, 0));
+    assert!(!progress.record_completion(0, 30.0, 0));
+    assert_eq!(progress.level(0).unwrap().best_time, Some(30.0));
+
+    assert!(progress.record_completion(0, 25.5, 0));
+    assert_eq!(progress.level(0).unwrap().best_time, Some(25.5));
+}
+
+// Completing a level further on leaves the ones before it untouched, and not completed
+#[test]
+fn levels_are_recorded_independently() {
+    let mut progress = Progress::default();
+    assert!(progress.record_completion(3, 40.0, 0b1));
+    assert_eq!(progress.levels.len(), 4);
+    for level in 0..3 {
+        assert!(progress.level(level) == Some(&LevelProgress::default()));
+        assert!(!progress.is_completed(level));
+    }
+
+    assert!(progress.record_completion(1, 50.0, 0));
+    assert_eq!(progress.levels.len(), 4);
+    assert_eq!(progress.level(3).unwrap().best_time, Some(40.0));
+    assert!(progress.level(7).is_none());
+}
+
+// Collectibles add up over runs, whether or not the run was a new best
+#[test]
+fn collectibles_accumulate_as_a_bitmask() {
+    let mut progress = Progress::default();
+    progress.record_completion(0, 30.0, 0b0101);
+    assert_eq!(progress.level(0).unwrap().collectibles, 0b0101);
+    progress.record_completion(0, 35.0, 0b0011);
+    assert_eq!(progress.level(0).unwrap().collectibles, 0b0111);
+    progress.record_completion(0, 20.0, 0);
+    assert_eq!(progress.level(0).unwrap().collectibles, 0b0111);
+    progress.record_completion(0, 40.0, 1 << 63);
+    assert_eq!(progress.level(0).unwrap().collectibles, 1 << 63 | 0b0111);
+}
+
+#[test]
+fn progress_round_trips_through_storage() {
+    let mut progress = Progress {
+        deaths: 12,
+        ..default()
+    };
+    progress.record_completion(0, 30.25, 0b11);
+    progress.record_completion(2, 61.5, 1 << 40);
+
+    let mut storage = MemoryBackend::default();
+    assert!(Progress::load(&storage) == Progress::default());
+    progress.save(&mut storage).unwrap();
+    assert!(Progress::load(&storage) == progress);
+
+    // Saving again replaces what was there
+    progress.record_completion(1, 10.0, 0);
+    progress.save(&mut storage).unwrap();
+    assert!(Progress::load(&storage) == progress);
+}
+
+// Progress that can't be read starts over instead of failing
+#[test]
+fn unreadable_progress_is_discarded() {
+    let mut storage = MemoryBackend::default();
+    storage.set("progress", "not ron at all (").unwrap();
+    assert!(Progress::load(&storage) == Progress::default());
+    // Valid RON, but not progress
+    save_ron(&mut storage, "progress", &("levels", 5)).unwrap();
+    assert!(Progress::load(&storage) == Progress::default());
+}
+
+// The plugin loads the stored progress, and completing a level saves it with the new time
+#[test]
+fn completing_a_level_saves_the_progress() {
+    let mut stored = Progress::default();
+    stored.record_completion(0, 100.0, 0b10);
+    let mut storage = MemoryBackend::default();
+    stored.save(&mut storage).unwrap();
+
+    let mut app = test_app();
+    app.insert_resource(Storage(Box::new(storage)))
+        .init_resource::<CurrentLevel>()
+        .add_plugin(ProgressPlugin);
+    assert!(*app.world.resource::<Progress>() == stored);
+    for _ in 0..10 {
+        app.update();
+    }
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::LevelComplete);
+    app.update();
+
+    let stats = app.world.resource::<LevelStats>();
+    assert!(stats.new_best);
+    assert_eq!(stats.previous_best, Some(100.0));
+    let time = stats.time;
+    let saved = Progress::load(app.world.resource::<Storage>().0.as_ref());
+    let level = saved.level(0).unwrap();
+    assert_eq!(level.best_time, Some(time));
+    assert_eq!(level.collectibles, 0b10);
+    assert!(saved == *app.world.resource::<Progress>());
+}
@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysObj, PhysicsConfig},
+    player::Player,
+    save::{SaveGame, SavePlugin},
+    shapes::FidgetSpinner,
+    storage::{load_ron, MemoryBackend, Storage},
+    testing::{press_key, spawn_test_ball, test_app},
+};
+
+const PLAYER_RADIUS: f32 = 20.0;
+
+// Where the player and the other ball are, and how fast they're turning
+type Frame = [(Vec3, Quat, f32); 2];
+
+// A spinning player and a bouncy ball, both in the air and well apart, saving to memory
+fn world_app() -> App {
+    let mut app = test_app();
+    app.insert_resource(Storage(Box::<MemoryBackend>::default()))
+        .add_plugin(SavePlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 300.0), PLAYER_RADIUS);
+    app.world
+        .entity_mut(player)
+        .insert((Player::default(), FidgetSpinner::new(PLAYER_RADIUS)));
+    let mut phys_obj = app.world.get_mut::<PhysObj>(player).unwrap();
+    phys_obj.vel = Vec2::new(150.0, 400.0);
+    phys_obj.angular_vel = 3.0;
+
+    let ball = spawn_test_ball(&mut app, Vec2::new(-400.0, floor_y + 100.0), 12.0);
+    let Collider::Ball {
+        coef_of_restitution,
+        ..
+    } = app.world.get_mut::<Collider>(ball).unwrap().into_inner();
+    *coef_of_restitution = 0.8;
+    app.world.get_mut::<PhysObj>(ball).unwrap().vel.x = -60.0;
+    app.update();
+    app
+}
+
+fn frame(app: &mut App) -> Frame {
+    let mut bodies: Vec<_> = app
+        .world
+        .query::<(&Transform, &PhysObj, Option<&Player>)>()
+        .iter(&app.world)
+        .map(|(transform, phys_obj, player)| {
+            (
+                player.is_none(),
+                (
+                    transform.translation,
+                    transform.rotation,
+                    phys_obj.angular_vel,
+                ),
+            )
+        })
+        .collect();
+    assert_eq!(bodies.len(), 2);
+    bodies.sort_by_key(|(other, _)| *other);
+    [bodies[0].1, bodies[1].1]
+}
+
+fn run(app: &mut App, frames: usize) -> Vec<Frame> {
+    (0..frames)
+        .map(|_| {
+            app.update();
+            frame(app)
+        })
+        .collect()
+}
+
+fn tap(app: &mut App, key: KeyCode) -> Frame {
+    press_key(app, key, true);
+    app.update();
+    press_key(app, key, false);
+    frame(app)
+}
+
+fn saved(app: &App) -> SaveGame {
+    load_ron::<SaveGame>(app.world.resource::<Storage>().0.as_ref(), "savegame")
+        .expect("a save was written")
+        .unwrap()
+}
+
+// Saving mid-air and loading later puts every body back where it was, going the way it was: the
+// frames after loading are the frames after saving, exactly
+#[test]
+fn quickload_continues_the_saved_trajectory() {
+    let mut app = world_app();
+    run(&mut app, 10);
+    // The save happens before or after the step of the frame F5 is pressed in
+    let mut saved_run = vec![frame(&mut app), tap(&mut app, KeyCode::F5)];
+    saved_run.extend(run(&mut app, 90));
+
+    let save = saved(&app);
+    assert_eq!(save.bodies.len(), 2);
+    let player = save
+        .bodies
+        .iter()
+        .find(|body| body.player.is_some())
+        .unwrap();
+    assert!(player.shape.is_some());
+    let saved_at = saved_run
+        .iter()
+        .position(|frame| frame[0].0 == player.translation)
+        .expect("saved in the frame F5 was pressed in");
+    assert!(saved_at <= 1, "{saved_at}");
+
+    // Likewise for the load, so the runs line up from the first frame they share
+    let mut loaded_run = vec![tap(&mut app, KeyCode::F9)];
+    loaded_run.extend(run(&mut app, 60));
+    let loaded_at = saved_run
+        .iter()
+        .position(|frame| *frame == loaded_run[0])
+        .expect("loading went back to the save");
+    assert!(
+        (saved_at..=saved_at + 1).contains(&loaded_at),
+        "{loaded_at}"
+    );
+    assert_eq!(loaded_run[..], saved_run[loaded_at..loaded_at + 61]);
+}
+
+// Loading replaces the bodies rather than adding to them, and keeps what isn't a body
+#[test]
+fn loading_replaces_the_bodies() {
+    let mut app = world_app();
+    let save = SaveGame::capture(&mut app.world);
+    let camera = app.world.spawn(Camera2dBundle::default()).id();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    spawn_test_ball(&mut app, Vec2::new(300.0, floor_y + 50.0), 5.0);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    save.restore(&mut app.world);
+    assert!(app.world.get_entity(camera).is_some());
+    let bodies = SaveGame::capture(&mut app.world).bodies;
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(
+        bodies.iter().filter(|body| body.player.is_some()).count(),
+        1
+    );
+    for body in &save.bodies {
+        assert!(bodies
+            .iter()
+            .any(|restored| restored.translation == body.translation
+                && restored.phys_obj.vel == body.phys_obj.vel));
+    }
+}
+
+// Loading with nothing saved leaves the world alone: it carries on just like one where F9 wasn't
+// pressed
+#[test]
+fn loading_without_a_save_does_nothing() {
+    let (mut app, mut untouched) = (world_app(), world_app());
+    let loaded = tap(&mut app, KeyCode::F9);
+    assert!(app.world.resource::<Storage>().0.get("savegame").is_none());
+    assert_eq!(loaded, run(&mut untouched, 1)[0]);
+    assert_eq!(run(&mut app, 30), run(&mut untouched, 30));
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "rapier-backend")]
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use bevy_game::{
+    level::{spawn_ball, Level},
+    physics::{Collider, PhysicsBackend, PhysicsConfig, PhysicsPlugin},
+    testing::test_app_with,
+};
+
+// The player's ball of the default level, dropped, then jumping once and spinning along the floor.
+// Returns where it was after every update, with the index of the first update of each phase.
+fn play(backend: PhysicsBackend) -> (Vec<Vec2>, [usize; 3]) {
+    let mut app = test_app_with(PhysicsPlugin::default().with_backend(backend));
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let mut queue = CommandQueue::default();
+    let ball = Level::default().balls[0].clone();
+    let player = spawn_ball(&mut Commands::new(&mut queue, &app.world), &config, &ball);
+    queue.apply(&mut app.world);
+
+    let mut trajectory = Vec::new();
+    let mut run = |app: &mut App, key: Option<KeyCode>, updates: usize| {
+        for _ in 0..updates {
+            app.update();
+            if let Some(key) = key {
+                // Just the one press, for jumps
+                app.world.resource_mut::<Input<KeyCode>>().release(key);
+            }
+            let at = app.world.get::<Transform>(player).unwrap().translation;
+            trajectory.push(at.truncate());
+        }
+    };
+
+    run(&mut app, None, 90);
+    let Collider::Ball {
+        touching_ground, ..
+    } = *app.world.get::<Collider>(player).unwrap();
+    assert!(touching_ground, "{backend:?} didn't land");
+
+    let jump = 90;
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Space);
+    run(&mut app, Some(KeyCode::Space), 1);
+    run(&mut app, None, 59);
+
+    let spin = 150;
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    run(&mut app, None, 60);
+    (trajectory, [0, jump, spin])
+}
+
+// The same scene under both backends stays close enough that the game feels the same: the ball
+// lands, jumps as high and rolls the same way
+#[test]
+fn backends_agree_on_the_scene() {
+    let (native, phases) = play(PhysicsBackend::Native);
+    let (rapier, _) = play(PhysicsBackend::Rapier);
+    assert_eq!(native.len(), rapier.len());
+
+    let ends = [phases[1], phases[2], native.len()];
+    for (name, (&start, &end)) in ["drop", "jump", "spin"]
+        .iter()
+        .zip(phases.iter().zip(&ends))
+    {
+        let divergence = native[start..end]
+            .iter()
+            .zip(&rapier[start..end])
+            .map(|(a, b)| a.distance(*b));
+        let (max, total) =
+            divergence.fold((0.0f32, 0.0), |(max, total), d| (max.max(d), total + d));
+        println!(
+            "{name}: max divergence {max:.1}, mean {:.1}",
+            total / (end - start) as f32
+        );
+    }
+
+    let floor_y = PhysicsConfig::default().floor_y;
+    let apex = |trajectory: &[Vec2]| {
+        trajectory[phases[1]..phases[2]]
+            .iter()
+            .map(|at| at.y - floor_y)
+            .fold(f32::MIN, f32::max)
+    };
+    let (native_apex, rapier_apex) = (apex(&native), apex(&rapier));
+    assert!(
+        (native_apex - rapier_apex).abs() < 0.1 * native_apex,
+        "apex {native_apex} vs {rapier_apex}"
+    );
+
+    let rolled = |trajectory: &[Vec2]| trajectory[trajectory.len() - 1].x - trajectory[phases[2]].x;
+    let (native_roll, rapier_roll) = (rolled(&native), rolled(&rapier));
+    assert!(native_roll.abs() > 10.0, "{native_roll}");
+    assert_eq!(native_roll.signum(), rapier_roll.signum());
+    assert!(
+        (native_roll - rapier_roll).abs() < 0.5 * native_roll.abs(),
+        "rolled {native_roll} vs {rapier_roll}"
+    );
+}
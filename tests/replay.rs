@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysicsConfig},
+    player::Player,
+    replay::{Ghost, Replay, ReplayPlayback, ReplayPlugin, ReplayRecorder},
+    settings::Settings,
+    storage::{MemoryBackend, Storage},
+    testing::{press_key, spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+// Recorded updates
+const RUN: usize = 150;
+
+fn replay_app() -> App {
+    let mut app = test_app();
+    app.insert_resource(Storage(Box::<MemoryBackend>::default()))
+        .add_plugin(ReplayPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    let mut collider = app.world.get_mut::<Collider>(player).unwrap();
+    let Collider::Ball {
+        coef_of_restitution,
+        ..
+    } = &mut *collider;
+    *coef_of_restitution = 0.5;
+    app.world.entity_mut(player).insert(Player::default());
+    app
+}
+
+// Rolls right, jumps, and rolls back left, with `also` held down as well
+fn play(app: &mut App, update: usize, also: &[KeyCode]) {
+    let map = Settings::default().input;
+    let key = match update {
+        0..=49 => map.spin_right,
+        50..=55 => map.jump,
+        _ => map.spin_left,
+    };
+    let held: Vec<KeyCode> = std::iter::once(key).chain(also.iter().copied()).collect();
+    let released: Vec<KeyCode> = app
+        .world
+        .resource::<Input<KeyCode>>()
+        .get_pressed()
+        .filter(|key| !held.contains(key))
+        .copied()
+        .collect();
+    for key in released {
+        press_key(app, key, false);
+    }
+    for key in held {
+        press_key(app, key, true);
+    }
+    app.update();
+}
+
+fn player_transform(app: &mut App) -> Transform {
+    *app.world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+}
+
+fn ghost(app: &mut App) -> Option<Transform> {
+    app.world
+        .query_filtered::<&Transform, With<Ghost>>()
+        .get_single(&app.world)
+        .ok()
+        .copied()
+}
+
+// Records RUN updates of play with F6, returning the app and where the player was after each
+fn record() -> (App, Vec<Transform>) {
+    let mut app = replay_app();
+    app.update();
+    let mut trajectory = Vec::new();
+    for update in 0..RUN {
+        let also: &[KeyCode] = if update == 0 { &[KeyCode::F6] } else { &[] };
+        play(&mut app, update, also);
+        trajectory.push(player_transform(&mut app));
+    }
+    play(&mut app, RUN, &[KeyCode::F6]);
+    (app, trajectory)
+}
+
+// Plays `replay` in a headless App, returning where the player was after each recorded frame
+fn play_back(replay: &Replay) -> Vec<Transform> {
+    let mut app = replay.playback_app();
+    // Only starts the level
+    app.update();
+    let mut trajectory = Vec::new();
+    while !app.world.resource::<ReplayPlayback>().finished() {
+        app.update();
+        trajectory.push(player_transform(&mut app));
+    }
+    trajectory
+}
+
+// Playing a recording back headlessly puts the player in the same place, exactly, on every frame
+#[test]
+fn playback_follows_the_recording_frame_by_frame() {
+    let (app, recorded) = record();
+    let replay = app.world.resource::<ReplayRecorder>().last.clone().unwrap();
+    assert_eq!(replay.frames.len(), RUN);
+    // The player went somewhere
+    assert!((recorded[RUN - 1].translation - recorded[0].translation).length() > 10.0);
+
+    let played = play_back(&replay);
+    assert_eq!(played.len(), recorded.len());
+    for (frame, (played, recorded)) in played.iter().zip(&recorded).enumerate() {
+        assert_eq!(played, recorded, "frame {frame}");
+    }
+}
+
+// What's saved when recording stops plays back the same as what was recorded
+#[test]
+fn saved_replay_plays_back_the_same() {
+    let (mut app, recorded) = record();
+    let storage = app.world.remove_resource::<Storage>().unwrap();
+    let replay = Replay::load(storage.0.as_ref()).expect("no replay was saved");
+    assert_eq!(replay.frames.len(), RUN);
+    assert_eq!(play_back(&replay), recorded);
+}
+
+// F7 plays the recording as a ghost, which follows the player's path frame by frame and goes away
+// at the end
+#[test]
+fn ghost_follows_the_recording() {
+    let (mut app, recorded) = record();
+    // Spawned where the recording started, the first update of its playback only starting the
+    // level
+    play(&mut app, 0, &[KeyCode::F7]);
+    assert!(ghost(&mut app).is_some());
+    for (frame, expected) in recorded.iter().enumerate().take(RUN - 1) {
+        app.update();
+        assert_eq!(ghost(&mut app), Some(*expected), "frame {frame}");
+    }
+    app.update();
+    assert_eq!(ghost(&mut app), None);
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysObj, PhysicsConfig},
+    rewind::{BodySnapshot, RewindBuffer, RewindConfig, RewindPlugin, REWIND_KEY, REWIND_SPEED},
+    testing::{set_test_ball_restitution, spawn_test_player, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = 10.0;
+
+fn phys_obj() -> PhysObj {
+    PhysObj {
+        mass: MASS,
+        vel: Vec2::new(150.0, 0.0),
+        acc: Vec2::ZERO,
+        acc_prev: Vec2::ZERO,
+        moment_of_inertia: MASS * 0.5 * RADIUS * RADIUS,
+        angular_vel: -3.0,
+        angular_acc: 0.0,
+        angular_acc_prev: 0.0,
+        com_offset: Vec2::ZERO,
+    }
+}
+
+#[test]
+fn buffer_wraps_without_growing() {
+    let mut buffer = RewindBuffer::new(&RewindConfig {
+        frames: 4,
+        ..default()
+    });
+    let memory = buffer.memory_bytes();
+
+    for i in 0..10 {
+        buffer.push().bodies.push(BodySnapshot {
+            entity: Entity::from_raw(0),
+            transform: Transform::from_xyz(i as f32, 0.0, 0.0),
+            phys_obj: phys_obj(),
+            collider: None,
+            rest_time: None,
+            asleep: false,
+        });
+        assert_eq!(buffer.len(), (i + 1).min(4));
+        assert_eq!(buffer.memory_bytes(), memory);
+    }
+
+    // Only the newest four are left, newest first
+    let mut popped = Vec::new();
+    while let Some(frame) = buffer.pop() {
+        popped.push(frame.bodies[0].transform.translation.x);
+    }
+    assert_eq!(popped, [9.0, 8.0, 7.0, 6.0]);
+    assert!(buffer.is_empty());
+
+    // And it carries on from wherever it stopped
+    buffer.push();
+    assert_eq!(buffer.len(), 1);
+    assert!(buffer.newest().unwrap().bodies.is_empty());
+    assert_eq!(buffer.memory_bytes(), memory);
+}
+
+// Everything about the player the physics uses, bit for bit
+fn state(app: &App, player: Entity) -> Vec<u32> {
+    let transform = app.world.get::<Transform>(player).unwrap();
+    let phys_obj = app.world.get::<PhysObj>(player).unwrap();
+    let Some(&Collider::Ball {
+        touching_ground,
+        friction_acc,
+        friction_acc_prev,
+        ..
+    }) = app.world.get::<Collider>(player)
+    else {
+        panic!("no collider");
+    };
+    [
+        transform.translation.to_array().as_slice(),
+        transform.rotation.to_array().as_slice(),
+        phys_obj.vel.to_array().as_slice(),
+        phys_obj.acc.to_array().as_slice(),
+        phys_obj.acc_prev.to_array().as_slice(),
+        &[
+            phys_obj.angular_vel,
+            phys_obj.angular_acc,
+            phys_obj.angular_acc_prev,
+            friction_acc,
+            friction_acc_prev,
+            touching_ground as u8 as f32,
+        ],
+    ]
+    .concat()
+    .into_iter()
+    .map(f32::to_bits)
+    .collect()
+}
+
+// A bouncing, rolling player is rewound into the middle of its bounces. After letting go, it goes
+// through exactly the states it went through the first time.
+#[test]
+fn rewinding_then_resuming_replays_the_same_run() {
+    let mut app = test_app();
+    app.add_plugin(RewindPlugin::default());
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let player = spawn_test_player(
+        &mut app,
+        Vec2::new(0.0, config.floor_y + RADIUS + 150.0),
+        RADIUS,
+    );
+    set_test_ball_restitution(&mut app, player, 0.7);
+    app.world.entity_mut(player).insert(phys_obj());
+
+    // history[k] is the state after k + 1 updates
+    let mut history = Vec::new();
+    for _ in 0..90 {
+        app.update();
+        history.push(state(&app, player));
+    }
+
+    // Each frame held goes REWIND_SPEED frames further back, from the last one recorded (the
+    // state the frame before)
+    let held = 10;
+    app.world.resource_mut::<Input<KeyCode>>().press(REWIND_KEY);
+    for frame in 1..=held {
+        app.update();
+        let expected = history.len() - 2 - REWIND_SPEED * frame;
+        assert!(state(&app, player) == history[expected], "frame {frame}");
+    }
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(REWIND_KEY);
+
+    let resumed = history.len() - 1 - REWIND_SPEED * held;
+    for (i, expected) in history.iter().enumerate().skip(resumed) {
+        app.update();
+        assert!(state(&app, player) == *expected, "history[{i}]");
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_game::{
+    launch::{LaunchOptions, LaunchPlugin},
+    rng::{GameRng, Rng},
+    visuals::ember_velocity,
+};
+
+fn draws(rng: &mut Rng, count: usize) -> Vec<u64> {
+    (0..count).map(|_| rng.next_u64()).collect()
+}
+
+fn ember_burst(rng: &mut GameRng) -> Vec<Vec2> {
+    (0..12)
+        .map(|_| ember_velocity(rng.stream("embers")))
+        .collect()
+}
+
+#[test]
+fn same_seed_same_numbers() {
+    let (mut a, mut b) = (GameRng::new(42), GameRng::new(42));
+    assert_eq!(
+        draws(a.stream("impact_pitch"), 100),
+        draws(b.stream("impact_pitch"), 100)
+    );
+    assert_eq!(ember_burst(&mut a), ember_burst(&mut b));
+    // And the next burst carries on from the last, rather than repeating it
+    let first = ember_burst(&mut GameRng::new(42));
+    assert_ne!(ember_burst(&mut a), first);
+
+    assert_ne!(ember_burst(&mut GameRng::new(43)), first);
+}
+
+#[test]
+fn numbers_stay_in_range() {
+    let mut rng = Rng::from_seed(0);
+    for _ in 0..10_000 {
+        let unit = rng.next_f32();
+        assert!((0.0..1.0).contains(&unit), "{unit}");
+        let signed = rng.signed();
+        assert!((-1.0..1.0).contains(&signed), "{signed}");
+        let ranged = rng.range(5.0..7.0);
+        assert!((5.0..7.0).contains(&ranged), "{ranged}");
+    }
+}
+
+// What one stream gives doesn't depend on how much the others have been drawn from
+#[test]
+fn labels_are_independent_streams() {
+    let mut a = GameRng::new(7);
+    let mut b = GameRng::new(7);
+    draws(a.stream("embers"), 50);
+    let pitch = draws(a.stream("impact_pitch"), 20);
+    assert_eq!(draws(b.stream("impact_pitch"), 20), pitch);
+    assert_ne!(draws(&mut a.fork("embers"), 20), pitch);
+
+    // A fork starts its label's stream over, whatever's been drawn from it
+    assert_eq!(
+        draws(&mut a.fork("impact_pitch"), 20),
+        draws(&mut GameRng::new(7).fork("impact_pitch"), 20)
+    );
+}
+
+#[test]
+fn launch_options_seed_the_rng() {
+    let mut app = App::new();
+    app.insert_resource(LaunchOptions {
+        seed: Some(1234),
+        ..default()
+    })
+    .add_plugin(LaunchPlugin);
+    assert_eq!(app.world.resource::<GameRng>().seed(), 1234);
+}
@@ -0,0 +1,192 @@
+use bevy::{
+    ecs::{event::ManualEventReader, system::CommandQueue},
+    prelude::*,
+};
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::{JumpEvent, Player},
+    rope::{
+        nearest_segment, release_velocity, spawn_rope, Grabbing, Rope, RopeAnchor, RopePlugin,
+        RopeSegment,
+    },
+    testing::{spawn_test_player, test_app, TEST_BALL_MASS, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+const ANCHOR: Vec2 = Vec2::new(0.0, 500.0);
+const ROPE: RopeAnchor = RopeAnchor {
+    segments: 8,
+    segment_length: 25.0,
+};
+
+fn test_player() -> Player {
+    Player {
+        torque: 0.0,
+        ..Default::default()
+    }
+}
+
+fn rope_app() -> (App, Vec<Entity>) {
+    let mut app = test_app();
+    app.add_plugin(RopePlugin);
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let mut queue = CommandQueue::default();
+    let rope = spawn_rope(
+        &mut Commands::new(&mut queue, &app.world),
+        &config,
+        ANCHOR,
+        ROPE,
+    );
+    queue.apply(&mut app.world);
+    let segments = app.world.get::<Rope>(rope).unwrap().segments.clone();
+    (app, segments)
+}
+
+fn press(app: &mut App, key: KeyCode, held: bool) {
+    let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+    if held {
+        keys.press(key);
+    } else {
+        keys.release(key);
+    }
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+fn vel(app: &App, entity: Entity) -> Vec2 {
+    app.world.get::<PhysObj>(entity).unwrap().vel
+}
+
+// Where the `index`th segment hangs at rest
+fn hanging(index: usize) -> Vec2 {
+    ANCHOR - Vec2::Y * ROPE.segment_length * (index + 1) as f32
+}
+
+// Every segment about where it's allowed to be, and not flying off
+fn assert_stable(app: &App, segments: &[Entity], max_stretch: f32, max_speed: f32) {
+    let mut above = ANCHOR;
+    for &segment in segments {
+        let at = position(app, segment);
+        let stretch = at.distance(above) - ROPE.segment_length;
+        assert!(stretch < max_stretch, "{segment:?} stretched by {stretch}");
+        let speed = vel(app, segment).length();
+        assert!(speed < max_speed, "{segment:?} going {speed}");
+        above = at;
+    }
+}
+
+#[test]
+fn grabbing_takes_the_nearest_segment() {
+    let near = Entity::from_raw(1);
+    let far = Entity::from_raw(2);
+    let segments = [(far, Vec2::new(0.0, 20.0)), (near, Vec2::new(5.0, 0.0))];
+    assert_eq!(nearest_segment(Vec2::ZERO, 30.0, segments), Some(near));
+    assert_eq!(nearest_segment(Vec2::ZERO, 1.0, segments), None);
+
+    let (mut app, segments) = rope_app();
+    // Overlapping a few, but closest to the fourth
+    let player = spawn_test_player(&mut app, hanging(3) + Vec2::new(5.0, -5.0), RADIUS);
+    app.world.entity_mut(player).insert(test_player());
+    press(&mut app, KeyCode::W, true);
+    app.update();
+    assert!(app.world.get::<Grabbing>(player).is_none());
+    // The physics runs from the second update
+    app.update();
+    assert_eq!(
+        app.world.get::<Grabbing>(player).unwrap().segment,
+        segments[3]
+    );
+
+    // Still holding up, it climbs towards the anchor
+    for _ in 0..20 {
+        app.update();
+    }
+    let segment = app.world.get::<Grabbing>(player).unwrap().segment;
+    assert!(app.world.get::<RopeSegment>(segment).unwrap().index < 3);
+}
+
+// Jumping off a swinging rope goes the way the rope was going, plus the jump
+#[test]
+fn letting_go_keeps_the_rope_velocity() {
+    let segment_vel = Vec2::new(150.0, -40.0);
+    assert_eq!(
+        release_velocity(segment_vel, &test_player(), MASS),
+        segment_vel + Vec2::Y * test_player().jump_impulse / MASS
+    );
+
+    let (mut app, _) = rope_app();
+    let player = spawn_test_player(&mut app, hanging(7) + Vec2::new(0.0, -RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(test_player());
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(400.0, 0.0);
+    press(&mut app, KeyCode::W, true);
+    for _ in 0..20 {
+        app.update();
+    }
+    press(&mut app, KeyCode::W, false);
+    let grabbing = *app.world.get::<Grabbing>(player).unwrap();
+    let swing = vel(&app, grabbing.segment);
+    assert!(swing.x > 10.0, "{swing}");
+
+    press(&mut app, KeyCode::Space, true);
+    app.update();
+    assert!(app.world.get::<Grabbing>(player).is_none());
+    let mut jumps = ManualEventReader::<JumpEvent>::default();
+    assert_eq!(
+        jumps
+            .iter(app.world.resource::<Events<JumpEvent>>())
+            .count(),
+        1
+    );
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let expected = release_velocity(swing, &test_player(), MASS) - Vec2::Y * gravity * TEST_DT;
+    let released = vel(&app, player);
+    assert!(
+        released.distance(expected) < 1.0,
+        "{released} != {expected}"
+    );
+
+    // Holding jump after letting go doesn't grab it again straight away
+    for _ in 0..5 {
+        app.update();
+        assert!(app.world.get::<Grabbing>(player).is_none());
+    }
+}
+
+// A rope hangs still by itself, and the player grabbing it on the move swings it without tearing
+// it apart
+#[test]
+fn ropes_stay_together_when_grabbed() {
+    let (mut app, segments) = rope_app();
+    for _ in 0..60 {
+        app.update();
+    }
+    assert_stable(&app, &segments, 0.5, 1.0);
+    for (index, &segment) in segments.iter().enumerate() {
+        assert!(position(&app, segment).distance(hanging(index)) < 1.0);
+    }
+
+    let player = spawn_test_player(&mut app, hanging(7) + Vec2::new(-15.0, -RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(test_player());
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(600.0, 300.0);
+    press(&mut app, KeyCode::W, true);
+    let mut last: Vec<Vec2> = segments.iter().map(|&s| position(&app, s)).collect();
+    for _ in 0..120 {
+        app.update();
+        for (segment, last) in segments.iter().zip(&mut last) {
+            let at = position(&app, *segment);
+            // No sudden jumps, even on the step it's grabbed
+            let moved = at.distance(*last);
+            assert!(moved < 40.0, "{segment:?} moved {moved}");
+            *last = at;
+        }
+        assert_stable(&app, &segments, 0.2 * ROPE.segment_length, 2500.0);
+    }
+    assert!(app.world.get::<Grabbing>(player).is_some());
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_game::{
+    level::{LevelPlugin, RestartLevelEvent},
+    physics::{PhysObj, PhysicsConfig},
+    player::Player,
+    runner::{
+        Obstacle, ObstacleTemplate, Runner, RunnerDifficulty, RunnerPlugin, RUNNER_DESPAWN_X,
+        RUNNER_SPAWN_X,
+    },
+    storage::{MemoryBackend, Storage},
+    testing::{spawn_test_ball, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+
+// The runner's level, without the player so that the run never ends
+fn runner_app() -> App {
+    let mut app = test_app();
+    app.insert_resource(Storage(Box::<MemoryBackend>::default()))
+        .add_plugin(LevelPlugin)
+        .add_plugin(RunnerPlugin);
+    app.update();
+    app.update();
+    let players: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(&app.world)
+        .collect();
+    assert_eq!(players.len(), 1);
+    for player in players {
+        app.world.despawn(player);
+    }
+    app
+}
+
+fn obstacles(app: &mut App) -> Vec<(Obstacle, Vec3)> {
+    app.world
+        .query::<(&Obstacle, &Transform)>()
+        .iter(&app.world)
+        .map(|(obstacle, transform)| (*obstacle, transform.translation))
+        .collect()
+}
+
+#[test]
+fn obstacles_close_up_as_the_world_speeds_up() {
+    let difficulty = RunnerDifficulty::default();
+    assert_eq!(difficulty.speed(0.0), difficulty.start_speed);
+    assert!(difficulty.speed(10.0) > difficulty.speed(5.0));
+    assert_eq!(difficulty.speed(1_000.0), difficulty.max_speed);
+
+    assert_eq!(difficulty.spacing(0.0), difficulty.start_spacing);
+    assert!(difficulty.spacing(10_000.0) < difficulty.spacing(5_000.0));
+    assert_eq!(difficulty.spacing(1_000_000.0), difficulty.min_spacing);
+}
+
+// Each obstacle comes in the difficulty's spacing after the last, and is where it would be had it
+// come in right on time. They're gone once they're off the left of the screen, so there are only
+// ever a few about.
+#[test]
+fn obstacles_come_in_on_time_and_go_once_they_are_past() {
+    let mut app = runner_app();
+    let difficulty = app.world.resource::<RunnerDifficulty>().clone();
+    let max_about = ((RUNNER_SPAWN_X - RUNNER_DESPAWN_X) / difficulty.min_spacing) as usize + 2;
+
+    let mut dues = Vec::new();
+    // When each came in, by due distance
+    let mut came_in = Vec::new();
+    for _ in 0..(60.0 / TEST_DT) as usize {
+        app.update();
+        let runner = app.world.resource::<Runner>().clone();
+        let obstacles = obstacles(&mut app);
+        assert!(obstacles.len() <= max_about, "{}", obstacles.len());
+        for (obstacle, translation) in obstacles {
+            if !dues.contains(&obstacle.due) {
+                dues.push(obstacle.due);
+                came_in.push((obstacle.due, runner.time));
+            }
+            let x = RUNNER_SPAWN_X - (runner.distance - obstacle.due);
+            // Boulders roll along with the floor, so only a third of it speeding up gets to them,
+            // and they fall behind by up to the rest of that since they came in
+            let tolerance = match obstacle.template {
+                ObstacleTemplate::Spikes => 0.1,
+                ObstacleTemplate::Boulder => {
+                    let (_, since) = came_in
+                        .iter()
+                        .find(|(due, _)| *due == obstacle.due)
+                        .unwrap();
+                    let speed_up = difficulty.speed(runner.time) - difficulty.speed(*since);
+                    2.0 / 3.0 * speed_up * (runner.time - since) + 1.0
+                }
+            };
+            assert!((translation.x - x).abs() < tolerance, "{x} {translation}");
+            assert!(translation.x >= RUNNER_DESPAWN_X - difficulty.max_speed * TEST_DT);
+        }
+    }
+
+    let runner = app.world.resource::<Runner>().clone();
+    assert_eq!(dues.len(), runner.obstacles);
+    assert!(dues.len() > 20, "{}", dues.len());
+    let mut last = 0.0;
+    for due in dues {
+        assert!((due - (last + difficulty.spacing(last))).abs() < 1e-3);
+        last = due;
+    }
+    // The spacing has started closing up
+    assert!(difficulty.spacing(last) < difficulty.start_spacing);
+
+    app.world.send_event(RestartLevelEvent);
+    app.update();
+    assert!(obstacles(&mut app).is_empty());
+    assert!(app.world.resource::<Runner>().distance < difficulty.max_speed * TEST_DT);
+}
+
+// Friction works on the velocity relative to the moving floor: a ball rolling against it at its
+// speed stays where it is, and one that isn't turning is dragged along
+#[test]
+fn a_moving_floor_drags_balls_along_with_it() {
+    let floor_velocity = -300.0;
+    let mut app = test_app();
+    app.world.resource_mut::<PhysicsConfig>().floor_velocity = floor_velocity;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let rolling = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    let still = spawn_test_ball(&mut app, Vec2::new(1000.0, floor_y + RADIUS), RADIUS);
+    // Spinning so that the bottom of the ball moves with the floor
+    app.world.get_mut::<PhysObj>(rolling).unwrap().angular_vel = floor_velocity / RADIUS;
+    for _ in 0..60 {
+        app.update();
+    }
+
+    let x = |entity| app.world.get::<Transform>(entity).unwrap().translation.x;
+    assert!(x(rolling).abs() < 1.0, "{}", x(rolling));
+    assert!(x(still) < 950.0, "{}", x(still));
+    let vel = app.world.get::<PhysObj>(still).unwrap().vel.x;
+    assert!(vel < 0.0 && vel > floor_velocity, "{vel}");
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{disk_mass, Collider, PhysObj, PhysicsConfig},
+    player::Player,
+    rng::GameRng,
+    sandbox::{SandboxBall, SandboxConfig, SandboxEvent, SandboxPlugin},
+    testing::test_app,
+};
+
+fn sandbox_app(config: SandboxConfig) -> App {
+    let mut app = test_app();
+    app.insert_resource(GameRng::new(7))
+        .insert_resource(config)
+        .add_plugin(SandboxPlugin);
+    app.update();
+    app
+}
+
+fn sandbox_balls(app: &mut App) -> Vec<(Entity, Vec2, PhysObj, Collider)> {
+    let mut query = app
+        .world
+        .query_filtered::<(Entity, &Transform, &PhysObj, &Collider), With<SandboxBall>>();
+    query
+        .iter(&app.world)
+        .map(|(entity, transform, phys_obj, collider)| {
+            (
+                entity,
+                transform.translation.truncate(),
+                phys_obj.clone(),
+                *collider,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn spawned_balls_are_random_disks_that_fall() {
+    let config = SandboxConfig::default();
+    let mut app = sandbox_app(config.clone());
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    for i in 0..10 {
+        let position = Vec2::new(i as f32 * 100.0, floor_y + 300.0);
+        app.world.send_event(SandboxEvent::Spawn(position));
+    }
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let balls = sandbox_balls(&mut app);
+    assert_eq!(balls.len(), 10);
+    let mut restitutions = Vec::new();
+    for (_, position, phys_obj, collider) in balls {
+        let Collider::Ball {
+            radius,
+            coef_of_restitution,
+            ..
+        } = collider;
+        assert!((config.min_radius..config.max_radius).contains(&radius));
+        assert!((phys_obj.mass - disk_mass(config.density, radius)).abs() < 1e-4);
+        let moment_of_inertia = 0.5 * phys_obj.mass * radius * radius;
+        assert!((phys_obj.moment_of_inertia - moment_of_inertia).abs() < 1e-2);
+        assert!(phys_obj.vel.y < 0.0);
+        assert!(position.y < floor_y + 300.0);
+        restitutions.push(coef_of_restitution);
+    }
+    restitutions.dedup();
+    assert!(restitutions.len() > 1);
+}
+
+#[test]
+fn spawning_stops_at_the_limit() {
+    let mut app = sandbox_app(SandboxConfig {
+        max_balls: 3,
+        ..default()
+    });
+    for i in 0..5 {
+        app.world
+            .send_event(SandboxEvent::Spawn(Vec2::new(i as f32 * 100.0, 300.0)));
+        app.update();
+    }
+    assert_eq!(sandbox_balls(&mut app).len(), 3);
+
+    // Removing one makes room again
+    app.world
+        .send_event(SandboxEvent::Despawn(Vec2::new(0.0, 300.0)));
+    app.world
+        .send_event(SandboxEvent::Spawn(Vec2::new(0.0, 300.0)));
+    app.update();
+    app.update();
+    assert_eq!(sandbox_balls(&mut app).len(), 3);
+}
+
+#[test]
+fn despawns_the_nearest_ball_but_never_the_player() {
+    let mut app = sandbox_app(SandboxConfig::default());
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let near = Vec2::new(100.0, floor_y + 300.0);
+    // Far enough apart that they can't be touching, however big they came out
+    let far = Vec2::new(190.0, floor_y + 300.0);
+    let player = Vec2::new(-300.0, floor_y + 300.0);
+    app.world.send_event(SandboxEvent::Spawn(near));
+    app.world.send_event(SandboxEvent::Spawn(far));
+    app.world.send_event(SandboxEvent::Spawn(player));
+    app.update();
+    let player = sandbox_balls(&mut app)
+        .into_iter()
+        .find(|(_, position, ..)| position.distance(player) < 1.0)
+        .unwrap()
+        .0;
+    app.world
+        .entity_mut(player)
+        .remove::<SandboxBall>()
+        .insert(Player {
+            torque: 0.0,
+            ..Default::default()
+        });
+
+    let send = |app: &mut App, position: Vec2| {
+        app.world.send_event(SandboxEvent::Despawn(position));
+        app.update();
+    };
+    // Both in reach
+    send(&mut app, near + Vec2::new(42.0, 0.0));
+    let left: Vec<Vec2> = sandbox_balls(&mut app)
+        .into_iter()
+        .map(|(_, position, ..)| position)
+        .collect();
+    assert_eq!(left.len(), 1);
+    assert!((left[0].x - far.x).abs() < 1.0);
+
+    // Too far from anything, and then on the player
+    let player_at = app.world.get::<Transform>(player).unwrap().translation;
+    send(&mut app, far + Vec2::new(200.0, 0.0));
+    send(&mut app, player_at.truncate());
+    assert_eq!(sandbox_balls(&mut app).len(), 1);
+    assert!(app.world.get_entity(player).is_some());
+}
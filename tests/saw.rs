@@ -0,0 +1,181 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    hazard::HazardPlugin,
+    level::RestartLevelEvent,
+    physics::PhysObj,
+    player::Player,
+    progress::Progress,
+    saw::{SawBlade, SawBladePlugin},
+    testing::{
+        set_test_ball_mass, set_test_ball_restitution, spawn_test_ball, spawn_test_player,
+        test_app, TEST_DT,
+    },
+};
+
+const BLADE_RADIUS: f32 = 40.0;
+const RADIUS: f32 = 10.0;
+const MASS: f32 = 1.0;
+
+fn saw_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(SawBladePlugin)
+        .add_plugin(HazardPlugin)
+        .init_resource::<Progress>();
+    app
+}
+
+fn spawn_blade(app: &mut App, blade: SawBlade) -> Entity {
+    app.world.spawn((TransformBundle::default(), blade)).id()
+}
+
+fn assert_near(a: Vec2, b: Vec2) {
+    assert!(a.distance(b) < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn blades_slide_back_and_forth_along_their_path() {
+    let (from, to) = (Vec2::new(0.0, 0.0), Vec2::new(100.0, 50.0));
+    let blade = SawBlade {
+        radius: BLADE_RADIUS,
+        angular_speed: 5.0,
+        path: Some((from, to, 2.0)),
+    };
+    let out = (to - from) / 2.0;
+    for (elapsed, position, vel) in [
+        (0.0, from, out),
+        (1.0, from.lerp(to, 0.5), out),
+        (2.0, to, -out),
+        (3.0, from.lerp(to, 0.5), -out),
+        (4.0, from, out),
+        (5.5, from.lerp(to, 0.75), out),
+    ] {
+        let (at, moving) = blade.path_state(elapsed).unwrap();
+        assert_near(at, position);
+        assert_near(moving, vel);
+    }
+    assert!(SawBlade {
+        path: None,
+        ..blade
+    }
+    .path_state(1.0)
+    .is_none());
+
+    // Kept on it, wherever it's put
+    let mut app = saw_app();
+    let entity = spawn_blade(&mut app, blade);
+    // The first update only starts the simulation
+    app.update();
+    for steps in 1..=300 {
+        app.update();
+        let position = app.world.get::<Transform>(entity).unwrap().translation;
+        let (expected, _) = blade.path_state(steps as f32 * TEST_DT).unwrap();
+        assert!(position.truncate().distance(expected) < 0.01, "{steps}");
+    }
+}
+
+#[test]
+fn the_rim_moves_with_the_spin() {
+    let blade = SawBlade {
+        radius: BLADE_RADIUS,
+        angular_speed: 2.0,
+        path: None,
+    };
+    // Counterclockwise, the top moves left and the right side up
+    assert_near(
+        blade.rim_velocity(Vec2::ZERO, Vec2::new(0.0, 10.0)),
+        Vec2::new(-20.0, 0.0),
+    );
+    assert_near(
+        blade.rim_velocity(Vec2::new(5.0, 0.0), Vec2::new(10.0, 0.0)),
+        Vec2::new(5.0, 20.0),
+    );
+}
+
+// Dropped straight onto the top of a blade, a ball is flung the way the rim's going there, never
+// faster than the rim. A still blade only bounces it.
+#[test]
+fn spinning_blades_fling_what_they_hit() {
+    let struck = |angular_speed| {
+        let mut app = saw_app();
+        spawn_blade(
+            &mut app,
+            SawBlade {
+                radius: BLADE_RADIUS,
+                angular_speed,
+                path: None,
+            },
+        );
+        let ball = spawn_test_ball(
+            &mut app,
+            Vec2::new(0.0, BLADE_RADIUS + RADIUS + 10.0),
+            RADIUS,
+        );
+        set_test_ball_mass(&mut app, ball, MASS);
+        set_test_ball_restitution(&mut app, ball, 0.5);
+        app.world.get_mut::<PhysObj>(ball).unwrap().vel = Vec2::new(0.0, -400.0);
+        // Till just after it bounces
+        for _ in 0..6 {
+            app.update();
+        }
+        app.world.get::<PhysObj>(ball).unwrap().clone()
+    };
+
+    let still = struck(0.0);
+    assert!(still.vel.x.abs() < 1e-3, "{}", still.vel);
+    assert!(still.vel.y > 0.0, "{}", still.vel);
+
+    let spin = 10.0;
+    let rim_speed = spin * BLADE_RADIUS;
+    let left = struck(spin);
+    assert!(left.vel.x < -50.0, "{}", left.vel);
+    assert!(left.vel.x >= -rim_speed, "{}", left.vel);
+    // Bounced just as high, as the rim moves along the surface
+    assert!((left.vel.y - still.vel.y).abs() < 1e-3, "{}", left.vel);
+    // Its bottom was dragged left, turning it clockwise
+    assert!(left.angular_vel < 0.0);
+
+    let right = struck(-spin);
+    assert!((right.vel.x + left.vel.x).abs() < 1e-3, "{}", right.vel);
+}
+
+// The player touching a blade dies: the death is counted and the level starts over
+#[test]
+fn blades_kill_the_player() {
+    let mut app = saw_app();
+    spawn_blade(
+        &mut app,
+        SawBlade {
+            radius: BLADE_RADIUS,
+            angular_speed: 10.0,
+            path: None,
+        },
+    );
+    let player = spawn_test_player(
+        &mut app,
+        Vec2::new(BLADE_RADIUS + RADIUS + 20.0, 0.0),
+        RADIUS,
+    );
+    set_test_ball_mass(&mut app, player, MASS);
+    set_test_ball_restitution(&mut app, player, 0.5);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(-600.0, 0.0);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: 1000.0,
+        torque: 20_000.0,
+        ..Default::default()
+    });
+    let mut restarts = ManualEventReader::<RestartLevelEvent>::default();
+
+    for _ in 0..10 {
+        app.update();
+        if restarts
+            .iter(app.world.resource::<Events<RestartLevelEvent>>())
+            .next()
+            .is_some()
+        {
+            assert_eq!(app.world.resource::<Progress>().deaths, 1);
+            return;
+        }
+        assert_eq!(app.world.resource::<Progress>().deaths, 0);
+    }
+    panic!("never died");
+}
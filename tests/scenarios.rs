@@ -0,0 +1,112 @@
+use bevy_game::scenario::{run_scenario, Scenario, ScenarioError};
+
+const SCENARIOS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/scenarios");
+
+// Every scenario in `scenarios/`, with its file name
+fn shipped() -> Vec<(String, Scenario)> {
+    let mut scenarios: Vec<(String, Scenario)> = std::fs::read_dir(SCENARIOS)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let scenario = Scenario::parse(&std::fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|error| panic!("{name}: {error}"));
+            (name, scenario)
+        })
+        .collect();
+    scenarios.sort_by(|a, b| a.0.cmp(&b.0));
+    scenarios
+}
+
+#[test]
+fn shipped_scenarios_pass() {
+    let scenarios = shipped();
+    assert!(scenarios.len() >= 8);
+    let failed: Vec<String> = scenarios
+        .iter()
+        .filter_map(|(name, scenario)| {
+            let report = run_scenario(scenario);
+            (!report.passed()).then(|| format!("{name}:\n{report}"))
+        })
+        .collect();
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
+
+const BOUNCE: &str = r#"(
+    duration: 1.5,
+    bodies: [(
+        name: "ball",
+        position: (0.0, -140.0),
+        radius: 20.0,
+        mass: 1.0,
+        restitution: 1.0,
+        friction: 0.5,
+    )],
+    assertions: [
+        MaxHeight(body: "ball", after: 0.5, height: -140.0, within: 2.0),
+        MaxHeight(body: "ball", after: 0.5, height: 0.0, within: 2.0),
+    ],
+)"#;
+
+// Each assertion is reported on its own, so one that fails doesn't hide the others
+#[test]
+fn failures_are_reported_per_assertion() {
+    let report = run_scenario(&Scenario::parse(BOUNCE).unwrap());
+    let passed: Vec<bool> = report.results.iter().map(|result| result.passed).collect();
+    assert_eq!(passed, [true, false], "{report}");
+    assert!(!report.passed());
+    assert!(report.to_string().contains("FAIL"));
+}
+
+// The ball in BOUNCE never settles, which all but the friction assertion notice
+#[test]
+fn resting_assertions_catch_a_bouncing_ball() {
+    let assertions = r#"assertions: [
+        StaysPut(body: "ball", after: 0.0, within: 1.0),
+        OnGround(body: "ball", after: 0.0),
+        NoBounces(body: "ball", after: 0.0),
+        FrictionImpulse(body: "ball", after: 0.0, max: 0.001),
+    ],
+)"#;
+    let ron = format!(
+        "{}{assertions}",
+        &BOUNCE[..BOUNCE.find("assertions").unwrap()]
+    );
+    let report = run_scenario(&Scenario::parse(&ron).unwrap());
+    let passed: Vec<bool> = report.results.iter().map(|result| result.passed).collect();
+    assert_eq!(passed, [false, false, false, true], "{report}");
+}
+
+// The shipped scenario with a slope passes, and only because of the slope: without it the ball
+// just drops onto the floor and stays put
+#[test]
+fn slope_roll_rolls_down_its_slope() {
+    let (_, mut scenario) = shipped()
+        .into_iter()
+        .find(|(name, _)| name == "slope_roll.ron")
+        .unwrap();
+    assert!(!scenario.heightfields.is_empty());
+    let report = run_scenario(&scenario);
+    assert!(report.passed(), "{report}");
+    scenario.heightfields.clear();
+    assert!(!run_scenario(&scenario).passed());
+}
+
+#[test]
+fn broken_scenarios_are_rejected() {
+    let invalid = |ron: &str| matches!(Scenario::parse(ron), Err(ScenarioError::Invalid(_)));
+    assert!(invalid(&BOUNCE.replace(
+        "body: \"ball\", after: 0.5, height: 0.0",
+        "body: \"bal\", after: 0.5, height: 0.0"
+    )));
+    assert!(invalid(&BOUNCE.replace("duration: 1.5", "duration: 0.0")));
+    assert!(invalid(&BOUNCE.replace("radius: 20.0", "radius: -20.0")));
+    assert!(invalid(&BOUNCE.replace(
+        "bodies:",
+        "heightfields: [(position: (0.0, 0.0), spacing: 10.0, heights: [0.0])], bodies:"
+    )));
+    assert!(matches!(
+        Scenario::parse("(duration: 1.0)"),
+        Err(ScenarioError::Ron(_))
+    ));
+}
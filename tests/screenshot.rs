@@ -0,0 +1,46 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    screenshot::{screenshot_file_name, ScreenshotPlugin},
+    testing::{press_key, test_app},
+    toast::ToastEvent,
+};
+
+// What the screenshots look like has to be checked by hand, in the browser: press F10 and open the
+// downloaded file.
+
+#[test]
+fn file_names_are_timestamped_pngs() {
+    assert_eq!(
+        screenshot_file_name(1_700_000_000_000),
+        "screenshot_1700000000000.png"
+    );
+    // Later ones sort after earlier ones of the same length
+    assert!(screenshot_file_name(1_700_000_000_001) > screenshot_file_name(1_700_000_000_000));
+}
+
+// Captures are synchronous, so pressing F10 again straight away takes another one instead of
+// waiting for the first. Natively each press is reported as unsupported rather than panicking.
+#[test]
+fn every_press_is_answered_with_a_toast() {
+    let mut app = test_app();
+    app.add_event::<ToastEvent>().add_plugin(ScreenshotPlugin);
+    let mut reader = ManualEventReader::<ToastEvent>::default();
+    let mut toasts = Vec::new();
+    for _ in 0..3 {
+        press_key(&mut app, KeyCode::F10, true);
+        app.update();
+        press_key(&mut app, KeyCode::F10, false);
+        app.update();
+        let events = app.world.resource::<Events<ToastEvent>>();
+        toasts.extend(reader.iter(events).map(|toast| toast.0.clone()));
+    }
+
+    assert_eq!(toasts.len(), 3, "{toasts:?}");
+    #[cfg(not(target_arch = "wasm32"))]
+    assert!(
+        toasts
+            .iter()
+            .all(|toast| toast.starts_with("Couldn't take a screenshot")),
+        "{toasts:?}"
+    );
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy_game::{
+    help::Hint,
+    settings::{InputAction, Settings, SettingsPlugin},
+    storage::{MemoryBackend, Storage, StorageBackend},
+    testing::{add_test_clock, TEST_DT},
+};
+
+// An app with nothing but the settings, kept in memory with `entries` already there
+fn settings_app(entries: &[(&str, &str)]) -> App {
+    let mut storage = MemoryBackend::default();
+    for (key, value) in entries {
+        storage.set(key, value).unwrap();
+    }
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(Storage(Box::new(storage)));
+    add_test_clock(&mut app).add_plugin(SettingsPlugin);
+    app
+}
+
+fn stored(app: &App) -> Option<String> {
+    app.world.resource::<Storage>().0.get("settings")
+}
+
+// Every field away from its default
+fn changed_settings() -> Settings {
+    let mut settings = Settings {
+        master_volume: 0.25,
+        sfx_volume: 0.5,
+        music_volume: 0.75,
+        muted: true,
+        vsync: false,
+        physics_substeps: 7,
+        landing_assist: true,
+        debug_overlay: true,
+        resume_on_focus: true,
+        controls_help_seen: true,
+        hints_shown: vec![Hint::Spin],
+        leaderboard_opt_in: true,
+        leaderboard_url: "https://example.com/scores".to_string(),
+        ..default()
+    };
+    settings.input.bind_swapping(InputAction::Jump, KeyCode::W);
+    settings.input.add_key(InputAction::SpinLeft, KeyCode::Left);
+    settings
+}
+
+#[test]
+fn every_field_round_trips() {
+    let settings = changed_settings();
+    assert!(settings != Settings::default());
+    let mut storage = MemoryBackend::default();
+    settings.save(&mut storage).unwrap();
+    assert!(Settings::load(&mut storage) == settings);
+
+    // And through the plugin, at startup
+    let app = settings_app(&[("settings", &storage.get("settings").unwrap())]);
+    assert!(*app.world.resource::<Settings>() == settings);
+}
+
+// Settings that can't be read are replaced by the defaults, and so is the file, which then loads
+#[test]
+fn corrupt_settings_fall_back_to_the_defaults() {
+    for corrupt in ["", "(master_volume: ", "[1, 2, 3]", "(vsync: 12)"] {
+        let app = settings_app(&[("settings", corrupt)]);
+        assert!(
+            *app.world.resource::<Settings>() == Settings::default(),
+            "{corrupt}"
+        );
+        let clean = stored(&app).unwrap();
+        assert_ne!(clean, corrupt);
+        let mut storage = MemoryBackend::default();
+        storage.set("settings", &clean).unwrap();
+        assert!(Settings::load(&mut storage) == Settings::default());
+    }
+}
+
+#[test]
+fn missing_settings_are_written_with_the_defaults() {
+    let mut storage = MemoryBackend::default();
+    assert!(Settings::load(&mut storage) == Settings::default());
+    assert!(storage.get("settings").is_some());
+}
+
+// Settings are written once they're left alone for a second, not on every change, and not at all
+// while nothing changes
+#[test]
+fn saving_waits_for_the_changes_to_stop() {
+    let mut app = settings_app(&[]);
+    let defaults = stored(&app).unwrap();
+    let second = (1.0 / TEST_DT).round() as usize;
+    for _ in 0..2 * second {
+        app.update();
+    }
+    assert_eq!(stored(&app).unwrap(), defaults);
+
+    // Like dragging a volume slider for a second
+    for i in 0..second {
+        app.world.resource_mut::<Settings>().master_volume = i as f32 / second as f32;
+        app.update();
+        assert_eq!(stored(&app).unwrap(), defaults, "{i}");
+    }
+    for i in 0..second - 2 {
+        app.update();
+        assert_eq!(stored(&app).unwrap(), defaults, "{i}");
+    }
+    for _ in 0..3 {
+        app.update();
+    }
+    let mut storage = MemoryBackend::default();
+    storage.set("settings", &stored(&app).unwrap()).unwrap();
+    let saved = Settings::load(&mut storage);
+    assert_eq!(saved.master_volume, (second - 1) as f32 / second as f32);
+}
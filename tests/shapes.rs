@@ -0,0 +1,206 @@
+use bevy::{
+    asset::AssetPlugin,
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+    sprite::Mesh2dHandle,
+};
+use bevy_game::{
+    mesh_cache::MeshCache,
+    player::Player,
+    shapes::{AnimatedSpinner, FidgetSpinner, ShapesPlugin, SpinnerProfile},
+    testing::{press_key, test_app},
+};
+
+fn positions(mesh: &Mesh) -> Vec<Vec2> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("no positions");
+    };
+    positions.iter().map(|&[x, y, _]| Vec2::new(x, y)).collect()
+}
+
+// Signed area of every triangle in the mesh, positive for counter-clockwise ones
+fn triangle_areas(mesh: &Mesh) -> Vec<f32> {
+    let positions = positions(mesh);
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        panic!("no indices");
+    };
+    assert_eq!(indices.len() % 3, 0);
+    indices
+        .chunks(3)
+        .map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            0.5 * (b - a).perp_dot(c - a)
+        })
+        .collect()
+}
+
+// Shoelace formula over the rim, in order
+fn polygon_area(rim: &[Vec2]) -> f32 {
+    let next = rim.iter().cycle().skip(1);
+    0.5 * rim
+        .iter()
+        .zip(next)
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+}
+
+fn assert_fan_covers_the_rim(spinner: FidgetSpinner) {
+    let mesh = Mesh::from(spinner);
+    let areas = triangle_areas(&mesh);
+    assert_eq!(areas.len(), spinner.vertices);
+    for (i, area) in areas.iter().enumerate() {
+        assert!(*area > 0.0, "triangle {i} of {}: {area}", spinner.vertices);
+    }
+
+    let rim: Vec<_> = spinner
+        .rim_positions(0.0, 0.0)
+        .map(|[x, y, _]| Vec2::new(x, y))
+        .collect();
+    assert_eq!(positions(&mesh)[1..], rim);
+    let expected = polygon_area(&rim);
+    let total = areas.iter().sum::<f32>();
+    assert!(
+        (total - expected).abs() < 1e-4 * expected,
+        "{total} {expected}"
+    );
+}
+
+// Every triangle of the fan winds counter-clockwise, including the one wrapping back to the first
+// rim vertex, and together they cover exactly the polygon of the rim
+#[test]
+fn spinner_fan_winds_counter_clockwise() {
+    for vertices in [3, 4, 5, 24, 97] {
+        for profile in [SpinnerProfile::Bumps, SpinnerProfile::Star] {
+            assert_fan_covers_the_rim(FidgetSpinner {
+                vertices,
+                profile,
+                ..FidgetSpinner::new(10.0)
+            });
+        }
+    }
+}
+
+// Without bumps the rim is a regular polygon, of area n·r²·sin(2π/n)/2
+#[test]
+fn smooth_spinner_is_a_regular_polygon() {
+    let radius = 10.0;
+    for vertices in [3, 6, 64] {
+        let spinner = FidgetSpinner {
+            bump_size: 0.0,
+            vertices,
+            ..FidgetSpinner::new(radius)
+        };
+        let total = triangle_areas(&Mesh::from(spinner)).iter().sum::<f32>();
+        let n = vertices as f32;
+        let expected = 0.5 * n * radius * radius * (std::f32::consts::TAU / n).sin();
+        assert!(
+            (total - expected).abs() < 1e-4 * expected,
+            "{vertices}: {total} {expected}"
+        );
+    }
+}
+
+// The smallest spinner is a single triangle's worth of rim: three triangles around the center
+#[test]
+fn three_vertex_spinner_has_three_triangles() {
+    let mesh = Mesh::from(FidgetSpinner {
+        vertices: 3,
+        ..FidgetSpinner::new(10.0)
+    });
+    assert_eq!(positions(&mesh).len(), 4);
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        panic!("no indices");
+    };
+    assert_eq!(indices, &[0, 1, 2, 0, 2, 3, 0, 3, 1]);
+}
+
+#[test]
+#[should_panic(expected = "at least 3 rim vertices")]
+fn spinner_needs_three_vertices() {
+    let _ = Mesh::from(FidgetSpinner {
+        vertices: 2,
+        ..FidgetSpinner::new(10.0)
+    });
+}
+
+// Rippling the bumps only moves the rim: the vertex count, indices and normals stay as they were,
+// and no vertex goes further than the bumps and the ripple reach
+#[test]
+fn animating_the_rim_keeps_the_mesh_layout() {
+    let spinner = FidgetSpinner::new(20.0);
+    let amplitude = 1.5;
+    let mut mesh = Mesh::from(spinner);
+    let indices = mesh.indices().cloned();
+    let normals = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).cloned();
+
+    for step in 0..100 {
+        spinner.update_mesh(&mut mesh, step as f32 * 0.1, amplitude);
+        let positions = positions(&mesh);
+        assert_eq!(positions.len(), spinner.vertices + 1);
+        assert_eq!(positions[0], Vec2::ZERO);
+        let reach = spinner.bump_size + amplitude;
+        for position in &positions[1..] {
+            let distance = position.length();
+            assert!(
+                (distance - spinner.radius).abs() <= reach + 1e-4,
+                "{step}: {distance}"
+            );
+        }
+    }
+    assert_eq!(
+        mesh.indices().map(|i| i.iter().collect::<Vec<_>>()),
+        indices.as_ref().map(|i| i.iter().collect::<Vec<_>>())
+    );
+    assert_eq!(
+        mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+            .map(|n| n.get_bytes()),
+        normals.as_ref().map(|n| n.get_bytes())
+    );
+}
+
+// The toggle key gives the player's spinner its own copy of the mesh to ripple, and takes it back
+// to the shared one
+#[test]
+fn toggling_the_animation_swaps_the_players_mesh() {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_asset::<Mesh>()
+        .add_plugin(ShapesPlugin);
+    let spinner = FidgetSpinner::new(20.0);
+    let shared = app
+        .world
+        .resource_scope(|world, mut cache: Mut<MeshCache>| {
+            cache.spinner(&mut world.resource_mut::<Assets<Mesh>>(), spinner)
+        });
+    let player = app
+        .world
+        .spawn((Player::default(), spinner, Mesh2dHandle(shared.clone())))
+        .id();
+    let mesh = |app: &App| app.world.get::<Mesh2dHandle>(player).unwrap().0.clone();
+    let rim_of = |app: &App, mesh: &Handle<Mesh>| {
+        positions(app.world.resource::<Assets<Mesh>>().get(mesh).unwrap())
+    };
+    let shared_rim = rim_of(&app, &shared);
+
+    press_key(&mut app, KeyCode::B, true);
+    app.update();
+    press_key(&mut app, KeyCode::B, false);
+    app.update();
+    assert!(app.world.get::<AnimatedSpinner>(player).is_some());
+    let own = mesh(&app);
+    assert_ne!(own, shared);
+    let rim = rim_of(&app, &own);
+    app.update();
+    let moved = rim_of(&app, &own);
+    assert_eq!(moved.len(), rim.len());
+    assert_ne!(moved, rim);
+    // The shared mesh isn't touched
+    assert_eq!(rim_of(&app, &shared), shared_rim);
+
+    press_key(&mut app, KeyCode::B, true);
+    app.update();
+    assert!(app.world.get::<AnimatedSpinner>(player).is_none());
+    assert_eq!(mesh(&app), shared);
+}
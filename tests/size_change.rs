@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, Gravity, PhysObj, PhysicsConfig},
+    player::Player,
+    powerup::{PowerUpPlugin, SizeChange, SizeChangeState, SizePickup},
+    testing::{spawn_test_ball, spawn_test_player, test_app, TEST_BALL_MASS},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+const JUMP_IMPULSE: f32 = 10_000.0;
+
+// The power-ups, and the player on the floor
+fn powerup_app() -> (App, Entity) {
+    let mut app = test_app();
+    app.add_plugin(PowerUpPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    let mut collider = app.world.get_mut::<Collider>(player).unwrap();
+    let Collider::Ball {
+        touching_ground, ..
+    } = &mut *collider;
+    *touching_ground = false;
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: JUMP_IMPULSE,
+        torque: 0.0,
+        ..Default::default()
+    });
+    (app, player)
+}
+
+fn radius(app: &App, entity: Entity) -> f32 {
+    let Some(&Collider::Ball { radius, .. }) = app.world.get::<Collider>(entity) else {
+        panic!("no collider");
+    };
+    radius
+}
+
+fn update_for(app: &mut App, seconds: f32) {
+    for _ in 0..(seconds * 60.0).round() as usize {
+        app.update();
+    }
+}
+
+#[test]
+fn growing_scales_mass_and_moment_of_inertia() {
+    let (mut app, player) = powerup_app();
+    app.world.entity_mut(player).insert(SizeChange {
+        target_radius: 2.0 * RADIUS,
+        duration: 5.0,
+        scale_jump_impulse: true,
+    });
+
+    update_for(&mut app, 1.0);
+
+    assert_eq!(radius(&app, player), 2.0 * RADIUS);
+    let phys_obj = app.world.get::<PhysObj>(player).unwrap();
+    assert!(
+        (phys_obj.mass - 4.0 * MASS).abs() < 1e-3,
+        "{}",
+        phys_obj.mass
+    );
+    // Still a solid disk
+    let expected = 0.5 * phys_obj.mass * (2.0 * RADIUS).powi(2);
+    assert!(
+        (phys_obj.moment_of_inertia - expected).abs() < 1e-5 * expected,
+        "{} != {expected}",
+        phys_obj.moment_of_inertia
+    );
+    let jump_impulse = app.world.get::<Player>(player).unwrap().jump_impulse;
+    assert!((jump_impulse - 4.0 * JUMP_IMPULSE).abs() < 1e-2);
+    assert_eq!(
+        app.world.get::<Transform>(player).unwrap().scale,
+        Vec3::new(2.0, 2.0, 1.0)
+    );
+    // Grown upwards, so it's still resting on the floor
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let bottom = app.world.get::<Transform>(player).unwrap().translation.y - 2.0 * RADIUS;
+    assert!((bottom - floor_y).abs() < 1e-3, "{bottom}");
+}
+
+#[test]
+fn growth_waits_until_there_is_room() {
+    let (mut app, player) = powerup_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    // Floating just above the player, in the way of it growing upwards
+    let blocker = spawn_test_ball(
+        &mut app,
+        Vec2::new(0.0, floor_y + 3.0 * RADIUS + 5.0),
+        RADIUS,
+    );
+    app.world.entity_mut(blocker).remove::<Gravity>();
+    app.world.entity_mut(player).insert(SizeChange {
+        target_radius: 2.0 * RADIUS,
+        duration: 5.0,
+        scale_jump_impulse: false,
+    });
+
+    update_for(&mut app, 1.0);
+    let blocked = radius(&app, player);
+    assert!(blocked < 1.2 * RADIUS, "grew to {blocked}");
+    assert_eq!(
+        app.world.get::<Transform>(blocker).unwrap().translation.y,
+        floor_y + 3.0 * RADIUS + 5.0
+    );
+
+    app.world.despawn(blocker);
+    update_for(&mut app, 1.0);
+    assert_eq!(radius(&app, player), 2.0 * RADIUS);
+}
+
+#[test]
+fn size_reverts_once_the_change_expires() {
+    let (mut app, player) = powerup_app();
+    let before = app.world.get::<PhysObj>(player).unwrap().clone();
+    app.world.entity_mut(player).insert(SizeChange {
+        target_radius: 0.5 * RADIUS,
+        duration: 1.0,
+        scale_jump_impulse: true,
+    });
+
+    update_for(&mut app, 0.5);
+    assert_eq!(radius(&app, player), 0.5 * RADIUS);
+
+    update_for(&mut app, 1.0);
+    assert_eq!(radius(&app, player), RADIUS);
+    let phys_obj = app.world.get::<PhysObj>(player).unwrap();
+    assert_eq!(phys_obj.mass, before.mass);
+    assert_eq!(phys_obj.moment_of_inertia, before.moment_of_inertia);
+    assert_eq!(
+        app.world.get::<Player>(player).unwrap().jump_impulse,
+        JUMP_IMPULSE
+    );
+    assert_eq!(app.world.get::<Transform>(player).unwrap().scale, Vec3::ONE);
+    assert!(app.world.get::<SizeChange>(player).is_none());
+    assert!(app.world.get::<SizeChangeState>(player).is_none());
+}
+
+#[test]
+fn touching_a_pickup_grants_its_change() {
+    let (mut app, player) = powerup_app();
+    let position = app.world.get::<Transform>(player).unwrap().translation;
+    let change = SizeChange {
+        target_radius: 2.0 * RADIUS,
+        duration: 5.0,
+        scale_jump_impulse: false,
+    };
+    let pickup = app
+        .world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(
+                position + Vec3::X * 100.0,
+            )),
+            SizePickup(change),
+        ))
+        .id();
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+
+    update_for(&mut app, 1.0);
+
+    assert!(app.world.get_entity(pickup).is_none());
+    assert!(app.world.get::<SizeChange>(player).is_some());
+    assert!(radius(&app, player) > RADIUS);
+}
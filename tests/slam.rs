@@ -0,0 +1,236 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    elevator::{Elevator, ElevatorPlugin, ELEVATOR_THICKNESS},
+    level::{Floor, SurfaceMaterial},
+    physics::{sleep::StaticBody, Collider, PhysObj, PhysicsConfig},
+    player::{Player, SlamEvent, Slamming},
+    settings::Settings,
+    testing::{set_test_ball_restitution, spawn_test_ball, spawn_test_player, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+
+// Too heavily loaded to move, with its top `height` above the floor and under the player
+fn spawn_elevator(app: &mut App, height: f32) -> f32 {
+    app.add_plugin(ElevatorPlugin);
+    let top = app.world.resource::<PhysicsConfig>().floor_y + height;
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(0.0, top, 0.0)),
+        Elevator {
+            top,
+            bottom: top - 100.0,
+            speed: 100.0,
+            width: 200.0,
+            threshold: 1000.0,
+        },
+    ));
+    top
+}
+
+// Under the player
+fn spawn_trampoline(app: &mut App) {
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(0.0, floor_y, 0.0)),
+        Floor { width: 400.0 },
+        SurfaceMaterial::Trampoline {
+            restitution_override: 1.2,
+            min_launch_speed: 800.0,
+        },
+        StaticBody,
+    ));
+}
+
+fn hold_slam(app: &mut App) {
+    let key = Settings::default().input.climb_down;
+    app.world.resource_mut::<Input<KeyCode>>().press(key);
+}
+
+#[test]
+fn slam_lands_dead_and_shoves_the_balls_nearby() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    // A bouncy player in the air, moving sideways
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.9);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        slam_speed: 1500.0,
+        ..Default::default()
+    });
+    let near = spawn_test_ball(&mut app, Vec2::new(100.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, near, 0.5);
+    let far = spawn_test_ball(&mut app, Vec2::new(1000.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, far, 0.5);
+    let mut reader = ManualEventReader::<SlamEvent>::default();
+    hold_slam(&mut app);
+
+    // The physics starts on the second update
+    for _ in 0..2 {
+        app.update();
+    }
+    assert!(app.world.get::<Slamming>(player).is_some());
+    let vel = app.world.get::<PhysObj>(player).unwrap().vel;
+    assert_eq!(vel.x, 0.0);
+    assert!(vel.y < -1500.0, "{vel}");
+
+    let mut slams = Vec::new();
+    for _ in 0..60 {
+        app.update();
+        slams.extend(
+            reader
+                .iter(app.world.resource::<Events<SlamEvent>>())
+                .map(|slam| (slam.entity, slam.impact_speed)),
+        );
+        if !slams.is_empty() {
+            break;
+        }
+    }
+    assert_eq!(slams.len(), 1);
+    assert_eq!(slams[0].0, player);
+    assert!(slams[0].1 >= 1500.0);
+    assert!(app.world.get::<Slamming>(player).is_none());
+    assert!(app.world.get::<PhysObj>(near).unwrap().vel.x > 0.0);
+    assert_eq!(app.world.get::<PhysObj>(far).unwrap().vel, Vec2::ZERO);
+
+    // Despite its restitution it stays down, and holding the key on the ground doesn't slam again
+    for _ in 0..30 {
+        app.update();
+        let y = app.world.get::<Transform>(player).unwrap().translation.y;
+        assert_eq!(y, floor_y + RADIUS);
+        assert!(app.world.get::<Slamming>(player).is_none());
+    }
+}
+
+// However fast it comes down, the slam lands on the floor rather than going through it
+#[test]
+fn fast_slam_lands_on_the_floor() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.9);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        slam_speed: 100_000.0,
+        ..Default::default()
+    });
+    let mut reader = ManualEventReader::<SlamEvent>::default();
+    hold_slam(&mut app);
+
+    let mut slams = 0;
+    for _ in 0..30 {
+        app.update();
+        slams += reader
+            .iter(app.world.resource::<Events<SlamEvent>>())
+            .count();
+        let y = app.world.get::<Transform>(player).unwrap().translation.y;
+        assert!(y >= floor_y + RADIUS - 0.01, "{y}");
+    }
+    assert_eq!(slams, 1);
+    let Collider::Ball {
+        touching_ground, ..
+    } = *app.world.get::<Collider>(player).unwrap();
+    assert!(touching_ground);
+}
+
+// Elevators don't send landings through the collision system, but a slam still lands on one
+#[test]
+fn slam_lands_on_an_elevator() {
+    let mut app = test_app();
+    let top = spawn_elevator(&mut app, 300.0);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 600.0), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.9);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        slam_speed: 1500.0,
+        ..Default::default()
+    });
+    let mut reader = ManualEventReader::<SlamEvent>::default();
+    hold_slam(&mut app);
+
+    let mut slams = 0;
+    for _ in 0..60 {
+        app.update();
+        slams += reader
+            .iter(app.world.resource::<Events<SlamEvent>>())
+            .count();
+    }
+    assert_eq!(slams, 1);
+    assert!(app.world.get::<Slamming>(player).is_none());
+    let y = app.world.get::<Transform>(player).unwrap().translation.y;
+    assert!((y - (top + RADIUS)).abs() < 0.01, "{y} {top}");
+}
+
+// A step of this slam is much longer than the elevator is thick, but it still can't go through
+#[test]
+fn fast_slam_lands_on_an_elevator() {
+    let mut app = test_app();
+    let top = spawn_elevator(&mut app, 300.0);
+    let slam_speed = 6000.0;
+    assert!(slam_speed / 60.0 > 4.0 * ELEVATOR_THICKNESS);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.9);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        slam_speed,
+        ..Default::default()
+    });
+    let mut reader = ManualEventReader::<SlamEvent>::default();
+    hold_slam(&mut app);
+
+    let mut slams = 0;
+    for _ in 0..30 {
+        app.update();
+        slams += reader
+            .iter(app.world.resource::<Events<SlamEvent>>())
+            .count();
+        let y = app.world.get::<Transform>(player).unwrap().translation.y;
+        assert!(y >= top + RADIUS - 0.01, "{y} {top}");
+    }
+    assert_eq!(slams, 1);
+    assert!(app.world.get::<Slamming>(player).is_none());
+}
+
+// A trampoline throws the slam back up instead of landing it, which still ends the slam
+#[test]
+fn slam_bounces_off_a_trampoline() {
+    let mut app = test_app();
+    spawn_trampoline(&mut app);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    set_test_ball_restitution(&mut app, player, 0.9);
+    app.world.get_mut::<PhysObj>(player).unwrap().vel.x = 300.0;
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        slam_speed: 1500.0,
+        ..Default::default()
+    });
+    let mut reader = ManualEventReader::<SlamEvent>::default();
+    hold_slam(&mut app);
+    for _ in 0..2 {
+        app.update();
+    }
+    assert!(app.world.get::<Slamming>(player).is_some());
+    let key = Settings::default().input.climb_down;
+    app.world.resource_mut::<Input<KeyCode>>().release(key);
+
+    let mut slams = 0;
+    for _ in 0..30 {
+        app.update();
+        slams += reader
+            .iter(app.world.resource::<Events<SlamEvent>>())
+            .count();
+        if slams > 0 {
+            break;
+        }
+    }
+    assert_eq!(slams, 1);
+    assert!(app.world.get::<Slamming>(player).is_none());
+    assert!(app.world.get::<PhysObj>(player).unwrap().vel.y > 0.0);
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_game::{
+    level::Floor,
+    physics::{
+        sleep::{Sleeping, StaticBody, StaticHash, STATIC_CELL_WIDTH},
+        Collider, PhysObj, PhysicsConfig,
+    },
+    testing::{spawn_test_ball, test_app},
+};
+
+fn spawn_resting_ball(app: &mut App) -> Entity {
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(app, Vec2::new(0.0, floor_y + 10.0), 10.0);
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.mass = 1.0;
+    phys_obj.moment_of_inertia = 50.0;
+    let mut collider = app.world.get_mut::<Collider>(ball).unwrap();
+    let Collider::Ball {
+        coef_of_restitution,
+        touching_ground,
+        ..
+    } = &mut *collider;
+    *coef_of_restitution = 0.5;
+    // Lands on the first update rather than starting out at rest
+    *touching_ground = false;
+    ball
+}
+
+#[test]
+fn resting_ball_falls_asleep_and_wakes_on_impulse() {
+    let mut app = test_app();
+    let ball = spawn_resting_ball(&mut app);
+    for _ in 0..120 {
+        app.update();
+    }
+    assert!(app.world.get::<Sleeping>(ball).is_some());
+
+    // Sleeping bodies aren't integrated, so they stay put
+    let position = app.world.get::<Transform>(ball).unwrap().translation;
+    app.update();
+    assert_eq!(
+        app.world.get::<Transform>(ball).unwrap().translation,
+        position
+    );
+
+    app.world.get_mut::<PhysObj>(ball).unwrap().vel = Vec2::new(100.0, 500.0);
+    app.update();
+    assert!(app.world.get::<Sleeping>(ball).is_none());
+    app.update();
+    assert!(app.world.get::<Transform>(ball).unwrap().translation.y > position.y);
+}
+
+// The static hash is kept while no static body moves, and rebuilt with a platform where it's
+// moved to as soon as it moves
+#[test]
+fn static_hash_follows_a_moving_platform() {
+    let mut app = test_app();
+    let platform = app
+        .world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 100.0, 0.0)),
+            Floor { width: 100.0 },
+            StaticBody,
+        ))
+        .id();
+    let far = 10.0 * STATIC_CELL_WIDTH;
+    app.update();
+    let hash = app.world.resource::<StaticHash>();
+    assert_eq!(hash.near(0.0), [platform]);
+    assert_eq!(hash.near(far), []);
+    let rebuilds = hash.rebuilds;
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<StaticHash>().rebuilds, rebuilds);
+
+    app.world
+        .get_mut::<Transform>(platform)
+        .unwrap()
+        .translation
+        .x = far;
+    app.update();
+    let hash = app.world.resource::<StaticHash>();
+    assert_eq!(hash.rebuilds, rebuilds + 1);
+    assert_eq!(hash.near(0.0), []);
+    assert_eq!(hash.near(far), [platform]);
+
+    app.world.despawn(platform);
+    app.update();
+    assert_eq!(app.world.resource::<StaticHash>().near(far), []);
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::Player,
+    slingshot::{SlingshotConfig, SlingshotCursor, SlingshotDrag, SlingshotPlugin},
+    testing::{spawn_test_player, test_app, TEST_BALL_MASS},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+
+fn slingshot_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(SlingshotPlugin);
+    // The physics starts on the second update
+    app.update();
+    app
+}
+
+fn position(app: &App, player: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(player)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+// Moves the mouse to `offset` from the player, with the button `held` or not, for one update
+fn mouse(app: &mut App, player: Entity, offset: Vec2, held: bool) {
+    let position = Some(position(app, player) + offset);
+    *app.world.resource_mut::<SlingshotCursor>() = SlingshotCursor { position, held };
+    app.update();
+}
+
+fn drag(app: &App) -> SlingshotDrag {
+    *app.world.resource::<SlingshotDrag>()
+}
+
+#[test]
+fn release_launches_away_from_the_drag() {
+    let mut app = slingshot_app();
+    let config = app.world.resource::<SlingshotConfig>().clone();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    mouse(&mut app, player, Vec2::new(5.0, 5.0), true);
+    assert!(matches!(drag(&app), SlingshotDrag::Aiming { .. }));
+
+    let pull = Vec2::new(-60.0, -80.0);
+    mouse(&mut app, player, pull, true);
+    mouse(&mut app, player, pull, false);
+    assert_eq!(drag(&app), SlingshotDrag::Idle);
+    let vel = app.world.get::<PhysObj>(player).unwrap().vel;
+    let launch = config.impulse(pull) / MASS;
+    assert!(launch.x > 0.0 && launch.y > 0.0);
+    assert!(
+        (vel - launch).length() < 0.1 * launch.length(),
+        "{vel} {launch}"
+    );
+}
+
+#[test]
+fn launch_is_clamped() {
+    let mut app = slingshot_app();
+    let config = app.world.resource::<SlingshotConfig>().clone();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    mouse(&mut app, player, Vec2::ZERO, true);
+    mouse(&mut app, player, Vec2::new(2000.0, 0.0), true);
+    mouse(&mut app, player, Vec2::new(2000.0, 0.0), false);
+    let speed = app.world.get::<PhysObj>(player).unwrap().vel.length();
+    let max_speed = config.max_impulse / MASS;
+    assert!((speed - max_speed).abs() < 0.05 * max_speed, "{speed}");
+}
+
+// Held in the air while aiming, and a short drag lets it fall again without a launch
+#[test]
+fn aiming_freezes_and_short_drags_cancel() {
+    let mut app = slingshot_app();
+    let config = app.world.resource::<SlingshotConfig>().clone();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 400.0), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    mouse(&mut app, player, Vec2::ZERO, true);
+    let held = position(&app, player);
+    for _ in 0..30 {
+        mouse(
+            &mut app,
+            player,
+            Vec2::new(0.5 * config.min_drag, 0.0),
+            true,
+        );
+        assert_eq!(position(&app, player), held);
+        assert_eq!(app.world.get::<PhysObj>(player).unwrap().vel, Vec2::ZERO);
+    }
+
+    mouse(
+        &mut app,
+        player,
+        Vec2::new(0.5 * config.min_drag, 0.0),
+        false,
+    );
+    assert_eq!(drag(&app), SlingshotDrag::Idle);
+    for _ in 0..5 {
+        app.update();
+    }
+    let phys_obj = app.world.get::<PhysObj>(player).unwrap();
+    assert_eq!(phys_obj.vel.x, 0.0);
+    assert!(phys_obj.vel.y < 0.0);
+}
+
+#[test]
+fn pressing_off_the_ball_does_nothing() {
+    let mut app = slingshot_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    mouse(&mut app, player, Vec2::new(2.0 * RADIUS, 0.0), true);
+    assert_eq!(drag(&app), SlingshotDrag::Idle);
+
+    // Nor does dragging onto it with the button already down
+    mouse(&mut app, player, Vec2::ZERO, true);
+    assert_eq!(drag(&app), SlingshotDrag::Idle);
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{CollisionEvent, PhysicsTime, SlowMotion},
+    testing::{press_key, test_app, TEST_DT},
+};
+
+fn slow_motion_app() -> App {
+    let mut app = test_app();
+    app.update();
+    app.update();
+    app
+}
+
+fn scale(app: &App) -> f32 {
+    app.world.resource::<PhysicsTime>().scale
+}
+
+// Holding the slow motion key eases time down to a tenth of its speed over 0.2 real seconds, and
+// letting go eases it back up as fast
+#[test]
+fn slow_motion_ramps_in_and_out() {
+    let mut app = slow_motion_app();
+    assert_eq!(scale(&app), 1.0);
+    press_key(&mut app, KeyCode::Tab, true);
+    let mut scales = Vec::new();
+    for _ in 0..20 {
+        app.update();
+        scales.push(scale(&app));
+    }
+    // A straight line down, then flat
+    let rate = 0.9 / 0.2 * TEST_DT;
+    for (frame, scale) in scales.iter().enumerate() {
+        let expected = (1.0 - rate * (frame + 1) as f32).max(0.1);
+        assert!((scale - expected).abs() < 1e-4, "{frame}: {scales:?}");
+    }
+    // The physics steps get shorter with it
+    let delta = app.world.resource::<PhysicsTime>().delta;
+    assert!((delta - 0.1 * TEST_DT).abs() < 1e-6, "{delta}");
+
+    press_key(&mut app, KeyCode::Tab, false);
+    let mut frames = 0;
+    while scale(&app) < 1.0 {
+        app.update();
+        frames += 1;
+        assert!(frames <= 13, "{}", scale(&app));
+    }
+    assert!(frames >= 12);
+}
+
+fn big_impact(app: &mut App) {
+    app.world.send_event(CollisionEvent {
+        entity: Entity::PLACEHOLDER,
+        impulse: 50_000.0,
+    });
+}
+
+// With auto slow motion on, a big impact slows time for half a second, and impacts in the next two
+// seconds don't slow it again
+#[test]
+fn auto_slow_motion_has_a_cooldown() {
+    let mut app = slow_motion_app();
+    // Not while it's off
+    big_impact(&mut app);
+    app.update();
+    assert_eq!(scale(&app), 1.0);
+
+    press_key(&mut app, KeyCode::O, true);
+    app.update();
+    press_key(&mut app, KeyCode::O, false);
+    assert!(app.world.resource::<SlowMotion>().auto);
+
+    big_impact(&mut app);
+    let mut slowed = Vec::new();
+    for frame in 0..(3.0 / TEST_DT) as usize {
+        // A bounce every few frames
+        if frame % 10 == 0 {
+            big_impact(&mut app);
+        }
+        app.update();
+        if scale(&app) < 1.0 {
+            slowed.push(frame);
+        }
+    }
+    // Slowed from the first impact for half a second and the ramp back up, then never again until
+    // the cooldown's done after two seconds
+    let first_slow_down = *slowed.first().unwrap();
+    assert_eq!(first_slow_down, 0);
+    let cooldown_frames = (2.0 / TEST_DT) as usize;
+    let slowed_again: Vec<_> = slowed
+        .iter()
+        .filter(|&&frame| frame > (0.5 / TEST_DT) as usize + 13)
+        .collect();
+    assert!(
+        slowed_again
+            .first()
+            .is_some_and(|&&frame| frame >= cooldown_frames - 1),
+        "{slowed:?}"
+    );
+}
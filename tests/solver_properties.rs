@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_game::physics::{
+    collision::calculate_collision_dt, friction::apply_friction_impulse, PhysObj,
+};
+use proptest::prelude::*;
+
+// How far from the floor a body `s` above it, moving at `v` and accelerating at `a`, was `t` ago
+fn height_ago(s: f32, v: f32, a: f32, t: f32) -> f32 {
+    s - v * t + 0.5 * a * t * t
+}
+
+proptest! {
+    // A body that crossed into the floor `ago` seconds ago, at `impact` (moving down into it), is
+    // found to have been at the floor no longer ago than that
+    #[test]
+    fn collision_dt_finds_the_last_crossing(
+        impact in -5000.0_f32..-1.0,
+        a in -5000.0_f32..5000.0,
+        ago in 0.0_f32..0.05,
+    ) {
+        let (v, s) = (impact + a * ago, impact * ago + 0.5 * a * ago * ago);
+        let t = calculate_collision_dt(s, v, a);
+        prop_assert!(t.is_some(), "no crossing for s={s} v={v} a={a}");
+        let t = t.unwrap();
+        prop_assert!(t >= 0.0, "{t}");
+        prop_assert!(t <= ago * (1.0 + 1e-3) + 1e-6, "{t} is before the crossing at {ago}");
+        // The scale of the terms that should cancel out
+        let scale = s.abs() + (v * t).abs() + (0.5 * a * t * t).abs();
+        let height = height_ago(s, v, a, t);
+        prop_assert!(height.abs() <= 1e-3 * scale + 1e-4, "{height} from the floor {t} ago");
+    }
+
+    // Below the floor, moving up and being pulled down (or not at all), a body was only ever
+    // further down
+    #[test]
+    fn collision_dt_is_none_without_a_crossing(
+        s in -100.0_f32..-0.01,
+        v in 0.0_f32..5000.0,
+        a in -5000.0_f32..=0.0,
+    ) {
+        prop_assert_eq!(calculate_collision_dt(s, v, a), None);
+    }
+
+    // Friction slows the contact point's sliding, at most to a stop and by no more than the
+    // kinetic cap left after what was applied earlier in the frame
+    #[test]
+    fn friction_impulse_never_overshoots(
+        mass in 0.1_f32..100.0,
+        radius in 1.0_f32..100.0,
+        // The moment of inertia over m·r², 1/2 for a solid disk
+        inertia in 0.1_f32..1.0,
+        vel in -5000.0_f32..5000.0,
+        angular_vel in -100.0_f32..100.0,
+        normal_impulse in -10.0_f32..100.0,
+        kinetic_friction in 0.0_f32..2.0,
+        applied_friction in -100.0_f32..100.0,
+    ) {
+        let mut phys_obj = PhysObj {
+            mass,
+            vel: Vec2::new(vel, 0.0),
+            acc: Vec2::ZERO,
+            acc_prev: Vec2::ZERO,
+            moment_of_inertia: inertia * mass * radius * radius,
+            angular_vel,
+            angular_acc: 0.0,
+            angular_acc_prev: 0.0,
+            com_offset: Vec2::ZERO,
+        };
+        let before = vel + angular_vel * radius;
+        apply_friction_impulse(
+            &mut phys_obj,
+            radius,
+            normal_impulse,
+            kinetic_friction,
+            applied_friction,
+        );
+        let after = phys_obj.vel.x + phys_obj.angular_vel * radius;
+        let tolerance = 1e-4 * (vel.abs() + (angular_vel * radius).abs()) + 1e-3;
+
+        prop_assert!(after * before.signum() >= -tolerance, "{before} went past 0 to {after}");
+        prop_assert!(after.abs() <= before.abs() + tolerance, "{before} sped up to {after}");
+        let cap = f32::max(
+            normal_impulse * kinetic_friction + applied_friction * before.signum(),
+            0.0,
+        );
+        let impulse = phys_obj.vel.x - vel;
+        prop_assert!(impulse.abs() <= cap * (1.0 + 1e-4) + tolerance, "{impulse} over {cap}");
+    }
+}
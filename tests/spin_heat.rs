@@ -0,0 +1,174 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::{OverheatEvent, SpinHeat, SpinHeatConfig},
+    testing::{set_test_ball_restitution, spawn_test_player, test_app, TEST_BALL_MASS, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+
+fn angular_vel(app: &App, player: Entity) -> f32 {
+    app.world.get::<PhysObj>(player).unwrap().angular_vel
+}
+
+fn overheated(app: &App, player: Entity) -> bool {
+    app.world
+        .get::<SpinHeat>(player)
+        .is_some_and(SpinHeat::overheated)
+}
+
+#[test]
+fn heat_builds_up_over_the_safe_spin_and_cools_down_under_it() {
+    let config = SpinHeatConfig::default();
+    let fast = 1.5 * config.max_safe_spin;
+    let mut heat = SpinHeat::default();
+
+    for _ in 0..30 {
+        assert!(!heat.update(&config, fast, true, TEST_DT));
+    }
+    assert!((heat.heat - 0.5).abs() < 1e-4, "{}", heat.heat);
+
+    // Either way round
+    for _ in 0..12 {
+        heat.update(&config, -fast, true, TEST_DT);
+    }
+    assert!((heat.heat - 0.7).abs() < 1e-4, "{}", heat.heat);
+
+    for _ in 0..24 {
+        heat.update(&config, 0.5 * config.max_safe_spin, true, TEST_DT);
+    }
+    let cooled = 0.7 - config.decay * 24.0 * TEST_DT;
+    assert!((heat.heat - cooled).abs() < 1e-4, "{}", heat.heat);
+
+    // Down to nothing, and no further
+    for _ in 0..600 {
+        heat.update(&config, 0.0, false, TEST_DT);
+    }
+    assert_eq!(heat.heat, 0.0);
+    assert!(!heat.overheated());
+}
+
+#[test]
+fn overheating_takes_a_second_and_lasts_the_cooldown() {
+    let config = SpinHeatConfig::default();
+    let fast = 1.5 * config.max_safe_spin;
+    let mut heat = SpinHeat::default();
+
+    let steps = (1..600)
+        .find(|_| heat.update(&config, fast, true, TEST_DT))
+        .unwrap();
+    // A second over the safe spin, give or take a step
+    let seconds = steps as f32 * TEST_DT;
+    assert!(
+        (seconds - config.overheat_time).abs() <= TEST_DT + 1e-4,
+        "{seconds}"
+    );
+    assert!(heat.overheated());
+
+    // Spinning on doesn't make the cooldown any longer, and starts over from cold
+    let steps = (1..600)
+        .find(|_| {
+            heat.update(&config, fast, true, TEST_DT);
+            !heat.overheated()
+        })
+        .unwrap();
+    let seconds = steps as f32 * TEST_DT;
+    assert!(
+        (seconds - config.cooldown).abs() <= TEST_DT + 1e-4,
+        "{seconds}"
+    );
+    assert_eq!(heat.heat, 0.0);
+}
+
+// Holding A spins the player up past the safe spin until it overheats. Then the torque is cut,
+// and the spin only wobbles, until it's cooled down and A spins it up again.
+#[test]
+fn overheated_players_lose_their_torque_until_they_cool_down() {
+    let mut app = test_app();
+    let config = app.world.resource::<SpinHeatConfig>().clone();
+    // High enough not to land for a while
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(
+        &mut app,
+        Vec2::new(0.0, floor_y + RADIUS + 1_000_000.0),
+        RADIUS,
+    );
+    set_test_ball_restitution(&mut app, player, 0.8);
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::A);
+    let mut reader = ManualEventReader::<OverheatEvent>::default();
+
+    let mut updates = 0;
+    while reader
+        .iter(app.world.resource::<Events<OverheatEvent>>())
+        .next()
+        .is_none()
+    {
+        app.update();
+        updates += 1;
+        assert!(updates < 600, "never overheated");
+    }
+    assert!(overheated(&app, player));
+    assert!(angular_vel(&app, player) > config.max_safe_spin);
+
+    let before = angular_vel(&app, player);
+    let mut wobbled = false;
+    let mut updates = 0;
+    loop {
+        let last = angular_vel(&app, player);
+        app.update();
+        updates += 1;
+        if !overheated(&app, player) {
+            break;
+        }
+        let change = angular_vel(&app, player) - last;
+        // Far short of what the torque would do
+        assert!(change.abs() < 0.25 * 200_000.0 / (MASS * 0.5 * RADIUS * RADIUS) * TEST_DT);
+        wobbled |= change != 0.0;
+    }
+    assert!(wobbled);
+    let seconds = updates as f32 * TEST_DT;
+    assert!(
+        (seconds - config.cooldown).abs() <= TEST_DT + 1e-4,
+        "{seconds}"
+    );
+    assert!((angular_vel(&app, player) - before).abs() < 5.0);
+
+    let cooled = angular_vel(&app, player);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(angular_vel(&app, player) > cooled + 5.0);
+}
+
+// Spin that the player isn't driving never heats the spinner up: not a spinning ball bouncing
+// around, and not one the player's braking
+#[test]
+fn spin_the_player_is_not_driving_does_not_overheat() {
+    for brake in [false, true] {
+        let mut app = test_app();
+        let config = app.world.resource::<SpinHeatConfig>().clone();
+        let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+        let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + 200.0), RADIUS);
+        set_test_ball_restitution(&mut app, player, 0.8);
+        app.world.get_mut::<PhysObj>(player).unwrap().angular_vel = 3.0 * config.max_safe_spin;
+        if brake {
+            app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+        }
+        let mut reader = ManualEventReader::<OverheatEvent>::default();
+
+        for _ in 0..120 {
+            app.update();
+            assert!(reader
+                .iter(app.world.resource::<Events<OverheatEvent>>())
+                .next()
+                .is_none());
+            assert_eq!(
+                app.world
+                    .get::<SpinHeat>(player)
+                    .map_or(0.0, |heat| heat.heat),
+                0.0
+            );
+        }
+    }
+}
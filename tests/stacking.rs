@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{Collider, PhysicsConfig},
+    testing::{set_test_ball_mass, spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 10.0;
+
+fn height(app: &App, entity: Entity) -> f32 {
+    app.world.get::<Transform>(entity).unwrap().translation.y
+}
+
+#[test]
+fn stacked_balls_do_not_sink_into_each_other() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let stack: Vec<Entity> = (0..5)
+        .map(|i| {
+            let y = config.floor_y + RADIUS + 2.0 * RADIUS * i as f32;
+            let ball = spawn_test_ball(&mut app, Vec2::new(0.0, y), RADIUS);
+            set_test_ball_mass(&mut app, ball, 1.0);
+            // Lands on the first update rather than starting out at rest
+            let mut collider = app.world.get_mut::<Collider>(ball).unwrap();
+            let Collider::Ball {
+                touching_ground, ..
+            } = &mut *collider;
+            *touching_ground = false;
+            ball
+        })
+        .collect();
+
+    for _ in 0..300 {
+        app.update();
+    }
+
+    assert_eq!(height(&app, stack[0]), config.floor_y + RADIUS);
+    // Overlaps up to the slop are allowed; what's beyond it should be all but gone
+    let compression: f32 = stack
+        .windows(2)
+        .map(|pair| {
+            let overlap = 2.0 * RADIUS - (height(&app, pair[1]) - height(&app, pair[0]));
+            (overlap - config.penetration_slop).max(0.0)
+        })
+        .sum();
+    assert!(
+        compression < config.penetration_slop,
+        "compressed by {compression}"
+    );
+}
+
+#[test]
+fn single_ball_rests_exactly_on_the_floor() {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_mass(&mut app, ball, 1.0);
+    // Lands on the first update rather than starting out at rest
+    let mut collider = app.world.get_mut::<Collider>(ball).unwrap();
+    let Collider::Ball {
+        touching_ground, ..
+    } = &mut *collider;
+    *touching_ground = false;
+
+    for _ in 0..300 {
+        app.update();
+        assert_eq!(height(&app, ball), floor_y + RADIUS);
+    }
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "debug-tools")]
+
+use bevy::{asset::AssetPlugin, prelude::*, utils::Duration};
+use bevy_game::{
+    debug::hud::{
+        format_distance, format_duration, format_energy, format_rpm, format_speed,
+        mechanical_energy, StatsHudPlugin,
+    },
+    physics::{Gravity, PhysObj},
+    testing::{press_key, spawn_test_player, test_app},
+};
+
+#[test]
+fn stats_are_formatted_with_their_units() {
+    assert_eq!(format_speed(123.4), "123 px/s");
+    assert_eq!(format_speed(-50.0), "-50 px/s");
+    // A turn a second
+    assert_eq!(format_rpm(std::f32::consts::TAU), "60 rpm");
+    assert_eq!(format_distance(12.345), "12.3 px");
+    assert_eq!(format_duration(Duration::from_micros(1500)), "1.50 ms");
+    assert_eq!(format_energy(2500.0), "2.5 kJ");
+}
+
+#[test]
+fn energy_is_kinetic_plus_potential() {
+    let phys_obj = PhysObj {
+        mass: 2.0,
+        vel: Vec2::new(3.0, 4.0),
+        acc: Vec2::ZERO,
+        acc_prev: Vec2::ZERO,
+        moment_of_inertia: 4.0,
+        angular_vel: 1.0,
+        angular_acc: 0.0,
+        angular_acc_prev: 0.0,
+        com_offset: Vec2::ZERO,
+    };
+    // 25 linear, 2 rotational
+    assert_eq!(mechanical_energy(&phys_obj, 10.0, None), 27.0);
+    assert_eq!(
+        mechanical_energy(&phys_obj, 10.0, Some(&Gravity(100.0))),
+        27.0 + 2000.0
+    );
+}
+
+// Whether the HUD's text changed in the last update
+#[derive(Resource, Default)]
+struct TextChanged(bool);
+
+fn record_text_changes(texts: Query<(), Changed<Text>>, mut changed: ResMut<TextChanged>) {
+    changed.0 = !texts.is_empty();
+}
+
+fn hud_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .init_resource::<TextChanged>()
+        .add_plugin(StatsHudPlugin)
+        .add_system(record_text_changes.in_base_set(CoreSet::PostUpdate));
+    spawn_test_player(&mut app, Vec2::new(0.0, 100.0), 20.0);
+    app.update();
+    app
+}
+
+fn text(app: &mut App) -> Text {
+    app.world.query::<&Text>().single(&app.world).clone()
+}
+
+// The text is only rewritten ten times a second, however many frames there are
+#[test]
+fn hud_updates_are_throttled() {
+    let mut app = hud_app();
+    let mut updated = Vec::new();
+    for frame in 0..60 {
+        app.update();
+        if app.world.resource::<TextChanged>().0 {
+            updated.push(frame);
+        }
+    }
+    // Every sixth frame at 60 fps
+    assert!((9..=11).contains(&updated.len()), "{updated:?}");
+    for pair in updated.windows(2) {
+        assert!((5..=7).contains(&(pair[1] - pair[0])), "{updated:?}");
+    }
+}
+
+// Without a floor below the player there's no height to show, so it's grayed out rather than left
+// out; F1 hides the whole HUD
+#[test]
+fn unavailable_values_are_grayed_out() {
+    let mut app = hud_app();
+    for _ in 0..10 {
+        app.update();
+    }
+    let text = text(&mut app);
+    let height = text
+        .sections
+        .iter()
+        .position(|section| section.value == "height: ")
+        .unwrap();
+    assert_eq!(text.sections[height + 1].value, "n/a\n");
+    assert_eq!(text.sections[height + 1].style.color, Color::GRAY);
+    let speed = &text.sections[1];
+    assert!(speed.value.ends_with("px/s\n"), "{}", speed.value);
+    assert_eq!(speed.style.color, Color::WHITE);
+
+    press_key(&mut app, KeyCode::F1, true);
+    app.update();
+    let visibility = *app
+        .world
+        .query_filtered::<&Visibility, With<Text>>()
+        .single(&app.world);
+    assert_eq!(visibility, Visibility::Hidden);
+}
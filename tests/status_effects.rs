@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use bevy_game::{
+    physics::{PhysObj, PhysicsConfig},
+    player::Player,
+    status::{
+        StatusEffect, StatusEffects, StatusEffectsPlugin, StatusSensor, LOW_FRICTION_SCALE,
+        SPEED_BOOST_TORQUE,
+    },
+    testing::{spawn_test_player, test_app, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const JUMP_IMPULSE: f32 = 10_000.0;
+
+fn status_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(StatusEffectsPlugin);
+    app
+}
+
+fn spawn_sensor(app: &mut App, position: Vec2, effect: StatusEffect, pickup: bool) -> Entity {
+    app.world
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            StatusSensor {
+                effect,
+                duration: 1.0,
+                radius: 15.0,
+                pickup,
+            },
+        ))
+        .id()
+}
+
+fn effects(app: &App, player: Entity) -> StatusEffects {
+    app.world
+        .get::<StatusEffects>(player)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn update_for(app: &mut App, seconds: f32) {
+    for _ in 0..(seconds / TEST_DT).round() as usize {
+        app.update();
+    }
+}
+
+#[test]
+fn the_same_effect_refreshes_instead_of_stacking() {
+    let mut effects = StatusEffects::default();
+    effects.apply(StatusEffect::NoJump, 2.0);
+    effects.apply(StatusEffect::SpeedBoost, 1.0);
+    effects.tick(1.5);
+    assert_eq!(effects.remaining(StatusEffect::NoJump), Some(0.5));
+    assert!(!effects.has(StatusEffect::SpeedBoost));
+
+    // Refreshed to the full duration, not added to it
+    effects.apply(StatusEffect::NoJump, 2.0);
+    assert_eq!(effects.remaining(StatusEffect::NoJump), Some(2.0));
+    assert_eq!(effects.iter().count(), 1);
+    // And never shortened
+    effects.apply(StatusEffect::NoJump, 1.0);
+    assert_eq!(effects.remaining(StatusEffect::NoJump), Some(2.0));
+
+    effects.tick(2.0);
+    assert!(effects.is_empty());
+}
+
+// A pickup gives its effect once and disappears. A hazard keeps giving it while the player's in it,
+// and it runs out a duration after they leave.
+#[test]
+fn sensors_apply_effects_that_expire() {
+    let mut app = status_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: JUMP_IMPULSE,
+        ..Default::default()
+    });
+    // Right where the player is
+    let position = Vec2::new(0.0, app.world.resource::<PhysicsConfig>().floor_y + RADIUS);
+    let pickup = spawn_sensor(&mut app, position, StatusEffect::NoJump, true);
+    let hazard = spawn_sensor(&mut app, position, StatusEffect::LowFriction, false);
+    app.update();
+    app.update();
+    assert!(effects(&app, player).has(StatusEffect::NoJump));
+    assert!(effects(&app, player).has(StatusEffect::LowFriction));
+    assert!(app.world.get_entity(pickup).is_none());
+
+    update_for(&mut app, 1.5);
+    let effects_now = effects(&app, player);
+    assert!(!effects_now.has(StatusEffect::NoJump));
+    assert_eq!(effects_now.remaining(StatusEffect::LowFriction), Some(1.0));
+
+    app.world.despawn(hazard);
+    update_for(&mut app, 0.5);
+    assert!(effects(&app, player).has(StatusEffect::LowFriction));
+    update_for(&mut app, 0.5 + TEST_DT);
+    assert!(effects(&app, player).is_empty());
+}
+
+// Holds `keys` for a quarter of a second with `effect` (if any) on the player, starting `height`
+// above the floor and set up by `setup`, and returns the player's PhysObj. Jumps go off when jump
+// is let go, so that's let go of for one more update at the end.
+fn play(
+    effect: Option<StatusEffect>,
+    height: f32,
+    keys: &[KeyCode],
+    setup: fn(&mut PhysObj),
+) -> PhysObj {
+    let mut app = status_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + RADIUS + height), RADIUS);
+    app.world.entity_mut(player).insert(Player {
+        jump_impulse: JUMP_IMPULSE,
+        ..Default::default()
+    });
+    setup(&mut app.world.get_mut::<PhysObj>(player).unwrap());
+    if let Some(effect) = effect {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect, 10.0);
+        app.world.entity_mut(player).insert(effects);
+    }
+    let mut input = app.world.resource_mut::<Input<KeyCode>>();
+    for &key in keys {
+        input.press(key);
+    }
+    update_for(&mut app, 0.25);
+    if keys.contains(&KeyCode::Space) {
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .release(KeyCode::Space);
+        app.update();
+    }
+    app.world.get::<PhysObj>(player).unwrap().clone()
+}
+
+#[test]
+fn inverted_controls_swap_the_spin_keys() {
+    let normal = play(None, 10_000.0, &[KeyCode::A], |_| {});
+    assert!(normal.angular_vel > 1.0);
+    let inverted = play(
+        Some(StatusEffect::InvertedControls),
+        10_000.0,
+        &[KeyCode::A],
+        |_| {},
+    );
+    assert_eq!(inverted.angular_vel, -normal.angular_vel);
+}
+
+#[test]
+fn speed_boost_multiplies_the_torque() {
+    let normal = play(None, 10_000.0, &[KeyCode::D], |_| {});
+    let boosted = play(
+        Some(StatusEffect::SpeedBoost),
+        10_000.0,
+        &[KeyCode::D],
+        |_| {},
+    );
+    let ratio = boosted.angular_vel / normal.angular_vel;
+    assert!((ratio - SPEED_BOOST_TORQUE).abs() < 1e-3, "{ratio}");
+}
+
+#[test]
+fn no_jump_stops_jumping() {
+    let normal = play(None, 0.0, &[KeyCode::Space], |_| {});
+    assert!(normal.vel.y > 100.0);
+    // Unrelated effects leave jumping alone
+    let boosted = play(
+        Some(StatusEffect::SpeedBoost),
+        0.0,
+        &[KeyCode::Space],
+        |_| {},
+    );
+    assert_eq!(boosted.vel, normal.vel);
+    let grounded = play(Some(StatusEffect::NoJump), 0.0, &[KeyCode::Space], |_| {});
+    assert_eq!(grounded.vel, Vec2::ZERO);
+}
+
+// A ball skidding along the floor without turning slows down less with low friction
+#[test]
+fn low_friction_makes_the_floor_slippery() {
+    let skid = |phys_obj: &mut PhysObj| phys_obj.vel.x = 1000.0;
+    let normal = play(None, 0.0, &[], skid);
+    let slippery = play(Some(StatusEffect::LowFriction), 0.0, &[], skid);
+    assert!(slippery.vel.x > normal.vel.x);
+    // Skidding the whole time, so the speed lost is in proportion to the friction
+    let ratio = (1000.0 - slippery.vel.x) / (1000.0 - normal.vel.x);
+    assert!((ratio - LOW_FRICTION_SCALE).abs() < 0.05, "{ratio}");
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use bevy_game::{
+    level::{Floor, SurfaceMaterial},
+    physics::{forces::ForceFn, Collider, PhysObj, PhysicsConfig},
+    player::Player,
+    sticky::{holding_force, StickyPlugin, Stuck, STICKY_POP_SPEED},
+    testing::{set_test_ball_restitution, spawn_test_ball, test_app, TEST_BALL_MASS, TEST_DT},
+};
+
+const RADIUS: f32 = 25.0;
+const MASS: f32 = TEST_BALL_MASS;
+const STRENGTH: f32 = 50_000.0;
+
+fn sticky_app(material: SurfaceMaterial) -> App {
+    let mut app = test_app();
+    app.add_plugin(StickyPlugin);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(0.0, floor_y, 0.0)),
+        Floor { width: 1000.0 },
+        material,
+    ));
+    app
+}
+
+// How far the bottom of the ball is above the floor
+fn height(app: &App, ball: Entity) -> f32 {
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.get::<Transform>(ball).unwrap().translation.y - RADIUS - floor_y
+}
+
+// The highest the ball gets in the second after it first lands
+fn rebound(app: &mut App, ball: Entity) -> f32 {
+    while !matches!(
+        app.world.get::<Collider>(ball),
+        Some(Collider::Ball {
+            touching_ground: true,
+            ..
+        })
+    ) {
+        app.update();
+    }
+    (0..60)
+        .map(|_| {
+            app.update();
+            height(app, ball)
+        })
+        .fold(f32::MIN, f32::max)
+}
+
+#[test]
+fn balls_stick_where_they_land_and_jump_off() {
+    let mut app = sticky_app(SurfaceMaterial::Normal);
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 200.0), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.9);
+    assert!(rebound(&mut app, ball) > 50.0);
+
+    let mut app = sticky_app(SurfaceMaterial::Sticky { strength: STRENGTH });
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS + 200.0), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.9);
+    assert!(rebound(&mut app, ball) < 1.0);
+    assert!(app.world.get::<Stuck>(ball).is_some());
+
+    // The player can still jump off
+    app.world.entity_mut(ball).insert(Player {
+        torque: 0.0,
+        ..Default::default()
+    });
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Space);
+    app.update();
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::Space);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(height(&app, ball) > 50.0);
+    assert!(app.world.get::<Stuck>(ball).is_none());
+}
+
+#[test]
+fn holding_on_to_a_ceiling_takes_the_weight() {
+    let gravity = PhysicsConfig::default().gravity;
+    let ceiling = Vec2::NEG_Y;
+    let acc = Vec2::new(0.0, -gravity);
+    assert_eq!(
+        holding_force(ceiling, MASS, Vec2::ZERO, acc, TEST_DT),
+        MASS * gravity
+    );
+    // Stopping it moving away takes more, moving into it or along it doesn't
+    let falling = Vec2::new(30.0, -20.0);
+    let expected = MASS * (gravity + 20.0 / TEST_DT);
+    assert!((holding_force(ceiling, MASS, falling, acc, TEST_DT) - expected).abs() < 1e-2);
+    let pushing = Vec2::new(30.0, 20.0);
+    assert_eq!(
+        holding_force(ceiling, MASS, pushing, acc, TEST_DT),
+        MASS * gravity
+    );
+    // A floor holds the weight up by itself
+    assert_eq!(holding_force(Vec2::Y, MASS, Vec2::ZERO, acc, TEST_DT), 0.0);
+}
+
+// Pulled up by a steady force, the ball stays on until the pull takes more than the strength to
+// hold, and then lets go after a frame's grace
+#[test]
+fn balls_come_off_once_pulling_away_exceeds_the_strength() {
+    let pulled = |lift: f32| {
+        let mut app = sticky_app(SurfaceMaterial::Sticky { strength: STRENGTH });
+        let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+        let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+        set_test_ball_restitution(&mut app, ball, 0.9);
+        app.world
+            .entity_mut(ball)
+            .insert(ForceFn::new(move |_, body, _| body.acc.y += lift));
+        app.update();
+        app.update();
+        let after_a_frame = height(&app, ball);
+        for _ in 0..30 {
+            app.update();
+        }
+        (after_a_frame, height(&app, ball))
+    };
+
+    let gravity = PhysicsConfig::default().gravity;
+    // Just under and just over the strength, on top of the ball's weight
+    let (_, held) = pulled(gravity + 0.9 * STRENGTH / MASS);
+    assert!(held.abs() < 1.0, "{held}");
+    let (after_a_frame, pulled_off) = pulled(gravity + 1.1 * STRENGTH / MASS);
+    assert!(after_a_frame.abs() < 1.0, "{after_a_frame}");
+    assert!(pulled_off > 50.0, "{pulled_off}");
+}
+
+// Letting go pops the ball off, without it being held on to again on the way
+#[test]
+fn balls_pop_off_when_let_go() {
+    let mut app = sticky_app(SurfaceMaterial::Sticky { strength: STRENGTH });
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.9);
+    let lift = PhysicsConfig::default().gravity + 1.1 * STRENGTH / MASS;
+    app.world
+        .entity_mut(ball)
+        .insert(ForceFn::new(move |_, body, _| body.acc.y += lift));
+    // The physics starts on the second update
+    app.update();
+    app.update();
+    while !app.world.get::<Stuck>(ball).unwrap().released {
+        app.update();
+    }
+    let vel = app.world.get::<PhysObj>(ball).unwrap().vel;
+    assert!(vel.y >= STICKY_POP_SPEED, "{vel}");
+    assert!(matches!(
+        app.world.get::<Collider>(ball),
+        Some(Collider::Ball {
+            touching_ground: false,
+            ..
+        })
+    ));
+
+    for _ in 0..5 {
+        app.update();
+        assert!(app
+            .world
+            .get::<Stuck>(ball)
+            .is_none_or(|stuck| stuck.released));
+    }
+    assert!(app.world.get::<Stuck>(ball).is_none());
+    assert!(height(&app, ball) > 0.0);
+}
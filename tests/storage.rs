@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use bevy_game::storage::{load_ron, save_ron, FileBackend, MemoryBackend, StorageBackend};
+
+// An empty directory of its own for each test, removed again when it's done
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("bevy_game_storage_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// What any backend has to do: give back what was set, replace it, and forget it when removed
+fn check_backend(storage: &mut dyn StorageBackend) {
+    assert_eq!(storage.get("settings"), None);
+    storage.set("settings", "(volume: 0.5)").unwrap();
+    storage.set("progress", "(deaths: 3)").unwrap();
+    assert_eq!(storage.get("settings").as_deref(), Some("(volume: 0.5)"));
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 3)"));
+
+    storage.set("settings", "(volume: 1.0)").unwrap();
+    assert_eq!(storage.get("settings").as_deref(), Some("(volume: 1.0)"));
+
+    storage.remove("settings").unwrap();
+    assert_eq!(storage.get("settings"), None);
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 3)"));
+    // Removing what isn't there isn't an error
+    storage.remove("settings").unwrap();
+
+    save_ron(storage, "numbers", &vec![1.5f32, -2.0, 1e-7]).unwrap();
+    let numbers: Vec<f32> = load_ron(storage, "numbers").unwrap().unwrap();
+    assert_eq!(numbers, [1.5, -2.0, 1e-7]);
+    assert!(load_ron::<Vec<f32>>(storage, "missing").is_none());
+    assert!(load_ron::<Vec<f32>>(storage, "progress").unwrap().is_err());
+}
+
+#[test]
+fn memory_backend_stores_values() {
+    let mut storage = MemoryBackend::default();
+    check_backend(&mut storage);
+    assert_eq!(storage.entries.len(), 2);
+}
+
+#[test]
+fn file_backend_stores_values() {
+    let dir = TempDir::new("values");
+    let mut storage = FileBackend::new(&dir.0);
+    check_backend(&mut storage);
+    assert!(dir.0.join("progress.ron").is_file());
+
+    // Another backend on the same directory, like the next session, sees the same values
+    let storage = FileBackend::new(&dir.0);
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 3)"));
+}
+
+// A write that was cut off (the game crashed or the power went) only got as far as the temporary
+// file, so the previous value is still what's read, and the next write replaces both
+#[test]
+fn interrupted_write_keeps_the_previous_value() {
+    let dir = TempDir::new("interrupted");
+    let mut storage = FileBackend::new(&dir.0);
+    storage.set("progress", "(deaths: 3)").unwrap();
+
+    let temp_path = storage.path("progress").with_extension("ron.tmp");
+    std::fs::write(&temp_path, "(deaths: 4, lev").unwrap();
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 3)"));
+
+    storage.set("progress", "(deaths: 5)").unwrap();
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 5)"));
+    assert!(!temp_path.exists());
+}
+
+// A write that fails part way leaves the previous value in place, and says so
+#[test]
+fn failed_write_keeps_the_previous_value() {
+    let dir = TempDir::new("failed");
+    let mut storage = FileBackend::new(&dir.0);
+    storage.set("progress", "(deaths: 3)").unwrap();
+
+    // Nothing can be written where the temporary file goes
+    let temp_path = storage.path("progress").with_extension("ron.tmp");
+    std::fs::create_dir(&temp_path).unwrap();
+    assert!(storage.set("progress", "(deaths: 4)").is_err());
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 3)"));
+
+    std::fs::remove_dir(&temp_path).unwrap();
+    storage.set("progress", "(deaths: 4)").unwrap();
+    assert_eq!(storage.get("progress").as_deref(), Some("(deaths: 4)"));
+}
+
+#[test]
+fn file_backend_in_a_missing_directory_fails_to_write() {
+    let dir = TempDir::new("missing");
+    let mut storage = FileBackend::new(dir.0.join("not_there"));
+    assert_eq!(storage.get("progress"), None);
+    assert!(storage.set("progress", "(deaths: 3)").is_err());
+    assert_eq!(storage.get("progress"), None);
+}
@@ -0,0 +1,100 @@
+// Run with `cargo test --release --test stress -- --ignored --nocapture`
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicIsize, AtomicUsize, Ordering},
+};
+
+use bevy::{prelude::default, utils::Duration};
+use bevy_game::{
+    physics::{timings::PhysicsTimings, PhysicsConfig, PhysicsSchedule},
+    stress::{StressPlugin, DEFAULT_STRESS_BALLS},
+    testing::test_app,
+};
+
+// Counts the bytes currently allocated, and the allocations made
+struct CountingAllocator;
+
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size() as isize, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size() as isize, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(
+            new_size as isize - layout.size() as isize,
+            Ordering::Relaxed,
+        );
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Once the balls have come to rest, stepping the simulation shouldn't keep allocating
+#[test]
+#[ignore = "benchmark, slow in debug builds"]
+fn stress_scene_does_not_grow_allocations() {
+    let mut app = test_app();
+    app.add_plugin(StressPlugin {
+        count: DEFAULT_STRESS_BALLS,
+    });
+    for _ in 0..600 {
+        app.update();
+    }
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let mut physics_time = Duration::ZERO;
+    for _ in 0..100 {
+        app.update();
+        physics_time += app.world.resource::<PhysicsTimings>().total();
+    }
+    let after = ALLOCATED.load(Ordering::Relaxed);
+
+    println!(
+        "{DEFAULT_STRESS_BALLS} balls: {:.3} ms of physics per frame",
+        physics_time.as_secs_f64() * 1000.0 / 100.0
+    );
+    assert!(
+        after <= before,
+        "allocated {} more bytes over 100 frames",
+        after - before
+    );
+}
+
+// The physics steps themselves shouldn't allocate at all once the scratch buffers have grown.
+// Resting balls send no events, so nothing else grows either. Serial, as the thread pool allocates
+// its tasks.
+#[test]
+#[ignore = "benchmark, slow in debug builds"]
+fn resting_physics_steps_do_not_allocate() {
+    let mut app = test_app();
+    app.insert_resource(PhysicsConfig {
+        serial: true,
+        ..default()
+    })
+    .add_plugin(StressPlugin {
+        count: DEFAULT_STRESS_BALLS,
+    });
+    for _ in 0..600 {
+        app.update();
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..100 {
+        app.world.run_schedule(PhysicsSchedule);
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert_eq!(allocations, 0, "{allocations} allocations in 100 steps");
+}
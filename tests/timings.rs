@@ -0,0 +1,104 @@
+use bevy::{
+    diagnostic::Diagnostics,
+    prelude::*,
+    utils::{Duration, Instant},
+};
+use bevy_game::{
+    physics::{
+        timings::{
+            PhysicsTimings, PHYSICS_BROAD_PHASE_TIME, PHYSICS_FRICTION_TIME,
+            PHYSICS_INTEGRATOR_TIME, PHYSICS_NARROW_PHASE_TIME, PHYSICS_TOTAL_TIME,
+        },
+        PhysicsConfig,
+    },
+    testing::{press_key, spawn_test_ball, test_app},
+};
+
+// Balls piled on the floor, so that every phase has work to do
+fn timed_app() -> App {
+    let mut app = test_app();
+    app.init_resource::<Diagnostics>();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    for i in 0..200 {
+        let position = Vec2::new(
+            (i % 20) as f32 * 30.0,
+            floor_y + 10.0 + (i / 20) as f32 * 25.0,
+        );
+        spawn_test_ball(&mut app, position, 10.0);
+    }
+    app.update();
+    app.update();
+    app
+}
+
+fn phases(timings: &PhysicsTimings) -> [Duration; 5] {
+    [
+        timings.integrator,
+        timings.forces,
+        timings.broad_phase,
+        timings.narrow_phase,
+        timings.friction,
+    ]
+}
+
+// Each phase took some of the frame's physics, and all of them together no more than the frame
+#[test]
+fn timings_are_recorded_while_enabled() {
+    let mut app = timed_app();
+    press_key(&mut app, KeyCode::F4, true);
+    for _ in 0..10 {
+        let start = Instant::now();
+        app.update();
+        let frame = start.elapsed();
+
+        let timings = app.world.resource::<PhysicsTimings>();
+        assert!(timings.enabled);
+        let phases = phases(timings);
+        assert!(
+            phases.iter().all(|phase| *phase > Duration::ZERO),
+            "{phases:?}"
+        );
+        assert!(phases.iter().all(|phase| *phase <= timings.total()));
+        assert_eq!(phases.iter().sum::<Duration>(), timings.total());
+        assert!(timings.total() <= frame, "{:?} {frame:?}", timings.total());
+    }
+
+    let diagnostics = app.world.resource::<Diagnostics>();
+    for id in [
+        PHYSICS_INTEGRATOR_TIME,
+        PHYSICS_BROAD_PHASE_TIME,
+        PHYSICS_NARROW_PHASE_TIME,
+        PHYSICS_FRICTION_TIME,
+        PHYSICS_TOTAL_TIME,
+    ] {
+        let value = diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.value());
+        assert!(value.is_some_and(|ms| ms > 0.0), "{value:?}");
+    }
+    let total = diagnostics
+        .get(PHYSICS_TOTAL_TIME)
+        .unwrap()
+        .value()
+        .unwrap();
+    let integrator = diagnostics
+        .get(PHYSICS_INTEGRATOR_TIME)
+        .unwrap()
+        .value()
+        .unwrap();
+    assert!(integrator <= total);
+}
+
+// Off by default, when nothing is measured
+#[test]
+fn timings_stay_zero_while_disabled() {
+    let mut app = timed_app();
+    for _ in 0..3 {
+        app.update();
+        let timings = app.world.resource::<PhysicsTimings>();
+        assert!(!timings.enabled);
+        assert_eq!(phases(timings), [Duration::ZERO; 5]);
+    }
+    let total = app.world.resource::<Diagnostics>().get(PHYSICS_TOTAL_TIME);
+    assert!(total.is_some_and(|total| total.value().is_none()));
+}
@@ -0,0 +1,158 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use bevy_game::{
+    testing::test_app,
+    toast::{ToastEvent, ToastPlugin, ToastQueue},
+};
+
+fn texts(queue: &ToastQueue) -> Vec<&str> {
+    queue
+        .toasts
+        .iter()
+        .map(|toast| toast.text.as_str())
+        .collect()
+}
+
+#[test]
+fn toasts_queue_oldest_first() {
+    let mut queue = ToastQueue::default();
+    queue.push("a".to_string());
+    queue.push("b".to_string());
+    queue.push("c".to_string());
+    assert_eq!(texts(&queue), ["a", "b", "c"]);
+}
+
+// Past the limit the oldest make way, and the newest are kept in order
+#[test]
+fn too_many_toasts_push_out_the_oldest() {
+    let mut queue = ToastQueue::default();
+    for i in 0..10 {
+        queue.push(i.to_string());
+    }
+    let len = queue.toasts.len();
+    assert!((1..10).contains(&len), "{len}");
+    let newest: Vec<_> = (10 - len..10).map(|i| i.to_string()).collect();
+    assert_eq!(texts(&queue), newest);
+
+    queue.push("new".to_string());
+    assert_eq!(queue.toasts.len(), len);
+    assert_eq!(texts(&queue)[len - 1], "new");
+}
+
+// Each toast runs out after its own time on screen, fading out at the end
+#[test]
+fn toasts_expire_in_order() {
+    let mut queue = ToastQueue::default();
+    queue.push("a".to_string());
+    assert!(!queue.tick(0.25));
+    queue.push("b".to_string());
+    let (a, b) = (&queue.toasts[0], &queue.toasts[1]);
+    assert!((b.remaining - a.remaining - 0.25).abs() < 1e-6);
+    assert_eq!(a.alpha(), 1.0);
+
+    // Fades out
+    let mut last_alpha = 1.0;
+    while queue.toasts[0].remaining > 0.02 {
+        assert!(!queue.tick(0.01));
+        let alpha = queue.toasts[0].alpha();
+        assert!(alpha <= last_alpha);
+        last_alpha = alpha;
+    }
+    assert!(last_alpha < 0.1);
+
+    // Gone, and only it
+    assert!(queue.tick(0.05));
+    assert_eq!(texts(&queue), ["b"]);
+    assert!(queue.toasts[0].alpha() > 0.0);
+    assert!(!queue.tick(0.01));
+    assert!(queue.tick(1.0));
+    assert!(queue.toasts.is_empty());
+    assert!(!queue.tick(1.0));
+}
+
+fn toast_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(AssetPlugin::default())
+        .add_plugin(ToastPlugin);
+    app
+}
+
+// The toasts in the queue and the text entities showing them, oldest first
+fn toast_texts(app: &mut App) -> (Vec<String>, Vec<Entity>) {
+    let (queue, children) = app
+        .world
+        .query::<(&ToastQueue, Option<&Children>)>()
+        .single(&app.world);
+    let children: Vec<_> = children.map_or(Vec::new(), |children| children.to_vec());
+    let texts = texts(queue).into_iter().map(str::to_string).collect();
+    (texts, children)
+}
+
+// The texts are rebuilt when toasts come and go, and left alone while they only tick down
+#[test]
+fn toast_texts_follow_the_queue() {
+    let mut app = toast_app();
+    app.update();
+    app.world.send_event(ToastEvent("first".to_string()));
+    app.world.send_event(ToastEvent("second".to_string()));
+    app.update();
+    let (texts, children) = toast_texts(&mut app);
+    assert_eq!(texts, ["first", "second"]);
+    assert_eq!(children.len(), 2);
+    let shown: Vec<_> = children
+        .iter()
+        .map(|&child| {
+            app.world.get::<Text>(child).unwrap().sections[0]
+                .value
+                .clone()
+        })
+        .collect();
+    assert_eq!(shown, texts);
+
+    app.update();
+    assert_eq!(toast_texts(&mut app).1, children);
+
+    app.world.send_event(ToastEvent("third".to_string()));
+    app.update();
+    let (texts, new_children) = toast_texts(&mut app);
+    assert_eq!(texts, ["first", "second", "third"]);
+    assert_eq!(new_children.len(), 3);
+    assert!(children
+        .iter()
+        .all(|child| app.world.get_entity(*child).is_none()));
+
+    // All of them run out eventually
+    for _ in 0..1000 {
+        app.update();
+    }
+    let (texts, children) = toast_texts(&mut app);
+    assert!(texts.is_empty());
+    assert!(children.is_empty());
+}
+
+// In debug builds physics anomalies show up as a toast too, one a frame however many there were
+#[cfg(debug_assertions)]
+#[test]
+fn anomalies_are_toasted_in_debug_builds() {
+    use bevy::ecs::event::ManualEventReader;
+    use bevy_game::physics::anomaly::PhysicsAnomaly;
+
+    let mut app = toast_app();
+    app.add_event::<PhysicsAnomaly>();
+    app.update();
+    let mut reader = ManualEventReader::<ToastEvent>::default();
+    let entity = app.world.spawn_empty().id();
+    app.world.send_event(PhysicsAnomaly::NaNVelocity { entity });
+    app.world.send_event(PhysicsAnomaly::NaNVelocity { entity });
+    app.update();
+
+    let toasts: Vec<_> = reader
+        .iter(app.world.resource::<Events<ToastEvent>>())
+        .map(|toast| toast.0.clone())
+        .collect();
+    assert_eq!(toasts.len(), 1, "{toasts:?}");
+    assert!(
+        toasts[0].starts_with("Physics anomaly: NaNVelocity"),
+        "{toasts:?}"
+    );
+    assert_eq!(toast_texts(&mut app).0, toasts);
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "debug-tools")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    debug::trace::{TracePlugin, TraceRecorder, TraceRow, Traced},
+    physics::PhysicsConfig,
+    testing::{press_key, spawn_test_ball, spawn_test_player, test_app, TEST_DT},
+};
+
+fn row(t: f32) -> TraceRow {
+    TraceRow {
+        t,
+        entity: 3,
+        x: 1.5,
+        y: -2.0,
+        vel_x: 0.25,
+        vel_y: -100.0,
+        angular_vel: 3.0,
+        touching_ground: true,
+        friction_impulse: 0.0,
+        normal_impulse: 12.5,
+    }
+}
+
+fn tracing_app() -> App {
+    let mut app = test_app();
+    app.add_plugin(TracePlugin);
+    app
+}
+
+// One header, then a row a line with the columns in its order and booleans as 0 or 1
+#[test]
+fn csv_has_a_header_and_a_line_a_row() {
+    let mut recorder = TraceRecorder::default();
+    recorder.push(row(0.5));
+    let mut other = row(1.0);
+    other.touching_ground = false;
+    recorder.push(other);
+    assert_eq!(
+        recorder.to_csv(),
+        "t,entity,x,y,vel_x,vel_y,angular_vel,touching_ground,friction_impulse,normal_impulse\n\
+         0.5,3,1.5,-2,0.25,-100,3,1,0,12.5\n\
+         1,3,1.5,-2,0.25,-100,3,0,0,12.5\n"
+    );
+}
+
+// JSON has no NaN, so values that aren't finite are written as null
+#[test]
+fn json_writes_non_finite_values_as_null() {
+    let mut recorder = TraceRecorder::default();
+    let mut poisoned = row(0.5);
+    poisoned.vel_y = f32::NAN;
+    poisoned.x = f32::INFINITY;
+    recorder.push(poisoned);
+    let json = recorder.to_json();
+    assert!(json.contains("\"x\":null"), "{json}");
+    assert!(json.contains("\"vel_y\":null"), "{json}");
+    assert!(json.contains("\"touching_ground\":true"), "{json}");
+    assert!(!json.contains("NaN") && !json.contains("inf"), "{json}");
+}
+
+// Starting reserves room for every row, so a full trace never grows the buffer, and rows past the
+// cap are dropped
+#[test]
+fn traces_are_capped_without_reallocating() {
+    let mut recorder = TraceRecorder::default();
+    recorder.start();
+    let capacity = recorder.rows.capacity();
+    let buffer = recorder.rows.as_ptr();
+    while !recorder.is_full() {
+        recorder.push(row(0.0));
+    }
+    let cap = recorder.rows.len();
+    assert!(cap <= capacity);
+    for _ in 0..10 {
+        recorder.push(row(1.0));
+    }
+    assert_eq!(recorder.rows.len(), cap);
+    assert_eq!(recorder.rows.as_ptr(), buffer);
+    assert!(recorder.rows.iter().all(|row| row.t == 0.0));
+
+    // Starting again keeps the buffer
+    recorder.start();
+    assert!(recorder.rows.is_empty());
+    assert_eq!(recorder.rows.as_ptr(), buffer);
+}
+
+// F8 traces the player when nothing is marked Traced, with a row each step of the bodies that are
+#[test]
+fn tracing_records_the_traced_bodies_each_step() {
+    let mut app = tracing_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_player(&mut app, Vec2::new(0.0, floor_y + 200.0), 10.0);
+    spawn_test_ball(&mut app, Vec2::new(200.0, floor_y + 200.0), 10.0);
+    app.update();
+
+    press_key(&mut app, KeyCode::F8, true);
+    app.update();
+    press_key(&mut app, KeyCode::F8, false);
+    assert!(app.world.get::<Traced>(player).is_some());
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let recorder = app.world.resource::<TraceRecorder>();
+    assert!(recorder.enabled);
+    let steps = recorder.rows.len();
+    assert!((10..=11).contains(&steps), "{steps}");
+    assert!(recorder.rows.iter().all(|row| row.entity == player.index()));
+    for (i, pair) in recorder.rows.windows(2).enumerate() {
+        assert!((pair[1].t - pair[0].t - TEST_DT).abs() < 1e-5, "{i}");
+        // Falling
+        assert!(pair[1].y < pair[0].y, "{i}");
+    }
+    let y = app.world.get::<Transform>(player).unwrap().translation.y;
+    assert_eq!(recorder.rows.last().unwrap().y, y);
+}
+
+// Until tracing starts, the recorder doesn't run at all: its clock stays put and nothing is kept
+#[test]
+fn nothing_is_recorded_while_tracing_is_off() {
+    let mut app = tracing_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 200.0), 10.0);
+    app.world.entity_mut(ball).insert(Traced);
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let recorder = app.world.resource::<TraceRecorder>();
+    assert!(!recorder.enabled);
+    assert_eq!(recorder.t, 0.0);
+    assert!(recorder.rows.is_empty());
+    assert_eq!(recorder.rows.capacity(), 0);
+}
@@ -0,0 +1,83 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    physics::{LandedEvent, PhysObj, PhysicsConfig},
+    player::{predict_trajectory, time_to_land},
+    testing::{spawn_test_ball, test_app, TEST_DT},
+};
+
+// The predicted path ends where a ball thrown the same way comes down on the floor
+#[test]
+fn predicted_landing_matches_a_simulated_jump() {
+    let mut app = test_app();
+    let config = app.world.resource::<PhysicsConfig>().clone();
+    let radius = 20.0;
+    for vel in [
+        Vec2::new(0.0, 900.0),
+        Vec2::new(300.0, 800.0),
+        Vec2::new(-450.0, 600.0),
+    ] {
+        let start = Vec2::new(0.0, config.floor_y + radius + 1.0);
+        let ball = spawn_test_ball(&mut app, start, radius);
+        app.world.get_mut::<PhysObj>(ball).unwrap().vel = vel;
+        let points = predict_trajectory(
+            start,
+            vel,
+            config.gravity,
+            Some(config.floor_y + radius),
+            24,
+        );
+        assert_eq!(points.len(), 25);
+        assert_eq!(points[0], start);
+        let predicted = *points.last().unwrap();
+        assert!((predicted.y - (config.floor_y + radius)).abs() < 1e-2);
+
+        let mut reader = ManualEventReader::<LandedEvent>::default();
+        reader
+            .iter(app.world.resource::<Events<LandedEvent>>())
+            .count();
+        let mut landed = None;
+        for _ in 0..(3.0 / TEST_DT) as usize {
+            app.update();
+            if reader
+                .iter(app.world.resource::<Events<LandedEvent>>())
+                .any(|event| event.entity == ball)
+            {
+                landed = Some(app.world.get::<Transform>(ball).unwrap().translation);
+                break;
+            }
+        }
+        let landed = landed.expect("the ball lands");
+        // Within a couple of steps: the one it lands in, and the one the integrator's first step
+        // loses by starting with only half the gravity
+        assert!(
+            (landed.x - predicted.x).abs() <= 2.0 * vel.x.abs() * TEST_DT + 1.0,
+            "{vel}: {landed} {predicted}"
+        );
+        app.world.despawn(ball);
+    }
+}
+
+#[test]
+fn landing_time_is_the_later_root() {
+    // Up at 1000 against 2000 of gravity: back down in a second
+    let t = time_to_land(0.0, 1000.0, 2000.0, 0.0).unwrap();
+    assert!((t - 1.0).abs() < 1e-5, "{t}");
+    // Dropped from 100 up
+    let t = time_to_land(100.0, 0.0, 2000.0, 0.0).unwrap();
+    assert!((t - 0.1_f32.sqrt()).abs() < 1e-5, "{t}");
+    // Never reaches a height above its peak
+    assert_eq!(time_to_land(0.0, 100.0, 2000.0, 1000.0), None);
+    // Without gravity, only if it's heading there
+    assert_eq!(time_to_land(100.0, -50.0, 0.0, 0.0), Some(2.0));
+    assert_eq!(time_to_land(100.0, 50.0, 0.0, 0.0), None);
+}
+
+// With nowhere to land, the path is cut off after a while instead of going on forever
+#[test]
+fn paths_without_a_landing_stop() {
+    let points = predict_trajectory(Vec2::ZERO, Vec2::new(100.0, 0.0), 0.0, None, 10);
+    assert_eq!(points.len(), 11);
+    let end = *points.last().unwrap();
+    assert!(end.x > 0.0 && end.x.is_finite());
+    assert_eq!(end.y, 0.0);
+}
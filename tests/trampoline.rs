@@ -0,0 +1,108 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_game::{
+    level::{Floor, SurfaceMaterial},
+    physics::{sleep::StaticBody, Collider, LandedEvent, PhysObj, PhysicsConfig},
+    testing::{set_test_ball_restitution, spawn_test_ball, test_app},
+};
+
+const RADIUS: f32 = 25.0;
+const MIN_LAUNCH_SPEED: f32 = 800.0;
+const MAX_BOUNCE_SPEED: f32 = 1500.0;
+
+// Normal floor left of x = 0, and a trampoline from there to x = 400
+fn trampoline_app() -> App {
+    let mut app = test_app();
+    app.world.resource_mut::<PhysicsConfig>().max_bounce_speed = MAX_BOUNCE_SPEED;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(-500.0, floor_y, 0.0)),
+        Floor { width: 1000.0 },
+        SurfaceMaterial::Normal,
+        StaticBody,
+    ));
+    app.world.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(200.0, floor_y, 0.0)),
+        Floor { width: 400.0 },
+        SurfaceMaterial::Trampoline {
+            restitution_override: 1.2,
+            min_launch_speed: MIN_LAUNCH_SPEED,
+        },
+        StaticBody,
+    ));
+    app
+}
+
+fn height(app: &App, ball: Entity) -> f32 {
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    app.world.get::<Transform>(ball).unwrap().translation.y - RADIUS - floor_y
+}
+
+fn touching_ground(app: &App, ball: Entity) -> bool {
+    let Some(&Collider::Ball {
+        touching_ground, ..
+    }) = app.world.get::<Collider>(ball)
+    else {
+        unreachable!()
+    };
+    touching_ground
+}
+
+#[test]
+fn bounces_gain_height_up_to_the_cap_without_landing() {
+    let mut app = trampoline_app();
+    let dropped_from = 200.0;
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(
+        &mut app,
+        Vec2::new(200.0, floor_y + RADIUS + dropped_from),
+        RADIUS,
+    );
+    set_test_ball_restitution(&mut app, ball, 0.3);
+    let mut landings = ManualEventReader::<LandedEvent>::default();
+
+    let mut peaks = Vec::new();
+    let mut last = (height(&app, ball), 0.0);
+    for _ in 0..600 {
+        app.update();
+        assert!(!touching_ground(&app, ball));
+        let events = app.world.resource::<Events<LandedEvent>>();
+        assert_eq!(landings.iter(events).count(), 0);
+
+        let vel = app.world.get::<PhysObj>(ball).unwrap().vel;
+        assert!(vel.y <= MAX_BOUNCE_SPEED, "{vel}");
+        let now = (height(&app, ball), vel.y);
+        if last.1 > 0.0 && now.1 <= 0.0 {
+            peaks.push(last.0.max(now.0));
+        }
+        last = now;
+    }
+
+    // Higher than it was dropped from, and higher each time until the cap
+    assert!(peaks[0] > dropped_from, "{peaks:?}");
+    assert!(peaks[1] > peaks[0], "{peaks:?}");
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let capped = MAX_BOUNCE_SPEED.powi(2) / (2.0 * gravity);
+    let highest = peaks.iter().copied().fold(f32::MIN, f32::max);
+    assert!(highest <= capped + 1.0, "{peaks:?}");
+    assert!(highest > 0.95 * capped, "{peaks:?}");
+}
+
+#[test]
+fn rolling_onto_it_throws_the_ball_up() {
+    let mut app = trampoline_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let ball = spawn_test_ball(&mut app, Vec2::new(-100.0, floor_y + RADIUS), RADIUS);
+    set_test_ball_restitution(&mut app, ball, 0.3);
+    let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+    phys_obj.vel = Vec2::new(300.0, 0.0);
+    phys_obj.angular_vel = -300.0 / RADIUS;
+    let highest = (0..60)
+        .map(|_| {
+            app.update();
+            height(&app, ball)
+        })
+        .fold(f32::MIN, f32::max);
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    let launched = MIN_LAUNCH_SPEED.powi(2) / (2.0 * gravity);
+    assert!(highest > 0.9 * launched, "{highest} {launched}");
+}
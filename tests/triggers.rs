@@ -0,0 +1,250 @@
+use bevy::{ecs::event::Event, prelude::*};
+use bevy_game::{
+    elevator::{Elevator, ElevatorPlugin},
+    level::{Level, LevelName, LevelPlugin, RestartLevelEvent},
+    physics::{Gravity, PhysObj, PhysicsConfig},
+    player::Player,
+    progress::{LevelTimer, Score},
+    saw::SawBlade,
+    state::AppState,
+    testing::{test_app, TEST_DT},
+    toast::ToastEvent,
+    trigger::{
+        Coin, Door, PlaySoundEvent, SensorEvent, TriggerCondition, TriggerPlugin, TriggerState,
+        TriggerZone,
+    },
+};
+
+const FIXTURE: &str = include_str!("fixtures/triggers.ron");
+
+fn fixture() -> Level {
+    ron::from_str(FIXTURE).unwrap()
+}
+
+// Playing the fixture, with everything spawned
+fn trigger_app() -> App {
+    let mut app = test_app();
+    app.insert_resource(fixture())
+        .add_plugin(LevelPlugin)
+        .add_plugin(ElevatorPlugin)
+        .add_plugin(TriggerPlugin);
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn named<T: Component>(app: &mut App, name: &str) -> Entity {
+    app.world
+        .query_filtered::<(Entity, &LevelName), With<T>>()
+        .iter(&app.world)
+        .find(|(_, other)| other.0 == name)
+        .map(|(entity, _)| entity)
+        .unwrap()
+}
+
+fn sensor(app: &mut App, event: SensorEvent) {
+    app.world.send_event(event);
+    app.update();
+}
+
+// What's in every event of type E still around
+fn sent<E: Event, T>(app: &App, content: impl Fn(&E) -> T) -> Vec<T> {
+    let events = app.world.resource::<Events<E>>();
+    events.get_reader().iter(events).map(content).collect()
+}
+
+fn count<T: Component>(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<T>>()
+        .iter(&app.world)
+        .count()
+}
+
+#[test]
+fn fixture_parses() {
+    let level = fixture();
+    assert_eq!(level.doors[0].name, "door1");
+    assert_eq!(level.zones[0].name, "door_switch");
+    assert_eq!(level.elevators[0].name.as_deref(), Some("lift"));
+    assert_eq!(level.coins.len(), 3);
+    assert_eq!(
+        level
+            .triggers
+            .iter()
+            .map(|trigger| trigger.when.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            TriggerCondition::SensorEntered("door_switch".to_string()),
+            TriggerCondition::SensorExited("door_switch".to_string()),
+            TriggerCondition::CoinsCollectedAtLeast(3),
+            TriggerCondition::TimerExceeds(60.0),
+        ]
+    );
+    assert!(level
+        .triggers
+        .iter()
+        .all(|trigger| trigger.validate().is_ok()));
+
+    // Levels from before triggers still load
+    let level: Level = ron::from_str("(floors: [], balls: [])").unwrap();
+    assert!(level.triggers.is_empty() && level.doors.is_empty());
+}
+
+#[test]
+fn sensor_opens_the_door_once() {
+    let mut app = trigger_app();
+    let door = named::<Door>(&mut app, "door1");
+    assert!(!app.world.get::<Door>(door).unwrap().open);
+
+    // Another sensor's events don't count
+    sensor(&mut app, SensorEvent::Entered("elsewhere".to_string()));
+    assert!(!app.world.get::<Door>(door).unwrap().open);
+
+    sensor(&mut app, SensorEvent::Entered("door_switch".to_string()));
+    assert!(app.world.get::<Door>(door).unwrap().open);
+    let sounds = sent(&app, |PlaySoundEvent(name)| name.clone());
+    assert_eq!(sounds, ["boing"]);
+    let toasts = sent(&app, |ToastEvent(text)| text.clone());
+    assert_eq!(toasts, ["Door opened"]);
+
+    // Fired already
+    for _ in 0..2 {
+        app.update();
+    }
+    sensor(&mut app, SensorEvent::Entered("door_switch".to_string()));
+    assert!(sent(&app, |ToastEvent(text)| text.clone()).is_empty());
+}
+
+// The lift's travel moves up, and it rises into it at its speed rather than jumping
+#[test]
+fn sensor_exit_moves_the_platform() {
+    let mut app = trigger_app();
+    let lift = named::<Elevator>(&mut app, "lift");
+    let height = |app: &App| app.world.get::<Transform>(lift).unwrap().translation.y;
+    assert_eq!(height(&app), -200.0);
+
+    sensor(&mut app, SensorEvent::Exited("door_switch".to_string()));
+    let elevator = *app.world.get::<Elevator>(lift).unwrap();
+    assert_eq!((elevator.top, elevator.bottom), (-100.0, -240.0));
+
+    let mut last = height(&app);
+    for _ in 0..120 {
+        app.update();
+        let now = height(&app);
+        assert!(now >= last && now - last <= 2.0 * elevator.speed * TEST_DT);
+        last = now;
+    }
+    assert_eq!(last, -100.0);
+}
+
+#[test]
+fn coins_spawn_a_saw_and_flip_gravity_until_restarted() {
+    let mut app = trigger_app();
+    let gravity = app.world.resource::<PhysicsConfig>().gravity;
+    app.world.resource_mut::<Score>().0 = 2;
+    app.update();
+    assert_eq!(count::<SawBlade>(&mut app), 0);
+
+    app.world.resource_mut::<Score>().0 = 3;
+    app.update();
+    app.update();
+    assert_eq!(count::<SawBlade>(&mut app), 1);
+    assert_eq!(app.world.resource::<PhysicsConfig>().gravity, -gravity);
+    let player = app
+        .world
+        .query_filtered::<&Gravity, With<Player>>()
+        .single(&app.world)
+        .0;
+    assert_eq!(player, -gravity);
+
+    // Back the way it was, and ready to go off again
+    app.world.send_event(RestartLevelEvent);
+    app.world.resource_mut::<Score>().0 = 0;
+    app.update();
+    app.update();
+    assert_eq!(app.world.resource::<PhysicsConfig>().gravity, gravity);
+    assert_eq!(count::<SawBlade>(&mut app), 0);
+    assert_eq!(count::<Coin>(&mut app), 3);
+    assert!(!app.world.resource::<TriggerState>().gravity_flipped);
+}
+
+#[test]
+fn timer_completes_the_level() {
+    let mut app = trigger_app();
+    app.world.resource_mut::<LevelTimer>().elapsed = 59.0;
+    app.update();
+    assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Playing);
+
+    app.world.resource_mut::<LevelTimer>().elapsed = 61.0;
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world.resource::<State<AppState>>().0,
+        AppState::LevelComplete
+    );
+}
+
+// The player rolling through the switch sends its events, and picking up coins counts them
+#[test]
+fn players_set_off_zones_and_take_coins() {
+    let mut app = trigger_app();
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+    let zone = app
+        .world
+        .query_filtered::<&Transform, With<TriggerZone>>()
+        .single(&app.world)
+        .translation;
+    app.world.get_mut::<Transform>(player).unwrap().translation = zone;
+    app.update();
+    assert_eq!(
+        sent(&app, SensorEvent::clone),
+        [SensorEvent::Entered("door_switch".to_string())]
+    );
+    let door = named::<Door>(&mut app, "door1");
+    assert!(app.world.get::<Door>(door).unwrap().open);
+
+    app.world
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 800.0;
+    app.update();
+    let exited = SensorEvent::Exited("door_switch".to_string());
+    assert!(sent(&app, SensorEvent::clone).contains(&exited));
+    app.update();
+    assert_eq!(app.world.resource::<Score>().0, 1);
+    assert_eq!(count::<Coin>(&mut app), 2);
+}
+
+// Until it's opened, a door stops a ball rolling at it
+#[test]
+fn closed_doors_block_balls() {
+    let mut app = trigger_app();
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+    app.world
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 300.0;
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(600.0, 0.0);
+    for _ in 0..60 {
+        app.update();
+    }
+    let x = app.world.get::<Transform>(player).unwrap().translation.x;
+    assert!(x < 400.0, "{x}");
+
+    sensor(&mut app, SensorEvent::Entered("door_switch".to_string()));
+    app.world.get_mut::<PhysObj>(player).unwrap().vel = Vec2::new(600.0, 0.0);
+    for _ in 0..60 {
+        app.update();
+    }
+    let x = app.world.get::<Transform>(player).unwrap().translation.x;
+    assert!(x > 400.0, "{x}");
+}
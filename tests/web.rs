@@ -0,0 +1,71 @@
+use bevy_game::web::{captures_key, panic_overlay_message};
+
+// In the browser, check by hand that Space and the arrows don't scroll the page, that clicking the
+// canvas gives it the keys, that right-clicking doesn't open the context menu, and that Ctrl+R
+// still reloads.
+
+// The keys the page would scroll or navigate on are kept from it
+#[test]
+fn page_keys_are_captured() {
+    for code in [
+        "Space",
+        "ArrowUp",
+        "ArrowDown",
+        "ArrowLeft",
+        "ArrowRight",
+        "Tab",
+        "Backspace",
+    ] {
+        assert!(captures_key(code, false, false, false), "{code}");
+    }
+}
+
+// Other keys, e.g. the letters the game uses, work the same either way, so the browser gets them
+#[test]
+fn other_keys_are_left_to_the_browser() {
+    for code in ["KeyA", "KeyR", "F5", "F11", "Escape", "Enter", "Digit1", ""] {
+        assert!(!captures_key(code, false, false, false), "{code}");
+    }
+    // Codes, not key names
+    assert!(!captures_key(" ", false, false, false));
+    assert!(!captures_key("space", false, false, false));
+}
+
+// With any modifier it's a browser shortcut, even on a captured key
+#[test]
+fn shortcuts_are_left_to_the_browser() {
+    for (ctrl, alt, meta) in [
+        (true, false, false),
+        (false, true, false),
+        (false, false, true),
+        (true, true, true),
+    ] {
+        assert!(!captures_key("KeyR", ctrl, alt, meta));
+        assert!(!captures_key("Tab", ctrl, alt, meta));
+        assert!(!captures_key("ArrowLeft", ctrl, alt, meta));
+    }
+}
+
+#[test]
+fn panic_messages_show_the_payload_and_where_it_happened() {
+    assert_eq!(
+        panic_overlay_message(
+            Some("index out of bounds"),
+            Some(("src/physics/mod.rs", 42))
+        ),
+        "The game crashed: index out of bounds (src/physics/mod.rs:42)"
+    );
+    assert_eq!(
+        panic_overlay_message(Some("oops"), None),
+        "The game crashed: oops"
+    );
+    // Panics with a payload that isn't a string
+    assert_eq!(
+        panic_overlay_message(None, Some(("src/main.rs", 7))),
+        "The game crashed: unknown error (src/main.rs:7)"
+    );
+    assert_eq!(
+        panic_overlay_message(None, None),
+        "The game crashed: unknown error"
+    );
+}
@@ -0,0 +1,108 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
+use bevy_game::{
+    level::{Floor, LevelPlugin, WindowBoundsPlugin},
+    physics::PhysicsConfig,
+    player::Player,
+    testing::{spawn_test_ball, test_app},
+};
+
+// The level in a headless app, with a primary window to resize that nothing draws to
+fn resizable_app() -> (App, Entity) {
+    let mut app = test_app();
+    app.add_event::<WindowResized>()
+        .add_plugin(LevelPlugin)
+        .add_plugin(WindowBoundsPlugin);
+    let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+    app.update();
+    app.update();
+    (app, window)
+}
+
+fn resize(app: &mut App, window: Entity, width: f32, height: f32) {
+    app.world.send_event(WindowResized {
+        window,
+        width,
+        height,
+    });
+}
+
+fn floors(app: &mut App) -> Vec<f32> {
+    app.world
+        .query_filtered::<&Transform, With<Floor>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation.y)
+        .collect()
+}
+
+// The floor, and every Floor of the level with it, is at the window's bottom edge
+#[test]
+fn the_floor_follows_the_window_height() {
+    let (mut app, window) = resizable_app();
+    assert!(!floors(&mut app).is_empty());
+
+    for height in [600.0, 1080.0, 300.0] {
+        resize(&mut app, window, 1280.0, height);
+        app.update();
+        let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+        assert_eq!(floor_y, -0.5 * height);
+        assert!(floors(&mut app).iter().all(|&y| y == floor_y), "{height}");
+    }
+}
+
+// Only the last size of the primary window counts
+#[test]
+fn other_windows_and_earlier_sizes_are_ignored() {
+    let (mut app, window) = resizable_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let other = app.world.spawn(Window::default()).id();
+    resize(&mut app, other, 800.0, 200.0);
+    app.update();
+    assert_eq!(app.world.resource::<PhysicsConfig>().floor_y, floor_y);
+
+    resize(&mut app, window, 800.0, 200.0);
+    resize(&mut app, window, 800.0, 500.0);
+    app.update();
+    assert_eq!(app.world.resource::<PhysicsConfig>().floor_y, -250.0);
+}
+
+// A smaller window brings the floor up into the balls resting on it, which are lifted back on top
+// and stay there, while balls above it are left where they are
+#[test]
+fn balls_the_floor_moves_into_are_put_back_on_it() {
+    let (mut app, window) = resizable_app();
+    let players: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(&app.world)
+        .collect();
+    for player in players {
+        app.world.despawn(player);
+    }
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let radius = 15.0;
+    let resting = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + radius), radius);
+    let high = spawn_test_ball(&mut app, Vec2::new(200.0, 200.0), radius);
+    app.update();
+
+    resize(&mut app, window, 1280.0, 400.0);
+    let high_before = app.world.get::<Transform>(high).unwrap().translation;
+    app.update();
+    let high_after = app.world.get::<Transform>(high).unwrap().translation;
+    // Only falling, a step's worth
+    assert_eq!(high_after.x, high_before.x);
+    assert!(
+        high_before.y - high_after.y < 5.0,
+        "{high_before} {high_after}"
+    );
+
+    for _ in 0..60 {
+        app.update();
+        let y = app.world.get::<Transform>(resting).unwrap().translation.y;
+        assert!(y >= -200.0 + radius - 1.0, "{y}");
+    }
+    let y = app.world.get::<Transform>(resting).unwrap().translation.y;
+    assert!((y - (-200.0 + radius)).abs() < 1.0, "{y}");
+}
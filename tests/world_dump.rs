@@ -0,0 +1,90 @@
+#![cfg(feature = "debug-tools")]
+
+use bevy::prelude::*;
+use bevy_game::{
+    debug::dump::{dump_world, load_dump, WorldDump},
+    physics::{Collider, PhysObj, PhysicsConfig},
+    player::Player,
+    shapes::FidgetSpinner,
+    state::AppState,
+    testing::{spawn_test_ball, test_app},
+};
+
+// A player and a few balls, some way into falling, bouncing and spinning
+fn busy_app() -> App {
+    let mut app = test_app();
+    let floor_y = app.world.resource::<PhysicsConfig>().floor_y;
+    let player = spawn_test_ball(&mut app, Vec2::new(0.0, floor_y + 200.0), 20.0);
+    app.world
+        .entity_mut(player)
+        .insert((Player::default(), FidgetSpinner::new(20.0)));
+    for i in 1..5 {
+        let ball = spawn_test_ball(
+            &mut app,
+            Vec2::new(i as f32 * 60.0, floor_y + 50.0 * i as f32),
+            10.0 + i as f32,
+        );
+        let mut phys_obj = app.world.get_mut::<PhysObj>(ball).unwrap();
+        phys_obj.vel = Vec2::new(-40.0 * i as f32, 100.0);
+        phys_obj.angular_vel = i as f32;
+        phys_obj.com_offset = Vec2::new(1.0, 0.0);
+        let Collider::Ball {
+            coef_of_restitution,
+            ..
+        } = app.world.get_mut::<Collider>(ball).unwrap().into_inner();
+        *coef_of_restitution = 0.2 * i as f32;
+    }
+    for _ in 0..20 {
+        app.update();
+    }
+    app
+}
+
+fn to_ron(dump: &WorldDump) -> String {
+    ron::ser::to_string_pretty(dump, ron::ser::PrettyConfig::default()).unwrap()
+}
+
+// Every body, state and all, comes back in a fresh app as it was dumped, and carries on the same
+#[test]
+fn dumps_round_trip_into_a_fresh_app() {
+    let mut app = busy_app();
+    let dump = dump_world(&mut app.world);
+    assert_eq!(dump.bodies.len(), 5);
+    assert_eq!(dump.state, AppState::Playing);
+    let text = to_ron(&dump);
+
+    let mut fresh = test_app();
+    let loaded: WorldDump = ron::from_str(&text).unwrap();
+    load_dump(&mut fresh.world, &loaded);
+    let reloaded = dump_world(&mut fresh.world);
+    for (body, again) in dump.bodies.iter().zip(&reloaded.bodies) {
+        assert_eq!(body.translation, again.translation);
+        assert_eq!(body.rotation, again.rotation);
+        assert_eq!(body.phys_obj.vel, again.phys_obj.vel);
+        assert_eq!(body.phys_obj.angular_vel, again.phys_obj.angular_vel);
+        assert_eq!(body.phys_obj.acc_prev, again.phys_obj.acc_prev);
+        assert_eq!(body.player.is_some(), again.player.is_some());
+        assert_eq!(body.shape.is_some(), again.shape.is_some());
+    }
+    // And every other field, through the text
+    let reloaded_text = to_ron(&WorldDump {
+        state: dump.state,
+        ..reloaded
+    });
+    assert_eq!(reloaded_text, text);
+
+    // The dumped state takes over from the fresh app's
+    fresh.update();
+    assert_eq!(
+        fresh.world.resource::<State<AppState>>().0,
+        AppState::Playing
+    );
+    for _ in 0..30 {
+        app.update();
+        fresh.update();
+    }
+    assert_eq!(
+        to_ron(&dump_world(&mut fresh.world)),
+        to_ron(&dump_world(&mut app.world))
+    );
+}